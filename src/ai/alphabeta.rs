@@ -0,0 +1,417 @@
+use shakmaty::{ByColor, Chess, Move, Position};
+use std::cmp::Reverse;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use rand::Rng;
+use rand::rngs::StdRng;
+use crate::board::geometry::square_index;
+use super::components::SearchProgress;
+use super::plugin::AiGameStateContext;
+use super::evaluation::{Evaluator, PieceSquareTables};
+use super::fallback::capture_value;
+use super::search_stack::SearchStack;
+use super::search_stats::SearchStats;
+use super::transposition::{Bound, TranspositionTable};
+use super::opponent_model::rng_outcomes;
+use super::zobrist::{ZobristKeys, search_turn_hash};
+use crate::drawbacks::definition::{DrawbackRule, TurnContext};
+use crate::game_logic::repetition::RepetitionTable;
+
+// Score of giving mate right away; every ply it takes costs a point, so the
+// search goes for the quickest mate and puts off being mated
+const MATE_SCORE: i32 = 30_000;
+// Scores at least this far from zero are mates
+const MATE_THRESHOLD: i32 = MATE_SCORE - 1_000;
+const INFINITY: i32 = MATE_SCORE + 1;
+// Nodes between looks at the clock
+const TIME_CHECK_INTERVAL: u64 = 1024;
+// Move ordering: the transposition table's move, captures (most valuable
+// victim first), the killer moves, then the other quiet moves by history
+const TT_MOVE_ORDER: i32 = 1_000_000;
+const CAPTURE_ORDER: i32 = 100_000;
+const KILLER_ORDER: i32 = 90_000;
+// Quiet moves told apart by the history table (2 colors x 64 x 64 squares)
+const HISTORY_SIZE: usize = 2 * 64 * 64;
+
+// State of one iterative deepening search
+struct Searcher<'a> {
+    ctx: &'a AiGameStateContext,
+    stack: SearchStack<'a>,
+    evaluator: Evaluator,            // Static evaluation of the leaves
+    tt: &'a mut TranspositionTable,
+    keys: &'a ZobristKeys,
+    rules: ByColor<Option<Arc<dyn DrawbackRule + Send + Sync>>>, // What the search holds each side to
+    counts_captures: bool,           // A rule depends on the moves without a capture
+    rng: StdRng,
+    stats: SearchStats,
+    line: Vec<Move>,                 // Moves from the root to the current node
+    repetitions: RepetitionTable,    // Board hashes of the game's positions and those along `line`
+    killers: Vec<[Option<Move>; 2]>, // Per ply, the last two quiet moves that caused a cutoff
+    history: Vec<i32>,               // Cutoffs of quiet moves by color, from and to square
+    nodes: u64,
+    deadline: Instant,
+    completed_depth: u8,             // Deepest iteration that finished
+    best_move: Option<Move>,         // Best move of the deepest finished iteration
+    root_best: Option<Move>,         // Best move of the iteration running now
+    aborted: bool,                   // Ran out of time in the running iteration
+}
+
+impl<'a> Searcher<'a> {
+    // Roll of the side to move's drawback at the current node (at the root:
+    // the game's, if known), None for a rule without one
+    fn roll(&mut self) -> Option<u8> {
+        let rule = self.rules.get(self.stack.position().turn()).as_ref().filter(|rule| rule.needs_turn_rng())?;
+        if self.stack.ply() == 0 {
+            return self.ctx.rng_outcome;
+        }
+        Some(self.rng.gen_range(0..rule.get_rng_outcomes().max(1)))
+    }
+
+    // Moves of the side to move at the current node under `rng_outcome`, with
+    // its drawback applied (at the root: the moves the game already filtered, if any)
+    fn node_moves(&mut self, rng_outcome: Option<u8>) -> Vec<Move> {
+        if self.stack.ply() == 0 && !self.ctx.allowed_moves.is_empty() {
+            return self.ctx.allowed_moves.clone();
+        }
+        let turn = TurnContext { rng_outcome, ..turn_context(self.ctx, &self.stack, &self.line) };
+        self.ctx.search_moves(self.stack.position(), &turn, &mut self.rng)
+    }
+
+    // Transposition table key of the current node: the board, plus whatever
+    // the drawbacks' moves depend on besides it (the roll, the last move, the
+    // moves without a capture), as the same board can have other moves with them
+    fn tt_key(&self, rng_outcome: Option<u8>) -> u64 {
+        let mover = self.stack.position().turn();
+        let reacts_to_last_move = self.rules.get(mover).as_ref().is_some_and(|rule| rule.id().reacts_to_last_move());
+        let last_move = self.line.last().or(self.ctx.last_move.as_ref()).filter(|_| reacts_to_last_move);
+        let moves_since_capture = self.counts_captures.then(|| ByColor::new_with(|color| self.stack.moves_since_capture(color)));
+        self.stack.hash() ^ search_turn_hash(mover, rng_outcome, last_move, moves_since_capture, self.keys)
+    }
+
+    // Score of a node where the game is over, None if it goes on
+    fn terminal_score(&self, moves: &[Move]) -> Option<i32> {
+        let position = self.stack.position();
+        let ply = self.stack.ply() as i32;
+        // At the root the game would have ended already
+        if ply > 0 && self.ctx.loses_by_drawback(position, moves, &turn_context(self.ctx, &self.stack, &self.line)) {
+            return Some(-(MATE_SCORE - ply));
+        }
+        if moves.is_empty() {
+            // No move left once the drawback is applied: mated or stalemated
+            return Some(if position.is_check() { -(MATE_SCORE - ply) } else { 0 });
+        }
+        position.is_insufficient_material().then_some(0)
+    }
+
+    // Whether the search has to stop. The first iteration always finishes,
+    // so there is a move to play.
+    fn out_of_time(&mut self) -> bool {
+        if !self.aborted && self.completed_depth > 0 && self.nodes.is_multiple_of(TIME_CHECK_INTERVAL) {
+            self.aborted = Instant::now() >= self.deadline;
+        }
+        self.aborted
+    }
+
+    fn make(&mut self, m: &Move) {
+        self.stack.make(m);
+        self.line.push(m.clone());
+        self.repetitions.push(self.stack.hash());
+        self.nodes += 1;
+    }
+
+    fn unmake(&mut self) {
+        self.stack.unmake();
+        self.line.pop();
+        self.repetitions.pop();
+    }
+
+    // The line the table expects from the root, at most `plies` long. A node
+    // of a rule with a roll is looked up under every outcome, first found first.
+    fn principal_variation(&mut self, plies: u8) -> Vec<Move> {
+        let mut line = Vec::new();
+        while line.len() < plies as usize {
+            // As `roll` gives them
+            let outcomes = match self.rules.get(self.stack.position().turn()) {
+                Some(rule) if self.stack.ply() > 0 => rng_outcomes(rule.as_ref()),
+                Some(rule) => vec![self.ctx.rng_outcome.filter(|_| rule.needs_turn_rng())],
+                None => vec![None],
+            };
+            let best = outcomes.into_iter().find_map(|outcome| {
+                let entry = self.tt.probe(self.tt_key(outcome))?;
+                entry.best_move.clone().filter(|m| self.stack.position().is_legal(m))
+            });
+            let Some(m) = best else {
+                break;
+            };
+            self.stack.make(&m);
+            self.line.push(m.clone());
+            self.repetitions.push(self.stack.hash());
+            line.push(m);
+        }
+        for _ in &line {
+            self.unmake();
+        }
+        line
+    }
+
+    fn negamax(&mut self, depth: u8, mut alpha: i32, beta: i32) -> i32 {
+        let ply = self.stack.ply();
+        self.stats.record_node(ply);
+        if self.out_of_time() {
+            return 0;
+        }
+        // Back in a position of the game or of the line: a draw
+        if ply > 0 && self.repetitions.is_repetition() {
+            return 0;
+        }
+        let rng_outcome = self.roll();
+        let moves = self.node_moves(rng_outcome);
+        if let Some(score) = self.terminal_score(&moves) {
+            return score;
+        }
+        if depth == 0 {
+            return self.quiesce(moves, alpha, beta, 0);
+        }
+
+        let hash = self.tt_key(rng_outcome);
+        let entry = self.tt.probe(hash).cloned();
+        self.stats.record_tt_probe(entry.is_some());
+        if let Some(entry) = entry.as_ref().filter(|entry| ply > 0 && entry.depth >= depth) {
+            let score = score_from_tt(entry.score, ply);
+            let usable = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => score >= beta,
+                Bound::Upper => score <= alpha,
+            };
+            if usable {
+                return score;
+            }
+        }
+
+        let hint = entry.and_then(|entry| entry.best_move).or_else(|| if ply == 0 { self.best_move.clone() } else { None });
+        let moves = self.order_moves(moves, hint.as_ref(), ply);
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        for (index, m) in moves.into_iter().enumerate() {
+            self.make(&m);
+            let score = -self.negamax(depth - 1, -beta, -alpha);
+            self.unmake();
+            if self.aborted {
+                return 0;
+            }
+            if score > best_score {
+                best_score = score;
+                best_move = Some(m.clone());
+                if ply == 0 {
+                    self.root_best = Some(m.clone());
+                }
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                self.stats.record_cutoff(index);
+                if !m.is_capture() {
+                    self.remember_quiet_cutoff(&m, ply, depth);
+                }
+                break;
+            }
+        }
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.tt.store(hash, depth, score_to_tt(best_score, ply), bound, best_move);
+        best_score
+    }
+
+    // Quiescence search below a node whose moves are known: captures only,
+    // until the position is quiet or `quiescence_depth` plies were added.
+    // Without `check_quietness` the static evaluation is taken as it is.
+    // The expensive terms of the evaluation are skipped far outside the window.
+    fn quiesce(&mut self, moves: Vec<Move>, mut alpha: i32, beta: i32, quiescence_ply: u8) -> i32 {
+        let stand_pat = self.evaluator.complete_lazy(self.stack.position(), self.stack.evaluate(), alpha, beta);
+        if !self.ctx.check_quietness || quiescence_ply >= self.ctx.quiescence_depth || stand_pat >= beta {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+
+        let captures: Vec<Move> = moves.into_iter().filter(Move::is_capture).collect();
+        let ply = self.stack.ply();
+        for m in self.order_moves(captures, None, ply) {
+            self.make(&m);
+            self.stats.record_qsearch_node();
+            let score = if self.out_of_time() {
+                0
+            } else {
+                let rng_outcome = self.roll();
+                let replies = self.node_moves(rng_outcome);
+                match self.terminal_score(&replies) {
+                    Some(score) => -score,
+                    None => -self.quiesce(replies, -beta, -alpha, quiescence_ply + 1),
+                }
+            };
+            self.unmake();
+            if self.aborted {
+                return 0;
+            }
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+
+    // Sorts the moves best first for the cutoffs to come early
+    fn order_moves(&self, mut moves: Vec<Move>, hint: Option<&Move>, ply: usize) -> Vec<Move> {
+        let killers = self.killers.get(ply);
+        moves.sort_by_cached_key(|m| {
+            let order = if Some(m) == hint {
+                TT_MOVE_ORDER
+            } else if let Some(victim) = m.capture() {
+                CAPTURE_ORDER + capture_value(victim) * 10 - capture_value(m.role())
+            } else if killers.is_some_and(|killers| killers.contains(&Some(m.clone()))) {
+                KILLER_ORDER
+            } else {
+                self.history[history_index(self.stack.position(), m)]
+            };
+            Reverse(order)
+        });
+        moves
+    }
+
+    // A quiet move refuted the opponent's last move: try it early at this ply
+    // from now on, and anywhere else the more the deeper the cutoff was
+    fn remember_quiet_cutoff(&mut self, m: &Move, ply: usize, depth: u8) {
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None, None]);
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0].as_ref() != Some(m) {
+            killers[1] = killers[0].take();
+            killers[0] = Some(m.clone());
+        }
+        let index = history_index(self.stack.position(), m);
+        self.history[index] = (self.history[index] + i32::from(depth) * i32::from(depth)).min(KILLER_ORDER - 1);
+    }
+}
+
+// What led to the current node of the search, for the side to move's drawback
+fn turn_context<'s>(ctx: &'s AiGameStateContext, stack: &SearchStack, line: &'s [Move]) -> TurnContext<'s> {
+    TurnContext {
+        rng_outcome: None,
+        last_move: line.last().or(ctx.last_move.as_ref()),
+        moves_since_capture: stack.moves_since_capture(stack.position().turn()),
+    }
+}
+
+// Index of a move in the history table (a dropped piece counts as coming from its target)
+fn history_index(position: &Chess, m: &Move) -> usize {
+    let to = square_index(m.to());
+    let from = m.from().map_or(to, square_index);
+    position.turn() as usize * 4096 + from * 64 + to
+}
+
+// Mate scores count plies from the root; the table stores them counted from
+// the position itself, so they stay right when it comes up at another ply
+fn score_to_tt(score: i32, ply: usize) -> i32 {
+    match score {
+        score if score >= MATE_THRESHOLD => score + ply as i32,
+        score if score <= -MATE_THRESHOLD => score - ply as i32,
+        score => score,
+    }
+}
+
+fn score_from_tt(score: i32, ply: usize) -> i32 {
+    match score {
+        score if score >= MATE_THRESHOLD => score - ply as i32,
+        score if score <= -MATE_THRESHOLD => score + ply as i32,
+        score => score,
+    }
+}
+
+/// Iterative deepening alpha-beta search: one full-width search per depth up
+/// to `ctx.depth`, each ordering its moves by what the ones before found
+/// (transposition table move, captures, killer moves and the history
+/// heuristic), then a quiescence search of the captures as set up by
+/// `ctx.check_quietness` and `ctx.quiescence_depth`. Every node holds the
+/// side to move to its drawback (see `AiGameStateContext::search_moves`), and
+/// a line coming back to a position of the game or of itself is a draw.
+/// When `time_limit` runs out the unfinished iteration is dropped and the
+/// move of the last finished one is played.
+pub fn find_best_move_alphabeta(
+    ctx: AiGameStateContext,
+    tt: &mut TranspositionTable,
+    time_limit: Duration,
+    pst: &PieceSquareTables,
+    keys: &ZobristKeys,
+    progress: &SearchProgress,
+) -> Option<Move> {
+    let start_time = Instant::now();
+    tt.new_search();
+    let rules = ByColor::new_with(|color| ctx.rule_for(color));
+    let stack = SearchStack::new(&ctx.board, ctx.moves_since_capture, pst, keys);
+    let mut repetitions = RepetitionTable::default();
+    for &key in &ctx.played_positions {
+        repetitions.push(key);
+    }
+    repetitions.push(stack.hash());
+    let counts_captures = rules.iter().any(|rule| rule.as_ref().is_some_and(|rule| rule.id().counts_moves_since_capture()));
+    let mut searcher = Searcher {
+        ctx: &ctx,
+        stack,
+        evaluator: Evaluator::new(pst.clone(), keys.clone()),
+        tt,
+        keys,
+        rules,
+        counts_captures,
+        rng: ctx.rng(),
+        stats: SearchStats::default(),
+        line: Vec::new(),
+        repetitions,
+        killers: Vec::new(),
+        history: vec![0; HISTORY_SIZE],
+        nodes: 0,
+        deadline: start_time + time_limit,
+        completed_depth: 0,
+        best_move: None,
+        root_best: None,
+        aborted: false,
+    };
+
+    let mut score = 0;
+    for depth in 1..=ctx.depth.max(1) {
+        let nodes_before = searcher.nodes;
+        searcher.root_best = None;
+        let iteration_score = searcher.negamax(depth, -INFINITY, INFINITY);
+        progress.add_nodes(searcher.nodes - nodes_before);
+        if searcher.aborted || searcher.root_best.is_none() {
+            break;
+        }
+        score = iteration_score;
+        searcher.completed_depth = depth;
+        searcher.best_move = searcher.root_best.take();
+        progress.set_depth(depth);
+        if let Some(best_move) = searcher.best_move.clone() {
+            progress.set_best_move(best_move);
+        }
+        progress.set_plan(searcher.principal_variation(depth));
+        // Nothing deeper changes a forced mate
+        if score.abs() >= MATE_THRESHOLD {
+            break;
+        }
+    }
+    let tt = &*searcher.tt;
+    searcher.stats.record_tt_usage(tt.memory_bytes(), tt.capacity(), tt.used());
+    progress.set_stats(searcher.stats);
+
+    println!(
+        "Alpha-beta: depth {} in {:?}, {} nodes, score {}",
+        searcher.completed_depth, start_time.elapsed(), searcher.nodes, score
+    );
+    searcher.best_move
+}
@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
+use shakmaty::Chess;
+use crate::game_logic::history::{MoveHistory, ReplayCursor};
+use super::pleco_ai::evaluate_position_pleco;
+
+/// Search depth used when analysing replayed positions
+pub const ANALYSIS_DEPTH: u16 = 3;
+
+/// Resource holding the engine evaluation of the position shown in replay mode
+#[derive(Resource, Default)]
+pub struct ReplayAnalysis {
+    pub position: Option<Chess>, // Position the score (or running task) belongs to
+    pub score_cp: Option<i32>,   // Centipawns from White's point of view
+    pub task: Option<Task<Option<i32>>>,
+}
+
+impl ReplayAnalysis {
+    pub fn is_running(&self) -> bool {
+        self.task.is_some()
+    }
+}
+
+/// System to start analysing the replay position whenever it changes
+pub fn request_replay_analysis(
+    history: Res<MoveHistory>,
+    cursor: Res<ReplayCursor>,
+    mut analysis: ResMut<ReplayAnalysis>,
+) {
+    if !cursor.is_changed() && !history.is_changed() {
+        return;
+    }
+
+    let position = history.position_at(cursor.ply);
+    if analysis.position.as_ref() == Some(&position) {
+        return; // Already analysed (e.g. only a comment changed)
+    }
+
+    let task_position = position.clone();
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        evaluate_position_pleco(&task_position, ANALYSIS_DEPTH)
+    });
+
+    // Replacing the task drops (cancels) any analysis still running for the old position
+    analysis.position = Some(position);
+    analysis.score_cp = None;
+    analysis.task = Some(task);
+}
+
+/// System to collect the finished analysis
+pub fn poll_replay_analysis(mut analysis: ResMut<ReplayAnalysis>) {
+    let Some(task) = analysis.task.as_mut() else {
+        return;
+    };
+
+    if let Some(score) = future::block_on(future::poll_once(task)) {
+        analysis.score_cp = score;
+        analysis.task = None;
+    }
+}
@@ -0,0 +1,118 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use shakmaty::Color as ChessColor;
+use crate::drawbacks::{DrawbackId, DrawbackRegistry};
+use crate::game_logic::legal_moves::DrawbackTelemetry;
+use crate::game_logic::pgn::{read_pgn, split_pgn_games};
+use super::analysis::ANALYSIS_DEPTH;
+use super::review::{build_review, evaluate_review, GameReview};
+
+/// Command line flag that analyses a folder of PGN files instead of starting
+/// the game: `--analyze-pgns <folder> [report.csv]`
+pub const BATCH_FLAG: &str = "--analyze-pgns";
+
+// Where the report goes when no file is given
+const DEFAULT_REPORT_PATH: &str = "pgn_analysis.csv";
+
+const CSV_HEADER: &str = "file,game,white,black,result,white_drawback,black_drawback,plies,\
+white_accuracy,black_accuracy,white_mistakes,black_mistakes,worst_move,worst_move_loss";
+
+/// Runs the batch analysis with the arguments after `BATCH_FLAG` and reports
+/// what went wrong; returns whether the report was written
+pub fn run_batch_analysis(args: &[String]) -> bool {
+    let Some(folder) = args.first() else {
+        eprintln!("Usage: {} <folder with .pgn files> [report.csv]", BATCH_FLAG);
+        return false;
+    };
+    let report_path = args.get(1).map(String::as_str).unwrap_or(DEFAULT_REPORT_PATH);
+    match analyse_folder(Path::new(folder)).and_then(|report| Ok(std::fs::write(report_path, report)?)) {
+        Ok(()) => {
+            println!("Analysis report written to {}", report_path);
+            true
+        }
+        Err(e) => {
+            eprintln!("Batch analysis failed: {}", e);
+            false
+        }
+    }
+}
+
+/// Reviews every game of every .pgn file in `folder` (sorted by name) at
+/// `ANALYSIS_DEPTH` and returns the CSV report, one row per game. Games that
+/// can't be read are reported and skipped.
+pub fn analyse_folder(folder: &Path) -> Result<String, Box<dyn Error>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(folder)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("pgn")))
+        .collect();
+    paths.sort();
+
+    let registry = DrawbackRegistry::default();
+    let mut report = format!("{}\n", CSV_HEADER);
+    for path in &paths {
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let text = std::fs::read_to_string(path)?;
+        for (index, game_text) in split_pgn_games(&text).iter().enumerate() {
+            let game = match read_pgn(game_text) {
+                Ok(game) => game,
+                Err(e) => {
+                    eprintln!("Skipping game {} of {}: {}", index + 1, file_name, e);
+                    continue;
+                }
+            };
+            println!("Analysing {} game {} ({} moves, depth {})", file_name, index + 1, game.history.len(), ANALYSIS_DEPTH);
+
+            let white_drawback = game.drawback(ChessColor::White, &registry).unwrap_or(DrawbackId::None);
+            let black_drawback = game.drawback(ChessColor::Black, &registry).unwrap_or(DrawbackId::None);
+            // No telemetry was recorded, so the drawback impact is recomputed from the moves
+            let review = build_review(&game.history, &DrawbackTelemetry::default(), white_drawback, black_drawback, &registry, None);
+            let review = evaluate_review(review, &game.history);
+
+            let drawback_name = |id: DrawbackId| registry.rules.get(&id).map_or("None", |rule| rule.name());
+            let mut row = vec![
+                file_name.clone(),
+                (index + 1).to_string(),
+                game.tag("White").unwrap_or("?").to_string(),
+                game.tag("Black").unwrap_or("?").to_string(),
+                game.tag("Result").unwrap_or("*").to_string(),
+                drawback_name(white_drawback).to_string(),
+                drawback_name(black_drawback).to_string(),
+                review.moves.len().to_string(),
+            ];
+            row.extend(player_columns(&review));
+            report.push_str(&row.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(","));
+            report.push('\n');
+        }
+    }
+    Ok(report)
+}
+
+// Accuracies, mistake counts and the worst move of the game (empty if unknown)
+fn player_columns(review: &GameReview) -> Vec<String> {
+    let accuracy = |color: ChessColor| review.accuracy(color).map(|accuracy| format!("{:.1}", accuracy)).unwrap_or_default();
+    let worst = review.moves.iter().max_by(|a, b| a.win_chance_lost().total_cmp(&b.win_chance_lost()));
+    let (worst_move, worst_loss) = match worst.filter(|review| review.win_chance_lost() > 0.0) {
+        Some(worst) => {
+            let dots = if worst.color == ChessColor::White { "." } else { "..." };
+            (format!("{}{} {}", worst.move_number, dots, worst.san), format!("{:.1}", worst.win_chance_lost()))
+        }
+        None => (String::new(), String::new()),
+    };
+    vec![
+        accuracy(ChessColor::White),
+        accuracy(ChessColor::Black),
+        review.mistake_count(ChessColor::White).to_string(),
+        review.mistake_count(ChessColor::Black).to_string(),
+        worst_move,
+        worst_loss,
+    ]
+}
+
+// Quotes a CSV field if it contains a separator, a quote or a line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
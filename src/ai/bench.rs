@@ -0,0 +1,131 @@
+use std::hint::black_box;
+use std::time::Instant;
+use shakmaty::{ByColor, CastlingMode, Chess, Color, Position, fen::Fen};
+use super::evaluation::{
+    evaluate_position_with_pst, evaluate_king_safety, evaluate_material_and_pst, evaluate_pawn_structure,
+    hanging_pieces, Evaluator, PieceSquareTables,
+};
+use super::search_stack::SearchStack;
+use super::zobrist::{ZobristKeys, calculate_board_hash, initialize_zobrist_keys};
+
+/// Command line flag that runs the evaluation benchmark instead of the game
+pub const BENCH_FLAG: &str = "--bench-eval";
+
+// How often each function is called per position
+const ITERATIONS: u32 = 20_000;
+
+// Opening, middlegame and endgame positions to time the evaluation on
+const BENCH_POSITIONS: [&str; 4] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "2r3k1/p4ppp/1p2p3/3pP3/3P1P2/P1R5/1P4PP/6K1 b - - 0 27",
+    "8/5k2/3p4/1p1Pp2p/pP2Pp1P/P4P1K/8/8 b - - 99 50",
+];
+
+/// Times the evaluation terms on a few fixed positions and prints the results
+pub fn run_evaluation_benchmark() {
+    let pst = PieceSquareTables::default();
+    let positions: Vec<Chess> = BENCH_POSITIONS
+        .iter()
+        .filter_map(|fen| {
+            let position = fen.parse::<Fen>().ok()?.into_position(CastlingMode::Standard).ok();
+            if position.is_none() {
+                eprintln!("Skipping invalid benchmark position: {}", fen);
+            }
+            position
+        })
+        .collect();
+
+    println!("Evaluation benchmark: {} positions, {} iterations each", positions.len(), ITERATIONS);
+    time("evaluate_position_with_pst", &positions, |board| evaluate_position_with_pst(board, &pst));
+    time("evaluate_king_safety", &positions, |board| {
+        evaluate_king_safety(board, Color::White) + evaluate_king_safety(board, Color::Black)
+    });
+    time("hanging_pieces", &positions, |board| {
+        (hanging_pieces(board, Color::White) | hanging_pieces(board, Color::Black)).count() as i32
+    });
+    time("evaluate_pawn_structure", &positions, evaluate_pawn_structure);
+
+    // Lazy evaluation with a window around the score (full evaluation, pawn hash hits)
+    // and with a window far away from it (material-first cutoff)
+    let mut evaluator = Evaluator::new(pst.clone(), initialize_zobrist_keys());
+    time("evaluate_lazy (in window)", &positions, |board| {
+        evaluator.evaluate_lazy(board, i32::MIN / 2, i32::MAX / 2)
+    });
+    time("evaluate_lazy (cutoff)", &positions, |board| evaluator.evaluate_lazy(board, 100_000, 100_001));
+    println!("  pawn hash: {} hits, {} misses", evaluator.pawn_table.hits, evaluator.pawn_table.misses);
+
+    // Nodes per second of a fixed-depth tree walk evaluating every node,
+    // cloning the position per move versus the copy-make search stack
+    let keys = initialize_zobrist_keys();
+    for board in &positions {
+        let start = Instant::now();
+        let clone_nodes = walk_cloning(board, &pst, TREE_DEPTH);
+        let clone_nps = nodes_per_second(clone_nodes, start);
+
+        let start = Instant::now();
+        let mut stack = SearchStack::new(board, ByColor::default(), &pst, &keys);
+        let mut hash_mismatches = 0;
+        let stack_nodes = walk_search_stack(&mut stack, &keys, TREE_DEPTH, &mut hash_mismatches);
+        let stack_nps = nodes_per_second(stack_nodes, start);
+
+        println!(
+            "  depth {} walk: {} nodes, clone {} nps, search stack {} nps ({:.1}x), {} hash mismatches",
+            TREE_DEPTH, stack_nodes, clone_nps, stack_nps, stack_nps as f64 / clone_nps.max(1) as f64, hash_mismatches
+        );
+    }
+}
+
+// Search depth of the tree walks
+const TREE_DEPTH: u32 = 3;
+
+// Helper function walking all moves to `depth`, cloning the position for every move
+fn walk_cloning(board: &Chess, pst: &PieceSquareTables, depth: u32) -> u64 {
+    black_box(evaluate_material_and_pst(board, pst));
+    if depth == 0 {
+        return 1;
+    }
+    let mut nodes = 1;
+    for m in board.legal_moves() {
+        let mut child = board.clone();
+        child.play_unchecked(&m);
+        nodes += walk_cloning(&child, pst, depth - 1);
+    }
+    nodes
+}
+
+// Helper function walking all moves to `depth` with make/unmake on the search stack,
+// counting nodes where the incremental hash differs from a full recomputation
+fn walk_search_stack(stack: &mut SearchStack, keys: &ZobristKeys, depth: u32, hash_mismatches: &mut u64) -> u64 {
+    black_box(stack.evaluate());
+    if stack.hash() != calculate_board_hash(stack.position(), keys) {
+        *hash_mismatches += 1;
+    }
+    if depth == 0 {
+        return 1;
+    }
+    let mut nodes = 1;
+    for m in stack.position().legal_moves() {
+        stack.make(&m);
+        nodes += walk_search_stack(stack, keys, depth - 1, hash_mismatches);
+        stack.unmake();
+    }
+    nodes
+}
+
+// Helper function for nodes per second since `start`
+fn nodes_per_second(nodes: u64, start: Instant) -> u64 {
+    (nodes as f64 / start.elapsed().as_secs_f64().max(1e-9)) as u64
+}
+
+// Helper function to time one evaluation function over all positions
+fn time(name: &str, positions: &[Chess], mut evaluate: impl FnMut(&Chess) -> i32) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        for board in positions {
+            black_box(evaluate(black_box(board)));
+        }
+    }
+    let calls = ITERATIONS as u128 * positions.len().max(1) as u128;
+    println!("  {:<28} {:>8} ns/call", name, start.elapsed().as_nanos() / calls);
+}
@@ -1,7 +1,78 @@
 use bevy::prelude::*;
 use bevy::tasks::Task;
 use shakmaty::Move;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+use std::time::Instant;
+use super::search_stats::SearchStats;
+
+/// What an AI task ends with: the move it found (if any), or the message
+/// of the panic that cut the search short
+pub type AiSearchResult = Result<Option<Move>, String>;
 
 /// Component for AI thinking task
 #[derive(Component)]
-pub struct AiThinking(pub Task<Option<Move>>); 
\ No newline at end of file
+pub struct AiThinking {
+    pub task: Task<AiSearchResult>,
+    pub progress: Arc<SearchProgress>, // Shared with the search so the UI can show progress
+    pub started_at: Instant,
+}
+
+/// Live search information written by the AI task and read by the UI.
+/// Uses atomics/mutex so it can be shared across the task pool thread.
+#[derive(Debug, Default)]
+pub struct SearchProgress {
+    depth: AtomicU8,
+    nodes: AtomicU64,
+    best_move: Mutex<Option<Move>>,
+    plan: Mutex<Vec<Move>>, // The best move followed by the line the engine expects after it
+    stats: Mutex<Option<SearchStats>>, // Set by the search when it finishes
+}
+
+impl SearchProgress {
+    pub fn set_depth(&self, depth: u8) {
+        self.depth.store(depth, Ordering::Relaxed);
+    }
+
+    pub fn depth(&self) -> u8 {
+        self.depth.load(Ordering::Relaxed)
+    }
+
+    pub fn add_nodes(&self, nodes: u64) {
+        self.nodes.fetch_add(nodes, Ordering::Relaxed);
+    }
+
+    pub fn nodes(&self) -> u64 {
+        self.nodes.load(Ordering::Relaxed)
+    }
+
+    pub fn set_best_move(&self, best_move: Move) {
+        if let Ok(mut guard) = self.best_move.lock() {
+            *guard = Some(best_move);
+        }
+    }
+
+    pub fn best_move(&self) -> Option<Move> {
+        self.best_move.lock().ok().and_then(|guard| guard.clone())
+    }
+
+    pub fn set_plan(&self, plan: Vec<Move>) {
+        if let Ok(mut guard) = self.plan.lock() {
+            *guard = plan;
+        }
+    }
+
+    pub fn plan(&self) -> Vec<Move> {
+        self.plan.lock().map(|guard| guard.clone()).unwrap_or_default()
+    }
+
+    pub fn set_stats(&self, stats: SearchStats) {
+        if let Ok(mut guard) = self.stats.lock() {
+            *guard = Some(stats);
+        }
+    }
+
+    pub fn stats(&self) -> Option<SearchStats> {
+        self.stats.lock().ok().and_then(|guard| guard.clone())
+    }
+}
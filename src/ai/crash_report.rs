@@ -0,0 +1,51 @@
+use shakmaty::EnPassantMode;
+use shakmaty::fen::Fen;
+use std::any::Any;
+use std::io::Write;
+use crate::game_logic::state::GameState;
+
+/// File the AI's crash reports are appended to
+pub const CRASH_REPORT_PATH: &str = "ai_crash_reports.log";
+
+/// What it takes to reproduce a search that panicked: the position, both
+/// drawbacks and the seed of the AI's random choices
+#[derive(Debug, Clone)]
+pub struct AiCrashReport {
+    pub fen: String,
+    pub white_drawback: String,
+    pub black_drawback: String,
+    pub ai_seed: u64,
+    pub message: String, // What the panic said
+}
+
+impl AiCrashReport {
+    pub fn new(game_state: &GameState, ai_seed: u64, message: String) -> Self {
+        Self {
+            fen: Fen::from_position(game_state.board.clone(), EnPassantMode::Legal).to_string(),
+            white_drawback: format!("{:?}", game_state.white_drawback),
+            black_drawback: format!("{:?}", game_state.black_drawback),
+            ai_seed,
+            message,
+        }
+    }
+
+    /// Adds the report to the end of the crash report file
+    pub fn append_to(&self, path: &str) -> std::io::Result<()> {
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "AI search panicked: {}", self.message)?;
+        writeln!(file, "  FEN: {}", self.fen)?;
+        writeln!(file, "  White drawback: {}", self.white_drawback)?;
+        writeln!(file, "  Black drawback: {}", self.black_drawback)?;
+        writeln!(file, "  AI seed: {}", self.ai_seed)?;
+        writeln!(file)
+    }
+}
+
+/// The message of a caught panic (`panic!` payloads are a `&str` or a `String`)
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
@@ -1,5 +1,11 @@
-use shakmaty::{Chess, Role, Square, Color, Piece, Position};
+use shakmaty::{attacks, Bitboard, Chess, Color, File, Piece, Position, Rank, Role, Square};
 use std::collections::HashMap;
+use std::error::Error;
+use bevy::prelude::Resource;
+use serde::{Serialize, Deserialize};
+use super::zobrist::{ZobristKeys, calculate_pawn_key};
+use crate::game_logic::perspective::PlayerPerspective;
+use crate::board::geometry::square_index;
 
 // Base piece values for midgame (mg) and endgame (eg)
 pub const PIECE_VALUES: [(i32, i32); 6] = [
@@ -24,220 +30,163 @@ const PIECE_PHASE_VALUES: [i32; 6] = [
 // Maximum possible game phase score
 const MAX_PHASE: f64 = 24.0;
 
-// Piece-square tables - midgame for white perspective
-// Pawns
-const MG_PAWN_PST: [i32; 64] = [
-    0,   0,   0,   0,   0,   0,   0,   0,
-   80,  80,  80,  80,  80,  80,  80,  80,
-   25,  25,  35,  45,  45,  35,  25,  25,
-   15,  15,  30,  70,  70,  30,  15,  15,
-   10,  10,  25,  55,  55,  25,  10,  10,
-    5,   5,  15,  25,  25,  15,   5,   5,
-    0,   0,   0, -10, -10,   0,   0,   0,
-    0,   0,   0,   0,   0,   0,   0,   0
-];
-
-// Knights
-const MG_KNIGHT_PST: [i32; 64] = [
-    -80, -50,  -30,  -30,  -30,  -30, -50, -80,
-    -50, -20,    0,    5,    5,    0, -20, -50,
-    -30,   5,   20,   30,   30,   20,   5, -30,
-    -30,  10,   30,   40,   40,   30,  10, -30,
-    -30,   5,   20,   30,   30,   20,   5, -30,
-    -30,   5,   15,   20,   20,   15,   5, -30,
-    -50, -20,    0,    5,    5,    0, -20, -50,
-    -80, -50,  -30,  -30,  -30,  -30, -50, -80
-];
-
-// Bishops
-const MG_BISHOP_PST: [i32; 64] = [
-    -20, -10, -10, -10, -10, -10, -10, -20,
-    -10,   0,   0,   0,   0,   0,   0, -10,
-    -10,   0,   5,  10,  10,   5,   0, -10,
-    -10,   5,   5,  10,  10,   5,   5, -10,
-    -10,   0,  10,  10,  10,  10,   0, -10,
-    -10,  10,  10,  10,  10,  10,  10, -10,
-    -10,  15,   0,   0,   0,   0,  15, -10,
-    -20, -10, -10, -10, -10, -10, -10, -20
-];
+/// How far outside the alpha/beta window the cheap score has to be to skip the expensive terms
+pub const LAZY_EVAL_MARGIN: i32 = 300;
 
-// Rooks
-const MG_ROOK_PST: [i32; 64] = [
-     40, 40,  40,   0,   0,  40,  40, 40,
-     5,  15,  15,  50,  50,  15,  50,  5,
-     5,   0,   0,   0,   0,   0,   0,  5,
-     5,   0,   0,   0,   0,   0,   0,  5,
-     5,   0,   0,   0,   0,   0,   0,  5,
-     5,   0,   0,   0,   0,   0,   0,  5,
-     5,   0,   0,   0,   0,   0,   0,  5,
-     0,  -5,   5,   5,   5,   10,  -5,  0
-];
+// Default number of pawn hash table entries
+const PAWN_HASH_ENTRIES: usize = 16 * 1024;
 
-// Queens
-const MG_QUEEN_PST: [i32; 64] = [
-    -20, -10, -10,  -5,  -5, -10, -10, -20,
-    -10,   0,   0,   0,   0,   0,   0, -10,
-    -10,   0,   5,   5,   5,   5,   0, -10,
-     -5,   0,   5,   5,   5,   5,   0,  -5,
-      0,   0,   5,   5,   5,   5,   0,  -5,
-    -10,   5,   5,   5,   5,   5,   0, -10,
-    -10,   0,   5,  -5,  -5,   0,   0, -10,
-    -20, -10, -10,  -2,  -5, -10, -10, -20
-];
+// Pawn structure terms
+const DOUBLED_PAWN_PENALTY: i32 = -15;
+const ISOLATED_PAWN_PENALTY: i32 = -12;
+// Bonus for a passed pawn by rank from its own side (ranks 1 and 8 never hold pawns)
+const PASSED_PAWN_BONUS: [i32; 8] = [0, 5, 10, 20, 35, 60, 100, 0];
 
-// Kings
-const MG_KING_PST: [i32; 64] = [
-    -40, -40, -40, -40, -40, -40, -40, -40,
-    -30, -30, -30, -30, -30, -30, -30, -30,
-    -25, -25, -25, -25, -25, -25, -25, -25,
-    -20, -20, -20, -20, -20, -20, -20, -20,
-    -15, -15, -15, -15, -15, -15, -15, -15,
-    -10, -10, -15, -15, -15, -15, -10, -10,
-      0,   0, -10, -15, -15, -10,   0,   0,
-     20,  40,  10,   0,   0,  10,  40,  20
-];
+/// File with the piece-square tables, read at startup (next to the config file)
+pub const PST_FILE_PATH: &str = "drawback_chess_pst.json";
 
-// Piece-square tables - endgame for white perspective
-// Pawns
-const EG_PAWN_PST: [i32; 64] = [
-      0,   0,   0,   0,   0,   0,   0,   0,
-    400, 400, 400, 400, 400, 400, 400, 400,
-     50,  55,  50,  50,  50,  50,  55,  50,
-     30,  35,  30,  30,  30,  30,  35,  30,
-     25,  20,  20,  20,  20,  20,  20,  25,
-     15,  10,  10,  10,  10,  10,  10,  15,
-     10,  10,  10,  10,  10,  10,  10,  10,
-      0,   0,   0,   0,   0,   0,   0,   0
-];
+// Built-in tables, used when no table file is present
+const DEFAULT_PST: &str = include_str!("pst/default.json");
 
-// Knights
-const EG_KNIGHT_PST: [i32; 64] = [
-    -50, -40, -30, -30, -30, -30, -40, -50,
-    -40, -20,   0,   0,   0,   0, -20, -40,
-    -30,   0,  10,  15,  15,  10,   0, -30,
-    -30,  10,  15,  20,  20,  15,  10, -30,
-    -30,   0,  15,  20,  20,  15,   0, -30,
-    -30,   5,  15,  15,  15,  15,   5, -30,
-    -40, -20,   0,   5,   5,   0, -20, -40,
-    -50, -40, -30, -30, -30, -30, -40, -50
-];
+// Order in which the roles are stored in the tables
+const ROLES: [Role; 6] = [Role::Pawn, Role::Knight, Role::Bishop, Role::Rook, Role::Queen, Role::King];
 
-// Bishops
-const EG_BISHOP_PST: [i32; 64] = [
-    -20, -10, -10, -10, -10, -10, -10, -20,
-    -10,   0,   0,   0,   0,   0,   0, -10,
-    -10,   0,   5,  10,  10,   5,   0, -10,
-    -10,   5,   5,  10,  10,   5,   5, -10,
-    -10,   0,  10,  10,  10,  10,   0, -10,
-    -10,  10,  10,  10,  10,  10,  10, -10,
-    -10,  10,   0,   0,   0,   0,  10, -10,
-    -20, -10, -10, -10, -10, -10, -10, -20
-];
+/// One table per piece, as stored in the table file.
+/// 64 values from White's point of view, index = rank * 8 + file with a1 = 0.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleTables {
+    pub pawn: Vec<i32>,
+    pub knight: Vec<i32>,
+    pub bishop: Vec<i32>,
+    pub rook: Vec<i32>,
+    pub queen: Vec<i32>,
+    pub king: Vec<i32>,
+}
 
-// Rooks
-const EG_ROOK_PST: [i32; 64] = [
-     40,  40,  40,   0,   0,  40,  40,  40,
-      5,  10,  10,  10,  10,  10,  10,   5,
-     -5,   0,   0,   0,   0,   0,   0,  -5,
-     -5,   0,   0,   0,   0,   0,   0,  -5,
-     -5,   0,   0,   0,   0,   0,   0,  -5,
-     -5,   0,   0,   0,   0,   0,   0,  -5,
-     -5,   0,   0,   0,   0,   0,   0,  -5,
-      0,   0,  10,   5,   5,  10,   0,   0
-];
+impl RoleTables {
+    fn get(&self, role: Role) -> &[i32] {
+        match role {
+            Role::Pawn => &self.pawn,
+            Role::Knight => &self.knight,
+            Role::Bishop => &self.bishop,
+            Role::Rook => &self.rook,
+            Role::Queen => &self.queen,
+            Role::King => &self.king,
+        }
+    }
 
-// Queens
-const EG_QUEEN_PST: [i32; 64] = [
-    -20, -10, -10,  -5,  -5, -10, -10, -20,
-    -10,   0,   0,   0,   0,   0,   0, -10,
-    -10,   0,   5,   5,   5,   5,   0, -10,
-     -5,   0,   5,   5,   5,   5,   0,  -5,
-      0,   0,   5,   5,   5,   5,   0,  -5,
-    -10,   5,   5,   5,   5,   5,   0, -10,
-    -10,   0,   5,   0,   0,   0,   0, -10,
-    -20, -10, -10,  -5,  -5, -10, -10, -20
-];
+    // Helper function to convert the table of `role` to a fixed size array
+    fn to_array(&self, role: Role) -> Result<[i32; 64], String> {
+        self.get(role)
+            .try_into()
+            .map_err(|_| format!("{:?} table has {} values instead of 64", role, self.get(role).len()))
+    }
+}
 
-// Kings
-const EG_KING_PST: [i32; 64] = [
-    -50, -30, -30, -30, -30, -30, -30, -50,
-    -30, -20, -20, -20, -20, -20, -20, -30,
-    -30, -10,  -5,   0,   0, -5, -10, -30,
-    -30, -10,   0,  10,  10,   0, -10, -30,
-    -30, -10,   0,  10,  10,   0, -10, -30,
-    -30, -10,  -5,   0,   0,  -5, -10, -30,
-    -30, -20, -20, -20, -20, -20, -20, -30,
-    -50, -30, -30, -30, -30, -30, -30, -50
-];
+/// Contents of a piece-square table file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PstFile {
+    pub midgame: RoleTables,
+    pub endgame: RoleTables,
+}
 
-// Lazy-initialized piece-square tables
-// This will be pre-calculated once for both white and black
-struct PieceSquareTables {
+/// Piece-square tables used by the evaluation, with the black tables
+/// pre-calculated by mirroring the white ones
+#[derive(Resource, Debug, Clone)]
+pub struct PieceSquareTables {
     white_mg: HashMap<Role, [i32; 64]>,
     white_eg: HashMap<Role, [i32; 64]>,
     black_mg: HashMap<Role, [i32; 64]>,
     black_eg: HashMap<Role, [i32; 64]>,
 }
 
+impl Default for PieceSquareTables {
+    fn default() -> Self {
+        Self::from_json(DEFAULT_PST).expect("built-in piece-square tables are valid")
+    }
+}
+
 impl PieceSquareTables {
-    fn new() -> Self {
+    /// Loads tables from a file, falling back to the built-in tables if it doesn't exist.
+    /// Personalities and tuning runs can point this at their own files.
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Ok(Self::from_json(&text)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, Box<dyn Error>> {
+        let file: PstFile = serde_json::from_str(text)?;
+        Ok(Self::from_tables(&file)?)
+    }
+
+    pub fn from_tables(file: &PstFile) -> Result<Self, String> {
         let mut white_mg = HashMap::new();
         let mut white_eg = HashMap::new();
         let mut black_mg = HashMap::new();
         let mut black_eg = HashMap::new();
 
-        // Add white tables
-        white_mg.insert(Role::Pawn, MG_PAWN_PST);
-        white_mg.insert(Role::Knight, MG_KNIGHT_PST);
-        white_mg.insert(Role::Bishop, MG_BISHOP_PST);
-        white_mg.insert(Role::Rook, MG_ROOK_PST);
-        white_mg.insert(Role::Queen, MG_QUEEN_PST);
-        white_mg.insert(Role::King, MG_KING_PST);
-
-        white_eg.insert(Role::Pawn, EG_PAWN_PST);
-        white_eg.insert(Role::Knight, EG_KNIGHT_PST);
-        white_eg.insert(Role::Bishop, EG_BISHOP_PST);
-        white_eg.insert(Role::Rook, EG_ROOK_PST);
-        white_eg.insert(Role::Queen, EG_QUEEN_PST);
-        white_eg.insert(Role::King, EG_KING_PST);
-
-        // Calculate black tables by mirroring values vertically
-        // Black wants the same positional objectives as white but from the other side
-        for role in [Role::Pawn, Role::Knight, Role::Bishop, Role::Rook, Role::Queen, Role::King] {
-            let white_mg_table = white_mg.get(&role).unwrap();
-            let white_eg_table = white_eg.get(&role).unwrap();
-            
+        for role in ROLES {
+            let white_mg_table = file.midgame.to_array(role).map_err(|e| format!("midgame: {}", e))?;
+            let white_eg_table = file.endgame.to_array(role).map_err(|e| format!("endgame: {}", e))?;
+
+            // Calculate black tables by mirroring values vertically
+            // Black wants the same positional objectives as white but from the other side
             let mut black_mg_table = [0; 64];
             let mut black_eg_table = [0; 64];
-            
-            for sq in 0..64 {
-                let rank = sq / 8;
-                let file = sq % 8;
-                
-                // Mirror square vertically - black's 7th rank is like white's 2nd rank
-                let mirror_rank = 7 - rank;
-                let mirror_sq = mirror_rank * 8 + file;
-                
-                // Black pieces should get the SAME bonus as white would on the mirrored square
-                black_mg_table[sq] = white_mg_table[mirror_sq];
-                black_eg_table[sq] = white_eg_table[mirror_sq];
+
+            let black = PlayerPerspective::new(Color::Black);
+            for square in Square::ALL {
+                // Black pieces get the SAME bonus as white would on the mirrored square
+                // (black's 7th rank is like white's 2nd rank)
+                let mirror_sq = square_index(black.square_as_white(square));
+                black_mg_table[square_index(square)] = white_mg_table[mirror_sq];
+                black_eg_table[square_index(square)] = white_eg_table[mirror_sq];
             }
-            
+
+            white_mg.insert(role, white_mg_table);
+            white_eg.insert(role, white_eg_table);
             black_mg.insert(role, black_mg_table);
             black_eg.insert(role, black_eg_table);
         }
 
-        Self {
+        Ok(Self {
             white_mg,
             white_eg,
             black_mg,
             black_eg,
+        })
+    }
+
+    /// The white tables in the table file format (e.g. for writing out tuned tables)
+    pub fn to_tables(&self) -> PstFile {
+        let tables = |phase: &HashMap<Role, [i32; 64]>| {
+            let table = |role: Role| phase.get(&role).unwrap().to_vec();
+            RoleTables {
+                pawn: table(Role::Pawn),
+                knight: table(Role::Knight),
+                bishop: table(Role::Bishop),
+                rook: table(Role::Rook),
+                queen: table(Role::Queen),
+                king: table(Role::King),
+            }
+        };
+        PstFile {
+            midgame: tables(&self.white_mg),
+            endgame: tables(&self.white_eg),
         }
     }
 
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        std::fs::write(path, serde_json::to_string_pretty(&self.to_tables())?)?;
+        Ok(())
+    }
+
     // Get the appropriate piece-square value
     fn get_piece_square_value(&self, piece: &Piece, sq: Square, is_endgame: f64) -> i32 {
-        let sq_idx = Self::square_to_index(sq);
+        let sq_idx = square_index(sq);
         let role = piece.role;
         let color = piece.color;
 
@@ -261,104 +210,335 @@ impl PieceSquareTables {
         
         ((mg_value as f64 * mg_phase) + (eg_value as f64 * eg_phase)) as i32
     }
-
-    // Convert shakmaty Square to 0-63 index
-    fn square_to_index(sq: Square) -> usize {
-        let file = sq.file().char() as usize - 'a' as usize;
-        let rank = sq.rank().char() as usize - '1' as usize;
-        rank * 8 + file
-    }
 }
 
 /// Calculate the game phase based on remaining pieces
 pub fn compute_game_phase(board: &Chess) -> f64 {
-    let mut phase = 0;
-    
-    for square in Square::ALL {
-        if let Some(piece) = board.board().piece_at(square) {
-            let role_idx = match piece.role {
-                Role::Pawn => 0,
-                Role::Knight => 1,
-                Role::Bishop => 2,
-                Role::Rook => 3,
-                Role::Queen => 4,
-                Role::King => 5,
-            };
-            
-            phase += PIECE_PHASE_VALUES[role_idx];
-        }
-    }
-    
+    let board = board.board();
+    let weight: i32 = ROLES
+        .into_iter()
+        .map(|role| phase_weight(role) * board.by_role(role).count() as i32)
+        .sum();
+    phase_from_weight(weight)
+}
+
+/// How much a piece counts towards the game phase
+pub fn phase_weight(role: Role) -> i32 {
+    PIECE_PHASE_VALUES[role_index(role)]
+}
+
+/// Game phase for a total phase weight: 0.0 is midgame and 1.0 is endgame
+pub fn phase_from_weight(weight: i32) -> f64 {
     // Normalize to [0.0, 1.0] where 0.0 is midgame and 1.0 is endgame
-    let phase = phase as f64;
+    let phase = weight as f64;
     let phase = phase.min(MAX_PHASE) / MAX_PHASE;
     
     // Invert so 0 is midgame and 1 is endgame
     1.0 - phase
 }
 
+/// Midgame and endgame value (material plus piece-square bonus) of a piece on a square,
+/// as used by `evaluate_material_and_pst`. Searches sum these up incrementally.
+pub fn piece_square_terms(pst: &PieceSquareTables, piece: Piece, square: Square) -> (i32, i32) {
+    let (mg_value, eg_value) = PIECE_VALUES[role_index(piece.role)];
+    let sq_idx = square_index(square);
+    let (mg_table, eg_table) = match piece.color {
+        Color::White => (&pst.white_mg, &pst.white_eg),
+        Color::Black => (&pst.black_mg, &pst.black_eg),
+    };
+    (
+        (mg_value as f64 * 1.1) as i32 + mg_table.get(&piece.role).unwrap()[sq_idx],
+        (eg_value as f64 * 1.1) as i32 + eg_table.get(&piece.role).unwrap()[sq_idx],
+    )
+}
+
+// Helper function for the index of a role in the per-role tables
+fn role_index(role: Role) -> usize {
+    usize::from(role) - 1 // shakmaty numbers roles from Pawn = 1
+}
+
 /// Evaluate a position using piece values and piece-square tables
-pub fn evaluate_position_with_pst(board: &Chess) -> i32 {
-    // Initialize piece-square tables (could be made static for performance)
-    let pst = PieceSquareTables::new();
-    
+pub fn evaluate_position_with_pst(board: &Chess, pst: &PieceSquareTables) -> i32 {
+    evaluate_material_and_pst(board, pst) + evaluate_mobility(board)
+}
+
+/// Cheap first stage of the evaluation: material and piece-square tables only,
+/// from the side to move's point of view
+pub fn evaluate_material_and_pst(board: &Chess, pst: &PieceSquareTables) -> i32 {
     // Determine game phase for interpolation
     let endgame_phase = compute_game_phase(board);
-    println!("Game phase: {:.2} (0.0=midgame, 1.0=endgame)", endgame_phase);
     
     let mut score = 0;
     
     // Get the side to move
     let side_to_move = board.turn();
     
-    println!("Evaluating position for side to move: {:?}", side_to_move);
-    
-    // Evaluate pieces with position-dependent values
-    for square in Square::ALL {
-        if let Some(piece) = board.board().piece_at(square) {
-            // Get role index for base piece value
-            let role_idx = match piece.role {
-                Role::Pawn => 0,
-                Role::Knight => 1,
-                Role::Bishop => 2,
-                Role::Rook => 3,
-                Role::Queen => 4,
-                Role::King => 5,
-            };
-            
-            // Calculate base piece value interpolated between midgame and endgame
-            let (mg_value, eg_value) = PIECE_VALUES[role_idx];
-            
+    // Evaluate pieces with position-dependent values, one piece bitboard at a time
+    for color in Color::ALL {
+        for (role, (mg_value, eg_value)) in ROLES.into_iter().zip(PIECE_VALUES) {
+            let piece = role.of(color);
+
             // Increase the base values for material by 10% to value capturing more
             let mg_value = (mg_value as f64 * 1.1) as i32;
             let eg_value = (eg_value as f64 * 1.1) as i32;
-            
+
+            // Calculate base piece value interpolated between midgame and endgame
             let piece_value = (mg_value as f64 * (1.0 - endgame_phase) + 
                               eg_value as f64 * endgame_phase) as i32;
-            
-            // Get position-dependent bonus from piece-square tables
-            let position_value = pst.get_piece_square_value(&piece, square, endgame_phase);
-            
-            // Debug output for important pieces (uncomment for detailed debugging)
-            if piece.role == Role::Queen || piece.role == Role::King {
-                println!("{:?} {:?} at {:?} - material: {}, position: {}", 
-                        piece.color, piece.role, square, piece_value, position_value);
-            }
-            
-            // Apply the score correctly based on piece color
-            let value = piece_value + position_value;
-            if piece.color == side_to_move {
-                score += value;
-            } else {
-                score -= value;
+
+            for square in board.board().by_piece(piece) {
+                // Get position-dependent bonus from piece-square tables
+                let position_value = pst.get_piece_square_value(&piece, square, endgame_phase);
+
+                // Apply the score correctly based on piece color
+                let value = piece_value + position_value;
+                if color == side_to_move {
+                    score += value;
+                } else {
+                    score -= value;
+                }
             }
         }
     }
     
-    // Bonus for piece mobility and development
+    score
+}
+
+/// Bonus for piece mobility and development of the side to move
+pub fn evaluate_mobility(board: &Chess) -> i32 {
     let legal_moves = board.legal_moves();
-    let mobility_bonus = (legal_moves.len() as i32) * 5; // 5 points per legal move
-    score += mobility_bonus; // Add mobility bonus
-    
+    (legal_moves.len() as i32) * 5 // 5 points per legal move
+}
+
+/// Evaluator for searches: owns the tables it needs and a pawn hash cache, so
+/// pawn structure is evaluated once per pawn formation instead of at every node
+#[derive(Debug, Clone)]
+pub struct Evaluator {
+    pub pst: PieceSquareTables,
+    pub keys: ZobristKeys,
+    pub pawn_table: PawnHashTable,
+}
+
+impl Evaluator {
+    pub fn new(pst: PieceSquareTables, keys: ZobristKeys) -> Self {
+        Self {
+            pst,
+            keys,
+            pawn_table: PawnHashTable::default(),
+        }
+    }
+
+    /// Full evaluation from the side to move's point of view: material, PST,
+    /// mobility, king safety and the cached pawn structure
+    pub fn evaluate(&mut self, board: &Chess) -> i32 {
+        self.evaluate_lazy(board, i32::MIN, i32::MAX)
+    }
+
+    /// Two-stage evaluation from the side to move's point of view. The expensive
+    /// terms (mobility, king safety, pawn structure) are only computed when the
+    /// cheap material+PST score is within `LAZY_EVAL_MARGIN` of the alpha/beta
+    /// window; further outside they can't change whether the position fails high or low.
+    pub fn evaluate_lazy(&mut self, board: &Chess, alpha: i32, beta: i32) -> i32 {
+        let lazy_score = evaluate_material_and_pst(board, &self.pst);
+        self.complete_lazy(board, lazy_score, alpha, beta)
+    }
+
+    /// Second stage of `evaluate_lazy`, for a material+PST score the caller
+    /// already has (the search keeps it up to date move by move)
+    pub fn complete_lazy(&mut self, board: &Chess, lazy_score: i32, alpha: i32, beta: i32) -> i32 {
+        if lazy_score + LAZY_EVAL_MARGIN <= alpha || lazy_score - LAZY_EVAL_MARGIN >= beta {
+            return lazy_score;
+        }
+
+        let us = board.turn();
+        lazy_score
+            + evaluate_mobility(board)
+            + evaluate_king_safety(board, us)
+            - evaluate_king_safety(board, !us)
+            + self.pawn_structure(board, us)
+    }
+
+    /// Pawn structure score for `color`, cached by pawn key
+    pub fn pawn_structure(&mut self, board: &Chess, color: Color) -> i32 {
+        let pawn_key = calculate_pawn_key(board, &self.keys);
+        let score = self.pawn_table.get_or_insert_with(pawn_key, || evaluate_pawn_structure(board));
+        if color == Color::White { score } else { -score }
+    }
+}
+
+/// Fixed-size cache of pawn structure scores, indexed by pawn key.
+/// Pawn structures repeat across most of a search, so hits are common.
+#[derive(Debug, Clone)]
+pub struct PawnHashTable {
+    entries: Vec<Option<(u64, i32)>>,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl Default for PawnHashTable {
+    fn default() -> Self {
+        Self::new(PAWN_HASH_ENTRIES)
+    }
+}
+
+impl PawnHashTable {
+    pub fn new(entries: usize) -> Self {
+        Self {
+            entries: vec![None; entries.max(1)],
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// The cached score for `pawn_key`, computing and storing it on a miss
+    pub fn get_or_insert_with(&mut self, pawn_key: u64, evaluate: impl FnOnce() -> i32) -> i32 {
+        let index = (pawn_key % self.entries.len() as u64) as usize;
+        match self.entries[index] {
+            Some((key, score)) if key == pawn_key => {
+                self.hits += 1;
+                score
+            }
+            _ => {
+                self.misses += 1;
+                let score = evaluate();
+                // Always replace: the newest structure is the most likely to come up again
+                self.entries[index] = Some((pawn_key, score));
+                score
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.iter_mut().for_each(|entry| *entry = None);
+        self.hits = 0;
+        self.misses = 0;
+    }
+}
+
+/// Pawn structure score from White's point of view: doubled and isolated pawns
+/// are penalized, passed pawns get a bonus that grows as they advance
+pub fn evaluate_pawn_structure(board: &Chess) -> i32 {
+    let board = board.board();
+    let mut score = 0;
+
+    for color in Color::ALL {
+        let our_pawns = board.by_piece(Role::Pawn.of(color));
+        let their_pawns = board.by_piece(Role::Pawn.of(!color));
+        let mut color_score = 0;
+
+        for file in File::ALL {
+            let pawns_on_file = (our_pawns & Bitboard::from_file(file)).count() as i32;
+            if pawns_on_file == 0 {
+                continue;
+            }
+
+            if pawns_on_file > 1 {
+                color_score += DOUBLED_PAWN_PENALTY * (pawns_on_file - 1);
+            }
+
+            if (our_pawns & adjacent_files(file)).is_empty() {
+                color_score += ISOLATED_PAWN_PENALTY * pawns_on_file;
+            }
+        }
+
+        for square in our_pawns {
+            // Passed if no enemy pawn on this or an adjacent file can stop it
+            let ahead = squares_ahead(square, color);
+            let lanes = Bitboard::from_file(square.file()) | adjacent_files(square.file());
+            if (their_pawns & ahead & lanes).is_empty() {
+                let relative_rank = PlayerPerspective::new(color).rank(square);
+                color_score += PASSED_PAWN_BONUS[usize::from(relative_rank)];
+            }
+        }
+
+        score += if color == Color::White { color_score } else { -color_score };
+    }
+
     score
-} 
\ No newline at end of file
+}
+
+// Helper function for the files next to `file`
+fn adjacent_files(file: File) -> Bitboard {
+    let mut files = Bitboard::EMPTY;
+    if let Some(left) = file.offset(-1) {
+        files |= Bitboard::from_file(left);
+    }
+    if let Some(right) = file.offset(1) {
+        files |= Bitboard::from_file(right);
+    }
+    files
+}
+
+// Helper function for all squares on ranks in front of `square` from `color`'s side
+fn squares_ahead(square: Square, color: Color) -> Bitboard {
+    let perspective = PlayerPerspective::new(color);
+    Square::ALL.into_iter().filter(|&other| perspective.is_ahead(other, square)).collect()
+}
+
+/// Evaluate king safety based on the squares around the king
+pub fn evaluate_king_safety(board: &Chess, color: Color) -> i32 {
+    let board = board.board();
+    let Some(king_sq) = board.king_of(color) else {
+        return 0; // No king found or already captured
+    };
+
+    let mut safety_score = 0;
+
+    // Bonus for king on edge or corner (generally safer in early/mid game)
+    let on_edge_file = matches!(king_sq.file(), File::A | File::H);
+    let on_edge_rank = matches!(king_sq.rank(), Rank::First | Rank::Eighth);
+    if on_edge_file || on_edge_rank {
+        safety_score += 20;
+
+        // Extra bonus for corner
+        if on_edge_file && on_edge_rank {
+            safety_score += 15;
+        }
+    }
+
+    // Friendly pieces next to the king protect it
+    let shield = attacks::king_attacks(king_sq) & board.by_color(color);
+    for (role, protection_bonus) in [
+        (Role::Pawn, 15),   // Pawns are good shields
+        (Role::Knight, 10),
+        (Role::Bishop, 8),
+        (Role::Rook, 12),
+        (Role::Queen, 5),   // Queen should usually not be next to king
+    ] {
+        safety_score += protection_bonus * (shield & board.by_role(role)).count() as i32;
+    }
+
+    // Give white king some extra safety bonus to balance black's advantage
+    if color == Color::White {
+        safety_score = (safety_score as f32 * 1.5) as i32; // 50% bonus for white king safety
+    }
+
+    safety_score
+}
+
+/// True if a piece of `attacker` attacks `square`
+pub fn is_attacked(board: &Chess, square: Square, attacker: Color) -> bool {
+    let board = board.board();
+    board.attacks_to(square, attacker, board.occupied()).any()
+}
+
+/// True if the piece on `square` is protected by another piece of its own color
+pub fn is_defended(board: &Chess, square: Square) -> bool {
+    let board = board.board();
+    let Some(color) = board.color_at(square) else {
+        return false;
+    };
+    board.attacks_to(square, color, board.occupied()).any()
+}
+
+/// Pieces of `color` (other than the king) that are attacked and not defended
+pub fn hanging_pieces(board: &Chess, color: Color) -> Bitboard {
+    let candidates = board.board().by_color(color) & !board.board().kings();
+    let mut hanging = Bitboard::EMPTY;
+    for square in candidates {
+        if is_attacked(board, square, !color) && !is_defended(board, square) {
+            hanging.add(square);
+        }
+    }
+    hanging
+}
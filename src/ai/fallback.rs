@@ -0,0 +1,67 @@
+use shakmaty::{Chess, Color as ChessColor, Move, Position, Role};
+use rand::seq::SliceRandom;
+use rand::rngs::StdRng;
+use crate::config::FallbackPolicy;
+use super::pleco_ai::evaluate_position_pleco;
+
+// Depth of the quick search used by `FallbackPolicy::ReSearch`
+const FALLBACK_SEARCH_DEPTH: u16 = 2;
+
+/// Why the engine's answer had to be replaced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AiFallbackReason {
+    InvalidMove(Move), // The engine returned a move that isn't allowed here
+    NoMove,            // The engine gave up although there were moves left
+    Crashed(String),   // The search panicked (with this message)
+}
+
+/// Event sent whenever the AI falls back to its fallback policy,
+/// so engine bugs show up in the log and stats instead of being masked
+#[derive(Debug, Clone)]
+pub struct AiFallbackEvent {
+    pub reason: AiFallbackReason,
+    pub policy: FallbackPolicy,
+    pub played: Option<Move>,
+}
+
+impl bevy::prelude::Event for AiFallbackEvent {}
+
+/// Picks a replacement from the moves the drawback allows
+pub fn fallback_move(policy: FallbackPolicy, board: &Chess, allowed_moves: &[Move], rng: &mut StdRng) -> Option<Move> {
+    match policy {
+        FallbackPolicy::Random => allowed_moves.choose(rng).cloned(),
+        FallbackPolicy::BestCapture => allowed_moves
+            .iter()
+            .filter_map(|chess_move| chess_move.capture().map(|captured| (chess_move, captured)))
+            .max_by_key(|(chess_move, captured)| capture_value(*captured) * 10 - capture_value(chess_move.role()))
+            .map(|(chess_move, _)| chess_move.clone())
+            .or_else(|| allowed_moves.choose(rng).cloned()),
+        FallbackPolicy::ReSearch => {
+            let sign = if board.turn() == ChessColor::White { 1 } else { -1 };
+            allowed_moves
+                .iter()
+                .max_by_key(|chess_move| {
+                    // Taking the king ends the game, nothing beats that
+                    if chess_move.capture() == Some(Role::King) {
+                        return i32::MAX;
+                    }
+                    let mut after = board.clone();
+                    after.play_unchecked(chess_move);
+                    evaluate_position_pleco(&after, FALLBACK_SEARCH_DEPTH).map_or(i32::MIN, |score| score * sign)
+                })
+                .cloned()
+        }
+    }
+}
+
+/// Rough piece value for ordering captures: most valuable victim first, least
+/// valuable attacker as the tie-break
+pub fn capture_value(role: Role) -> i32 {
+    match role {
+        Role::Pawn => 1,
+        Role::Knight | Role::Bishop => 3,
+        Role::Rook => 5,
+        Role::Queen => 9,
+        Role::King => 100,
+    }
+}
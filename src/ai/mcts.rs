@@ -1,327 +1,376 @@
-use shakmaty::{Move, Position, Chess, Role, Square, Color, Outcome};
+use shakmaty::{ByColor, Chess, Move, Position};
 use std::time::{Duration, Instant};
-use crate::drawbacks::DrawbackId;
-use super::plugin::AiGameStateContext;
-use super::evaluation::evaluate_position_with_pst;
+use super::components::SearchProgress;
+use super::plugin::{AiGameStateContext, count_capture};
+use crate::drawbacks::definition::TurnContext;
+use super::evaluation::{Evaluator, PieceSquareTables};
+use super::search_stats::SearchStats;
+use super::transposition::{Bound, TranspositionTable};
+use super::zobrist::{ZobristKeys, calculate_board_hash};
 use rand::prelude::*;
 
-/// AI implementation to find a move with improved heuristics.
-/// Evaluates positions by examining material, checks, and board control.
-/// Prioritizes capturing the king or defending against king captures.
-pub fn find_best_move_mcts(ctx: AiGameStateContext, iterations: u32) -> Option<Move> {
-    // Initialize timing
-    let start_time = Instant::now();
-    let time_limit = Duration::from_millis(ctx.time_limit_ms as u64);
-    println!("AI starting search with time limit: {}ms", ctx.time_limit_ms);
+// Exploration constant of the UCT formula (sqrt(2) in the textbook version)
+const EXPLORATION: f64 = 1.4;
+// Centipawns at which the evaluation cutoff gives the side to move ~73% to win
+const EVAL_SCALE_CP: f64 = 400.0;
+// Plies below the root a position may be found at when the tree is re-rooted
+// (the AI's own move and the reply)
+const REROOT_SEARCH_PLIES: usize = 2;
+// Node limit so a long think can't take all the memory; once it is reached
+// leaves are evaluated without being expanded
+const MAX_TREE_NODES: usize = 1_000_000;
+// Iterations between updates of the best move shown while thinking
+const PROGRESS_INTERVAL: u32 = 256;
 
-    // Get legal moves from current position
-    let board_copy = ctx.board.clone();
-    let legal_moves = board_copy.legal_moves();
-    
-    if legal_moves.is_empty() {
-        return None;
+// One position in the search tree
+#[derive(Debug, Clone)]
+struct MctsNode {
+    position: Chess,
+    key: u64,             // Zobrist hash of the position, to find it again when re-rooting
+    mv: Option<Move>,     // Move that led here (None for the root)
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<Move>,   // Moves not expanded into children yet
+    drawback_loss: bool,  // The side to move loses to its drawback's loss condition
+    moves_since_capture: ByColor<u32>, // Each side's moves without a capture up to here
+    visits: u32,
+    value: f64,           // Sum of the results for the side that played `mv`
+}
+
+impl MctsNode {
+    // A node whose moves are the legal moves the side to move's drawback
+    // allows (for a rule with a per-turn roll: under one rolled outcome)
+    fn new(
+        position: Chess,
+        mv: Option<Move>,
+        parent: Option<usize>,
+        moves_since_capture: ByColor<u32>,
+        ctx: &AiGameStateContext,
+        keys: &ZobristKeys,
+        rng: &mut impl Rng,
+    ) -> Self {
+        // The root's move is the one the game last played
+        let turn = TurnContext {
+            rng_outcome: None,
+            last_move: mv.as_ref().or(ctx.last_move.as_ref()),
+            moves_since_capture: *moves_since_capture.get(position.turn()),
+        };
+        let untried = ctx.search_moves(&position, &turn, rng);
+        Self {
+            key: calculate_board_hash(&position, keys),
+            drawback_loss: ctx.loses_by_drawback(&position, &untried, &turn),
+            moves_since_capture,
+            position,
+            mv,
+            parent,
+            children: Vec::new(),
+            untried,
+            visits: 0,
+            value: 0.0,
+        }
     }
-    
-    let legal_moves_vec: Vec<Move> = legal_moves.into_iter().collect();
-    let mut rng = rand::thread_rng();
-    
-    // Double check all our candidates are legal
-    println!("AI considering {} legal moves", legal_moves_vec.len());
-    
-    // Check if we're in check - prioritize getting out of check if we are
-    let in_check = board_copy.is_check();
-    
-    // Initialize best move tracking
-    let mut overall_best_moves: Vec<(Move, i32)> = Vec::new();
-    let mut iterations_completed = 0;
-    
-    // Main search loop - continue until time limit or iteration limit
-    while start_time.elapsed() < time_limit && iterations_completed < iterations {
-        iterations_completed += 1;
-        
-        // For tracking progress
-        if iterations_completed % 1000 == 0 {
-            println!("Search progress: {} iterations, elapsed: {:?}", 
-                     iterations_completed, start_time.elapsed());
+
+    // Expected result (0 = loss, 1 = win) for the side to move: the game
+    // result when it is over, otherwise the evaluation squashed into a win
+    // probability. Evaluations are looked up in and added to `tt`.
+    fn side_to_move_result(&self, evaluator: &mut Evaluator, tt: &mut TranspositionTable, stats: &mut SearchStats) -> f64 {
+        if self.drawback_loss {
+            return 0.0;
         }
-        
-        // Evaluate each move
-        let mut move_scores: Vec<(Move, i32)> = Vec::new();
-        
-        for m in &legal_moves_vec {
-            let mut test_board = board_copy.clone();
-            
-            // Check if the move is a capture and what's being captured
-            let capture_value = get_capture_value(&test_board, m);
-            
-            // Check if we're capturing a king (immediate win in Drawback Chess)
-            let capturing_king = is_king_capture(&test_board, m);
-            
-            // Play the move and see what results
-            test_board.play_unchecked(m);
-            
-            // Calculate a score for this move
-            let mut score = capture_value * 3; // Significantly increase the value of captures
-            
-            // Heavily prioritize capturing the king (immediate win in Drawback Chess)
-            if capturing_king {
-                score += 20000; // Extremely high value for king capture
-            }
+        if self.untried.is_empty() && self.children.is_empty() {
+            // No move left once the drawback is applied: mated or stalemated
+            return if self.position.is_check() { 0.0 } else { 0.5 };
+        }
+        if self.position.is_insufficient_material() {
+            return 0.5;
+        }
+        let cached = tt.probe(self.key).map(|entry| entry.score);
+        stats.record_tt_probe(cached.is_some());
+        let eval = cached.unwrap_or_else(|| {
+            let eval = evaluator.evaluate(&self.position);
+            tt.store(self.key, 0, eval, Bound::Exact, None);
+            eval
+        });
+        win_probability(eval)
+    }
 
-            // Get whose turn it is (this is who we're playing as)
-            let our_color = match test_board.turn() {
-                Color::White => Color::Black, // If white to move next, we just moved as black
-                Color::Black => Color::White, // If black to move next, we just moved as white
-            };
+    // Average result for the side that played the move into this node
+    fn mean_value(&self) -> f64 {
+        if self.visits == 0 { 0.0 } else { self.value / self.visits as f64 }
+    }
+}
 
-            // Check if opponent can capture our king after this move
-            // To do this, we need to check all opponent responses
-            let opponent_responses = test_board.legal_moves();
-            let mut opponent_can_capture_king = false;
-            let mut our_pieces_at_risk = 0;
-            let mut max_piece_value_at_risk = 0;
-            
-            for opp_move in opponent_responses.iter() {
-                if is_king_capture(&test_board, opp_move) {
-                    // Opponent can capture our king on their next move - very bad!
-                    opponent_can_capture_king = true;
-                    break;
-                }
-                
-                // Check if opponent can capture any of our pieces
-                let piece_value = get_capture_value(&test_board, opp_move);
-                if piece_value > 0 {
-                    our_pieces_at_risk += 1;
-                    max_piece_value_at_risk = max_piece_value_at_risk.max(piece_value);
-                }
-            }
-            
-            if opponent_can_capture_king {
-                score -= 15000; // Strongly avoid moves that allow opponent to capture our king
-            }
-            
-            // Avoid hanging pieces - Penalize moves that leave our pieces undefended
-            if our_pieces_at_risk > 0 {
-                // More aggressive protection for white pieces to balance against black
-                let piece_risk_penalty = if our_color == Color::White {
-                    max_piece_value_at_risk * 2
-                } else {
-                    max_piece_value_at_risk * 2
-                };
-                score -= piece_risk_penalty;
-            }
-            
-            // Prefer moves that put opponent in check
-            if test_board.is_check() {
-                // We're checking the opponent
-                let opponent_legal_moves = test_board.legal_moves();
-                let possible_responses = opponent_legal_moves.len();
-                
-                // The fewer responses, the better for us
-                score += 70 + (30 * (20 - possible_responses as i32)).max(0); 
-            }
-            
-            // If we're in check, prioritize moves that get us out
-            if in_check {
-                score += 500; // Higher bonus for escaping check
-            }
-            
-            // Add king safety bonus, with extra protection for white's king
-            score += evaluate_king_safety(&test_board, our_color);
-            
-            // Add evaluation using piece-square tables
-            let pst_score = -evaluate_position_with_pst(&test_board);
-            score += pst_score;
-            
-            // Add the scored move to our list
-            move_scores.push((m.clone(), score));
+/// Monte Carlo search tree, kept between moves so the visits spent on the
+/// position that came up on the board aren't thrown away. Nodes live in one
+/// arena; the root is always the first one.
+#[derive(Debug, Clone, Default)]
+pub struct MctsTree {
+    nodes: Vec<MctsNode>,
+}
+
+impl MctsTree {
+    /// Number of positions in the tree
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Visits of the root so far (what a reused tree brings along)
+    pub fn root_visits(&self) -> u32 {
+        self.nodes.first().map_or(0, |root| root.visits)
+    }
+
+    /// Makes the position of `ctx` the root: the subtree below it is kept
+    /// when it is the root or a few plies below it (with the same moves
+    /// without a capture), otherwise the tree starts over. The root's moves become the context's allowed moves (unless
+    /// empty), which the game has already filtered with the real roll.
+    pub fn reroot(&mut self, ctx: &AiGameStateContext, keys: &ZobristKeys, rng: &mut impl Rng) {
+        let key = calculate_board_hash(&ctx.board, keys);
+        let new_root = self.find_within(key, ctx.moves_since_capture, REROOT_SEARCH_PLIES);
+        match new_root {
+            Some(index) => self.compact(index),
+            None => self.nodes = vec![MctsNode::new(ctx.board.clone(), None, None, ctx.moves_since_capture, ctx, keys, rng)],
         }
-        
-        // Sort moves by score (descending)
-        move_scores.sort_by(|a, b| b.1.cmp(&a.1));
-        
-        // Update overall best moves
-        if overall_best_moves.is_empty() || 
-           (move_scores.first().map(|(_, s)| *s).unwrap_or(0) > 
-            overall_best_moves.first().map(|(_, s)| *s).unwrap_or(0)) {
-            overall_best_moves = move_scores.clone();
+
+        let allowed_moves = &ctx.allowed_moves;
+        if allowed_moves.is_empty() {
+            return;
         }
-        
-        // If we find a winning move, we can stop searching
-        if move_scores.first().map(|(_, s)| *s).unwrap_or(0) > 10000 {
-            println!("Found winning move after {} iterations", iterations_completed);
-            break;
+        let kept: Vec<usize> = self.nodes[0]
+            .children
+            .iter()
+            .copied()
+            .filter(|&child| self.nodes[child].mv.as_ref().is_some_and(|m| allowed_moves.contains(m)))
+            .collect();
+        if kept.len() != self.nodes[0].children.len() {
+            self.nodes[0].children = kept;
+            // The dropped moves' subtrees go, and their visits with them
+            self.compact(0);
+            self.nodes[0].visits = self.nodes[0].children.iter().map(|&child| self.nodes[child].visits).sum();
         }
+        let expanded: Vec<Move> = self.nodes[0].children.iter().filter_map(|&child| self.nodes[child].mv.clone()).collect();
+        let root = &mut self.nodes[0];
+        root.untried = allowed_moves.iter().filter(|m| !expanded.contains(m)).cloned().collect();
+        root.drawback_loss = false; // The game would have ended already
     }
-    
-    println!("AI completed {} iterations in {:?}", iterations_completed, start_time.elapsed());
-    
-    // Print the top 3 best moves with their scores for debugging
-    println!("Top move evaluations:");
-    for (i, (mv, score)) in overall_best_moves.iter().take(3).enumerate() {
-        println!("  {}. {:?} - Score: {}", i+1, mv, score);
-        // Debug PST evaluation
-        let mut test_board = board_copy.clone();
-        test_board.play_unchecked(mv);
-        let pst_score = -evaluate_position_with_pst(&test_board);
-        println!("Move {:?} - PST evaluation: {}", mv, pst_score);
-    }
-    
-    // Take the best move, or if scores are tied, choose randomly among the best
-    let best_score = if let Some((_, score)) = overall_best_moves.first() {
-        *score
-    } else {
-        return legal_moves_vec.choose(&mut rng).cloned();
-    };
-    
-    // Get all moves with the best score
-    let best_moves: Vec<Move> = overall_best_moves
-        .iter()
-        .filter(|(_, score)| *score == best_score)
-        .map(|(mv, _)| mv.clone())
-        .collect();
-    
-    println!("Found {} moves with best score {}", best_moves.len(), best_score);
-    
-    // Choose randomly among best moves
-    let selected_move = best_moves.choose(&mut rng).cloned();
-    
-    // Final validation - make sure the move is legal
-    if let Some(mv) = &selected_move {
-        // Make sure this move is in our legal moves list
-        if !legal_moves_vec.contains(mv) {
-            println!("WARNING: AI tried to play illegal move: {:?}", mv);
-            // Fall back to a random legal move
-            return legal_moves_vec.choose(&mut rng).cloned();
+
+    // Index of the node with position `key` at most `plies` below the root
+    fn find_within(&self, key: u64, moves_since_capture: ByColor<u32>, plies: usize) -> Option<usize> {
+        let mut layer = if self.nodes.is_empty() { Vec::new() } else { vec![0] };
+        for _ in 0..=plies {
+            let found = layer.iter().find(|&&index| {
+                let node = &self.nodes[index];
+                node.key == key && node.moves_since_capture == moves_since_capture
+            });
+            if let Some(&found) = found {
+                return Some(found);
+            }
+            layer = layer.iter().flat_map(|&index| self.nodes[index].children.iter().copied()).collect();
         }
-        println!("AI selected valid move: {:?}", mv);
+        None
     }
-    
-    selected_move
-}
 
-/// Helper function to check if a move captures the king
-fn is_king_capture(board: &Chess, m: &Move) -> bool {
-    if let Some(piece) = board.board().piece_at(m.to()) {
-        return piece.role == Role::King;
+    // Keeps only the subtree below `new_root`, which becomes the first node
+    fn compact(&mut self, new_root: usize) {
+        let mut order = vec![new_root];
+        let mut i = 0;
+        while i < order.len() {
+            order.extend(self.nodes[order[i]].children.iter().copied());
+            i += 1;
+        }
+        let mut new_index = vec![usize::MAX; self.nodes.len()];
+        for (new, &old) in order.iter().enumerate() {
+            new_index[old] = new;
+        }
+        let mut old_nodes: Vec<Option<MctsNode>> = std::mem::take(&mut self.nodes).into_iter().map(Some).collect();
+        self.nodes = order
+            .iter()
+            .map(|&old| {
+                let mut node = old_nodes[old].take().expect("Each node is reached once");
+                node.parent = node.parent.map(|parent| new_index[parent]).filter(|&parent| parent != usize::MAX);
+                node.children = node.children.iter().map(|&child| new_index[child]).collect();
+                node
+            })
+            .collect();
+        self.nodes[0].parent = None;
+        self.nodes[0].mv = None;
     }
-    false
-}
 
-/// Helper function to get the value of a captured piece
-fn get_capture_value(board: &Chess, m: &Move) -> i32 {
-    const PAWN_VALUE: i32 = 120;
-    const KNIGHT_VALUE: i32 = 370;
-    const BISHOP_VALUE: i32 = 380;
-    const ROOK_VALUE: i32 = 550;
-    const QUEEN_VALUE: i32 = 1000;
-    const KING_VALUE: i32 = 20000; // Very high value for king capture in Drawback Chess
-    
-    // Direct capture - destination has a piece
-    if let Some(piece) = board.board().piece_at(m.to()) {
-        return match piece.role {
-            Role::Pawn => PAWN_VALUE,
-            Role::Knight => KNIGHT_VALUE,
-            Role::Bishop => BISHOP_VALUE,
-            Role::Rook => ROOK_VALUE,
-            Role::Queen => QUEEN_VALUE,
-            Role::King => KING_VALUE, // Can capture king in Drawback Chess
+    // Child of `index` with the best UCT score
+    fn select_child(&self, index: usize) -> usize {
+        let node = &self.nodes[index];
+        let log_visits = (node.visits.max(1) as f64).ln();
+        let uct = |child: usize| {
+            let child = &self.nodes[child];
+            child.mean_value() + EXPLORATION * (log_visits / child.visits.max(1) as f64).sqrt()
         };
+        node.children
+            .iter()
+            .copied()
+            .max_by(|&a, &b| uct(a).total_cmp(&uct(b)))
+            .expect("Only called on nodes with children")
     }
-    
-    // Check for en passant capture - pawn moving diagonally without a piece at destination
-    if let Some(from_square) = m.from() {
-        if let Some(piece) = board.board().piece_at(from_square) {
-            if piece.role == Role::Pawn && from_square.file() != m.to().file() {
-                return PAWN_VALUE; // En passant always captures a pawn
-            }
-        }
+
+    // Most visited root child (the move to play)
+    fn best_child(&self, index: usize) -> Option<usize> {
+        self.nodes[index].children.iter().copied().max_by(|&a, &b| {
+            let (a, b) = (&self.nodes[a], &self.nodes[b]);
+            a.visits.cmp(&b.visits).then(a.mean_value().total_cmp(&b.mean_value()))
+        })
     }
-    
-    0
-}
 
-/// Evaluate king safety based on surrounding pieces and pawn structure
-fn evaluate_king_safety(board: &Chess, color: Color) -> i32 {
-    let king_square = find_king_square(board, color);
-    if king_square.is_none() {
-        return 0; // No king found or already captured
+    /// Most visited move at the root
+    pub fn best_move(&self) -> Option<Move> {
+        self.nodes.first()?;
+        self.best_child(0).and_then(|child| self.nodes[child].mv.clone())
     }
-    
-    let king_sq = king_square.unwrap();
-    let mut safety_score = 0;
-    
-    // Check if king is in a corner or on edge (generally safer in early/mid game)
-    let file = king_sq.file().char() as u8 - b'a';
-    let rank = king_sq.rank().char() as u8 - b'1';
-    
-    // Bonus for king on edge or corner
-    if file == 0 || file == 7 || rank == 0 || rank == 7 {
-        safety_score += 20;
-        
-        // Extra bonus for corner
-        if (file == 0 || file == 7) && (rank == 0 || rank == 7) {
-            safety_score += 15;
+
+    // The most visited line from the root
+    fn principal_variation(&self) -> Vec<Move> {
+        let mut line = Vec::new();
+        let mut index = 0;
+        while let Some(child) = self.best_child(index) {
+            if self.nodes[child].visits == 0 {
+                break;
+            }
+            line.extend(self.nodes[child].mv.clone());
+            index = child;
         }
+        line
     }
-    
-    // Check for friendly pawns protecting the king
-    for offset in &[(-1, 1), (0, 1), (1, 1), (-1, 0), (1, 0), (-1, -1), (0, -1), (1, -1)] {
-        let new_file = file as i32 + offset.0;
-        let new_rank = rank as i32 + offset.1;
-        
-        // Skip if out of bounds
-        if new_file < 0 || new_file > 7 || new_rank < 0 || new_rank > 7 {
-            continue;
+
+    // One iteration: selection, expansion, evaluation and backpropagation.
+    // Returns the ply of the node that was evaluated.
+    fn iterate(
+        &mut self,
+        ctx: &AiGameStateContext,
+        evaluator: &mut Evaluator,
+        keys: &ZobristKeys,
+        tt: &mut TranspositionTable,
+        rng: &mut impl Rng,
+        stats: &mut SearchStats,
+    ) -> usize {
+        // Selection: down the tree while every move of a node has been tried
+        let mut index = 0;
+        let mut ply = 0;
+        while self.nodes[index].untried.is_empty() && !self.nodes[index].children.is_empty() {
+            index = self.select_child(index);
+            ply += 1;
         }
-        
-        // Convert to square using a direct approach
-        let new_file_char = (new_file as u8 + b'a') as char;
-        let new_rank_char = (new_rank as u8 + b'1') as char;
-        
-        // We'll just check all squares on the board
-        for adj_square in Square::ALL {
-            let square_file = adj_square.file().char();
-            let square_rank = adj_square.rank().char();
-            
-            // Only consider the square we're interested in
-            if square_file == new_file_char && square_rank == new_rank_char {
-                // Check if friendly piece is on this square
-                if let Some(piece) = board.board().piece_at(adj_square) {
-                    if piece.color == color {
-                        // Friendly piece is protecting king
-                        let protection_bonus = match piece.role {
-                            Role::Pawn => 15,    // Pawns are good shields
-                            Role::Knight => 10,
-                            Role::Bishop => 8,
-                            Role::Rook => 12,
-                            Role::Queen => 5,    // Queen should usually not be next to king
-                            Role::King => 0,     // Another king? Shouldn't happen
-                        };
-                        safety_score += protection_bonus;
-                    }
-                }
-                break; // Found the square we were looking for
-            }
+
+        // Expansion: one untried move, unless the tree is full. The best move
+        // an earlier search stored for the position goes first.
+        if !self.nodes[index].untried.is_empty() && self.nodes.len() < MAX_TREE_NODES {
+            let node = &mut self.nodes[index];
+            let stored_best = tt.probe(node.key).and_then(|entry| entry.best_move.as_ref());
+            let pick = stored_best
+                .and_then(|best| node.untried.iter().position(|m| m == best))
+                .unwrap_or_else(|| rng.gen_range(0..node.untried.len()));
+            let mv = node.untried.swap_remove(pick);
+            let moves_since_capture = count_capture(node.moves_since_capture, node.position.turn(), &mv);
+            let mut position = node.position.clone();
+            position.play_unchecked(&mv);
+            let child = self.nodes.len();
+            self.nodes.push(MctsNode::new(position, Some(mv), Some(index), moves_since_capture, ctx, keys, rng));
+            self.nodes[index].children.push(child);
+            index = child;
+            ply += 1;
         }
+        stats.record_node(ply);
+
+        // Evaluation cutoff instead of a random playout: the result for the
+        // side that moved into the node
+        let mut result = 1.0 - self.nodes[index].side_to_move_result(evaluator, tt, stats);
+
+        // Backpropagation, flipping the point of view at every ply
+        let mut current = Some(index);
+        while let Some(i) = current {
+            let node = &mut self.nodes[i];
+            node.visits += 1;
+            node.value += result;
+            result = 1.0 - result;
+            current = node.parent;
+        }
+        ply
     }
-    
-    // Give white king some extra safety bonus to balance black's advantage
-    if color == Color::White {
-        safety_score = (safety_score as f32 * 1.5) as i32; // 50% bonus for white king safety
-    }
-    
-    safety_score
 }
 
-/// Find the king's square for a given color
-fn find_king_square(board: &Chess, color: Color) -> Option<Square> {
-    for square in Square::ALL {
-        if let Some(piece) = board.board().piece_at(square) {
-            if piece.role == Role::King && piece.color == color {
-                return Some(square);
+// Win probability of the side to move for an evaluation in centipawns
+fn win_probability(eval_cp: i32) -> f64 {
+    1.0 / (1.0 + (-eval_cp as f64 / EVAL_SCALE_CP).exp())
+}
+
+// Centipawns matching a win probability (the inverse of `win_probability`)
+fn centipawns(probability: f64) -> i32 {
+    let probability = probability.clamp(0.001, 0.999);
+    (EVAL_SCALE_CP * (probability / (1.0 - probability)).ln()).round() as i32
+}
+
+/// Monte Carlo Tree Search: UCT selection, expansion of one move per
+/// iteration, an evaluation cutoff instead of random playouts, and
+/// backpropagation. `tree` is what earlier searches left; it is re-rooted at
+/// the current position and searched further, and stays around for the next
+/// move. Every node holds the side to move to its drawback (see
+/// `AiGameStateContext::search_moves`). Leaf evaluations are shared through
+/// `tt`, which also remembers the move chosen here so it is expanded first
+/// when the position comes up again. Stops after `ctx.iteration_limit`
+/// iterations or `time_limit`, whichever comes first.
+pub fn find_best_move_mcts(
+    ctx: AiGameStateContext,
+    tree: &mut MctsTree,
+    tt: &mut TranspositionTable,
+    time_limit: Duration,
+    pst: &PieceSquareTables,
+    keys: &ZobristKeys,
+    progress: &SearchProgress,
+) -> Option<Move> {
+    let start_time = Instant::now();
+    let mut rng = ctx.rng();
+    tt.new_search();
+    tree.reroot(&ctx, keys, &mut rng);
+    let reused = tree.root_visits();
+    let mut stats = SearchStats::default();
+    let mut evaluator = Evaluator::new(pst.clone(), keys.clone());
+
+    let root = &tree.nodes[0];
+    if root.children.is_empty() && root.untried.is_empty() {
+        return None;
+    }
+
+    let mut completed = 0;
+    let mut deepest = 0;
+    while completed < ctx.iteration_limit && start_time.elapsed() < time_limit {
+        let ply = tree.iterate(&ctx, &mut evaluator, keys, tt, &mut rng, &mut stats);
+        completed += 1;
+        if ply > deepest {
+            deepest = ply;
+            progress.set_depth(deepest.min(u8::MAX as usize) as u8);
+        }
+        if completed % PROGRESS_INTERVAL == 0 {
+            progress.add_nodes(PROGRESS_INTERVAL as u64);
+            if let Some(best_move) = tree.best_move() {
+                progress.set_best_move(best_move);
             }
+            progress.set_plan(tree.principal_variation());
         }
     }
-    None
-}
\ No newline at end of file
+    progress.add_nodes((completed % PROGRESS_INTERVAL) as u64);
+    progress.set_plan(tree.principal_variation());
+    stats.record_tt_usage(tt.memory_bytes(), tt.capacity(), tt.used());
+    progress.set_stats(stats);
+
+    println!(
+        "MCTS: {} iterations in {:?} ({} visits reused, {} nodes in the tree)",
+        completed, start_time.elapsed(), reused, tree.len()
+    );
+    let best = tree.best_child(0);
+    if let Some(child) = best {
+        let child = &tree.nodes[child];
+        let score = centipawns(child.mean_value());
+        tt.store(tree.nodes[0].key, deepest.min(u8::MAX as usize) as u8, score, Bound::Exact, child.mv.clone());
+    }
+    best.and_then(|child| tree.nodes[child].mv.clone())
+}
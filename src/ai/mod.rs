@@ -1,9 +1,21 @@
 pub mod components;
 pub mod plugin;
 pub mod mcts;
+pub mod alphabeta;
 pub mod zobrist;
+pub mod transposition;
 pub mod evaluation;
+pub mod bench;
+pub mod batch;
+pub mod sprt;
+pub mod search_stack;
 pub mod pleco_ai;
+pub mod analysis;
+pub mod review;
+pub mod fallback;
+pub mod crash_report;
+pub mod search_stats;
+pub mod opponent_model;
 
 pub use plugin::AiPlugin;
 pub use zobrist::{ZobristPlugin};
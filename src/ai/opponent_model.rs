@@ -0,0 +1,211 @@
+use bevy::prelude::*;
+use shakmaty::{ByColor, Chess, Color as ChessColor, Move, Position};
+use crate::config::GameConfig;
+use crate::drawbacks::{DrawbackRegistry, DrawbackId, definition::{DrawbackRule, TurnContext}};
+use crate::game_logic::history::MoveHistory;
+use crate::game_logic::state::{GameState, ActiveBoard};
+use std::sync::Arc;
+
+/// A candidate for the opponent's drawback and how likely it is. The rule is
+/// None for "no drawback".
+pub type WeightedDrawback = (Option<Arc<dyn DrawbackRule + Send + Sync>>, f64);
+
+/// Probability distribution over the drawbacks one player might have,
+/// built from the moves they were seen to play
+#[derive(Debug, Clone, Default)]
+pub struct DrawbackBelief {
+    weights: Vec<(DrawbackId, f64)>, // Empty until the first observed move: every drawback equally likely
+}
+
+impl DrawbackBelief {
+    fn uniform(registry: &DrawbackRegistry) -> Vec<(DrawbackId, f64)> {
+        let candidates: Vec<DrawbackId> = std::iter::once(DrawbackId::None).chain(registry.sorted_ids()).collect();
+        let weight = 1.0 / candidates.len() as f64;
+        candidates.into_iter().map(|id| (id, weight)).collect()
+    }
+
+    /// Every candidate with its probability, the probabilities add up to 1
+    pub fn weights(&self, registry: &DrawbackRegistry) -> Vec<(DrawbackId, f64)> {
+        if self.weights.is_empty() {
+            Self::uniform(registry)
+        } else {
+            self.weights.clone()
+        }
+    }
+
+    /// The `count` likeliest candidates that are still possible, likeliest first
+    pub fn likeliest(&self, registry: &DrawbackRegistry, count: usize) -> Vec<(DrawbackId, f64)> {
+        let mut weights: Vec<(DrawbackId, f64)> = self.weights(registry).into_iter().filter(|(_, weight)| *weight > 0.0).collect();
+        weights.sort_by(|a, b| b.1.total_cmp(&a.1));
+        weights.truncate(count);
+        weights
+    }
+
+    /// The candidates that are still possible, with their rules, for the search
+    pub fn weighted_rules(&self, registry: &DrawbackRegistry) -> Vec<WeightedDrawback> {
+        self.weights(registry)
+            .into_iter()
+            .filter(|(_, weight)| *weight > 0.0)
+            .map(|(id, weight)| (registry.rules.get(&id).cloned(), weight))
+            .collect()
+    }
+
+    /// Updates the belief with a move played from `position` (reached as `turn`
+    /// tells), where `legal_moves` were all the moves the rules of chess allowed. A candidate that would have
+    /// forbidden the move is ruled out; the others gain weight the fewer moves they
+    /// leave, as a player picking among fewer moves is likelier to pick this one.
+    /// Returns the candidates the move ruled out.
+    pub fn observe(
+        &mut self,
+        position: &Chess,
+        turn: &TurnContext,
+        legal_moves: &[Move],
+        played: &Move,
+        registry: &DrawbackRegistry,
+    ) -> Vec<DrawbackId> {
+        let previous = self.weights(registry);
+        let updated: Vec<(DrawbackId, f64)> = previous
+            .iter()
+            .map(|&(id, weight)| {
+                let likelihood = match registry.rules.get(&id) {
+                    Some(rule) if weight > 0.0 => move_likelihood(rule.as_ref(), position, turn, legal_moves, played),
+                    _ => 1.0, // No drawback allows every legal move
+                };
+                (id, weight * likelihood)
+            })
+            .collect();
+
+        let total: f64 = updated.iter().map(|(_, weight)| weight).sum();
+        if total <= 0.0 {
+            // Nothing we know of explains the move, keep what we had
+            self.weights = previous;
+            return Vec::new();
+        }
+        let ruled_out = previous
+            .iter()
+            .zip(&updated)
+            .filter(|((_, before), (_, after))| *before > 0.0 && *after <= 0.0)
+            .map(|((id, _), _)| *id)
+            .collect();
+        self.weights = updated.into_iter().map(|(id, weight)| (id, weight / total)).collect();
+        ruled_out
+    }
+}
+
+// Outcomes of a rule's per-turn RNG, or just None for rules without one
+pub fn rng_outcomes(rule: &dyn DrawbackRule) -> Vec<Option<u8>> {
+    if rule.needs_turn_rng() {
+        (0..rule.get_rng_outcomes()).map(Some).collect()
+    } else {
+        vec![None]
+    }
+}
+
+// Chance of a player with the rule picking the move, relative to a player
+// without a drawback: legal moves over allowed moves, 0 if the rule forbids it.
+// The opponent's RNG outcome isn't visible, so random rules average over all of them.
+fn move_likelihood(rule: &dyn DrawbackRule, position: &Chess, turn: &TurnContext, legal_moves: &[Move], played: &Move) -> f64 {
+    let outcomes = rng_outcomes(rule);
+    let sum: f64 = outcomes
+        .iter()
+        .map(|&rng_outcome| {
+            let allowed = rule.filter_moves(position, legal_moves.to_vec(), &TurnContext { rng_outcome, ..*turn });
+            if allowed.contains(played) {
+                legal_moves.len() as f64 / allowed.len() as f64
+            } else {
+                0.0
+            }
+        })
+        .sum();
+    sum / outcomes.len() as f64
+}
+
+/// The belief about one player right after one of their moves was observed
+#[derive(Debug, Clone)]
+pub struct BeliefSnapshot {
+    pub ply: usize,                     // Number of plies played, including the observed move
+    pub weights: Vec<(DrawbackId, f64)>,
+    pub ruled_out: Vec<DrawbackId>,     // Candidates the move ruled out
+}
+
+/// Resource with the AI's belief about each player's drawback.
+/// Only used when `AiSettings::opponent_model` is on; otherwise the AI reads the real drawbacks.
+#[derive(Resource, Default)]
+pub struct OpponentModel {
+    pub beliefs: ByColor<DrawbackBelief>,
+    pub history: ByColor<Vec<BeliefSnapshot>>, // How each belief changed over the game
+    observed_plies: usize,
+    position_before: Option<(Chess, Vec<Move>, u32)>, // The position of the move not observed yet, its legal moves and the mover's moves without a capture
+}
+
+/// System to update the beliefs with each move played. Runs before `apply_move`,
+/// so the move of the previous frame is judged against the position it was played in.
+pub fn observe_played_moves(
+    mut model: ResMut<OpponentModel>,
+    history: Res<MoveHistory>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    registry: Res<DrawbackRegistry>,
+    config: Res<GameConfig>,
+) {
+    if !config.ai_settings.opponent_model {
+        return;
+    }
+    if history.len() < model.observed_plies {
+        // A new game (or a loaded one) replaced the history
+        *model = OpponentModel::default();
+    }
+    if history.len() == model.observed_plies && model.position_before.is_some() {
+        return;
+    }
+
+    if history.len() == model.observed_plies + 1 {
+        if let Some((position, legal_moves, moves_since_capture)) = model.position_before.take() {
+            let color = position.turn();
+            let played = &history.moves[model.observed_plies].chess_move;
+            // The opponent's roll isn't visible, `move_likelihood` goes through every outcome
+            let turn = TurnContext {
+                rng_outcome: None,
+                last_move: model.observed_plies.checked_sub(1).map(|ply| &history.moves[ply].chess_move),
+                moves_since_capture,
+            };
+            let ruled_out = model.beliefs.get_mut(color).observe(&position, &turn, &legal_moves, played, &registry);
+            for id in &ruled_out {
+                println!("Opponent model: {:?} can't have {:?}", color, id);
+            }
+            let snapshot = BeliefSnapshot {
+                ply: history.len(),
+                weights: model.beliefs.get(color).weights(&registry),
+                ruled_out,
+            };
+            model.history.get_mut(color).push(snapshot);
+        }
+    }
+    // Moves we didn't see being played (e.g. an imported game) are skipped
+    model.observed_plies = history.len();
+    model.position_before = boards
+        .get_single()
+        .ok()
+        .map(|game_state| (game_state.board.clone(), game_state.legal_moves(), game_state.moves_since_capture(game_state.current_player_turn)));
+}
+
+/// System to forget what the AI learned about the previous game's drawbacks
+pub fn reset_opponent_model(mut model: ResMut<OpponentModel>) {
+    *model = OpponentModel::default();
+}
+
+/// What the AI playing `color` believes about its opponent's drawback
+pub fn opponent_belief(
+    color: ChessColor,
+    game_state: &GameState,
+    config: &GameConfig,
+    model: &OpponentModel,
+    registry: &DrawbackRegistry,
+) -> Vec<WeightedDrawback> {
+    let opponent = !color;
+    if config.ai_settings.opponent_model {
+        model.beliefs.get(opponent).weighted_rules(registry)
+    } else {
+        // Drawbacks aren't hidden from the AI: it is certain of the real one
+        vec![(game_state.drawback_rule(opponent, registry), 1.0)]
+    }
+}
@@ -1,23 +1,30 @@
 use shakmaty::{Move, Position, Chess, Role, Square, File, Rank};
-use super::plugin::AiGameStateContext;
+use super::plugin::{AiGameStateContext, count_capture};
+use crate::drawbacks::definition::TurnContext;
+use super::components::SearchProgress;
+use super::search_stats::SearchStats;
+use super::opponent_model::rng_outcomes;
 use std::time::Duration;
 use rand::seq::SliceRandom;
-use pleco::{Board, BitMove, PieceType};
+use pleco::{Board, BitMove, PieceType, Player};
 use pleco::core::score::Score;
+use pleco::bots::alphabeta::alpha_beta_search;
+
+// Length of the expected line published for the observer arrows, in plies
+const PLAN_PLIES: usize = 4;
+// Score of leaving the opponent without a move their drawback allows
+const STUCK_OPPONENT_SCORE: i32 = 100_000;
 
 // Convert shakmaty Chess to Pleco Board
 fn to_pleco_board(chess: &Chess) -> Option<Board> {
     // Convert position to FEN
     let position_fen = format!("{} {} - - 0 1", 
-        chess.board().to_string(), 
+        chess.board(), 
         if chess.turn() == shakmaty::Color::White { "w" } else { "b" }
     );
     
     // Create a Pleco board from FEN
-    match Board::from_fen(&position_fen) {
-        Ok(board) => Some(board),
-        Err(_) => None,
-    }
+    Board::from_fen(&position_fen).ok()
 }
 
 // Convert square coordinates to shakmaty Square
@@ -111,10 +118,145 @@ fn is_better_score(score: Score, best_score: Score) -> bool {
     }
 }
 
+// The line the engine expects after a move: each side's best one-ply reply in turn,
+// from the point of view of the side to move, among the moves its drawback allows
+// as the search holds it to them. Stops at the first move that can't be played on
+// the shakmaty board, or when the side to move has nothing left.
+fn expected_line(ctx: &AiGameStateContext, first: BitMove, board: &Board) -> Vec<Move> {
+    let mut rng = ctx.rng();
+    let mut line = Vec::with_capacity(PLAN_PLIES);
+    let mut board = board.clone();
+    let mut chess = ctx.board.clone();
+    let mut moves_since_capture = ctx.moves_since_capture;
+    let mut next = Some(first);
+    while let Some(bit_move) = next {
+        let Some(m) = to_shakmaty_move(bit_move, &chess).filter(|m| chess.is_legal(m)) else {
+            break;
+        };
+        board.apply_move(bit_move);
+        moves_since_capture = count_capture(moves_since_capture, chess.turn(), &m);
+        chess.play_unchecked(&m);
+        line.push(m);
+        if line.len() == PLAN_PLIES {
+            break;
+        }
+        let turn = TurnContext {
+            rng_outcome: None,
+            last_move: line.last(),
+            moves_since_capture: *moves_since_capture.get(chess.turn()),
+        };
+        let allowed = ctx.search_moves(&chess, &turn, &mut rng);
+        next = best_reply(&board, |reply| to_shakmaty_move(reply, &chess).is_some_and(|m| allowed.contains(&m)));
+    }
+    line
+}
+
+// Best move of the side to move among those `allowed`, by the piece-square
+// score of the position it leads to
+fn best_reply(board: &Board, allowed: impl Fn(BitMove) -> bool) -> Option<BitMove> {
+    let sign = if board.turn() == Player::White { 1 } else { -1 };
+    board.generate_moves().into_iter().filter(|&bit_move| allowed(bit_move)).max_by_key(|&bit_move| {
+        let mut next = board.clone();
+        next.apply_move(bit_move);
+        let Score(mg, eg) = next.psq();
+        (mg * sign, eg * sign)
+    })
+}
+
+// Score of a root move. Without a belief about the opponent's drawback it is the
+// piece-square score of the position the move leads to (from White's side). With
+// one it is from the mover's side: the opponent answers with its best reply the
+// candidate drawback allows, averaged over the candidates by their probability.
+fn root_move_score(
+    ctx: &AiGameStateContext,
+    board: &Board,
+    bit_move: BitMove,
+    progress: &SearchProgress,
+    stats: &mut SearchStats,
+) -> Score {
+    let mut after = board.clone();
+    after.apply_move(bit_move);
+    progress.add_nodes(1);
+    stats.record_node(1);
+
+    let sign = if ctx.player_turn == shakmaty::Color::White { 1 } else { -1 };
+    let side_score = |board: &Board| {
+        let Score(mg, eg) = board.psq();
+        (mg * sign, eg * sign)
+    };
+    let Some((root_move, chess_after)) = (!ctx.opponent_belief.is_empty())
+        .then(|| to_shakmaty_move(bit_move, &ctx.board))
+        .flatten()
+        .map(|m| {
+            let mut chess = ctx.board.clone();
+            chess.play_unchecked(&m);
+            (m, chess)
+        })
+    else {
+        return after.psq();
+    };
+
+    // Every reply with the score it leaves us
+    let replies: Vec<(Move, (i32, i32))> = after
+        .generate_moves()
+        .into_iter()
+        .filter_map(|reply| {
+            let m = to_shakmaty_move(reply, &chess_after)?;
+            let mut next = after.clone();
+            next.apply_move(reply);
+            progress.add_nodes(1);
+            stats.record_node(2);
+            Some((m, side_score(&next)))
+        })
+        .collect();
+    if replies.is_empty() {
+        let (mg, eg) = side_score(&after);
+        return Score(mg, eg);
+    }
+
+    let reply_moves: Vec<Move> = replies.iter().map(|(m, _)| m.clone()).collect();
+    let moves_since_capture = count_capture(ctx.moves_since_capture, ctx.player_turn, &root_move);
+    let (mut mg, mut eg) = (0.0, 0.0);
+    for (rule, weight) in &ctx.opponent_belief {
+        let outcomes = rule.as_deref().map_or(vec![None], |rule| rng_outcomes(rule));
+        for &rng_outcome in &outcomes {
+            let turn = TurnContext {
+                rng_outcome,
+                last_move: Some(&root_move),
+                moves_since_capture: *moves_since_capture.get(!ctx.player_turn),
+            };
+            let allowed = match rule {
+                Some(rule) => rule.filter_moves(&chess_after, reply_moves.clone(), &turn),
+                None => reply_moves.clone(),
+            };
+            // No reply left means the drawback makes the opponent lose
+            let (reply_mg, reply_eg) = replies
+                .iter()
+                .filter(|(m, _)| allowed.contains(m))
+                .map(|(_, score)| *score)
+                .min()
+                .unwrap_or((STUCK_OPPONENT_SCORE, STUCK_OPPONENT_SCORE));
+            let share = weight / outcomes.len() as f64;
+            mg += share * reply_mg as f64;
+            eg += share * reply_eg as f64;
+        }
+    }
+    Score(mg.round() as i32, eg.round() as i32)
+}
+
 // Find the best move using Pleco's analysis
-pub fn find_best_move_pleco(ctx: AiGameStateContext, time_limit: Duration, depth: u16) -> Option<Move> {
-    // Get all legal moves
-    let legal_moves = ctx.board.legal_moves();
+pub fn find_best_move_pleco(
+    ctx: AiGameStateContext,
+    time_limit: Duration,
+    depth: u16,
+    progress: &SearchProgress,
+) -> Option<Move> {
+    // Get all moves the drawback allows
+    let legal_moves: Vec<Move> = if ctx.allowed_moves.is_empty() {
+        ctx.board.legal_moves().into_iter().collect()
+    } else {
+        ctx.allowed_moves.clone()
+    };
     
     if legal_moves.is_empty() {
         return None;
@@ -125,35 +267,50 @@ pub fn find_best_move_pleco(ctx: AiGameStateContext, time_limit: Duration, depth
         Some(board) => board,
         None => {
             // Fallback to random move if conversion fails
-            let mut rng = rand::thread_rng();
+            let mut rng = ctx.rng();
             return legal_moves.choose(&mut rng).cloned();
         }
     };
     
+    // Whether the drawback allows a Pleco move at the root
+    let root_allowed = |bit_move: BitMove| {
+        to_shakmaty_move(bit_move, &ctx.board).is_some_and(|m| legal_moves.contains(&m))
+    };
+
+    // Root node; every move tried below is a node one ply deeper
+    let mut stats = SearchStats::default();
+    stats.record_node(0);
+
+    // One ply, or two when the opponent's replies are weighed by what their drawback allows
+    let search_depth = if ctx.opponent_belief.is_empty() { 1 } else { 2 };
+
     // Use Pleco's search capabilities
     let depth_limit = if depth < 1 { 3 } else { depth as usize };
     
     // Find the best move using Pleco's minimax search
     let best_move = if time_limit.as_millis() > 100 {
         // Use a time-limited search
-        let search_depth = std::cmp::min(depth_limit, 4); // Limit depth for time-based search
+        let _search_depth = std::cmp::min(depth_limit, 4); // Limit depth for time-based search
         let mut best_score = Score(-999999, -999999); // Use minimal Score value
         let mut best_bit_move = None;
         
         // Generate all legal moves in Pleco
         let pleco_moves = pleco_board.generate_moves();
+        progress.set_depth(search_depth);
         
-        // Try each move and evaluate
-        for bit_move in pleco_moves {
-            let mut new_board = pleco_board.clone();
-            new_board.apply_move(bit_move);
-            
-            // Simple evaluation based on material count
-            let score = new_board.psq();
+        // Try each move the drawback allows and evaluate
+        for bit_move in pleco_moves.into_iter().filter(|&bit_move| root_allowed(bit_move)) {
+            let score = root_move_score(&ctx, &pleco_board, bit_move, progress, &mut stats);
             
             if is_better_score(score, best_score) {
                 best_score = score;
                 best_bit_move = Some(bit_move);
+                
+                // Publish the best move so far for the thinking indicator
+                if let Some(m) = to_shakmaty_move(bit_move, &ctx.board) {
+                    progress.set_best_move(m);
+                }
+                progress.set_plan(expected_line(&ctx, bit_move, &pleco_board));
             }
         }
         
@@ -165,50 +322,69 @@ pub fn find_best_move_pleco(ctx: AiGameStateContext, time_limit: Duration, depth
         
         // Generate all legal moves in Pleco
         let pleco_moves = pleco_board.generate_moves();
+        progress.set_depth(search_depth);
         
-        // Try each move and evaluate
-        for bit_move in pleco_moves {
-            let mut new_board = pleco_board.clone();
-            new_board.apply_move(bit_move);
-            
-            // Simple evaluation based on material count
-            let score = new_board.psq();
+        // Try each move the drawback allows and evaluate
+        for bit_move in pleco_moves.into_iter().filter(|&bit_move| root_allowed(bit_move)) {
+            let score = root_move_score(&ctx, &pleco_board, bit_move, progress, &mut stats);
             
             if is_better_score(score, best_score) {
                 best_score = score;
                 best_bit_move = Some(bit_move);
+                
+                // Publish the best move so far for the thinking indicator
+                if let Some(m) = to_shakmaty_move(bit_move, &ctx.board) {
+                    progress.set_best_move(m);
+                }
+                progress.set_plan(expected_line(&ctx, bit_move, &pleco_board));
             }
         }
         
         best_bit_move
     };
     
+    progress.set_stats(stats);
+
     // Handle the result
     match best_move {
         Some(bit_move) => {
             // Convert the move
             match to_shakmaty_move(bit_move, &ctx.board) {
                 Some(m) => {
-                    // Verify the move is legal
-                    if ctx.board.is_legal(&m) {
+                    // Verify the move is legal and allowed
+                    if legal_moves.contains(&m) {
                         Some(m)
                     } else {
                         // Fallback to random move if illegal
-                        let mut rng = rand::thread_rng();
+                        let mut rng = ctx.rng();
                         legal_moves.choose(&mut rng).cloned()
                     }
                 },
                 None => {
                     // Fallback to random move if conversion fails
-                    let mut rng = rand::thread_rng();
+                    let mut rng = ctx.rng();
                     legal_moves.choose(&mut rng).cloned()
                 }
             }
         },
         None => {
             // Fallback to random move if search fails
-            let mut rng = rand::thread_rng();
+            let mut rng = ctx.rng();
             legal_moves.choose(&mut rng).cloned()
         }
     }
-} 
\ No newline at end of file
+} 
+/// Static-plus-search evaluation of a position in centipawns from White's point of view,
+/// using Pleco's alpha-beta search. Returns None if the position can't be converted.
+pub fn evaluate_position_pleco(chess: &Chess, depth: u16) -> Option<i32> {
+    let mut pleco_board = to_pleco_board(chess)?;
+
+    // Pleco scores from the side to move's perspective
+    let result = alpha_beta_search(&mut pleco_board, -i16::MAX, i16::MAX, depth);
+    let score = result.score as i32;
+
+    Some(match chess.turn() {
+        shakmaty::Color::White => score,
+        shakmaty::Color::Black => -score,
+    })
+}
@@ -1,29 +1,103 @@
 use bevy::prelude::*;
 use bevy::tasks::AsyncComputeTaskPool;
 use futures_lite::future;
-use shakmaty::{Chess, Color as ChessColor, Move, Position};
-use crate::game_logic::state::{GameState, TurnState};
-use crate::game_logic::events::MakeMoveEvent;
-use crate::drawbacks::{DrawbackRegistry, DrawbackId, definition::DrawbackRule};
-use crate::config::GameConfig;
+use shakmaty::{ByColor, Chess, Color as ChessColor, Move, Position};
+use crate::game_logic::state::{GameState, ActiveBoard, GameStatus, TurnState, PauseState, ReplayState, gameplay_active};
+use crate::game_logic::events::{MakeMoveEvent, NewGameEvent, ForceAiMoveEvent, SwapSidesEvent};
+use crate::drawbacks::{DrawbackRegistry, DrawbackId, definition::{DrawbackRule, TurnContext}};
+use crate::config::{AiEngine, GameConfig};
 use crate::constants::DEFAULT_BOARD_FLIPPED;
-use super::components::AiThinking;
+use crate::game_logic::rng::GameRng;
+use crate::game_logic::clock::GameClock;
+use crate::game_logic::history::MoveHistory;
+use super::components::{AiThinking, SearchProgress};
 use super::pleco_ai::find_best_move_pleco;
-use rand::Rng;
-use std::sync::Arc;
+use super::mcts::{MctsTree, find_best_move_mcts};
+use super::alphabeta::find_best_move_alphabeta;
+use super::transposition::SharedTranspositionTable;
+use super::zobrist::{ZobristKeys, calculate_board_hash};
+use super::evaluation::{PieceSquareTables, PST_FILE_PATH};
+use super::search_stats::{SearchStatsLog, handle_search_stats_keys};
+use crate::input::focus::keyboard_shortcuts_enabled;
+use super::analysis::{ReplayAnalysis, request_replay_analysis, poll_replay_analysis};
+use super::fallback::{AiFallbackEvent, AiFallbackReason, fallback_move};
+use super::crash_report::{AiCrashReport, CRASH_REPORT_PATH, panic_message};
+use crate::game_logic::legal_moves::{LegalMovesCache, refresh_legal_moves_cache};
+use super::review::{GameReviewState, start_game_review, poll_game_review, clear_game_review};
+use super::opponent_model::{OpponentModel, WeightedDrawback, opponent_belief, observe_played_moves, reset_opponent_model};
+use crate::game_logic::systems::apply_move;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 pub struct AiPlugin;
 
 impl Plugin for AiPlugin {
     fn build(&self, app: &mut App) {
+        // Evaluation tables can be swapped without recompiling by editing the table file
+        let pst = PieceSquareTables::load(PST_FILE_PATH).unwrap_or_else(|e| {
+            eprintln!("Failed to read {}: {} (using the built-in tables)", PST_FILE_PATH, e);
+            PieceSquareTables::default()
+        });
+
         app
             // Set up default configuration - use max_power_ai preset
             .insert_resource(crate::config::presets::max_power_ai())
+            .insert_resource(pst)
+            .init_resource::<ReplayAnalysis>()
+            .init_resource::<GameReviewState>()
+            .init_resource::<OpponentModel>()
+            .init_resource::<ForcedAiMove>()
+            .init_resource::<MctsTreeCache>()
+            .init_resource::<SharedTranspositionTable>()
+            .add_event::<AiFallbackEvent>()
+            .insert_resource(SearchStatsLog::from_args())
             // Add systems
             .add_systems(Startup, initialize_board_state)
-            .add_systems(Update, request_ai_move)
-            .add_systems(Update, check_ai_move_result);
+            .add_systems(
+                Update,
+                (request_ai_move, check_ai_move_result.after(refresh_legal_moves_cache))
+                    .run_if(gameplay_active)
+            )
+            // Developer keys for dumping search statistics
+            .add_systems(Update, handle_search_stats_keys.run_if(keyboard_shortcuts_enabled))
+            // Stop pondering as soon as the game is paused or replayed
+            .add_systems(OnEnter(PauseState::Paused), cancel_ai_thinking)
+            .add_systems(OnEnter(ReplayState::Replay), cancel_ai_thinking)
+            .add_systems(Update, cancel_ai_thinking.run_if(on_event::<NewGameEvent>().or_else(on_event::<SwapSidesEvent>())))
+            .add_systems(Update, (clear_mcts_tree, clear_transposition_table).run_if(on_event::<NewGameEvent>()))
+            // The transposition table follows its size setting without a restart
+            .add_systems(Update, resize_transposition_table.run_if(resource_changed::<GameConfig>()))
+            // Moves the AI is made to play for the side to move (console or menu)
+            .add_systems(
+                Update,
+                force_ai_move
+                    .after(check_ai_move_result)
+                    .before(request_ai_move)
+                    .run_if(on_event::<ForceAiMoveEvent>())
+                    .run_if(gameplay_active)
+            )
+            // Engine evaluation of the position shown in replay mode
+            .add_systems(
+                Update,
+                (request_replay_analysis, poll_replay_analysis)
+                    .chain()
+                    .run_if(in_state(ReplayState::Replay))
+            )
+            // What the AI makes of the moves played, for when drawbacks are hidden from it
+            .add_systems(
+                Update,
+                (reset_opponent_model.run_if(on_event::<NewGameEvent>()), observe_played_moves)
+                    .chain()
+                    .after(refresh_legal_moves_cache)
+                    .before(apply_move)
+                    .before(request_ai_move)
+            )
+            // Review of the finished game (accuracy, mistakes, drawback impact)
+            .add_systems(Update, (start_game_review, poll_game_review))
+            .add_systems(Update, clear_game_review.run_if(on_event::<NewGameEvent>()));
     }
 }
 
@@ -39,6 +113,15 @@ pub struct AiGameStateContext {
     pub check_quietness: bool,  // Whether to ensure positions are quiet
     pub quiescence_depth: u8,   // Extra depth for non-quiet positions
     pub time_limit_ms: u32,     // Time limit in milliseconds
+    pub iteration_limit: u32,   // Iteration limit of the Monte Carlo search
+    pub rng_seed: Option<u64>,  // Seed for random choices (from `GameRng`), None = entropy
+    pub opponent_belief: Vec<WeightedDrawback>, // Candidates for the opponent's drawback, empty = ignore it
+    pub allowed_moves: Vec<Move>, // Root moves the mover's drawback allows, empty = every legal move
+    pub player_rule: Option<Arc<dyn DrawbackRule + Send + Sync>>, // Our drawback, for the nodes below the root where we move
+    pub last_move: Option<Move>, // Move that led to the root
+    pub rng_outcome: Option<u8>, // The side to move's roll for the root, if its drawback has one
+    pub moves_since_capture: ByColor<u32>, // Each side's moves without a capture at the root
+    pub played_positions: Vec<u64>, // Board hashes of the game's positions before the root, oldest first
 }
 
 impl AiGameStateContext {
@@ -56,10 +139,149 @@ impl AiGameStateContext {
             check_quietness: config.ai_settings.check_quietness,
             quiescence_depth: config.ai_settings.quiescence_depth,
             time_limit_ms: config.ai_settings.time_limit_ms,
+            iteration_limit: config.ai_settings.iteration_limit,
+            rng_seed: None,
+            opponent_belief: Vec::new(),
+            allowed_moves: Vec::new(),
+            player_rule: None,
+            last_move: game_state.last_move.clone(),
+            rng_outcome: game_state.current_turn_rng_outcome,
+            moves_since_capture: ByColor {
+                white: game_state.white_moves_since_capture,
+                black: game_state.black_moves_since_capture,
+            },
+            played_positions: Vec::new(),
+        }
+    }
+
+    /// The drawback the search holds `color` to: ours, or the likeliest
+    /// candidate for the opponent's
+    pub fn rule_for(&self, color: ChessColor) -> Option<Arc<dyn DrawbackRule + Send + Sync>> {
+        if color == self.player_turn {
+            return self.player_rule.clone();
         }
+        self.opponent_belief
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .and_then(|(rule, _)| rule.clone())
+    }
+
+    /// Moves of the side to move in a position of the search, once its
+    /// drawback is applied. `turn` is what led to the position; a rule with a
+    /// per-turn roll gets an outcome rolled with `rng`, as the game would,
+    /// unless `turn` already has one.
+    pub fn search_moves(&self, position: &Chess, turn: &TurnContext, rng: &mut impl Rng) -> Vec<Move> {
+        let moves: Vec<Move> = position.legal_moves().into_iter().collect();
+        match self.rule_for(position.turn()) {
+            Some(rule) => {
+                let rolled = TurnContext {
+                    rng_outcome: turn.rng_outcome.or_else(|| rule.needs_turn_rng().then(|| rng.gen_range(0..rule.get_rng_outcomes().max(1)))),
+                    ..*turn
+                };
+                rule.filter_moves(position, moves, &rolled)
+            }
+            None => moves,
+        }
+    }
+
+    /// Whether the side to move loses to its drawback's loss condition, with
+    /// `moves` left after `search_moves`
+    pub fn loses_by_drawback(&self, position: &Chess, moves: &[Move], turn: &TurnContext) -> bool {
+        self.rule_for(position.turn()).is_some_and(|rule| rule.check_loss_condition(position, moves, turn))
+    }
+
+    /// RNG for the engine's random choices. With a seed it is derived from the
+    /// position too, so the same position always gets the same choice.
+    pub fn rng(&self) -> StdRng {
+        seeded_rng(self.rng_seed, self.current_hash)
+    }
+}
+
+/// Board hashes of the positions of `history` before its last one, oldest first
+pub fn played_positions(history: &MoveHistory, keys: &ZobristKeys) -> Vec<u64> {
+    let mut position = history.start_position.clone();
+    let mut hashes = Vec::with_capacity(history.len());
+    for record in &history.moves {
+        hashes.push(calculate_board_hash(&position, keys));
+        position.play_unchecked(&record.chess_move);
+    }
+    hashes
+}
+
+/// Each side's moves without a capture once `mover` has played `m`
+pub fn count_capture(mut moves_since_capture: ByColor<u32>, mover: ChessColor, m: &Move) -> ByColor<u32> {
+    let count = moves_since_capture.get_mut(mover);
+    *count = TurnContext::count_capture(*count, m);
+    moves_since_capture
+}
+
+// Helper function to build a (possibly seeded) RNG for a position
+fn seeded_rng(seed: Option<u64>, position_hash: u64) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed ^ position_hash),
+        None => StdRng::from_entropy(),
     }
 }
 
+/// Resource naming the position (by its hash) in which the AI was made to
+/// move for the side to move, whoever plays it
+#[derive(Resource, Debug, Default)]
+pub struct ForcedAiMove {
+    pub position_key: Option<u64>,
+}
+
+/// System to make the AI move for the side to move right away: a search that
+/// is already running plays the best move it has found so far, otherwise one
+/// is started (`request_ai_move` treats the side to move as the AI)
+fn force_ai_move(
+    mut commands: Commands,
+    mut ev_force: EventReader<ForceAiMoveEvent>,
+    mut ev_make_move: EventWriter<MakeMoveEvent>,
+    mut forced: ResMut<ForcedAiMove>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    task_q: Query<(Entity, &AiThinking)>,
+    legal_moves: Res<LegalMovesCache>,
+) {
+    ev_force.clear();
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    if game_state.status != GameStatus::Ongoing {
+        return;
+    }
+    for (entity, ai_task) in task_q.iter() {
+        if let Some(best_move) = ai_task.progress.best_move().filter(|best_move| legal_moves.contains(best_move)) {
+            println!("AI forced to move now: {:?}", best_move);
+            ev_make_move.send(MakeMoveEvent(best_move));
+            commands.entity(entity).despawn();
+            return;
+        }
+    }
+    println!("AI forced to move for {:?}", game_state.current_player_turn);
+    forced.position_key = Some(game_state.zobrist_hash);
+}
+
+/// Search tree of the Monte Carlo engine, kept from one AI move to the next.
+/// A search takes the tree out while it runs and puts it back when done.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct MctsTreeCache(pub Arc<Mutex<MctsTree>>);
+
+/// System to forget the Monte Carlo tree of the last game. A search still
+/// running puts its tree back into the old cache, which is dropped.
+fn clear_mcts_tree(mut cache: ResMut<MctsTreeCache>) {
+    *cache = MctsTreeCache::default();
+}
+
+/// System to start every game with an empty transposition table
+fn clear_transposition_table(mut table: ResMut<SharedTranspositionTable>) {
+    *table = SharedTranspositionTable::default();
+}
+
+/// System to resize the transposition table when its settings change
+fn resize_transposition_table(config: Res<GameConfig>, table: Res<SharedTranspositionTable>) {
+    table.resize(config.ai_settings.tt_size_mb, config.ai_settings.tt_replacement);
+}
+
 fn is_current_player_ai(game_state: &GameState, config: &GameConfig) -> bool {
     match game_state.current_player_turn {
         ChessColor::White => config.white_player.is_ai,
@@ -67,22 +289,42 @@ fn is_current_player_ai(game_state: &GameState, config: &GameConfig) -> bool {
     }
 }
 
-fn initialize_board_state(game_state: Option<ResMut<GameState>>) {
-    if let Some(mut state) = game_state {
+fn initialize_board_state(mut boards: Query<&mut GameState, With<ActiveBoard>>) {
+    for mut state in boards.iter_mut() {
         state.board_flipped = DEFAULT_BOARD_FLIPPED;
     }
 }
 
 /// System to spawn the AI calculation task
+#[allow(clippy::too_many_arguments)]
 fn request_ai_move(
     mut commands: Commands,
-    game_state: Res<GameState>,
+    boards: Query<&GameState, With<ActiveBoard>>,
     config: Res<GameConfig>,
-    _drawback_registry: Res<DrawbackRegistry>,
+    drawback_registry: Res<DrawbackRegistry>,
+    opponent_model: Res<OpponentModel>,
+    game_rng: Res<GameRng>,
     q_ai_task: Query<&AiThinking>,
+    mut forced: ResMut<ForcedAiMove>,
+    pst: Res<PieceSquareTables>,
+    mcts_tree: Res<MctsTreeCache>,
+    transposition_table: Res<SharedTranspositionTable>,
+    zobrist_keys: Res<ZobristKeys>,
+    clock: Res<GameClock>,
+    history: Res<MoveHistory>,
 ) {
-    // Check if it's the AI's turn based on the current player color and config
-    if !is_current_player_ai(&game_state, &config) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    // Nothing to think about once the game has ended (e.g. after a resignation)
+    if game_state.status != GameStatus::Ongoing {
+        return;
+    }
+    
+    // Check if it's the AI's turn based on the current player color and config,
+    // or if the AI was made to move in this position
+    let forced_here = forced.position_key == Some(game_state.zobrist_hash);
+    if !is_current_player_ai(game_state, &config) && !forced_here {
         return;
     }
     
@@ -90,6 +332,9 @@ fn request_ai_move(
     if !q_ai_task.is_empty() {
         return;
     }
+    if forced.position_key.is_some() {
+        forced.position_key = None;
+    }
 
     println!("AI turn detected. Spawning calculation task...");
     
@@ -107,48 +352,65 @@ fn request_ai_move(
     
     let thread_pool = AsyncComputeTaskPool::get();
 
-    // Create a deep copy of the game state for AI to use
-    let game_state_copy = GameState {
-        board: game_state.board.clone(),
-        current_player_turn: game_state.current_player_turn,
-        status: game_state.status.clone(),
-        white_drawback: game_state.white_drawback,
-        black_drawback: game_state.black_drawback,
-        current_turn_rng_outcome: game_state.current_turn_rng_outcome,
-        zobrist_hash: game_state.zobrist_hash,
-        board_flipped: game_state.board_flipped,
-    };
+    // The search task only gets the context built below (which owns copies of
+    // what it needs), so the live state is read directly instead of cloned
+    let mut ai_context = AiGameStateContext::from_game_state(game_state, &config);
+    // Tie-breaks come from the game's AI stream so a seeded game plays out the same
+    ai_context.rng_seed = Some(game_rng.ai_seed());
+    // The real drawback, or the guess at it when drawbacks are hidden from the AI
+    ai_context.opponent_belief = opponent_belief(
+        game_state.current_player_turn, game_state, &config, &opponent_model, &drawback_registry,
+    );
 
-    let (player_id, opponent_id) = match game_state_copy.current_player_turn {
-         ChessColor::White => (game_state_copy.white_drawback, game_state_copy.black_drawback),
-         ChessColor::Black => (game_state_copy.black_drawback, game_state_copy.white_drawback),
-    };
-
-    let _player_drawback_arc: Option<Arc<dyn DrawbackRule>> = if player_id != DrawbackId::None {
-        // Some(drawback_registry.rules.get(&player_id).cloned())
-        None
-    } else {
-        None
-    };
+    // Only moves our own drawback allows are searched
+    ai_context.allowed_moves = game_state.allowed_moves(&drawback_registry);
+    // ...and at every node below where we move
+    ai_context.player_rule = game_state.current_drawback_rule(&drawback_registry);
+    // Lines coming back to a position of the game are draws
+    ai_context.played_positions = played_positions(&history, &zobrist_keys);
 
-    let _opponent_drawback_arc: Option<Arc<dyn DrawbackRule>> = if opponent_id != DrawbackId::None {
-        // Some(drawback_registry.rules.get(&opponent_id).cloned())
-        None
-    } else {
-        None
-    };
-
-    let ai_context = AiGameStateContext::from_game_state(&game_state_copy, &config);
-    let iterations = config.ai_settings.iteration_limit;
-
-    let time_limit = Duration::from_millis(1000);
+    // With clocks the search gets a share of the AI's remaining time
+    let time_limit = Duration::from_millis(clock.search_budget_ms(game_state.current_player_turn).unwrap_or(1000));
+    ai_context.time_limit_ms = time_limit.as_millis() as u32;
     let depth = ai_context.depth as u16;
 
     debug!("AI starting calculation: time_limit={:?}, depth={}", time_limit, depth);
     let start_time = std::time::Instant::now();
+    
+    // Shared with the UI so it can show depth and the best move so far
+    let progress = Arc::new(SearchProgress::default());
+    let task_progress = progress.clone();
 
+    let engine = config.ai_settings.engine;
+    let (tt_size_mb, tt_replacement) = (config.ai_settings.tt_size_mb, config.ai_settings.tt_replacement);
+    let pst = pst.clone();
+    let keys = zobrist_keys.clone();
+    let tree = mcts_tree.0.clone();
+    let shared_tt = transposition_table.clone();
     let task = thread_pool.spawn(async move {
-        let result = find_best_move_pleco(ai_context, time_limit, depth);
+        // A panic in the search is caught and reported instead of leaving the
+        // game waiting for a move that never comes. The tree and table it had
+        // taken out are lost; the next search starts new ones.
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| match engine {
+            AiEngine::Pleco => find_best_move_pleco(ai_context, time_limit, depth, &task_progress),
+            AiEngine::Mcts => {
+                let mut mcts_tree = tree.lock().map(|mut guard| std::mem::take(&mut *guard)).unwrap_or_default();
+                let mut tt = shared_tt.take(tt_size_mb, tt_replacement);
+                let best_move = find_best_move_mcts(ai_context, &mut mcts_tree, &mut tt, time_limit, &pst, &keys, &task_progress);
+                if let Ok(mut guard) = tree.lock() {
+                    *guard = mcts_tree;
+                }
+                shared_tt.put_back(tt);
+                best_move
+            }
+            AiEngine::AlphaBeta => {
+                let mut tt = shared_tt.take(tt_size_mb, tt_replacement);
+                let best_move = find_best_move_alphabeta(ai_context, &mut tt, time_limit, &pst, &keys, &task_progress);
+                shared_tt.put_back(tt);
+                best_move
+            }
+        }))
+        .map_err(|payload| panic_message(&*payload));
         
         let elapsed = start_time.elapsed();
         debug!("AI finished calculation in {:?}", elapsed);
@@ -156,47 +418,76 @@ fn request_ai_move(
         result
     });
 
-    commands.spawn(AiThinking(task));
+    commands.spawn(AiThinking {
+        task,
+        progress,
+        started_at: start_time,
+    });
     println!("AI calculation task spawned.");
 }
 
 /// System to check the AiThinking task result
+#[allow(clippy::too_many_arguments)]
 fn check_ai_move_result(
     mut commands: Commands,
     mut task_q: Query<(Entity, &mut AiThinking)>,
     mut ev_make_move: EventWriter<MakeMoveEvent>,
     mut next_state: ResMut<NextState<TurnState>>,
-    game_state: Res<GameState>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    game_rng: Res<GameRng>,
+    config: Res<GameConfig>,
+    legal_moves: Res<LegalMovesCache>,
+    mut ev_fallback: EventWriter<AiFallbackEvent>,
+    mut stats_log: ResMut<SearchStatsLog>,
 ) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    // The move is checked against the filtered moves, so wait for them
+    if legal_moves.computing_for().is_some() {
+        return;
+    }
+    let policy = config.ai_settings.fallback_policy;
+    let mut rng = seeded_rng(Some(game_rng.ai_seed()), game_state.zobrist_hash);
     for (entity, mut ai_task) in task_q.iter_mut() {
-        if let Some(result_move) = future::block_on(future::poll_once(&mut ai_task.0)) {
+        if let Some(search_result) = future::block_on(future::poll_once(&mut ai_task.task)) {
             println!("AI calculation task finished.");
-            if let Some(ai_move) = result_move {
-                let is_valid = validate_ai_move(&game_state, &ai_move);
-                if is_valid {
+            if let Some(stats) = ai_task.progress.stats() {
+                if stats_log.print_every_search {
+                    print!("{}", stats.report());
+                }
+                stats_log.last = Some(stats);
+            }
+            let reason = match search_result {
+                Err(message) => {
+                    eprintln!("AI search panicked: {}", message);
+                    let report = AiCrashReport::new(game_state, game_rng.ai_seed(), message.clone());
+                    if let Err(e) = report.append_to(CRASH_REPORT_PATH) {
+                        eprintln!("Failed to write {}: {}", CRASH_REPORT_PATH, e);
+                    }
+                    Some(AiFallbackReason::Crashed(message))
+                }
+                Ok(Some(ai_move)) if validate_ai_move(legal_moves.moves(), &ai_move) => {
                     println!("AI requests move: {:?}", ai_move);
                     ev_make_move.send(MakeMoveEvent(ai_move));
-                } else {
-                    eprintln!("AI requested invalid move: {:?}, ignoring it", ai_move);
-                    if let Some(fallback_move) = get_fallback_move(&game_state) {
-                        println!("Using fallback move instead: {:?}", fallback_move);
-                        ev_make_move.send(MakeMoveEvent(fallback_move));
-                    } else {
-                        eprintln!("No valid moves available. Game might be in a terminal state.");
-                        next_state.set(TurnState::PlayerTurn);
-                    }
+                    None
                 }
-            } else {
-                eprintln!("AI task finished but returned no move. Game state might be terminal.");
-                let legal_moves = game_state.board.legal_moves();
-                if legal_moves.is_empty() {
-                    eprintln!("No legal moves available. Game over detected. Restarting the game.");
+                Ok(Some(ai_move)) => Some(AiFallbackReason::InvalidMove(ai_move)),
+                Ok(None) if legal_moves.moves().is_empty() => {
+                    eprintln!("AI task finished without a move and none are left. Game over detected.");
                     next_state.set(TurnState::GameOver);
                     return;
-                } else if let Some(fallback_move) = get_fallback_move(&game_state) {
-                    println!("Using fallback random move as AI couldn't decide: {:?}", fallback_move);
-                    ev_make_move.send(MakeMoveEvent(fallback_move));
                 }
+                Ok(None) => Some(AiFallbackReason::NoMove),
+            };
+
+            if let Some(reason) = reason {
+                let played = fallback_move(policy, &game_state.board, legal_moves.moves(), &mut rng);
+                match &played {
+                    Some(fallback) => ev_make_move.send(MakeMoveEvent(fallback.clone())),
+                    None => next_state.set(TurnState::PlayerTurn),
+                }
+                ev_fallback.send(AiFallbackEvent { reason, policy, played });
             }
             commands.entity(entity).despawn();
             println!("Despawned AI task entity.");
@@ -205,24 +496,23 @@ fn check_ai_move_result(
     }
 }
 
-/// Validate that an AI move is valid for the current game state
-fn validate_ai_move(game_state: &GameState, proposed_move: &Move) -> bool {
-    let legal_moves = game_state.board.legal_moves();
-    if !legal_moves.contains(proposed_move) {
-         eprintln!("Invalid move from AI: {:?}. Legal moves: {:?}", proposed_move, legal_moves);
-         return false;
+/// System to abort any running AI search (e.g. when the game is paused).
+/// Dropping the task cancels it; `request_ai_move` starts a fresh one on resume.
+pub fn cancel_ai_thinking(
+    mut commands: Commands,
+    task_q: Query<Entity, With<AiThinking>>,
+) {
+    for entity in task_q.iter() {
+        commands.entity(entity).despawn();
+        println!("Cancelled AI calculation task.");
     }
-    true
 }
 
-/// Select a random fallback move from legal moves
-fn get_fallback_move(game_state: &GameState) -> Option<Move> {
-    let legal_moves = game_state.board.legal_moves();
-    if legal_moves.is_empty() {
-        return None;
+/// Validate that an AI move is one of the moves the drawback allows
+fn validate_ai_move(allowed_moves: &[Move], proposed_move: &Move) -> bool {
+    if !allowed_moves.contains(proposed_move) {
+         eprintln!("Invalid move from AI: {:?}. Allowed moves: {:?}", proposed_move, allowed_moves);
+         return false;
     }
-    
-    let mut rng = rand::thread_rng();
-    let random_idx = rng.gen_range(0..legal_moves.len());
-    Some(legal_moves[random_idx].clone())
+    true
 } 
\ No newline at end of file
@@ -0,0 +1,266 @@
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
+use shakmaty::{ByColor, Chess, Color as ChessColor, Move, Position};
+use crate::drawbacks::{DrawbackRegistry, DrawbackId, definition::TurnContext};
+use crate::game_logic::events::{GameOverEvent, GameOverReason};
+use crate::game_logic::captures::material_balance;
+use crate::game_logic::history::MoveHistory;
+use crate::game_logic::legal_moves::DrawbackTelemetry;
+use crate::game_logic::state::{GameState, ActiveBoard};
+use super::analysis::ANALYSIS_DEPTH;
+use super::pleco_ai::evaluate_position_pleco;
+use super::plugin::count_capture;
+
+// Number of mistakes listed in the review
+const MISTAKES_SHOWN: usize = 3;
+// Win chance (in percent) a move has to give away to count as a mistake
+const MISTAKE_THRESHOLD: f32 = 10.0;
+
+/// Analysis of a single move of a finished game
+#[derive(Debug, Clone)]
+pub struct MoveReview {
+    pub ply: usize, // Index of the move in `MoveHistory` (the position before it is `position_at(ply)`)
+    pub move_number: u32,
+    pub color: ChessColor,
+    pub san: String,
+    pub eval_before_cp: Option<i32>, // Centipawns from White's point of view
+    pub eval_after_cp: Option<i32>,
+    pub legal_moves: usize,   // Moves the position allowed under normal chess rules
+    pub allowed_moves: usize, // ... and the ones left once the mover's drawback was applied
+    pub material: i32,        // White's material minus Black's after the move, in pawns
+    pub time_ms: Option<u64>, // Time the mover spent on it, if it was recorded
+}
+
+impl MoveReview {
+    /// Win chance (in percent) the move gave away, from the mover's point of view
+    pub fn win_chance_lost(&self) -> f32 {
+        let (Some(before), Some(after)) = (self.eval_before_cp, self.eval_after_cp) else {
+            return 0.0; // Unscorable (e.g. the king was captured), nothing to blame
+        };
+        let sign = if self.color == ChessColor::White { 1 } else { -1 };
+        (win_chance(before * sign) - win_chance(after * sign)).max(0.0)
+    }
+
+    /// Accuracy of the move from 0 to 100, using the same curve as Lichess
+    pub fn accuracy(&self) -> f32 {
+        (103.1668 * (-0.04354 * self.win_chance_lost()).exp() - 3.1669).clamp(0.0, 100.0)
+    }
+
+    pub fn removed_by_drawback(&self) -> usize {
+        self.legal_moves.saturating_sub(self.allowed_moves)
+    }
+}
+
+/// Review of a finished game, built from its move history
+#[derive(Debug, Clone)]
+pub struct GameReview {
+    pub reason: Option<GameOverReason>, // None for games reviewed without knowing how they ended
+    pub start_material: i32,
+    pub moves: Vec<MoveReview>,
+}
+
+impl GameReview {
+    fn moves_of(&self, color: ChessColor) -> impl Iterator<Item = &MoveReview> {
+        self.moves.iter().filter(move |review| review.color == color)
+    }
+
+    /// Average accuracy of a player's moves, None if they didn't move
+    pub fn accuracy(&self, color: ChessColor) -> Option<f32> {
+        average(self.moves_of(color).map(MoveReview::accuracy))
+    }
+
+    /// How many of a player's candidate moves their drawback removed per turn on average
+    pub fn drawback_impact(&self, color: ChessColor) -> Option<f32> {
+        average(self.moves_of(color).map(|review| review.removed_by_drawback() as f32))
+    }
+
+    /// The worst moves of the game, worst first
+    pub fn biggest_mistakes(&self) -> Vec<&MoveReview> {
+        let mut mistakes: Vec<&MoveReview> = self
+            .moves
+            .iter()
+            .filter(|review| review.win_chance_lost() >= MISTAKE_THRESHOLD)
+            .collect();
+        mistakes.sort_by(|a, b| b.win_chance_lost().total_cmp(&a.win_chance_lost()));
+        mistakes.truncate(MISTAKES_SHOWN);
+        mistakes
+    }
+
+    /// How many of a player's moves gave away enough win chance to count as a mistake
+    pub fn mistake_count(&self, color: ChessColor) -> usize {
+        self.moves_of(color).filter(|review| review.win_chance_lost() >= MISTAKE_THRESHOLD).count()
+    }
+
+    /// Material balance before the first move and after every move
+    pub fn material_graph(&self) -> Vec<i32> {
+        std::iter::once(self.start_material).chain(self.moves.iter().map(|review| review.material)).collect()
+    }
+
+    /// Engine evaluation before the first move and after every move, as White's
+    /// winning chances from -1 (Black wins) to 1; None where it couldn't be scored
+    pub fn eval_graph(&self) -> Vec<Option<f32>> {
+        let start = self.moves.first().and_then(|review| review.eval_before_cp);
+        std::iter::once(start)
+            .chain(self.moves.iter().map(|review| review.eval_after_cp))
+            .map(|eval| eval.map(|cp| win_chance(cp) / 50.0 - 1.0))
+            .collect()
+    }
+
+    /// Time spent on every move as a share of the longest one (0 to 1),
+    /// None where no time was recorded
+    pub fn time_graph(&self) -> Vec<Option<f32>> {
+        let longest = self.moves.iter().filter_map(|review| review.time_ms).max().unwrap_or(0).max(1);
+        self.moves.iter().map(|review| review.time_ms.map(|ms| ms as f32 / longest as f32)).collect()
+    }
+}
+
+/// Resource with the review of the last finished game.
+/// The engine evaluations are computed on a background task after game over.
+#[derive(Resource, Default)]
+pub struct GameReviewState {
+    pub review: Option<GameReview>, // Set once the analysis is complete
+    pub task: Option<Task<GameReview>>,
+}
+
+impl GameReviewState {
+    pub fn is_running(&self) -> bool {
+        self.task.is_some()
+    }
+}
+
+// Win chance in percent for a centipawn score, from the Lichess accuracy model
+fn win_chance(cp: i32) -> f32 {
+    50.0 + 50.0 * (2.0 / (1.0 + (-0.00368208 * cp as f32).exp()) - 1.0)
+}
+
+fn average(values: impl Iterator<Item = f32>) -> Option<f32> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+    (count > 0).then(|| sum / count as f32)
+}
+
+// Moves of the side to move with and without its drawback
+fn candidate_counts(position: &Chess, turn: &TurnContext, drawback: DrawbackId, registry: &DrawbackRegistry) -> (usize, usize) {
+    let moves: Vec<Move> = position.legal_moves().into_iter().collect();
+    let legal = moves.len();
+    let allowed = match registry.rules.get(&drawback) {
+        // The per-turn RNG outcome isn't recorded, so random rules are replayed without it
+        Some(rule) => rule.filter_moves(position, moves, turn).len(),
+        None => legal,
+    };
+    (legal, allowed)
+}
+
+/// Builds the review without the engine evaluations (those are filled in by `evaluate_review`).
+/// The drawback impact comes from the counts recorded while playing when they cover the
+/// whole game, and is recomputed from the history otherwise (e.g. for imported games).
+pub fn build_review(
+    history: &MoveHistory,
+    telemetry: &DrawbackTelemetry,
+    white_drawback: DrawbackId,
+    black_drawback: DrawbackId,
+    registry: &DrawbackRegistry,
+    reason: Option<GameOverReason>,
+) -> GameReview {
+    let mut position = history.start_position.clone();
+    let mut moves = Vec::with_capacity(history.len());
+    let recorded = (telemetry.turns.len() == history.len()).then_some(&telemetry.turns);
+    let mut moves_since_capture = ByColor::<u32>::default();
+
+    for (ply, record) in history.moves.iter().enumerate() {
+        let color = position.turn();
+        let move_number = position.fullmoves().get();
+        let drawback = if color == ChessColor::White { white_drawback } else { black_drawback };
+        let (legal_moves, allowed_moves) = match recorded {
+            Some(turns) => (turns[ply].legal_moves, turns[ply].allowed_moves),
+            None => {
+                let turn = TurnContext {
+                    rng_outcome: None,
+                    last_move: ply.checked_sub(1).map(|previous| &history.moves[previous].chess_move),
+                    moves_since_capture: *moves_since_capture.get(color),
+                };
+                candidate_counts(&position, &turn, drawback, registry)
+            }
+        };
+        moves_since_capture = count_capture(moves_since_capture, color, &record.chess_move);
+        position.play_unchecked(&record.chess_move);
+        moves.push(MoveReview {
+            ply,
+            move_number,
+            color,
+            san: record.san.clone(),
+            eval_before_cp: None,
+            eval_after_cp: None,
+            legal_moves,
+            allowed_moves,
+            material: material_balance(&position),
+            time_ms: record.time_ms,
+        });
+    }
+
+    GameReview {
+        reason,
+        start_material: material_balance(&history.start_position),
+        moves,
+    }
+}
+
+/// Fills in the engine evaluation before and after every move
+pub fn evaluate_review(mut review: GameReview, history: &MoveHistory) -> GameReview {
+    let mut position = history.start_position.clone();
+    let mut eval = evaluate_position_pleco(&position, ANALYSIS_DEPTH);
+
+    for (review_move, record) in review.moves.iter_mut().zip(&history.moves) {
+        position.play_unchecked(&record.chess_move);
+        review_move.eval_before_cp = eval;
+        eval = evaluate_position_pleco(&position, ANALYSIS_DEPTH);
+        review_move.eval_after_cp = eval;
+    }
+    review
+}
+
+/// System to start reviewing the game as soon as it is over
+pub fn start_game_review(
+    mut ev_game_over: EventReader<GameOverEvent>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    history: Res<MoveHistory>,
+    telemetry: Res<DrawbackTelemetry>,
+    registry: Res<DrawbackRegistry>,
+    mut review_state: ResMut<GameReviewState>,
+) {
+    let Some(ev) = ev_game_over.read().last() else {
+        return;
+    };
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+
+    let review = build_review(&history, &telemetry, game_state.white_drawback, game_state.black_drawback, &registry, Some(ev.0.reason));
+    let history = history.clone();
+    println!("Reviewing the game ({} moves)", history.len());
+
+    // Replacing the task drops any review still running for an earlier game
+    review_state.review = None;
+    review_state.task = Some(AsyncComputeTaskPool::get().spawn(async move {
+        evaluate_review(review, &history)
+    }));
+}
+
+/// System to collect the finished review
+pub fn poll_game_review(mut review_state: ResMut<GameReviewState>) {
+    // Polling alone shouldn't count as a change, the review screen rebuilds on changes
+    let Some(task) = review_state.bypass_change_detection().task.as_mut() else {
+        return;
+    };
+
+    if let Some(review) = future::block_on(future::poll_once(task)) {
+        println!("Game review ready");
+        review_state.review = Some(review);
+        review_state.task = None;
+    }
+}
+
+/// System to forget the review of the previous game when a new one starts
+pub fn clear_game_review(mut review_state: ResMut<GameReviewState>) {
+    *review_state = GameReviewState::default();
+}
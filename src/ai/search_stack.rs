@@ -0,0 +1,166 @@
+use shakmaty::{ByColor, Chess, Color, Move, Piece, Position, Role, Square};
+use super::evaluation::{phase_from_weight, phase_weight, piece_square_terms, PieceSquareTables};
+use super::plugin::count_capture;
+use super::zobrist::{ZobristKeys, calculate_board_hash, castling_and_en_passant_hash, piece_key};
+
+/// State of one ply of the search: the position plus accumulators that are
+/// updated from the move instead of being recomputed from the whole board
+#[derive(Debug, Clone)]
+pub struct SearchFrame {
+    pub position: Chess,
+    pub hash: u64,    // Same as `calculate_board_hash(&position)`
+    mg: i32,          // Material + PST, midgame, White minus Black
+    eg: i32,          // Material + PST, endgame, White minus Black
+    phase_weight: i32,
+    moves_since_capture: ByColor<u32>, // Each side's moves without a capture
+}
+
+/// Copy-make search stack. The frames are allocated once and reused, so going
+/// one ply deeper copies the position into the next frame and applies the move
+/// there; going back up just drops to the previous frame.
+pub struct SearchStack<'a> {
+    frames: Vec<SearchFrame>,
+    ply: usize,
+    pst: &'a PieceSquareTables,
+    keys: &'a ZobristKeys,
+}
+
+impl<'a> SearchStack<'a> {
+    pub fn new(root: &Chess, moves_since_capture: ByColor<u32>, pst: &'a PieceSquareTables, keys: &'a ZobristKeys) -> Self {
+        let mut root_frame = SearchFrame {
+            position: root.clone(),
+            hash: calculate_board_hash(root, keys),
+            mg: 0,
+            eg: 0,
+            phase_weight: 0,
+            moves_since_capture,
+        };
+        for square in root.board().occupied() {
+            if let Some(piece) = root.board().piece_at(square) {
+                add_piece(&mut root_frame, pst, piece, square);
+            }
+        }
+
+        Self {
+            frames: vec![root_frame],
+            ply: 0,
+            pst,
+            keys,
+        }
+    }
+
+    /// Number of moves made since the root
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    pub fn current(&self) -> &SearchFrame {
+        &self.frames[self.ply]
+    }
+
+    pub fn position(&self) -> &Chess {
+        &self.current().position
+    }
+
+    pub fn hash(&self) -> u64 {
+        self.current().hash
+    }
+
+    /// Moves `color` made without a capture up to the current position
+    pub fn moves_since_capture(&self, color: Color) -> u32 {
+        *self.current().moves_since_capture.get(color)
+    }
+
+    /// Plays `m` one ply deeper. The move must be legal (or pseudo-legal) in the current position.
+    pub fn make(&mut self, m: &Move) {
+        if self.ply + 1 == self.frames.len() {
+            let copy = self.frames[self.ply].clone();
+            self.frames.push(copy);
+        } else {
+            let (played, next) = self.frames.split_at_mut(self.ply + 1);
+            next[0].clone_from(&played[self.ply]);
+        }
+        self.ply += 1;
+
+        let pst = self.pst;
+        let keys = self.keys;
+        let frame = &mut self.frames[self.ply];
+        let us = frame.position.turn();
+
+        // Castling rights and en passant can change with any move, so swap that part of the hash
+        frame.hash ^= castling_and_en_passant_hash(&frame.position, keys);
+
+        match *m {
+            Move::Normal { role, from, capture, to, promotion } => {
+                remove_piece(frame, pst, keys, role.of(us), from);
+                if let Some(captured) = capture {
+                    remove_piece(frame, pst, keys, captured.of(!us), to);
+                }
+                place_piece(frame, pst, keys, promotion.unwrap_or(role).of(us), to);
+            }
+            Move::EnPassant { from, to } => {
+                remove_piece(frame, pst, keys, Role::Pawn.of(us), from);
+                remove_piece(frame, pst, keys, Role::Pawn.of(!us), Square::from_coords(to.file(), from.rank()));
+                place_piece(frame, pst, keys, Role::Pawn.of(us), to);
+            }
+            Move::Castle { king, rook } => {
+                let side = m.castling_side().expect("castling move has a side");
+                remove_piece(frame, pst, keys, Role::King.of(us), king);
+                remove_piece(frame, pst, keys, Role::Rook.of(us), rook);
+                place_piece(frame, pst, keys, Role::King.of(us), side.king_to(us));
+                place_piece(frame, pst, keys, Role::Rook.of(us), side.rook_to(us));
+            }
+            Move::Put { role, to } => {
+                place_piece(frame, pst, keys, role.of(us), to);
+            }
+        }
+
+        frame.moves_since_capture = count_capture(frame.moves_since_capture, us, m);
+        frame.position.play_unchecked(m);
+        frame.hash ^= keys.turn ^ castling_and_en_passant_hash(&frame.position, keys);
+    }
+
+    /// Takes back the last move made with `make`
+    pub fn unmake(&mut self) {
+        debug_assert!(self.ply > 0, "unmake at the search root");
+        self.ply -= 1;
+    }
+
+    /// Material and piece-square score of the current position from the side
+    /// to move's point of view (the incremental version of `evaluate_material_and_pst`,
+    /// equal up to rounding)
+    pub fn evaluate(&self) -> i32 {
+        let frame = self.current();
+        let endgame_phase = phase_from_weight(frame.phase_weight);
+        let score = (frame.mg as f64 * (1.0 - endgame_phase) + frame.eg as f64 * endgame_phase) as i32;
+        match frame.position.turn() {
+            Color::White => score,
+            Color::Black => -score,
+        }
+    }
+}
+
+// Helper function to count a piece in the accumulators
+fn add_piece(frame: &mut SearchFrame, pst: &PieceSquareTables, piece: Piece, square: Square) {
+    let (mg, eg) = piece_square_terms(pst, piece, square);
+    let sign = if piece.color == Color::White { 1 } else { -1 };
+    frame.mg += sign * mg;
+    frame.eg += sign * eg;
+    frame.phase_weight += phase_weight(piece.role);
+}
+
+// Helper function to put a piece on a square (hash and accumulators only)
+fn place_piece(frame: &mut SearchFrame, pst: &PieceSquareTables, keys: &ZobristKeys, piece: Piece, square: Square) {
+    frame.hash ^= piece_key(keys, piece, square);
+    add_piece(frame, pst, piece, square);
+}
+
+// Helper function to take a piece off a square (hash and accumulators only)
+fn remove_piece(frame: &mut SearchFrame, pst: &PieceSquareTables, keys: &ZobristKeys, piece: Piece, square: Square) {
+    frame.hash ^= piece_key(keys, piece, square);
+    let (mg, eg) = piece_square_terms(pst, piece, square);
+    let sign = if piece.color == Color::White { 1 } else { -1 };
+    frame.mg -= sign * mg;
+    frame.eg -= sign * eg;
+    frame.phase_weight -= phase_weight(piece.role);
+}
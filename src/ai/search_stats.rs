@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+use std::fmt::Write;
+
+/// Command line flag that prints the search statistics after every AI move
+pub const SEARCH_STATS_FLAG: &str = "--search-stats";
+
+/// Counters collected by a search, for tuning move ordering, the transposition
+/// table and the quiescence search. Searches fill in the parts they support.
+#[derive(Debug, Clone, Default)]
+pub struct SearchStats {
+    pub nodes_by_depth: Vec<u64>, // Main search nodes per ply from the root (root = 0)
+    pub qsearch_nodes: u64,
+    pub tt_probes: u64,
+    pub tt_hits: u64,
+    pub cutoffs: u64,            // Beta cutoffs
+    pub first_move_cutoffs: u64, // Beta cutoffs caused by the first move searched
+    pub tt_memory_bytes: usize,  // Memory of the transposition table (0 = none was used)
+    pub tt_entries: usize,       // Slots of the transposition table
+    pub tt_used: usize,          // Slots holding an entry when the search ended
+}
+
+impl SearchStats {
+    pub fn record_node(&mut self, ply: usize) {
+        if self.nodes_by_depth.len() <= ply {
+            self.nodes_by_depth.resize(ply + 1, 0);
+        }
+        self.nodes_by_depth[ply] += 1;
+    }
+
+    pub fn record_qsearch_node(&mut self) {
+        self.qsearch_nodes += 1;
+    }
+
+    pub fn record_tt_probe(&mut self, hit: bool) {
+        self.tt_probes += 1;
+        if hit {
+            self.tt_hits += 1;
+        }
+    }
+
+    /// Records the size and fill of the transposition table the search used
+    pub fn record_tt_usage(&mut self, memory_bytes: usize, entries: usize, used: usize) {
+        self.tt_memory_bytes = memory_bytes;
+        self.tt_entries = entries;
+        self.tt_used = used;
+    }
+
+    /// Records a beta cutoff by the move at `move_index` in the ordered move list
+    pub fn record_cutoff(&mut self, move_index: usize) {
+        self.cutoffs += 1;
+        if move_index == 0 {
+            self.first_move_cutoffs += 1;
+        }
+    }
+
+    pub fn main_nodes(&self) -> u64 {
+        self.nodes_by_depth.iter().sum()
+    }
+
+    pub fn total_nodes(&self) -> u64 {
+        self.main_nodes() + self.qsearch_nodes
+    }
+
+    pub fn tt_hit_rate(&self) -> Option<f64> {
+        percentage(self.tt_hits, self.tt_probes)
+    }
+
+    pub fn tt_fill_rate(&self) -> Option<f64> {
+        percentage(self.tt_used as u64, self.tt_entries as u64)
+    }
+
+    pub fn qsearch_share(&self) -> Option<f64> {
+        percentage(self.qsearch_nodes, self.total_nodes())
+    }
+
+    pub fn first_move_cutoff_rate(&self) -> Option<f64> {
+        percentage(self.first_move_cutoffs, self.cutoffs)
+    }
+
+    /// Effective branching factor going from each ply to the next
+    pub fn branching_factors(&self) -> Vec<f64> {
+        self.nodes_by_depth
+            .windows(2)
+            .map(|pair| if pair[0] == 0 { 0.0 } else { pair[1] as f64 / pair[0] as f64 })
+            .collect()
+    }
+
+    /// Multi-line summary for the console
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+        let rate = |value: Option<f64>| value.map_or("n/a".to_string(), |value| format!("{:.1}%", value));
+
+        let _ = writeln!(report, "Search statistics: {} nodes ({} main, {} quiescence)",
+            self.total_nodes(), self.main_nodes(), self.qsearch_nodes);
+        let _ = writeln!(report, "  TT hit rate:            {} ({}/{} probes)", rate(self.tt_hit_rate()), self.tt_hits, self.tt_probes);
+        let _ = writeln!(report, "  TT memory:              {:.1} MB, {} of {} entries used ({})",
+            self.tt_memory_bytes as f64 / (1024.0 * 1024.0), self.tt_used, self.tt_entries, rate(self.tt_fill_rate()));
+        let _ = writeln!(report, "  Q-search node share:    {}", rate(self.qsearch_share()));
+        let _ = writeln!(report, "  First-move cutoffs:     {} ({}/{} cutoffs)",
+            rate(self.first_move_cutoff_rate()), self.first_move_cutoffs, self.cutoffs);
+        for (ply, nodes) in self.nodes_by_depth.iter().enumerate() {
+            let branching = match ply.checked_sub(1).and_then(|previous| self.branching_factors().get(previous).copied()) {
+                Some(factor) => format!("  branching {:.2}", factor),
+                None => String::new(),
+            };
+            let _ = writeln!(report, "  ply {:>2}: {:>10} nodes{}", ply, nodes, branching);
+        }
+        report
+    }
+}
+
+// Helper function for `part` as a percentage of `total`
+fn percentage(part: u64, total: u64) -> Option<f64> {
+    (total > 0).then(|| part as f64 * 100.0 / total as f64)
+}
+
+/// Statistics of the last finished AI search, and whether to print every search
+#[derive(Resource, Debug, Default)]
+pub struct SearchStatsLog {
+    pub last: Option<SearchStats>,
+    pub print_every_search: bool,
+}
+
+impl SearchStatsLog {
+    pub fn from_args() -> Self {
+        Self {
+            last: None,
+            print_every_search: std::env::args().any(|arg| arg == SEARCH_STATS_FLAG),
+        }
+    }
+}
+
+/// Developer keys: F9 prints the statistics of the last search, F10 toggles
+/// printing them after every AI move
+pub fn handle_search_stats_keys(keys: Res<Input<KeyCode>>, mut log: ResMut<SearchStatsLog>) {
+    if keys.just_pressed(KeyCode::F9) {
+        match &log.last {
+            Some(stats) => print!("{}", stats.report()),
+            None => println!("No AI search has finished yet"),
+        }
+    }
+
+    if keys.just_pressed(KeyCode::F10) {
+        log.print_every_search = !log.print_every_search;
+        println!("Printing search statistics after every AI move: {}", if log.print_every_search { "on" } else { "off" });
+    }
+}
@@ -0,0 +1,382 @@
+use std::error::Error;
+use std::time::Duration;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use shakmaty::{Color as ChessColor, Move, Position};
+use crate::config::{AiEngine, AiSettings, GameConfig};
+use crate::drawbacks::DrawbackRegistry;
+use crate::game_logic::repetition::RepetitionTable;
+use crate::game_logic::state::GameState;
+use super::alphabeta::find_best_move_alphabeta;
+use super::components::SearchProgress;
+use super::evaluation::{PieceSquareTables, PST_FILE_PATH};
+use super::fallback::fallback_move;
+use super::mcts::{MctsTree, find_best_move_mcts};
+use super::pleco_ai::find_best_move_pleco;
+use super::plugin::AiGameStateContext;
+use super::transposition::TranspositionTable;
+use super::zobrist::{ZobristKeys, calculate_board_hash, initialize_zobrist_keys};
+
+/// Command line flag that plays two AI configurations against each other
+/// until a sequential probability ratio test decides which is stronger:
+/// `--sprt <a.json> <b.json> [max games] [elo0] [elo1]`. The files hold
+/// `ai_settings` as in the config file.
+pub const SPRT_FLAG: &str = "--sprt";
+
+// Games played at most when no limit is given
+const DEFAULT_MAX_GAMES: u32 = 1000;
+
+// Random moves played from the start before the engines take over, so the
+// game pairs don't all repeat the same game
+const OPENING_PLIES: usize = 4;
+
+// Games still going after this many plies are scored as draws
+const MAX_GAME_PLIES: usize = 300;
+
+/// Hypotheses of the test: H0 is that A is `elo0` stronger than B, H1 that it
+/// is `elo1` stronger. `alpha` and `beta` are the chances of accepting H1
+/// when H0 is true and H0 when H1 is true.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SprtBounds {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Default for SprtBounds {
+    fn default() -> Self {
+        Self { elo0: 0.0, elo1: 10.0, alpha: 0.05, beta: 0.05 }
+    }
+}
+
+impl SprtBounds {
+    /// Log-likelihood ratio at which H0 is accepted
+    pub fn lower(&self) -> f64 {
+        (self.beta / (1.0 - self.alpha)).ln()
+    }
+
+    /// Log-likelihood ratio at which H1 is accepted
+    pub fn upper(&self) -> f64 {
+        ((1.0 - self.beta) / self.alpha).ln()
+    }
+
+    pub fn verdict(&self, llr: f64) -> SprtVerdict {
+        if llr >= self.upper() {
+            SprtVerdict::AcceptH1
+        } else if llr <= self.lower() {
+            SprtVerdict::AcceptH0
+        } else {
+            SprtVerdict::Continue
+        }
+    }
+}
+
+/// Where the test stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SprtVerdict {
+    AcceptH1, // A is stronger by `elo1` or more
+    AcceptH0, // A is not stronger by more than `elo0`
+    Continue, // Not decided yet
+}
+
+/// Results of the match from A's point of view
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MatchScore {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl MatchScore {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Counts a game A played as `a_color` and `winner` won (None = draw)
+    pub fn record(&mut self, a_color: ChessColor, winner: Option<ChessColor>) {
+        match winner {
+            Some(winner) if winner == a_color => self.wins += 1,
+            Some(_) => self.losses += 1,
+            None => self.draws += 1,
+        }
+    }
+
+    /// Points per game (a draw is half a point), 0.5 before the first game
+    pub fn score(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.5;
+        }
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games() as f64
+    }
+
+    // Variance of the points of one game
+    fn variance(&self) -> f64 {
+        let games = self.games().max(1) as f64;
+        let score = self.score();
+        (self.wins as f64 * (1.0 - score).powi(2) + self.draws as f64 * (0.5 - score).powi(2) + self.losses as f64 * score.powi(2)) / games
+    }
+
+    /// Elo difference of A over B the score points to
+    pub fn elo(&self) -> f64 {
+        elo_from_score(self.score())
+    }
+
+    /// Half the width of the 95% confidence interval of `elo`
+    pub fn elo_error(&self) -> f64 {
+        let margin = 1.96 * (self.variance() / self.games().max(1) as f64).sqrt();
+        (elo_from_score(self.score() + margin) - elo_from_score(self.score() - margin)) / 2.0
+    }
+
+    /// Log-likelihood ratio of H1 over H0 (the usual normal approximation of
+    /// the trinomial model), 0 while the games don't vary
+    pub fn llr(&self, bounds: &SprtBounds) -> f64 {
+        let variance = self.variance();
+        if variance <= 0.0 {
+            return 0.0;
+        }
+        let (score0, score1) = (score_from_elo(bounds.elo0), score_from_elo(bounds.elo1));
+        self.games() as f64 * (score1 - score0) * (2.0 * self.score() - score0 - score1) / (2.0 * variance)
+    }
+
+    /// Summary of the match and the test
+    pub fn report(&self, bounds: &SprtBounds) -> String {
+        let llr = self.llr(bounds);
+        let verdict = match bounds.verdict(llr) {
+            SprtVerdict::AcceptH1 => format!("H1 accepted: A is at least {} Elo stronger", bounds.elo1),
+            SprtVerdict::AcceptH0 => format!("H0 accepted: A is at most {} Elo stronger", bounds.elo0),
+            SprtVerdict::Continue => "Inconclusive: the game limit was reached first".to_string(),
+        };
+        format!(
+            "Games: {} (+{} ={} -{}), score {:.1}%\nElo: {:.1} +/- {:.1} (95%)\nLLR: {:.2} ({:.2}, {:.2}) [{}, {}]\n{}\n",
+            self.games(), self.wins, self.draws, self.losses, self.score() * 100.0,
+            self.elo(), self.elo_error(),
+            llr, bounds.lower(), bounds.upper(), bounds.elo0, bounds.elo1,
+            verdict
+        )
+    }
+}
+
+// Helper function for the Elo difference behind an expected score
+fn elo_from_score(score: f64) -> f64 {
+    let score = score.clamp(0.001, 0.999);
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+// Helper function for the expected score of an Elo difference
+fn score_from_elo(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+/// Runs the test with the arguments after `SPRT_FLAG` and prints the report;
+/// returns whether the match could be played
+pub fn run_sprt_test(args: &[String]) -> bool {
+    let (Some(path_a), Some(path_b)) = (args.first(), args.get(1)) else {
+        eprintln!("Usage: {} <a.json> <b.json> [max games] [elo0] [elo1]", SPRT_FLAG);
+        return false;
+    };
+    let max_games = args.get(2).and_then(|games| games.parse().ok()).unwrap_or(DEFAULT_MAX_GAMES);
+    let defaults = SprtBounds::default();
+    let bounds = SprtBounds {
+        elo0: args.get(3).and_then(|elo| elo.parse().ok()).unwrap_or(defaults.elo0),
+        elo1: args.get(4).and_then(|elo| elo.parse().ok()).unwrap_or(defaults.elo1),
+        ..defaults
+    };
+    let (a, b) = match (read_settings(path_a), read_settings(path_b)) {
+        (Ok(a), Ok(b)) => (a, b),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("SPRT failed: {}", e);
+            return false;
+        }
+    };
+
+    let pst = PieceSquareTables::load(PST_FILE_PATH).unwrap_or_default();
+    println!("SPRT: A = {} ({:?}) vs B = {} ({:?}), at most {} games", path_a, a.engine, path_b, b.engine, max_games);
+    let score = run_sprt(&a, &b, &bounds, max_games, &pst, |score| {
+        println!("  {} games: +{} ={} -{}, LLR {:.2}", score.games(), score.wins, score.draws, score.losses, score.llr(&bounds));
+    });
+    print!("{}", score.report(&bounds));
+    true
+}
+
+// Reads the AI settings of one side from a JSON file
+fn read_settings(path: &str) -> Result<AiSettings, Box<dyn Error>> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    Ok(serde_json::from_str(&text).map_err(|e| format!("{}: {}", path, e))?)
+}
+
+/// Plays game pairs between A and B until the test decides or `max_games`
+/// are played. Both games of a pair start from the same random opening and
+/// drawbacks, with the engines swapping colors. `on_pair` sees the score
+/// after every pair.
+pub fn run_sprt(
+    a: &AiSettings,
+    b: &AiSettings,
+    bounds: &SprtBounds,
+    max_games: u32,
+    pst: &PieceSquareTables,
+    mut on_pair: impl FnMut(&MatchScore),
+) -> MatchScore {
+    let registry = DrawbackRegistry::default();
+    let keys = initialize_zobrist_keys();
+    let mut score = MatchScore::default();
+    let mut pair = 0;
+    while score.games() < max_games {
+        let mut rng = StdRng::seed_from_u64(pair);
+        let start = random_opening(&registry, &keys, &mut rng);
+        for a_color in [ChessColor::White, ChessColor::Black] {
+            let (white, black) = if a_color == ChessColor::White { (a, b) } else { (b, a) };
+            let winner = play_game(white, black, &start, &registry, pst, &keys, pair);
+            score.record(a_color, winner);
+        }
+        on_pair(&score);
+        if bounds.verdict(score.llr(bounds)) != SprtVerdict::Continue {
+            break;
+        }
+        pair += 1;
+    }
+    score
+}
+
+// Helper function for a start position: random drawbacks and a few random moves they allow
+fn random_opening(registry: &DrawbackRegistry, keys: &ZobristKeys, rng: &mut StdRng) -> GameState {
+    let mut game_state = GameState {
+        white_drawback: registry.random_id(rng),
+        black_drawback: registry.random_id(rng),
+        ..GameState::default()
+    };
+    game_state.roll_turn(registry, rng);
+    game_state.zobrist_hash = game_state.position_key(keys);
+    for _ in 0..OPENING_PLIES {
+        let moves = game_state.allowed_moves(registry);
+        let Some(chess_move) = moves.choose(rng) else {
+            break;
+        };
+        play_move(&mut game_state, chess_move, registry, keys, rng);
+    }
+    game_state
+}
+
+// Helper function to make a move on the state, as `apply_move` does in the game:
+// the drawback may play another move, and the next turn is rolled from `rng`
+fn play_move(game_state: &mut GameState, chess_move: &Move, registry: &DrawbackRegistry, keys: &ZobristKeys, rng: &mut StdRng) {
+    let chess_move = game_state
+        .current_drawback_rule(registry)
+        .and_then(|rule| rule.substitute_move(&game_state.board, chess_move, game_state.current_turn_rng_outcome))
+        .unwrap_or_else(|| chess_move.clone());
+    game_state.count_capture(&chess_move);
+    game_state.board.play_unchecked(&chess_move);
+    game_state.last_move = Some(chess_move);
+    game_state.current_player_turn = !game_state.current_player_turn;
+    game_state.roll_turn(registry, rng);
+    game_state.zobrist_hash = game_state.position_key(keys);
+}
+
+/// One side of a game: its settings and the search state it keeps between moves
+struct EnginePlayer {
+    config: GameConfig,
+    tt: TranspositionTable,
+    tree: MctsTree,
+}
+
+impl EnginePlayer {
+    fn new(settings: &AiSettings) -> Self {
+        Self {
+            config: GameConfig { ai_settings: settings.clone(), ..GameConfig::default() },
+            tt: TranspositionTable::new(settings.tt_size_mb, settings.tt_replacement),
+            tree: MctsTree::default(),
+        }
+    }
+
+    // The engine's move for the side to move, with drawbacks known to both sides
+    #[allow(clippy::too_many_arguments)]
+    fn best_move(&mut self, game_state: &GameState, allowed_moves: &[Move], played_positions: &[u64], registry: &DrawbackRegistry, pst: &PieceSquareTables, keys: &ZobristKeys, seed: u64) -> Option<Move> {
+        let settings = &self.config.ai_settings;
+        let mut ctx = AiGameStateContext::from_game_state(game_state, &self.config);
+        ctx.rng_seed = Some(seed);
+        ctx.opponent_belief = vec![(game_state.drawback_rule(!game_state.current_player_turn, registry), 1.0)];
+        ctx.allowed_moves = allowed_moves.to_vec();
+        ctx.player_rule = game_state.current_drawback_rule(registry);
+        ctx.played_positions = played_positions.to_vec();
+
+        let time_limit = Duration::from_millis(settings.time_limit_ms as u64);
+        let progress = SearchProgress::default();
+        match settings.engine {
+            AiEngine::Pleco => find_best_move_pleco(ctx, time_limit, settings.depth_limit as u16, &progress),
+            AiEngine::Mcts => find_best_move_mcts(ctx, &mut self.tree, &mut self.tt, time_limit, pst, keys, &progress),
+            AiEngine::AlphaBeta => find_best_move_alphabeta(ctx, &mut self.tt, time_limit, pst, keys, &progress),
+        }
+    }
+}
+
+/// Plays one game between two AI configurations from `start` and returns the
+/// winner (None for a draw). The game ends as in the app; games that run
+/// past `MAX_GAME_PLIES` or are left without mating material are drawn.
+pub fn play_game(
+    white: &AiSettings,
+    black: &AiSettings,
+    start: &GameState,
+    registry: &DrawbackRegistry,
+    pst: &PieceSquareTables,
+    keys: &ZobristKeys,
+    seed: u64,
+) -> Option<ChessColor> {
+    let mut players = [EnginePlayer::new(white), EnginePlayer::new(black)];
+    let mut game_state = start.clone();
+    let mut repetitions = RepetitionTable::new(game_state.zobrist_hash);
+    let mut played_positions = Vec::new();
+    let mut rng = StdRng::seed_from_u64(seed);
+    for _ in 0..MAX_GAME_PLIES {
+        let to_move = game_state.current_player_turn;
+        let moves = game_state.allowed_moves(registry);
+        if game_state.loses_by_drawback(registry, &moves) {
+            return Some(!to_move);
+        }
+        if moves.is_empty() {
+            // Checkmate, or stalemate
+            return game_state.board.is_check().then_some(!to_move);
+        }
+        if repetitions.is_draw() || game_state.board.is_insufficient_material() {
+            return None;
+        }
+
+        let player = &mut players[if to_move == ChessColor::White { 0 } else { 1 }];
+        let policy = player.config.ai_settings.fallback_policy;
+        let chess_move = player
+            .best_move(&game_state, &moves, &played_positions, registry, pst, keys, seed)
+            .filter(|chess_move| moves.contains(chess_move))
+            .or_else(|| fallback_move(policy, &game_state.board, &moves, &mut rng))?;
+        played_positions.push(calculate_board_hash(&game_state.board, keys));
+        play_move(&mut game_state, &chess_move, registry, keys, &mut rng);
+        repetitions.push(game_state.zobrist_hash);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn llr_follows_the_score_and_decides_at_the_bounds() {
+        let bounds = SprtBounds::default();
+        assert!((bounds.upper() - 2.944).abs() < 0.001);
+        assert!((bounds.lower() + 2.944).abs() < 0.001);
+
+        let even = MatchScore { wins: 40, draws: 20, losses: 40 };
+        assert!(even.elo().abs() < 1e-9);
+        assert!(even.llr(&bounds) < 0.0, "an even match favors H0");
+
+        let ahead = MatchScore { wins: 300, draws: 100, losses: 200 };
+        assert!(ahead.elo() > 50.0);
+        assert_eq!(bounds.verdict(ahead.llr(&bounds)), SprtVerdict::AcceptH1);
+
+        let behind = MatchScore { wins: 200, draws: 100, losses: 300 };
+        assert_eq!(bounds.verdict(behind.llr(&bounds)), SprtVerdict::AcceptH0);
+
+        // Nothing to go on before the first decisive game
+        assert_eq!(MatchScore::default().llr(&bounds), 0.0);
+        assert_eq!(MatchScore { wins: 0, draws: 6, losses: 0 }.llr(&bounds), 0.0);
+    }
+}
@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+use shakmaty::Move;
+use std::sync::{Arc, Mutex};
+use crate::config::TtReplacement;
+
+/// How a stored score relates to the position's true score
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact, // The score itself
+    Lower, // At least the score (the search failed high)
+    Upper, // At most the score (the search failed low)
+}
+
+/// What the table knows about one position
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TtEntry {
+    pub key: u64,               // Full Zobrist hash, to tell apart positions sharing the slot
+    pub depth: u8,              // Remaining depth the score was searched to (0 = static evaluation)
+    pub score: i32,             // Centipawns from the side to move's point of view
+    pub bound: Bound,
+    pub best_move: Option<Move>,
+    generation: u8,             // Search that stored the entry
+}
+
+/// Transposition table: a fixed number of slots indexed by the position's
+/// Zobrist hash (`ZobristKeys`), so a position reached through different move
+/// orders is evaluated once. An empty table (size 0) stores nothing.
+#[derive(Debug, Clone, Default)]
+pub struct TranspositionTable {
+    slots: Vec<Option<TtEntry>>,
+    used: usize, // Slots holding an entry
+    size_mb: u32,
+    replacement: Option<TtReplacement>, // None only for the empty default table
+    generation: u8,
+}
+
+impl TranspositionTable {
+    /// A table taking about `size_mb` megabytes (rounded down to a power of two slots)
+    pub fn new(size_mb: u32, replacement: TtReplacement) -> Self {
+        let bytes = size_mb as usize * 1024 * 1024;
+        let slots = bytes / std::mem::size_of::<Option<TtEntry>>();
+        let slots = if slots == 0 { 0 } else { 1 << slots.ilog2() };
+        Self {
+            slots: vec![None; slots],
+            used: 0,
+            size_mb,
+            replacement: Some(replacement),
+            generation: 0,
+        }
+    }
+
+    /// Whether the table was made with these settings
+    pub fn matches(&self, size_mb: u32, replacement: TtReplacement) -> bool {
+        self.size_mb == size_mb && self.replacement == Some(replacement)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Slots holding an entry
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// Memory the slots take, in bytes
+    pub fn memory_bytes(&self) -> usize {
+        self.slots.capacity() * std::mem::size_of::<Option<TtEntry>>()
+    }
+
+    /// Marks the start of a new search, so entries of earlier ones age
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.used = 0;
+    }
+
+    fn index(&self, key: u64) -> Option<usize> {
+        (!self.slots.is_empty()).then(|| (key & (self.slots.len() as u64 - 1)) as usize)
+    }
+
+    /// The entry for the position with hash `key`, if it is stored
+    pub fn probe(&self, key: u64) -> Option<&TtEntry> {
+        self.slots[self.index(key)?].as_ref().filter(|entry| entry.key == key)
+    }
+
+    /// Stores what a search found out about a position, unless the
+    /// replacement policy keeps the entry already in its slot
+    pub fn store(&mut self, key: u64, depth: u8, score: i32, bound: Bound, best_move: Option<Move>) {
+        let Some(index) = self.index(key) else {
+            return;
+        };
+        let generation = self.generation;
+        let slot = &mut self.slots[index];
+        let replace = match (slot.as_ref(), self.replacement) {
+            (None, _) | (_, Some(TtReplacement::Always) | None) => true,
+            (Some(old), Some(TtReplacement::DepthPreferred)) => {
+                old.key == key || old.generation != generation || depth >= old.depth
+            }
+        };
+        if !replace {
+            return;
+        }
+        // A shallower result for the same position still knows its best move
+        let best_move = best_move.or_else(|| slot.as_ref().filter(|old| old.key == key).and_then(|old| old.best_move.clone()));
+        if slot.is_none() {
+            self.used += 1;
+        }
+        *slot = Some(TtEntry { key, depth, score, bound, best_move, generation });
+    }
+}
+
+/// The AI's transposition table, kept between moves. A search takes the table
+/// out while it runs and puts it back when done.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct SharedTranspositionTable(pub Arc<Mutex<TranspositionTable>>);
+
+impl SharedTranspositionTable {
+    /// Takes the table out for a search, made anew if the settings changed
+    pub fn take(&self, size_mb: u32, replacement: TtReplacement) -> TranspositionTable {
+        let table = self.0.lock().map(|mut guard| std::mem::take(&mut *guard)).unwrap_or_default();
+        if table.matches(size_mb, replacement) {
+            table
+        } else {
+            TranspositionTable::new(size_mb, replacement)
+        }
+    }
+
+    /// Makes the table match changed settings right away instead of at the
+    /// next search (a resized table starts out empty). While a search has the
+    /// table out, the next search resizes it.
+    pub fn resize(&self, size_mb: u32, replacement: TtReplacement) {
+        if let Ok(mut guard) = self.0.lock() {
+            if guard.capacity() > 0 && !guard.matches(size_mb, replacement) {
+                *guard = TranspositionTable::new(size_mb, replacement);
+            }
+        }
+    }
+
+    /// Empties the table, keeping its memory
+    pub fn clear(&self) {
+        if let Ok(mut guard) = self.0.lock() {
+            guard.clear();
+        }
+    }
+
+    /// Puts the table back after a search
+    pub fn put_back(&self, table: TranspositionTable) {
+        if let Ok(mut guard) = self.0.lock() {
+            *guard = table;
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use bevy::prelude::*;
-use shakmaty::{Chess, Square, Color as ChessColor, Role, Position, CastlingSide, EnPassantMode};
+use shakmaty::{ByColor, Chess, Move, Square, Color as ChessColor, Piece, Role, Position, CastlingSide, EnPassantMode};
 use crate::game_logic::state::GameState;
+use crate::game_logic::drops::DROPPABLE_ROLES;
+use crate::board::geometry::square_index;
 use rand::{Rng, SeedableRng};
 use rand::rngs::StdRng;
 
@@ -10,6 +12,12 @@ pub const MAX_DRAWBACK_INDICES: usize = 1024;
 // Maximum number of RNG outcomes to pre-compute
 pub const MAX_RNG_OUTCOMES: usize = 256;
 
+// Most pieces of one kind a hand is hashed with (more share the last key)
+pub const MAX_RESERVE_COUNT: usize = 16;
+
+// Most moves without a capture the side to move is hashed with (more share the last key)
+pub const MAX_MOVES_SINCE_CAPTURE: usize = 16;
+
 #[derive(Resource, Clone, Debug)]
 pub struct ZobristKeys {
     // Pieces[piece_type][square]
@@ -24,21 +32,35 @@ pub struct ZobristKeys {
     // En passant file
     pub en_passant: [u64; 8],
     
-    // Drawbacks - just need the drawback ID, not per color since we only care about current player
-    pub drawbacks: [u64; MAX_DRAWBACK_INDICES],
+    // Drawbacks drawbacks[color][drawback index]: both players' drawbacks are part of the position
+    pub drawbacks: [[u64; MAX_DRAWBACK_INDICES]; 2],
     
-    // RNG outcome
-    pub rng_outcomes: [u64; MAX_RNG_OUTCOMES + 1] // +1 for "no outcome" state
+    // Pending RNG outcome of the side to move rng_outcomes[color][outcome]
+    pub rng_outcomes: [[u64; MAX_RNG_OUTCOMES + 1]; 2], // +1 for "no outcome" state
+
+    // Pawn structure keys pawns[color][square], separate from the piece keys so
+    // the pawn key identifies a pawn formation on its own
+    pub pawns: [[u64; 64]; 2],
+
+    // Pieces in hand reserves[piece_type][count], for rulesets with drops
+    pub reserves: [[u64; MAX_RESERVE_COUNT + 1]; 12],
+
+    // Kind of piece the opponent last moved last_move_roles[role], for drawbacks that react to it
+    pub last_move_roles: [u64; 6],
+
+    // Moves the side to move made without capturing moves_since_capture[count], for drawbacks that count them
+    pub moves_since_capture: [u64; MAX_MOVES_SINCE_CAPTURE + 1],
 }
 
 pub struct ZobristPlugin;
 
 impl Plugin for ZobristPlugin {
     fn build(&self, app: &mut App) {
-        // Initialize the Zobrist keys
+        // Initialize the Zobrist keys. `GameState::zobrist_hash` is kept up to
+        // date where the state changes: updated from the move in `apply_move`,
+        // computed over again when a game is started, loaded or taken back.
         let zobrist_keys = initialize_zobrist_keys();
-        app.insert_resource(zobrist_keys)
-           .add_systems(Update, calculate_and_update_zobrist_hash);
+        app.insert_resource(zobrist_keys);
     }
 }
 
@@ -54,8 +76,12 @@ pub fn initialize_zobrist_keys() -> ZobristKeys {
         turn: 0,
         castling: [0; 4],
         en_passant: [0; 8],
-        drawbacks: [0; MAX_DRAWBACK_INDICES],
-        rng_outcomes: [0; MAX_RNG_OUTCOMES + 1],
+        drawbacks: [[0; MAX_DRAWBACK_INDICES]; 2],
+        rng_outcomes: [[0; MAX_RNG_OUTCOMES + 1]; 2],
+        pawns: [[0; 64]; 2],
+        reserves: [[0; MAX_RESERVE_COUNT + 1]; 12],
+        last_move_roles: [0; 6],
+        moves_since_capture: [0; MAX_MOVES_SINCE_CAPTURE + 1],
     };
     
     // Initialize piece keys
@@ -78,36 +104,62 @@ pub fn initialize_zobrist_keys() -> ZobristKeys {
         keys.en_passant[i] = rng.gen();
     }
     
-    // Initialize drawback keys - simplified to just per drawback ID
-    for drawback_id in 0..MAX_DRAWBACK_INDICES {
-        keys.drawbacks[drawback_id] = rng.gen();
+    // Initialize drawback keys per color and drawback ID
+    for color_keys in keys.drawbacks.iter_mut() {
+        for key in color_keys.iter_mut() {
+            *key = rng.gen();
+        }
     }
     
-    // Initialize RNG outcome keys
-    for i in 0..=MAX_RNG_OUTCOMES {
-        keys.rng_outcomes[i] = rng.gen();
+    // Initialize RNG outcome keys per color
+    for color_keys in keys.rng_outcomes.iter_mut() {
+        for key in color_keys.iter_mut() {
+            *key = rng.gen();
+        }
+    }
+
+    // Initialize pawn structure keys last so all keys above stay the same
+    for color_keys in keys.pawns.iter_mut() {
+        for key in color_keys.iter_mut() {
+            *key = rng.gen();
+        }
+    }
+
+    // Reserve keys come after those so the older keys stay the same too
+    for piece_keys in keys.reserves.iter_mut() {
+        for key in piece_keys.iter_mut() {
+            *key = rng.gen();
+        }
+    }
+
+    // And the last-move keys after those
+    for key in keys.last_move_roles.iter_mut() {
+        *key = rng.gen();
+    }
+
+    // And the capture count keys last, so the earlier keys stay as they were
+    for key in keys.moves_since_capture.iter_mut() {
+        *key = rng.gen();
     }
     
     keys
 }
 
-/// Calculate and update the Zobrist hash in the GameState
-pub fn calculate_and_update_zobrist_hash(
-    mut game_state: ResMut<GameState>,
-    zobrist_keys: Res<ZobristKeys>,
-) {
-    // Calculate the Zobrist hash
-    let hash = calculate_zobrist_hash(&game_state, &zobrist_keys);
-    
-    // Update the game state with the new hash
-    game_state.zobrist_hash = hash;
+// Helper function to convert a color to the index of its per-color keys
+fn color_index(color: ChessColor) -> usize {
+    match color {
+        ChessColor::White => 0,
+        ChessColor::Black => 1,
+    }
 }
 
-// Helper function to convert square to array index (0-63)
-fn square_to_index(sq: Square) -> usize {
-    let file_idx = sq.file().char() as usize - 'a' as usize;
-    let rank_idx = sq.rank().char() as usize - '1' as usize;
-    rank_idx * 8 + file_idx
+// Helper function to turn a value into a well-spread key (splitmix64 finalizer),
+// for parts of the state too varied to have precomputed keys
+fn mix_key(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
 }
 
 // Helper function to convert piece type and color to array index
@@ -131,106 +183,176 @@ fn piece_to_index(role: Role, color: ChessColor) -> usize {
 
 /// Calculate the Zobrist hash for a given GameState
 pub fn calculate_zobrist_hash(game_state: &GameState, keys: &ZobristKeys) -> u64 {
+    // 1.-4. Pieces, side to move, castling rights and en passant
+    let mut hash = calculate_board_hash(&game_state.board, keys);
+    
+    // 5. Both players' drawbacks, with their parameters (if any) so the same
+    // drawback with other parameters is a different position
+    for color in [ChessColor::White, ChessColor::Black] {
+        let (drawback, params) = game_state.drawback_of(color);
+        let drawback_idx = drawback.to_key_index() as usize % MAX_DRAWBACK_INDICES;
+        let drawback_key = keys.drawbacks[color_index(color)][drawback_idx];
+        hash ^= drawback_key;
+        if !params.is_empty() {
+            hash ^= mix_key(drawback_key ^ params.fingerprint());
+        }
+    }
+
+    // 6.-8. What moves change besides the board
+    hash ^ turn_state_hash(game_state, keys)
+}
+
+/// The part of `calculate_zobrist_hash` a move changes besides the board: the
+/// pending RNG outcome, the pieces in hand and the piece last moved
+pub fn turn_state_hash(game_state: &GameState, keys: &ZobristKeys) -> u64 {
     let mut hash: u64 = 0;
-    let board = &game_state.board;
-    let board_pieces = board.board();
 
-    // 1. Pieces
-    for square in Square::ALL {
-        if let Some(piece) = board_pieces.piece_at(square) {
-            let piece_idx = piece_to_index(piece.role, piece.color);
-            let square_idx = square_to_index(square);
-            hash ^= keys.pieces[piece_idx][square_idx];
+    // 6. RNG outcome pending for the side to move (if any), so identical boards
+    // with different outcomes (e.g. a different blocked file) are different positions
+    if let Some(outcome) = game_state.current_turn_rng_outcome {
+        let outcome_idx = outcome as usize % (MAX_RNG_OUTCOMES + 1);
+        hash ^= keys.rng_outcomes[color_index(game_state.current_player_turn)][outcome_idx];
+    }
+
+    // 7. Pieces in hand, which only rulesets and drawbacks with drops fill
+    if !game_state.reserves.is_empty() {
+        for color in [ChessColor::White, ChessColor::Black] {
+            for role in DROPPABLE_ROLES {
+                let count = game_state.reserves.count(color, role) as usize;
+                if count > 0 {
+                    hash ^= keys.reserves[piece_to_index(role, color)][count.min(MAX_RESERVE_COUNT)];
+                }
+            }
         }
     }
-    
-    // 2. Side to move
-    if game_state.current_player_turn == ChessColor::Black {
-        hash ^= keys.turn;
+
+    // 8. The kind of piece the opponent last moved, if the drawback of the side
+    // to move depends on it (otherwise repetitions would count as they always did)
+    if let Some(last_move) = game_state.last_move.as_ref().filter(|_| game_state.get_current_player_drawback_id().reacts_to_last_move()) {
+        hash ^= keys.last_move_roles[piece_to_index(last_move.role(), ChessColor::White)];
     }
-    
-    // 3. Castling rights
-    let castles = board.castles();
-    
-    if castles.has(ChessColor::White, CastlingSide::KingSide) {
-        hash ^= keys.castling[0];
+
+    // 9. The side to move's moves without a capture, if its drawback counts them
+    if game_state.get_current_player_drawback_id().counts_moves_since_capture() {
+        let count = game_state.moves_since_capture(game_state.current_player_turn) as usize;
+        hash ^= keys.moves_since_capture[count.min(MAX_MOVES_SINCE_CAPTURE)];
     }
     
-    if castles.has(ChessColor::White, CastlingSide::QueenSide) {
-        hash ^= keys.castling[1]; 
+    hash
+}
+
+/// The part of a search node's key that `turn_state_hash` is in the game: what
+/// the drawbacks know of the node beyond its board. Each part is None when no
+/// rule in the search depends on it, so those positions keep their board hash.
+pub fn search_turn_hash(
+    mover: ChessColor,
+    rng_outcome: Option<u8>,
+    last_move: Option<&Move>,
+    moves_since_capture: Option<ByColor<u32>>,
+    keys: &ZobristKeys,
+) -> u64 {
+    let mut hash: u64 = 0;
+    if let Some(outcome) = rng_outcome {
+        hash ^= keys.rng_outcomes[color_index(mover)][outcome as usize % (MAX_RNG_OUTCOMES + 1)];
     }
-    
-    if castles.has(ChessColor::Black, CastlingSide::KingSide) {
-        hash ^= keys.castling[2];
+    if let Some(last_move) = last_move {
+        hash ^= keys.last_move_roles[piece_to_index(last_move.role(), ChessColor::White)];
     }
-    
-    if castles.has(ChessColor::Black, CastlingSide::QueenSide) {
-        hash ^= keys.castling[3];
+    // The opponent's count decides its moves further down, so it is part of the key too
+    if let Some(counts) = moves_since_capture {
+        let count_key = |color: ChessColor| keys.moves_since_capture[(*counts.get(color) as usize).min(MAX_MOVES_SINCE_CAPTURE)];
+        hash ^= count_key(mover) ^ mix_key(count_key(!mover));
     }
-    
-    // 4. En passant square
-    if let Some(ep_square) = board.ep_square(EnPassantMode::Legal) {
-        let file_idx = ep_square.file().char() as usize - 'a' as usize;
-        hash ^= keys.en_passant[file_idx];
+    hash
+}
+
+/// Pawn structure key: hashes only the pawns, using the dedicated pawn keys.
+/// Positions with the same pawn formation share a key whatever the other pieces do.
+pub fn calculate_pawn_key(board: &Chess, keys: &ZobristKeys) -> u64 {
+    let mut hash: u64 = 0;
+    for color in [ChessColor::White, ChessColor::Black] {
+        for square in board.board().by_piece(Role::Pawn.of(color)) {
+            hash ^= keys.pawns[color_index(color)][square_index(square)];
+        }
     }
-    
-    // 5. Only hash the current player's drawback - simplified approach
-    let current_drawback = game_state.get_current_player_drawback_id();
-    let drawback_idx = current_drawback.to_key_index() as usize % MAX_DRAWBACK_INDICES;
-    hash ^= keys.drawbacks[drawback_idx];
-    
-    // 6. RNG outcome (if any)
-    if let Some(outcome) = game_state.current_turn_rng_outcome {
-        let outcome_idx = outcome as usize % (MAX_RNG_OUTCOMES + 1);
-        hash ^= keys.rng_outcomes[outcome_idx];
+    hash
+}
+
+/// What `chess_move` changes in `calculate_board_hash`: the pieces it moves,
+/// captures or drops, the side to move, and the castling rights and en passant
+/// square of `before` (the position it is played in) and `after` (the one it
+/// leads to). XORing it into the hash of `before` gives the hash of `after`.
+pub fn board_move_hash(before: &Chess, chess_move: &Move, after: &Chess, keys: &ZobristKeys) -> u64 {
+    let us = before.turn();
+    let mut hash = keys.turn ^ castling_and_en_passant_hash(before, keys) ^ castling_and_en_passant_hash(after, keys);
+    match *chess_move {
+        Move::Normal { role, from, capture, to, promotion } => {
+            hash ^= piece_key(keys, role.of(us), from) ^ piece_key(keys, promotion.unwrap_or(role).of(us), to);
+            if let Some(captured) = capture {
+                hash ^= piece_key(keys, captured.of(!us), to);
+            }
+        }
+        Move::EnPassant { from, to } => {
+            hash ^= piece_key(keys, Role::Pawn.of(us), from) ^ piece_key(keys, Role::Pawn.of(us), to);
+            hash ^= piece_key(keys, Role::Pawn.of(!us), Square::from_coords(to.file(), from.rank()));
+        }
+        Move::Castle { king, rook } => {
+            let side = chess_move.castling_side().expect("castling move has a side");
+            hash ^= piece_key(keys, Role::King.of(us), king) ^ piece_key(keys, Role::King.of(us), side.king_to(us));
+            hash ^= piece_key(keys, Role::Rook.of(us), rook) ^ piece_key(keys, Role::Rook.of(us), side.rook_to(us));
+        }
+        Move::Put { role, to } => {
+            hash ^= piece_key(keys, role.of(us), to);
+        }
     }
-    
     hash
 }
 
-/// Calculate Zobrist hash just for a chess board (simplified version)
-pub fn calculate_zobrist_hash_for_board(board: &Chess, keys: &ZobristKeys) -> u64 {
+/// Zobrist hash of just the chess position (pieces, side to move, castling rights
+/// and en passant), without the drawback state. Searches update it incrementally.
+pub fn calculate_board_hash(board: &Chess, keys: &ZobristKeys) -> u64 {
     let mut hash: u64 = 0;
-    let board_pieces = board.board();
 
     // 1. Pieces
-    for square in Square::ALL {
-        if let Some(piece) = board_pieces.piece_at(square) {
-            let piece_idx = piece_to_index(piece.role, piece.color);
-            let square_idx = square_to_index(square);
-            hash ^= keys.pieces[piece_idx][square_idx];
+    for square in board.board().occupied() {
+        if let Some(piece) = board.board().piece_at(square) {
+            hash ^= piece_key(keys, piece, square);
         }
     }
-    
+
     // 2. Side to move
     if board.turn() == ChessColor::Black {
         hash ^= keys.turn;
     }
-    
-    // 3. Castling rights
+
+    // 3.-4. Castling rights and en passant
+    hash ^ castling_and_en_passant_hash(board, keys)
+}
+
+/// Key of a single piece on a square
+pub fn piece_key(keys: &ZobristKeys, piece: Piece, square: Square) -> u64 {
+    keys.pieces[piece_to_index(piece.role, piece.color)][square_index(square)]
+}
+
+/// The castling rights and en passant part of the hash
+pub fn castling_and_en_passant_hash(board: &Chess, keys: &ZobristKeys) -> u64 {
+    let mut hash: u64 = 0;
     let castles = board.castles();
-    
-    if castles.has(ChessColor::White, CastlingSide::KingSide) {
-        hash ^= keys.castling[0];
-    }
-    
-    if castles.has(ChessColor::White, CastlingSide::QueenSide) {
-        hash ^= keys.castling[1]; 
-    }
-    
-    if castles.has(ChessColor::Black, CastlingSide::KingSide) {
-        hash ^= keys.castling[2];
-    }
-    
-    if castles.has(ChessColor::Black, CastlingSide::QueenSide) {
-        hash ^= keys.castling[3];
+
+    for (i, (color, side)) in [
+        (ChessColor::White, CastlingSide::KingSide),
+        (ChessColor::White, CastlingSide::QueenSide),
+        (ChessColor::Black, CastlingSide::KingSide),
+        (ChessColor::Black, CastlingSide::QueenSide),
+    ].into_iter().enumerate() {
+        if castles.has(color, side) {
+            hash ^= keys.castling[i];
+        }
     }
-    
-    // 4. En passant square
+
     if let Some(ep_square) = board.ep_square(EnPassantMode::Legal) {
-        let file_idx = ep_square.file().char() as usize - 'a' as usize;
-        hash ^= keys.en_passant[file_idx];
+        hash ^= keys.en_passant[usize::from(ep_square.file())];
     }
-    
+
     hash
-} 
\ No newline at end of file
+}
@@ -0,0 +1,4 @@
+pub mod plugin;
+pub mod server;
+
+pub use plugin::LocalApiPlugin;
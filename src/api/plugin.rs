@@ -0,0 +1,175 @@
+use bevy::prelude::*;
+use serde_json::json;
+use shakmaty::{Color as ChessColor, EnPassantMode, Move};
+use shakmaty::fen::Fen;
+use std::sync::Mutex;
+use std::sync::mpsc::Receiver;
+use crate::drawbacks::{DrawbackRegistry, DrawbackId};
+use crate::game_logic::events::{MakeMoveEvent, NewGameEvent};
+use crate::game_logic::history::MoveHistory;
+use crate::game_logic::notation::{format_san, format_uci, parse_uci};
+use crate::game_logic::state::{GameState, ActiveBoard, GameStatus, TurnState, AppState, PauseState, ReplayState};
+use crate::game_logic::systems::apply_move;
+use crate::stats::plugin::drawback_name;
+use super::server::{ApiRequest, ApiResponse, start_server};
+
+/// `--api [port]` starts the local API (only in builds with the `local-api` feature)
+pub const API_FLAG: &str = "--api";
+pub const DEFAULT_API_PORT: u16 = 7878;
+
+/// Resource holding the channel the API server sends its requests on
+#[derive(Resource)]
+pub struct LocalApi {
+    requests: Mutex<Receiver<ApiRequest>>,
+}
+
+/// Plugin for the local HTTP API that lets scripts and bots observe and drive the game
+pub struct LocalApiPlugin;
+
+impl Plugin for LocalApiPlugin {
+    fn build(&self, app: &mut App) {
+        let args: Vec<String> = std::env::args().collect();
+        let Some(flag) = args.iter().position(|arg| arg == API_FLAG) else {
+            return;
+        };
+        let port = args
+            .get(flag + 1)
+            .and_then(|port| port.parse::<u16>().ok())
+            .unwrap_or(DEFAULT_API_PORT);
+
+        match start_server(port) {
+            Ok(receiver) => {
+                println!("Local API listening on http://127.0.0.1:{}/", port);
+                app.insert_resource(LocalApi { requests: Mutex::new(receiver) })
+                   // Before apply_move so submitted moves are played in the same frame
+                   .add_systems(Update, handle_api_requests.before(apply_move));
+            }
+            Err(e) => eprintln!("Failed to start local API on port {}: {}", port, e),
+        }
+    }
+}
+
+/// System answering the requests that arrived since the last frame.
+///
+/// - `GET /state`: FEN, side to move, status, last move and both drawbacks
+/// - `GET /moves`: moves the side to move may play under its drawback
+/// - `POST /move`: plays a move for the human side, body `e2e4` or `{"uci": "e2e4"}`
+/// - `POST /new-game`: starts a new game with the current configuration
+#[allow(clippy::too_many_arguments)]
+pub fn handle_api_requests(
+    api: Res<LocalApi>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    history: Res<MoveHistory>,
+    registry: Res<DrawbackRegistry>,
+    turn_state: Res<State<TurnState>>,
+    app_state: Res<State<AppState>>,
+    pause_state: Res<State<PauseState>>,
+    replay_state: Res<State<ReplayState>>,
+    mut ev_make_move: EventWriter<MakeMoveEvent>,
+    mut ev_new_game: EventWriter<NewGameEvent>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    let Ok(requests) = api.requests.lock() else {
+        return;
+    };
+
+    for request in requests.try_iter() {
+        let path = request.path.split('?').next().unwrap_or_default();
+        let response = match (request.method.as_str(), path) {
+            ("GET", "/state") => ApiResponse::ok(state_json(game_state, &history, &registry).to_string()),
+            ("GET", "/moves") => {
+                let moves: Vec<_> = game_state
+                    .allowed_moves(&registry)
+                    .iter()
+                    .map(|m| move_json(game_state, m))
+                    .collect();
+                ApiResponse::ok(json!({ "moves": moves }).to_string())
+            }
+            ("POST", "/move") => {
+                let live = *app_state.get() == AppState::InGame
+                    && *pause_state.get() == PauseState::Running
+                    && *replay_state.get() == ReplayState::Live;
+                if !live {
+                    ApiResponse::error(409, "the game is paused or in replay")
+                } else if *turn_state.get() != TurnState::PlayerTurn {
+                    ApiResponse::error(409, "it is not the player's turn")
+                } else {
+                    match parse_move(game_state, &registry, &request.body) {
+                        Ok(chess_move) => {
+                            let body = move_json(game_state, &chess_move).to_string();
+                            ev_make_move.send(MakeMoveEvent(chess_move));
+                            ApiResponse { status: 202, body }
+                        }
+                        Err(message) => ApiResponse::error(400, &message),
+                    }
+                }
+            }
+            ("POST", "/new-game") => {
+                ev_new_game.send(NewGameEvent::default());
+                ApiResponse { status: 202, body: "{}".to_string() }
+            }
+            _ => ApiResponse::error(404, "unknown endpoint"),
+        };
+        // The client may have disconnected already
+        let _ = request.reply.send(response);
+    }
+}
+
+// Snapshot of the game for `GET /state`
+fn state_json(game_state: &GameState, history: &MoveHistory, registry: &DrawbackRegistry) -> serde_json::Value {
+    let drawback = |id: DrawbackId| json!({ "id": format!("{:?}", id), "name": drawback_name(registry, id) });
+    json!({
+        "fen": Fen::from_position(game_state.board.clone(), EnPassantMode::Legal).to_string(),
+        "turn": color_name(game_state.current_player_turn),
+        "status": match game_state.status {
+            GameStatus::Ongoing => "ongoing",
+            GameStatus::Finished(_) => "over",
+        },
+        "result": game_state.status.result().map(|result| json!({
+            "result": result.pgn_result(),
+            "winner": result.winner.map(color_name),
+            "reason": result.reason.to_string(),
+        })),
+        "ply": history.len(),
+        "last_move": history.moves.last().map(|record| json!({
+            "uci": format_uci(&record.chess_move),
+            "san": record.san,
+        })),
+        "drawbacks": {
+            "white": drawback(game_state.white_drawback),
+            "black": drawback(game_state.black_drawback),
+        },
+        "rng_outcome": game_state.current_turn_rng_outcome,
+    })
+}
+
+fn move_json(game_state: &GameState, chess_move: &Move) -> serde_json::Value {
+    json!({
+        "uci": format_uci(chess_move),
+        "san": format_san(&game_state.board, chess_move),
+    })
+}
+
+fn color_name(color: ChessColor) -> &'static str {
+    match color {
+        ChessColor::White => "white",
+        ChessColor::Black => "black",
+    }
+}
+
+// Reads a UCI move from a plain or JSON body and checks the drawback allows it
+fn parse_move(game_state: &GameState, registry: &DrawbackRegistry, body: &str) -> Result<Move, String> {
+    let body = body.trim();
+    let uci_text = match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(value) => value["uci"].as_str().ok_or("expected {\"uci\": \"e2e4\"}")?.to_string(),
+        Err(_) => body.to_string(),
+    };
+
+    let chess_move = parse_uci(&game_state.board, &uci_text)?;
+    if !game_state.allowed_moves(registry).contains(&chess_move) {
+        return Err(format!("move {} is not allowed by the drawback", uci_text));
+    }
+    Ok(chess_move)
+}
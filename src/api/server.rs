@@ -0,0 +1,135 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+
+/// A request received by the local API, answered by a Bevy system
+pub struct ApiRequest {
+    pub method: String,
+    pub path: String,
+    pub body: String,
+    pub reply: Sender<ApiResponse>,
+}
+
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: String, // JSON
+}
+
+impl ApiResponse {
+    pub fn ok(body: String) -> Self {
+        Self { status: 200, body }
+    }
+
+    /// Error response with a `{"error": ...}` body
+    pub fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+}
+
+// Largest request body we read (a move or a small JSON object)
+const MAX_BODY_BYTES: usize = 4096;
+
+// How long a request waits for the game to answer (it is paused or hanging otherwise)
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Starts the HTTP server on 127.0.0.1 and returns the channel its requests arrive on
+pub fn start_server(port: u16) -> Result<Receiver<ApiRequest>, std::io::Error> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    let (sender, receiver) = channel();
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+            let sender = sender.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = handle_connection(stream, &sender) {
+                    eprintln!("Local API client error: {}", e);
+                }
+            });
+        }
+    });
+
+    Ok(receiver)
+}
+
+// Reads one request, hands it to the game and writes back the answer
+fn handle_connection(stream: TcpStream, sender: &Sender<ApiRequest>) -> Result<(), std::io::Error> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    let mut from_web_page = false;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+            // Browsers send it with requests a page makes; scripts and bots don't
+            if name.trim().eq_ignore_ascii_case("origin") {
+                from_web_page = true;
+            }
+        }
+    }
+
+    let mut body = vec![0u8; content_length.min(MAX_BODY_BYTES)];
+    reader.read_exact(&mut body)?;
+
+    let response = if from_web_page {
+        // Any web page open in a browser could otherwise play moves through the API
+        ApiResponse::error(403, "requests from web pages are not allowed")
+    } else {
+        let (reply, answer) = channel();
+        let request = ApiRequest {
+            method,
+            path,
+            body: String::from_utf8_lossy(&body).into_owned(),
+            reply,
+        };
+        match sender.send(request) {
+            Ok(()) => match answer.recv_timeout(ANSWER_TIMEOUT) {
+                Ok(response) => response,
+                Err(RecvTimeoutError::Timeout) => ApiResponse::error(503, "game did not answer in time"),
+                Err(RecvTimeoutError::Disconnected) => ApiResponse::error(503, "game is shutting down"),
+            },
+            Err(_) => ApiResponse::error(503, "game is shutting down"),
+        }
+    };
+
+    write_response(stream, &response)
+}
+
+fn write_response(mut stream: TcpStream, response: &ApiResponse) -> Result<(), std::io::Error> {
+    let reason = match response.status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        409 => "Conflict",
+        503 => "Service Unavailable",
+        _ => "Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.body.len(),
+        response.body
+    )
+}
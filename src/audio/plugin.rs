@@ -0,0 +1,11 @@
+use bevy::prelude::*;
+use super::sounds::{play_game_over_sound, play_move_sounds};
+
+/// Plugin playing the game's sound effects (moves, captures, checks, game over)
+pub struct SoundEffectsPlugin;
+
+impl Plugin for SoundEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (play_move_sounds, play_game_over_sound));
+    }
+}
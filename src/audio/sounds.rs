@@ -0,0 +1,107 @@
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use shakmaty::Move;
+use std::time::Duration;
+use crate::config::GameConfig;
+use crate::game_logic::events::{GameOverEvent, MovePlayedEvent};
+
+/// What a sound effect is played for. Every kind has a tone of its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundEffect {
+    Move,
+    Capture,
+    Castle,
+    Promotion,
+    Check,
+    GameOver,
+}
+
+impl SoundEffect {
+    /// The sound of a played move; giving check sounds over anything else the move does
+    pub fn of_move(ev: &MovePlayedEvent) -> Self {
+        match &ev.chess_move {
+            _ if ev.is_check => Self::Check,
+            chess_move if chess_move.is_promotion() => Self::Promotion,
+            Move::Castle { .. } => Self::Castle,
+            chess_move if chess_move.is_capture() => Self::Capture,
+            _ => Self::Move,
+        }
+    }
+
+    // Frequency (Hz) and length (ms) of the tone
+    fn tone(self) -> (f32, u64) {
+        match self {
+            Self::Move => (440.0, 60),
+            Self::Capture => (294.0, 120),
+            Self::Castle => (523.0, 100),
+            Self::Promotion => (784.0, 160),
+            Self::Check => (880.0, 180),
+            Self::GameOver => (330.0, 450),
+        }
+    }
+}
+
+/// Plays a tone at the configured volume, unless sound is switched off
+pub fn play_tone(commands: &mut Commands, pitches: &mut Assets<Pitch>, config: &GameConfig, frequency: f32, length_ms: u64) {
+    if !config.display.sound_enabled {
+        return;
+    }
+    let volume = config.display.sound_volume.clamp(0.0, 1.0);
+    commands.spawn(PitchBundle {
+        source: pitches.add(Pitch::new(frequency, Duration::from_millis(length_ms))),
+        settings: PlaybackSettings::DESPAWN.with_volume(Volume::new_relative(volume)),
+    });
+}
+
+/// Plays the sound of every move `apply_move` put on the board
+pub fn play_move_sounds(
+    mut commands: Commands,
+    mut ev_played: EventReader<MovePlayedEvent>,
+    config: Res<GameConfig>,
+    mut pitches: ResMut<Assets<Pitch>>,
+) {
+    for ev in ev_played.read() {
+        let (frequency, length) = SoundEffect::of_move(ev).tone();
+        play_tone(&mut commands, &mut pitches, &config, frequency, length);
+    }
+}
+
+/// Plays the game over sound once a game ends
+pub fn play_game_over_sound(
+    mut commands: Commands,
+    mut ev_game_over: EventReader<GameOverEvent>,
+    config: Res<GameConfig>,
+    mut pitches: ResMut<Assets<Pitch>>,
+) {
+    if ev_game_over.read().last().is_some() {
+        let (frequency, length) = SoundEffect::GameOver.tone();
+        play_tone(&mut commands, &mut pitches, &config, frequency, length);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{Role, Square};
+
+    fn played(chess_move: Move, is_check: bool) -> MovePlayedEvent {
+        MovePlayedEvent { chess_move, is_check, picked: None }
+    }
+
+    #[test]
+    fn moves_get_the_sound_of_what_they_do() {
+        let quiet = Move::Normal { role: Role::Knight, from: Square::G1, capture: None, to: Square::F3, promotion: None };
+        let capture = Move::Normal { role: Role::Knight, from: Square::G1, capture: Some(Role::Pawn), to: Square::F3, promotion: None };
+        let promotion = Move::Normal { role: Role::Pawn, from: Square::B7, capture: Some(Role::Rook), to: Square::A8, promotion: Some(Role::Queen) };
+        let castle = Move::Castle { king: Square::E1, rook: Square::H1 };
+        let en_passant = Move::EnPassant { from: Square::E5, to: Square::D6 };
+
+        assert_eq!(SoundEffect::of_move(&played(quiet.clone(), false)), SoundEffect::Move);
+        assert_eq!(SoundEffect::of_move(&played(capture, false)), SoundEffect::Capture);
+        assert_eq!(SoundEffect::of_move(&played(en_passant, false)), SoundEffect::Capture);
+        assert_eq!(SoundEffect::of_move(&played(promotion.clone(), false)), SoundEffect::Promotion);
+        assert_eq!(SoundEffect::of_move(&played(castle, false)), SoundEffect::Castle);
+        assert_eq!(SoundEffect::of_move(&played(promotion, true)), SoundEffect::Check);
+        assert_eq!(SoundEffect::of_move(&played(quiet, true)), SoundEffect::Check);
+    }
+}
@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use shakmaty::{File, Rank, Square};
+use crate::constants::TILE_SIZE;
+
+// Square math shared by the engine tables and the board on screen. `flipped`
+// is `GameState::board_flipped`: rank 1 is at the bottom of the screen when
+// it is set, at the top otherwise. Files always run a-h from left to right.
+
+/// Index of `square` into 64-entry tables: a1 = 0, b1 = 1, ..., h8 = 63
+pub fn square_index(square: Square) -> usize {
+    usize::from(square)
+}
+
+/// Column and row of `square` counted from the bottom-left corner of the screen (0-7)
+pub fn screen_coords(square: Square, flipped: bool) -> (usize, usize) {
+    let (file, rank) = (usize::from(square.file()), usize::from(square.rank()));
+    (file, if flipped { rank } else { 7 - rank })
+}
+
+/// Center of `square`'s tile in world space (the board is centered on the origin)
+pub fn world_pos(square: Square, flipped: bool) -> Vec2 {
+    let (column, row) = screen_coords(square, flipped);
+    Vec2::new((column as f32 - 3.5) * TILE_SIZE, (row as f32 - 3.5) * TILE_SIZE)
+}
+
+/// Square whose tile contains the world position, None off the board
+pub fn square_at(world: Vec2, flipped: bool) -> Option<Square> {
+    let column = (world.x / TILE_SIZE + 4.0).floor();
+    let row = (world.y / TILE_SIZE + 4.0).floor();
+    if !(0.0..8.0).contains(&column) || !(0.0..8.0).contains(&row) {
+        return None;
+    }
+    let rank = if flipped { row as u32 } else { 7 - row as u32 };
+    Some(Square::from_coords(File::new(column as u32), Rank::new(rank)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn squares_map_to_tiles_and_back_in_both_orientations() {
+        assert_eq!((square_index(Square::A1), square_index(Square::H1), square_index(Square::H8)), (0, 7, 63));
+
+        // White at the bottom when flipped, at the top otherwise
+        assert_eq!(world_pos(Square::A1, true), Vec2::new(-3.5 * TILE_SIZE, -3.5 * TILE_SIZE));
+        assert_eq!(world_pos(Square::A1, false), Vec2::new(-3.5 * TILE_SIZE, 3.5 * TILE_SIZE));
+
+        for flipped in [true, false] {
+            for square in Square::ALL {
+                let center = world_pos(square, flipped);
+                assert_eq!(square_at(center, flipped), Some(square));
+                // Anywhere on the tile, not just the center
+                let corner = center + Vec2::splat(TILE_SIZE * 0.49);
+                assert_eq!(square_at(corner, flipped), Some(square));
+            }
+            assert_eq!(square_at(Vec2::new(4.01 * TILE_SIZE, 0.0), flipped), None);
+            assert_eq!(square_at(Vec2::new(0.0, -4.01 * TILE_SIZE), flipped), None);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use shakmaty::{Chess, Color as ChessColor, Move, Position, Square};
+use crate::constants::*;
+use crate::game_logic::state::{GameState, ActiveBoard};
+use super::components::BoardSquare;
+
+/// Marker for the tint on a square the last move left or reached
+#[derive(Component)]
+pub struct LastMoveHighlight;
+
+/// Marker for the red glow under a king that is attacked
+#[derive(Component)]
+pub struct CheckGlow;
+
+/// The squares to highlight for a move played by `mover`: where the piece came
+/// from and where it went (the king's destination when castling, the square
+/// only when dropping)
+pub fn last_move_squares(chess_move: &Move, mover: ChessColor) -> Vec<Square> {
+    match *chess_move {
+        Move::Castle { king, .. } => match chess_move.castling_side() {
+            Some(side) => vec![king, side.king_to(mover)],
+            None => vec![king],
+        },
+        Move::Put { to, .. } => vec![to],
+        Move::Normal { from, to, .. } | Move::EnPassant { from, to } => vec![from, to],
+    }
+}
+
+/// The squares of the kings an enemy piece attacks. Drawbacks can let a king
+/// stay attacked after its own move, so both sides are looked at.
+pub fn attacked_kings(position: &Chess) -> Vec<Square> {
+    let board = position.board();
+    [ChessColor::White, ChessColor::Black]
+        .into_iter()
+        .filter_map(|color| board.king_of(color).map(|king| (color, king)))
+        .filter(|&(color, king)| board.attacks_to(king, !color, board.occupied()).any())
+        .map(|(_, king)| king)
+        .collect()
+}
+
+/// Rebuilds the last move highlight and the check glow whenever the position
+/// changes, so the previous ones are gone as soon as the next move is applied
+/// (or taken back). Like the drawback overlay they are children of the board squares.
+#[allow(clippy::type_complexity)]
+pub fn update_last_move_highlight(
+    mut commands: Commands,
+    boards: Query<Ref<GameState>, With<ActiveBoard>>,
+    highlights: Query<Entity, Or<(With<LastMoveHighlight>, With<CheckGlow>)>>,
+    board_squares: Query<(Entity, &BoardSquare)>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    if !game_state.is_changed() {
+        return;
+    }
+
+    for entity in highlights.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    // The mover of the last move is the side no longer to move
+    let last_move = game_state.last_move.as_ref()
+        .map(|chess_move| last_move_squares(chess_move, !game_state.board.turn()))
+        .unwrap_or_default();
+    let tints = last_move.into_iter().map(|square| (square, LAST_MOVE_COLOR, Z_LAST_MOVE, false))
+        .chain(attacked_kings(&game_state.board).into_iter().map(|square| (square, CHECK_GLOW_COLOR, Z_CHECK_GLOW, true)));
+
+    for (square, color, z, glow) in tints {
+        let Some((entity, _)) = board_squares.iter().find(|(_, board_square)| board_square.square == square) else {
+            continue;
+        };
+        commands.entity(entity).with_children(|parent| {
+            let sprite = SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, z),
+                ..default()
+            };
+            if glow {
+                parent.spawn((sprite, CheckGlow));
+            } else {
+                parent.spawn((sprite, LastMoveHighlight));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{fen::Fen, CastlingMode, Role};
+
+    fn position(fen: &str) -> Chess {
+        fen.parse::<Fen>().unwrap().into_position(CastlingMode::Standard).unwrap()
+    }
+
+    #[test]
+    fn highlights_castling_by_the_king_and_finds_attacked_kings() {
+        let castle = Move::Castle { king: Square::E1, rook: Square::H1 };
+        assert_eq!(last_move_squares(&castle, ChessColor::White), vec![Square::E1, Square::G1]);
+        let push = Move::Normal { role: Role::Pawn, from: Square::E2, capture: None, to: Square::E4, promotion: None };
+        assert_eq!(last_move_squares(&push, ChessColor::White), vec![Square::E2, Square::E4]);
+
+        assert!(attacked_kings(&Chess::default()).is_empty());
+        // Black to move and in check from the rook
+        assert_eq!(attacked_kings(&position("4k3/8/8/8/8/8/8/4RK2 b - - 0 1")), vec![Square::E8]);
+    }
+}
@@ -1,4 +1,6 @@
 pub mod components;
+pub mod geometry;
 pub mod plugin;
 
- 
\ No newline at end of file
+ pub mod overlay;
+pub mod last_move;
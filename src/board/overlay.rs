@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+use shakmaty::Color as ChessColor;
+use crate::config::GameConfig;
+use crate::constants::*;
+use crate::drawbacks::DrawbackRegistry;
+use crate::game_logic::state::{GameState, ActiveBoard, GameStatus};
+use super::components::BoardSquare;
+
+/// Marker for a square tinted by the board overlay of the drawback of the side to move
+#[derive(Component)]
+pub struct DrawbackOverlay;
+
+/// Rebuilds the drawback overlay whenever the position changes. The tints are
+/// children of the board squares, so they follow them when the board is flipped.
+/// A drawback the AI keeps secret from a human player isn't shown.
+pub fn update_drawback_overlay(
+    mut commands: Commands,
+    boards: Query<Ref<GameState>, With<ActiveBoard>>,
+    config: Res<GameConfig>,
+    registry: Res<DrawbackRegistry>,
+    overlays: Query<Entity, With<DrawbackOverlay>>,
+    board_squares: Query<(Entity, &BoardSquare)>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    if !game_state.is_changed() && !config.is_changed() {
+        return;
+    }
+
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let (mover_is_ai, other_is_ai) = match game_state.current_player_turn {
+        ChessColor::White => (config.white_player.is_ai, config.black_player.is_ai),
+        ChessColor::Black => (config.black_player.is_ai, config.white_player.is_ai),
+    };
+    let secret = mover_is_ai && !other_is_ai;
+    if !config.display.show_drawback_overlays || secret || game_state.status != GameStatus::Ongoing {
+        return;
+    }
+    let Some(rule) = game_state.current_drawback_rule(&registry) else {
+        return;
+    };
+
+    for (square, color) in rule.board_overlay(&game_state.board, game_state.current_turn_rng_outcome) {
+        let Some((entity, _)) = board_squares.iter().find(|(_, board_square)| board_square.square == square) else {
+            continue;
+        };
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, 0.0, Z_DRAWBACK_OVERLAY),
+                    ..default()
+                },
+                DrawbackOverlay,
+            ));
+        });
+    }
+}
@@ -1,44 +1,54 @@
 use bevy::prelude::*;
 use crate::constants::*;
 use super::components::*;
+use super::overlay::update_drawback_overlay;
+use super::last_move::update_last_move_highlight;
 use shakmaty::{Square, File, Rank};
-use crate::game_logic::state::GameState;
+use super::geometry::world_pos;
+use crate::game_logic::state::{GameState, ActiveBoard};
+use crate::game_logic::events::FlipBoardEvent;
+use crate::input::focus::keyboard_shortcuts_enabled;
 
 pub struct BoardPlugin;
 
 impl Plugin for BoardPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, setup_board)
-           .add_systems(Update, handle_board_flip);
+           .add_systems(Update, handle_board_flip.run_if(keyboard_shortcuts_enabled))
+           // Zones and rolls of the drawback of the side to move
+           .add_systems(Update, update_drawback_overlay)
+           // Squares of the last move and kings in check
+           .add_systems(Update, update_last_move_highlight);
     }
 }
 
-// System to handle board flipping with the 'R' key
+// System to handle board flipping with the 'F' key or a FlipBoardEvent (e.g. from the menu)
 fn handle_board_flip(
     keys: Res<Input<KeyCode>>,
-    mut game_state: ResMut<GameState>,
+    mut ev_flip: EventReader<FlipBoardEvent>,
+    mut boards: Query<&mut GameState, With<ActiveBoard>>,
     mut board_squares: Query<(&mut Transform, &BoardSquare)>,
     mut pieces: Query<(&mut Transform, &crate::pieces::components::Piece), Without<BoardSquare>>,
 ) {
-    if keys.just_pressed(KeyCode::F) {
+    let Ok(mut game_state) = boards.get_single_mut() else {
+        return;
+    };
+    // Always drain the events so a menu request isn't replayed next frame
+    let flip_requested = ev_flip.read().count() > 0;
+    
+    if keys.just_pressed(KeyCode::F) || flip_requested {
         // Toggle the board flipped state
         game_state.board_flipped = !game_state.board_flipped;
         println!("Board flipped: {}", game_state.board_flipped);
         
         // Update all board square positions
         for (mut transform, square) in board_squares.iter_mut() {
-            let (x, y) = (square.x, square.y);
-            let position = calculate_square_position(x, y, game_state.board_flipped);
-            transform.translation = position;
+            transform.translation = world_pos(square.square, game_state.board_flipped).extend(0.0);
         }
         
         // Update all piece positions
         for (mut transform, piece) in pieces.iter_mut() {
-            let file = piece.pos.file().char() as u8 - b'a';
-            let rank = piece.pos.rank().char() as u8 - b'1';
-            
-            let (x, y) = (file as usize, rank as usize);
-            let position = calculate_square_position(x, y, game_state.board_flipped);
+            let position = world_pos(piece.pos, game_state.board_flipped);
             transform.translation.x = position.x;
             transform.translation.y = position.y;
             // Keep the z-coordinate (pieces should remain above the board)
@@ -47,25 +57,6 @@ fn handle_board_flip(
     }
 }
 
-// Helper function to calculate square positions based on board orientation
-fn calculate_square_position(x: usize, y: usize, flipped: bool) -> Vec3 {
-    if flipped {
-        // Flipped board (white at top)
-        Vec3::new(
-            (x as f32 - 3.5) * TILE_SIZE,
-            (y as f32 - 3.5) * TILE_SIZE,
-            0.0
-        )
-    } else {
-        // Standard board (white at bottom)
-        Vec3::new(
-            (x as f32 - 3.5) * TILE_SIZE,
-            ((7 - y) as f32 - 3.5) * TILE_SIZE,
-            0.0
-        )
-    }
-}
-
 fn setup_board(mut commands: Commands) {
     println!("Setting up chess board...");
     
@@ -76,42 +67,12 @@ fn setup_board(mut commands: Commands) {
             // This means (x+y) should be odd for dark squares
             let is_white = (x + y) % 2 == 1; // Swapped the condition from (x+y)%2==0
             
-            // Position the squares in world space
-            // (0,0) is at the center, with the board centered on it
-            let position = Vec3::new(
-                (x as f32 - 3.5) * TILE_SIZE, // Center the board horizontally
-                ((7 - y) as f32 - 3.5) * TILE_SIZE, // Standard orientation (white at bottom)
-                0.0, // Place at z=0 as the background
-            );
-            
-            // Convert x,y coordinates to shakmaty File/Rank
-            let file = match x {
-                0 => File::A,
-                1 => File::B,
-                2 => File::C,
-                3 => File::D,
-                4 => File::E,
-                5 => File::F,
-                6 => File::G,
-                7 => File::H,
-                _ => panic!("Invalid file index"),
-            };
-            
-            // Map our grid coordinates to chess rank - standard orientation
-            let rank = match y {
-                0 => Rank::First,  // Bottom row is Rank 1 (white's first rank)
-                1 => Rank::Second,
-                2 => Rank::Third,
-                3 => Rank::Fourth,
-                4 => Rank::Fifth,
-                5 => Rank::Sixth,
-                6 => Rank::Seventh,
-                7 => Rank::Eighth, // Top row is Rank 8 (black's first rank)
-                _ => panic!("Invalid rank index"),
-            };
-            
-            // Create the shakmaty Square
-            let square = Square::from_coords(file, rank);
+            // Grid coordinates are the file and rank
+            let square = Square::from_coords(File::new(x as u32), Rank::new(y as u32));
+
+            // Position the squares in world space, the board centered on (0,0),
+            // in the orientation new games start with
+            let position = world_pos(square, DEFAULT_BOARD_FLIPPED).extend(0.0); // z=0 as the background
             
             println!("Creating board square: {:?} at position {:?}, color: {}", 
                      square, position, if is_white { "white" } else { "black" });
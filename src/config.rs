@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 use crate::drawbacks::registry::DrawbackId;
+use crate::drawbacks::params::DrawbackParams;
+use crate::game_logic::state::{GameState, ActiveBoard, active_board_exists};
 use serde::{Serialize, Deserialize};
+use std::error::Error;
 
 //==============================================================================
 // GAME CONFIGURATION
@@ -13,14 +16,15 @@ use serde::{Serialize, Deserialize};
 //
 // White Player (Bottom):
 // - Set is_ai to true if you want AI to play as White, or false for human player
-// - Set drawback to a number or name (see list below)
+// - Set drawback to a number or name (see list below), or "Random" for a new
+//   random drawback every game
 const WHITE_IS_AI: bool = false;
-const WHITE_DRAWBACK_NAME: Option<&str> = None; // e.g. Some("No Castling")
+const WHITE_DRAWBACK_NAME: Option<&str> = None; // e.g. Some("No Castling") or Some("Random")
 const WHITE_DRAWBACK_INDEX: Option<u16> = None; // e.g. Some(1)
 
 // Black Player (Top):
 // - Set is_ai to true if you want AI to play as Black, or false for human player
-// - Set drawback to a number or name (see list below)
+// - Set drawback to a number or name (see list below), or "Random"
 const BLACK_IS_AI: bool = true;
 const BLACK_DRAWBACK_NAME: Option<&str> = None;
 const BLACK_DRAWBACK_INDEX: Option<u16> = None;
@@ -33,6 +37,154 @@ const AI_TIME_LIMIT_MS: u32 = 3000;      // Always take 3 seconds
 const AI_DEPTH_LIMIT: u8 = 24;           // Deep search
 const AI_CHECK_QUIETNESS: bool = true;  
 const AI_QUIESCENCE_DEPTH: u8 = 20;     
+// What to play when the engine comes up with an invalid move (or none):
+// Random, BestCapture, or ReSearch (a quick shallow search)
+const AI_FALLBACK_POLICY: FallbackPolicy = FallbackPolicy::Random;
+// Hide the opponent's drawback from the AI as well: it starts out treating
+// every drawback as equally likely, rules out the ones your moves contradict
+// and plays against the weighted guess
+const AI_OPPONENT_MODEL: bool = false;
+// Search algorithm: Pleco (fixed-depth search), Mcts (Monte Carlo tree
+// search that keeps its tree from move to move) or AlphaBeta (iterative
+// deepening up to the depth limit, with the quiescence settings above)
+const AI_ENGINE: AiEngine = AiEngine::Pleco;
+// Transposition table: memory it may take (0 = off) and which entry gives way
+// when two positions share a slot: Always (the newest) or DepthPreferred
+// (the one searched deeper, unless it is left over from an earlier move)
+const AI_TT_SIZE_MB: u32 = 64;
+const AI_TT_REPLACEMENT: TtReplacement = TtReplacement::DepthPreferred;
+
+// DISPLAY SETTINGS
+// ----------------
+// Low power mode only redraws on input (or while the AI is thinking),
+// instead of rendering at full vsync while you think about your move
+const LOW_POWER_MODE: bool = true;
+// Show an "AI is thinking…" indicator (elapsed time + search depth),
+// optionally with an arrow for the best move found so far
+const SHOW_AI_THINKING: bool = true;
+const SHOW_AI_BEST_MOVE_ARROW: bool = false;
+// In AI vs AI games, draw the line each engine expects as arrows in its own
+// color, with the plan behind the previous move left faded on the board
+const SHOW_AI_OBSERVER_ARROWS: bool = true;
+// Teaching mode: with a piece selected, also show (greyed out and struck
+// through) the moves your drawback takes away. Can be toggled in the settings.
+const SHOW_BLOCKED_MOVES: bool = false;
+// Tint the squares a drawback cares about while its owner is to move
+// (e.g. the file "Random File Blocked" rolled this turn)
+const SHOW_DRAWBACK_OVERLAYS: bool = true;
+// Language of the user interface: "en" (English) or "de" (Deutsch).
+// Can also be changed in the pause menu settings.
+const LANGUAGE: &str = "en";
+// Sound effects (moves, captures, checks, game over and the low time beeps).
+// Can be toggled in the pause menu settings.
+const SOUND_ENABLED: bool = true;
+// Volume of the sound effects, from 0.0 (silent) to 1.0 (full)
+const SOUND_VOLUME: f32 = 0.6;
+// The first time a human player's drawback takes away moves of a selected
+// piece or rolls for the turn, explain the rule in a popup. Its "don't show
+// again" button turns this off.
+const SHOW_RULE_EXPLANATIONS: bool = true;
+
+// TIME CONTROL
+// ------------
+// Chess clocks are off by default. Times are per side so the clocks can be
+// uneven; the increment is added after every move.
+const TIME_CONTROL_ENABLED: bool = false;
+const WHITE_TIME_MS: u64 = 5 * 60 * 1000;
+const BLACK_TIME_MS: u64 = 5 * 60 * 1000;
+const INCREMENT_MS: u64 = 0;
+// Delay instead of (or on top of) increment: "Simple" (US delay) waits the
+// delay before the clock starts running, "Bronstein" gives back the time you
+// used up to the delay after each move
+const DELAY_MODE: DelayMode = DelayMode::None;
+const DELAY_MS: u64 = 0;
+// Time per move instead of per game: each turn starts with this much on the
+// mover's clock and nothing carries over (0 = off, the times above are used)
+const PER_MOVE_MS: u64 = 0;
+// What a drawn game counts as: a draw, or a win for one side
+// (Armageddon: White gets more time, Black wins on a draw)
+const DRAW_RESULT: DrawResult = DrawResult::Draw;
+
+// Low time warnings: the clock turns yellow below the first threshold and red
+// below the second, with a beep each time your clock crosses one. The
+// promotion picker can be skipped (always a queen) once your time is critical.
+const LOW_TIME_WARNING_MS: u64 = 60 * 1000;
+const LOW_TIME_CRITICAL_MS: u64 = 10 * 1000;
+const LOW_TIME_SOUND: bool = true;
+const LOW_TIME_AUTO_QUEEN: bool = false;
+
+// Anti-stall timer for casual games without clocks: a reminder appears once
+// a player has thought this long about a move, and optionally a random legal
+// move is played for them after the grace period (kids, kiosk demos)
+const ANTI_STALL_ENABLED: bool = false;
+const ANTI_STALL_MOVE_SECS: f32 = 60.0;
+const ANTI_STALL_AUTO_MOVE: bool = false;
+const ANTI_STALL_GRACE_SECS: f32 = 15.0;
+
+// RULESET
+// -------
+// Standard, or Crazyhouse: captured pieces go into the capturer's hand and
+// can be dropped back onto any empty square instead of moving (pawns not on
+// the first or last rank). The AI doesn't drop pieces yet.
+const RULESET: Ruleset = Ruleset::Standard;
+
+// AUTO ROLLOVER (AI vs AI only)
+// -------------
+// For unattended AI-vs-AI sessions (balance testing, screensaver): start a
+// new game this many seconds after game over, optionally with new random
+// drawbacks for both sides, and log every result to the stats file
+const AUTO_ROLLOVER_ENABLED: bool = false;
+const AUTO_ROLLOVER_DELAY_SECS: f32 = 5.0;
+const AUTO_ROLLOVER_REROLL_DRAWBACKS: bool = false;
+const AUTO_ROLLOVER_LOG_RESULTS: bool = true;
+
+// ARENA (best-of-N match)
+// -----
+// Play a match of up to this many games between the two players configured
+// above (human or AI). Colors swap every game if enabled; the drawbacks either
+// stay with their player, stay with their color, or are rerolled every game.
+// The match ends once one player can no longer be caught.
+const ARENA_ENABLED: bool = false;
+const ARENA_GAMES: u32 = 5;
+const ARENA_ALTERNATE_COLORS: bool = true;
+const ARENA_DRAWBACKS: ArenaDrawbacks = ArenaDrawbacks::FollowPlayer;
+const ARENA_DELAY_SECS: f32 = 5.0;
+
+// RANDOMNESS
+// ----------
+// Seed for everything random in the game (AI tie-breaks, random drawbacks).
+// None picks a new seed every game; the seed is printed at the start of each
+// game, so putting it here plays that game's random choices out again
+const RNG_SEED: Option<u64> = None;
+
+// STARTING POSITION
+// -----------------
+// Game to start with instead of the standard position: a FEN, or the path of
+// a PGN file whose game goes on from its last move. --fen / --pgn on the
+// command line take precedence
+const START_FROM: Option<&str> = None;
+
+// NETWORK GAMES
+// -------------
+// How long a player who lost the connection in a game started with --host /
+// --join has to come back before they forfeit it, and how often the joining
+// side tries to reconnect
+const NETWORK_ABANDON_TIMEOUT_SECS: f32 = 60.0;
+const NETWORK_RECONNECT_INTERVAL_SECS: f32 = 2.0;
+// Quick messages offered as buttons next to the chat of a network game, and
+// whether chat messages are kept as comments in the game's PGN
+const NETWORK_EMOTES: [&str; 4] = ["Hello!", "Good luck", "Nice move", "Good game"];
+const NETWORK_CHAT_IN_PGN: bool = false;
+// Whether the host keeps its drawback secret from the other player until the
+// game is over (the other player's drawback is always known to the host)
+const NETWORK_HIDDEN_DRAWBACKS: bool = false;
+
+// INTEGRATION SETTINGS
+// --------------------
+// Show the current game in your Discord status (needs a build with
+// `--features discord` and the ID of a Discord application to show it as)
+const DISCORD_PRESENCE: bool = false;
+const DISCORD_CLIENT_ID: &str = "";
 
 //==============================================================================
 // DRAWBACK LIST
@@ -41,11 +193,37 @@ const AI_QUIESCENCE_DEPTH: u8 = 20;
 // - "No Castling"
 // - "Pawns Advance One"
 // - "Random File Blocked"
+// - "Recycling"
+// - "Pacifist Opening"
+// - "Edge-phobic"
+// - "Follow the Leader"
+// - "Pawn Horde"
+// - "Mirror"
+// - "Claustrophobia"
+// - "Vampire"
+// - "Glass Cannon"
+// - "Slippery Fingers"
 //
 // Indices:
 // - 1: No Castling
 // - 2: Pawns Advance One
 // - 3: Random File Blocked
+// - 4: Recycling
+// - 5: Pacifist Opening
+// - 6: Edge-phobic
+// - 7: Follow the Leader
+// - 8: Pawn Horde
+// - 9: Mirror
+// - 10: Claustrophobia
+// - 11: Vampire
+// - 12: Glass Cannon
+// - 13: Slippery Fingers
+//
+// Parameters (in a drawback setting's "params", all optional):
+// - Random File Blocked: "file" (0-7 = a-h) blocks that file every turn
+// - Pacifist Opening: "first_capture_move" (default 6)
+// - Edge-phobic: "castling_exempt", "promotion_exempt" (1 = yes, 0 = no)
+// - Pawn Horde: "minimum" (0-8, default 4)
 //==============================================================================
 
 /// Settings for an individual player
@@ -55,12 +233,28 @@ pub struct PlayerSettings {
     pub drawback: DrawbackSetting, // The drawback for this player
 }
 
+/// Drawback name that rolls a random drawback whenever a game starts
+pub const RANDOM_DRAWBACK: &str = "Random";
+
 /// Settings for an individual drawback
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DrawbackSetting {
     // Either name OR index should be specified (name takes precedence)
-    pub name: Option<String>,    // Drawback name (e.g., "No Castling")
+    pub name: Option<String>,    // Drawback name (e.g., "No Castling", or "Random")
     pub index: Option<u16>,      // Drawback index (e.g., 1 for NoCastling)
+    #[serde(default, skip_serializing_if = "DrawbackParams::is_empty")]
+    pub params: DrawbackParams,  // Rule parameters (e.g., {"minimum": 5} for Pawn Horde), empty = defaults
+}
+
+impl DrawbackSetting {
+    /// A random drawback, picked from the registry when each game starts
+    pub fn random() -> Self {
+        Self { name: Some(RANDOM_DRAWBACK.to_string()), index: None, params: DrawbackParams::default() }
+    }
+
+    pub fn is_random(&self) -> bool {
+        self.name.as_deref() == Some(RANDOM_DRAWBACK)
+    }
 }
 
 /// AI algorithm configuration
@@ -71,6 +265,363 @@ pub struct AiSettings {
     pub depth_limit: u8,          // Maximum search depth
     pub check_quietness: bool,    // Whether to check for quiet positions before ending search
     pub quiescence_depth: u8,     // Extra depth to search in non-quiet positions
+    #[serde(default)]
+    pub fallback_policy: FallbackPolicy, // Move to play when the engine's move is invalid
+    #[serde(default)]
+    pub opponent_model: bool,     // Whether the AI has to infer the opponent's drawback from their moves
+    #[serde(default)]
+    pub engine: AiEngine,         // Search algorithm the AI uses
+    #[serde(default = "default_tt_size_mb")]
+    pub tt_size_mb: u32,          // Memory of the transposition table in megabytes (0 = off)
+    #[serde(default)]
+    pub tt_replacement: TtReplacement, // Which transposition table entry gives way on a collision
+}
+
+/// Transposition table size of the settings above, for settings made elsewhere
+pub fn default_tt_size_mb() -> u32 {
+    AI_TT_SIZE_MB
+}
+
+/// Search algorithm behind the AI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AiEngine {
+    Pleco,     // Pleco's evaluation, one or two plies deep
+    Mcts,      // Monte Carlo tree search, `iteration_limit` iterations at most
+    AlphaBeta, // Iterative deepening alpha-beta, `depth_limit` plies at most
+}
+
+impl Default for AiEngine {
+    fn default() -> Self {
+        AI_ENGINE
+    }
+}
+
+/// Which transposition table entry is overwritten when two positions share a slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TtReplacement {
+    Always,         // The newest entry wins
+    DepthPreferred, // The entry searched deeper stays, unless it is from an earlier search
+}
+
+impl Default for TtReplacement {
+    fn default() -> Self {
+        AI_TT_REPLACEMENT
+    }
+}
+
+/// How the AI replaces a move the engine got wrong
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FallbackPolicy {
+    Random,      // Any allowed move
+    BestCapture, // The most valuable capture, or any allowed move without one
+    ReSearch,    // The best move of a quick shallow search
+}
+
+impl Default for FallbackPolicy {
+    fn default() -> Self {
+        AI_FALLBACK_POLICY
+    }
+}
+
+/// Display and rendering configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisplaySettings {
+    pub low_power_mode: bool,     // Whether to use reactive (event-driven) rendering
+    #[serde(default = "default_true")]
+    pub show_ai_thinking: bool,   // Whether to show the AI thinking indicator
+    #[serde(default)]
+    pub show_ai_best_move_arrow: bool, // Whether to draw the AI's best move so far
+    #[serde(default = "default_true")]
+    pub show_ai_observer_arrows: bool, // Whether to draw both engines' plans in AI vs AI games
+    #[serde(default)]
+    pub show_blocked_moves: bool, // Teaching mode: show the moves the drawback removed
+    #[serde(default = "default_true")]
+    pub show_drawback_overlays: bool, // Whether to tint the squares the drawback of the side to move cares about
+    #[serde(default)]
+    pub share_with_fen: bool,     // Whether shared position images also show the FEN and move number
+    #[serde(default = "default_language")]
+    pub language: String,         // Language code of the user interface
+    #[serde(default = "default_sound_enabled")]
+    pub sound_enabled: bool,      // Whether sound effects are played
+    #[serde(default = "default_sound_volume")]
+    pub sound_volume: f32,        // Volume of the sound effects (0.0-1.0)
+    #[serde(default = "default_true")]
+    pub show_rule_explanations: bool, // Whether a drawback's rule is explained the first time it triggers in a game
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_language() -> String {
+    LANGUAGE.to_string()
+}
+
+fn default_sound_enabled() -> bool {
+    SOUND_ENABLED
+}
+
+fn default_sound_volume() -> f32 {
+    SOUND_VOLUME
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            low_power_mode: LOW_POWER_MODE,
+            show_ai_thinking: SHOW_AI_THINKING,
+            show_ai_best_move_arrow: SHOW_AI_BEST_MOVE_ARROW,
+            show_ai_observer_arrows: SHOW_AI_OBSERVER_ARROWS,
+            show_blocked_moves: SHOW_BLOCKED_MOVES,
+            show_drawback_overlays: SHOW_DRAWBACK_OVERLAYS,
+            share_with_fen: false,
+            language: default_language(),
+            sound_enabled: SOUND_ENABLED,
+            sound_volume: SOUND_VOLUME,
+            show_rule_explanations: SHOW_RULE_EXPLANATIONS,
+        }
+    }
+}
+
+/// What a drawn game counts as in the result and the statistics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DrawResult {
+    #[default]
+    Draw,
+    WhiteWins,
+    BlackWins,
+}
+
+/// Which rules the game is played by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Ruleset {
+    Standard,
+    Crazyhouse, // Captured pieces can be dropped back onto the board
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        RULESET
+    }
+}
+
+/// How the delay of a time control works
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DelayMode {
+    #[default]
+    None,
+    Simple,    // The clock only starts once the delay has passed
+    Bronstein, // After the move, the time used (up to the delay) is added back
+}
+
+/// Delay used when one is switched on in the settings without a length configured
+pub const DEFAULT_DELAY_MS: u64 = 5000;
+
+impl DelayMode {
+    /// The mode after this one (for cycling through them in the settings)
+    pub fn next(self) -> DelayMode {
+        match self {
+            Self::None => Self::Simple,
+            Self::Simple => Self::Bronstein,
+            Self::Bronstein => Self::None,
+        }
+    }
+}
+
+/// Chess clock and result rule configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeControlSettings {
+    pub enabled: bool,            // Whether the game is played with clocks
+    pub white_time_ms: u64,       // White's starting time
+    pub black_time_ms: u64,       // Black's starting time
+    #[serde(default)]
+    pub increment_ms: u64,        // Added to a player's clock after each of their moves
+    #[serde(default)]
+    pub delay_mode: DelayMode,    // Simple or Bronstein delay
+    #[serde(default)]
+    pub delay_ms: u64,            // Length of the delay
+    #[serde(default)]
+    pub per_move_ms: u64,         // Time for each move on its own (0 = off)
+    #[serde(default)]
+    pub draw_result: DrawResult,  // What a draw counts as (e.g. a Black win in Armageddon)
+    #[serde(default)]
+    pub low_time: LowTimeSettings, // Warnings when a clock runs low
+}
+
+/// Warnings and shortcuts for when a player is low on time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LowTimeSettings {
+    pub warning_ms: u64,          // First warning (clock turns yellow)
+    pub critical_ms: u64,         // Second warning (clock turns red)
+    #[serde(default = "default_true")]
+    pub play_sound: bool,         // Beep when your clock crosses a threshold
+    #[serde(default)]
+    pub auto_queen: bool,         // Skip the promotion picker once your time is critical
+}
+
+impl Default for LowTimeSettings {
+    fn default() -> Self {
+        Self {
+            warning_ms: LOW_TIME_WARNING_MS,
+            critical_ms: LOW_TIME_CRITICAL_MS,
+            play_sound: LOW_TIME_SOUND,
+            auto_queen: LOW_TIME_AUTO_QUEEN,
+        }
+    }
+}
+
+impl TimeControlSettings {
+    /// Armageddon: White has 5 minutes against Black's 4, but a draw counts as a win for Black
+    pub fn armageddon() -> Self {
+        Self {
+            enabled: true,
+            white_time_ms: 5 * 60 * 1000,
+            black_time_ms: 4 * 60 * 1000,
+            increment_ms: 0,
+            delay_mode: DelayMode::None,
+            delay_ms: 0,
+            per_move_ms: 0,
+            draw_result: DrawResult::BlackWins,
+            low_time: LowTimeSettings::default(),
+        }
+    }
+}
+
+impl Default for TimeControlSettings {
+    fn default() -> Self {
+        Self {
+            enabled: TIME_CONTROL_ENABLED,
+            white_time_ms: WHITE_TIME_MS,
+            black_time_ms: BLACK_TIME_MS,
+            increment_ms: INCREMENT_MS,
+            delay_mode: DELAY_MODE,
+            delay_ms: DELAY_MS,
+            per_move_ms: PER_MOVE_MS,
+            draw_result: DRAW_RESULT,
+            low_time: LowTimeSettings::default(),
+        }
+    }
+}
+
+/// Soft per-move timer for casual games
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AntiStallSettings {
+    pub enabled: bool,            // Whether players are reminded to move
+    pub move_secs: f32,           // Thinking time before the reminder
+    pub auto_move: bool,          // Play a random legal move once the grace period is over
+    pub grace_secs: f32,          // Time between the reminder and the automatic move
+}
+
+impl Default for AntiStallSettings {
+    fn default() -> Self {
+        Self {
+            enabled: ANTI_STALL_ENABLED,
+            move_secs: ANTI_STALL_MOVE_SECS,
+            auto_move: ANTI_STALL_AUTO_MOVE,
+            grace_secs: ANTI_STALL_GRACE_SECS,
+        }
+    }
+}
+
+/// Settings for starting AI-vs-AI games back to back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoRolloverSettings {
+    pub enabled: bool,            // Whether a new game starts by itself after game over
+    pub delay_secs: f32,          // Time to look at the final position first
+    pub reroll_drawbacks: bool,   // Give both sides new random drawbacks every game
+    pub log_results: bool,        // Record every result in the stats file
+}
+
+impl Default for AutoRolloverSettings {
+    fn default() -> Self {
+        Self {
+            enabled: AUTO_ROLLOVER_ENABLED,
+            delay_secs: AUTO_ROLLOVER_DELAY_SECS,
+            reroll_drawbacks: AUTO_ROLLOVER_REROLL_DRAWBACKS,
+            log_results: AUTO_ROLLOVER_LOG_RESULTS,
+        }
+    }
+}
+
+/// Which drawback each side gets in the games of an arena match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArenaDrawbacks {
+    FollowPlayer, // Each player keeps their drawback when the colors swap
+    FollowColor,  // The drawbacks stay with White and Black
+    Random,       // Both players get a new random drawback every game
+}
+
+/// Settings for a best-of-N match between the two configured players
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArenaSettings {
+    pub enabled: bool,            // Whether the games are played as a match
+    pub games: u32,               // Length of the match (best of N)
+    pub alternate_colors: bool,   // Swap colors after every game
+    pub drawbacks: ArenaDrawbacks, // Who gets which drawback in the next game
+    pub delay_secs: f32,          // Time to look at the final position before the next game
+}
+
+impl Default for ArenaSettings {
+    fn default() -> Self {
+        Self {
+            enabled: ARENA_ENABLED,
+            games: ARENA_GAMES,
+            alternate_colors: ARENA_ALTERNATE_COLORS,
+            drawbacks: ARENA_DRAWBACKS,
+            delay_secs: ARENA_DELAY_SECS,
+        }
+    }
+}
+
+/// Settings for games against another machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkSettings {
+    pub abandon_timeout_secs: f32,     // Time to reconnect before the game is forfeited
+    pub reconnect_interval_secs: f32,  // Pause between the joining side's reconnect attempts
+    pub emotes: Vec<String>,           // Preset chat messages (none for no buttons)
+    pub chat_in_pgn: bool,             // Whether chat is recorded as comments on the moves
+    pub hidden_drawbacks: bool,        // Host: keep our drawback from the other player until the end
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            abandon_timeout_secs: NETWORK_ABANDON_TIMEOUT_SECS,
+            reconnect_interval_secs: NETWORK_RECONNECT_INTERVAL_SECS,
+            emotes: NETWORK_EMOTES.iter().map(|emote| emote.to_string()).collect(),
+            chat_in_pgn: NETWORK_CHAT_IN_PGN,
+            hidden_drawbacks: NETWORK_HIDDEN_DRAWBACKS,
+        }
+    }
+}
+
+/// Settings for talking to other programs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrationSettings {
+    #[serde(default)]
+    pub discord_presence: bool,   // Whether to show the game as Discord Rich Presence
+    #[serde(default)]
+    pub discord_client_id: String, // Discord application ID the presence is shown as
+}
+
+impl Default for IntegrationSettings {
+    fn default() -> Self {
+        Self {
+            discord_presence: DISCORD_PRESENCE,
+            discord_client_id: DISCORD_CLIENT_ID.to_string(),
+        }
+    }
 }
 
 /// Resource for storing game configuration
@@ -82,6 +633,46 @@ pub struct GameConfig {
     
     // AI settings
     pub ai_settings: AiSettings,
+    
+    // Display settings
+    #[serde(default)]
+    pub display: DisplaySettings,
+
+    // Time control settings
+    #[serde(default)]
+    pub time_control: TimeControlSettings,
+
+    // Reminder (and optional random move) for players who take too long
+    #[serde(default)]
+    pub anti_stall: AntiStallSettings,
+
+    // Standard chess or a variant with drops
+    #[serde(default)]
+    pub ruleset: Ruleset,
+
+    // Automatic new games in AI-vs-AI mode
+    #[serde(default)]
+    pub auto_rollover: AutoRolloverSettings,
+
+    // Best-of-N match between the two players
+    #[serde(default)]
+    pub arena: ArenaSettings,
+
+    // Seed for the game's randomness (None = random)
+    #[serde(default)]
+    pub rng_seed: Option<u64>,
+
+    // FEN or PGN file to start from (None = standard position)
+    #[serde(default)]
+    pub start_from: Option<String>,
+
+    // Games against another machine
+    #[serde(default)]
+    pub network: NetworkSettings,
+
+    // Integration settings
+    #[serde(default)]
+    pub integrations: IntegrationSettings,
 }
 
 impl Default for GameConfig {
@@ -93,6 +684,7 @@ impl Default for GameConfig {
                 drawback: DrawbackSetting {
                     name: WHITE_DRAWBACK_NAME.map(|s| s.to_string()),
                     index: WHITE_DRAWBACK_INDEX,
+                    params: DrawbackParams::default(),
                 },
             },
             black_player: PlayerSettings {
@@ -100,6 +692,7 @@ impl Default for GameConfig {
                 drawback: DrawbackSetting {
                     name: BLACK_DRAWBACK_NAME.map(|s| s.to_string()),
                     index: BLACK_DRAWBACK_INDEX,
+                    params: DrawbackParams::default(),
                 },
             },
             ai_settings: AiSettings {
@@ -108,7 +701,22 @@ impl Default for GameConfig {
                 depth_limit: AI_DEPTH_LIMIT,
                 check_quietness: AI_CHECK_QUIETNESS,
                 quiescence_depth: AI_QUIESCENCE_DEPTH,
+                fallback_policy: AI_FALLBACK_POLICY,
+                opponent_model: AI_OPPONENT_MODEL,
+                engine: AI_ENGINE,
+                tt_size_mb: AI_TT_SIZE_MB,
+                tt_replacement: AI_TT_REPLACEMENT,
             },
+            display: DisplaySettings::default(),
+            time_control: TimeControlSettings::default(),
+            anti_stall: AntiStallSettings::default(),
+            ruleset: RULESET,
+            auto_rollover: AutoRolloverSettings::default(),
+            arena: ArenaSettings::default(),
+            rng_seed: RNG_SEED,
+            start_from: START_FROM.map(str::to_string),
+            network: NetworkSettings::default(),
+            integrations: IntegrationSettings::default(),
         }
     }
 }
@@ -125,6 +733,7 @@ pub mod presets {
                 drawback: DrawbackSetting {
                     name: None,
                     index: None,
+                    params: DrawbackParams::default(),
                 },
             },
             black_player: PlayerSettings {
@@ -132,6 +741,7 @@ pub mod presets {
                 drawback: DrawbackSetting {
                     name: None,
                     index: None,
+                    params: DrawbackParams::default(),
                 },
             },
             ai_settings: AiSettings {
@@ -140,7 +750,13 @@ pub mod presets {
                 depth_limit: 18,
                 check_quietness: true,
                 quiescence_depth: 16,
+                fallback_policy: AI_FALLBACK_POLICY,
+                opponent_model: AI_OPPONENT_MODEL,
+                engine: AI_ENGINE,
+                tt_size_mb: AI_TT_SIZE_MB,
+                tt_replacement: AI_TT_REPLACEMENT,
             },
+            ..GameConfig::default()
         }
     }
     
@@ -152,6 +768,7 @@ pub mod presets {
                 drawback: DrawbackSetting {
                     name: None,
                     index: None,
+                    params: DrawbackParams::default(),
                 },
             },
             black_player: PlayerSettings {
@@ -159,6 +776,7 @@ pub mod presets {
                 drawback: DrawbackSetting {
                     name: None,
                     index: None,
+                    params: DrawbackParams::default(),
                 },
             },
             ai_settings: AiSettings {
@@ -167,7 +785,13 @@ pub mod presets {
                 depth_limit: 18,
                 check_quietness: true,
                 quiescence_depth: 16,
+                fallback_policy: AI_FALLBACK_POLICY,
+                opponent_model: AI_OPPONENT_MODEL,
+                engine: AI_ENGINE,
+                tt_size_mb: AI_TT_SIZE_MB,
+                tt_replacement: AI_TT_REPLACEMENT,
             },
+            ..GameConfig::default()
         }
     }
     
@@ -179,6 +803,7 @@ pub mod presets {
                 drawback: DrawbackSetting {
                     name: None,
                     index: None,
+                    params: DrawbackParams::default(),
                 },
             },
             black_player: PlayerSettings {
@@ -186,6 +811,7 @@ pub mod presets {
                 drawback: DrawbackSetting {
                     name: None,
                     index: None,
+                    params: DrawbackParams::default(),
                 },
             },
             ai_settings: AiSettings {
@@ -194,7 +820,13 @@ pub mod presets {
                 depth_limit: 24,           // Deep search
                 check_quietness: true,
                 quiescence_depth: 20,
+                fallback_policy: AI_FALLBACK_POLICY,
+                opponent_model: AI_OPPONENT_MODEL,
+                engine: AI_ENGINE,
+                tt_size_mb: AI_TT_SIZE_MB,
+                tt_replacement: AI_TT_REPLACEMENT,
             },
+            ..GameConfig::default()
         }
     }
     
@@ -206,6 +838,7 @@ pub mod presets {
                 drawback: DrawbackSetting {
                     name: None,
                     index: None,
+                    params: DrawbackParams::default(),
                 },
             },
             black_player: PlayerSettings {
@@ -213,6 +846,7 @@ pub mod presets {
                 drawback: DrawbackSetting {
                     name: None, 
                     index: None,
+                    params: DrawbackParams::default(),
                 },
             },
             ai_settings: AiSettings {
@@ -221,7 +855,13 @@ pub mod presets {
                 depth_limit: 12,
                 check_quietness: true,
                 quiescence_depth: 8,
+                fallback_policy: AI_FALLBACK_POLICY,
+                opponent_model: AI_OPPONENT_MODEL,
+                engine: AI_ENGINE,
+                tt_size_mb: AI_TT_SIZE_MB,
+                tt_replacement: AI_TT_REPLACEMENT,
             },
+            ..GameConfig::default()
         }
     }
     
@@ -233,6 +873,7 @@ pub mod presets {
                 drawback: DrawbackSetting {
                     name: None,
                     index: None,
+                    params: DrawbackParams::default(),
                 },
             },
             black_player: PlayerSettings {
@@ -240,6 +881,7 @@ pub mod presets {
                 drawback: DrawbackSetting {
                     name: None,
                     index: None,
+                    params: DrawbackParams::default(),
                 },
             },
             ai_settings: AiSettings {
@@ -248,7 +890,13 @@ pub mod presets {
                 depth_limit: 8,
                 check_quietness: false,
                 quiescence_depth: 4,
+                fallback_policy: AI_FALLBACK_POLICY,
+                opponent_model: AI_OPPONENT_MODEL,
+                engine: AI_ENGINE,
+                tt_size_mb: AI_TT_SIZE_MB,
+                tt_replacement: AI_TT_REPLACEMENT,
             },
+            ..GameConfig::default()
         }
     }
     
@@ -260,6 +908,7 @@ pub mod presets {
                 drawback: DrawbackSetting {
                     name: None,
                     index: None,
+                    params: DrawbackParams::default(),
                 },
             },
             black_player: PlayerSettings {
@@ -267,6 +916,7 @@ pub mod presets {
                 drawback: DrawbackSetting {
                     name: None,
                     index: None,
+                    params: DrawbackParams::default(),
                 },
             },
             ai_settings: AiSettings {
@@ -275,7 +925,13 @@ pub mod presets {
                 depth_limit: 24,
                 check_quietness: true,
                 quiescence_depth: 20,
+                fallback_policy: AI_FALLBACK_POLICY,
+                opponent_model: AI_OPPONENT_MODEL,
+                engine: AI_ENGINE,
+                tt_size_mb: AI_TT_SIZE_MB,
+                tt_replacement: AI_TT_REPLACEMENT,
             },
+            ..GameConfig::default()
         }
     }
     
@@ -287,6 +943,7 @@ pub mod presets {
                 drawback: DrawbackSetting {
                     name: None,
                     index: None,
+                    params: DrawbackParams::default(),
                 },
             },
             black_player: PlayerSettings {
@@ -294,6 +951,7 @@ pub mod presets {
                 drawback: DrawbackSetting {
                     name: None,
                     index: None,
+                    params: DrawbackParams::default(),
                 },
             },
             ai_settings: AiSettings {
@@ -302,7 +960,21 @@ pub mod presets {
                 depth_limit: 20,
                 check_quietness: true,
                 quiescence_depth: 18,
+                fallback_policy: AI_FALLBACK_POLICY,
+                opponent_model: AI_OPPONENT_MODEL,
+                engine: AI_ENGINE,
+                tt_size_mb: AI_TT_SIZE_MB,
+                tt_replacement: AI_TT_REPLACEMENT,
             },
+            ..GameConfig::default()
+        }
+    }
+
+    // Armageddon against the AI: more time for White, draw odds for Black
+    pub fn armageddon() -> GameConfig {
+        GameConfig {
+            time_control: TimeControlSettings::armageddon(),
+            ..human_vs_ai()
         }
     }
 }
@@ -320,6 +992,18 @@ impl GameConfig {
                 "No Castling" => DrawbackId::NoCastling,
                 "Pawns Advance One" => DrawbackId::PawnPushOneOnly,
                 "Random File Blocked" => DrawbackId::BlockRandomFile,
+                "Recycling" => DrawbackId::Recycling,
+                "Pacifist Opening" => DrawbackId::PacifistOpening,
+                "Edge-phobic" => DrawbackId::EdgePhobic,
+                "Follow the Leader" => DrawbackId::FollowTheLeader,
+                "Pawn Horde" => DrawbackId::PawnHorde,
+                "Mirror" => DrawbackId::Mirror,
+                "Claustrophobia" => DrawbackId::Claustrophobia,
+                "Vampire" => DrawbackId::Vampire,
+                "Glass Cannon" => DrawbackId::GlassCannon,
+                "Slippery Fingers" => DrawbackId::SlipperyFingers,
+                // Only known once a game starts (see `DrawbackRegistry::random_id`)
+                RANDOM_DRAWBACK => DrawbackId::None,
                 // Add more drawbacks here as they're implemented
                 _ => {
                     eprintln!("Unknown drawback name: {}", name);
@@ -331,6 +1015,16 @@ impl GameConfig {
                 1 => DrawbackId::NoCastling,
                 2 => DrawbackId::PawnPushOneOnly,
                 3 => DrawbackId::BlockRandomFile,
+                4 => DrawbackId::Recycling,
+                5 => DrawbackId::PacifistOpening,
+                6 => DrawbackId::EdgePhobic,
+                7 => DrawbackId::FollowTheLeader,
+                8 => DrawbackId::PawnHorde,
+                9 => DrawbackId::Mirror,
+                10 => DrawbackId::Claustrophobia,
+                11 => DrawbackId::Vampire,
+                12 => DrawbackId::GlassCannon,
+                13 => DrawbackId::SlipperyFingers,
                 // Add more drawbacks here as they're implemented
                 _ => {
                     eprintln!("Unknown drawback index: {}", index);
@@ -343,6 +1037,137 @@ impl GameConfig {
     }
 }
 
+/// File the settings changed in the menus are kept in between starts
+pub const CONFIG_FILE_PATH: &str = "drawback_chess_config.json";
+
+/// Resource with the settings the player changed in the menus, kept in
+/// `CONFIG_FILE_PATH`. Only settings that were changed are stored (None = as
+/// the preset has it), and never the players or their drawbacks, so what the
+/// modes set up for one session (daily challenge, kiosk, tutorial) isn't
+/// taken for the player's preference the next time.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedSettings {
+    pub low_power_mode: Option<bool>,
+    pub show_ai_thinking: Option<bool>,
+    pub show_ai_best_move_arrow: Option<bool>,
+    pub show_ai_observer_arrows: Option<bool>,
+    pub show_blocked_moves: Option<bool>,
+    pub show_drawback_overlays: Option<bool>,
+    pub show_rule_explanations: Option<bool>,
+    pub share_with_fen: Option<bool>,
+    pub sound_enabled: Option<bool>,
+    pub language: Option<String>,
+    pub delay_mode: Option<DelayMode>,
+    pub delay_ms: Option<u64>,
+    pub tt_size_mb: Option<u32>,
+    pub time_limit_ms: Option<u32>,
+    pub depth_limit: Option<u8>,
+    pub iteration_limit: Option<u32>,
+    pub check_quietness: Option<bool>,
+    pub quiescence_depth: Option<u8>,
+    pub opponent_model: Option<bool>,
+}
+
+impl SavedSettings {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Ok(serde_json::from_str(&text)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Remembers the settings a menu changed from `before` to `after`
+    pub fn record(&mut self, before: &GameConfig, after: &GameConfig) {
+        let (display, was) = (&after.display, &before.display);
+        remember(&mut self.low_power_mode, &was.low_power_mode, &display.low_power_mode);
+        remember(&mut self.show_ai_thinking, &was.show_ai_thinking, &display.show_ai_thinking);
+        remember(&mut self.show_ai_best_move_arrow, &was.show_ai_best_move_arrow, &display.show_ai_best_move_arrow);
+        remember(&mut self.show_ai_observer_arrows, &was.show_ai_observer_arrows, &display.show_ai_observer_arrows);
+        remember(&mut self.show_blocked_moves, &was.show_blocked_moves, &display.show_blocked_moves);
+        remember(&mut self.show_drawback_overlays, &was.show_drawback_overlays, &display.show_drawback_overlays);
+        remember(&mut self.show_rule_explanations, &was.show_rule_explanations, &display.show_rule_explanations);
+        remember(&mut self.share_with_fen, &was.share_with_fen, &display.share_with_fen);
+        remember(&mut self.sound_enabled, &was.sound_enabled, &display.sound_enabled);
+        remember(&mut self.language, &was.language, &display.language);
+
+        let (time_control, was) = (&after.time_control, &before.time_control);
+        remember(&mut self.delay_mode, &was.delay_mode, &time_control.delay_mode);
+        remember(&mut self.delay_ms, &was.delay_ms, &time_control.delay_ms);
+
+        let (ai, was) = (&after.ai_settings, &before.ai_settings);
+        remember(&mut self.tt_size_mb, &was.tt_size_mb, &ai.tt_size_mb);
+        remember(&mut self.time_limit_ms, &was.time_limit_ms, &ai.time_limit_ms);
+        remember(&mut self.depth_limit, &was.depth_limit, &ai.depth_limit);
+        remember(&mut self.iteration_limit, &was.iteration_limit, &ai.iteration_limit);
+        remember(&mut self.check_quietness, &was.check_quietness, &ai.check_quietness);
+        remember(&mut self.quiescence_depth, &was.quiescence_depth, &ai.quiescence_depth);
+        remember(&mut self.opponent_model, &was.opponent_model, &ai.opponent_model);
+    }
+
+    /// Puts the remembered settings into `config`
+    pub fn apply(&self, config: &mut GameConfig) {
+        let display = &mut config.display;
+        restore(&self.low_power_mode, &mut display.low_power_mode);
+        restore(&self.show_ai_thinking, &mut display.show_ai_thinking);
+        restore(&self.show_ai_best_move_arrow, &mut display.show_ai_best_move_arrow);
+        restore(&self.show_ai_observer_arrows, &mut display.show_ai_observer_arrows);
+        restore(&self.show_blocked_moves, &mut display.show_blocked_moves);
+        restore(&self.show_drawback_overlays, &mut display.show_drawback_overlays);
+        restore(&self.show_rule_explanations, &mut display.show_rule_explanations);
+        restore(&self.share_with_fen, &mut display.share_with_fen);
+        restore(&self.sound_enabled, &mut display.sound_enabled);
+        restore(&self.language, &mut display.language);
+
+        let time_control = &mut config.time_control;
+        restore(&self.delay_mode, &mut time_control.delay_mode);
+        restore(&self.delay_ms, &mut time_control.delay_ms);
+
+        let ai = &mut config.ai_settings;
+        restore(&self.tt_size_mb, &mut ai.tt_size_mb);
+        restore(&self.time_limit_ms, &mut ai.time_limit_ms);
+        restore(&self.depth_limit, &mut ai.depth_limit);
+        restore(&self.iteration_limit, &mut ai.iteration_limit);
+        restore(&self.check_quietness, &mut ai.check_quietness);
+        restore(&self.quiescence_depth, &mut ai.quiescence_depth);
+        restore(&self.opponent_model, &mut ai.opponent_model);
+    }
+
+    /// Records what a menu changed and saves it, reporting a failure on the console
+    pub fn record_and_save(&mut self, before: &GameConfig, after: &GameConfig) {
+        self.record(before, after);
+        if let Err(e) = self.save(CONFIG_FILE_PATH) {
+            eprintln!("Failed to save {}: {}", CONFIG_FILE_PATH, e);
+        }
+    }
+}
+
+// Helper function to remember a setting once it differs from what it was
+fn remember<T: Clone + PartialEq>(saved: &mut Option<T>, before: &T, after: &T) {
+    if before != after {
+        *saved = Some(after.clone());
+    }
+}
+
+// Helper function to put a remembered setting back
+fn restore<T: Clone>(saved: &Option<T>, value: &mut T) {
+    if let Some(saved) = saved {
+        *value = saved.clone();
+    }
+}
+
+/// System set of the PreStartup system putting the saved settings into the
+/// configuration. Modes that set up a session of their own (`--daily`,
+/// `--kiosk`) run after it, so their settings win for that session.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LoadSavedSettings;
+
 /// Plugin to handle game configuration
 pub struct ConfigPlugin;
 
@@ -354,15 +1179,34 @@ impl Plugin for ConfigPlugin {
         // Comment out the default config line:
         // app.insert_resource(GameConfig::default());
         
-        app.add_systems(Update, apply_config_to_game_state.run_if(resource_exists::<crate::game_logic::state::GameState>()));
+        // Settings can be given beforehand (e.g. by tests) instead of read from the file
+        if !app.world.contains_resource::<SavedSettings>() {
+            let saved = SavedSettings::load(CONFIG_FILE_PATH).unwrap_or_else(|e| {
+                eprintln!("Failed to read {}: {} (using the default settings)", CONFIG_FILE_PATH, e);
+                SavedSettings::default()
+            });
+            app.insert_resource(saved);
+        }
+        // The saved settings replace those of the presets the plugins put in
+        app.add_systems(PreStartup, apply_saved_settings.in_set(LoadSavedSettings));
+        app.add_systems(Update, apply_config_to_game_state.run_if(active_board_exists));
     }
 }
 
+/// System to put the settings saved last time into the configuration
+fn apply_saved_settings(saved: Res<SavedSettings>, mut config: ResMut<GameConfig>) {
+    saved.apply(&mut config);
+}
+
 /// System to apply configuration to game state
 fn apply_config_to_game_state(
     config: Res<GameConfig>,
-    mut game_state: ResMut<crate::game_logic::state::GameState>,
+    mut boards: Query<&mut GameState, With<ActiveBoard>>,
+    zobrist_keys: Res<crate::ai::zobrist::ZobristKeys>,
 ) {
+    let Ok(mut game_state) = boards.get_single_mut() else {
+        return;
+    };
     // Only apply once
     static mut APPLIED: bool = false;
     unsafe {
@@ -372,9 +1216,16 @@ fn apply_config_to_game_state(
         APPLIED = true;
     }
 
-    // Set drawbacks based on configuration
-    game_state.white_drawback = config.resolve_drawback_id(&config.white_player.drawback);
-    game_state.black_drawback = config.resolve_drawback_id(&config.black_player.drawback);
+    // Set drawbacks based on configuration (random ones were rolled when the game started)
+    if !config.white_player.drawback.is_random() {
+        game_state.white_drawback = config.resolve_drawback_id(&config.white_player.drawback);
+        game_state.white_drawback_params = config.white_player.drawback.params.clone();
+    }
+    if !config.black_player.drawback.is_random() {
+        game_state.black_drawback = config.resolve_drawback_id(&config.black_player.drawback);
+        game_state.black_drawback_params = config.black_player.drawback.params.clone();
+    }
+    game_state.zobrist_hash = game_state.position_key(&zobrist_keys);
     
     println!("Applied configuration:");
     println!("- White: AI={}, Drawback={:?}", 
@@ -1,3 +1,4 @@
+
 use bevy::prelude::*;
 
 pub const SPRITE_SIZE: f32 = 64.0;
@@ -20,10 +21,16 @@ pub const BLACK_SQUARE_COLOR: Color = Color::rgb(0.36, 0.25, 0.20); // Darker br
 // Colors for piece selection and move highlighting
 pub const SELECTED_COLOR: Color = Color::rgba(0.0, 0.5, 1.0, 0.5);  // Blue, semi-transparent
 pub const LEGAL_MOVE_COLOR: Color = Color::rgba(0.2, 0.8, 0.2, 0.7); // Bright green, more opaque
+pub const BLOCKED_MOVE_COLOR: Color = Color::rgba(0.5, 0.5, 0.5, 0.55); // Grey, for moves the drawback removed
 pub const HOVER_COLOR: Color = Color::rgba(0.0, 0.0, 1.0, 0.3);    // Blue, more transparent
+pub const LAST_MOVE_COLOR: Color = Color::rgba(1.0, 0.85, 0.1, 0.4); // Yellow, the squares of the last move
+pub const CHECK_GLOW_COLOR: Color = Color::rgba(1.0, 0.1, 0.1, 0.6); // Red, under an attacked king
 
 // Z-index constants for proper layering
 pub const Z_BOARD: f32 = 0.0;      // Base layer - board squares
+pub const Z_LAST_MOVE: f32 = 0.03; // Last move's squares (relative to the square)
+pub const Z_DRAWBACK_OVERLAY: f32 = 0.05; // Squares tinted by a drawback (relative to the square)
+pub const Z_CHECK_GLOW: f32 = 0.07; // Glow under an attacked king (relative to the square)
 pub const Z_HIGHLIGHT: f32 = 0.1;  // Selection highlight
 pub const Z_LEGAL_MOVES: f32 = 0.2; // Legal move indicators
 pub const Z_PIECES: f32 = 0.3;     // Chess pieces
@@ -1,17 +1,52 @@
-use shakmaty::{Chess, Move, File};
-use super::definition::DrawbackRule;
+use bevy::prelude::Color;
+use shakmaty::{Chess, Move, File, Square};
+use std::sync::Arc;
+use super::definition::{DrawbackRule, TurnContext};
+use super::params::DrawbackParams;
 use super::registry::DrawbackId;
 
-#[derive(Debug, Clone)]
-pub struct BlockRandomFile;
+// Tint of the file that is blocked this turn
+const BLOCKED_FILE_COLOR: Color = Color::rgba(0.8, 0.15, 0.15, 0.3);
+
+/// A random file is blocked each turn, or always the same one if `fixed_file` is set.
+/// Parameter: `file` (0-7 = a-h), which makes the blocked file fixed.
+#[derive(Debug, Clone, Default)]
+pub struct BlockRandomFile {
+    pub fixed_file: Option<u8>,
+}
+
+impl BlockRandomFile {
+    // The file blocked this turn: the fixed one, or else what the RNG rolled
+    fn blocked_file(&self, rng_outcome: Option<u8>) -> Option<u8> {
+        self.fixed_file.or(rng_outcome)
+    }
+
+    // The fixed file the descriptions name, if it is one of the 8 files
+    fn described_file(&self) -> Option<File> {
+        self.fixed_file.filter(|&index| index < 8).map(|index| File::new(index as u32))
+    }
+}
 
 impl DrawbackRule for BlockRandomFile {
     fn id(&self) -> DrawbackId { DrawbackId::BlockRandomFile }
     fn name(&self) -> &'static str { "Random File Blocked" }
-    fn description(&self) -> &'static str { "At the start of your turn, a random file (A-H) is chosen. You cannot move any piece TO that file this turn." }
+    fn description(&self) -> String {
+        match self.described_file() {
+            Some(file) => format!("You can never move any piece TO the {}-file.", file.char()),
+            None => "At the start of your turn, a random file (A-H) is chosen. You cannot move any piece TO that file this turn.".to_string(),
+        }
+    }
+
+    fn description_args(&self) -> Vec<(&'static str, String)> {
+        self.described_file().map(|file| vec![("file", file.char().to_string())]).unwrap_or_default()
+    }
+
+    fn description_variant(&self) -> Option<&'static str> {
+        self.described_file().map(|_| "fixed")
+    }
 
     fn needs_turn_rng(&self) -> bool {
-        true // This rule requires per-turn RNG
+        self.fixed_file.is_none() // This rule requires per-turn RNG unless the file is fixed
     }
 
     fn get_rng_outcomes(&self) -> u8 {
@@ -24,13 +59,11 @@ impl DrawbackRule for BlockRandomFile {
         moves: Vec<Move>,
         rng_outcome: Option<u8>, // Expecting 0-7 if RNG applies
     ) -> Vec<Move> {
-        if let Some(blocked_file_index) = rng_outcome {
+        if let Some(blocked_file_index) = self.blocked_file(rng_outcome) {
             if blocked_file_index < 8 {
                 // Create a file from index (0-7 = a-h)
                 let file_char = (b'a' + blocked_file_index) as char;
                 let blocked_file = File::from_char(file_char).unwrap();
-                
-                println!("Applying BlockRandomFile: File '{}' is blocked this turn.", blocked_file);
                 return moves.into_iter().filter(|mv| {
                     mv.to().file() != blocked_file
                 }).collect();
@@ -43,7 +76,39 @@ impl DrawbackRule for BlockRandomFile {
         moves
     }
 
-    fn check_loss_condition(&self, _position: &Chess, _legal_moves: &Vec<Move>) -> bool {
+    fn explain_piece(&self, _position: &Chess, _square: Square, turn: &TurnContext) -> Option<String> {
+        let index = self.blocked_file(turn.rng_outcome).filter(|&index| index < 8)?;
+        let how = if self.fixed_file.is_some() { "every turn" } else { "this turn, rolled at its start" };
+        Some(format!("Cannot move to the {}-file {}", File::new(index as u32).char(), how))
+    }
+
+    fn check_loss_condition(&self, _position: &Chess, _legal_moves: &[Move], _turn: &TurnContext) -> bool {
         false // No specific loss condition from this rule itself
     }
-} 
\ No newline at end of file
+
+    fn board_overlay(&self, _position: &Chess, rng_outcome: Option<u8>) -> Vec<(Square, Color)> {
+        match self.blocked_file(rng_outcome) {
+            Some(index) if index < 8 => {
+                let file = File::new(index as u32);
+                Square::ALL.into_iter().filter(|square| square.file() == file).map(|square| (square, BLOCKED_FILE_COLOR)).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn params(&self) -> DrawbackParams {
+        match self.fixed_file {
+            Some(file) => DrawbackParams::default().with("file", file as i32),
+            None => DrawbackParams::default(),
+        }
+    }
+
+    fn with_params(&self, params: &DrawbackParams) -> Option<Arc<dyn DrawbackRule + Send + Sync>> {
+        let fixed_file = match params.get("file") {
+            None => self.fixed_file,
+            Some(file) if (0..8).contains(&file) => Some(file as u8),
+            Some(_) => return None,
+        };
+        Some(Arc::new(Self { fixed_file }))
+    }
+}
@@ -0,0 +1,47 @@
+use bevy::prelude::Color;
+use shakmaty::{attacks, Bitboard, Chess, Move, Position, Role, Square};
+use super::definition::{DrawbackRule, TurnContext};
+use super::registry::DrawbackId;
+
+// Tint of the squares around the king
+const KING_ZONE_COLOR: Color = Color::rgba(0.6, 0.2, 0.7, 0.2);
+
+/// No piece may move onto one of the (up to) 8 squares around its own king.
+/// The king itself may go anywhere it could, castling included.
+#[derive(Debug, Clone)]
+pub struct Claustrophobia;
+
+// The squares around the side to move's king (none without a king)
+fn king_zone(position: &Chess) -> Bitboard {
+    position.board().king_of(position.turn()).map_or(Bitboard::EMPTY, attacks::king_attacks)
+}
+
+impl DrawbackRule for Claustrophobia {
+    fn id(&self) -> DrawbackId { DrawbackId::Claustrophobia }
+    fn name(&self) -> &'static str { "Claustrophobia" }
+    fn description(&self) -> String { "Your pieces may not move next to your own king. The king itself may still move and castle.".to_string() }
+
+    fn filter_pseudo_legal_moves(
+        &self,
+        position: &Chess,
+        moves: Vec<Move>,
+        _rng_outcome: Option<u8>, // Ignored
+    ) -> Vec<Move> {
+        let zone = king_zone(position);
+        // Castling is a king move too, wherever the rook ends up
+        moves.into_iter().filter(|mv| mv.role() == Role::King || !zone.contains(mv.to())).collect()
+    }
+
+    fn explain_piece(&self, position: &Chess, square: Square, _turn: &TurnContext) -> Option<String> {
+        let role = position.board().role_at(square)?;
+        (role != Role::King && king_zone(position).any()).then(|| "May not move next to your own king".to_string())
+    }
+
+    fn check_loss_condition(&self, _position: &Chess, _legal_moves: &[Move], _turn: &TurnContext) -> bool {
+        false
+    }
+
+    fn board_overlay(&self, position: &Chess, _rng_outcome: Option<u8>) -> Vec<(Square, Color)> {
+        king_zone(position).into_iter().map(|square| (square, KING_ZONE_COLOR)).collect()
+    }
+}
@@ -1,19 +1,54 @@
-use shakmaty::{Chess, Move};
+use bevy::prelude::Color;
+use shakmaty::{Chess, Move, Role, Square};
 use std::fmt::Debug;
- // Use Arc for sharing
+use std::sync::Arc; // Use Arc for sharing
+use super::params::DrawbackParams;
 use super::registry::DrawbackId; // Use the new ID type
 
+/// What a rule may know about a turn beyond its position: the game's state
+/// outside the board, as the game keeps it (`GameState::turn_context`) and as
+/// the AI's search follows it from node to node
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TurnContext<'a> {
+    pub rng_outcome: Option<u8>,     // This turn's roll, for rules with `needs_turn_rng`
+    pub last_move: Option<&'a Move>, // The opponent's move that led here (None on the first move)
+    pub moves_since_capture: u32,    // The owner's moves in a row without a capture
+}
+
+impl TurnContext<'_> {
+    /// The owner's moves in a row without a capture once they play `chess_move`:
+    /// a capture starts the count again
+    pub fn count_capture(moves_since_capture: u32, chess_move: &Move) -> u32 {
+        if chess_move.is_capture() { 0 } else { moves_since_capture + 1 }
+    }
+}
+
 /// Trait defining the interface for a Drawback rule.
 /// Must be `Send + Sync` and implement `Debug`.
 pub trait DrawbackRule: Send + Sync + Debug {
     /// Returns the unique Enum ID for this drawback.
     fn id(&self) -> DrawbackId;
 
-    /// Gets a human-readable name.
+    /// Gets the built-in (English) name, also used in configs and saved stats.
+    /// The UI shows `Localization::drawback_name` instead, which falls back to this.
     fn name(&self) -> &'static str;
 
-    /// Gets a description of the rule.
-    fn description(&self) -> &'static str;
+    /// Gets the built-in (English) description of the rule, with its parameters filled in.
+    /// The UI shows `Localization::drawback_description` instead.
+    fn description(&self) -> String;
+
+    /// Values of the `{ $name }` placeables in the translated description, taken
+    /// from the parameters of this instance (e.g. `minimum` for Pawn Horde)
+    fn description_args(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    /// Picks another translated description for parameters that change what
+    /// the rule says: `drawback-<key>-<variant>-description` instead of
+    /// `drawback-<key>-description`
+    fn description_variant(&self) -> Option<&'static str> {
+        None
+    }
 
     /// Indicates if this drawback requires a random value determined at the START of the owning player's turn.
     /// For example, a rule blocking a random file needs this to be true.
@@ -32,6 +67,8 @@ pub trait DrawbackRule: Send + Sync + Debug {
     /// `moves`: The list of moves generated so far (possibly filtered by other means).
     /// `rng_outcome`: The result of the per-turn RNG (0 to N-1), if `needs_turn_rng` was true for this rule.
     /// It should NOT check for leaving the king in check unless that is part of the rule itself.
+    /// Rules that grant moves add them to the list here (e.g. `Mirror`'s diagonal
+    /// pawn steps); those have to keep the king out of check themselves.
     fn filter_pseudo_legal_moves(
         &self,
         position: &Chess,
@@ -39,11 +76,68 @@ pub trait DrawbackRule: Send + Sync + Debug {
         rng_outcome: Option<u8>, // Added RNG outcome parameter
     ) -> Vec<Move>;
 
+    /// Filters the moves of a turn like `filter_pseudo_legal_moves`, knowing the
+    /// rest of the turn's context (the opponent's last move, the owner's moves
+    /// without a capture). This is what the game and the AI's search call; only
+    /// rules that depend on more than the position and the roll override it.
+    fn filter_moves(&self, position: &Chess, moves: Vec<Move>, turn: &TurnContext) -> Vec<Move> {
+        self.filter_pseudo_legal_moves(position, moves, turn.rng_outcome)
+    }
+
     /// Checks if a specific loss condition imposed by this drawback is met.
     /// `position`: The state AFTER the opponent's last move (it's the current player's turn).
     /// `legal_moves`: The list of moves available to the current player *after all filtering*.
+    /// `turn`: The turn's context, as passed to `filter_moves`.
     /// Returns `true` if the current player loses due to this rule.
-    fn check_loss_condition(&self, position: &Chess, legal_moves: &Vec<Move>) -> bool;
+    fn check_loss_condition(&self, position: &Chess, legal_moves: &[Move], turn: &TurnContext) -> bool;
+
+    /// The move actually played when the owner picks `chess_move`, for rules that
+    /// change a move after it was chosen (None plays it as picked). Called once
+    /// the move has passed the filter; the substitute must be legal as well.
+    /// `rng_outcome` as in `TurnContext`. The AI's search doesn't foresee it.
+    fn substitute_move(&self, _position: &Chess, _chess_move: &Move, _rng_outcome: Option<u8>) -> Option<Move> {
+        None
+    }
+
+    /// Whether a piece of this kind the owner loses to a capture goes back into
+    /// their hand, to be dropped later (see `game_logic::drops`).
+    fn returns_lost_piece(&self, _role: Role) -> bool {
+        false
+    }
+
+    /// Whether a piece of this kind the owner captures goes into their own hand.
+    /// Where it may be dropped is up to `filter_pseudo_legal_moves` (drops are `Move::Put`).
+    fn keeps_captured_piece(&self, _role: Role) -> bool {
+        false
+    }
+
+    /// Squares to tint on the board while the owner is to move, each with its tint
+    /// (e.g. a zone the owner may not move to). `rng_outcome` is this turn's RNG
+    /// result, so rules with `needs_turn_rng` can show what was rolled.
+    fn board_overlay(&self, _position: &Chess, _rng_outcome: Option<u8>) -> Vec<(Square, Color)> {
+        Vec::new()
+    }
+
+    /// Explains (in English, like `description`) how the rule holds back the
+    /// owner's piece on `square` this turn, for the piece tooltip, e.g. "Cannot
+    /// capture before move 6". Called with the owner to move and the turn's
+    /// context, as passed to `filter_moves`. None if the piece isn't affected.
+    fn explain_piece(&self, _position: &Chess, _square: Square, _turn: &TurnContext) -> Option<String> {
+        None
+    }
+
+    /// The parameters this instance was built with (see `with_params`);
+    /// empty for rules without any.
+    fn params(&self) -> DrawbackParams {
+        DrawbackParams::default()
+    }
+
+    /// A copy of the rule using the given parameters, the ones it doesn't know
+    /// left at their defaults. None if the rule takes no parameters or a value
+    /// is out of range.
+    fn with_params(&self, _params: &DrawbackParams) -> Option<Arc<dyn DrawbackRule + Send + Sync>> {
+        None
+    }
 
     // Potential future methods...
 } 
\ No newline at end of file
@@ -0,0 +1,110 @@
+use bevy::prelude::Color;
+use shakmaty::{Bitboard, Chess, Move, Position, Role, Square};
+use std::sync::Arc;
+use super::definition::{DrawbackRule, TurnContext};
+use super::params::DrawbackParams;
+use super::registry::DrawbackId;
+
+// The a/h files and the 1st/8th ranks
+const RIM: Bitboard = Bitboard(0xff81_8181_8181_81ff);
+
+// Tint of the squares on the rim of the board
+const RIM_COLOR: Color = Color::rgba(0.8, 0.5, 0.1, 0.2);
+
+/// No piece may move onto the a/h files or the 1st/8th ranks.
+/// Castling (onto the king's own back rank) and promoting (onto the last rank)
+/// are exceptions unless switched off, as otherwise the king could never castle
+/// and pawns would be stuck on the 7th rank for good.
+/// Parameters: `castling_exempt` and `promotion_exempt` (1 = exempt, 0 = not).
+#[derive(Debug, Clone)]
+pub struct EdgePhobic {
+    pub castling_exempt: bool,
+    pub promotion_exempt: bool,
+}
+
+impl Default for EdgePhobic {
+    fn default() -> Self {
+        Self {
+            castling_exempt: true,
+            promotion_exempt: true,
+        }
+    }
+}
+
+impl EdgePhobic {
+    fn allows(&self, mv: &Move) -> bool {
+        match mv {
+            Move::Castle { .. } => self.castling_exempt,
+            Move::Normal { promotion: Some(_), .. } => self.promotion_exempt,
+            _ => !RIM.contains(mv.to()),
+        }
+    }
+}
+
+impl DrawbackRule for EdgePhobic {
+    fn id(&self) -> DrawbackId { DrawbackId::EdgePhobic }
+    fn name(&self) -> &'static str { "Edge-phobic" }
+    fn description(&self) -> String {
+        let exceptions = match (self.castling_exempt, self.promotion_exempt) {
+            (true, true) => " Castling and promoting are still allowed.",
+            (true, false) => " Castling is still allowed, promoting is not.",
+            (false, true) => " Promoting is still allowed, castling is not.",
+            (false, false) => " Castling and promoting aren't allowed either.",
+        };
+        format!("Your pieces may not move onto the a/h files or the 1st/8th ranks.{}", exceptions)
+    }
+
+    fn description_variant(&self) -> Option<&'static str> {
+        match (self.castling_exempt, self.promotion_exempt) {
+            (true, true) => None,
+            (true, false) => Some("castling"),
+            (false, true) => Some("promotion"),
+            (false, false) => Some("strict"),
+        }
+    }
+
+    fn filter_pseudo_legal_moves(
+        &self,
+        _position: &Chess,
+        moves: Vec<Move>,
+        _rng_outcome: Option<u8>, // Ignored
+    ) -> Vec<Move> {
+        moves.into_iter().filter(|mv| self.allows(mv)).collect()
+    }
+
+    fn explain_piece(&self, position: &Chess, square: Square, _turn: &TurnContext) -> Option<String> {
+        let exemption = match position.board().role_at(square) {
+            Some(Role::King) if self.castling_exempt => " (castling still is)",
+            Some(Role::Pawn) if self.promotion_exempt => " (promoting still is)",
+            _ => "",
+        };
+        Some(format!("Moving onto the a/h files or the 1st/8th ranks is not allowed{}", exemption))
+    }
+
+    fn check_loss_condition(&self, _position: &Chess, _legal_moves: &[Move], _turn: &TurnContext) -> bool {
+        false
+    }
+
+    fn board_overlay(&self, _position: &Chess, _rng_outcome: Option<u8>) -> Vec<(Square, Color)> {
+        RIM.into_iter().map(|square| (square, RIM_COLOR)).collect()
+    }
+
+    fn params(&self) -> DrawbackParams {
+        DrawbackParams::default()
+            .with("castling_exempt", self.castling_exempt as i32)
+            .with("promotion_exempt", self.promotion_exempt as i32)
+    }
+
+    fn with_params(&self, params: &DrawbackParams) -> Option<Arc<dyn DrawbackRule + Send + Sync>> {
+        let flag = |name: &str, default: bool| match params.get(name) {
+            None => Some(default),
+            Some(0) => Some(false),
+            Some(1) => Some(true),
+            Some(_) => None,
+        };
+        Some(Arc::new(Self {
+            castling_exempt: flag("castling_exempt", self.castling_exempt)?,
+            promotion_exempt: flag("promotion_exempt", self.promotion_exempt)?,
+        }))
+    }
+}
@@ -0,0 +1,45 @@
+use shakmaty::{Chess, Move, Position, Square};
+use super::definition::{DrawbackRule, TurnContext};
+use super::registry::DrawbackId;
+
+#[derive(Debug, Clone)]
+pub struct FollowTheLeader;
+
+impl DrawbackRule for FollowTheLeader {
+    fn id(&self) -> DrawbackId { DrawbackId::FollowTheLeader }
+    fn name(&self) -> &'static str { "Follow the Leader" }
+    fn description(&self) -> String { "You must move the same kind of piece your opponent just moved, if you can.".to_string() }
+
+    fn filter_pseudo_legal_moves(
+        &self,
+        _position: &Chess,
+        moves: Vec<Move>,
+        _rng_outcome: Option<u8>, // Ignored
+    ) -> Vec<Move> {
+        // Without the opponent's last move there is nothing to follow
+        moves
+    }
+
+    fn filter_moves(&self, _position: &Chess, moves: Vec<Move>, turn: &TurnContext) -> Vec<Move> {
+        // White's first move (or the first move from a set-up position) is free
+        let Some(leader) = turn.last_move.map(Move::role) else {
+            return moves;
+        };
+        // Castling counts as a king move, promoting as a pawn move
+        let following: Vec<Move> = moves.iter().filter(|mv| mv.role() == leader).cloned().collect();
+        if following.is_empty() { moves } else { following }
+    }
+
+    fn explain_piece(&self, position: &Chess, square: Square, turn: &TurnContext) -> Option<String> {
+        let leader = turn.last_move?.role();
+        if position.board().role_at(square) == Some(leader) {
+            return None;
+        }
+        let leader = format!("{:?}", leader).to_lowercase();
+        Some(format!("Your opponent moved a {}: this piece may only move if no {} can", leader, leader))
+    }
+
+    fn check_loss_condition(&self, _position: &Chess, _legal_moves: &[Move], _turn: &TurnContext) -> bool {
+        false
+    }
+}
@@ -0,0 +1,84 @@
+use shakmaty::{Bitboard, Chess, Color, Move, Position, Role, Square};
+use crate::ai::evaluation::is_defended;
+use super::definition::{DrawbackRule, TurnContext};
+use super::registry::DrawbackId;
+
+/// Your queen may never be defended: no move may leave one of your pieces
+/// newly protecting it (directly or by uncovering a line), and the queen may
+/// not go to a square your pieces protect. Protection it already had stays.
+#[derive(Debug, Clone)]
+pub struct GlassCannon;
+
+impl DrawbackRule for GlassCannon {
+    fn id(&self) -> DrawbackId { DrawbackId::GlassCannon }
+    fn name(&self) -> &'static str { "Glass Cannon" }
+    fn description(&self) -> String { "Your queen may never be defended: no move may protect it, and it may not move to a protected square.".to_string() }
+
+    fn filter_pseudo_legal_moves(
+        &self,
+        position: &Chess,
+        moves: Vec<Move>,
+        _rng_outcome: Option<u8>, // Ignored
+    ) -> Vec<Move> {
+        moves.into_iter().filter(|mv| !defends_queen(position, mv)).collect()
+    }
+
+    fn explain_piece(&self, position: &Chess, square: Square, _turn: &TurnContext) -> Option<String> {
+        (position.board().role_at(square)? == Role::Queen).then(|| "Can't be defended by your other pieces".to_string())
+    }
+
+    fn check_loss_condition(&self, _position: &Chess, _legal_moves: &[Move], _turn: &TurnContext) -> bool {
+        false
+    }
+}
+
+/// Whether the move leaves a queen of the side to move protected by a piece
+/// that didn't protect it before, or puts a queen on a protected square
+pub fn defends_queen(position: &Chess, chess_move: &Move) -> bool {
+    let us = position.turn();
+    let mut after = position.clone();
+    after.play_unchecked(chess_move);
+
+    // A queen arriving (moved, promoted or dropped) may not land under protection
+    let arrived = (chess_move.role() == Role::Queen || chess_move.promotion() == Some(Role::Queen))
+        .then(|| chess_move.to());
+    if arrived.is_some_and(|square| is_defended(&after, square)) {
+        return true;
+    }
+
+    let queens = after.board().queens() & after.board().by_color(us);
+    queens.into_iter().filter(|&queen| Some(queen) != arrived).any(|queen| {
+        let before = moved_defenders(defenders(position, queen, us), chess_move, us);
+        (defenders(&after, queen, us) & !before).any()
+    })
+}
+
+// Helper function: pieces of `us` protecting `square`
+fn defenders(position: &Chess, square: Square, us: Color) -> Bitboard {
+    let board = position.board();
+    board.attacks_to(square, us, board.occupied())
+}
+
+// Helper function: the squares of `defenders` once the move has taken the
+// pieces among them along, so a defender that moves on isn't counted as new
+fn moved_defenders(defenders: Bitboard, chess_move: &Move, us: Color) -> Bitboard {
+    // Where each piece the move takes along ends up (castling moves two at once)
+    let carried = match *chess_move {
+        Move::Castle { king, rook } => match chess_move.castling_side() {
+            Some(side) => vec![(king, side.king_to(us)), (rook, side.rook_to(us))],
+            None => Vec::new(),
+        },
+        Move::Normal { from, to, .. } | Move::EnPassant { from, to } => vec![(from, to)],
+        Move::Put { .. } => Vec::new(),
+    };
+    let mut moved = defenders;
+    for &(from, _) in &carried {
+        moved.discard(from);
+    }
+    for (from, to) in carried {
+        if defenders.contains(from) {
+            moved.add(to);
+        }
+    }
+    moved
+}
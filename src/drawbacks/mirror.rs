@@ -0,0 +1,72 @@
+use shakmaty::{attacks, Chess, Color, Move, Position, Role, Square};
+use crate::game_logic::perspective::PlayerPerspective;
+use super::definition::{DrawbackRule, TurnContext};
+use super::registry::DrawbackId;
+
+// Pieces a pawn may promote to, as in normal chess
+const PROMOTION_ROLES: [Role; 4] = [Role::Knight, Role::Bishop, Role::Rook, Role::Queen];
+
+/// Grants moves instead of taking them away: pawns may also step one square
+/// diagonally forward onto an empty square
+#[derive(Debug, Clone)]
+pub struct Mirror;
+
+impl DrawbackRule for Mirror {
+    fn id(&self) -> DrawbackId { DrawbackId::Mirror }
+    fn name(&self) -> &'static str { "Mirror" }
+    fn description(&self) -> String { "Your pawns may also move one square diagonally forward without capturing.".to_string() }
+
+    fn filter_pseudo_legal_moves(
+        &self,
+        position: &Chess,
+        mut moves: Vec<Move>,
+        _rng_outcome: Option<u8>, // Ignored
+    ) -> Vec<Move> {
+        moves.extend(diagonal_steps(position));
+        moves
+    }
+
+    fn check_loss_condition(&self, _position: &Chess, _legal_moves: &[Move], _turn: &TurnContext) -> bool {
+        false
+    }
+}
+
+/// Non-capturing diagonal steps of the side to move's pawns that don't leave
+/// its king in check. A step onto the last rank comes once per promotion piece.
+pub fn diagonal_steps(position: &Chess) -> Vec<Move> {
+    let us = position.turn();
+    let board = position.board();
+    let last_rank = |square: Square| PlayerPerspective::new(us).rank(square) == 7;
+    let mut moves = Vec::new();
+    for from in board.pawns() & board.by_color(us) {
+        for to in attacks::pawn_attacks(us, from) & !board.occupied() {
+            let promotions = if last_rank(to) { PROMOTION_ROLES.map(Some).to_vec() } else { vec![None] };
+            moves.extend(
+                promotions
+                    .into_iter()
+                    .map(|promotion| Move::Normal { role: Role::Pawn, from, capture: None, to, promotion })
+                    .filter(|step| keeps_king_safe(position, step, us)),
+            );
+        }
+    }
+    moves
+}
+
+/// The diagonal step of the side to move's pawn from `from` to `to`, if it
+/// can make one (for reading moves the notation alone can't tell apart)
+pub fn diagonal_step(position: &Chess, from: Square, to: Square, promotion: Option<Role>) -> Option<Move> {
+    if promotion.is_some_and(|role| !PROMOTION_ROLES.contains(&role)) {
+        return None;
+    }
+    diagonal_steps(position)
+        .into_iter()
+        .find(|step| step.from() == Some(from) && step.to() == to && step.promotion() == promotion)
+}
+
+// Helper function: whether `us` is out of check after the move
+fn keeps_king_safe(position: &Chess, chess_move: &Move, us: Color) -> bool {
+    let mut after = position.clone();
+    after.play_unchecked(chess_move);
+    let board = after.board();
+    board.king_of(us).is_none_or(|king| board.attacks_to(king, !us, board.occupied()).is_empty())
+}
@@ -1,11 +1,23 @@
 // Publicly export core definitions and the plugin
 pub mod definition;
 pub mod registry;
+pub mod params;
 pub mod no_castling;
 pub mod pawn_push_one;
 pub mod block_random_file;
+pub mod recycling;
+pub mod pacifist_opening;
+pub mod edge_phobic;
+pub mod follow_the_leader;
+pub mod pawn_horde;
+pub mod mirror;
+pub mod claustrophobia;
+pub mod vampire;
+pub mod glass_cannon;
+pub mod slippery_fingers;
 
 pub use registry::{DrawbackRegistry, DrawbackId, DrawbacksPlugin};
+pub use params::DrawbackParams;
 
 // Modules for specific drawback implementations
 // ... include modules for all other drawbacks ... 
\ No newline at end of file
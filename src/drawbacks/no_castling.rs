@@ -1,5 +1,5 @@
-use shakmaty::{Chess, Move};
-use super::definition::DrawbackRule;
+use shakmaty::{Chess, Move, Position, Role, Square};
+use super::definition::{DrawbackRule, TurnContext};
 use super::registry::DrawbackId; // Use the ID enum
 
 #[derive(Debug, Clone)]
@@ -8,7 +8,7 @@ pub struct NoCastling;
 impl DrawbackRule for NoCastling {
     fn id(&self) -> DrawbackId { DrawbackId::NoCastling } // Return Enum ID
     fn name(&self) -> &'static str { "No Castling" }
-    fn description(&self) -> &'static str { "Castling (Kingside or Queenside) is not allowed." }
+    fn description(&self) -> String { "Castling (Kingside or Queenside) is not allowed.".to_string() }
 
     fn filter_pseudo_legal_moves(
         &self,
@@ -19,7 +19,12 @@ impl DrawbackRule for NoCastling {
         moves.into_iter().filter(|mv| !matches!(mv, Move::Castle { .. })).collect()
     }
 
-    fn check_loss_condition(&self, _position: &Chess, _legal_moves: &Vec<Move>) -> bool {
+    fn explain_piece(&self, position: &Chess, square: Square, _turn: &TurnContext) -> Option<String> {
+        let king = position.board().role_at(square) == Some(Role::King);
+        (king && position.castles().has_color(position.turn())).then(|| "This king may not castle".to_string())
+    }
+
+    fn check_loss_condition(&self, _position: &Chess, _legal_moves: &[Move], _turn: &TurnContext) -> bool {
         false
     }
 } 
\ No newline at end of file
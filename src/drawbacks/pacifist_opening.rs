@@ -0,0 +1,61 @@
+use shakmaty::{Chess, Move, Position, Square};
+use std::sync::Arc;
+use super::definition::{DrawbackRule, TurnContext};
+use super::params::DrawbackParams;
+use super::registry::DrawbackId;
+
+// First full move on which captures are allowed again
+const FIRST_CAPTURE_MOVE: u32 = 6;
+
+/// No captures before move `first_capture_move`.
+/// Parameter: `first_capture_move` (1 or later).
+#[derive(Debug, Clone)]
+pub struct PacifistOpening {
+    pub first_capture_move: u32,
+}
+
+impl Default for PacifistOpening {
+    fn default() -> Self {
+        Self { first_capture_move: FIRST_CAPTURE_MOVE }
+    }
+}
+
+impl DrawbackRule for PacifistOpening {
+    fn id(&self) -> DrawbackId { DrawbackId::PacifistOpening }
+    fn name(&self) -> &'static str { "Pacifist Opening" }
+    fn description(&self) -> String { format!("You may not capture anything (en passant included) before move {}.", self.first_capture_move) }
+
+    fn description_args(&self) -> Vec<(&'static str, String)> {
+        vec![("first_capture_move", self.first_capture_move.to_string())]
+    }
+
+    fn filter_pseudo_legal_moves(
+        &self,
+        position: &Chess,
+        moves: Vec<Move>,
+        _rng_outcome: Option<u8>, // Ignored
+    ) -> Vec<Move> {
+        if position.fullmoves().get() >= self.first_capture_move {
+            return moves;
+        }
+        // `is_capture` covers en passant, where the target square is empty
+        moves.into_iter().filter(|mv| !mv.is_capture()).collect()
+    }
+
+    fn explain_piece(&self, position: &Chess, _square: Square, _turn: &TurnContext) -> Option<String> {
+        (position.fullmoves().get() < self.first_capture_move).then(|| format!("Cannot capture before move {}", self.first_capture_move))
+    }
+
+    fn check_loss_condition(&self, _position: &Chess, _legal_moves: &[Move], _turn: &TurnContext) -> bool {
+        false
+    }
+
+    fn params(&self) -> DrawbackParams {
+        DrawbackParams::default().with("first_capture_move", self.first_capture_move as i32)
+    }
+
+    fn with_params(&self, params: &DrawbackParams) -> Option<Arc<dyn DrawbackRule + Send + Sync>> {
+        let first_capture_move = params.get("first_capture_move").unwrap_or(self.first_capture_move as i32);
+        (first_capture_move >= 1).then(|| Arc::new(Self { first_capture_move: first_capture_move as u32 }) as Arc<dyn DrawbackRule + Send + Sync>)
+    }
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Named integer parameters of a drawback instance, e.g. `minimum = 5` for a
+/// Pawn Horde that needs more pawns. Empty means the rule's defaults; a rule
+/// reads the ones it knows in `DrawbackRule::with_params`. Kept sorted so
+/// equal parameters always print (and hash) the same.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DrawbackParams(pub BTreeMap<String, i32>);
+
+impl DrawbackParams {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<i32> {
+        self.0.get(name).copied()
+    }
+
+    /// Adds (or replaces) a parameter
+    pub fn with(mut self, name: &str, value: i32) -> Self {
+        self.0.insert(name.to_string(), value);
+        self
+    }
+
+    /// Parses the "name=value,name=value" form written by `Display` (PGN tags)
+    pub fn from_tag(text: &str) -> Result<Self, String> {
+        let mut params = Self::default();
+        for pair in text.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let (name, value) = pair.split_once('=').ok_or_else(|| format!("Expected name=value, got '{}'", pair))?;
+            let value = value.trim().parse().map_err(|_| format!("Invalid value for {}: '{}'", name.trim(), value.trim()))?;
+            params = params.with(name.trim(), value);
+        }
+        Ok(params)
+    }
+
+    /// Stable 64-bit digest (FNV-1a) for the Zobrist key; 0 for no parameters
+    pub fn fingerprint(&self) -> u64 {
+        if self.is_empty() {
+            return 0;
+        }
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in self.to_string().bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        hash
+    }
+}
+
+impl fmt::Display for DrawbackParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pairs: Vec<String> = self.0.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+        write!(f, "{}", pairs.join(","))
+    }
+}
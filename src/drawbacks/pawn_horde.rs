@@ -0,0 +1,64 @@
+use shakmaty::{Chess, Move, Position, Role, Square};
+use std::sync::Arc;
+use super::definition::{DrawbackRule, TurnContext};
+use super::params::DrawbackParams;
+use super::registry::DrawbackId;
+
+// Fewest pawns a player with the drawback may have
+const DEFAULT_MINIMUM_PAWNS: usize = 4;
+
+/// You lose as soon as you have fewer than `minimum` pawns (checked at the start
+/// of your turn, so losing a pawn to a capture or to promotion both count).
+/// Parameter: `minimum` (0-8).
+#[derive(Debug, Clone)]
+pub struct PawnHorde {
+    pub minimum: usize,
+}
+
+impl Default for PawnHorde {
+    fn default() -> Self {
+        Self { minimum: DEFAULT_MINIMUM_PAWNS }
+    }
+}
+
+impl DrawbackRule for PawnHorde {
+    fn id(&self) -> DrawbackId { DrawbackId::PawnHorde }
+    fn name(&self) -> &'static str { "Pawn Horde" }
+    fn description(&self) -> String { format!("You lose as soon as you have fewer than {} pawns.", self.minimum) }
+
+    fn description_args(&self) -> Vec<(&'static str, String)> {
+        vec![("minimum", self.minimum.to_string())]
+    }
+
+    fn filter_pseudo_legal_moves(
+        &self,
+        _position: &Chess,
+        moves: Vec<Move>,
+        _rng_outcome: Option<u8>, // Ignored
+    ) -> Vec<Move> {
+        moves
+    }
+
+    fn explain_piece(&self, position: &Chess, square: Square, _turn: &TurnContext) -> Option<String> {
+        if position.board().role_at(square) != Some(Role::Pawn) {
+            return None;
+        }
+        let board = position.board();
+        let pawns = (board.pawns() & board.by_color(position.turn())).count();
+        Some(format!("You lose with fewer than {} pawns ({} left)", self.minimum, pawns))
+    }
+
+    fn check_loss_condition(&self, position: &Chess, _legal_moves: &[Move], _turn: &TurnContext) -> bool {
+        let board = position.board();
+        (board.pawns() & board.by_color(position.turn())).count() < self.minimum
+    }
+
+    fn params(&self) -> DrawbackParams {
+        DrawbackParams::default().with("minimum", self.minimum as i32)
+    }
+
+    fn with_params(&self, params: &DrawbackParams) -> Option<Arc<dyn DrawbackRule + Send + Sync>> {
+        let minimum = params.get("minimum").unwrap_or(self.minimum as i32);
+        (0..=8).contains(&minimum).then(|| Arc::new(Self { minimum: minimum as usize }) as Arc<dyn DrawbackRule + Send + Sync>)
+    }
+}
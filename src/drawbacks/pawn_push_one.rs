@@ -1,5 +1,6 @@
-use shakmaty::{Chess, Move, Role};
-use super::definition::DrawbackRule;
+use shakmaty::{Chess, Move, Position, Role, Square};
+use crate::game_logic::perspective::PlayerPerspective;
+use super::definition::{DrawbackRule, TurnContext};
 use super::registry::DrawbackId; // Use the ID enum
 
 #[derive(Debug, Clone)]
@@ -8,34 +9,32 @@ pub struct PawnPushOneOnly;
 impl DrawbackRule for PawnPushOneOnly {
     fn id(&self) -> DrawbackId { DrawbackId::PawnPushOneOnly } // Return Enum ID
     fn name(&self) -> &'static str { "Pawns Advance One" }
-    fn description(&self) -> &'static str { "Pawns may not advance two squares on their first move." }
+    fn description(&self) -> String { "Pawns may not advance two squares on their first move.".to_string() }
 
     fn filter_pseudo_legal_moves(
         &self,
-        _position: &Chess,
+        position: &Chess,
         moves: Vec<Move>,
         _rng_outcome: Option<u8>, // Ignored
     ) -> Vec<Move> {
+         let perspective = PlayerPerspective::new(position.turn());
          moves.into_iter().filter(|mv| {
              match mv {
-                Move::Normal { role, from, to, .. } => {
-                    if *role == Role::Pawn {
-                         // Calculate absolute difference between ranks
-                         let from_rank = from.rank().char() as i8 - '1' as i8;
-                         let to_rank = to.rank().char() as i8 - '1' as i8;
-                         let rank_diff = (to_rank - from_rank).abs();
-                         
-                         rank_diff != 2 // Allow only if NOT a double push
-                    } else {
-                        true // Allow non-pawn moves
-                    }
+                Move::Normal { role: Role::Pawn, from, to, .. } => {
+                     perspective.ranks_forward(*from, *to) != 2 // Allow only if NOT a double push
                 }
-                 _ => true, // Allow castling, en passant (these might be filtered by other rules later)
+                 _ => true, // Allow non-pawn moves, castling, en passant (these might be filtered by other rules later)
              }
          }).collect()
     }
 
-    fn check_loss_condition(&self, _position: &Chess, _legal_moves: &Vec<Move>) -> bool {
+    fn explain_piece(&self, position: &Chess, square: Square, _turn: &TurnContext) -> Option<String> {
+        let on_start_rank = PlayerPerspective::new(position.turn()).rank(square) == 1;
+        let unmoved_pawn = position.board().role_at(square) == Some(Role::Pawn) && on_start_rank;
+        unmoved_pawn.then(|| "This pawn may not advance two squares".to_string())
+    }
+
+    fn check_loss_condition(&self, _position: &Chess, _legal_moves: &[Move], _turn: &TurnContext) -> bool {
         false
     }
 } 
\ No newline at end of file
@@ -0,0 +1,32 @@
+use shakmaty::{Chess, Move, Position, Role};
+use crate::game_logic::perspective::PlayerPerspective;
+use super::definition::{DrawbackRule, TurnContext};
+use super::registry::DrawbackId;
+
+#[derive(Debug, Clone)]
+pub struct Recycling;
+
+impl DrawbackRule for Recycling {
+    fn id(&self) -> DrawbackId { DrawbackId::Recycling }
+    fn name(&self) -> &'static str { "Recycling" }
+    fn description(&self) -> String { "Pawns you capture go to your reserve. Instead of moving, you may drop one on an empty square in your own half of the board.".to_string() }
+
+    fn filter_pseudo_legal_moves(
+        &self,
+        position: &Chess,
+        moves: Vec<Move>,
+        _rng_outcome: Option<u8>, // Ignored
+    ) -> Vec<Move> {
+        let perspective = PlayerPerspective::new(position.turn());
+        let own_half = |mv: &Move| perspective.in_own_half(mv.to());
+        moves.into_iter().filter(|mv| !matches!(mv, Move::Put { .. }) || own_half(mv)).collect()
+    }
+
+    fn check_loss_condition(&self, _position: &Chess, _legal_moves: &[Move], _turn: &TurnContext) -> bool {
+        false
+    }
+
+    fn keeps_captured_piece(&self, role: Role) -> bool {
+        role == Role::Pawn
+    }
+}
@@ -1,11 +1,24 @@
 use bevy::prelude::*;
 use std::collections::HashMap;
 use std::sync::Arc;
+use rand::Rng;
+use rand::seq::SliceRandom;
 use super::definition::DrawbackRule;
+use super::params::DrawbackParams;
 // Import the actual drawback structs:
 use super::no_castling::NoCastling;
 use super::pawn_push_one::PawnPushOneOnly;
 use super::block_random_file::BlockRandomFile;
+use super::recycling::Recycling;
+use super::pacifist_opening::PacifistOpening;
+use super::edge_phobic::EdgePhobic;
+use super::follow_the_leader::FollowTheLeader;
+use super::pawn_horde::PawnHorde;
+use super::mirror::Mirror;
+use super::claustrophobia::Claustrophobia;
+use super::vampire::Vampire;
+use super::glass_cannon::GlassCannon;
+use super::slippery_fingers::SlipperyFingers;
 
 /// Enum of all available drawbacks.
 /// This enum provides a way to:
@@ -18,6 +31,16 @@ pub enum DrawbackId {
     NoCastling,
     PawnPushOneOnly,
     BlockRandomFile,
+    Recycling,
+    PacifistOpening,
+    EdgePhobic,
+    FollowTheLeader,
+    PawnHorde,
+    Mirror,
+    Claustrophobia,
+    Vampire,
+    GlassCannon,
+    SlipperyFingers,
     // ... Add all other drawback IDs here ...
     // Example: CannotCaptureKnights,
     // Example: KingMustMoveForward,
@@ -27,15 +50,38 @@ pub enum DrawbackId {
 impl DrawbackId {
     /// Get a numeric index for Zobrist hashing
     /// Must be unique across all drawbacks
-    pub fn to_key_index(&self) -> u16 {
+    pub fn to_key_index(self) -> u16 {
         match self {
             Self::None => 0,
             Self::NoCastling => 1,
             Self::PawnPushOneOnly => 2,
             Self::BlockRandomFile => 3,
+            Self::Recycling => 4,
+            Self::PacifistOpening => 5,
+            Self::EdgePhobic => 6,
+            Self::FollowTheLeader => 7,
+            Self::PawnHorde => 8,
+            Self::Mirror => 9,
+            Self::Claustrophobia => 10,
+            Self::Vampire => 11,
+            Self::GlassCannon => 12,
+            Self::SlipperyFingers => 13,
             // ... Map others to sequential IDs ...
         }
     }
+
+    /// Whether the drawback's moves depend on the opponent's last move, which
+    /// then has to be part of the position's hash (see `calculate_zobrist_hash`)
+    pub fn reacts_to_last_move(self) -> bool {
+        matches!(self, Self::FollowTheLeader)
+    }
+
+    /// Whether the drawback's moves depend on how many moves its owner made
+    /// without capturing (`GameState::moves_since_capture`), which then has to
+    /// be part of the position's hash as well
+    pub fn counts_moves_since_capture(self) -> bool {
+        matches!(self, Self::Vampire)
+    }
 }
 
 /// Resource mapping DrawbackId enum values to actual implementations.
@@ -45,6 +91,39 @@ pub struct DrawbackRegistry {
     pub rules: HashMap<DrawbackId, Arc<dyn DrawbackRule + Send + Sync>>,
 }
 
+impl DrawbackRegistry {
+    /// IDs of all registered drawbacks in a stable order (by key index)
+    pub fn sorted_ids(&self) -> Vec<DrawbackId> {
+        let mut ids: Vec<DrawbackId> = self.rules.keys().copied().collect();
+        ids.sort_by_key(|id| id.to_key_index());
+        ids
+    }
+
+    /// A drawback picked uniformly from the registered ones (None if there are none)
+    pub fn random_id(&self, rng: &mut impl Rng) -> DrawbackId {
+        self.sorted_ids().choose(rng).copied().unwrap_or(DrawbackId::None)
+    }
+
+    /// The drawback with this built-in name (as written in the PGN tags)
+    pub fn id_by_name(&self, name: &str) -> Option<DrawbackId> {
+        self.rules.values().find(|rule| rule.name() == name).map(|rule| rule.id())
+    }
+
+    /// The rule of a drawback instance: the registered rule with `params`
+    /// applied (the registered one itself if there are none). Parameters the
+    /// rule rejects are reported and the defaults are used instead.
+    pub fn rule(&self, id: DrawbackId, params: &DrawbackParams) -> Option<Arc<dyn DrawbackRule + Send + Sync>> {
+        let rule = self.rules.get(&id)?;
+        if params.is_empty() {
+            return Some(rule.clone());
+        }
+        rule.with_params(params).or_else(|| {
+            eprintln!("Invalid parameters for {}: {}", rule.name(), params);
+            Some(rule.clone())
+        })
+    }
+}
+
 impl Default for DrawbackRegistry {
     fn default() -> Self {
         initialize_drawback_registry()
@@ -74,9 +153,39 @@ fn initialize_drawback_registry() -> DrawbackRegistry {
     let pawn_push_one_rule = Arc::new(PawnPushOneOnly) as Arc<dyn DrawbackRule + Send + Sync>;
     rules.insert(pawn_push_one_rule.id(), pawn_push_one_rule);
 
-    let block_random_file_rule = Arc::new(BlockRandomFile) as Arc<dyn DrawbackRule + Send + Sync>;
+    let block_random_file_rule = Arc::new(BlockRandomFile::default()) as Arc<dyn DrawbackRule + Send + Sync>;
     rules.insert(block_random_file_rule.id(), block_random_file_rule);
 
+    let recycling_rule = Arc::new(Recycling) as Arc<dyn DrawbackRule + Send + Sync>;
+    rules.insert(recycling_rule.id(), recycling_rule);
+
+    let pacifist_opening_rule = Arc::new(PacifistOpening::default()) as Arc<dyn DrawbackRule + Send + Sync>;
+    rules.insert(pacifist_opening_rule.id(), pacifist_opening_rule);
+
+    let edge_phobic_rule = Arc::new(EdgePhobic::default()) as Arc<dyn DrawbackRule + Send + Sync>;
+    rules.insert(edge_phobic_rule.id(), edge_phobic_rule);
+
+    let follow_the_leader_rule = Arc::new(FollowTheLeader) as Arc<dyn DrawbackRule + Send + Sync>;
+    rules.insert(follow_the_leader_rule.id(), follow_the_leader_rule);
+
+    let pawn_horde_rule = Arc::new(PawnHorde::default()) as Arc<dyn DrawbackRule + Send + Sync>;
+    rules.insert(pawn_horde_rule.id(), pawn_horde_rule);
+
+    let mirror_rule = Arc::new(Mirror) as Arc<dyn DrawbackRule + Send + Sync>;
+    rules.insert(mirror_rule.id(), mirror_rule);
+
+    let claustrophobia_rule = Arc::new(Claustrophobia) as Arc<dyn DrawbackRule + Send + Sync>;
+    rules.insert(claustrophobia_rule.id(), claustrophobia_rule);
+
+    let vampire_rule = Arc::new(Vampire) as Arc<dyn DrawbackRule + Send + Sync>;
+    rules.insert(vampire_rule.id(), vampire_rule);
+
+    let glass_cannon_rule = Arc::new(GlassCannon) as Arc<dyn DrawbackRule + Send + Sync>;
+    rules.insert(glass_cannon_rule.id(), glass_cannon_rule);
+
+    let slippery_fingers_rule = Arc::new(SlipperyFingers) as Arc<dyn DrawbackRule + Send + Sync>;
+    rules.insert(slippery_fingers_rule.id(), slippery_fingers_rule);
+
     // ... Add ALL other ~200 rule instances here ...
 
     println!("Loading drawbacks into registry...");
@@ -0,0 +1,99 @@
+use bevy::prelude::Color;
+use shakmaty::{Chess, File, Move, Position, Rank, Role, Square};
+use super::definition::{DrawbackRule, TurnContext};
+use super::registry::DrawbackId;
+
+// Outcomes of the per-turn roll; the fingers slip on one of them
+const SLIP_OUTCOMES: u8 = 3;
+const SLIP_OUTCOME: u8 = 0;
+
+// Shortest slide (in squares) that can overshoot
+const MIN_SLIDE: u32 = 4;
+
+// Tint of the sliding pieces while they are slippery this turn
+const SLIPPERY_COLOR: Color = Color::rgba(0.2, 0.6, 0.9, 0.25);
+
+/// At the start of each of your turns there is a 1 in 3 chance your fingers
+/// slip: a bishop, rook or queen sliding 4 or more squares without capturing
+/// then goes one square further the same way, if that square is empty and the
+/// longer slide is legal. The game swaps the move after it was picked.
+#[derive(Debug, Clone)]
+pub struct SlipperyFingers;
+
+// Helper function: whether this turn's roll made the fingers slip
+fn slipping(rng_outcome: Option<u8>) -> bool {
+    rng_outcome == Some(SLIP_OUTCOME)
+}
+
+impl DrawbackRule for SlipperyFingers {
+    fn id(&self) -> DrawbackId { DrawbackId::SlipperyFingers }
+    fn name(&self) -> &'static str { "Slippery Fingers" }
+    fn description(&self) -> String { "Each turn there is a 1 in 3 chance that your slides of 4 or more squares overshoot by one square.".to_string() }
+
+    fn needs_turn_rng(&self) -> bool {
+        true
+    }
+
+    fn get_rng_outcomes(&self) -> u8 {
+        SLIP_OUTCOMES
+    }
+
+    fn filter_pseudo_legal_moves(
+        &self,
+        _position: &Chess,
+        moves: Vec<Move>,
+        _rng_outcome: Option<u8>, // Only matters once a move is picked
+    ) -> Vec<Move> {
+        moves
+    }
+
+    fn substitute_move(&self, position: &Chess, chess_move: &Move, rng_outcome: Option<u8>) -> Option<Move> {
+        slipping(rng_outcome).then(|| overshoot(position, chess_move)).flatten()
+    }
+
+    fn explain_piece(&self, position: &Chess, square: Square, turn: &TurnContext) -> Option<String> {
+        let role = position.board().role_at(square)?;
+        (slipping(turn.rng_outcome) && is_slider(role))
+            .then(|| format!("Slippery this turn: slides of {} or more squares go one square further", MIN_SLIDE))
+    }
+
+    fn check_loss_condition(&self, _position: &Chess, _legal_moves: &[Move], _turn: &TurnContext) -> bool {
+        false
+    }
+
+    fn board_overlay(&self, position: &Chess, rng_outcome: Option<u8>) -> Vec<(Square, Color)> {
+        if !slipping(rng_outcome) {
+            return Vec::new();
+        }
+        let board = position.board();
+        let sliders = (board.bishops() | board.rooks() | board.queens()) & board.by_color(position.turn());
+        sliders.into_iter().map(|square| (square, SLIPPERY_COLOR)).collect()
+    }
+}
+
+/// The longer slide `chess_move` turns into when the fingers slip: one square
+/// further the same way, if the slide is long enough, captures nothing and the
+/// square beyond is empty. None if the move stays as it is.
+pub fn overshoot(position: &Chess, chess_move: &Move) -> Option<Move> {
+    let Move::Normal { role, from, capture: None, to, promotion: None } = *chess_move else {
+        return None;
+    };
+    if !is_slider(role) || from.distance(to) < MIN_SLIDE {
+        return None;
+    }
+
+    let step = |from: u32, to: u32| (to as i32 - from as i32).signum();
+    let file = u32::from(to.file()) as i32 + step(u32::from(from.file()), u32::from(to.file()));
+    let rank = u32::from(to.rank()) as i32 + step(u32::from(from.rank()), u32::from(to.rank()));
+    if !(0..8).contains(&file) || !(0..8).contains(&rank) {
+        return None;
+    }
+    let beyond = Square::from_coords(File::new(file as u32), Rank::new(rank as u32));
+    let longer = Move::Normal { role, from, capture: None, to: beyond, promotion: None };
+    (position.board().piece_at(beyond).is_none() && position.is_legal(&longer)).then_some(longer)
+}
+
+// Helper function: pieces that slide
+fn is_slider(role: Role) -> bool {
+    matches!(role, Role::Bishop | Role::Rook | Role::Queen)
+}
@@ -0,0 +1,53 @@
+use shakmaty::{Chess, Move, Square};
+use super::definition::{DrawbackRule, TurnContext};
+use super::registry::DrawbackId;
+
+// A player has to capture at least once in this many of their moves
+const MOVES_PER_CAPTURE: u32 = 5;
+
+/// You must capture at least once every 5 of your moves: after 4 moves in a row
+/// without a capture only captures are allowed, and you lose if there is none.
+/// The count is kept by the game (`GameState::moves_since_capture`) and by the
+/// AI's search, and reaches the rule through `TurnContext`.
+#[derive(Debug, Clone)]
+pub struct Vampire;
+
+// Helper function: whether the owner's next move has to capture
+fn must_capture(moves_since_capture: u32) -> bool {
+    moves_since_capture + 1 >= MOVES_PER_CAPTURE
+}
+
+impl DrawbackRule for Vampire {
+    fn id(&self) -> DrawbackId { DrawbackId::Vampire }
+    fn name(&self) -> &'static str { "Vampire" }
+    fn description(&self) -> String { "You must capture at least once every 5 of your moves, or you lose.".to_string() }
+
+    fn filter_pseudo_legal_moves(
+        &self,
+        _position: &Chess,
+        moves: Vec<Move>,
+        _rng_outcome: Option<u8>, // Ignored
+    ) -> Vec<Move> {
+        // Without the count nothing is known to be forbidden
+        moves
+    }
+
+    fn filter_moves(&self, _position: &Chess, moves: Vec<Move>, turn: &TurnContext) -> Vec<Move> {
+        if !must_capture(turn.moves_since_capture) {
+            return moves;
+        }
+        moves.into_iter().filter(Move::is_capture).collect()
+    }
+
+    fn explain_piece(&self, _position: &Chess, _square: Square, turn: &TurnContext) -> Option<String> {
+        if must_capture(turn.moves_since_capture) {
+            return Some("Must capture this move, or you lose".to_string());
+        }
+        let left = MOVES_PER_CAPTURE - 1 - turn.moves_since_capture;
+        Some(format!("{} more move(s) without a capture allowed", left))
+    }
+
+    fn check_loss_condition(&self, _position: &Chess, legal_moves: &[Move], turn: &TurnContext) -> bool {
+        must_capture(turn.moves_since_capture) && !legal_moves.iter().any(Move::is_capture)
+    }
+}
@@ -0,0 +1,4 @@
+pub mod plugin;
+pub mod windows;
+
+pub use plugin::EguiUiPlugin;
@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPlugin};
+use crate::input::focus::TextInputFocus;
+use super::windows::{EguiWindows, draw_settings_window, draw_drawback_browser, draw_analysis_window, draw_console_window};
+
+/// Plugin with the egui windows (settings, drawback browser, analysis and the
+/// developer console), an alternative to the hand-built UI for tools that
+/// need a lot of widgets. F1 shows or hides them.
+pub struct EguiUiPlugin;
+
+impl Plugin for EguiUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(EguiPlugin)
+           .init_resource::<EguiWindows>()
+           .add_systems(Update, toggle_egui_windows)
+           .add_systems(
+               Update,
+               (draw_settings_window, draw_drawback_browser, draw_analysis_window, draw_console_window, share_keyboard_focus)
+                   .chain()
+                   .run_if(|windows: Res<EguiWindows>| windows.visible)
+           );
+    }
+}
+
+/// F1 shows or hides the egui windows
+fn toggle_egui_windows(keys: Res<Input<KeyCode>>, focus: Res<TextInputFocus>, mut windows: ResMut<EguiWindows>) {
+    if keys.just_pressed(KeyCode::F1) && !focus.0 {
+        windows.visible = !windows.visible;
+    }
+}
+
+/// Keeps the game's keyboard shortcuts off while an egui text field is being typed in
+fn share_keyboard_focus(mut contexts: EguiContexts, mut focus: ResMut<TextInputFocus>, mut had_focus: Local<bool>) {
+    let wants_keyboard = contexts.ctx_mut().wants_keyboard_input();
+    if wants_keyboard != *had_focus {
+        focus.0 = wants_keyboard;
+        *had_focus = wants_keyboard;
+    }
+}
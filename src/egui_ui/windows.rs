@@ -0,0 +1,206 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use shakmaty::{Color as ChessColor, Position};
+use crate::ai::analysis::ReplayAnalysis;
+use crate::ai::components::AiThinking;
+use crate::ai::evaluation::{evaluate_position_with_pst, PieceSquareTables};
+use crate::config::{GameConfig, SavedSettings};
+use crate::drawbacks::DrawbackRegistry;
+use crate::game_logic::state::{ActiveBoard, GameState, ReplayState};
+use crate::game_logic::notation::format_uci;
+use crate::i18n::Localization;
+use crate::ui::console::DevConsole;
+
+// Log lines kept in view by the console window's scroll area
+const CONSOLE_HEIGHT: f32 = 240.0;
+
+/// Resource with which egui windows are open; `visible` (F1) shows or hides all of them
+#[derive(Resource, Debug)]
+pub struct EguiWindows {
+    pub visible: bool,
+    pub settings: bool,
+    pub drawbacks: bool,
+    pub analysis: bool,
+    pub console: bool,
+    console_input: String,
+}
+
+impl Default for EguiWindows {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            settings: true,
+            drawbacks: true,
+            analysis: true,
+            console: true,
+            console_input: String::new(),
+        }
+    }
+}
+
+/// Display and AI settings. Written back only when a widget changed, since
+/// other systems react to every change of the configuration.
+pub fn draw_settings_window(
+    mut contexts: EguiContexts,
+    mut windows: ResMut<EguiWindows>,
+    mut config: ResMut<GameConfig>,
+    mut saved: ResMut<SavedSettings>,
+) {
+    let mut display = config.display.clone();
+    let mut ai_settings = config.ai_settings.clone();
+    let (mut white_ai, mut black_ai) = (config.white_player.is_ai, config.black_player.is_ai);
+    let mut changed = false;
+
+    egui::Window::new("Settings").open(&mut windows.settings).show(contexts.ctx_mut(), |ui| {
+        ui.heading("Players");
+        changed |= ui.checkbox(&mut white_ai, "White is played by the AI").changed();
+        changed |= ui.checkbox(&mut black_ai, "Black is played by the AI").changed();
+
+        ui.separator();
+        ui.heading("Display");
+        changed |= ui.checkbox(&mut display.low_power_mode, "Low power mode").changed();
+        changed |= ui.checkbox(&mut display.show_ai_thinking, "AI thinking indicator").changed();
+        changed |= ui.checkbox(&mut display.show_ai_best_move_arrow, "AI best move arrow").changed();
+        changed |= ui.checkbox(&mut display.show_ai_observer_arrows, "Engine plans in AI vs AI games").changed();
+        changed |= ui.checkbox(&mut display.show_blocked_moves, "Show moves the drawback removed").changed();
+        changed |= ui.checkbox(&mut display.show_drawback_overlays, "Drawback overlays").changed();
+        changed |= ui.checkbox(&mut display.show_rule_explanations, "Explain a drawback the first time it triggers").changed();
+
+        ui.separator();
+        ui.heading("AI");
+        changed |= ui.add(egui::Slider::new(&mut ai_settings.depth_limit, 1..=12).text("Depth limit")).changed();
+        changed |= ui.add(egui::Slider::new(&mut ai_settings.time_limit_ms, 100..=30_000).text("Time limit (ms)")).changed();
+        changed |= ui.checkbox(&mut ai_settings.opponent_model, "Infer the opponent's drawback").changed();
+    });
+
+    if changed {
+        let before = config.clone();
+        config.display = display;
+        config.ai_settings = ai_settings;
+        config.white_player.is_ai = white_ai;
+        config.black_player.is_ai = black_ai;
+        saved.record_and_save(&before, &config);
+    }
+}
+
+/// Every registered drawback with its description; the buttons give it to a
+/// side through the console's `setdrawback` command
+pub fn draw_drawback_browser(
+    mut contexts: EguiContexts,
+    mut windows: ResMut<EguiWindows>,
+    mut console: ResMut<DevConsole>,
+    registry: Res<DrawbackRegistry>,
+    localization: Res<Localization>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+) {
+    let current = boards.get_single().ok().map(|game_state| (game_state.white_drawback, game_state.black_drawback));
+
+    egui::Window::new("Drawbacks").open(&mut windows.drawbacks).show(contexts.ctx_mut(), |ui| {
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for id in registry.sorted_ids() {
+                let Some(rule) = registry.rules.get(&id) else {
+                    continue;
+                };
+                let mut title = localization.drawback_name(&registry, id);
+                if current.is_some_and(|(white, _)| white == id) {
+                    title.push_str("  (White)");
+                }
+                if current.is_some_and(|(_, black)| black == id) {
+                    title.push_str("  (Black)");
+                }
+                ui.collapsing(title, |ui| {
+                    let params = rule.params();
+                    ui.label(localization.drawback_description(&registry, id, &params));
+                    if !params.is_empty() {
+                        ui.monospace(format!("Default parameters: {}", params));
+                    }
+                    ui.horizontal(|ui| {
+                        for (label, side) in [("Give to White", "white"), ("Give to Black", "black")] {
+                            if ui.button(label).clicked() {
+                                console.submit(format!("setdrawback {} {}", side, rule.name()));
+                            }
+                        }
+                    });
+                });
+            }
+        });
+    });
+}
+
+/// The position on the board: evaluation, hash and the running search
+pub fn draw_analysis_window(
+    mut contexts: EguiContexts,
+    mut windows: ResMut<EguiWindows>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    thinking: Query<&AiThinking>,
+    replay_analysis: Res<ReplayAnalysis>,
+    replay_state: Res<State<ReplayState>>,
+    pst: Res<PieceSquareTables>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+
+    egui::Window::new("Analysis").open(&mut windows.analysis).show(contexts.ctx_mut(), |ui| {
+        let to_move = game_state.board.turn();
+        let score = evaluate_position_with_pst(&game_state.board, &pst);
+        let white_score = if to_move == ChessColor::White { score } else { -score };
+        ui.label(format!("{:?} to move, move {}", to_move, game_state.board.fullmoves()));
+        ui.label(format!("Static evaluation: {:+.2} for White", white_score as f32 / 100.0));
+        ui.monospace(format!("Zobrist {:016x}", game_state.zobrist_hash));
+        if let Some(last_move) = &game_state.last_move {
+            ui.label(format!("Last move: {}", format_uci(last_move)));
+        }
+
+        ui.separator();
+        match thinking.iter().next() {
+            Some(task) => {
+                let best_move = task.progress.best_move().map_or("-".to_string(), |best_move| format_uci(&best_move));
+                ui.label(format!(
+                    "AI thinking for {:.1}s: depth {}, {} nodes, best {}",
+                    task.started_at.elapsed().as_secs_f32(),
+                    task.progress.depth(),
+                    task.progress.nodes(),
+                    best_move,
+                ));
+                let plan: Vec<String> = task.progress.plan().iter().map(format_uci).collect();
+                if !plan.is_empty() {
+                    ui.monospace(plan.join(" "));
+                }
+            }
+            None => {
+                ui.label("AI idle");
+            }
+        }
+
+        if *replay_state.get() == ReplayState::Replay {
+            ui.separator();
+            match replay_analysis.score_cp {
+                Some(cp) => ui.label(format!("Replay position: {:+.2} for White", cp as f32 / 100.0)),
+                None if replay_analysis.is_running() => ui.label("Analysing the replay position..."),
+                None => ui.label("Replay position: no score"),
+            };
+        }
+    });
+}
+
+/// The developer console's log with an input line; commands run through the
+/// same `run_console_commands` system as the ` console
+pub fn draw_console_window(mut contexts: EguiContexts, mut windows: ResMut<EguiWindows>, mut console: ResMut<DevConsole>) {
+    let windows = &mut *windows;
+    egui::Window::new("Console").open(&mut windows.console).show(contexts.ctx_mut(), |ui| {
+        egui::ScrollArea::vertical().max_height(CONSOLE_HEIGHT).stick_to_bottom(true).show(ui, |ui| {
+            for line in console.lines() {
+                ui.monospace(line);
+            }
+        });
+        let response = ui.text_edit_singleline(&mut windows.console_input);
+        if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+            let line = std::mem::take(&mut windows.console_input);
+            if !line.trim().is_empty() {
+                console.submit(line);
+            }
+            response.request_focus();
+        }
+    });
+}
@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+use shakmaty::{Chess, Color as ChessColor, Position, Role};
+use super::history::MoveHistory;
+
+// Values of the pieces in pawns, as usually counted over the board
+const MATERIAL_VALUES: [(Role, i32); 5] = [(Role::Pawn, 1), (Role::Knight, 3), (Role::Bishop, 3), (Role::Rook, 5), (Role::Queen, 9)];
+
+/// Resource with the pieces each player has taken so far and the material
+/// balance, kept in step with `MoveHistory` (take backs included)
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct CaptureLog {
+    pub by_white: Vec<Role>, // Black pieces White took, in the order taken
+    pub by_black: Vec<Role>, // White pieces Black took, in the order taken
+    pub material: i32,       // White minus Black in pawns, promotions included
+}
+
+impl CaptureLog {
+    /// The captures of the recorded moves and the material after the last one
+    pub fn from_history(history: &MoveHistory) -> Self {
+        let mut log = Self::default();
+        let mut position = history.start_position.clone();
+        for record in &history.moves {
+            // En passant reports the pawn it takes as well
+            if let Some(role) = record.chess_move.capture() {
+                match position.turn() {
+                    ChessColor::White => log.by_white.push(role),
+                    ChessColor::Black => log.by_black.push(role),
+                }
+            }
+            position.play_unchecked(&record.chess_move);
+        }
+        log.material = material_balance(&position);
+        log
+    }
+
+    /// Pieces `color` has taken from its opponent
+    pub fn taken_by(&self, color: ChessColor) -> &[Role] {
+        match color {
+            ChessColor::White => &self.by_white,
+            ChessColor::Black => &self.by_black,
+        }
+    }
+
+    /// How many pawns' worth of material `color` is ahead (0 when it isn't)
+    pub fn lead(&self, color: ChessColor) -> i32 {
+        let balance = match color {
+            ChessColor::White => self.material,
+            ChessColor::Black => -self.material,
+        };
+        balance.max(0)
+    }
+}
+
+/// Simple material count in pawns (1/3/3/5/9), White minus Black
+pub fn material_balance(position: &Chess) -> i32 {
+    let board = position.board();
+    MATERIAL_VALUES
+        .into_iter()
+        .map(|(role, value)| {
+            let pieces = board.by_role(role);
+            value * ((pieces & board.white()).count() as i32 - (pieces & board.black()).count() as i32)
+        })
+        .sum()
+}
+
+/// Rebuilds the capture log whenever a move is recorded, taken back or a new game starts
+pub fn update_capture_log(history: Res<MoveHistory>, mut log: ResMut<CaptureLog>) {
+    if !history.is_changed() {
+        return;
+    }
+    log.set_if_neq(CaptureLog::from_history(&history));
+}
@@ -0,0 +1,306 @@
+use bevy::prelude::*;
+use shakmaty::Color as ChessColor;
+use crate::config::{GameConfig, TimeControlSettings, DelayMode};
+use super::events::{GameOverEvent, GameOverReason, GameResult, NewGameEvent};
+use super::history::MoveHistory;
+use super::state::{GameState, ActiveBoard, GameStatus, TurnState};
+
+/// How low a clock has run, by the thresholds of the low time settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LowTimeLevel {
+    Warning,
+    Critical,
+}
+
+/// Event sent when a clock drops below a low time threshold
+#[derive(Debug, Clone, Copy)]
+pub struct ClockThresholdEvent {
+    pub color: ChessColor,
+    pub level: LowTimeLevel,
+}
+
+impl Event for ClockThresholdEvent {}
+
+// Moves the remaining time is shared out over when budgeting a search
+const EXPECTED_MOVES_TO_GO: u64 = 30;
+// Most time a search leaves on the clock for the move to get played
+const MAX_SAFETY_MARGIN_MS: u64 = 500;
+// Least time a search gets, even with the clock almost out
+const MIN_SEARCH_MS: u64 = 10;
+
+/// Resource with the remaining time of both players.
+/// Only ticks when the time control is enabled in the configuration.
+#[derive(Resource, Debug, Clone)]
+pub struct GameClock {
+    pub enabled: bool,
+    pub white_ms: u64,
+    pub black_ms: u64,
+    pub increment_ms: u64,
+    pub delay_mode: DelayMode,
+    pub delay_ms: u64,
+    pub per_move_ms: u64, // Time each move gets on its own, 0 = the clocks run for the whole game
+    // Time the side to move has spent on the current move
+    turn_elapsed_ms: u64,
+    // Side whose clock ran last frame, to add the increment once they have moved
+    last_turn: ChessColor,
+    // Sub-millisecond part of the frame times, so it doesn't get lost each frame
+    carry_us: u64,
+    pub warning_ms: u64,
+    pub critical_ms: u64,
+    // Lowest level each side (White, Black) has been warned about
+    warned: [Option<LowTimeLevel>; 2],
+}
+
+impl GameClock {
+    pub fn new(settings: &TimeControlSettings) -> Self {
+        let per_move = settings.per_move_ms > 0;
+        Self {
+            enabled: settings.enabled,
+            white_ms: if per_move { settings.per_move_ms } else { settings.white_time_ms },
+            black_ms: if per_move { settings.per_move_ms } else { settings.black_time_ms },
+            increment_ms: settings.increment_ms,
+            delay_mode: settings.delay_mode,
+            delay_ms: settings.delay_ms,
+            per_move_ms: settings.per_move_ms,
+            turn_elapsed_ms: 0,
+            last_turn: ChessColor::White,
+            carry_us: 0,
+            warning_ms: settings.low_time.warning_ms,
+            critical_ms: settings.low_time.critical_ms,
+            warned: [None, None],
+        }
+    }
+
+    /// How low the clock of `color` has run, None while it has plenty of time
+    pub fn low_time_level(&self, color: ChessColor) -> Option<LowTimeLevel> {
+        let remaining = self.remaining_ms(color);
+        if !self.enabled {
+            None
+        } else if remaining < self.critical_ms {
+            Some(LowTimeLevel::Critical)
+        } else if remaining < self.warning_ms {
+            Some(LowTimeLevel::Warning)
+        } else {
+            None
+        }
+    }
+
+    // Level to warn `color` about now, if their clock just crossed a threshold.
+    // Time won back (increment, delay) arms the warning again.
+    fn take_new_warning(&mut self, color: ChessColor) -> Option<LowTimeLevel> {
+        let level = self.low_time_level(color);
+        let warned = &mut self.warned[color_index(color)];
+        let crossed = level.filter(|level| warned.is_none_or(|warned| *level > warned));
+        *warned = level;
+        crossed
+    }
+
+    /// What is left of the delay of the current move (None without a delay).
+    /// With a simple delay the main clock only runs once this reaches zero;
+    /// with Bronstein delay it is the most the move can still be refunded.
+    pub fn delay_remaining_ms(&self) -> Option<u64> {
+        match self.delay_mode {
+            DelayMode::None => None,
+            DelayMode::Simple | DelayMode::Bronstein => Some(self.delay_ms.saturating_sub(self.turn_elapsed_ms)),
+        }
+    }
+
+    // Called once `mover` has made their move
+    fn finish_turn(&mut self, mover: ChessColor) {
+        if self.per_move_ms > 0 {
+            // Nothing carries over: the next move gets the full time again
+            self.white_ms = self.per_move_ms;
+            self.black_ms = self.per_move_ms;
+            self.turn_elapsed_ms = 0;
+            return;
+        }
+        let mut bonus = self.increment_ms;
+        if self.delay_mode == DelayMode::Bronstein {
+            bonus += self.turn_elapsed_ms.min(self.delay_ms);
+        }
+        *self.remaining_mut(mover) += bonus;
+        self.turn_elapsed_ms = 0;
+    }
+
+    // Runs the clock of `turn` for `elapsed_ms`
+    fn run(&mut self, turn: ChessColor, elapsed_ms: u64) {
+        // A simple delay is used up before the main time
+        let delayed = match self.delay_mode {
+            DelayMode::Simple => elapsed_ms.min(self.delay_ms.saturating_sub(self.turn_elapsed_ms)),
+            DelayMode::None | DelayMode::Bronstein => 0,
+        };
+        self.turn_elapsed_ms += elapsed_ms;
+        let remaining = self.remaining_mut(turn);
+        *remaining = remaining.saturating_sub(elapsed_ms - delayed);
+    }
+
+    /// How long `color` may think about their move: a share of the remaining
+    /// time plus most of the increment, always leaving a margin on the clock.
+    /// None when the game is played without clocks.
+    pub fn search_budget_ms(&self, color: ChessColor) -> Option<u64> {
+        if !self.enabled {
+            return None;
+        }
+        let remaining = self.remaining_ms(color);
+        let margin = (remaining / 10).min(MAX_SAFETY_MARGIN_MS);
+        let budget = if self.per_move_ms > 0 {
+            remaining
+        } else {
+            remaining / EXPECTED_MOVES_TO_GO + self.increment_ms * 3 / 4 + self.delay_remaining_ms().unwrap_or(0)
+        };
+        Some(budget.min(remaining.saturating_sub(margin)).max(MIN_SEARCH_MS))
+    }
+
+    pub fn remaining_ms(&self, color: ChessColor) -> u64 {
+        match color {
+            ChessColor::White => self.white_ms,
+            ChessColor::Black => self.black_ms,
+        }
+    }
+
+    fn remaining_mut(&mut self, color: ChessColor) -> &mut u64 {
+        match color {
+            ChessColor::White => &mut self.white_ms,
+            ChessColor::Black => &mut self.black_ms,
+        }
+    }
+}
+
+/// Resource with the time the side to move has spent on the current move.
+/// Runs whether or not the clock is enabled, for the times in `MoveHistory`.
+#[derive(Resource, Debug, Default)]
+pub struct MoveTimer {
+    elapsed_us: u64,
+}
+
+impl MoveTimer {
+    /// Milliseconds spent on the move just played; starts timing the next one
+    pub fn take_ms(&mut self) -> u64 {
+        std::mem::take(&mut self.elapsed_us) / 1000
+    }
+
+    /// Seconds spent on the move being thought about so far
+    pub fn elapsed_secs(&self) -> f32 {
+        self.elapsed_us as f32 / 1_000_000.0
+    }
+}
+
+fn color_index(color: ChessColor) -> usize {
+    match color {
+        ChessColor::White => 0,
+        ChessColor::Black => 1,
+    }
+}
+
+/// Formats a clock time as m:ss, with tenths under ten seconds
+pub fn format_clock(ms: u64) -> String {
+    if ms < 10_000 {
+        format!("0:{:02}.{}", ms / 1000, (ms % 1000) / 100)
+    } else {
+        let seconds = ms.div_ceil(1000);
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+/// System to set the clocks back to the configured times when a new game starts
+pub fn reset_clock(
+    mut ev_new_game: EventReader<NewGameEvent>,
+    config: Res<GameConfig>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    mut clock: ResMut<GameClock>,
+) {
+    if ev_new_game.read().count() == 0 {
+        return;
+    }
+    *clock = GameClock::new(&config.time_control);
+    if let Ok(game_state) = boards.get_single() {
+        clock.last_turn = game_state.current_player_turn;
+    }
+}
+
+/// System to time the move being thought about (paused with the game)
+pub fn tick_move_timer(
+    time: Res<Time>,
+    mut timer: ResMut<MoveTimer>,
+    mut ev_new_game: EventReader<NewGameEvent>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+) {
+    if ev_new_game.read().count() > 0 {
+        *timer = MoveTimer::default();
+    }
+    if boards.get_single().is_ok_and(|game_state| !game_state.status.is_over()) {
+        timer.elapsed_us += time.delta().as_micros() as u64;
+    }
+}
+
+/// System to run the clock of the side to move and end the game when it runs out.
+/// The mover's clock after each move is kept in the history for the PGN.
+#[allow(clippy::too_many_arguments)]
+pub fn tick_clock(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    mut clock: ResMut<GameClock>,
+    mut history: ResMut<MoveHistory>,
+    mut boards: Query<&mut GameState, With<ActiveBoard>>,
+    mut next_state: ResMut<NextState<TurnState>>,
+    mut ev_game_over: EventWriter<GameOverEvent>,
+    mut ev_threshold: EventWriter<ClockThresholdEvent>,
+) {
+    let Ok(mut game_state) = boards.get_single_mut() else {
+        return;
+    };
+    if !clock.enabled || game_state.status.is_over() {
+        return;
+    }
+
+    let turn = game_state.current_player_turn;
+    if turn != clock.last_turn {
+        let mover = clock.last_turn;
+        clock.finish_turn(mover);
+        clock.last_turn = turn;
+        if let Some(record) = history.moves.last_mut() {
+            record.clock_ms = Some(clock.remaining_ms(mover));
+        }
+    }
+
+    let elapsed_us = time.delta().as_micros() as u64 + clock.carry_us;
+    clock.carry_us = elapsed_us % 1000;
+    clock.run(turn, elapsed_us / 1000);
+    if let Some(level) = clock.take_new_warning(turn) {
+        ev_threshold.send(ClockThresholdEvent { color: turn, level });
+    }
+
+    if clock.remaining_ms(turn) == 0 {
+        // The side to move lost on time
+        let result = GameResult::new(GameOverReason::Timeout { loser: turn }, config.time_control.draw_result);
+        game_state.status = GameStatus::Finished(result);
+        next_state.set(TurnState::GameOver);
+        ev_game_over.send(GameOverEvent(result));
+        println!("Game over: Timeout ({:?} ran out of time)", turn);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn per_move_clocks_refill_and_searches_get_a_share_of_the_clock() {
+        let settings = TimeControlSettings { enabled: true, per_move_ms: 5000, ..TimeControlSettings::default() };
+        let mut clock = GameClock::new(&settings);
+        assert_eq!((clock.white_ms, clock.black_ms), (5000, 5000));
+        clock.run(ChessColor::White, 3000);
+        assert_eq!(clock.search_budget_ms(ChessColor::White), Some(1800));
+        clock.finish_turn(ChessColor::White);
+        assert_eq!((clock.white_ms, clock.black_ms), (5000, 5000));
+
+        // Five minutes with a two second increment
+        let settings = TimeControlSettings { enabled: true, increment_ms: 2000, ..TimeControlSettings::default() };
+        let mut clock = GameClock::new(&settings);
+        assert_eq!(clock.search_budget_ms(ChessColor::Black), Some(300_000 / 30 + 1500));
+        clock.black_ms = 5;
+        assert_eq!(clock.search_budget_ms(ChessColor::Black), Some(MIN_SEARCH_MS));
+
+        assert_eq!(GameClock::new(&TimeControlSettings::default()).search_budget_ms(ChessColor::White), None);
+    }
+}
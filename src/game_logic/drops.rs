@@ -0,0 +1,123 @@
+use shakmaty::{attacks, Bitboard, ByColor, ByRole, Chess, Color as ChessColor, Move, Piece, Position, Role, Square};
+use crate::config::Ruleset;
+use crate::drawbacks::definition::DrawbackRule;
+
+/// Pieces that can be held in hand, in the order the reserve tray shows them
+pub const DROPPABLE_ROLES: [Role; 5] = [Role::Pawn, Role::Knight, Role::Bishop, Role::Rook, Role::Queen];
+
+/// The pieces each player holds in hand, ready to be dropped onto the board
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Reserves {
+    pieces: ByColor<ByRole<u8>>,
+    promoted: Bitboard, // Squares of promoted pieces, which go into a hand as pawns
+}
+
+impl Reserves {
+    pub fn count(&self, color: ChessColor, role: Role) -> u8 {
+        *self.pieces.get(color).get(role)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pieces.iter().all(|hand| hand.iter().all(|count| *count == 0))
+    }
+
+    pub fn add(&mut self, color: ChessColor, role: Role) {
+        let count = self.pieces.get_mut(color).get_mut(role);
+        *count = count.saturating_add(1);
+    }
+
+    /// Takes a piece out of a hand, false if there was none
+    pub fn take(&mut self, color: ChessColor, role: Role) -> bool {
+        let count = self.pieces.get_mut(color).get_mut(role);
+        let taken = *count > 0;
+        *count = count.saturating_sub(1);
+        taken
+    }
+
+    /// Updates the hands for a move about to be played on `position`: a drop
+    /// leaves its hand, and a captured piece goes to the hand `captured_hand`
+    /// picks for it (if any). Promoted pieces are handed over as pawns.
+    pub fn record_move(&mut self, position: &Chess, chess_move: &Move, captured_hand: impl FnOnce(Piece) -> Option<ChessColor>) {
+        let mover = position.turn();
+        let captured = match *chess_move {
+            Move::Normal { capture: Some(role), to, .. } => Some((to, role)),
+            Move::EnPassant { from, to } => Some((Square::from_coords(to.file(), from.rank()), Role::Pawn)),
+            _ => None,
+        };
+        if let Some((square, role)) = captured {
+            let role = if self.promoted.contains(square) { Role::Pawn } else { role };
+            self.promoted.discard(square);
+            if role != Role::King {
+                if let Some(hand) = captured_hand(Piece { color: !mover, role }) {
+                    self.add(hand, role);
+                }
+            }
+        }
+
+        match *chess_move {
+            Move::Normal { from, to, promotion, .. } => {
+                if self.promoted.contains(from) || promotion.is_some() {
+                    self.promoted.add(to);
+                }
+                self.promoted.discard(from);
+            }
+            Move::Put { role, .. } => {
+                self.take(mover, role);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Whose hand a captured piece goes to: the capturer's in Crazyhouse or if the
+/// capturer's drawback keeps pieces of that kind, otherwise back to its owner if
+/// the owner's drawback returns lost pieces of that kind
+pub fn captured_piece_hand(
+    captured: Piece,
+    ruleset: Ruleset,
+    owner_drawback: Option<&dyn DrawbackRule>,
+    capturer_drawback: Option<&dyn DrawbackRule>,
+) -> Option<ChessColor> {
+    let capturer_keeps = capturer_drawback.is_some_and(|rule| rule.keeps_captured_piece(captured.role));
+    let owner_gets_back = owner_drawback.is_some_and(|rule| rule.returns_lost_piece(captured.role));
+    if ruleset == Ruleset::Crazyhouse || capturer_keeps {
+        Some(!captured.color)
+    } else if owner_gets_back {
+        Some(captured.color)
+    } else {
+        None
+    }
+}
+
+/// Whether a drawback can put pieces into a hand, so the reserve is worth showing
+pub fn drawback_has_drops(rule: &dyn DrawbackRule) -> bool {
+    DROPPABLE_ROLES.iter().any(|&role| rule.keeps_captured_piece(role) || rule.returns_lost_piece(role))
+}
+
+/// Drops the side to move can make from its hand: any empty square, except the
+/// first and last rank for pawns. In check only drops that block it are legal,
+/// the same as for the moves from `legal_moves`.
+pub fn drop_moves(position: &Chess, reserves: &Reserves) -> Vec<Move> {
+    let color = position.turn();
+    let board = position.board();
+    let mut targets = !board.occupied();
+
+    let checkers = position.checkers();
+    if checkers.any() {
+        // A single sliding checker can be blocked, anything else can't
+        targets &= match (board.king_of(color), checkers.single_square()) {
+            (Some(king), Some(checker)) => attacks::between(king, checker),
+            _ => Bitboard::EMPTY,
+        };
+    }
+
+    let mut moves = Vec::new();
+    for role in DROPPABLE_ROLES {
+        if reserves.count(color, role) == 0 {
+            continue;
+        }
+        let role_targets = if role == Role::Pawn { targets & !Bitboard::BACKRANKS } else { targets };
+        moves.extend(role_targets.into_iter().map(|to| Move::Put { role, to }));
+    }
+    moves
+}
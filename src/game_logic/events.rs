@@ -1,12 +1,166 @@
 use bevy::prelude::*;
-use shakmaty::{Move, Color as ChessColor};
+use shakmaty::{Chess, Color as ChessColor, Move, Square};
+use std::fmt;
+use crate::config::DrawResult;
+use crate::drawbacks::DrawbackId;
+use super::history::MoveHistory;
 
 /// Event triggered to request a move
 pub struct MakeMoveEvent(pub Move);
 
+/// Why a requested move wasn't played
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveRejection {
+    WrongTurn,    // Not the turn of the side the move is for
+    Restricted,   // Not the move the tutorial asks for
+    NotAllowed,   // Illegal, or forbidden by the mover's drawback
+    WrongPiece,   // No piece of the side to move on the source square
+}
+
+impl fmt::Display for MoveRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongTurn => write!(f, "wrong turn"),
+            Self::Restricted => write!(f, "not the move the tutorial asks for"),
+            Self::NotAllowed => write!(f, "not allowed"),
+            Self::WrongPiece => write!(f, "no piece of the side to move there"),
+        }
+    }
+}
+
+/// Event sent by `apply_move` once a move is on the board (the sound effects
+/// tell moves apart from it)
+pub struct MovePlayedEvent {
+    pub chess_move: Move,
+    pub is_check: bool,        // The move gives check
+    pub picked: Option<Move>, // The move the player picked, when their drawback played another one instead
+}
+
+/// Event sent when `apply_move` refuses a `MakeMoveEvent`
+pub struct MoveRejectedEvent {
+    pub chess_move: Move,
+    pub reason: MoveRejection,
+}
+
+/// How a game ended. Decisive endings name the side they went for or
+/// against, so nobody has to work the winner out from the board afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameOverReason {
+    KingCaptured { winner: ChessColor, square: Square }, // Square the king was taken on
+    Checkmate { winner: ChessColor },
+    DrawbackLoss { loser: ChessColor, drawback: DrawbackId }, // The loser's drawback ended the game
+    Timeout { loser: ChessColor },
+    Resignation { loser: ChessColor },
+    Abandoned { loser: ChessColor }, // Left a network game and didn't come back in time
+    Stalemate,
+    DrawByRepetition,
+}
+
+impl GameOverReason {
+    /// Winner of the game; draws go to whoever the time control's draw rule names
+    fn winner(&self, draw_result: DrawResult) -> Option<ChessColor> {
+        match *self {
+            Self::KingCaptured { winner, .. } | Self::Checkmate { winner } => Some(winner),
+            Self::DrawbackLoss { loser, .. } | Self::Timeout { loser } | Self::Resignation { loser } | Self::Abandoned { loser } => Some(!loser),
+            Self::Stalemate | Self::DrawByRepetition => match draw_result {
+                DrawResult::Draw => None,
+                DrawResult::WhiteWins => Some(ChessColor::White),
+                DrawResult::BlackWins => Some(ChessColor::Black),
+            },
+        }
+    }
+
+}
+
+/// Built-in (English) description, used in logs and stored results.
+/// The UI shows `Localization::game_over_reason` instead.
+impl fmt::Display for GameOverReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::KingCaptured { .. } => write!(f, "King Captured"),
+            Self::Checkmate { .. } => write!(f, "Checkmate"),
+            Self::DrawbackLoss { loser, drawback } => write!(f, "{:?} lost to their drawback ({:?})", loser, drawback),
+            Self::Timeout { .. } => write!(f, "Timeout"),
+            Self::Resignation { loser } => write!(f, "{:?} resigned", loser),
+            Self::Abandoned { loser } => write!(f, "{:?} left the game", loser),
+            Self::Stalemate => write!(f, "Stalemate"),
+            Self::DrawByRepetition => write!(f, "Repetition"),
+        }
+    }
+}
+
+/// Outcome of a finished game: how it ended and who won (None for a draw)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameResult {
+    pub winner: Option<ChessColor>,
+    pub reason: GameOverReason,
+}
+
+impl GameResult {
+    /// Decides the winner, with draws going to whoever `draw_result` names
+    pub fn new(reason: GameOverReason, draw_result: DrawResult) -> Self {
+        Self {
+            winner: reason.winner(draw_result),
+            reason,
+        }
+    }
+
+    /// PGN result tag for the game
+    pub fn pgn_result(&self) -> &'static str {
+        match self.winner {
+            Some(ChessColor::White) => "1-0",
+            Some(ChessColor::Black) => "0-1",
+            None => "1/2-1/2",
+        }
+    }
+}
+
 /// Event triggered when the game is over
-pub struct GameOverEvent(pub String); // Contains reason for game over (e.g., "Checkmate", "Stalemate")
+pub struct GameOverEvent(pub GameResult);
+
+/// Event triggered to flip the board orientation (keyboard or menu)
+pub struct FlipBoardEvent;
+
+/// Event triggered to have the AI pick the move of the side to move right
+/// away, even if that side is played by a human (console or menu)
+pub struct ForceAiMoveEvent;
+
+/// Event triggered to swap which sides the human and the AI play mid-game
+pub struct SwapSidesEvent;
+
+/// Event triggered to save the board with both drawbacks (and the clocks)
+/// captioned as an image for sharing
+pub struct SharePositionEvent;
+
+/// Event triggered to take back the last move (against the AI, back to the
+/// human's last turn)
+pub struct UndoMoveEvent;
+
+/// Event triggered to play a move taken back again
+pub struct RedoMoveEvent;
+
+/// Event triggered to start a fresh game, with players and drawbacks taken
+/// from the current GameConfig. Starts from the standard position unless
+/// another start position is given (e.g. by the tutorial).
+#[derive(Default)]
+pub struct NewGameEvent {
+    pub start_position: Option<Chess>,
+}
+
+/// Event triggered along with a `NewGameEvent` from the same start position
+/// to go on with a recorded game: its moves are played on the new board
+pub struct LoadGameEvent(pub MoveHistory);
 
 // Implement Event traits for our custom events
 impl Event for MakeMoveEvent {}
-impl Event for GameOverEvent {} 
\ No newline at end of file
+impl Event for MoveRejectedEvent {}
+impl Event for MovePlayedEvent {}
+impl Event for GameOverEvent {}
+impl Event for FlipBoardEvent {}
+impl Event for ForceAiMoveEvent {}
+impl Event for SwapSidesEvent {}
+impl Event for SharePositionEvent {}
+impl Event for UndoMoveEvent {}
+impl Event for RedoMoveEvent {}
+impl Event for NewGameEvent {}
+impl Event for LoadGameEvent {}
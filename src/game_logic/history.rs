@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use shakmaty::{Chess, Move, Position, Role};
+use super::notation::format_san;
+
+/// Numeric Annotation Glyphs supported by the comment editor (PGN `$1`..`$6`)
+pub const NAG_GOOD: u8 = 1;        // !
+pub const NAG_MISTAKE: u8 = 2;     // ?
+pub const NAG_BRILLIANT: u8 = 3;   // !!
+pub const NAG_BLUNDER: u8 = 4;     // ??
+pub const NAG_INTERESTING: u8 = 5; // !?
+pub const NAG_DUBIOUS: u8 = 6;     // ?!
+
+/// Returns the traditional glyph for a NAG, if it has one
+pub fn nag_glyph(nag: u8) -> Option<&'static str> {
+    match nag {
+        NAG_GOOD => Some("!"),
+        NAG_MISTAKE => Some("?"),
+        NAG_BRILLIANT => Some("!!"),
+        NAG_BLUNDER => Some("??"),
+        NAG_INTERESTING => Some("!?"),
+        NAG_DUBIOUS => Some("?!"),
+        _ => None,
+    }
+}
+
+/// Parses a suffix glyph ("!?", "??", ...) back into its NAG
+pub fn nag_from_glyph(glyph: &str) -> Option<u8> {
+    match glyph {
+        "!" => Some(NAG_GOOD),
+        "?" => Some(NAG_MISTAKE),
+        "!!" => Some(NAG_BRILLIANT),
+        "??" => Some(NAG_BLUNDER),
+        "!?" => Some(NAG_INTERESTING),
+        "?!" => Some(NAG_DUBIOUS),
+        _ => None,
+    }
+}
+
+/// A single applied move with its notation and annotations
+#[derive(Debug, Clone)]
+pub struct MoveRecord {
+    pub chess_move: Move,
+    pub san: String,             // SAN (with check suffix) from the position before the move
+    pub comment: Option<String>, // Free text comment shown after the move
+    pub nags: Vec<u8>,           // Annotation glyphs attached to the move
+    pub time_ms: Option<u64>,    // Time the mover spent on it (human thinking or AI search)
+    pub clock_ms: Option<u64>,   // Mover's clock after it, when the game had a clock
+    pub captured: Option<Role>,  // Piece the move took, if any
+}
+
+/// Resource recording every move applied to the game, in order
+#[derive(Resource, Debug, Clone)]
+pub struct MoveHistory {
+    pub start_position: Chess, // Position before the first recorded move
+    pub moves: Vec<MoveRecord>,
+    pub undone: Vec<MoveRecord>, // Moves taken back, the most recent last; playing a new move drops them
+}
+
+impl Default for MoveHistory {
+    fn default() -> Self {
+        Self::new(Chess::default())
+    }
+}
+
+impl MoveHistory {
+    pub fn new(start_position: Chess) -> Self {
+        Self {
+            start_position,
+            moves: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    /// Records a move played from `position_before`
+    pub fn push(&mut self, position_before: &Chess, chess_move: Move) {
+        let san = format_san(position_before, &chess_move);
+        self.undone.clear();
+        self.moves.push(MoveRecord {
+            captured: chess_move.capture(),
+            chess_move,
+            san,
+            comment: None,
+            nags: Vec::new(),
+            time_ms: None,
+            clock_ms: None,
+        });
+    }
+
+    /// Takes back the last move; it can be played again with `redo`
+    pub fn undo(&mut self) -> bool {
+        match self.moves.pop() {
+            Some(record) => {
+                self.undone.push(record);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Plays the last move taken back again
+    pub fn redo(&mut self) -> bool {
+        match self.undone.pop() {
+            Some(record) => {
+                self.moves.push(record);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// Rebuilds the position after the first `ply` moves
+    pub fn position_at(&self, ply: usize) -> Chess {
+        let mut position = self.start_position.clone();
+        for record in self.moves.iter().take(ply) {
+            position.play_unchecked(&record.chess_move);
+        }
+        position
+    }
+
+    /// Position after every recorded move
+    pub fn final_position(&self) -> Chess {
+        self.position_at(self.moves.len())
+    }
+}
+
+/// Resource holding the ply currently shown while in replay mode
+/// (0 = start position, `history.len()` = latest position)
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct ReplayCursor {
+    pub ply: usize,
+    /// Moves recorded when the history was browsed from the live board (wheel
+    /// or arrow keys); None for the Tab replay mode
+    pub browsing_from: Option<usize>,
+}
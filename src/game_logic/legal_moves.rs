@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
+use shakmaty::Move;
+use crate::ai::zobrist::ZobristKeys;
+use crate::drawbacks::{DrawbackRegistry, definition::{DrawbackRule, TurnContext}};
+use super::state::{GameState, ActiveBoard};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// The moves of one position split by the drawback of the side to move
+#[derive(Debug, Default)]
+struct FilteredMoves {
+    moves: Vec<Move>,
+    blocked: Vec<Move>, // Legal moves the drawback took away
+    unfiltered_count: usize, // Legal moves before the drawback was applied
+}
+
+impl FilteredMoves {
+    fn split(legal: Vec<Move>, moves: Vec<Move>) -> Self {
+        let unfiltered_count = legal.len();
+        let blocked = legal.into_iter().filter(|chess_move| !moves.contains(chess_move)).collect();
+        Self { moves, blocked, unfiltered_count }
+    }
+}
+
+/// Filtering that runs on the compute pool for a position
+struct PendingMoves {
+    position_key: u64,
+    started_at: Instant,
+    task: Task<FilteredMoves>,
+}
+
+/// Resource with the moves the side to move may play on the active board,
+/// already filtered by its drawback. Generated once per position and shared
+/// by the selection highlights and move validation. At the start of a turn
+/// the filtering runs as a background task, like the AI's search, so long
+/// move lists don't hold up the frame; until it is done the cache is empty.
+#[derive(Resource, Default)]
+pub struct LegalMovesCache {
+    position_key: Option<u64>, // `GameState::position_key` the moves belong to
+    filtered: FilteredMoves,
+    pending: Option<PendingMoves>,
+}
+
+impl LegalMovesCache {
+    /// Regenerates the moves unless they already belong to this position,
+    /// waiting for them if they are still being filtered in the background.
+    /// Returns true if they had to be regenerated.
+    pub fn refresh(&mut self, game_state: &GameState, registry: &DrawbackRegistry, keys: &ZobristKeys) -> bool {
+        let key = game_state.position_key(keys);
+        if self.position_key == Some(key) {
+            return false;
+        }
+        match self.pending.take() {
+            Some(pending) if pending.position_key == key => {
+                self.filtered = future::block_on(pending.task);
+            }
+            _ => {
+                self.filtered = FilteredMoves::split(game_state.legal_moves(), game_state.allowed_moves(registry));
+            }
+        }
+        self.position_key = Some(key);
+        true
+    }
+
+    /// Starts filtering the moves of a new position in the background.
+    /// Returns true if a task was started (the cache is emptied until it finishes).
+    pub fn start_refresh(&mut self, game_state: &GameState, registry: &DrawbackRegistry, keys: &ZobristKeys) -> bool {
+        let key = game_state.position_key(keys);
+        if self.position_key == Some(key) || self.pending.as_ref().is_some_and(|pending| pending.position_key == key) {
+            return false;
+        }
+
+        let board = game_state.board.clone();
+        let legal = game_state.legal_moves();
+        let rule: Option<Arc<dyn DrawbackRule + Send + Sync>> = game_state.current_drawback_rule(registry);
+        let rng_outcome = game_state.current_turn_rng_outcome;
+        let last_move = game_state.last_move.clone();
+        let moves_since_capture = game_state.moves_since_capture(game_state.current_player_turn);
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            let turn = TurnContext { rng_outcome, last_move: last_move.as_ref(), moves_since_capture };
+            let moves = match rule {
+                Some(rule) => rule.filter_moves(&board, legal.clone(), &turn),
+                None => legal.clone(),
+            };
+            FilteredMoves::split(legal, moves)
+        });
+
+        // Replacing the task drops (cancels) the filtering of a position that is already gone
+        self.pending = Some(PendingMoves { position_key: key, started_at: Instant::now(), task });
+        self.position_key = None;
+        self.filtered = FilteredMoves::default();
+        true
+    }
+
+    /// Takes the background filtering's result once it is done.
+    /// Returns true if it just finished.
+    fn poll(&mut self) -> bool {
+        let Some(pending) = self.pending.as_mut() else {
+            return false;
+        };
+        let Some(filtered) = future::block_on(future::poll_once(&mut pending.task)) else {
+            return false;
+        };
+        self.position_key = self.pending.take().map(|pending| pending.position_key);
+        self.filtered = filtered;
+        true
+    }
+
+    /// Whether the moves of `key`'s position are in the cache
+    pub fn is_ready_for(&self, key: u64) -> bool {
+        self.position_key == Some(key)
+    }
+
+    /// How long the moves have been filtered in the background, None if they aren't
+    pub fn computing_for(&self) -> Option<std::time::Duration> {
+        self.pending.as_ref().map(|pending| pending.started_at.elapsed())
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.filtered.moves
+    }
+
+    pub fn contains(&self, chess_move: &Move) -> bool {
+        self.filtered.moves.contains(chess_move)
+    }
+
+    /// Moves the drawback of the side to move took away (for teaching mode)
+    pub fn blocked(&self) -> &[Move] {
+        &self.filtered.blocked
+    }
+
+    /// Legal moves of the position under normal chess rules
+    pub fn unfiltered_count(&self) -> usize {
+        self.filtered.unfiltered_count
+    }
+
+    /// How many of those moves the drawback took away
+    pub fn removed_count(&self) -> usize {
+        self.filtered.unfiltered_count.saturating_sub(self.filtered.moves.len())
+    }
+}
+
+/// How many moves the mover's drawback filtered out before one move
+#[derive(Debug, Clone, Copy)]
+pub struct TurnImpact {
+    pub legal_moves: usize,
+    pub allowed_moves: usize,
+}
+
+/// Resource recording the drawback impact of every move of the current game,
+/// in the same order as `MoveHistory`
+#[derive(Resource, Debug, Default)]
+pub struct DrawbackTelemetry {
+    pub turns: Vec<TurnImpact>,
+}
+
+impl DrawbackTelemetry {
+    /// Records the move about to be played with the moves cached for its position
+    pub fn record(&mut self, cache: &LegalMovesCache) {
+        self.turns.push(TurnImpact {
+            legal_moves: cache.unfiltered_count(),
+            allowed_moves: cache.moves().len(),
+        });
+    }
+}
+
+/// System to start filtering the moves as soon as a new position is on the
+/// board, and to fill the cache once they are ready
+pub fn refresh_legal_moves_cache(
+    boards: Query<&GameState, With<ActiveBoard>>,
+    registry: Res<DrawbackRegistry>,
+    keys: Res<ZobristKeys>,
+    mut cache: ResMut<LegalMovesCache>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    // Only mark the resource changed when the moves change, so readers can rely on change detection
+    let cache_bypass = cache.bypass_change_detection();
+    let started = cache_bypass.start_refresh(game_state, &registry, &keys);
+    if cache_bypass.poll() || started {
+        cache.set_changed();
+    }
+}
@@ -2,5 +2,20 @@ pub mod state;
 pub mod events;
 pub mod systems;
 pub mod plugin;
+pub mod history;
+pub mod notation;
+pub mod repetition;
+pub mod clock;
+pub mod legal_moves;
+pub mod rng;
+pub mod pgn;
+pub mod online_import;
+pub mod drops;
+pub mod captures;
+pub mod perspective;
+pub mod watchdog;
+pub mod startup_game;
 
- 
\ No newline at end of file
+ 
+#[cfg(debug_assertions)] // Time travel debugging only exists in dev builds
+pub mod time_travel;
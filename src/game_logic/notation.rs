@@ -0,0 +1,64 @@
+use shakmaty::{Chess, Move, Position, Role, Square};
+use shakmaty::san::{San, SanPlus, Suffix};
+use shakmaty::uci::Uci;
+use crate::drawbacks::mirror::diagonal_step;
+
+// Everything that writes or reads moves goes through here, so the move list,
+// PGN files, the API and the logs all agree on the notation.
+
+/// True if the move takes the enemy king, which ends a drawback game on the spot
+pub fn captures_king(position: &Chess, chess_move: &Move) -> bool {
+    position.board().piece_at(chess_move.to()).is_some_and(|piece| piece.role == Role::King)
+}
+
+/// SAN of a move played from `position_before`, with its "+" or "#" suffix.
+/// Capturing the king is written without a suffix: there is no position
+/// after it to give check in, the game is simply over.
+pub fn format_san(position_before: &Chess, chess_move: &Move) -> String {
+    if captures_king(position_before, chess_move) {
+        return San::from_move(position_before, chess_move).to_string();
+    }
+    if let Move::Normal { role: Role::Pawn, from, capture: None, to, promotion } = *chess_move {
+        if from.file() != to.file() {
+            // A pawn stepping diagonally without capturing (Mirror) is written
+            // with its file like a capture, but without the "x": "de3"
+            let san = San::Normal { role: Role::Pawn, file: Some(from.file()), rank: None, capture: false, to, promotion };
+            let mut after = position_before.clone();
+            after.play_unchecked(chess_move);
+            return SanPlus { san, suffix: Suffix::from_position(&after) }.to_string();
+        }
+    }
+    SanPlus::from_move(position_before.clone(), chess_move).to_string()
+}
+
+/// UCI of a move, with castling written as the king's two-square step (e1g1)
+pub fn format_uci(chess_move: &Move) -> String {
+    Uci::from_standard(chess_move).to_string()
+}
+
+/// Reads a SAN move ("Nf3", "exd8=Q+", "O-O") in `position`
+pub fn parse_san(position: &Chess, text: &str) -> Result<Move, String> {
+    let san = SanPlus::from_ascii(text.trim().as_bytes()).map_err(|e| format!("Invalid SAN '{}': {}", text, e))?;
+    san.san.to_move(position).or_else(|e| {
+        // "de3": a diagonal pawn step without a capture (see `format_san`)
+        let step = match san.san {
+            San::Normal { role: Role::Pawn, file: Some(file), rank: None, capture: false, to, promotion } => {
+                let from_rank = to.rank().offset(if position.turn().is_white() { -1 } else { 1 });
+                from_rank.and_then(|rank| diagonal_step(position, Square::from_coords(file, rank), to, promotion))
+            }
+            _ => None,
+        };
+        step.ok_or_else(|| format!("Illegal move '{}': {}", text, e))
+    })
+}
+
+/// Reads a UCI move ("e2e4", "e7e8q") in `position`
+pub fn parse_uci(position: &Chess, text: &str) -> Result<Move, String> {
+    let uci = Uci::from_ascii(text.trim().as_bytes()).map_err(|_| format!("Not a UCI move: {}", text))?;
+    uci.to_move(position).or_else(|_| match uci {
+        // Normal chess has no diagonal pawn step without a capture (Mirror does)
+        Uci::Normal { from, to, promotion } => diagonal_step(position, from, to, promotion).ok_or(()),
+        _ => Err(()),
+    })
+    .map_err(|_| format!("Illegal move: {}", text))
+}
@@ -0,0 +1,120 @@
+use std::error::Error;
+
+/// A game hosted on an online chess site
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameSource {
+    Lichess(String),             // 8 character game ID
+    ChessCom { id: String, daily: bool },
+}
+
+impl GameSource {
+    /// Human readable description for log messages
+    pub fn describe(&self) -> String {
+        match self {
+            GameSource::Lichess(id) => format!("lichess game {}", id),
+            GameSource::ChessCom { id, daily: false } => format!("chess.com live game {}", id),
+            GameSource::ChessCom { id, daily: true } => format!("chess.com daily game {}", id),
+        }
+    }
+}
+
+/// Parses a lichess/chess.com game URL or a bare lichess game ID.
+///
+/// Accepted forms include `https://lichess.org/abcdEFGH`, `lichess.org/abcdEFGH/black`,
+/// `abcdEFGH`, `https://www.chess.com/game/live/123456789` and
+/// `https://www.chess.com/game/daily/123456789`.
+pub fn parse_game_source(input: &str) -> Option<GameSource> {
+    let input = input.trim();
+    let without_scheme = input
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("www.");
+    let mut parts = without_scheme.split(['/', '?', '#']).filter(|part| !part.is_empty());
+
+    match parts.next()? {
+        "lichess.org" => {
+            // Player URLs append 4 more characters to the 8 character game ID
+            let id = parts.next()?;
+            if id.len() >= 8 && id.chars().all(|c| c.is_ascii_alphanumeric()) {
+                Some(GameSource::Lichess(id[..8].to_string()))
+            } else {
+                None
+            }
+        }
+        "chess.com" => {
+            // /game/live/<id>, /game/daily/<id> or the older /live/game/<id>
+            let segments: Vec<&str> = parts.collect();
+            let daily = segments.contains(&"daily");
+            let id = segments.iter().find(|segment| segment.chars().all(|c| c.is_ascii_digit()))?;
+            Some(GameSource::ChessCom { id: id.to_string(), daily })
+        }
+        bare if bare.len() == 8 && bare.chars().all(|c| c.is_ascii_alphanumeric()) => {
+            Some(GameSource::Lichess(bare.to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Downloads the PGN of a game
+#[cfg(feature = "network")]
+pub fn fetch_game_pgn(source: &GameSource) -> Result<String, Box<dyn Error>> {
+    match source {
+        GameSource::Lichess(id) => {
+            let url = format!("https://lichess.org/game/export/{}?evals=false&clocks=false", id);
+            let pgn = ureq::get(&url)
+                .set("Accept", "application/x-chess-pgn")
+                .call()?
+                .into_string()?;
+            Ok(pgn)
+        }
+        GameSource::ChessCom { id, daily } => fetch_chess_com_pgn(id, *daily),
+    }
+}
+
+/// Without the `network` feature there is no HTTP client to download with
+#[cfg(not(feature = "network"))]
+pub fn fetch_game_pgn(source: &GameSource) -> Result<String, Box<dyn Error>> {
+    Err(format!(
+        "Cannot download {}: built without the `network` feature (cargo run --features network)",
+        source.describe()
+    ).into())
+}
+
+// chess.com only exposes PGNs through the monthly archives of a player, so we
+// first look up the players and date of the game, then search that archive
+#[cfg(feature = "network")]
+fn fetch_chess_com_pgn(id: &str, daily: bool) -> Result<String, Box<dyn Error>> {
+    let kind = if daily { "daily" } else { "live" };
+    let info: serde_json::Value = serde_json::from_str(
+        &ureq::get(&format!("https://www.chess.com/callback/{}/game/{}", kind, id))
+            .call()?
+            .into_string()?,
+    )?;
+
+    let headers = &info["game"]["pgnHeaders"];
+    let player = headers["White"].as_str().ok_or("chess.com game has no White player")?;
+    let date = headers["Date"].as_str().ok_or("chess.com game has no date")?; // "2024.03.17"
+    let mut date_parts = date.split('.');
+    let (year, month) = match (date_parts.next(), date_parts.next()) {
+        (Some(year), Some(month)) => (year, month),
+        _ => return Err(format!("Unexpected chess.com date '{}'", date).into()),
+    };
+
+    let archive: serde_json::Value = serde_json::from_str(
+        &ureq::get(&format!(
+            "https://api.chess.com/pub/player/{}/games/{}/{}",
+            player.to_lowercase(), year, month
+        ))
+        .call()?
+        .into_string()?,
+    )?;
+
+    archive["games"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|game| game["url"].as_str().is_some_and(|url| url.ends_with(&format!("/{}", id))))
+        .and_then(|game| game["pgn"].as_str())
+        .map(str::to_string)
+        .ok_or_else(|| format!("Game {} not found in {}'s {}/{} archive", id, player, year, month).into())
+}
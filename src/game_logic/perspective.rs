@@ -0,0 +1,87 @@
+use shakmaty::{Color, File, Rank, Square};
+
+/// Ranks and files as one side sees the board from its seat: rank 0 is its
+/// own back rank and rank 7 the opponent's, file 0 is on its left. Rules that
+/// care about "forward", "own half" or "home rank" go through this instead of
+/// doing the arithmetic for each color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerPerspective {
+    color: Color,
+}
+
+impl PlayerPerspective {
+    pub fn new(color: Color) -> Self {
+        Self { color }
+    }
+
+    pub fn color(self) -> Color {
+        self.color
+    }
+
+    /// This side's back rank (where its king starts)
+    pub fn back_rank(self) -> Rank {
+        self.color.fold_wb(Rank::First, Rank::Eighth)
+    }
+
+    /// Rank of `square` counted from this side's back rank (0-7)
+    pub fn rank(self, square: Square) -> u8 {
+        u8::from(self.square_as_white(square).rank())
+    }
+
+    /// File of `square` counted from this side's left (0-7)
+    pub fn file(self, square: Square) -> u8 {
+        let file = square.file();
+        u8::from(self.color.fold_wb(file, file.flip_horizontal()))
+    }
+
+    /// The square `rank` ranks up from this side's back rank and `file` files
+    /// in from its left (both 0-7)
+    pub fn square(self, file: u8, rank: u8) -> Square {
+        let square = Square::from_coords(File::new(u32::from(file)), Rank::new(u32::from(rank)));
+        self.color.fold_wb(square, square.flip_horizontal().flip_vertical())
+    }
+
+    /// The square as White sees it: unchanged for White, mirrored vertically
+    /// for Black. Piece-square tables written for White are read through this.
+    pub fn square_as_white(self, square: Square) -> Square {
+        self.color.fold_wb(square, square.flip_vertical())
+    }
+
+    /// Ranks gained toward the opponent from `from` to `to` (negative = backwards)
+    pub fn ranks_forward(self, from: Square, to: Square) -> i8 {
+        self.rank(to) as i8 - self.rank(from) as i8
+    }
+
+    /// Whether `square` is in this side's half of the board (its ranks 0-3)
+    pub fn in_own_half(self, square: Square) -> bool {
+        self.rank(square) < 4
+    }
+
+    /// Whether `square` is on a rank in front of `of` from this side's seat
+    pub fn is_ahead(self, square: Square, of: Square) -> bool {
+        self.ranks_forward(of, square) > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn both_sides_see_the_board_from_their_own_seat() {
+        let white = PlayerPerspective::new(Color::White);
+        let black = PlayerPerspective::new(Color::Black);
+
+        assert_eq!((white.rank(Square::E2), black.rank(Square::E7)), (1, 1));
+        assert_eq!((white.file(Square::A1), black.file(Square::H8)), (0, 0));
+        assert_eq!((white.square(0, 1), black.square(0, 1)), (Square::A2, Square::H7));
+        assert_eq!(black.back_rank(), Rank::Eighth);
+        assert_eq!(black.square_as_white(Square::D7), Square::D2);
+
+        assert_eq!(white.ranks_forward(Square::E2, Square::E4), 2);
+        assert_eq!(black.ranks_forward(Square::E7, Square::E5), 2);
+        assert_eq!(black.ranks_forward(Square::E5, Square::E6), -1);
+        assert!(white.in_own_half(Square::H4) && !black.in_own_half(Square::H4));
+        assert!(black.is_ahead(Square::A1, Square::B2) && !white.is_ahead(Square::A1, Square::B2));
+    }
+}
@@ -0,0 +1,403 @@
+use shakmaty::{fen::Fen, Chess, CastlingMode, EnPassantMode, Position, Color as ChessColor};
+use super::notation::parse_san;
+use std::error::Error;
+use super::events::{GameOverReason, GameResult};
+use super::history::{MoveHistory, nag_from_glyph};
+use super::state::GameState;
+use crate::drawbacks::{DrawbackId, DrawbackParams, DrawbackRegistry};
+
+/// The seven tag roster every PGN file should start with, in order
+const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+/// A game read from a PGN file
+#[derive(Debug, Clone)]
+pub struct ImportedGame {
+    pub tags: Vec<(String, String)>,
+    pub history: MoveHistory,
+}
+
+impl ImportedGame {
+    /// Looks up a tag value by name
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    /// A player's drawback from the WhiteDrawback/BlackDrawback tag, None if
+    /// there is no such tag or it names no known drawback
+    pub fn drawback(&self, color: ChessColor, registry: &DrawbackRegistry) -> Option<DrawbackId> {
+        let name = self.tag(color.fold_wb("WhiteDrawback", "BlackDrawback"))?;
+        match name {
+            "None" => Some(DrawbackId::None),
+            _ => registry.id_by_name(name),
+        }
+    }
+
+    /// Parameters of a player's drawback from the WhiteDrawbackParams/BlackDrawbackParams
+    /// tag, None if there is no such tag (or it can't be read)
+    pub fn drawback_params(&self, color: ChessColor) -> Option<DrawbackParams> {
+        let name = color.fold_wb("WhiteDrawbackParams", "BlackDrawbackParams");
+        DrawbackParams::from_tag(self.tag(name)?)
+            .map_err(|e| eprintln!("Ignoring {} tag: {}", name, e))
+            .ok()
+    }
+}
+
+/// Result and Termination tags for a game that ended, none while it is still running
+pub fn outcome_tags(outcome: Option<GameResult>) -> Vec<(String, String)> {
+    let Some(result) = outcome else {
+        return Vec::new();
+    };
+    let termination = match result.reason {
+        GameOverReason::Timeout { .. } => "time forfeit",
+        GameOverReason::Abandoned { .. } => "abandoned",
+        _ => "normal",
+    };
+    vec![
+        ("Result".to_string(), result.pgn_result().to_string()),
+        ("Termination".to_string(), termination.to_string()),
+        // Not a standard tag, but tells readers which ending it was
+        ("DrawbackEnding".to_string(), result.reason.to_string()),
+    ]
+}
+
+/// WhiteDrawback/BlackDrawback tags naming both drawbacks, and
+/// WhiteDrawbackParams/BlackDrawbackParams for the ones with parameters
+pub fn drawback_tags(game_state: &GameState, registry: &DrawbackRegistry) -> Vec<(String, String)> {
+    let mut tags = Vec::new();
+    for (color, prefix) in [(ChessColor::White, "White"), (ChessColor::Black, "Black")] {
+        let (id, params) = game_state.drawback_of(color);
+        let name = registry.rules.get(&id).map(|rule| rule.name()).unwrap_or("None");
+        tags.push((format!("{}Drawback", prefix), name.to_string()));
+        if !params.is_empty() {
+            tags.push((format!("{}DrawbackParams", prefix), params.to_string()));
+        }
+    }
+    tags
+}
+
+/// Serializes the move history (with comments and NAGs) as a PGN game.
+/// `tags` override the defaults of the seven tag roster and may add custom tags.
+pub fn write_pgn(history: &MoveHistory, tags: &[(String, String)]) -> String {
+    let mut out = String::new();
+
+    // Seven tag roster first (with defaults), then any extra tags
+    for name in SEVEN_TAG_ROSTER {
+        let value = tags
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+            .unwrap_or_else(|| default_tag_value(name).to_string());
+        out.push_str(&format!("[{} \"{}\"]\n", name, escape_tag_value(&value)));
+    }
+    for (name, value) in tags {
+        if !SEVEN_TAG_ROSTER.contains(&name.as_str()) {
+            out.push_str(&format!("[{} \"{}\"]\n", name, escape_tag_value(value)));
+        }
+    }
+
+    // Non-standard start positions need SetUp/FEN tags
+    if history.start_position != Chess::default() {
+        let fen = Fen::from_position(history.start_position.clone(), EnPassantMode::Legal);
+        out.push_str("[SetUp \"1\"]\n");
+        out.push_str(&format!("[FEN \"{}\"]\n", fen));
+    }
+    out.push('\n');
+
+    // Movetext
+    let mut tokens: Vec<String> = Vec::new();
+    let mut position = history.start_position.clone();
+    let mut need_move_number = true;
+    for record in &history.moves {
+        let fullmove = position.fullmoves().get();
+        if position.turn() == ChessColor::White {
+            tokens.push(format!("{}.", fullmove));
+        } else if need_move_number {
+            tokens.push(format!("{}...", fullmove));
+        }
+        need_move_number = false;
+
+        tokens.push(record.san.clone());
+        for nag in &record.nags {
+            tokens.push(format!("${}", nag));
+        }
+        // Clock after the move and time spent on it as embedded commands, then the comment
+        let mut comment: Vec<String> = Vec::new();
+        if let Some(clock_ms) = record.clock_ms {
+            comment.push(format!("[%clk {}]", format_pgn_time(clock_ms, false)));
+        }
+        if let Some(time_ms) = record.time_ms {
+            comment.push(format!("[%emt {}]", format_pgn_time(time_ms, true)));
+        }
+        if let Some(text) = &record.comment {
+            // Braces can't be nested inside PGN comments
+            comment.push(text.replace('}', ")"));
+        }
+        if !comment.is_empty() {
+            tokens.push(format!("{{{}}}", comment.join(" ")));
+            // After a comment the next black move needs its number repeated
+            need_move_number = true;
+        }
+
+        position.play_unchecked(&record.chess_move);
+    }
+
+    let result = tags
+        .iter()
+        .find(|(key, _)| key == "Result")
+        .map(|(_, value)| value.clone())
+        .unwrap_or_else(|| "*".to_string());
+    tokens.push(result);
+
+    // Wrap lines at 80 characters as recommended by the PGN standard
+    let mut line_len = 0;
+    for token in tokens {
+        if line_len > 0 && line_len + 1 + token.len() > 80 {
+            out.push('\n');
+            line_len = 0;
+        } else if line_len > 0 {
+            out.push(' ');
+            line_len += 1;
+        }
+        line_len += token.len();
+        out.push_str(&token);
+    }
+    out.push('\n');
+
+    out
+}
+
+/// Splits a PGN text with several games (a database) into one text per game:
+/// a new game starts at the first tag line after some movetext
+pub fn split_pgn_games(text: &str) -> Vec<String> {
+    let mut games = Vec::new();
+    let mut current = String::new();
+    let mut in_movetext = false;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && in_movetext {
+            games.push(std::mem::take(&mut current));
+            in_movetext = false;
+        } else if !trimmed.is_empty() && !trimmed.starts_with('[') {
+            in_movetext = true;
+        }
+        current.push_str(line);
+        current.push('\n');
+    }
+    if !current.trim().is_empty() {
+        games.push(current);
+    }
+    games
+}
+
+/// Parses the first game of a PGN text, including comments and NAGs.
+/// Variations are skipped; only the main line is imported.
+pub fn read_pgn(text: &str) -> Result<ImportedGame, Box<dyn Error>> {
+    let mut tags: Vec<(String, String)> = Vec::new();
+    let mut movetext = String::new();
+
+    // Split header and movetext
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && trimmed.ends_with(']') && movetext.trim().is_empty() {
+            if let Some((name, value)) = parse_tag_line(trimmed) {
+                tags.push((name, value));
+            }
+        } else if !trimmed.starts_with('%') {
+            movetext.push_str(line);
+            movetext.push('\n');
+        }
+    }
+
+    // Starting position
+    let start_position = match tags.iter().find(|(key, _)| key == "FEN") {
+        Some((_, fen)) => {
+            let fen = Fen::from_ascii(fen.as_bytes())?;
+            fen.into_position::<Chess>(CastlingMode::Standard)?
+        }
+        None => Chess::default(),
+    };
+
+    let mut history = MoveHistory::new(start_position.clone());
+    let mut position = start_position;
+    let chars: Vec<char> = movetext.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '{' => {
+                // Comment up to the closing brace, attached to the last move
+                let end = chars[i..].iter().position(|&ch| ch == '}').map(|p| i + p)
+                    .ok_or("Unterminated comment in PGN")?;
+                let comment: String = chars[i + 1..end].iter().collect();
+                if let Some(last) = history.moves.last_mut() {
+                    last.clock_ms = embedded_time(&comment, "clk").or(last.clock_ms);
+                    last.time_ms = embedded_time(&comment, "emt").or(last.time_ms);
+                }
+                let comment = strip_embedded_commands(&comment);
+                let comment = comment.split_whitespace().collect::<Vec<_>>().join(" ");
+                if let Some(last) = history.moves.last_mut() {
+                    if !comment.is_empty() {
+                        last.comment = Some(match last.comment.take() {
+                            Some(existing) => format!("{} {}", existing, comment),
+                            None => comment,
+                        });
+                    }
+                }
+                i = end + 1;
+            }
+            ';' => {
+                // Rest-of-line comment
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '(' => {
+                // Skip (possibly nested) variations
+                let mut depth = 0;
+                while i < chars.len() {
+                    match chars[i] {
+                        '(' => depth += 1,
+                        ')' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                    i += 1;
+                }
+                i += 1;
+            }
+            '$' => {
+                let start = i + 1;
+                let mut end = start;
+                while end < chars.len() && chars[end].is_ascii_digit() {
+                    end += 1;
+                }
+                let nag: String = chars[start..end].iter().collect();
+                if let (Ok(nag), Some(last)) = (nag.parse::<u8>(), history.moves.last_mut()) {
+                    last.nags.push(nag);
+                }
+                i = end;
+            }
+            _ if c.is_whitespace() => {
+                i += 1;
+            }
+            _ => {
+                // A token: move number, result or SAN move
+                let start = i;
+                while i < chars.len() && !chars[i].is_whitespace() && !"{}();$".contains(chars[i]) {
+                    i += 1;
+                }
+                let token: String = chars[start..i].iter().collect();
+
+                if matches!(token.as_str(), "1-0" | "0-1" | "1/2-1/2" | "*") {
+                    break; // End of game
+                }
+
+                // Strip move numbers like "12." or "12..." (possibly glued to the move)
+                let token = strip_move_number(&token);
+                if token.is_empty() {
+                    continue;
+                }
+
+                // Split off suffix annotations like "!?" which sit after the SAN
+                let san_end = token.find(['!', '?']).unwrap_or(token.len());
+                let (san_text, glyph) = token.split_at(san_end);
+
+                // Castling is sometimes written with zeros ("0-0", "0-0-0")
+                let san_text = if san_text.starts_with("0-0") { san_text.replace('0', "O") } else { san_text.to_string() };
+                let chess_move = parse_san(&position, &san_text)?;
+
+                history.push(&position, chess_move.clone());
+                if let Some(nag) = nag_from_glyph(glyph) {
+                    if let Some(last) = history.moves.last_mut() {
+                        last.nags.push(nag);
+                    }
+                }
+                position.play_unchecked(&chess_move);
+            }
+        }
+    }
+
+    Ok(ImportedGame { tags, history })
+}
+
+// Removes a move number like "12." or "12..." from the start of a token; digits
+// without a dot after them are left alone (zeros of castling like "0-0")
+fn strip_move_number(token: &str) -> &str {
+    let rest = token.trim_start_matches(|ch: char| ch.is_ascii_digit());
+    match rest.strip_prefix('.') {
+        Some(rest) if rest.len() + 1 < token.len() => rest.trim_start_matches('.'),
+        _ => token,
+    }
+}
+
+// Formats a time for `%clk`/`%emt` as h:mm:ss, optionally with tenths
+fn format_pgn_time(ms: u64, tenths: bool) -> String {
+    let seconds = ms / 1000;
+    let time = format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if tenths {
+        format!("{}.{}", time, ms % 1000 / 100)
+    } else {
+        time
+    }
+}
+
+// Reads the time of an embedded command like `[%clk 0:09:58]` (fractions allowed) in milliseconds
+fn embedded_time(comment: &str, command: &str) -> Option<u64> {
+    let start = comment.find(&format!("[%{} ", command))? + command.len() + 3;
+    let value = comment[start..].split(']').next()?.trim();
+    let mut seconds = 0.0;
+    for part in value.split(':') {
+        seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+    }
+    Some((seconds * 1000.0).round() as u64)
+}
+
+// Removes embedded commands like `[%clk 0:09:58]` or `[%eval 0.3]` from a comment
+fn strip_embedded_commands(comment: &str) -> String {
+    let mut out = String::new();
+    let mut rest = comment;
+    while let Some(start) = rest.find("[%") {
+        out.push_str(&rest[..start]);
+        match rest[start..].find(']') {
+            Some(end) => rest = &rest[start + end + 1..],
+            None => {
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// Default values for the seven tag roster
+fn default_tag_value(name: &str) -> &'static str {
+    match name {
+        "Event" => "Drawback Chess",
+        "Site" => "Local",
+        "Date" => "????.??.??",
+        "Round" => "-",
+        "White" => "White",
+        "Black" => "Black",
+        "Result" => "*",
+        _ => "?",
+    }
+}
+
+// Quotes and backslashes must be escaped inside tag values
+fn escape_tag_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// Parses a line like `[White "Magnus"]`
+fn parse_tag_line(line: &str) -> Option<(String, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let (name, rest) = inner.split_once(char::is_whitespace)?;
+    let rest = rest.trim();
+    let value = rest.strip_prefix('"')?.strip_suffix('"')?;
+    Some((name.to_string(), value.replace("\\\"", "\"").replace("\\\\", "\\")))
+}
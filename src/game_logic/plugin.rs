@@ -1,32 +1,122 @@
 use bevy::prelude::*;
-use shakmaty::{fen::Fen, Chess, Color, Role, Move, CastlingMode, Position, Setup};
-use crate::drawbacks::DrawbackId;
-use crate::config::GameConfig;
-use crate::drawbacks::registry::DrawbackRegistry;
+use shakmaty::{fen::Fen, Chess, Color, CastlingMode, Position};
+use crate::config::{DrawbackSetting, GameConfig};
+use crate::drawbacks::DrawbackRegistry;
 use crate::constants::DEFAULT_BOARD_FLIPPED;
-use super::state::{GameState, TurnState, GameStatus};
-use super::systems::apply_move;
-use super::events::{MakeMoveEvent, GameOverEvent};
+use super::state::{GameState, GameBoard, ActiveBoard, TurnState, GameStatus, PauseState, ReplayState, AppState, TutorialState, MoveRestriction, gameplay_active};
+use super::history::{MoveHistory, ReplayCursor};
+use super::repetition::RepetitionTable;
+use super::drops::Reserves;
+use super::captures::{CaptureLog, update_capture_log};
+use super::legal_moves::{LegalMovesCache, DrawbackTelemetry, refresh_legal_moves_cache};
+use super::clock::{GameClock, ClockThresholdEvent, MoveTimer, reset_clock, tick_clock, tick_move_timer};
+use super::systems::{apply_move, resume_loaded_game, start_next_turn, swap_sides, take_back_moves};
+use super::startup_game::load_startup_game;
+use super::rng::{GameRng, restart_game_rng};
+use super::watchdog::{TurnWatchdog, watch_turn_state};
+use super::events::{MakeMoveEvent, MovePlayedEvent, MoveRejectedEvent, GameOverEvent, FlipBoardEvent, ForceAiMoveEvent, SwapSidesEvent, SharePositionEvent, NewGameEvent, LoadGameEvent, UndoMoveEvent, RedoMoveEvent};
+use crate::ai::zobrist::ZobristKeys;
+#[cfg(debug_assertions)]
+use super::time_travel::{TimeTravel, record_game_state_snapshots, handle_time_travel_keys};
+#[cfg(debug_assertions)]
+use crate::input::focus::keyboard_shortcuts_enabled;
 
 pub struct GameLogicPlugin;
 
 /// The standard chess starting position FEN (including castling rights)
 pub const STANDARD_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
-/// The flipped chess starting position FEN (black at bottom, white at top)
-pub const FLIPPED_FEN: &str = "RNBQKBNR/PPPPPPPP/8/8/8/8/pppppppp/rnbqkbnr w - - 0 1";
-
 impl Plugin for GameLogicPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_state::<TurnState>()
+            .add_state::<PauseState>()
+            .add_state::<ReplayState>()
+            .add_state::<AppState>()
+            .add_state::<TutorialState>()
+            .init_resource::<ReplayCursor>()
+            .init_resource::<MoveRestriction>()
+            .init_resource::<LegalMovesCache>()
+            .init_resource::<DrawbackTelemetry>()
+            .init_resource::<TurnWatchdog>()
+            .init_resource::<MoveTimer>()
+            .init_resource::<CaptureLog>()
             .add_event::<MakeMoveEvent>()
+            .add_event::<MoveRejectedEvent>()
+            .add_event::<MovePlayedEvent>()
             .add_event::<GameOverEvent>()
-            .add_systems(Startup, init_game_state)
+            .add_event::<FlipBoardEvent>()
+            .add_event::<ForceAiMoveEvent>()
+            .add_event::<SwapSidesEvent>()
+            .add_event::<SharePositionEvent>()
+            .add_event::<UndoMoveEvent>()
+            .add_event::<RedoMoveEvent>()
+            .add_event::<NewGameEvent>()
+            .add_event::<LoadGameEvent>()
+            .add_event::<ClockThresholdEvent>()
+            .add_systems(Startup, (init_game_state, load_startup_game))
+            .add_systems(Update, start_new_game)
+            // The start position's roll comes from the restarted rules stream
+            .add_systems(Update, restart_game_rng.before(start_new_game).run_if(on_event::<NewGameEvent>()))
+            // A loaded game goes on from its last move (--fen / --pgn)
+            .add_systems(
+                Update,
+                resume_loaded_game
+                    .after(start_new_game)
+                    .before(refresh_legal_moves_cache)
+                    .run_if(on_event::<LoadGameEvent>())
+            )
+            // Take backs rebuild the position before its moves are filtered
+            .add_systems(
+                Update,
+                take_back_moves
+                    .after(start_new_game)
+                    .before(refresh_legal_moves_cache)
+                    .run_if(on_event::<UndoMoveEvent>().or_else(on_event::<RedoMoveEvent>()))
+                    .run_if(gameplay_active)
+            )
+            // Moves of the side to move, ready before input and apply_move need them
+            .add_systems(Update, refresh_legal_moves_cache.after(start_new_game).before(apply_move))
+            .add_systems(
+                Update,
+                apply_move
+                    .run_if(in_state(TurnState::PlayerTurn).or_else(in_state(TurnState::AiTurn)))
+                    .run_if(gameplay_active)
+            )
+            // Once the new position's moves are filtered, the next turn starts (or the game ends)
+            .add_systems(
+                Update,
+                start_next_turn
+                    .after(refresh_legal_moves_cache)
+                    .run_if(in_state(TurnState::ProcessingMove))
+                    .run_if(gameplay_active)
+            )
+            .add_systems(Update, swap_sides.run_if(on_event::<SwapSidesEvent>()))
+            // Captured pieces and material balance for the trays beside the board
+            .add_systems(Update, update_capture_log.after(apply_move).after(take_back_moves))
+            // Unsticks a game that never finishes processing a move
+            .add_systems(Update, watch_turn_state.after(start_next_turn).run_if(gameplay_active))
+            // Chess clocks (when enabled in the time control settings) and the time spent per move
             .add_systems(
                 Update,
-                apply_move.run_if(in_state(TurnState::PlayerTurn).or_else(in_state(TurnState::AiTurn)))
+                (
+                    reset_clock.after(start_new_game),
+                    tick_clock.after(apply_move).run_if(gameplay_active),
+                    tick_move_timer.after(start_new_game).before(apply_move).run_if(gameplay_active),
+                )
             );
+
+        // Dev builds keep the engine state after every move to step through (F5/F6) and dump (F7)
+        #[cfg(debug_assertions)]
+        app.init_resource::<TimeTravel>()
+           .add_systems(
+               Update,
+               (
+                   record_game_state_snapshots.after(start_new_game).after(apply_move),
+                   handle_time_travel_keys.run_if(keyboard_shortcuts_enabled).run_if(gameplay_active),
+               )
+                   .chain()
+           );
     }
 }
 
@@ -34,61 +124,110 @@ impl Plugin for GameLogicPlugin {
 fn init_game_state(
     mut commands: Commands,
     config: Res<GameConfig>,
-    drawback_registry: Res<DrawbackRegistry>,
-    zobrist_keys: Res<crate::ai::zobrist::ZobristKeys>,
+    zobrist_keys: Res<ZobristKeys>,
+    registry: Res<DrawbackRegistry>,
+) {
+    let mut rng = GameRng::new(config.rng_seed);
+    let game_state = new_game_state(&config, &zobrist_keys, &registry, &mut rng, DEFAULT_BOARD_FLIPPED, None);
+
+    // Start recording moves and positions from the initial position
+    commands.insert_resource(MoveHistory::new(game_state.board.clone()));
+    commands.insert_resource(RepetitionTable::new(game_state.position_key(&zobrist_keys)));
+    commands.insert_resource(GameClock::new(&config.time_control));
+    println!("Game RNG seed: {}", rng.game_seed());
+    commands.insert_resource(rng);
+
+    // The live game is the first board and starts out active
+    commands.spawn((GameBoard, ActiveBoard, game_state));
+}
+
+/// System to throw away the current game and start a new one when requested
+#[allow(clippy::too_many_arguments)]
+pub fn start_new_game(
+    mut ev_new_game: EventReader<NewGameEvent>,
+    config: Res<GameConfig>,
+    zobrist_keys: Res<ZobristKeys>,
+    registry: Res<DrawbackRegistry>,
+    mut rng: ResMut<GameRng>,
+    mut boards: Query<&mut GameState, With<ActiveBoard>>,
+    mut history: ResMut<MoveHistory>,
+    mut repetitions: ResMut<RepetitionTable>,
+    mut telemetry: ResMut<DrawbackTelemetry>,
+    mut next_turn_state: ResMut<NextState<TurnState>>,
 ) {
-    // Use the standard chess position FEN instead of the flipped one
-    // This matches the visual representation (white at bottom, black at top)
-    let fen = Fen::from_ascii(STANDARD_FEN.as_bytes()).expect("Valid FEN");
-    let chess: Chess = fen.into_position(CastlingMode::Standard).expect("Valid position");
+    // Only the latest request matters
+    let Some(start_position) = ev_new_game.read().last().map(|ev| ev.start_position.clone()) else {
+        return;
+    };
+    let Ok(mut game_state) = boards.get_single_mut() else {
+        return;
+    };
+
+    println!("Starting a new game");
+    // Keep the board orientation the player chose
+    *game_state = new_game_state(&config, &zobrist_keys, &registry, &mut rng, game_state.board_flipped, start_position);
+    *history = MoveHistory::new(game_state.board.clone());
+    *repetitions = RepetitionTable::new(game_state.position_key(&zobrist_keys));
+    *telemetry = DrawbackTelemetry::default();
+    next_turn_state.set(match game_state.current_player_turn {
+        Color::White => TurnState::PlayerTurn,
+        Color::Black => TurnState::AiTurn,
+    });
+}
+
+// Helper function to build the state of a fresh game from the configuration
+// (standard start position unless `start_position` is given). Random
+// drawbacks are rolled here, from the session stream of `rng`; the first
+// turn's roll comes from its rules stream.
+fn new_game_state(
+    config: &GameConfig,
+    zobrist_keys: &ZobristKeys,
+    registry: &DrawbackRegistry,
+    rng: &mut GameRng,
+    board_flipped: bool,
+    start_position: Option<Chess>,
+) -> GameState {
+    let chess = start_position.unwrap_or_else(|| {
+        // Use the standard chess position FEN instead of the flipped one
+        // This matches the visual representation (white at bottom, black at top)
+        let fen = Fen::from_ascii(STANDARD_FEN.as_bytes()).expect("Valid FEN");
+        fen.into_position(CastlingMode::Standard).expect("Valid position")
+    });
     
     // Initialize the GameState with default drawbacks from config
-    let white_drawback_id = config.resolve_drawback_id(&config.white_player.drawback);
-    let black_drawback_id = config.resolve_drawback_id(&config.black_player.drawback);
+    let mut drawback_id = |setting: &DrawbackSetting| {
+        if setting.is_random() {
+            registry.random_id(rng.session())
+        } else {
+            config.resolve_drawback_id(setting)
+        }
+    };
+    let white_drawback_id = drawback_id(&config.white_player.drawback);
+    let black_drawback_id = drawback_id(&config.black_player.drawback);
     
     println!("Initializing game with drawbacks - White: {:?}, Black: {:?}", 
              white_drawback_id, black_drawback_id);
     
-    // Initialize and insert the GameState resource
     let mut game_state = GameState {
+        current_player_turn: chess.turn(),
         board: chess,
-        current_player_turn: Color::White,
         white_drawback: white_drawback_id,
         black_drawback: black_drawback_id,
+        white_drawback_params: config.white_player.drawback.params.clone(),
+        black_drawback_params: config.black_player.drawback.params.clone(),
         zobrist_hash: 0,  // Will be initialized properly
         status: GameStatus::Ongoing,
         current_turn_rng_outcome: None,
-        board_flipped: DEFAULT_BOARD_FLIPPED,
+        last_move: None,
+        board_flipped,
+        reserves: Reserves::default(),
+        white_moves_since_capture: 0,
+        black_moves_since_capture: 0,
     };
 
-    // Update the zobrist hash with the initial position
-    let hash = crate::ai::zobrist::calculate_zobrist_hash_for_board(&game_state.board, &zobrist_keys);
-    game_state.zobrist_hash = hash;
-    
-    // Insert the initialized GameState as a resource
-    commands.insert_resource(game_state);
-}
+    game_state.roll_turn(registry, rng.rules());
 
-// Check if a move captures the king (used for Drawback Chess win condition)
-pub fn is_king_capture(board: &Chess, game_move: &Move) -> bool {
-    if let Some(piece) = board.board().piece_at(game_move.to()) {
-        return piece.role == Role::King;
-    }
-    false
+    // Update the zobrist hash with the initial position
+    game_state.zobrist_hash = game_state.position_key(zobrist_keys);
+    game_state
 }
-
-fn setup_drawbacks(
-    mut commands: Commands,
-    mut game_state: ResMut<GameState>,
-    config: Res<GameConfig>,
-) {
-    println!("Setting up drawbacks from configuration...");
-    
-    // Get drawback IDs from configuration
-    let white_drawback_id = config.resolve_drawback_id(&config.white_player.drawback);
-    let black_drawback_id = config.resolve_drawback_id(&config.black_player.drawback);
-    
-    // Set drawbacks in game state
-    game_state.white_drawback = white_drawback_id;
-    game_state.black_drawback = black_drawback_id;
-} 
\ No newline at end of file
@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// How often a position has to occur for the game to be drawn by repetition
+pub const REPETITION_DRAW_COUNT: u32 = 3;
+
+/// Position keys (see `GameState::position_key`) of the positions played so far.
+/// Works as a stack so a search can push the positions along its current line
+/// and pop them again while backing up, with counts kept in sync.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct RepetitionTable {
+    keys: Vec<u64>,
+    counts: HashMap<u64, u32>,
+}
+
+impl RepetitionTable {
+    /// A table holding just the starting position
+    pub fn new(start_key: u64) -> Self {
+        let mut table = Self::default();
+        table.push(start_key);
+        table
+    }
+
+    /// Records that the position with `key` was reached
+    pub fn push(&mut self, key: u64) {
+        self.keys.push(key);
+        *self.counts.entry(key).or_insert(0) += 1;
+    }
+
+    /// Takes back the last recorded position
+    pub fn pop(&mut self) -> Option<u64> {
+        let key = self.keys.pop()?;
+        if let Some(count) = self.counts.get_mut(&key) {
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(&key);
+            }
+        }
+        Some(key)
+    }
+
+    /// How often the position with `key` occurred
+    pub fn count(&self, key: u64) -> u32 {
+        self.counts.get(&key).copied().unwrap_or(0)
+    }
+
+    /// True if the latest position occurred before. Searches treat this as a draw.
+    pub fn is_repetition(&self) -> bool {
+        self.keys.last().is_some_and(|key| self.count(*key) > 1)
+    }
+
+    /// True if the latest position occurred `REPETITION_DRAW_COUNT` times, drawing the game
+    pub fn is_draw(&self) -> bool {
+        self.keys.last().is_some_and(|key| self.count(*key) >= REPETITION_DRAW_COUNT)
+    }
+}
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+// Mixed into the game seed so every stream gets its own sequence
+const RULES_STREAM: u64 = 0x9E37_79B9_7F4A_7C15;
+const AI_STREAM: u64 = 0xC2B2_AE3D_27D4_EB4F;
+
+/// Resource all game randomness goes through, so a game can be replayed
+/// exactly from its seed. Separate streams keep the AI's tie-breaks from
+/// shifting the rolls of the game rules and the other way round:
+/// - rules: rolled in order during a game (restarts with every game)
+/// - AI: derived from the position, so a position always gets the same choice
+/// - session: things outside the game rules, like picking the next game's
+///   drawbacks or the move played for a player who ran out of thinking time
+#[derive(Resource, Debug, Clone)]
+pub struct GameRng {
+    configured_seed: Option<u64>,
+    game_seed: u64,
+    rules: StdRng,
+    session: StdRng,
+}
+
+impl GameRng {
+    /// Uses the configured seed, or a random one if there is none
+    pub fn new(seed: Option<u64>) -> Self {
+        let mut session = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let game_seed = seed.unwrap_or_else(|| session.gen());
+        Self {
+            configured_seed: seed,
+            game_seed,
+            rules: StdRng::seed_from_u64(game_seed ^ RULES_STREAM),
+            session,
+        }
+    }
+
+    /// Seed of the current game. Putting it in the config replays the game's randomness.
+    pub fn game_seed(&self) -> u64 {
+        self.game_seed
+    }
+
+    /// Starts the streams over for a new game: with a configured seed every game
+    /// gets the same rolls, otherwise each game gets a fresh seed
+    pub fn start_game(&mut self) {
+        self.game_seed = match self.configured_seed {
+            Some(seed) => seed,
+            None => self.session.gen(),
+        };
+        self.restart_rules();
+        println!("Game RNG seed: {}", self.game_seed);
+    }
+
+    /// Starts the rules stream of the current game over, so replaying its
+    /// moves rolls what was rolled the first time
+    pub fn restart_rules(&mut self) {
+        self.rules = StdRng::seed_from_u64(self.game_seed ^ RULES_STREAM);
+    }
+
+    /// Stream for random game rules (e.g. a drawback rolled at the start of a turn)
+    pub fn rules(&mut self) -> &mut StdRng {
+        &mut self.rules
+    }
+
+    /// Seed for the AI's random choices; `AiGameStateContext::rng` mixes in the position
+    pub fn ai_seed(&self) -> u64 {
+        self.game_seed ^ AI_STREAM
+    }
+
+    /// Stream for choices outside the game rules
+    pub fn session(&mut self) -> &mut StdRng {
+        &mut self.session
+    }
+}
+
+/// System to restart the game streams whenever a new game starts
+pub fn restart_game_rng(mut rng: ResMut<GameRng>) {
+    rng.start_game();
+}
@@ -0,0 +1,89 @@
+use std::error::Error;
+use std::path::Path;
+use bevy::prelude::*;
+use shakmaty::{Chess, Color as ChessColor};
+use crate::config::{DrawbackSetting, GameConfig};
+use crate::drawbacks::{DrawbackParams, DrawbackRegistry};
+use super::events::{LoadGameEvent, NewGameEvent};
+use super::pgn::{read_pgn, ImportedGame};
+use super::state::GameState;
+
+/// `--fen <fen>` starts the game from a position
+pub const FEN_FLAG: &str = "--fen";
+/// `--pgn <file>` goes on with the game of a PGN file from its last move
+pub const PGN_FLAG: &str = "--pgn";
+
+/// The game to start with instead of the standard position
+#[derive(Debug, Clone)]
+pub enum StartupGame {
+    Position(Chess),
+    Game(ImportedGame),
+}
+
+impl StartupGame {
+    /// Reads a FEN, or the PGN file at `source` if there is one
+    pub fn parse(source: &str) -> Result<Self, Box<dyn Error>> {
+        let source = source.trim();
+        if source.ends_with(".pgn") || Path::new(source).is_file() {
+            let text = std::fs::read_to_string(source)?;
+            return Ok(Self::Game(read_pgn(&text)?));
+        }
+        Ok(Self::Position(GameState::from_fen(source)?.board))
+    }
+}
+
+/// Startup system: starts the game given with `--fen` / `--pgn` or in the
+/// `start_from` setting. A PGN game brings its drawbacks along.
+pub fn load_startup_game(
+    mut config: ResMut<GameConfig>,
+    registry: Res<DrawbackRegistry>,
+    mut ev_new_game: EventWriter<NewGameEvent>,
+    mut ev_load: EventWriter<LoadGameEvent>,
+) {
+    let Some(source) = source_from_args().or_else(|| config.start_from.clone()) else {
+        return;
+    };
+    let startup_game = match StartupGame::parse(&source) {
+        Ok(startup_game) => startup_game,
+        Err(e) => {
+            eprintln!("Can't start from {}: {}", source, e);
+            return;
+        }
+    };
+
+    match startup_game {
+        StartupGame::Position(position) => {
+            println!("Starting from {}", source);
+            ev_new_game.send(NewGameEvent { start_position: Some(position) });
+        }
+        StartupGame::Game(imported) => {
+            println!("Going on with the game in {} ({} moves)", source, imported.history.len());
+            for color in [ChessColor::White, ChessColor::Black] {
+                let Some(id) = imported.drawback(color, &registry) else {
+                    continue;
+                };
+                let params = imported.drawback_params(color).unwrap_or_else(DrawbackParams::default);
+                let player = match color {
+                    ChessColor::White => &mut config.white_player,
+                    ChessColor::Black => &mut config.black_player,
+                };
+                player.drawback = DrawbackSetting { name: None, index: Some(id.to_key_index()), params };
+            }
+            ev_new_game.send(NewGameEvent { start_position: Some(imported.history.start_position.clone()) });
+            ev_load.send(LoadGameEvent(imported.history));
+        }
+    }
+}
+
+// Reads `--fen <fen>` or `--pgn <file>` from the command line
+fn source_from_args() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    [FEN_FLAG, PGN_FLAG].iter().find_map(|flag| {
+        let i = args.iter().position(|arg| arg == flag)?;
+        let value = args.get(i + 1).cloned();
+        if value.is_none() {
+            eprintln!("Usage: {} <{}>", flag, if *flag == FEN_FLAG { "fen" } else { "file" });
+        }
+        value
+    })
+}
@@ -1,12 +1,36 @@
 use bevy::prelude::*;
-use shakmaty::{Chess, Color as ChessColor, Position, CastlingMode};
-use crate::drawbacks::registry::DrawbackId; // Use the ID enum
+use shakmaty::{Chess, Color as ChessColor, Position, CastlingMode, Move};
+use crate::drawbacks::registry::{DrawbackId, DrawbackRegistry}; // Use the ID enum
+use crate::drawbacks::definition::{DrawbackRule, TurnContext};
+use crate::drawbacks::params::DrawbackParams;
 use crate::constants::DEFAULT_BOARD_FLIPPED;
+use super::events::GameResult;
+use super::drops::{Reserves, drop_moves};
+use crate::ai::zobrist::{ZobristKeys, calculate_zobrist_hash};
+use rand::Rng;
 use std::error::Error;
+use std::sync::Arc;
 
 // Represents the overall status of the game
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum GameStatus { Ongoing, GameOver }
+pub enum GameStatus {
+    Ongoing,
+    Finished(GameResult),
+}
+
+impl GameStatus {
+    pub fn is_over(&self) -> bool {
+        matches!(self, Self::Finished(_))
+    }
+
+    /// How the game ended, None while it is still being played
+    pub fn result(&self) -> Option<GameResult> {
+        match *self {
+            Self::Ongoing => None,
+            Self::Finished(result) => Some(result),
+        }
+    }
+}
 
 // Bevy State to manage whose turn it is / what phase we are in
 #[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
@@ -18,8 +42,94 @@ pub enum TurnState {
     GameOver,
 }
 
-/// Resource holding the primary chess game state.
-#[derive(Resource)]
+// Bevy State for pausing the game. Kept separate from TurnState so the
+// current turn survives a pause/resume cycle untouched.
+#[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+// Bevy State for stepping through the recorded moves (replay/analysis).
+// While replaying, the live game is frozen just like when paused.
+#[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ReplayState {
+    #[default]
+    Live,
+    Replay,
+}
+
+// Bevy State for which screen the app is showing. Gameplay only runs
+// InGame; other screens (like the ladder) are shown over a frozen board.
+#[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum AppState {
+    #[default]
+    InGame,
+    Setup, // Picking the players and drawbacks of the next game
+    Ladder,
+    Trophies,
+    Review,
+}
+
+// Bevy State for the interactive tutorial. The tutorial plays scripted
+// lessons on the normal game board, so it runs alongside AppState::InGame.
+#[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum TutorialState {
+    #[default]
+    Off,
+    Active,
+}
+
+/// Resource limiting which moves may be played. Normal games are `Free`;
+/// the tutorial forces scripted moves and freezes the board while explaining.
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Default)]
+pub enum MoveRestriction {
+    #[default]
+    Free,
+    Only(Move),
+    Frozen,
+}
+
+impl MoveRestriction {
+    pub fn allows(&self, chess_move: &Move) -> bool {
+        match self {
+            Self::Free => true,
+            Self::Only(forced) => forced == chess_move,
+            Self::Frozen => false,
+        }
+    }
+}
+
+/// Run condition: true while the live game is actually being played
+/// (in game, not paused and not replaying old moves)
+pub fn gameplay_active(
+    app_state: Res<State<AppState>>,
+    pause_state: Res<State<PauseState>>,
+    replay_state: Res<State<ReplayState>>,
+) -> bool {
+    *app_state.get() == AppState::InGame
+        && *pause_state.get() == PauseState::Running
+        && *replay_state.get() == ReplayState::Live
+}
+
+/// Marker for entities holding a chess game in their `GameState` component
+/// (the live game, and later analysis, puzzle or simul boards)
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameBoard;
+
+/// Marker for the one board the input, UI and AI work on
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActiveBoard;
+
+/// Run condition: true once the active board has been spawned
+pub fn active_board_exists(boards: Query<(), (With<GameBoard>, With<ActiveBoard>)>) -> bool {
+    !boards.is_empty()
+}
+
+/// Component holding the chess game state of a `GameBoard` entity.
+/// `Clone` copies every field, so a snapshot can't miss one added later.
+#[derive(Component, Clone)]
 pub struct GameState {
     pub board: Chess, // Current board position
     pub current_player_turn: ChessColor,
@@ -27,10 +137,15 @@ pub struct GameState {
     // --- Drawback State ---
     pub white_drawback: DrawbackId, // White's active drawback (None if no drawback)
     pub black_drawback: DrawbackId, // Black's active drawback (None if no drawback)
+    pub white_drawback_params: DrawbackParams, // Parameters of White's drawback (empty = its defaults)
+    pub black_drawback_params: DrawbackParams, // Parameters of Black's drawback (empty = its defaults)
     // --- Per-Turn Randomness State ---
     // Stores the outcome of RNG generated *for the current player* at the start of their turn,
     // if their active drawback requires it. Cleared after the turn.
     pub current_turn_rng_outcome: Option<u8>,
+    // The move that led to this position (the opponent's last move), None at the
+    // start of a game. Drawbacks that react to it get it in `filter_moves`.
+    pub last_move: Option<Move>,
      // --- Zobrist Hash ---
      // Placeholder: A proper Zobrist hash implementation is complex.
      // Add a field to store the hash, calculated elsewhere.
@@ -41,6 +156,15 @@ pub struct GameState {
      // If false, board is in standard orientation (white pieces at bottom)
      pub board_flipped: bool,
 
+     // --- Drops ---
+     // Pieces each player holds in hand (only filled by rulesets and drawbacks with drops)
+     pub reserves: Reserves,
+
+     // --- Captures ---
+     // Moves each player has made in a row without capturing (for `Vampire`)
+     pub white_moves_since_capture: u32,
+     pub black_moves_since_capture: u32,
+
      // Add history Vec<MoveInfo> etc. later if needed
 }
 
@@ -54,9 +178,15 @@ impl Default for GameState {
             status: GameStatus::Ongoing,
             white_drawback: DrawbackId::None, // Start with no drawback
             black_drawback: DrawbackId::None, // Start with no drawback
+            white_drawback_params: DrawbackParams::default(),
+            black_drawback_params: DrawbackParams::default(),
             current_turn_rng_outcome: None,
+            last_move: None,
             zobrist_hash: 0, // Initialize hash (will be calculated properly)
             board_flipped: DEFAULT_BOARD_FLIPPED,
+            reserves: Reserves::default(),
+            white_moves_since_capture: 0,
+            black_moves_since_capture: 0,
         }
     }
 }
@@ -69,6 +199,100 @@ impl GameState {
              ChessColor::Black => self.black_drawback,
          }
     }
+
+    /// The turn state this position calls for, once no move is being processed
+    pub fn expected_turn_state(&self) -> TurnState {
+        match (self.status, self.current_player_turn) {
+            (GameStatus::Finished(_), _) => TurnState::GameOver,
+            (GameStatus::Ongoing, ChessColor::White) => TurnState::PlayerTurn,
+            (GameStatus::Ongoing, ChessColor::Black) => TurnState::AiTurn,
+        }
+    }
+
+    /// The drawback of `color` and its parameters
+    pub fn drawback_of(&self, color: ChessColor) -> (DrawbackId, &DrawbackParams) {
+        match color {
+            ChessColor::White => (self.white_drawback, &self.white_drawback_params),
+            ChessColor::Black => (self.black_drawback, &self.black_drawback_params),
+        }
+    }
+
+    /// The rule of `color`'s drawback with its parameters applied, None without a drawback
+    pub fn drawback_rule(&self, color: ChessColor, registry: &DrawbackRegistry) -> Option<Arc<dyn DrawbackRule + Send + Sync>> {
+        let (id, params) = self.drawback_of(color);
+        registry.rule(id, params)
+    }
+
+    /// The rule of the side to move's drawback with its parameters applied
+    pub fn current_drawback_rule(&self, registry: &DrawbackRegistry) -> Option<Arc<dyn DrawbackRule + Send + Sync>> {
+        self.drawback_rule(self.current_player_turn, registry)
+    }
+    
+    /// Legal moves of the side to move under normal chess rules, plus drops from its hand
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut moves: Vec<Move> = self.board.legal_moves().into_iter().collect();
+        if !self.reserves.is_empty() {
+            moves.extend(drop_moves(&self.board, &self.reserves));
+        }
+        moves
+    }
+
+    /// Legal moves of the side to move once its drawback is applied
+    pub fn allowed_moves(&self, registry: &DrawbackRegistry) -> Vec<Move> {
+        let moves = self.legal_moves();
+        match self.current_drawback_rule(registry) {
+            Some(rule) => rule.filter_moves(&self.board, moves, &self.turn_context()),
+            None => moves,
+        }
+    }
+
+    /// Rolls the side to move's per-turn outcome, for drawbacks with
+    /// `needs_turn_rng` (None for any other); called as each turn starts
+    pub fn roll_turn(&mut self, registry: &DrawbackRegistry, rng: &mut impl Rng) {
+        self.current_turn_rng_outcome = self
+            .current_drawback_rule(registry)
+            .filter(|rule| rule.needs_turn_rng())
+            .map(|rule| rng.gen_range(0..rule.get_rng_outcomes().max(1)));
+    }
+
+    /// What the side to move's drawback may know about this turn besides the board
+    pub fn turn_context(&self) -> TurnContext<'_> {
+        TurnContext {
+            rng_outcome: self.current_turn_rng_outcome,
+            last_move: self.last_move.as_ref(),
+            moves_since_capture: self.moves_since_capture(self.current_player_turn),
+        }
+    }
+
+    /// Whether the side to move loses to its drawback's loss condition, with
+    /// `allowed_moves` left once the drawback is applied
+    pub fn loses_by_drawback(&self, registry: &DrawbackRegistry, allowed_moves: &[Move]) -> bool {
+        self.current_drawback_rule(registry).is_some_and(|rule| rule.check_loss_condition(&self.board, allowed_moves, &self.turn_context()))
+    }
+
+    /// Moves `color` has made in a row without capturing
+    pub fn moves_since_capture(&self, color: ChessColor) -> u32 {
+        match color {
+            ChessColor::White => self.white_moves_since_capture,
+            ChessColor::Black => self.black_moves_since_capture,
+        }
+    }
+
+    /// Counts the side to move's move towards (or resets) its moves without a
+    /// capture; called before the move is made
+    pub fn count_capture(&mut self, chess_move: &Move) {
+        let count = match self.current_player_turn {
+            ChessColor::White => &mut self.white_moves_since_capture,
+            ChessColor::Black => &mut self.black_moves_since_capture,
+        };
+        *count = TurnContext::count_capture(*count, chess_move);
+    }
+
+    /// Key identifying this position for repetition detection: the board, the
+    /// drawbacks and any pending RNG outcome of the side to move
+    pub fn position_key(&self, keys: &ZobristKeys) -> u64 {
+        calculate_zobrist_hash(self, keys)
+    }
     
     // Utility function to create a GameState from a FEN string
     pub fn from_fen(fen: &str) -> Result<Self, Box<dyn Error>> {
@@ -85,9 +309,15 @@ impl GameState {
             status: GameStatus::Ongoing,
             white_drawback: DrawbackId::None,
             black_drawback: DrawbackId::None,
+            white_drawback_params: DrawbackParams::default(),
+            black_drawback_params: DrawbackParams::default(),
             current_turn_rng_outcome: None,
+            last_move: None,
             zobrist_hash: 0,
             board_flipped: DEFAULT_BOARD_FLIPPED,
+            reserves: Reserves::default(),
+            white_moves_since_capture: 0,
+            black_moves_since_capture: 0,
         })
     }
 } 
\ No newline at end of file
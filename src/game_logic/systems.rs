@@ -1,30 +1,56 @@
 use bevy::prelude::*;
-use shakmaty::{Color as ChessColor, Position, Role, Square, Move};
-use crate::game_logic::state::{GameState, TurnState, GameStatus};
-use crate::game_logic::events::{MakeMoveEvent, GameOverEvent};
-use crate::drawbacks::{DrawbackRegistry, DrawbackId};
-
-/// Check if a move captures the king (Drawback Chess win condition)
-fn is_king_capture(board: &shakmaty::Chess, m: &Move) -> bool {
-    if let Some(piece) = board.board().piece_at(m.to()) {
-        return piece.role == Role::King;
-    }
-    false
+use bevy::ecs::system::SystemParam;
+use shakmaty::{Color as ChessColor, Position, Role, Move};
+use crate::game_logic::state::{GameState, ActiveBoard, TurnState, GameStatus, MoveRestriction};
+use crate::config::GameConfig;
+use crate::game_logic::events::{MakeMoveEvent, MovePlayedEvent, MoveRejectedEvent, MoveRejection, GameOverEvent, GameOverReason, GameResult, SwapSidesEvent, FlipBoardEvent, UndoMoveEvent, RedoMoveEvent, LoadGameEvent};
+use crate::game_logic::history::MoveHistory;
+use crate::game_logic::clock::MoveTimer;
+use crate::game_logic::repetition::RepetitionTable;
+use crate::game_logic::rng::GameRng;
+use crate::game_logic::legal_moves::{LegalMovesCache, DrawbackTelemetry};
+use crate::game_logic::notation::{captures_king, format_san, format_uci};
+use crate::ai::zobrist::{ZobristKeys, board_move_hash, turn_state_hash};
+use crate::drawbacks::DrawbackRegistry;
+use crate::drawbacks::definition::DrawbackRule;
+use crate::game_logic::drops::{captured_piece_hand, Reserves};
+use crate::ai::components::AiThinking;
+use crate::config::Ruleset;
+
+/// Events telling what came of a requested move
+#[derive(SystemParam)]
+pub struct MoveOutcomeEvents<'w> {
+    played: EventWriter<'w, MovePlayedEvent>,
+    game_over: EventWriter<'w, GameOverEvent>,
+    rejected: EventWriter<'w, MoveRejectedEvent>,
 }
 
 /// System to apply a move to the game state
+#[allow(clippy::too_many_arguments)]
 pub fn apply_move(
-    _commands: Commands,
     mut ev_make_move: EventReader<MakeMoveEvent>,
-    mut ev_game_over: EventWriter<GameOverEvent>,
-    mut game_state: ResMut<GameState>,
+    mut outcomes: MoveOutcomeEvents,
+    mut boards: Query<&mut GameState, With<ActiveBoard>>,
     mut next_state: ResMut<NextState<TurnState>>,
     current_state: Res<State<TurnState>>,
     drawback_registry: Res<DrawbackRegistry>,
+    mut history: ResMut<MoveHistory>,
+    restriction: Res<MoveRestriction>,
+    mut repetitions: ResMut<RepetitionTable>,
+    zobrist_keys: Res<ZobristKeys>,
+    mut legal_moves: ResMut<LegalMovesCache>,
+    mut telemetry: ResMut<DrawbackTelemetry>,
+    mut move_timer: ResMut<MoveTimer>,
+    config: Res<GameConfig>,
+    mut rng: ResMut<GameRng>,
 ) {
+    let Ok(mut game_state) = boards.get_single_mut() else {
+        return;
+    };
     for ev in ev_make_move.read() {
         let move_to_make = ev.0.clone();
         println!(">>> RECEIVED MOVE EVENT: {:?}", move_to_make);
+        let mut reject = |reason| outcomes.rejected.send(MoveRejectedEvent { chess_move: move_to_make.clone(), reason });
         
         // Ensure we're only processing events in the correct turn state
         // This prevents the AI from making multiple moves
@@ -34,26 +60,20 @@ pub fn apply_move(
         if (is_ai_move && *current_state.get() != TurnState::AiTurn) || 
            (is_player_move && *current_state.get() != TurnState::PlayerTurn) {
             println!("!!! MOVE IGNORED: Wrong turn state for current player");
+            reject(MoveRejection::WrongTurn);
             continue;
         }
         
-        // Check if move is legal ignoring check constraints (for Drawback Chess)
-        let mut legal_moves = game_state.board.legal_moves();
-        
-        // Validate move against player's drawback constraints
-        let player_drawback_id = game_state.get_current_player_drawback_id();
-        if player_drawback_id != DrawbackId::None {
-            if let Some(drawback_rule) = drawback_registry.rules.get(&player_drawback_id) {
-                // Filter moves according to this player's drawback
-                let moves_vec: Vec<Move> = legal_moves.into_iter().collect();
-                legal_moves = drawback_rule.filter_pseudo_legal_moves(
-                    &game_state.board,
-                    moves_vec,
-                    game_state.current_turn_rng_outcome
-                ).into_iter().collect();
-                println!("After applying drawback: {} legal moves", legal_moves.len());
-            }
+        // The tutorial only lets its scripted moves through
+        if !restriction.allows(&move_to_make) {
+            println!("!!! MOVE IGNORED: Not the move the tutorial asks for");
+            reject(MoveRejection::Restricted);
+            continue;
         }
+
+        // Check the move against the drawback-filtered moves of this position
+        // (usually cached already at the start of the turn)
+        legal_moves.refresh(&game_state, &drawback_registry, &zobrist_keys);
         
         // In Drawback Chess, you're allowed to move into check if opponent's drawback prevents capture
         // For simplicity, we'll check if the move is in our legal moves list
@@ -62,10 +82,11 @@ pub fn apply_move(
         
         if !move_is_allowed {
             println!("!!! ILLEGAL MOVE ATTEMPTED: {:?}", move_to_make);
+            reject(MoveRejection::NotAllowed);
             continue;
         }
         
-        println!("*** LEGAL MOVE CONFIRMED: {:?}", move_to_make);
+        println!("*** LEGAL MOVE CONFIRMED: {} ({})", format_san(&game_state.board, &move_to_make), format_uci(&move_to_make));
         
         // Log the move
         if let Some(from_square) = move_to_make.from() {
@@ -75,33 +96,42 @@ pub fn apply_move(
             if let Some(piece) = game_state.board.board().piece_at(from_square) {
                 if piece.color != game_state.current_player_turn {
                     println!("!!! WRONG COLOR PIECE MOVE ATTEMPTED: {:?}", move_to_make);
+                    reject(MoveRejection::WrongPiece);
                     continue;
                 }
             } else {
                 println!("Warning: Couldn't find piece at source square {:?}", from_square);
+                reject(MoveRejection::WrongPiece);
                 continue;
             }
         }
         
+        // Some drawbacks play another move than the one picked
+        let picked = move_to_make.clone();
+        let move_to_make = game_state
+            .current_drawback_rule(&drawback_registry)
+            .and_then(|rule| rule.substitute_move(&game_state.board, &picked, game_state.current_turn_rng_outcome))
+            .unwrap_or_else(|| picked.clone());
+        let picked = (picked != move_to_make).then_some(picked);
+        if let Some(picked) = &picked {
+            println!("*** DRAWBACK PLAYS {} INSTEAD OF {}", format_uci(&move_to_make), format_uci(picked));
+        }
+
         let to_square = move_to_make.to();
         
         // Check for capture - flag captures for future AI evaluation
         let is_capture = match game_state.board.board().piece_at(to_square) {
             Some(_) => true, // Destination square has a piece (standard capture)
             None => {
-                // Check for en passant capture
-                if let Some(from_square) = move_to_make.from() {
-                    if let Some(piece) = game_state.board.board().piece_at(from_square) {
-                        if piece.role == Role::Pawn && from_square.file() != to_square.file() {
-                            true // Pawn moving diagonally without a piece at destination is en passant
-                        } else {
-                            false
-                        }
-                    } else {
-                        false
+                // Check for en passant capture:
+                // a pawn moving diagonally without a piece at destination is en passant
+                match move_to_make.from().and_then(|from_square| {
+                    game_state.board.board().piece_at(from_square).map(|piece| (from_square, piece))
+                }) {
+                    Some((from_square, piece)) => {
+                        piece.role == Role::Pawn && from_square.file() != to_square.file()
                     }
-                } else {
-                    false
+                    None => false,
                 }
             }
         };
@@ -111,11 +141,38 @@ pub fn apply_move(
         }
         
         // Check if this move captures the king (Drawback Chess win condition)
-        let king_captured = is_king_capture(&game_state.board, &move_to_make);
+        let king_captured = captures_king(&game_state.board, &move_to_make);
         
+        // Record the move (with its SAN) before the board changes
+        history.push(&game_state.board, move_to_make.clone());
+        if let Some(record) = history.moves.last_mut() {
+            record.time_ms = Some(move_timer.take_ms());
+        }
+        telemetry.record(&legal_moves);
+
+        // The hash is updated from what the move changes instead of being
+        // computed over again: the turn state part is taken out here and put
+        // back once the move is made
+        let hash_without_turn_state = game_state.zobrist_hash ^ turn_state_hash(&game_state, &zobrist_keys);
+        
+        // Drops leave the mover's hand, and captures may fill one
+        let white_rule = game_state.drawback_rule(ChessColor::White, &drawback_registry);
+        let black_rule = game_state.drawback_rule(ChessColor::Black, &drawback_registry);
+        let state = &mut *game_state;
+        let rule_of = |color| {
+            let rule = if color == ChessColor::White { &white_rule } else { &black_rule };
+            rule.as_deref().map(|rule| rule as &dyn DrawbackRule)
+        };
+        state.reserves.record_move(&state.board, &move_to_make, |captured| {
+            captured_piece_hand(captured, config.ruleset, rule_of(captured.color), rule_of(!captured.color))
+        });
+
+        game_state.count_capture(&move_to_make);
+
         // Clone the current board state and apply the move
         let mut new_board = game_state.board.clone();
         new_board.play_unchecked(&move_to_make);
+        let board_changes = board_move_hash(&game_state.board, &move_to_make, &new_board, &zobrist_keys);
         
         // Check if the move results in check (just for convenience and AI logic)
         let is_check = new_board.is_check();
@@ -128,36 +185,274 @@ pub fn apply_move(
         
         // Update the game state with the new board
         game_state.board = new_board;
+        game_state.last_move = Some(move_to_make.clone());
         
         // Update turn state
-        game_state.current_player_turn = !game_state.current_player_turn;
+        let mover = game_state.current_player_turn;
+        game_state.current_player_turn = !mover;
+        game_state.roll_turn(&drawback_registry, rng.rules());
+
+        // Remember the new position for repetition detection
+        let position_key = hash_without_turn_state ^ board_changes ^ turn_state_hash(&game_state, &zobrist_keys);
+        debug_assert_eq!(position_key, game_state.position_key(&zobrist_keys), "Hash out of step after {:?}", move_to_make);
+        game_state.zobrist_hash = position_key;
+        repetitions.push(position_key);
+        outcomes.played.send(MovePlayedEvent { chess_move: move_to_make.clone(), is_check, picked });
         
-        // First, set to processing state to prevent double moves
+        // Processing until the moves of the new position are filtered (see `start_next_turn`)
         next_state.set(TurnState::ProcessingMove);
         
-        // Handle game over conditions
+        // Capturing the king ends the game right away
         if is_game_over {
-            game_state.status = GameStatus::GameOver;
+            let reason = GameOverReason::KingCaptured { winner: mover, square: move_to_make.to() };
+            let result = GameResult::new(reason, config.time_control.draw_result);
+            game_state.status = GameStatus::Finished(result);
             next_state.set(TurnState::GameOver);
-            
-            // Send game over event
-            ev_game_over.send(GameOverEvent("King Captured".to_string()));
-            
-            println!("Game over: King Captured");
+            outcomes.game_over.send(GameOverEvent(result));
+            println!("Game over: {}", reason);
+        }
+    }
+}
+
+/// System to take back moves (`UndoMoveEvent`) and play them again
+/// (`RedoMoveEvent`). Against the AI both go on until a human is to move, so
+/// the AI's reply is taken back along with the move it answered.
+#[allow(clippy::too_many_arguments)]
+pub fn take_back_moves(
+    mut commands: Commands,
+    mut ev_undo: EventReader<UndoMoveEvent>,
+    mut ev_redo: EventReader<RedoMoveEvent>,
+    mut boards: Query<&mut GameState, With<ActiveBoard>>,
+    mut history: ResMut<MoveHistory>,
+    mut repetitions: ResMut<RepetitionTable>,
+    mut next_state: ResMut<NextState<TurnState>>,
+    mut move_timer: ResMut<MoveTimer>,
+    ai_tasks: Query<Entity, With<AiThinking>>,
+    drawback_registry: Res<DrawbackRegistry>,
+    zobrist_keys: Res<ZobristKeys>,
+    config: Res<GameConfig>,
+    mut rng: ResMut<GameRng>,
+) {
+    let undo = ev_undo.read().count() > 0;
+    let redo = ev_redo.read().count() > 0;
+    let Ok(mut game_state) = boards.get_single_mut() else {
+        return;
+    };
+    if !undo && !redo {
+        return;
+    }
+    let step = |history: &mut MoveHistory| if undo { history.undo() } else { redo && history.redo() };
+    if !step(&mut history) {
+        println!("No move to {}", if undo { "take back" } else { "play again" });
+        return;
+    }
+
+    let is_ai = |color| match color {
+        ChessColor::White => config.white_player.is_ai,
+        ChessColor::Black => config.black_player.is_ai,
+    };
+    if !(is_ai(ChessColor::White) && is_ai(ChessColor::Black)) {
+        let start_turn = history.start_position.turn();
+        let to_move = |plies: usize| if plies.is_multiple_of(2) { start_turn } else { !start_turn };
+        while is_ai(to_move(history.len())) && step(&mut history) {}
+    }
+
+    *repetitions = replay_history(&mut game_state, &history, &drawback_registry, &zobrist_keys, &mut rng, config.ruleset);
+    println!("Back at ply {} ({} move(s) to play again)", history.len(), history.undone.len());
+
+    // A search of the position we left would come back with a move for the wrong one
+    for entity in ai_tasks.iter() {
+        commands.entity(entity).despawn();
+    }
+    move_timer.take_ms();
+
+    continue_after_replay(&mut game_state, &history, &config, &mut next_state);
+}
+
+/// System playing the moves of a loaded game on the board of the game that
+/// was started from its start position in the same frame
+#[allow(clippy::too_many_arguments)]
+pub fn resume_loaded_game(
+    mut ev_load: EventReader<LoadGameEvent>,
+    mut boards: Query<&mut GameState, With<ActiveBoard>>,
+    mut history: ResMut<MoveHistory>,
+    mut repetitions: ResMut<RepetitionTable>,
+    mut next_state: ResMut<NextState<TurnState>>,
+    drawback_registry: Res<DrawbackRegistry>,
+    zobrist_keys: Res<ZobristKeys>,
+    config: Res<GameConfig>,
+    mut rng: ResMut<GameRng>,
+) {
+    let Some(LoadGameEvent(loaded)) = ev_load.read().last() else {
+        return;
+    };
+    let Ok(mut game_state) = boards.get_single_mut() else {
+        return;
+    };
+    if loaded.start_position != history.start_position {
+        eprintln!("Not loading the game: it doesn't start from the position on the board");
+        return;
+    }
+
+    *history = loaded.clone();
+    *repetitions = replay_history(&mut game_state, &history, &drawback_registry, &zobrist_keys, &mut rng, config.ruleset);
+    println!("Loaded a game of {} moves", history.len());
+    continue_after_replay(&mut game_state, &history, &config, &mut next_state);
+}
+
+// Helper function to pick up the game after its moves were replayed
+fn continue_after_replay(game_state: &mut GameState, history: &MoveHistory, config: &GameConfig, next_state: &mut NextState<TurnState>) {
+    // The game ends on a king capture before the next turn could start
+    match history.moves.last().filter(|record| record.captured == Some(Role::King)) {
+        Some(record) => {
+            let reason = GameOverReason::KingCaptured { winner: !game_state.current_player_turn, square: record.chess_move.to() };
+            game_state.status = GameStatus::Finished(GameResult::new(reason, config.time_control.draw_result));
+            next_state.set(TurnState::GameOver);
+        }
+        // Loss conditions and repetitions are checked again as after any move
+        None => next_state.set(TurnState::ProcessingMove),
+    }
+}
+
+// Helper function to put the game back to the position after the recorded
+// moves, with everything apply_move keeps along: drawback hands, the last
+// move, the turn rolls (the rules stream starts over, so they come out the
+// same), the Zobrist hash and the positions seen for repetitions
+fn replay_history(
+    game_state: &mut GameState,
+    history: &MoveHistory,
+    drawback_registry: &DrawbackRegistry,
+    zobrist_keys: &ZobristKeys,
+    rng: &mut GameRng,
+    ruleset: Ruleset,
+) -> RepetitionTable {
+    let white_rule = game_state.drawback_rule(ChessColor::White, drawback_registry);
+    let black_rule = game_state.drawback_rule(ChessColor::Black, drawback_registry);
+    let rule_of = |color| {
+        let rule = if color == ChessColor::White { &white_rule } else { &black_rule };
+        rule.as_deref().map(|rule| rule as &dyn DrawbackRule)
+    };
+
+    game_state.board = history.start_position.clone();
+    game_state.current_player_turn = game_state.board.turn();
+    game_state.reserves = Reserves::default();
+    game_state.last_move = None;
+    game_state.white_moves_since_capture = 0;
+    game_state.black_moves_since_capture = 0;
+    game_state.status = GameStatus::Ongoing;
+    rng.restart_rules();
+    game_state.roll_turn(drawback_registry, rng.rules());
+    let mut repetitions = RepetitionTable::new(game_state.position_key(zobrist_keys));
+
+    for record in &history.moves {
+        let state = &mut *game_state;
+        state.reserves.record_move(&state.board, &record.chess_move, |captured| {
+            captured_piece_hand(captured, ruleset, rule_of(captured.color), rule_of(!captured.color))
+        });
+        state.count_capture(&record.chess_move);
+        state.board.play_unchecked(&record.chess_move);
+        state.current_player_turn = state.board.turn();
+        state.last_move = Some(record.chess_move.clone());
+        state.roll_turn(drawback_registry, rng.rules());
+        repetitions.push(state.position_key(zobrist_keys));
+    }
+    game_state.zobrist_hash = game_state.position_key(zobrist_keys);
+    repetitions
+}
+
+/// System to hand the turn over once the moves of the position after a move are
+/// filtered: the side to move may have lost to its drawback (or have nothing left
+/// to play), or the position may be drawn by repetition
+#[allow(clippy::too_many_arguments)]
+pub fn start_next_turn(
+    mut ev_game_over: EventWriter<GameOverEvent>,
+    mut boards: Query<&mut GameState, With<ActiveBoard>>,
+    mut next_state: ResMut<NextState<TurnState>>,
+    drawback_registry: Res<DrawbackRegistry>,
+    repetitions: Res<RepetitionTable>,
+    zobrist_keys: Res<ZobristKeys>,
+    legal_moves: Res<LegalMovesCache>,
+    config: Res<GameConfig>,
+) {
+    let Ok(mut game_state) = boards.get_single_mut() else {
+        return;
+    };
+    if game_state.status != GameStatus::Ongoing || !legal_moves.is_ready_for(game_state.position_key(&zobrist_keys)) {
+        return;
+    }
+
+    let game_over_reason = if let Some(reason) = turn_start_loss(&game_state, &drawback_registry, &legal_moves) {
+        // The side to move lost to its drawback or has nothing left to play
+        Some(reason)
+    } else if repetitions.is_draw() {
+        Some(GameOverReason::DrawByRepetition)
+    } else {
+        None
+    };
+
+    if let Some(reason) = game_over_reason {
+        let result = GameResult::new(reason, config.time_control.draw_result);
+        game_state.status = GameStatus::Finished(result);
+        next_state.set(TurnState::GameOver);
+        ev_game_over.send(GameOverEvent(result));
+        println!("Game over: {}", reason);
+    } else {
+        // Set next state based on current player
+        if game_state.current_player_turn == ChessColor::Black {
+            next_state.set(TurnState::AiTurn);
         } else {
-            // Set next state based on current player
-            if game_state.current_player_turn == ChessColor::Black {
-                next_state.set(TurnState::AiTurn);
-            } else {
-                next_state.set(TurnState::PlayerTurn);
-            }
+            next_state.set(TurnState::PlayerTurn);
+        }
+    }
+}
+
+/// Returns the game over reason if the side to move loses at the start of its turn:
+/// its drawback's loss condition is met, or it has no moves left once the drawback is applied
+fn turn_start_loss(
+    game_state: &GameState,
+    drawback_registry: &DrawbackRegistry,
+    legal_moves: &LegalMovesCache,
+) -> Option<GameOverReason> {
+    let to_move = game_state.current_player_turn;
+    let drawback = game_state.get_current_player_drawback_id();
+    if game_state.loses_by_drawback(drawback_registry, legal_moves.moves()) {
+        return Some(GameOverReason::DrawbackLoss { loser: to_move, drawback });
+    }
+    if !legal_moves.moves().is_empty() {
+        return None;
+    }
+
+    if game_state.board.is_check() {
+        Some(GameOverReason::Checkmate { winner: !to_move })
+    } else {
+        Some(GameOverReason::Stalemate)
+    }
+}
+
+/// System to swap the players of the two sides (who is human and who is the AI).
+/// The board is flipped along with them, so the human's pieces stay at the bottom.
+/// An AI search already running is cancelled by the AI plugin.
+pub fn swap_sides(
+    mut ev_swap: EventReader<SwapSidesEvent>,
+    mut config: ResMut<GameConfig>,
+    mut ev_flip: EventWriter<FlipBoardEvent>,
+) {
+    for _ in ev_swap.read() {
+        let (white_is_ai, black_is_ai) = (config.white_player.is_ai, config.black_player.is_ai);
+        if white_is_ai == black_is_ai {
+            println!("Both sides are played by the same kind of player, nothing to swap");
+            continue;
         }
+        config.white_player.is_ai = black_is_ai;
+        config.black_player.is_ai = white_is_ai;
+        ev_flip.send(FlipBoardEvent);
+        println!("Sides swapped: the human now plays {}", if white_is_ai { "White" } else { "Black" });
     }
 }
 
 /// Helper function to determine if a move into check might be allowable in Drawback Chess
 /// based on opponent's potential drawback that might prevent king capture
-fn is_allowable_check_move(game_state: &GameState, candidate_move: &Move) -> bool {
+fn is_allowable_check_move(_game_state: &GameState, _candidate_move: &Move) -> bool {
     // This is a simplified version - a full implementation would:
     // 1. Apply the move to a temporary board state
     // 2. Look ahead to see if the opponent would be able to capture the king
@@ -0,0 +1,199 @@
+use bevy::prelude::*;
+use serde_json::json;
+use shakmaty::{Chess, Color as ChessColor, EnPassantMode, Move, Position, Role, fen::Fen};
+use std::collections::VecDeque;
+use crate::ai::components::AiThinking;
+use crate::board::components::BoardSquare;
+use crate::drawbacks::{DrawbackId, DrawbackParams};
+use crate::pieces::components::{PieceId, PieceIdAllocator};
+use crate::pieces::plugin::{sync_pieces_to_board, PieceEntities};
+use super::drops::Reserves;
+use super::history::MoveHistory;
+use super::notation::format_uci;
+use super::state::{GameState, ActiveBoard, GameStatus, TurnState};
+
+// Snapshots kept; the oldest are dropped first
+const MAX_SNAPSHOTS: usize = 512;
+
+/// Copy of the engine state of the active board after one move
+#[derive(Debug, Clone)]
+pub struct GameStateSnapshot {
+    pub ply: usize, // Moves recorded in `MoveHistory` when the snapshot was taken
+    pub board: Chess,
+    pub status: GameStatus,
+    pub white_drawback: DrawbackId,
+    pub black_drawback: DrawbackId,
+    pub white_drawback_params: DrawbackParams,
+    pub black_drawback_params: DrawbackParams,
+    pub current_turn_rng_outcome: Option<u8>,
+    pub last_move: Option<Move>,
+    pub zobrist_hash: u64,
+    pub reserves: Reserves,
+    pub white_moves_since_capture: u32,
+    pub black_moves_since_capture: u32,
+}
+
+impl GameStateSnapshot {
+    fn capture(game_state: &GameState, ply: usize) -> Self {
+        Self {
+            ply,
+            board: game_state.board.clone(),
+            status: game_state.status,
+            white_drawback: game_state.white_drawback,
+            black_drawback: game_state.black_drawback,
+            white_drawback_params: game_state.white_drawback_params.clone(),
+            black_drawback_params: game_state.black_drawback_params.clone(),
+            current_turn_rng_outcome: game_state.current_turn_rng_outcome,
+            last_move: game_state.last_move.clone(),
+            zobrist_hash: game_state.zobrist_hash,
+            reserves: game_state.reserves.clone(),
+            white_moves_since_capture: game_state.white_moves_since_capture,
+            black_moves_since_capture: game_state.black_moves_since_capture,
+        }
+    }
+
+    fn restore(&self, game_state: &mut GameState) {
+        game_state.board = self.board.clone();
+        game_state.current_player_turn = self.board.turn();
+        game_state.status = self.status;
+        game_state.white_drawback = self.white_drawback;
+        game_state.black_drawback = self.black_drawback;
+        game_state.white_drawback_params = self.white_drawback_params.clone();
+        game_state.black_drawback_params = self.black_drawback_params.clone();
+        game_state.current_turn_rng_outcome = self.current_turn_rng_outcome;
+        game_state.last_move = self.last_move.clone();
+        game_state.zobrist_hash = self.zobrist_hash;
+        game_state.reserves = self.reserves.clone();
+        game_state.white_moves_since_capture = self.white_moves_since_capture;
+        game_state.black_moves_since_capture = self.black_moves_since_capture;
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let hand = |color: ChessColor| {
+            Role::ALL
+                .into_iter()
+                .filter(|&role| self.reserves.count(color, role) > 0)
+                .map(|role| (role.char().to_string(), json!(self.reserves.count(color, role))))
+                .collect::<serde_json::Map<_, _>>()
+        };
+        json!({
+            "ply": self.ply,
+            "fen": Fen::from_position(self.board.clone(), EnPassantMode::Legal).to_string(),
+            "status": match self.status {
+                GameStatus::Ongoing => "ongoing".to_string(),
+                GameStatus::Finished(result) => format!("{} ({})", result.pgn_result(), result.reason),
+            },
+            "white_drawback": format!("{:?}", self.white_drawback),
+            "black_drawback": format!("{:?}", self.black_drawback),
+            "white_drawback_params": self.white_drawback_params,
+            "black_drawback_params": self.black_drawback_params,
+            "rng_outcome": self.current_turn_rng_outcome,
+            "last_move": self.last_move.as_ref().map(format_uci),
+            "zobrist_hash": format!("{:016x}", self.zobrist_hash),
+            "reserves": { "white": hand(ChessColor::White), "black": hand(ChessColor::Black) },
+            "moves_since_capture": { "white": self.white_moves_since_capture, "black": self.black_moves_since_capture },
+        })
+    }
+}
+
+/// Resource (dev builds only) with the recent engine states of the game and
+/// which one is on the board. Stepping restores the real `GameState`, not just
+/// the pieces; `MoveHistory` is left as it is. Playing on from an older state
+/// drops the states after it.
+#[derive(Resource, Debug, Default)]
+pub struct TimeTravel {
+    snapshots: VecDeque<GameStateSnapshot>,
+    cursor: Option<usize>, // Snapshot on the board, None = the live state
+    recorded_plies: usize,
+}
+
+/// Debug system: snapshots the game state after every move (and at the start of a game)
+pub fn record_game_state_snapshots(
+    mut time_travel: ResMut<TimeTravel>,
+    history: Res<MoveHistory>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    if history.len() == time_travel.recorded_plies && !time_travel.snapshots.is_empty() {
+        return;
+    }
+
+    if history.len() < time_travel.recorded_plies {
+        // A new game (or a loaded one) replaced the history
+        time_travel.snapshots.clear();
+    } else if let Some(cursor) = time_travel.cursor {
+        // A move was played from an older state: the later states are another timeline now
+        time_travel.snapshots.truncate(cursor + 1);
+    }
+    time_travel.cursor = None;
+    time_travel.recorded_plies = history.len();
+    if time_travel.snapshots.len() == MAX_SNAPSHOTS {
+        time_travel.snapshots.pop_front();
+    }
+    time_travel.snapshots.push_back(GameStateSnapshot::capture(game_state, history.len()));
+}
+
+/// Developer keys (dev builds only): F5 steps the game state back one move,
+/// F6 forward again, F7 writes all snapshots to a JSON file for bug reports
+#[allow(clippy::too_many_arguments)]
+pub fn handle_time_travel_keys(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    mut time_travel: ResMut<TimeTravel>,
+    mut boards: Query<&mut GameState, With<ActiveBoard>>,
+    mut next_turn_state: ResMut<NextState<TurnState>>,
+    ai_tasks: Query<Entity, With<AiThinking>>,
+    asset_server: Res<AssetServer>,
+    mut pieces: PieceEntities,
+    board_squares: Query<(&Transform, &BoardSquare), Without<PieceId>>,
+    mut piece_ids: ResMut<PieceIdAllocator>,
+) {
+    if keys.just_pressed(KeyCode::F7) {
+        dump_snapshots(&time_travel);
+    }
+
+    let newest = time_travel.snapshots.len().saturating_sub(1);
+    let current = time_travel.cursor.unwrap_or(newest);
+    let target = if keys.just_pressed(KeyCode::F5) {
+        current.saturating_sub(1)
+    } else if keys.just_pressed(KeyCode::F6) {
+        (current + 1).min(newest)
+    } else {
+        return;
+    };
+    if target == current {
+        return;
+    }
+    let (Some(snapshot), Ok(mut game_state)) = (time_travel.snapshots.get(target), boards.get_single_mut()) else {
+        return;
+    };
+
+    snapshot.restore(&mut game_state);
+    // A search of the state we left would come back with a move for the wrong position
+    for entity in ai_tasks.iter() {
+        commands.entity(entity).despawn();
+    }
+    next_turn_state.set(game_state.expected_turn_state());
+    sync_pieces_to_board(&mut commands, &asset_server, game_state.board.board(), snapshot.ply, &mut pieces, &board_squares, &mut piece_ids);
+    println!("Time travel: game state after ply {} ({}/{})", snapshot.ply, target + 1, newest + 1);
+    time_travel.cursor = (target != newest).then_some(target);
+}
+
+// Writes every snapshot (and which one is on the board) to a file in the working directory
+fn dump_snapshots(time_travel: &TimeTravel) {
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let path = format!("gamestate_dump_{}.json", seconds);
+    let dump = json!({
+        "cursor": time_travel.cursor,
+        "snapshots": time_travel.snapshots.iter().map(GameStateSnapshot::to_json).collect::<Vec<_>>(),
+    });
+    match serde_json::to_string_pretty(&dump).map_err(|e| e.to_string()).and_then(|text| std::fs::write(&path, text).map_err(|e| e.to_string())) {
+        Ok(()) => println!("Time travel: {} game state snapshot(s) written to {}", time_travel.snapshots.len(), path),
+        Err(e) => eprintln!("Failed to write {}: {}", path, e),
+    }
+}
@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use shakmaty::{fen::Fen, EnPassantMode};
+use std::time::Duration;
+use crate::ai::zobrist::ZobristKeys;
+use crate::drawbacks::DrawbackRegistry;
+use super::legal_moves::LegalMovesCache;
+use super::state::{GameState, ActiveBoard, TurnState};
+
+// Seconds a move may stay in processing before the game counts as stuck
+const STUCK_TURN_SECS: f32 = 5.0;
+
+// Longest the moves of a position may take to filter before that counts as stuck too
+const MAX_FILTER_TIME: Duration = Duration::from_secs(30);
+
+/// Resource timing how long the game has been processing the last move
+#[derive(Resource, Debug, Default)]
+pub struct TurnWatchdog {
+    processing_for: f32, // Seconds of play spent in `ProcessingMove` so far
+    recoveries: u32,     // How often the game had to be unstuck
+}
+
+impl TurnWatchdog {
+    pub fn recoveries(&self) -> u32 {
+        self.recoveries
+    }
+}
+
+/// System to catch a game that never leaves `ProcessingMove` (e.g. because a
+/// system panicked or an event got lost). After `STUCK_TURN_SECS` of play it
+/// logs what it knows, fills the move cache right away and hands the turn to
+/// whoever the game state says is to move. Paused time doesn't count.
+#[allow(clippy::too_many_arguments)]
+pub fn watch_turn_state(
+    time: Res<Time>,
+    turn_state: Res<State<TurnState>>,
+    mut next_turn_state: ResMut<NextState<TurnState>>,
+    mut watchdog: ResMut<TurnWatchdog>,
+    mut legal_moves: ResMut<LegalMovesCache>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    registry: Res<DrawbackRegistry>,
+    zobrist_keys: Res<ZobristKeys>,
+) {
+    if *turn_state.get() != TurnState::ProcessingMove || turn_state.is_changed() || next_turn_state.0.is_some() {
+        watchdog.processing_for = 0.0;
+        return;
+    }
+    watchdog.processing_for += time.delta_seconds();
+    // Moves that are still being filtered are slow, not stuck (up to a point)
+    let filtering = legal_moves.computing_for().is_some_and(|elapsed| elapsed < MAX_FILTER_TIME);
+    if watchdog.processing_for < STUCK_TURN_SECS || filtering {
+        return;
+    }
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+
+    let key = game_state.position_key(&zobrist_keys);
+    eprintln!("Turn watchdog: stuck processing a move for {:.1}s", watchdog.processing_for);
+    eprintln!("- Position: {}", Fen::from_position(game_state.board.clone(), EnPassantMode::Legal));
+    eprintln!("- To move: {:?}, status: {:?}", game_state.current_player_turn, game_state.status);
+    eprintln!("- Position key: {:016x} (stored {:016x})", key, game_state.zobrist_hash);
+    eprintln!(
+        "- Move cache: {} ({} moves), filtering for {:?}",
+        if legal_moves.is_ready_for(key) { "ready" } else { "not ready" },
+        legal_moves.moves().len(),
+        legal_moves.computing_for(),
+    );
+
+    legal_moves.refresh(game_state, &registry, &zobrist_keys);
+    let recovered = game_state.expected_turn_state();
+    eprintln!("Turn watchdog: recovered to {:?}", recovered);
+    next_turn_state.set(recovered);
+    watchdog.processing_for = 0.0;
+    watchdog.recoveries += 1;
+}
@@ -0,0 +1,294 @@
+# Deutsche Texte für Drawback Chess.
+# Fehlende Schlüssel fallen auf die englischen Texte (en.ftl) zurück.
+
+language-name = Deutsch
+
+## Drawbacks
+drawback-none-name = Keiner
+drawback-no-castling-name = Keine Rochade
+drawback-no-castling-description = Rochieren (kurz oder lang) ist nicht erlaubt.
+drawback-pawn-push-one-name = Bauern nur ein Feld
+drawback-pawn-push-one-description = Bauern dürfen auch im ersten Zug nicht zwei Felder vorrücken.
+drawback-block-random-file-name = Gesperrte Linie
+drawback-block-random-file-description = Zu Beginn deines Zuges wird zufällig eine Linie (A-H) gewählt. In diesem Zug darfst du keine Figur AUF diese Linie ziehen.
+drawback-block-random-file-fixed-description = Du darfst nie eine Figur AUF die { $file }-Linie ziehen.
+drawback-recycling-name = Recycling
+drawback-recycling-description = Bauern, die du schlägst, kommen in deine Reserve. Statt zu ziehen, darfst du einen davon auf ein leeres Feld deiner eigenen Bretthälfte einsetzen.
+drawback-pacifist-opening-name = Friedliche Eröffnung
+drawback-pacifist-opening-description = Vor dem { $first_capture_move }. Zug darfst du nichts schlagen, auch nicht en passant.
+drawback-edge-phobic-name = Randscheu
+drawback-edge-phobic-description = Deine Figuren dürfen nicht auf die a-/h-Linie oder die 1./8. Reihe ziehen. Rochade und Umwandlung bleiben erlaubt.
+drawback-edge-phobic-castling-description = Deine Figuren dürfen nicht auf die a-/h-Linie oder die 1./8. Reihe ziehen. Die Rochade bleibt erlaubt, die Umwandlung nicht.
+drawback-edge-phobic-promotion-description = Deine Figuren dürfen nicht auf die a-/h-Linie oder die 1./8. Reihe ziehen. Die Umwandlung bleibt erlaubt, die Rochade nicht.
+drawback-edge-phobic-strict-description = Deine Figuren dürfen nicht auf die a-/h-Linie oder die 1./8. Reihe ziehen. Auch Rochade und Umwandlung sind verboten.
+drawback-follow-the-leader-name = Mir nach
+drawback-follow-the-leader-description = Du musst dieselbe Figurenart ziehen wie dein Gegner zuletzt, wenn das möglich ist.
+drawback-pawn-horde-name = Bauernhorde
+drawback-pawn-horde-description = Du verlierst, sobald du weniger als { $minimum } Bauern hast.
+drawback-mirror-name = Spiegel
+drawback-mirror-description = Deine Bauern dürfen auch ein Feld schräg nach vorn ziehen, ohne zu schlagen.
+drawback-claustrophobia-name = Platzangst
+drawback-claustrophobia-description = Deine Figuren dürfen nicht neben deinen eigenen König ziehen. Der König selbst darf weiter ziehen und rochieren.
+drawback-vampire-name = Vampir
+drawback-vampire-description = Du musst mindestens einmal in 5 deiner Züge schlagen, sonst verlierst du.
+drawback-glass-cannon-name = Glaskanone
+drawback-glass-cannon-description = Deine Dame darf nie gedeckt sein: Kein Zug darf sie decken, und sie darf nicht auf ein gedecktes Feld ziehen.
+drawback-slippery-fingers-name = Rutschige Finger
+drawback-slippery-fingers-description = In jedem Zug besteht eine Chance von 1 zu 3, dass deine Züge über 4 oder mehr Felder ein Feld zu weit rutschen.
+
+## Game over reasons and results
+reason-king-captured = König geschlagen
+reason-checkmate = Schachmatt
+reason-stalemate = Patt
+reason-repetition = Dreifache Stellungswiederholung
+reason-timeout = Zeit abgelaufen
+reason-resigned = { $color } hat aufgegeben
+reason-abandoned = { $color } hat die Partie verlassen
+reason-drawback-loss = { $color } verliert durch den eigenen Drawback ({ $drawback })
+color-white = Weiß
+color-black = Schwarz
+piece-pawn = Bauer
+piece-knight = Springer
+piece-bishop = Läufer
+piece-rook = Turm
+piece-queen = Dame
+piece-king = König
+result-win = Sieg
+result-loss = Niederlage
+result-draw = Remis
+
+## Game over banner
+game-over-king-captured = { $color } schlägt den König!
+game-over-king-captured-detail = Der König fiel auf { $square }
+game-over-wins = { $color } gewinnt
+game-over-draw = Remis
+game-over-timeout-detail = { $color } hat die Zeit überschritten
+game-over-save = Partie speichern (S)
+game-over-saved = Gespeichert als { $file }
+game-over-save-failed = Partie konnte nicht gespeichert werden: { $error }
+
+## Pause menu
+menu-paused = Pause
+menu-settings = Einstellungen
+menu-new-game = Neue Partie
+menu-resume = Weiter
+menu-ladder = Rangliste
+menu-trophies = Trophäen
+menu-review = Partieanalyse
+menu-tutorial = Tutorial
+menu-leave-tutorial = Tutorial beenden
+menu-force-ai-move = KI zieht jetzt
+menu-swap-sides = Seiten tauschen
+menu-share-position = Stellung teilen
+menu-offer-rematch = Revanche anbieten
+menu-accept-rematch = Revanche annehmen
+menu-new-drawbacks = Neue Nachteile
+menu-resign = Aufgeben
+menu-quit = Beenden
+menu-flip-board = Brett drehen
+menu-toggle-low-power = Stromsparmodus
+menu-teaching-mode = Lernmodus: { $state }
+menu-on = An
+menu-off = Aus
+menu-language = Sprache: { $language }
+menu-clock-delay = Verzögerung: { $mode }
+menu-clock-delay-none = Aus
+menu-clock-delay-simple = Einfach
+menu-clock-delay-bronstein = Bronstein
+menu-share-with-fen = Geteilte Bilder zeigen die FEN: { $state }
+menu-hash-size = Hashtabelle der KI: { $size } MB
+menu-sound = Ton: { $state }
+menu-rule-explanations = Regelerklärungen: { $state }
+menu-ai-strength = KI-Stärke: { $level }
+menu-ai-strength-easy = Leicht
+menu-ai-strength-medium = Mittel
+menu-ai-strength-hard = Schwer
+menu-ai-strength-custom = Eigene
+menu-back = Zurück
+
+## Setup screen
+setup-title = Neue Partie
+setup-human = Mensch
+setup-ai = KI
+setup-random-drawback = Zufall
+setup-random-description = Ein zufälliger Drawback, gezogen wenn die Partie beginnt
+setup-start = Starten
+setup-back = Zurück
+
+## Ladder
+ladder-title = Drawback-Rangliste
+ladder-status-beaten = Besiegt
+ladder-status-next = Als Nächstes
+ladder-status-locked = Gesperrt
+ladder-rung =
+    { $number }. { $name }  [{ $status }]
+    Dein Drawback: { $drawback }
+ladder-back = Zurück zum Spiel
+ladder-quit = Beenden
+ladder-rung-apprentice = Der Lehrling
+ladder-rung-squire = Der Knappe
+ladder-rung-knight = Der Ritter
+ladder-rung-warden = Der Wächter
+ladder-rung-grandmaster = Der Großmeister
+ladder-result-win = Du hast { $name } besiegt! ({ $reason })
+ladder-result-loss = { $name } hat dich besiegt ({ $reason })
+ladder-result-draw = Remis gegen { $name } ({ $reason })
+
+## Daily challenge banner
+daily-title = Tägliche Herausforderung { $date }
+daily-your-drawback = Dein Drawback: { $drawback }
+daily-ai-drawback-was = Drawback der KI war: { $drawback }
+daily-ai-drawback-hidden = Drawback der KI: ???
+daily-result = Ergebnis: { $result }
+daily-streak = Serie: { $streak } Tag(e) (beste { $best })
+
+## Zufälliger Drawback
+random-drawback = { $color } hat gezogen: { $drawback }
+random-drawback-hidden = { $color } hat einen geheimen Drawback gezogen
+
+## Von einem Drawback geänderter Zug
+move-substituted = { $color } wollte nach { $picked }, rutschte aber bis { $played }!
+
+## Regelerklärung
+rule-explanation-title = Drawback von { $color }: { $drawback }
+rule-explanation-filtered = Er hat dieser Figur gerade Züge weggenommen.
+rule-explanation-rolled = Er hat für diesen Zug gewürfelt.
+rule-explanation-close = Verstanden
+rule-explanation-dont-show = Nicht mehr anzeigen
+
+## Kiosk-Modus
+kiosk-title = Drawback Chess
+kiosk-drawbacks = Weiß: { $white }   gegen   Schwarz: { $black }
+
+## Netzwerkpartie
+net-waiting-for-player = Warte auf den anderen Spieler...
+net-connecting = Verbinde mit dem Host...
+net-waiting-for-setup = Verbunden, warte auf den Start der Partie
+net-playing = Netzwerkpartie: du spielst { $color }
+net-hidden-host = Dein Drawback bleibt bis zum Ende der Partie geheim; du siehst den des anderen Spielers
+net-hidden-client = Der Drawback des Hosts bleibt bis zum Ende der Partie geheim; der Host sieht deinen
+net-desynced = Nicht mehr synchron mit dem Host, lade seine Partie...
+net-resyncing = Spiele die Partie des Hosts nach...
+net-opponent-away = Verbindung verloren, warte { $seconds }s auf die Rückkehr des anderen Spielers
+net-reconnecting = Verbindung verloren, verbinde erneut mit dem Host (noch { $seconds }s)
+net-failed = Netzwerkpartie abgebrochen: { $reason }
+net-disconnected = Verbindung getrennt: { $reason }
+
+## Chat der Netzwerkpartie
+chat-hint = Enter: Chat
+chat-muted = Chat stummgeschaltet
+chat-mute = Stumm
+chat-unmute = Laut
+
+## Match-Anzeige
+arena-title = Match (Best of { $games }), Partie { $game }
+arena-score = { $first } { $first_score } - { $second_score } { $second }
+arena-player = Spieler { $number } ({ $kind }, { $color })
+arena-human = Mensch
+arena-ai = KI
+arena-won = Match vorbei: Spieler { $number } gewinnt
+arena-drawn = Match vorbei: unentschieden
+
+## Move tooltip
+tooltip-blocked-move = { $square }  { $san }: gesperrt durch { $drawback }
+tooltip-piece = { $piece } auf { $square }: { $allowed } von { $legal } Zügen erlaubt
+
+## Geteilte Stellungsbilder
+share-drawback = { $color }: { $drawback }
+share-fen = Zug { $move }: { $fen }
+
+## Promotion picker
+promotion-title = Umwandeln in (Q/R/B/N, Esc bricht ab)
+
+## Drawback meter
+drawback-meter = Dein Drawback hat diesen Zug { $removed } von { $total } Zügen gestrichen
+
+## Gegnermodell
+belief-likely = Die KI hält für { $color } am wahrscheinlichsten: { $candidates }
+belief-trend = { $drawback } im Spielverlauf (Halbzug: Chance): { $trend }
+belief-ruled-out = Halbzug { $ply }: ausgeschlossen { $drawbacks }
+
+## Schachuhr
+clock-draw-counts-as = Remis zählt als Sieg für { $color }
+move-reminder = Zeit für einen Zug!
+move-reminder-auto = Zeit für einen Zug! In { $seconds }s wird ein zufälliger Zug gespielt
+
+## AI thinking indicator
+ai-thinking = { $spinner } KI denkt nach... { $seconds }s  Tiefe { $depth }  Knoten { $nodes }
+computing-legal-moves = { $spinner } Berechne erlaubte Züge...
+
+## AI crash banner
+ai-crashed = Die KI ist abgestürzt und hat einen Ersatzzug gespielt ({ $move }). Details stehen in { $path }
+
+## Replay panel
+replay-start = WIEDERGABE  Startstellung (0/{ $total })
+replay-move = WIEDERGABE  { $number } { $san }{ $glyphs }  ({ $ply }/{ $total })
+replay-time = Bedenkzeit: { $spent }
+replay-time-clock = Bedenkzeit: { $spent }, Uhr nach dem Zug: { $clock }
+replay-eval = Bewertung: { $score }
+replay-comment-editor = Kommentar: { $text }_   [Enter] speichern  [Esc] abbrechen
+replay-help = [Links/Rechts/Pos1/Ende] blättern  [1-6] ! ? !! ?? !? ?!  [0] löschen  [C] Kommentar  [S] speichern  [L] laden  [Tab] live
+
+## Trophies and achievements
+trophies-title = Trophäen ({ $unlocked }/{ $total })
+trophies-back = Zurück zum Spiel
+achievement-unlocked = Erfolg freigeschaltet: { $name }
+achievement-first-win-name = Erster Sieg
+achievement-first-win-description = Gewinne eine Partie gegen die KI
+achievement-win-with-drawback-name = Gehandicapter Held: { $drawback }
+achievement-win-with-drawback-description = Gewinne eine Partie mit dem Drawback "{ $drawback }"
+achievement-win-with-every-drawback-name = Alleskönner
+achievement-win-with-every-drawback-description = Gewinne mindestens einmal mit jedem Drawback
+achievement-win-on-time-name = Zeitjäger
+achievement-win-on-time-description = Gewinne, weil der KI die Zeit ausgeht
+achievement-knight-promotion-mate-name = Ritterschlag
+achievement-knight-promotion-mate-description = Wandle einen Bauern in einen Springer um und gewinne durch Schachmatt
+achievement-survive-random-file-name = Linienläufer
+achievement-survive-random-file-description = Beende eine Partie mit "{ $drawback }", ohne zu verlieren
+achievement-quick-win-name = Blitzkrieg
+achievement-quick-win-description = Gewinne in höchstens { $moves } Zügen
+achievement-daily-win-name = Tägliche Pflicht
+achievement-daily-win-description = Gewinne eine tägliche Herausforderung
+achievement-daily-streak-name = Gewohnheitstier
+achievement-daily-streak-description = Spiele die tägliche Herausforderung { $days } Tage in Folge
+achievement-ladder-champion-name = Ganz oben
+achievement-ladder-champion-description = Besiege jeden Gegner der Rangliste
+
+## Game review
+review-title = Partieanalyse
+review-reason = Ergebnis: { $reason }
+review-analysing = Partie wird analysiert...
+review-none = Noch keine beendete Partie zum Analysieren.
+review-player = { $color }: Genauigkeit { $accuracy }%, Drawback strich { $removed } Züge pro Zug
+review-graph = Material (Balken) und Bewertung (Linie), Klick springt zum Zug
+review-time = Bedenkzeit pro Zug
+review-mistakes = Größte Fehler
+review-no-mistakes = Keine großen Fehler, gut gespielt!
+review-mistake = { $number } { $san }  (-{ $loss }% Gewinnchance)
+review-back = Zurück zum Spiel
+
+## Tutorial popup
+tutorial-lesson = Lektion { $number }/{ $total }: { $title }
+tutorial-continue = Enter: weiter
+tutorial-play-move = Spiele den markierten Zug
+tutorial-opponent-moving = Dein Gegner zieht...
+tutorial-guess-question = Welchen Drawback hat Schwarz?
+tutorial-guess-correct = Richtig! Schwarz hat "{ $drawback }": { $description }
+tutorial-guess-wrong = Nicht "{ $drawback }", versuch es noch einmal.
+tutorial-menu-hint = (Esc: Menü)
+
+## Tutorial lessons (referenced by src/modes/tutorial.txt)
+tutorial-king-title = Schlag den König
+tutorial-king-welcome = Willkommen bei Drawback Chess! Es gelten die normalen Schachregeln mit einer Ausnahme: Es gibt kein Schach. Du darfst ins Schach ziehen, und gewonnen hat, wer den gegnerischen König tatsächlich schlägt.
+tutorial-king-attack = Deine Dame und dein Läufer zielen beide auf f7. Schlage dort mit der Dame: Klicke die Dame an, dann das markierte Feld.
+tutorial-king-end = Der schwarze König wird angegriffen und bleibt es nach jedem Zug, also würde er im nächsten Zug geschlagen. Damit ist die Partie hier vorbei.
+tutorial-drawback-title = Jeder hat einen Drawback
+tutorial-drawback-intro = Jeder Spieler bekommt einen geheimen Drawback: eine Regel, an die nur er sich halten muss. Diesmal ist dein Drawback "Bauern nur ein Feld".
+tutorial-drawback-push = Deine Bauern dürfen nie zwei Felder vorrücken, auch nicht im ersten Zug. Ziehe den Königsbauern ein Feld vor.
+tutorial-drawback-end = Schwarz ist problemlos zwei Felder gezogen, denn Schwarz hat nicht deinen Drawback. Richte dein Spiel nach deinem aus!
+tutorial-hidden-title = Versteckte Drawbacks
+tutorial-hidden-intro = Den Drawback deines Gegners siehst du nicht, aber du kannst beobachten, wie er spielt. Du hast jetzt keinen Drawback, also rochiere kurz: Klicke den König an, dann den markierten Turm.
+tutorial-hidden-end = Der schwarze König ist nach f8 gelaufen, statt zu rochieren, und hat damit sein Rochaderecht für immer verloren. Seltsam... es sei denn, Schwarz durfte gar nicht rochieren.
+tutorial-guess-title = Errate den Drawback
+tutorial-guess-intro = Wer den Drawback des Gegners erkennt, kann ihn ausnutzen. Welchen Drawback hat Schwarz, nach dem was du gerade gesehen hast?
+tutorial-guess-end = Das ist das ganze Spiel: Spiele um deinen eigenen Drawback herum, finde den des Gegners heraus und schlage den König. Viel Glück!
@@ -0,0 +1,295 @@
+# English strings for Drawback Chess.
+# Fluent-style syntax: `key = value`, placeables are written `{ $name }`,
+# indented lines continue the previous value on a new line.
+
+language-name = English
+
+## Drawbacks
+drawback-none-name = None
+drawback-no-castling-name = No Castling
+drawback-no-castling-description = Castling (Kingside or Queenside) is not allowed.
+drawback-pawn-push-one-name = Pawns Advance One
+drawback-pawn-push-one-description = Pawns may not advance two squares on their first move.
+drawback-block-random-file-name = Random File Blocked
+drawback-block-random-file-description = At the start of your turn, a random file (A-H) is chosen. You cannot move any piece TO that file this turn.
+drawback-block-random-file-fixed-description = You can never move any piece TO the { $file }-file.
+drawback-recycling-name = Recycling
+drawback-recycling-description = Pawns you capture go to your reserve. Instead of moving, you may drop one on an empty square in your own half of the board.
+drawback-pacifist-opening-name = Pacifist Opening
+drawback-pacifist-opening-description = You may not capture anything (en passant included) before move { $first_capture_move }.
+drawback-edge-phobic-name = Edge-phobic
+drawback-edge-phobic-description = Your pieces may not move onto the a/h files or the 1st/8th ranks. Castling and promoting are still allowed.
+drawback-edge-phobic-castling-description = Your pieces may not move onto the a/h files or the 1st/8th ranks. Castling is still allowed, promoting is not.
+drawback-edge-phobic-promotion-description = Your pieces may not move onto the a/h files or the 1st/8th ranks. Promoting is still allowed, castling is not.
+drawback-edge-phobic-strict-description = Your pieces may not move onto the a/h files or the 1st/8th ranks. Castling and promoting aren't allowed either.
+drawback-follow-the-leader-name = Follow the Leader
+drawback-follow-the-leader-description = You must move the same kind of piece your opponent just moved, if you can.
+drawback-pawn-horde-name = Pawn Horde
+drawback-pawn-horde-description = You lose as soon as you have fewer than { $minimum } pawns.
+drawback-mirror-name = Mirror
+drawback-mirror-description = Your pawns may also move one square diagonally forward without capturing.
+drawback-claustrophobia-name = Claustrophobia
+drawback-claustrophobia-description = Your pieces may not move next to your own king. The king itself may still move and castle.
+drawback-vampire-name = Vampire
+drawback-vampire-description = You must capture at least once every 5 of your moves, or you lose.
+drawback-glass-cannon-name = Glass Cannon
+drawback-glass-cannon-description = Your queen may never be defended: no move may protect it, and it may not move to a protected square.
+drawback-slippery-fingers-name = Slippery Fingers
+drawback-slippery-fingers-description = Each turn there is a 1 in 3 chance that your slides of 4 or more squares overshoot by one square.
+
+## Game over reasons and results
+reason-king-captured = King Captured
+reason-checkmate = Checkmate
+reason-stalemate = Stalemate
+reason-repetition = Threefold Repetition
+reason-timeout = Timeout
+reason-resigned = { $color } resigned
+reason-abandoned = { $color } left the game
+reason-drawback-loss = { $color } lost to their drawback ({ $drawback })
+color-white = White
+color-black = Black
+piece-pawn = Pawn
+piece-knight = Knight
+piece-bishop = Bishop
+piece-rook = Rook
+piece-queen = Queen
+piece-king = King
+result-win = Win
+result-loss = Loss
+result-draw = Draw
+
+## Game over banner
+game-over-king-captured = { $color } captures the king!
+game-over-king-captured-detail = The king fell on { $square }
+game-over-wins = { $color } wins
+game-over-draw = Draw
+game-over-timeout-detail = { $color } ran out of time
+game-over-save = Save game (S)
+game-over-saved = Saved to { $file }
+game-over-save-failed = Could not save the game: { $error }
+
+## Pause menu
+menu-paused = Paused
+menu-settings = Settings
+menu-new-game = New Game
+menu-resume = Resume
+menu-ladder = Ladder
+menu-trophies = Trophies
+menu-review = Game Review
+menu-tutorial = Tutorial
+menu-leave-tutorial = Leave Tutorial
+menu-force-ai-move = AI Moves Now
+menu-swap-sides = Swap Sides
+menu-share-position = Share Position
+menu-offer-rematch = Offer Rematch
+menu-accept-rematch = Accept Rematch
+menu-new-drawbacks = New Drawbacks
+menu-resign = Resign
+menu-quit = Quit
+menu-flip-board = Flip Board
+menu-toggle-low-power = Toggle Low Power
+menu-teaching-mode = Teaching mode: { $state }
+menu-on = On
+menu-off = Off
+menu-language = Language: { $language }
+menu-clock-delay = Clock delay: { $mode }
+menu-clock-delay-none = Off
+menu-clock-delay-simple = Simple
+menu-clock-delay-bronstein = Bronstein
+menu-share-with-fen = Shared images show the FEN: { $state }
+menu-hash-size = AI hash table: { $size } MB
+menu-sound = Sound: { $state }
+menu-rule-explanations = Rule explanations: { $state }
+menu-ai-strength = AI strength: { $level }
+menu-ai-strength-easy = Easy
+menu-ai-strength-medium = Medium
+menu-ai-strength-hard = Hard
+menu-ai-strength-custom = Custom
+menu-back = Back
+
+## Setup screen
+setup-title = New Game
+setup-human = Human
+setup-ai = AI
+setup-random-drawback = Random
+setup-random-description = A random drawback, rolled when the game starts
+setup-start = Start
+setup-back = Back
+
+## Ladder
+ladder-title = Drawback Ladder
+ladder-status-beaten = Beaten
+ladder-status-next = Next
+ladder-status-locked = Locked
+ladder-rung =
+    { $number }. { $name }  [{ $status }]
+    Your drawback: { $drawback }
+ladder-back = Back to Game
+ladder-quit = Quit
+ladder-rung-apprentice = The Apprentice
+ladder-rung-squire = The Squire
+ladder-rung-knight = The Knight
+ladder-rung-warden = The Warden
+ladder-rung-grandmaster = The Grandmaster
+ladder-result-win = You beat { $name }! ({ $reason })
+ladder-result-loss = { $name } beat you ({ $reason })
+ladder-result-draw = Draw against { $name } ({ $reason })
+
+## Daily challenge banner
+daily-title = Daily Challenge { $date }
+daily-your-drawback = Your drawback: { $drawback }
+daily-ai-drawback-was = AI drawback was: { $drawback }
+daily-ai-drawback-hidden = AI drawback: ???
+daily-result = Result: { $result }
+daily-streak = Streak: { $streak } day(s) (best { $best })
+
+## Random drawback banner
+random-drawback = { $color } rolled: { $drawback }
+random-drawback-hidden = { $color } rolled a secret drawback
+
+## Move changed by a drawback
+move-substituted = { $color } aimed for { $picked } but slipped to { $played }!
+
+## Rule explanation popup
+rule-explanation-title = { $color }'s drawback: { $drawback }
+rule-explanation-filtered = It just took away some of this piece's moves.
+rule-explanation-rolled = It rolled the dice for this turn.
+rule-explanation-close = Got it
+rule-explanation-dont-show = Don't show again
+
+## Kiosk mode overlay
+kiosk-title = Drawback Chess
+kiosk-drawbacks = White: { $white }   vs   Black: { $black }
+
+## Network game banner
+net-waiting-for-player = Waiting for the other player to join...
+net-connecting = Connecting to the host...
+net-waiting-for-setup = Connected, waiting for the host to start a game
+net-playing = Network game: you play { $color }
+net-hidden-host = Your drawback is hidden until the game is over; you see the other player's
+net-hidden-client = The host's drawback is hidden until the game is over; the host sees yours
+net-desynced = Out of sync with the host, fetching its game...
+net-resyncing = Replaying the host's game...
+net-opponent-away = Connection lost, waiting { $seconds }s for the other player to come back
+net-reconnecting = Connection lost, reconnecting to the host ({ $seconds }s left)
+net-failed = Network game stopped: { $reason }
+net-disconnected = Disconnected: { $reason }
+
+## Network game chat
+chat-hint = Enter: chat
+chat-muted = Chat muted
+chat-mute = Mute
+chat-unmute = Unmute
+
+## Arena match header
+arena-title = Match (best of { $games }), game { $game }
+arena-score = { $first } { $first_score } - { $second_score } { $second }
+arena-player = Player { $number } ({ $kind }, { $color })
+arena-human = Human
+arena-ai = AI
+arena-won = Match over: Player { $number } wins
+arena-drawn = Match over: drawn
+
+## Move tooltip
+tooltip-blocked-move = { $square }  { $san }: blocked by { $drawback }
+tooltip-piece = { $piece } on { $square }: { $allowed } of { $legal } moves allowed
+
+## Shared position images
+share-drawback = { $color }: { $drawback }
+share-fen = Move { $move }: { $fen }
+
+## Promotion picker
+promotion-title = Promote to (Q/R/B/N, Esc cancels)
+
+## Drawback meter
+drawback-meter = Your drawback removed { $removed } of { $total } moves this turn
+
+## Opponent model panel
+belief-likely = AI thinks { $color } likely has: { $candidates }
+belief-trend = { $drawback } over the game (ply: chance): { $trend }
+belief-ruled-out = Ply { $ply }: ruled out { $drawbacks }
+
+## Chess clock
+clock-draw-counts-as = Draw counts as a win for { $color }
+move-reminder = Time to move!
+move-reminder-auto = Time to move! A random move is played in { $seconds }s
+
+## AI thinking indicator
+ai-thinking = { $spinner } AI is thinking... { $seconds }s  depth { $depth }  nodes { $nodes }
+computing-legal-moves = { $spinner } Computing legal moves...
+
+## AI crash banner
+ai-crashed = The AI crashed and played a fallback move ({ $move }). Details are in { $path }
+
+## Replay panel
+replay-start = REPLAY  start position (0/{ $total })
+replay-move = REPLAY  { $number } { $san }{ $glyphs }  ({ $ply }/{ $total })
+replay-time = Time spent: { $spent }
+replay-time-clock = Time spent: { $spent }, clock after the move: { $clock }
+replay-eval = Eval: { $score }
+replay-comment-editor = Comment: { $text }_   [Enter] save  [Esc] cancel
+replay-help = [Left/Right/Home/End] step  [1-6] ! ? !! ?? !? ?!  [0] clear  [C] comment  [S] save  [L] load  [Tab] live
+
+## Trophies and achievements
+trophies-title = Trophies ({ $unlocked }/{ $total })
+trophies-back = Back to Game
+achievement-unlocked = Achievement unlocked: { $name }
+achievement-first-win-name = First Blood
+achievement-first-win-description = Win a game against the AI
+achievement-win-with-drawback-name = Handicapped Hero: { $drawback }
+achievement-win-with-drawback-description = Win a game with the "{ $drawback }" drawback
+achievement-win-with-every-drawback-name = Jack of All Drawbacks
+achievement-win-with-every-drawback-description = Win at least once with every drawback
+achievement-win-on-time-name = Flag Hunter
+achievement-win-on-time-description = Win because the AI ran out of time
+achievement-knight-promotion-mate-name = Knighty Knight
+achievement-knight-promotion-mate-description = Promote a pawn to a knight and win by checkmate
+achievement-survive-random-file-name = File Dodger
+achievement-survive-random-file-description = Finish a "{ $drawback }" game without losing
+achievement-quick-win-name = Blitzkrieg
+achievement-quick-win-description = Win in { $moves } moves or fewer
+achievement-daily-win-name = Daily Grind
+achievement-daily-win-description = Win a daily challenge
+achievement-daily-streak-name = Creature of Habit
+achievement-daily-streak-description = Finish the daily challenge { $days } days in a row
+achievement-ladder-champion-name = Top of the Ladder
+achievement-ladder-champion-description = Beat every opponent on the ladder
+
+## Game review
+review-title = Game Review
+review-reason = Result: { $reason }
+review-analysing = Analysing the game...
+review-none = No finished game to review yet.
+review-player = { $color }: accuracy { $accuracy }%, drawback removed { $removed } moves per turn
+review-graph = Material (bars) and evaluation (line), click to jump to a move
+review-time = Time spent per move
+review-mistakes = Biggest mistakes
+review-no-mistakes = No big mistakes, well played!
+review-mistake = { $number } { $san }  (-{ $loss }% win chance)
+review-back = Back to Game
+
+## Tutorial popup
+tutorial-lesson = Lesson { $number }/{ $total }: { $title }
+tutorial-continue = Enter: continue
+tutorial-play-move = Play the highlighted move
+tutorial-opponent-moving = Your opponent is moving...
+tutorial-guess-question = Which drawback does Black have?
+tutorial-guess-correct = Correct! Black has "{ $drawback }": { $description }
+tutorial-guess-wrong = Not "{ $drawback }", try again.
+tutorial-menu-hint = (Esc: menu)
+
+## Tutorial lessons (referenced by src/modes/tutorial.txt)
+tutorial-king-title = Capture the king
+tutorial-king-welcome = Welcome to Drawback Chess! The rules are normal chess with one twist: there is no check. Moving into check is allowed, and you win by actually capturing the enemy king.
+tutorial-king-attack = Your queen and bishop are both aiming at f7. Capture it with the queen: click the queen, then the highlighted square.
+tutorial-king-end = Black's king is attacked and every move leaves it attacked, so the king would be captured next turn. That ends the game right here.
+tutorial-drawback-title = Everyone has a drawback
+tutorial-drawback-intro = Each player gets a secret drawback: a rule that only they must follow. This time your drawback is "Pawns Advance One".
+tutorial-drawback-push = Your pawns can never make the two-square jump, not even on their first move. Push the king's pawn one square.
+tutorial-drawback-end = Black jumped two squares without trouble, because Black doesn't share your drawback. Plan your game around yours!
+tutorial-hidden-title = Hidden drawbacks
+tutorial-hidden-intro = You can't see your opponent's drawback, but you can watch how they play. You have no drawback now, so castle kingside: click the king, then the highlighted rook.
+tutorial-hidden-end = Black's king walked to f8 instead of castling, giving up its castling rights for good. A strange choice... unless Black wasn't allowed to castle.
+tutorial-guess-title = Guess the drawback
+tutorial-guess-intro = Spotting the opponent's drawback lets you exploit it. Based on what you just saw, which drawback does Black have?
+tutorial-guess-end = That's the whole game: play around your own drawback, figure out theirs, and capture the king. Good luck!
@@ -0,0 +1,240 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use shakmaty::{Color as ChessColor, Role};
+use crate::drawbacks::{DrawbackRegistry, DrawbackId, DrawbackParams};
+use crate::game_logic::events::GameOverReason;
+use crate::stats::store::PlayerResult;
+
+/// Languages the game ships strings for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    /// Code used in the configuration ("en", "de", ...)
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::German => "de",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Language> {
+        Self::ALL.into_iter().find(|language| language.code().eq_ignore_ascii_case(code))
+    }
+
+    /// The language after this one (for cycling through them in the settings)
+    pub fn next(self) -> Language {
+        let index = Self::ALL.iter().position(|language| *language == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    // The strings for this language, compiled into the binary
+    fn source(self) -> &'static str {
+        match self {
+            Self::English => include_str!("locales/en.ftl"),
+            Self::German => include_str!("locales/de.ftl"),
+        }
+    }
+}
+
+/// Resource with the user-facing strings of the current language.
+/// Keys missing from a translation fall back to English, then to the key itself.
+#[derive(Resource)]
+pub struct Localization {
+    language: Language,
+    messages: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+impl Default for Localization {
+    fn default() -> Self {
+        Self::new(Language::default())
+    }
+}
+
+impl Localization {
+    pub fn new(language: Language) -> Self {
+        Self {
+            language,
+            messages: parse_messages(language.source()),
+            fallback: parse_messages(Language::English.source()),
+        }
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// True if a string exists for `key`
+    pub fn has(&self, key: &str) -> bool {
+        self.messages.contains_key(key) || self.fallback.contains_key(key)
+    }
+
+    /// The string for `key`
+    pub fn text(&self, key: &str) -> String {
+        self.text_with(key, &[])
+    }
+
+    /// The string for `key` with its `{ $name }` placeables filled in
+    pub fn text_with(&self, key: &str, args: &[(&str, String)]) -> String {
+        let Some(pattern) = self.messages.get(key).or_else(|| self.fallback.get(key)) else {
+            return key.to_string();
+        };
+        fill_placeables(pattern, args)
+    }
+
+    /// Display name of a drawback in the current language
+    pub fn drawback_name(&self, registry: &DrawbackRegistry, id: DrawbackId) -> String {
+        let key = format!("drawback-{}-name", drawback_key(id));
+        if self.has(&key) {
+            return self.text(&key);
+        }
+        // Drawbacks without a translation yet show their built-in name
+        registry.rules.get(&id).map(|rule| rule.name().to_string()).unwrap_or_else(|| self.text("drawback-none-name"))
+    }
+
+    /// Description of a drawback in the current language, as it plays with `params`
+    pub fn drawback_description(&self, registry: &DrawbackRegistry, id: DrawbackId, params: &DrawbackParams) -> String {
+        let Some(rule) = registry.rule(id, params) else {
+            return String::new();
+        };
+        let key = match rule.description_variant() {
+            Some(variant) => format!("drawback-{}-{}-description", drawback_key(id), variant),
+            None => format!("drawback-{}-description", drawback_key(id)),
+        };
+        if self.has(&key) {
+            return self.text_with(&key, &rule.description_args());
+        }
+        rule.description()
+    }
+
+    /// Translates a game over reason as sent in `GameOverEvent`
+    pub fn game_over_reason(&self, reason: &GameOverReason, registry: &DrawbackRegistry) -> String {
+        match *reason {
+            GameOverReason::KingCaptured { .. } => self.text("reason-king-captured"),
+            GameOverReason::Checkmate { .. } => self.text("reason-checkmate"),
+            GameOverReason::DrawbackLoss { loser, drawback } => self.text_with("reason-drawback-loss", &[
+                ("color", self.color_name(loser)),
+                ("drawback", self.drawback_name(registry, drawback)),
+            ]),
+            GameOverReason::Timeout { .. } => self.text("reason-timeout"),
+            GameOverReason::Resignation { loser } => self.text_with("reason-resigned", &[("color", self.color_name(loser))]),
+            GameOverReason::Abandoned { loser } => self.text_with("reason-abandoned", &[("color", self.color_name(loser))]),
+            GameOverReason::Stalemate => self.text("reason-stalemate"),
+            GameOverReason::DrawByRepetition => self.text("reason-repetition"),
+        }
+    }
+
+    pub fn color_name(&self, color: ChessColor) -> String {
+        match color {
+            ChessColor::White => self.text("color-white"),
+            ChessColor::Black => self.text("color-black"),
+        }
+    }
+
+    pub fn role_name(&self, role: Role) -> String {
+        self.text(&format!("piece-{}", format!("{:?}", role).to_lowercase()))
+    }
+
+    pub fn player_result(&self, result: PlayerResult) -> String {
+        match result {
+            PlayerResult::Win => self.text("result-win"),
+            PlayerResult::Loss => self.text("result-loss"),
+            PlayerResult::Draw => self.text("result-draw"),
+        }
+    }
+}
+
+// Key fragment used for a drawback's strings
+fn drawback_key(id: DrawbackId) -> &'static str {
+    match id {
+        DrawbackId::None => "none",
+        DrawbackId::NoCastling => "no-castling",
+        DrawbackId::PawnPushOneOnly => "pawn-push-one",
+        DrawbackId::BlockRandomFile => "block-random-file",
+        DrawbackId::Recycling => "recycling",
+        DrawbackId::PacifistOpening => "pacifist-opening",
+        DrawbackId::EdgePhobic => "edge-phobic",
+        DrawbackId::FollowTheLeader => "follow-the-leader",
+        DrawbackId::PawnHorde => "pawn-horde",
+        DrawbackId::Mirror => "mirror",
+        DrawbackId::Claustrophobia => "claustrophobia",
+        DrawbackId::Vampire => "vampire",
+        DrawbackId::GlassCannon => "glass-cannon",
+        DrawbackId::SlipperyFingers => "slippery-fingers",
+    }
+}
+
+/// Parses a small subset of the Fluent (.ftl) format: `key = value` lines,
+/// `#` comments, and indented lines continuing the previous value
+fn parse_messages(source: &str) -> HashMap<String, String> {
+    let mut messages: HashMap<String, String> = HashMap::new();
+    let mut current_key: Option<String> = None;
+
+    for line in source.lines() {
+        let is_continuation = line.starts_with(' ') || line.starts_with('\t');
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() || (!is_continuation && trimmed.starts_with('#')) {
+            if trimmed.is_empty() {
+                current_key = None;
+            }
+            continue;
+        }
+
+        if is_continuation {
+            if let Some(value) = current_key.as_ref().and_then(|key| messages.get_mut(key)) {
+                if !value.is_empty() {
+                    value.push('\n');
+                }
+                value.push_str(trimmed);
+            }
+            continue;
+        }
+
+        match trimmed.split_once('=') {
+            Some((key, value)) => {
+                let key = key.trim().to_string();
+                messages.insert(key.clone(), value.trim().to_string());
+                current_key = Some(key);
+            }
+            None => {
+                eprintln!("Ignoring malformed localization line: {}", trimmed);
+                current_key = None;
+            }
+        }
+    }
+
+    messages
+}
+
+// Helper function to replace `{ $name }` placeables with their values
+fn fill_placeables(pattern: &str, args: &[(&str, String)]) -> String {
+    let mut result = String::with_capacity(pattern.len());
+    let mut rest = pattern;
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let Some(length) = rest[start..].find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+
+        let placeable = rest[start + 1..start + length].trim();
+        match placeable.strip_prefix('$').and_then(|name| args.iter().find(|(arg, _)| *arg == name)) {
+            Some((_, value)) => result.push_str(value),
+            // Unknown placeables are kept so missing arguments are easy to spot
+            None => result.push_str(&rest[start..=start + length]),
+        }
+        rest = &rest[start + length + 1..];
+    }
+
+    result.push_str(rest);
+    result
+}
@@ -0,0 +1,5 @@
+pub mod plugin;
+pub mod localization;
+
+pub use plugin::I18nPlugin;
+pub use localization::Localization;
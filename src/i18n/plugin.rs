@@ -0,0 +1,32 @@
+use bevy::prelude::*;
+use crate::config::GameConfig;
+use super::localization::{Localization, Language};
+
+/// Plugin providing the `Localization` resource for the configured language
+pub struct I18nPlugin;
+
+impl Plugin for I18nPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Localization>()
+           // PreStartup so the UI spawned at startup already uses the right language
+           .add_systems(PreStartup, sync_language)
+           .add_systems(Update, sync_language);
+    }
+}
+
+/// System to load the strings of the configured language whenever it changes
+fn sync_language(config: Res<GameConfig>, mut localization: ResMut<Localization>) {
+    if !config.is_changed() {
+        return;
+    }
+
+    let language = Language::from_code(&config.display.language).unwrap_or_else(|| {
+        eprintln!("Unknown language '{}', using English", config.display.language);
+        Language::English
+    });
+
+    if language != localization.language() {
+        println!("Switching language to {}", language.code());
+        *localization = Localization::new(language);
+    }
+}
@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use shakmaty::Square;
+use crate::board::geometry::square_at;
+
+// All window positions here are in logical pixels, the unit of `Window::cursor_position`
+// and of UI `Val::Px`. The OS reports the cursor in physical pixels, so on a display
+// scaled by 2 a logical pixel covers two physical ones.
+
+/// World position under the cursor, None when the cursor is outside the window
+/// or outside the camera's viewport
+pub fn cursor_world_position(window: &Window, camera: &Camera, camera_transform: &GlobalTransform) -> Option<Vec2> {
+    window_to_world(window.cursor_position()?, camera, camera_transform)
+}
+
+/// World position of a point of the window
+pub fn window_to_world(position: Vec2, camera: &Camera, camera_transform: &GlobalTransform) -> Option<Vec2> {
+    // The camera maps positions relative to its viewport, which needn't start at the window corner
+    let viewport = camera.logical_viewport_rect()?;
+    if !viewport.contains(position) {
+        return None;
+    }
+    camera.viewport_to_world_2d(camera_transform, position - viewport.min)
+}
+
+/// Window position of a point of the world, e.g. to anchor a UI node to a square
+pub fn world_to_window(world: Vec3, camera: &Camera, camera_transform: &GlobalTransform) -> Option<Vec2> {
+    let viewport = camera.logical_viewport_rect()?;
+    Some(camera.world_to_viewport(camera_transform, world)? + viewport.min)
+}
+
+/// Square under the cursor, None when the cursor isn't over the board.
+/// `flipped` is the board orientation (`GameState::board_flipped`).
+pub fn hovered_square(window: &Window, camera: &Camera, camera_transform: &GlobalTransform, flipped: bool) -> Option<Square> {
+    square_at(cursor_world_position(window, camera, camera_transform)?, flipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::TILE_SIZE;
+    use bevy::math::DVec2;
+    use bevy::render::camera::{camera_system, ManualTextureViews};
+    use bevy::window::{PrimaryWindow, WindowCreated, WindowResized, WindowResolution};
+
+    // A window with the given scale factor and a 2D camera looking at the board
+    fn test_app(scale_factor: f64) -> App {
+        let mut app = App::new();
+        app.add_event::<WindowCreated>()
+            .add_event::<WindowResized>()
+            .add_event::<AssetEvent<Image>>()
+            .insert_resource(Assets::<Image>::default())
+            .insert_resource(ManualTextureViews::default())
+            .add_systems(Update, camera_system::<OrthographicProjection>);
+
+        let window = app.world.spawn((
+            Window {
+                resolution: WindowResolution::new(1600.0, 1200.0).with_scale_factor_override(scale_factor),
+                ..default()
+            },
+            PrimaryWindow,
+        )).id();
+        app.world.send_event(WindowCreated { window });
+        let camera = Camera2dBundle::default();
+        let camera_transform = GlobalTransform::from(camera.transform);
+        app.world.spawn(camera).insert(camera_transform);
+        app.update();
+        app
+    }
+
+    // Square under a cursor reported by the OS in physical pixels, with a1 in
+    // the bottom-left corner
+    fn square_under_physical_cursor(app: &mut App, physical: DVec2) -> Option<Square> {
+        let mut windows = app.world.query::<&mut Window>();
+        windows.single_mut(&mut app.world).set_physical_cursor_position(Some(physical));
+
+        let mut windows = app.world.query::<&Window>();
+        let mut cameras = app.world.query::<(&Camera, &GlobalTransform)>();
+        let window = windows.single(&app.world);
+        let (camera, camera_transform) = cameras.single(&app.world);
+        hovered_square(window, camera, camera_transform, true)
+    }
+
+    // Physical cursor position over the middle of a world point
+    fn physical_position_of(app: &mut App, world: Vec2) -> DVec2 {
+        let mut windows = app.world.query::<&Window>();
+        let mut cameras = app.world.query::<(&Camera, &GlobalTransform)>();
+        let scale_factor = windows.single(&app.world).scale_factor();
+        let (camera, camera_transform) = cameras.single(&app.world);
+        let logical = world_to_window(world.extend(0.0), camera, camera_transform).unwrap();
+        logical.as_dvec2() * scale_factor
+    }
+
+    #[test]
+    fn cursor_hits_the_same_square_at_any_scale_factor() {
+        let a1 = Vec2::new(-3.5 * TILE_SIZE, -3.5 * TILE_SIZE);
+        for scale_factor in [1.0, 1.5, 2.0] {
+            let mut app = test_app(scale_factor);
+            let center = physical_position_of(&mut app, a1);
+            assert_eq!(square_under_physical_cursor(&mut app, center), Some(Square::A1), "scale {}", scale_factor);
+
+            // A bit more than half a tile to the right is the next square
+            let right = physical_position_of(&mut app, a1 + Vec2::new(TILE_SIZE * 0.6, 0.0));
+            assert_eq!(square_under_physical_cursor(&mut app, right), Some(Square::B1), "scale {}", scale_factor);
+
+            // ... and a bit more than half a tile below is off the board
+            let below = physical_position_of(&mut app, a1 - Vec2::new(0.0, TILE_SIZE * 0.6));
+            assert_eq!(square_under_physical_cursor(&mut app, below), None, "scale {}", scale_factor);
+        }
+    }
+
+    #[test]
+    fn clicks_follow_a_zoomed_and_panned_camera() {
+        let a1 = Vec2::new(-3.5 * TILE_SIZE, -3.5 * TILE_SIZE);
+        let mut app = test_app(1.0);
+        let mut cameras = app.world.query::<(&mut Transform, &mut GlobalTransform, &mut OrthographicProjection)>();
+        let (mut transform, mut global_transform, mut projection) = cameras.single_mut(&mut app.world);
+        projection.scale = 0.5;
+        transform.translation = a1.extend(transform.translation.z);
+        *global_transform = GlobalTransform::from(*transform);
+        app.update();
+
+        // The middle of the window is now a1, and a tile takes twice the pixels
+        assert_eq!(square_under_physical_cursor(&mut app, DVec2::new(800.0, 600.0)), Some(Square::A1));
+        assert_eq!(square_under_physical_cursor(&mut app, DVec2::new(800.0 + TILE_SIZE as f64 * 1.1, 600.0)), Some(Square::B1));
+        assert_eq!(square_under_physical_cursor(&mut app, DVec2::new(800.0, 600.0 + TILE_SIZE as f64 * 1.1)), None);
+    }
+
+    #[test]
+    fn window_center_is_the_world_origin() {
+        let mut app = test_app(2.0);
+        let mut windows = app.world.query::<&Window>();
+        let mut cameras = app.world.query::<(&Camera, &GlobalTransform)>();
+        let window = windows.single(&app.world);
+        let (camera, camera_transform) = cameras.single(&app.world);
+
+        // 1600x1200 physical pixels at scale 2 is a logical 800x600 window
+        assert_eq!((window.width(), window.height()), (800.0, 600.0));
+        let world = window_to_world(Vec2::new(400.0, 300.0), camera, camera_transform).unwrap();
+        assert!(world.length() < 0.01, "got {:?}", world);
+        assert_eq!(window_to_world(Vec2::new(801.0, 300.0), camera, camera_transform), None);
+    }
+}
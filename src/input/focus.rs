@@ -0,0 +1,11 @@
+use bevy::prelude::*;
+
+/// Resource set while a text field (e.g. the comment editor) owns the keyboard,
+/// so single-key shortcuts like F (flip) or Esc (pause) don't fire while typing
+#[derive(Resource, Debug, Default)]
+pub struct TextInputFocus(pub bool);
+
+/// Run condition: true when keyboard shortcuts should be handled
+pub fn keyboard_shortcuts_enabled(focus: Res<TextInputFocus>) -> bool {
+    !focus.0
+}
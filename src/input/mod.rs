@@ -1,4 +1,7 @@
 pub mod plugin;
 pub mod systems;
+pub mod focus;
+pub mod cursor;
+pub mod zoom;
 
  
\ No newline at end of file
@@ -1,11 +1,68 @@
 use bevy::prelude::*;
 use super::systems::*;
-use crate::game_logic::state::TurnState;
+use super::focus::{TextInputFocus, keyboard_shortcuts_enabled};
+use super::zoom::zoom_and_pan_board;
+use crate::game_logic::state::{ReplayState, gameplay_active};
+use crate::game_logic::events::{NewGameEvent, UndoMoveEvent, RedoMoveEvent};
+use crate::game_logic::legal_moves::refresh_legal_moves_cache;
+use crate::game_logic::systems::apply_move;
+use crate::pieces::promotion::{PromotionCancelledEvent, no_pending_promotion, handle_promotion_selection};
 
 pub struct InputPlugin;
 
 impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, handle_piece_selection.run_if(in_state(TurnState::PlayerTurn)));
+        app.init_resource::<TextInputFocus>()
+           .init_resource::<MoveReminder>()
+           .add_systems(
+                Update,
+                handle_piece_selection
+                    .after(refresh_legal_moves_cache)
+                    .run_if(human_to_move)
+                    .run_if(gameplay_active)
+                    .run_if(no_pending_promotion)
+                    // A click that cancels a promotion still selects what was clicked
+                    .after(handle_promotion_selection)
+           )
+           // Drops from the reserve tray, before the click reaches the board
+           .add_systems(
+                Update,
+                handle_reserve_selection
+                    .after(refresh_legal_moves_cache)
+                    .before(handle_piece_selection)
+                    .run_if(human_to_move)
+                    .run_if(gameplay_active)
+                    .run_if(no_pending_promotion)
+           )
+           .add_systems(
+                Update,
+                reselect_cancelled_promotion
+                    .after(handle_piece_selection)
+                    .run_if(on_event::<PromotionCancelledEvent>())
+           )
+           // Selection highlights are meaningless while replaying old positions
+           .add_systems(OnEnter(ReplayState::Replay), clear_move_indicators)
+           .add_systems(
+                Update,
+                clear_move_indicators.run_if(on_event::<NewGameEvent>().or_else(on_event::<UndoMoveEvent>()).or_else(on_event::<RedoMoveEvent>()))
+           )
+           // Anti-stall timer: a reminder, then maybe a random move (played before
+           // apply_move so the same frame starts timing the next move)
+           .add_systems(
+                Update,
+                (
+                    remind_stalled_player
+                        .after(refresh_legal_moves_cache)
+                        .before(apply_move)
+                        .run_if(human_to_move)
+                        .run_if(gameplay_active)
+                        .run_if(no_pending_promotion),
+                    clear_move_reminder.run_if(not(human_to_move)),
+                )
+           )
+           // Board zoom (Ctrl + wheel) and pan (middle or right drag); works in replays too
+           .add_systems(Update, zoom_and_pan_board)
+           // Ctrl+Z takes a move back, Ctrl+Y (or Ctrl+Shift+Z) plays it again
+           .add_systems(Update, handle_take_back_keys.run_if(keyboard_shortcuts_enabled).run_if(gameplay_active));
     }
-} 
\ No newline at end of file
+}
@@ -1,11 +1,21 @@
 use bevy::prelude::*;
-use std::ops::Add;
-use crate::game_logic::events::MakeMoveEvent;
-use crate::game_logic::state::{GameState, TurnState};
+use crate::game_logic::events::{MakeMoveEvent, UndoMoveEvent, RedoMoveEvent};
+use crate::game_logic::state::{GameState, ActiveBoard, MoveRestriction, TurnState};
+use crate::game_logic::clock::{GameClock, LowTimeLevel, MoveTimer};
+use crate::game_logic::rng::GameRng;
+use crate::game_logic::legal_moves::LegalMovesCache;
+use crate::game_logic::perspective::PlayerPerspective;
+use crate::config::GameConfig;
+use crate::net::lockstep::NetSession;
 use crate::board::components::BoardSquare;
+use crate::board::geometry::world_pos;
 use crate::pieces::components::Piece;
-use crate::constants::{SELECTED_COLOR, LEGAL_MOVE_COLOR, TILE_SIZE, Z_LEGAL_MOVES, Z_HIGHLIGHT, Z_PIECES};
-use shakmaty::{Move, Square, Role, Position, Color as ChessColor, File, Rank};
+use crate::pieces::promotion::PromotionCancelledEvent;
+use super::cursor::hovered_square;
+use crate::ui::reserve_tray::ReserveButton;
+use crate::constants::{SELECTED_COLOR, LEGAL_MOVE_COLOR, BLOCKED_MOVE_COLOR, TILE_SIZE, Z_LEGAL_MOVES, Z_HIGHLIGHT};
+use shakmaty::{Move, Square, Role, Color as ChessColor, File};
+use rand::seq::SliceRandom;
 
 // Component to mark the currently selected piece
 #[derive(Component)]
@@ -17,10 +27,104 @@ pub struct ValidMoveDestination {
     pub chess_move: Move,
 }
 
+// Component to mark squares the selected piece could reach if not for the drawback
+// (teaching mode). They are selection highlights too, so they are cleared with them.
+#[derive(Component)]
+pub struct BlockedMoveDestination {
+    pub chess_move: Move,
+}
+
 // Add a new component to differentiate selection highlights from move indicators
 #[derive(Component)]
 pub struct PieceSelectionHighlight;
 
+/// Run condition: true while a side played by a human is to move (whichever
+/// color that is, e.g. after the sides were swapped). In network games only
+/// the side of the player at this machine counts.
+pub fn human_to_move(
+    turn_state: Res<State<TurnState>>,
+    config: Res<GameConfig>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    network: Option<Res<NetSession>>,
+) -> bool {
+    let waiting_for_move = matches!(turn_state.get(), TurnState::PlayerTurn | TurnState::AiTurn);
+    waiting_for_move && boards.get_single().is_ok_and(|game_state| {
+        let color = game_state.current_player_turn;
+        let is_human = match color {
+            ChessColor::White => !config.white_player.is_ai,
+            ChessColor::Black => !config.black_player.is_ai,
+        };
+        is_human && network.is_none_or(|session| session.local_may_move(color))
+    })
+}
+
+/// Resource with the anti-stall reminder for the player to move
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct MoveReminder {
+    pub shown: bool,                     // The player has thought longer than the settings allow
+    pub auto_move_in_secs: Option<f32>,  // Time left before a random move is played for them
+}
+
+/// System reminding a player who takes longer than the anti-stall settings
+/// allow, and playing a random legal move for them once the grace period is
+/// over if the settings ask for that
+#[allow(clippy::too_many_arguments)]
+pub fn remind_stalled_player(
+    config: Res<GameConfig>,
+    timer: Res<MoveTimer>,
+    mut reminder: ResMut<MoveReminder>,
+    legal_moves: Res<LegalMovesCache>,
+    restriction: Res<MoveRestriction>,
+    mut rng: ResMut<GameRng>,
+    mut ev_make_move: EventWriter<MakeMoveEvent>,
+    mut commands: Commands,
+    selected: Query<Entity, With<SelectedPiece>>,
+    valid_moves: Query<(Entity, &ValidMoveDestination)>,
+    selection_highlights: Query<Entity, With<PieceSelectionHighlight>>,
+) {
+    let settings = &config.anti_stall;
+    let overtime = timer.elapsed_secs() - settings.move_secs;
+    if !settings.enabled || overtime < 0.0 {
+        reminder.set_if_neq(MoveReminder::default());
+        return;
+    }
+    let auto_move_in_secs = settings.auto_move.then(|| (settings.grace_secs - overtime).max(0.0));
+    reminder.set_if_neq(MoveReminder { shown: true, auto_move_in_secs });
+    if auto_move_in_secs != Some(0.0) {
+        return;
+    }
+
+    let moves: Vec<&Move> = legal_moves.moves().iter().filter(|chess_move| restriction.allows(chess_move)).collect();
+    if let Some(chess_move) = moves.choose(rng.session()) {
+        println!("Out of time to think, playing {:?}", chess_move);
+        ev_make_move.send(MakeMoveEvent((*chess_move).clone()));
+        clear_selection(&mut commands, &selected, &valid_moves, &selection_highlights);
+    }
+}
+
+/// System taking the reminder down while nobody at this machine is to move
+pub fn clear_move_reminder(mut reminder: ResMut<MoveReminder>) {
+    reminder.set_if_neq(MoveReminder::default());
+}
+
+/// System sending take backs for Ctrl+Z and redos for Ctrl+Y or Ctrl+Shift+Z
+pub fn handle_take_back_keys(
+    keys: Res<Input<KeyCode>>,
+    mut ev_undo: EventWriter<UndoMoveEvent>,
+    mut ev_redo: EventWriter<RedoMoveEvent>,
+) {
+    if !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if keys.just_pressed(KeyCode::Y) || (shift && keys.just_pressed(KeyCode::Z)) {
+        ev_redo.send(RedoMoveEvent);
+    } else if keys.just_pressed(KeyCode::Z) {
+        ev_undo.send(UndoMoveEvent);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn handle_piece_selection(
     mouse_button: Res<Input<MouseButton>>,
     windows: Query<&Window>,
@@ -28,45 +132,52 @@ pub fn handle_piece_selection(
     board_squares: Query<(&Transform, &BoardSquare)>,
     pieces: Query<(Entity, &Piece, &Transform)>,
     mut commands: Commands,
-    game_state: Res<GameState>,
+    boards: Query<&GameState, With<ActiveBoard>>,
     mut ev_make_move: EventWriter<MakeMoveEvent>,
     selected: Query<Entity, With<SelectedPiece>>,
     valid_moves: Query<(Entity, &ValidMoveDestination)>,
     selection_highlights: Query<Entity, With<PieceSelectionHighlight>>,
+    restriction: Res<MoveRestriction>,
+    config: Res<GameConfig>,
+    clock: Res<GameClock>,
+    legal_moves: Res<LegalMovesCache>,
 ) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
     // Only process clicks when it's the player's turn
     if !mouse_button.just_pressed(MouseButton::Left) {
         return;
     }
     
-    // Get the primary window and camera
-    let window = windows.single();
-    let (camera, camera_transform) = cameras.single();
-    
-    // Get the cursor position in world coordinates
-    if let Some(cursor_position) = window.cursor_position() {
-        let cursor_world_position = cursor_to_world_position(cursor_position, window, camera, camera_transform);
-        
-        // Find the closest board square to the click
-        let closest_square = find_closest_board_square(cursor_world_position, &board_squares);
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.get_single(), cameras.get_single()) else {
+        return;
+    };
+
+    if window.cursor_position().is_some() {
+        let clicked_square = hovered_square(window, camera, camera_transform, game_state.board_flipped);
         
-        if let Some((_, square)) = closest_square {
+        if let Some(square) = clicked_square {
             println!("Clicked on square: {:?}", square);
             
             // First, check if clicked on a valid move destination
-            let mut clicked_on_valid_move = false;
-            
-            for (_, valid_move) in valid_moves.iter() {
-                if valid_move.chess_move.to() == square {
-                    // Valid move selected - send event to make the move
-                    println!("Making move: {:?}", valid_move.chess_move);
-                    ev_make_move.send(MakeMoveEvent(valid_move.chess_move.clone()));
-                    clicked_on_valid_move = true;
-                    
-                    // Clear selection and valid moves
-                    clear_selection(&mut commands, &selected, &valid_moves, &selection_highlights);
-                    return;
+            if let Some((_, valid_move)) = valid_moves.iter().find(|(_, valid_move)| valid_move.chess_move.to() == square) {
+                let mut chess_move = valid_move.chess_move.clone();
+                if let Move::Normal { promotion: promotion @ Some(_), .. } = &mut chess_move {
+                    // Promotions go through the picker, unless the player is short on time
+                    // and would rather always get a queen
+                    let auto_queen = config.time_control.low_time.auto_queen
+                        && clock.low_time_level(game_state.current_player_turn) == Some(LowTimeLevel::Critical);
+                    *promotion = if auto_queen { Some(Role::Queen) } else { None };
                 }
+
+                // Valid move selected - send event to make the move
+                println!("Making move: {:?}", chess_move);
+                ev_make_move.send(MakeMoveEvent(chess_move));
+                
+                // Clear selection and valid moves
+                clear_selection(&mut commands, &selected, &valid_moves, &selection_highlights);
+                return;
             }
             
             // If we didn't click on a valid move, then we're either:
@@ -86,7 +197,7 @@ pub fn handle_piece_selection(
                     // This prevents the bug where clicking on a rook allows diagonal capture
                     if piece.role == Role::Rook {
                         // Check if king is in initial position
-                        let king_rank = if piece.color == ChessColor::White { Rank::First } else { Rank::Eighth };
+                        let king_rank = PlayerPerspective::new(piece.color).back_rank();
                         let king_file = File::E;
                         let king_square = Square::from_coords(king_file, king_rank);
                         
@@ -114,48 +225,8 @@ pub fn handle_piece_selection(
                         }
                     }
                     
-                    println!("Selected piece: {:?} {:?} at {:?}", piece.color, piece.role, piece.pos);
-                    
-                    // Mark this piece as selected
-                    commands.entity(entity).insert(SelectedPiece);
+                    select_piece(&mut commands, entity, piece, game_state, &legal_moves, &restriction, &board_squares, config.display.show_blocked_moves);
                     found_friendly_piece = true;
-                    
-                    // Calculate the piece's file and rank
-                    let file = piece.pos.file().char() as u8 - b'a';
-                    let rank = piece.pos.rank().char() as u8 - b'1';
-                    
-                    // Calculate highlight position based on board orientation
-                    let highlight_pos = calculate_highlight_position(
-                        file as usize,
-                        rank as usize,
-                        Z_HIGHLIGHT,
-                        game_state.board_flipped
-                    );
-                    
-                    // Spawn a highlight sprite for the selected piece
-                    commands.spawn((
-                        SpriteBundle {
-                            sprite: Sprite {
-                                color: SELECTED_COLOR,
-                                custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
-                                ..default()
-                            },
-                            transform: Transform::from_translation(highlight_pos),
-                            ..default()
-                        },
-                        PieceSelectionHighlight, // Use the new component for selection highlights
-                    ));
-                    
-                    // Find and display valid moves for this piece
-                    display_valid_moves(
-                        &mut commands, 
-                        &game_state,
-                        piece.pos, 
-                        piece.color, 
-                        piece.role,
-                        &board_squares,
-                    );
-                    
                     break;
                 }
             }
@@ -172,6 +243,136 @@ pub fn handle_piece_selection(
     }
 }
 
+/// System to select the pawn again after the player backed out of its promotion
+#[allow(clippy::too_many_arguments)]
+pub fn reselect_cancelled_promotion(
+    mut ev_cancelled: EventReader<PromotionCancelledEvent>,
+    mut commands: Commands,
+    pieces: Query<(Entity, &Piece, &Transform)>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    board_squares: Query<(&Transform, &BoardSquare)>,
+    legal_moves: Res<LegalMovesCache>,
+    restriction: Res<MoveRestriction>,
+    config: Res<GameConfig>,
+) {
+    let (Some(ev), Ok(game_state)) = (ev_cancelled.read().last(), boards.get_single()) else {
+        return;
+    };
+    if let Some((entity, piece, _)) = pieces.iter().find(|(_, piece, _)| piece.pos == ev.from) {
+        select_piece(&mut commands, entity, piece, game_state, &legal_moves, &restriction, &board_squares, config.display.show_blocked_moves);
+    }
+}
+
+// Helper function to mark a piece as selected, highlight it and show where it can move
+#[allow(clippy::too_many_arguments)]
+fn select_piece(
+    commands: &mut Commands,
+    entity: Entity,
+    piece: &Piece,
+    game_state: &GameState,
+    legal_moves: &LegalMovesCache,
+    restriction: &MoveRestriction,
+    board_squares: &Query<(&Transform, &BoardSquare)>,
+    show_blocked: bool,
+) {
+    println!("Selected piece: {:?} {:?} at {:?}", piece.color, piece.role, piece.pos);
+    
+    // Mark this piece as selected
+    commands.entity(entity).insert(SelectedPiece);
+    
+    // Calculate highlight position based on board orientation
+    let highlight_pos = world_pos(piece.pos, game_state.board_flipped).extend(Z_HIGHLIGHT);
+    
+    // Spawn a highlight sprite for the selected piece
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: SELECTED_COLOR,
+                custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(highlight_pos),
+            ..default()
+        },
+        PieceSelectionHighlight, // Use the new component for selection highlights
+    ));
+    
+    // Find and display valid moves for this piece
+    display_valid_moves(
+        commands,
+        game_state,
+        legal_moves,
+        restriction,
+        piece.pos,
+        piece.color,
+        piece.role,
+        board_squares,
+        show_blocked,
+    );
+}
+
+/// System to pick a piece from the reserve tray: shows the squares it can be dropped on,
+/// and the drop is then played by clicking one of them like any other move
+#[allow(clippy::too_many_arguments)]
+pub fn handle_reserve_selection(
+    mut commands: Commands,
+    mut mouse_buttons: ResMut<Input<MouseButton>>,
+    buttons: Query<(&Interaction, &ReserveButton), Changed<Interaction>>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    selected: Query<Entity, With<SelectedPiece>>,
+    valid_moves: Query<(Entity, &ValidMoveDestination)>,
+    selection_highlights: Query<Entity, With<PieceSelectionHighlight>>,
+    legal_moves: Res<LegalMovesCache>,
+    restriction: Res<MoveRestriction>,
+    config: Res<GameConfig>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    let Some(button) = buttons
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+        .map(|(_, button)| button)
+    else {
+        return;
+    };
+    // The click picked a piece from the tray, it mustn't also act on the board
+    mouse_buttons.reset(MouseButton::Left);
+
+    clear_selection(&mut commands, &selected, &valid_moves, &selection_highlights);
+    if button.color != game_state.current_player_turn {
+        return;
+    }
+    let is_drop = |chess_move: &&Move| matches!(chess_move, Move::Put { role, .. } if *role == button.role);
+    let drops = legal_moves
+        .moves()
+        .iter()
+        .filter(is_drop)
+        .filter(|chess_move| restriction.allows(chess_move));
+    let mut count = 0;
+    for chess_move in drops {
+        spawn_move_indicator(&mut commands, chess_move, chess_move.to(), game_state.board_flipped);
+        count += 1;
+    }
+    println!("Selected {:?} from the reserve: {} squares to drop on", button.role, count);
+
+    if config.display.show_blocked_moves {
+        for chess_move in legal_moves.blocked().iter().filter(is_drop) {
+            spawn_blocked_indicator(&mut commands, chess_move, game_state.board_flipped);
+        }
+    }
+}
+
+/// System to remove any selection highlight and move indicators
+pub fn clear_move_indicators(
+    mut commands: Commands,
+    selected: Query<Entity, With<SelectedPiece>>,
+    valid_moves: Query<(Entity, &ValidMoveDestination)>,
+    selection_highlights: Query<Entity, With<PieceSelectionHighlight>>,
+) {
+    clear_selection(&mut commands, &selected, &valid_moves, &selection_highlights);
+}
+
 // Helper function to clear current selection
 fn clear_selection(
     commands: &mut Commands,
@@ -197,84 +398,45 @@ fn clear_selection(
     }
 }
 
-// Helper function to convert cursor position to world coordinates
-fn cursor_to_world_position(
-    cursor_pos: Vec2,
-    _window: &Window,
-    camera: &Camera,
-    camera_transform: &GlobalTransform,
-) -> Vec2 {
-    // For 2D games with a Camera2dBundle, use viewport_to_world_2d
-    // This correctly handles the camera's view and projection
-    match camera.viewport_to_world_2d(camera_transform, cursor_pos) {
-        Some(world_pos) => {
-            println!("Converted screen ({:?}) to world ({:?})", cursor_pos, world_pos);
-            world_pos
-        }
-        None => {
-            println!("Failed to convert cursor position to world coordinates");
-            Vec2::ZERO
-        }
-    }
-}
-
-// Helper function to find the closest board square to a world position
-fn find_closest_board_square(
-    world_pos: Vec2,
-    board_squares: &Query<(&Transform, &BoardSquare)>,
-) -> Option<(Vec2, Square)> {
-    let mut closest_square = None;
-    let mut closest_distance = f32::MAX;
-    
-    for (transform, board_square) in board_squares.iter() {
-        let square_pos = transform.translation.truncate();
-        let distance = world_pos.distance_squared(square_pos);
-        
-        println!("Square: {:?} at position {:?}, distance: {}", board_square.square, square_pos, distance);
-        
-        if distance < closest_distance {
-            closest_distance = distance;
-            closest_square = Some((square_pos, board_square.square));
-        }
-    }
-    
-    closest_square
-}
+// Helper function to spawn the indicator for a move's destination square
+fn spawn_move_indicator(commands: &mut Commands, chess_move: &Move, square: Square, board_flipped: bool) {
+    let position = world_pos(square, board_flipped).extend(Z_LEGAL_MOVES);
 
-// Add a helper function to calculate visual positions based on board orientation
-fn calculate_highlight_position(file: usize, rank: usize, z: f32, board_flipped: bool) -> Vec3 {
-    if board_flipped {
-        // Flipped board (white at top)
-        Vec3::new(
-            (file as f32 - 3.5) * TILE_SIZE,
-            (rank as f32 - 3.5) * TILE_SIZE,
-            z
-        )
-    } else {
-        // Standard board (white at bottom)
-        Vec3::new(
-            (file as f32 - 3.5) * TILE_SIZE,
-            ((7 - rank) as f32 - 3.5) * TILE_SIZE,
-            z
-        )
-    }
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: LEGAL_MOVE_COLOR,
+                custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(position),
+            ..default()
+        },
+        ValidMoveDestination {
+            chess_move: chess_move.clone(),
+        },
+    ));
 }
 
 // Helper function to display valid moves for a selected piece
+#[allow(clippy::too_many_arguments)]
 fn display_valid_moves(
     commands: &mut Commands,
     game_state: &GameState,
+    legal_moves: &LegalMovesCache,
+    restriction: &MoveRestriction,
     from_square: Square,
     piece_color: ChessColor,
     piece_role: Role,
     board_squares: &Query<(&Transform, &BoardSquare)>,
+    show_blocked: bool,
 ) {
-    // Get all legal moves for the current game state
-    let legals = game_state.board.legal_moves();
+    // The side to move's moves, already filtered by its drawback
+    let legals = legal_moves.moves();
     println!("Found {} total legal moves", legals.len());
     
     // Debug output of all legal moves
-    for m in &legals {
+    for m in legals {
         if let Some(sq) = m.from() {
             println!("Legal move from: {:?} to {:?}", sq, m.to());
         } else {
@@ -284,7 +446,12 @@ fn display_valid_moves(
     
     // Filter moves to only those from the selected piece's square
     let mut valid_move_count = 0;
-    for chess_move in legals {
+    for chess_move in legals.iter().cloned() {
+        // Don't offer moves the tutorial won't accept
+        if !restriction.allows(&chess_move) {
+            continue;
+        }
+
         // Special handling for castling moves
         if piece_role == Role::King {
             if let Move::Castle { king, rook: _ } = chess_move {
@@ -296,37 +463,8 @@ fn display_valid_moves(
                     println!("Valid castling move: King from {:?} to {:?}", from_square, king_to);
                     
                     // Find the board square entity for the king's destination
-                    for (_, board_square) in board_squares.iter() {
-                        if board_square.square == king_to {
-                            // Calculate the file and rank for king's destination
-                            let file = king_to.file().char() as u8 - b'a';
-                            let rank = king_to.rank().char() as u8 - b'1';
-                            
-                            // Create highlight for king's destination square
-                            let position = calculate_highlight_position(
-                                file as usize,
-                                rank as usize,
-                                Z_LEGAL_MOVES,
-                                game_state.board_flipped
-                            );
-                            
-                            // Spawn a move indicator for the destination
-                            commands.spawn((
-                                SpriteBundle {
-                                    sprite: Sprite {
-                                        color: LEGAL_MOVE_COLOR,
-                                        custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
-                                        ..default()
-                                    },
-                                    transform: Transform::from_translation(position),
-                                    ..default()
-                                },
-                                ValidMoveDestination {
-                                    chess_move: chess_move.clone(),
-                                },
-                            ));
-                            break;
-                        }
+                    if board_squares.iter().any(|(_, board_square)| board_square.square == king_to) {
+                        spawn_move_indicator(commands, &chess_move, king_to, game_state.board_flipped);
                     }
                     continue;
                 }
@@ -342,47 +480,59 @@ fn display_valid_moves(
                          from_square, to_square, piece_color, piece_role);
                 
                 // Find the board square entity for the destination
-                for (_, board_square) in board_squares.iter() {
-                    if board_square.square == to_square {
-                        // Determine if this is a capture move
-                        let is_capture = match game_state.board.board().piece_at(to_square) {
-                            Some(_) => true,
-                            None => false
-                        };
-                        
-                        // Calculate file and rank for the destination square
-                        let file = to_square.file().char() as u8 - b'a';
-                        let rank = to_square.rank().char() as u8 - b'1';
-                        
-                        // Set up position for the indicator based on board orientation
-                        let position = calculate_highlight_position(
-                            file as usize, 
-                            rank as usize, 
-                            Z_LEGAL_MOVES,
-                            game_state.board_flipped
-                        );
-                        
-                        // Spawn the move indicator
-                        commands.spawn((
-                            SpriteBundle {
-                                sprite: Sprite {
-                                    color: LEGAL_MOVE_COLOR,
-                                    custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
-                                    ..default()
-                                },
-                                transform: Transform::from_translation(position),
-                                ..default()
-                            },
-                            ValidMoveDestination {
-                                chess_move: chess_move.clone(),
-                            },
-                        ));
-                        break;
-                    }
+                if board_squares.iter().any(|(_, board_square)| board_square.square == to_square) {
+                    spawn_move_indicator(commands, &chess_move, to_square, game_state.board_flipped);
                 }
             }
         }
     }
     
     println!("Found {} valid moves for selected piece", valid_move_count);
+
+    if show_blocked {
+        // Castling moves start on the king's square too, and go to `to()` like above
+        let reachable: Vec<Square> = legals.iter().filter(|m| m.from() == Some(from_square)).map(|m| m.to()).collect();
+        let blocked = legal_moves
+            .blocked()
+            .iter()
+            .filter(|m| m.from() == Some(from_square) && !reachable.contains(&m.to()));
+        for chess_move in blocked {
+            spawn_blocked_indicator(commands, chess_move, game_state.board_flipped);
+        }
+    }
+}
+
+// Helper function to spawn the greyed out, struck through indicator of a move the drawback removed
+fn spawn_blocked_indicator(commands: &mut Commands, chess_move: &Move, board_flipped: bool) {
+    let square = chess_move.to();
+    let position = world_pos(square, board_flipped).extend(Z_LEGAL_MOVES);
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: BLOCKED_MOVE_COLOR,
+                custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(position),
+            ..default()
+        },
+        BlockedMoveDestination {
+            chess_move: chess_move.clone(),
+        },
+        PieceSelectionHighlight,
+    )).with_children(|parent| {
+        // Strike the square out with a cross
+        for angle in [std::f32::consts::FRAC_PI_4, -std::f32::consts::FRAC_PI_4] {
+            parent.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: BLOCKED_MOVE_COLOR.with_a(0.9),
+                    custom_size: Some(Vec2::new(TILE_SIZE * 1.1, TILE_SIZE * 0.06)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 0.1).with_rotation(Quat::from_rotation_z(angle)),
+                ..default()
+            });
+        }
+    });
 }
\ No newline at end of file
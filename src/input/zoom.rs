@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+use crate::constants::BOARD_SIZE_PX;
+use super::cursor::cursor_world_position;
+
+// Camera scale limits: below 1 the board is enlarged, above 1 it shrinks
+const MIN_CAMERA_SCALE: f32 = 0.4;
+const MAX_CAMERA_SCALE: f32 = 1.5;
+// Scale change per wheel notch
+const ZOOM_STEP: f32 = 0.1;
+// Pixel scrolling (touchpads) counts this many pixels as one notch
+const PIXELS_PER_NOTCH: f32 = 100.0;
+
+/// Camera scale after scrolling `notches` (positive zooms in), within the limits
+pub fn zoomed_scale(scale: f32, notches: f32) -> f32 {
+    (scale * (1.0 - ZOOM_STEP).powf(notches)).clamp(MIN_CAMERA_SCALE, MAX_CAMERA_SCALE)
+}
+
+/// Camera position kept over the board, so some of it is always in view
+pub fn clamp_pan(position: Vec2) -> Vec2 {
+    position.clamp(Vec2::splat(-BOARD_SIZE_PX / 2.0), Vec2::splat(BOARD_SIZE_PX / 2.0))
+}
+
+/// System for zooming the board with Ctrl + mouse wheel (towards the cursor)
+/// and panning it by dragging with the middle or right mouse button.
+/// Ctrl+0 goes back to the default view. UI text isn't scaled with the board.
+pub fn zoom_and_pan_board(
+    keys: Res<Input<KeyCode>>,
+    mouse_button: Res<Input<MouseButton>>,
+    mut wheel: EventReader<MouseWheel>,
+    mut motion: EventReader<MouseMotion>,
+    windows: Query<&Window>,
+    mut cameras: Query<(&Camera, &GlobalTransform, &mut Transform, &mut OrthographicProjection)>,
+) {
+    let Ok((camera, camera_transform, mut transform, mut projection)) = cameras.get_single_mut() else {
+        return;
+    };
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+
+    if ctrl && keys.any_just_pressed([KeyCode::Key0, KeyCode::Numpad0]) {
+        projection.scale = 1.0;
+        transform.translation = Vec3::new(0.0, 0.0, transform.translation.z);
+        return;
+    }
+
+    let notches: f32 = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_NOTCH,
+        })
+        .sum();
+    let mut position = transform.translation.truncate();
+    if ctrl && notches != 0.0 {
+        let scale = zoomed_scale(projection.scale, notches);
+        // Keep the point under the cursor where it is
+        let anchor = windows
+            .get_single()
+            .ok()
+            .and_then(|window| cursor_world_position(window, camera, camera_transform))
+            .unwrap_or(position);
+        position = anchor + (position - anchor) * (scale / projection.scale);
+        projection.scale = scale;
+    }
+
+    let dragged: Vec2 = motion.read().map(|event| event.delta).sum();
+    if mouse_button.any_pressed([MouseButton::Middle, MouseButton::Right]) {
+        // Screen y points down, world y up
+        position += Vec2::new(-dragged.x, dragged.y) * projection.scale;
+    }
+
+    let position = clamp_pan(position);
+    if position != transform.translation.truncate() {
+        transform.translation = position.extend(transform.translation.z);
+    }
+}
@@ -0,0 +1,25 @@
+// The game is a library so the integration tests in `tests/` can build the App
+// from the same plugins as the binary (see main.rs)
+
+// --- Modules ---
+pub mod constants;
+pub mod game_logic;
+pub mod board;
+pub mod pieces;
+pub mod input;
+pub mod ai;
+pub mod ui;
+pub mod drawbacks; // Import the drawbacks module
+pub mod config; // Import the configuration module
+pub mod modes;
+pub mod stats;
+pub mod i18n;
+pub mod net;
+pub mod audio;
+#[cfg(feature = "local-api")]
+pub mod api;
+#[cfg(feature = "discord")]
+pub mod presence;
+#[cfg(feature = "egui")]
+pub mod egui_ui;
+// The images directory contains assets, not Rust code, so no need to import it as a module
@@ -1,34 +1,51 @@
 use bevy::prelude::*;
 
-// --- Modules ---
-mod constants;
-mod game_logic;
-mod board;
-mod pieces;
-mod input;
-mod ai;
-mod ui;
-mod drawbacks; // Import the drawbacks module
-mod config; // Import the configuration module
-// The images directory contains assets, not Rust code, so no need to import it as a module
-
 // Use module plugins
-use game_logic::plugin::GameLogicPlugin;
-use board::plugin::BoardPlugin;
-use pieces::plugin::PiecesPlugin;
-use input::plugin::InputPlugin;
-use ai::{AiPlugin, ZobristPlugin}; // Import Zobrist from AI
-use ui::plugin::UiPlugin;
-use drawbacks::DrawbacksPlugin; // Use the drawbacks plugin (registers rules)
-use config::ConfigPlugin; // Use the config plugin
+use drawback_chess::{ai, constants};
+use drawback_chess::game_logic::plugin::GameLogicPlugin;
+use drawback_chess::board::plugin::BoardPlugin;
+use drawback_chess::pieces::plugin::PiecesPlugin;
+use drawback_chess::input::plugin::InputPlugin;
+use drawback_chess::ai::{AiPlugin, ZobristPlugin}; // Import Zobrist from AI
+use drawback_chess::ui::plugin::UiPlugin;
+use drawback_chess::drawbacks::DrawbacksPlugin; // Use the drawbacks plugin (registers rules)
+use drawback_chess::config::ConfigPlugin; // Use the config plugin
+use drawback_chess::modes::ModesPlugin;
+use drawback_chess::stats::StatsPlugin;
+use drawback_chess::i18n::I18nPlugin;
+use drawback_chess::net::NetPlugin;
+use drawback_chess::audio::SoundEffectsPlugin;
+#[cfg(feature = "local-api")]
+use drawback_chess::api;
+#[cfg(feature = "discord")]
+use drawback_chess::presence;
+#[cfg(feature = "egui")]
+use drawback_chess::egui_ui;
 
 fn main() {
+    // `--bench-eval` times the AI evaluation and exits without opening a window
+    if std::env::args().any(|arg| arg == ai::bench::BENCH_FLAG) {
+        ai::bench::run_evaluation_benchmark();
+        return;
+    }
+    // `--analyze-pgns <folder> [report.csv]` writes an accuracy report of every game in the folder
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(position) = args.iter().position(|arg| arg == ai::batch::BATCH_FLAG) {
+        let succeeded = ai::batch::run_batch_analysis(&args[position + 1..]);
+        std::process::exit(if succeeded { 0 } else { 1 });
+    }
+    // `--sprt <a.json> <b.json> [max games] [elo0] [elo1]` plays two AI configurations against each other
+    if let Some(position) = args.iter().position(|arg| arg == ai::sprt::SPRT_FLAG) {
+        let succeeded = ai::sprt::run_sprt_test(&args[position + 1..]);
+        std::process::exit(if succeeded { 0 } else { 1 });
+    }
+
     // Make sure the window is large enough to show the entire board
     let window_width = constants::BOARD_SIZE_PX;
     let window_height = constants::BOARD_SIZE_PX;
 
-    App::new()
-        .insert_resource(ClearColor(Color::rgb(0.1, 0.1, 0.1))) // Dark background
+    let mut app = App::new();
+    app.insert_resource(ClearColor(Color::rgb(0.1, 0.1, 0.1))) // Dark background
         .add_plugins(DefaultPlugins.set(WindowPlugin {
              primary_window: Some(Window {
                  title: "Drawback Chess".into(),
@@ -47,6 +64,8 @@ fn main() {
         .add_plugins(ConfigPlugin)
         // 2. Register Drawback Rules (Needed by GameState/Logic)
         .add_plugins(DrawbacksPlugin)
+        // Translated user-facing strings (after the configuration picks the language)
+        .add_plugins(I18nPlugin)
         // 3. Core Logic (incl. GameState, Events)
         .add_plugins(GameLogicPlugin) // Initializes GameState, schedules apply_move
         // 4. Zobrist Hashing (after GameState)
@@ -60,5 +79,23 @@ fn main() {
         .add_plugins(InputPlugin)
         // 8. AI Logic (Needs GameState, DrawbackRegistry)
         .add_plugins(AiPlugin)
-        .run();
+        // 9. Game modes (daily challenge) and local statistics
+        .add_plugins(ModesPlugin)
+        .add_plugins(StatsPlugin)
+        // Games against another machine (--host / --join)
+        .add_plugins(NetPlugin)
+        // Sound effects for moves, captures, checks and the end of the game
+        .add_plugins(SoundEffectsPlugin);
+
+    // 10. Local API for scripts and bots (feature-gated, started with --api)
+    #[cfg(feature = "local-api")]
+    app.add_plugins(api::LocalApiPlugin);
+    // 11. Discord Rich Presence (feature-gated, enabled in the config)
+    #[cfg(feature = "discord")]
+    app.add_plugins(presence::PresencePlugin);
+    // 12. egui windows for settings, drawbacks, analysis and the console (feature-gated, F1)
+    #[cfg(feature = "egui")]
+    app.add_plugins(egui_ui::EguiUiPlugin);
+
+    app.run();
 } 
\ No newline at end of file
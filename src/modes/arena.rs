@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use shakmaty::Color as ChessColor;
+use crate::config::{GameConfig, PlayerSettings, DrawbackSetting, ArenaDrawbacks};
+use crate::drawbacks::{DrawbackRegistry, DrawbackParams};
+use crate::game_logic::events::{GameOverEvent, NewGameEvent};
+use crate::game_logic::rng::GameRng;
+use crate::modes::daily::today_utc;
+use crate::stats::store::{PlayerStats, PlayerResult, ArenaMatchRecord, STATS_FILE_PATH};
+
+/// Resource with the state of the best-of-N match (if arena mode is on).
+/// Player 1 is the player configured as White when the match started.
+#[derive(Resource, Debug, Default)]
+pub struct ArenaMatch {
+    pub players: Vec<PlayerSettings>, // Player 1 and 2 as configured at the start, empty = no match
+    pub scores: [f32; 2],             // Points of player 1 and 2, a draw is worth half a point
+    pub results: Vec<PlayerResult>,   // Every finished game from player 1's side
+    pub player_one_white: bool,       // Whether player 1 has White in the current game
+    pub finished: bool,
+    pub next_game_timer: Option<Timer>,
+}
+
+impl ArenaMatch {
+    pub fn is_active(&self) -> bool {
+        !self.players.is_empty()
+    }
+
+    /// Number of the game being played (or the last one, once the match is over)
+    pub fn game_number(&self) -> usize {
+        if self.finished { self.results.len() } else { self.results.len() + 1 }
+    }
+
+    /// Index (0 or 1) of the player with `color` in the current game
+    pub fn player_with(&self, color: ChessColor) -> usize {
+        if (color == ChessColor::White) == self.player_one_white { 0 } else { 1 }
+    }
+
+    /// Whether the match is decided after `best_of` games at most: either all
+    /// games are played or the trailing player can't catch up anymore
+    fn is_decided(&self, best_of: u32) -> bool {
+        let remaining = best_of.saturating_sub(self.results.len() as u32) as f32;
+        remaining == 0.0 || (self.scores[0] - self.scores[1]).abs() > remaining
+    }
+}
+
+/// "Human" or "AI", as players are recorded in the stats
+pub fn player_kind(player: &PlayerSettings) -> &'static str {
+    if player.is_ai { "AI" } else { "Human" }
+}
+
+/// Startup system: takes the configured players as the two sides of the match
+pub fn start_arena_match(config: Res<GameConfig>, mut arena: ResMut<ArenaMatch>) {
+    if !config.arena.enabled {
+        return;
+    }
+
+    *arena = ArenaMatch {
+        players: vec![config.white_player.clone(), config.black_player.clone()],
+        player_one_white: true,
+        ..default()
+    };
+    println!("Arena: best of {} between {} and {}", config.arena.games, player_kind(&arena.players[0]), player_kind(&arena.players[1]));
+}
+
+/// System to score a finished game and either end the match or start the countdown to the next game
+pub fn record_arena_game(
+    mut ev_game_over: EventReader<GameOverEvent>,
+    config: Res<GameConfig>,
+    mut arena: ResMut<ArenaMatch>,
+    mut stats: ResMut<PlayerStats>,
+) {
+    for ev in ev_game_over.read() {
+        if !arena.is_active() || arena.finished {
+            continue;
+        }
+
+        let result = match ev.0.winner {
+            Some(winner) if arena.player_with(winner) == 0 => PlayerResult::Win,
+            Some(_) => PlayerResult::Loss,
+            None => PlayerResult::Draw,
+        };
+        let points = match result {
+            PlayerResult::Win => [1.0, 0.0],
+            PlayerResult::Loss => [0.0, 1.0],
+            PlayerResult::Draw => [0.5, 0.5],
+        };
+        arena.scores[0] += points[0];
+        arena.scores[1] += points[1];
+        arena.results.push(result);
+        println!("Arena: game {} {:?} for player 1 ({}), score {} - {}", arena.results.len(), result, ev.0.reason, arena.scores[0], arena.scores[1]);
+
+        if !arena.is_decided(config.arena.games) {
+            arena.next_game_timer = Some(Timer::from_seconds(config.arena.delay_secs.max(0.0), TimerMode::Once));
+            continue;
+        }
+
+        arena.finished = true;
+        arena.next_game_timer = None;
+        println!("Arena: match over, {} - {}", arena.scores[0], arena.scores[1]);
+        stats.arena_matches.push(ArenaMatchRecord {
+            date: today_utc(),
+            best_of: config.arena.games,
+            player_one: player_kind(&arena.players[0]).to_string(),
+            player_two: player_kind(&arena.players[1]).to_string(),
+            score_one: arena.scores[0],
+            score_two: arena.scores[1],
+            results: arena
+                .results
+                .iter()
+                .map(|result| match result {
+                    PlayerResult::Win => "1-0",
+                    PlayerResult::Loss => "0-1",
+                    PlayerResult::Draw => "1/2-1/2",
+                }.to_string())
+                .collect(),
+        });
+        if let Err(e) = stats.save(STATS_FILE_PATH) {
+            eprintln!("Failed to save {}: {}", STATS_FILE_PATH, e);
+        }
+    }
+}
+
+/// System to set up the players of the next game once the countdown is over and start it
+pub fn run_arena_match(
+    time: Res<Time>,
+    mut config: ResMut<GameConfig>,
+    registry: Res<DrawbackRegistry>,
+    mut rng: ResMut<GameRng>,
+    mut arena: ResMut<ArenaMatch>,
+    mut ev_new_game: EventWriter<NewGameEvent>,
+) {
+    let Some(timer) = arena.next_game_timer.as_mut() else {
+        return;
+    };
+    if !timer.tick(time.delta()).finished() {
+        return;
+    }
+    arena.next_game_timer = None;
+
+    if config.arena.alternate_colors {
+        arena.player_one_white = !arena.player_one_white;
+    }
+    let white = arena.players[arena.player_with(ChessColor::White)].clone();
+    let black = arena.players[arena.player_with(ChessColor::Black)].clone();
+    let config = &mut *config;
+    match config.arena.drawbacks {
+        ArenaDrawbacks::FollowPlayer => {
+            config.white_player = white;
+            config.black_player = black;
+        }
+        ArenaDrawbacks::FollowColor => {
+            config.white_player.is_ai = white.is_ai;
+            config.black_player.is_ai = black.is_ai;
+        }
+        ArenaDrawbacks::Random => {
+            config.white_player.is_ai = white.is_ai;
+            config.black_player.is_ai = black.is_ai;
+            let ids = registry.sorted_ids();
+            for player in [&mut config.white_player, &mut config.black_player] {
+                if let Some(id) = ids.choose(rng.session()) {
+                    player.drawback = DrawbackSetting {
+                        name: None,
+                        index: Some(id.to_key_index()),
+                        params: DrawbackParams::default(),
+                    };
+                }
+            }
+        }
+    }
+
+    println!("Arena: starting game {}", arena.game_number());
+    ev_new_game.send(NewGameEvent::default());
+}
+
+/// System to stop the countdown when a new game was started some other way
+pub fn cancel_arena_countdown(mut arena: ResMut<ArenaMatch>) {
+    arena.next_game_timer = None;
+}
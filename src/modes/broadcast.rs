@@ -0,0 +1,216 @@
+use bevy::prelude::*;
+use serde::Serialize;
+use shakmaty::{Color as ChessColor, EnPassantMode};
+use shakmaty::fen::Fen;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use crate::game_logic::events::{GameOverEvent, NewGameEvent};
+use crate::game_logic::history::MoveHistory;
+use crate::game_logic::notation::format_uci;
+use crate::game_logic::clock::GameClock;
+use crate::game_logic::state::{GameState, ActiveBoard, GameStatus};
+
+/// `--broadcast <path>` writes the live game as JSON to a file
+pub const BROADCAST_FILE_FLAG: &str = "--broadcast";
+/// `--broadcast-http <port>` serves the live game as JSON on 127.0.0.1
+pub const BROADCAST_HTTP_FLAG: &str = "--broadcast-http";
+
+// How often the frame is refreshed even if no move was played (for the clocks)
+const BROADCAST_INTERVAL_SECS: f32 = 1.0;
+
+/// One snapshot of the live game, as read by stream overlays
+#[derive(Serialize, Debug, Clone)]
+pub struct BroadcastFrame {
+    pub fen: String,
+    pub turn: &'static str,
+    pub ply: usize,
+    pub last_move: Option<BroadcastMove>,
+    pub status: &'static str,
+    pub result: Option<&'static str>, // PGN result once the game is over
+    pub reason: Option<String>,
+    pub clock: BroadcastClock,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct BroadcastMove {
+    pub uci: String,
+    pub san: String,
+}
+
+/// Time used so far by each side, in milliseconds, and the time left
+/// on their chess clocks when the game has a time control
+#[derive(Serialize, Debug, Clone, Copy, Default)]
+pub struct BroadcastClock {
+    pub white_ms: u64,
+    pub black_ms: u64,
+    pub running: bool,
+    pub white_remaining_ms: Option<u64>,
+    pub black_remaining_ms: Option<u64>,
+}
+
+/// Resource present while broadcasting. Drawbacks are deliberately left out
+/// of the frame so viewers can't spoil them for the player.
+#[derive(Resource)]
+pub struct BroadcastState {
+    pub file: Option<PathBuf>,
+    pub http_port: Option<u16>,
+    pub timer: Timer,
+    pub clock: BroadcastClock,
+    // Latest frame as JSON, shared with the HTTP thread
+    pub latest: Arc<Mutex<String>>,
+}
+
+impl BroadcastState {
+    /// Reads the broadcast flags, None if neither is given
+    pub fn from_args() -> Option<Self> {
+        let args: Vec<String> = std::env::args().collect();
+        let argument = |flag: &str| args.iter().position(|arg| arg == flag).and_then(|i| args.get(i + 1));
+
+        let file = argument(BROADCAST_FILE_FLAG).map(PathBuf::from);
+        let http_port = argument(BROADCAST_HTTP_FLAG).and_then(|port| match port.parse::<u16>() {
+            Ok(port) => Some(port),
+            Err(_) => {
+                eprintln!("Invalid broadcast port: {}", port);
+                None
+            }
+        });
+        if file.is_none() && http_port.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            file,
+            http_port,
+            timer: Timer::from_seconds(BROADCAST_INTERVAL_SECS, TimerMode::Repeating),
+            clock: BroadcastClock::default(),
+            latest: Arc::new(Mutex::new(String::from("{}"))),
+        })
+    }
+}
+
+/// Builds the frame for the current game
+pub fn broadcast_frame(game_state: &GameState, history: &MoveHistory, clock: BroadcastClock) -> BroadcastFrame {
+    let last_move = history.moves.last().map(|record| BroadcastMove {
+        uci: format_uci(&record.chess_move),
+        san: record.san.clone(),
+    });
+
+    BroadcastFrame {
+        fen: Fen::from_position(game_state.board.clone(), EnPassantMode::Legal).to_string(),
+        turn: match game_state.current_player_turn {
+            ChessColor::White => "white",
+            ChessColor::Black => "black",
+        },
+        ply: history.len(),
+        last_move,
+        status: match game_state.status {
+            GameStatus::Ongoing => "ongoing",
+            GameStatus::Finished(_) => "over",
+        },
+        result: game_state.status.result().map(|result| result.pgn_result()),
+        reason: game_state.status.result().map(|result| result.reason.to_string()),
+        clock,
+    }
+}
+
+/// Startup system: with `--broadcast-http`, serves the latest frame on a background thread
+pub fn start_broadcast_server(broadcast: Res<BroadcastState>) {
+    let Some(port) = broadcast.http_port else {
+        return;
+    };
+
+    let listener = match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Failed to start broadcast server on port {}: {}", port, e);
+            return;
+        }
+    };
+    println!("Broadcasting the game on http://127.0.0.1:{}/", port);
+
+    let latest = broadcast.latest.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            // Every request gets the current frame, whatever path it asked for
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request);
+            let body = latest.lock().map(|json| json.clone()).unwrap_or_default();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nAccess-Control-Allow-Origin: *\r\nCache-Control: no-store\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            if let Err(e) = stream.write_all(response.as_bytes()) {
+                eprintln!("Broadcast client error: {}", e);
+            }
+        }
+    });
+}
+
+/// System to refresh the broadcast frame after every move and once per interval
+pub fn update_broadcast(
+    time: Res<Time>,
+    mut broadcast: ResMut<BroadcastState>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    history: Res<MoveHistory>,
+    game_clock: Res<GameClock>,
+    mut ev_game_over: EventReader<GameOverEvent>,
+    mut ev_new_game: EventReader<NewGameEvent>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    let mut changed = history.is_changed();
+    if ev_new_game.read().count() > 0 {
+        broadcast.clock = BroadcastClock::default();
+        changed = true;
+    }
+    if ev_game_over.read().count() > 0 {
+        changed = true;
+    }
+
+    // Charge the elapsed time to the side to move while the game runs
+    let running = game_state.status == GameStatus::Ongoing;
+    broadcast.clock.running = running;
+    if running {
+        let elapsed = time.delta().as_millis() as u64;
+        match game_state.current_player_turn {
+            ChessColor::White => broadcast.clock.white_ms += elapsed,
+            ChessColor::Black => broadcast.clock.black_ms += elapsed,
+        }
+    }
+
+    let remaining = |color| game_clock.enabled.then(|| game_clock.remaining_ms(color));
+    broadcast.clock.white_remaining_ms = remaining(ChessColor::White);
+    broadcast.clock.black_remaining_ms = remaining(ChessColor::Black);
+
+    let ticked = broadcast.timer.tick(time.delta()).just_finished();
+    if !changed && !ticked {
+        return;
+    }
+
+    let frame = broadcast_frame(game_state, &history, broadcast.clock);
+    let json = match serde_json::to_string_pretty(&frame) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("Failed to serialize broadcast frame: {}", e);
+            return;
+        }
+    };
+
+    if let Some(path) = &broadcast.file {
+        // Write to a temporary file first so overlays never read half a frame
+        let temp_path = path.with_extension("tmp");
+        if let Err(e) = std::fs::write(&temp_path, &json).and_then(|_| std::fs::rename(&temp_path, path)) {
+            eprintln!("Failed to write broadcast file {}: {}", path.display(), e);
+        }
+    }
+    if let Ok(mut latest) = broadcast.latest.lock() {
+        *latest = json;
+    }
+}
@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::config::{GameConfig, DrawbackSetting};
+use crate::drawbacks::{DrawbackRegistry, DrawbackId, DrawbackParams};
+
+/// Command line flag that starts today's daily challenge
+pub const DAILY_FLAG: &str = "--daily";
+
+/// Resource present while playing the daily challenge.
+/// Everything random about the game is derived from the date, so everyone
+/// playing on the same (UTC) day gets the same matchup.
+#[derive(Resource, Debug, Clone)]
+pub struct DailyChallenge {
+    pub date: String, // YYYY-MM-DD (UTC)
+    pub seed: u64,
+    pub player_drawback: DrawbackId,   // The human plays White
+    pub opponent_drawback: DrawbackId, // The AI plays Black
+}
+
+impl DailyChallenge {
+    /// Builds the challenge for a given date
+    pub fn for_date(date: &str, registry: &DrawbackRegistry) -> Self {
+        let seed = date_seed(date);
+        let mut rng = SplitMix64(seed);
+
+        // HashMap order differs between runs, so pick from a sorted list
+        let mut drawbacks: Vec<DrawbackId> = registry.rules.keys().copied().collect();
+        drawbacks.sort_by_key(|id| id.to_key_index());
+
+        let mut pick = || {
+            if drawbacks.is_empty() {
+                DrawbackId::None
+            } else {
+                drawbacks[rng.below(drawbacks.len() as u64) as usize]
+            }
+        };
+        let player_drawback = pick();
+        let opponent_drawback = pick();
+
+        Self {
+            date: date.to_string(),
+            seed,
+            player_drawback,
+            opponent_drawback,
+        }
+    }
+}
+
+/// PreStartup system: with `--daily`, sets up today's challenge before the game state is created
+pub fn setup_daily_challenge(
+    mut commands: Commands,
+    mut config: ResMut<GameConfig>,
+    registry: Res<DrawbackRegistry>,
+) {
+    if !std::env::args().any(|arg| arg == DAILY_FLAG) {
+        return;
+    }
+
+    let challenge = DailyChallenge::for_date(&today_utc(), &registry);
+    println!(
+        "Daily challenge {}: you play White with {:?}, the AI has a secret drawback",
+        challenge.date, challenge.player_drawback
+    );
+
+    // Human (White) vs AI (Black) with the seeded drawbacks
+    config.white_player.is_ai = false;
+    config.white_player.drawback = DrawbackSetting {
+        name: None,
+        index: Some(challenge.player_drawback.to_key_index()),
+        params: DrawbackParams::default(),
+    };
+    config.black_player.is_ai = true;
+    config.black_player.drawback = DrawbackSetting {
+        name: None,
+        index: Some(challenge.opponent_drawback.to_key_index()),
+        params: DrawbackParams::default(),
+    };
+    // The AI's random choices must play out the same for everyone
+    config.rng_seed = Some(challenge.seed);
+
+    commands.insert_resource(challenge);
+}
+
+/// Today's date in UTC as YYYY-MM-DD
+pub fn today_utc() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// The calendar day before a YYYY-MM-DD date
+pub fn previous_date(date: &str) -> Option<String> {
+    let mut parts = date.split('-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    let (year, month, day) = civil_from_days(days_from_civil(year, month, day) - 1);
+    Some(format!("{:04}-{:02}-{:02}", year, month, day))
+}
+
+// Stable FNV-1a hash of the date; std's hashers aren't guaranteed to be
+// the same across Rust versions, which would split players into different challenges
+fn date_seed(date: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in date.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+// Small portable PRNG so the same seed gives the same picks on every platform and build
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}
+
+// Days since 1970-01-01 to (year, month, day), proleptic Gregorian calendar
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// (year, month, day) to days since 1970-01-01
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+use crate::config::{GameConfig, DrawbackSetting};
+
+/// `--kiosk` plays endless AI-vs-AI games for demo machines and screensavers
+pub const KIOSK_FLAG: &str = "--kiosk";
+
+// Time to look at a finished game before the next one starts
+const KIOSK_ROLLOVER_DELAY_SECS: f32 = 8.0;
+
+/// Resource present in kiosk mode
+#[derive(Resource, Debug, Default)]
+pub struct KioskMode;
+
+impl KioskMode {
+    pub fn from_args() -> Option<Self> {
+        std::env::args().any(|arg| arg == KIOSK_FLAG).then_some(Self)
+    }
+}
+
+/// PreStartup system: in kiosk mode both sides are played by the AI with a
+/// new random drawback every game, and the next game starts by itself. The
+/// debug overlays are turned off; the attract overlay shows the drawbacks.
+pub fn setup_kiosk(mut config: ResMut<GameConfig>) {
+    println!("Kiosk mode: AI vs AI with random drawbacks, games restart by themselves");
+    let config = &mut *config;
+    for player in [&mut config.white_player, &mut config.black_player] {
+        player.is_ai = true;
+        player.drawback = DrawbackSetting::random();
+    }
+    config.arena.enabled = false;
+    config.auto_rollover.enabled = true;
+    config.auto_rollover.delay_secs = KIOSK_ROLLOVER_DELAY_SECS;
+    // Random drawbacks are rolled with every game already
+    config.auto_rollover.reroll_drawbacks = false;
+    // Nobody wants a demo machine's games in their stats
+    config.auto_rollover.log_results = false;
+    config.display.show_ai_thinking = false;
+    config.display.show_ai_best_move_arrow = false;
+    config.display.show_ai_observer_arrows = false;
+}
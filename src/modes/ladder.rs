@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+use crate::config::{GameConfig, AiSettings, DrawbackSetting, FallbackPolicy, AiEngine, TtReplacement, default_tt_size_mb};
+use crate::drawbacks::{DrawbackId, DrawbackParams};
+use crate::game_logic::events::{GameOverEvent, GameOverReason, NewGameEvent};
+use crate::game_logic::state::AppState;
+use crate::stats::store::{PlayerStats, PlayerResult, STATS_FILE_PATH};
+use shakmaty::Color as ChessColor;
+
+/// Command line flag that opens the ladder screen at startup
+pub const LADDER_FLAG: &str = "--ladder";
+
+// Seconds the result stays on the board before going back to the ladder screen
+const RETURN_TO_LADDER_SECS: f32 = 3.0;
+
+/// One opponent on the ladder. The player always plays White.
+#[derive(Debug, Clone)]
+pub struct LadderRung {
+    pub name: &'static str,
+    pub key: &'static str, // Localization key of the name
+    pub player_drawback: DrawbackId,
+    pub opponent_drawback: DrawbackId,
+    pub ai_settings: AiSettings,
+}
+
+/// The ladder from the easiest to the hardest opponent: the player's drawback
+/// gets nastier, the AI loses its own handicap and searches longer
+pub fn ladder_rungs() -> Vec<LadderRung> {
+    let ai = |time_limit_ms: u32, depth_limit: u8| AiSettings {
+        iteration_limit: 100_000 * depth_limit as u32,
+        time_limit_ms,
+        depth_limit,
+        check_quietness: depth_limit > 4,
+        quiescence_depth: depth_limit / 2,
+        fallback_policy: FallbackPolicy::default(),
+        opponent_model: false,
+        engine: AiEngine::default(),
+        tt_size_mb: default_tt_size_mb(),
+        tt_replacement: TtReplacement::default(),
+    };
+
+    vec![
+        LadderRung {
+            name: "The Apprentice",
+            key: "ladder-rung-apprentice",
+            player_drawback: DrawbackId::None,
+            opponent_drawback: DrawbackId::PawnPushOneOnly,
+            ai_settings: ai(500, 2),
+        },
+        LadderRung {
+            name: "The Squire",
+            key: "ladder-rung-squire",
+            player_drawback: DrawbackId::NoCastling,
+            opponent_drawback: DrawbackId::NoCastling,
+            ai_settings: ai(1000, 4),
+        },
+        LadderRung {
+            name: "The Knight",
+            key: "ladder-rung-knight",
+            player_drawback: DrawbackId::NoCastling,
+            opponent_drawback: DrawbackId::None,
+            ai_settings: ai(1500, 8),
+        },
+        LadderRung {
+            name: "The Warden",
+            key: "ladder-rung-warden",
+            player_drawback: DrawbackId::PawnPushOneOnly,
+            opponent_drawback: DrawbackId::None,
+            ai_settings: ai(2000, 12),
+        },
+        LadderRung {
+            name: "The Grandmaster",
+            key: "ladder-rung-grandmaster",
+            player_drawback: DrawbackId::BlockRandomFile,
+            opponent_drawback: DrawbackId::None,
+            ai_settings: ai(3000, 24),
+        },
+    ]
+}
+
+/// How the last ladder game went, shown on the ladder screen
+#[derive(Debug, Clone)]
+pub struct LadderResult {
+    pub rung: usize,
+    pub result: PlayerResult,
+    pub reason: GameOverReason,
+}
+
+/// Resource tracking the ladder game in progress (if any)
+#[derive(Resource, Debug, Default)]
+pub struct LadderSession {
+    pub active_rung: Option<usize>,
+    pub last_result: Option<LadderResult>,
+    pub return_timer: Option<Timer>,
+}
+
+/// Startup system: `--ladder` opens the ladder screen instead of the default game
+pub fn open_ladder_on_startup(mut next_app_state: ResMut<NextState<AppState>>) {
+    if std::env::args().any(|arg| arg == LADDER_FLAG) {
+        next_app_state.set(AppState::Ladder);
+    }
+}
+
+/// Configures the players for a rung and starts the game
+pub fn start_ladder_game(
+    rung_index: usize,
+    config: &mut GameConfig,
+    session: &mut LadderSession,
+    ev_new_game: &mut EventWriter<NewGameEvent>,
+    next_app_state: &mut NextState<AppState>,
+) {
+    let rungs = ladder_rungs();
+    let Some(rung) = rungs.get(rung_index) else {
+        return;
+    };
+
+    println!("Ladder: playing rung {} ({})", rung_index + 1, rung.name);
+
+    config.white_player.is_ai = false;
+    config.white_player.drawback = DrawbackSetting {
+        name: None,
+        index: Some(rung.player_drawback.to_key_index()),
+        params: DrawbackParams::default(),
+    };
+    config.black_player.is_ai = true;
+    config.black_player.drawback = DrawbackSetting {
+        name: None,
+        index: Some(rung.opponent_drawback.to_key_index()),
+        params: DrawbackParams::default(),
+    };
+    config.ai_settings = rung.ai_settings.clone();
+
+    session.active_rung = Some(rung_index);
+    session.return_timer = None;
+    ev_new_game.send(NewGameEvent::default());
+    next_app_state.set(AppState::InGame);
+}
+
+/// System to record the result of a ladder game and unlock the next rung on a win
+pub fn record_ladder_result(
+    mut ev_game_over: EventReader<GameOverEvent>,
+    mut session: ResMut<LadderSession>,
+    mut stats: ResMut<PlayerStats>,
+) {
+    for ev in ev_game_over.read() {
+        let Some(rung_index) = session.active_rung.take() else {
+            continue;
+        };
+        let rung_name = ladder_rungs().get(rung_index).map(|rung| rung.name).unwrap_or("?");
+
+        let result = match ev.0.winner {
+            Some(ChessColor::White) => {
+                if stats.ladder.rungs_beaten == rung_index {
+                    stats.ladder.rungs_beaten += 1;
+                    if let Err(e) = stats.save(STATS_FILE_PATH) {
+                        eprintln!("Failed to save {}: {}", STATS_FILE_PATH, e);
+                    }
+                }
+                PlayerResult::Win
+            }
+            Some(_) => PlayerResult::Loss,
+            None => PlayerResult::Draw,
+        };
+
+        println!("Ladder: {:?} against {} ({})", result, rung_name, ev.0.reason);
+        session.last_result = Some(LadderResult {
+            rung: rung_index,
+            result,
+            reason: ev.0.reason,
+        });
+        session.return_timer = Some(Timer::from_seconds(RETURN_TO_LADDER_SECS, TimerMode::Once));
+    }
+}
+
+/// System to go back to the ladder screen shortly after a ladder game ended
+pub fn return_to_ladder(
+    time: Res<Time>,
+    mut session: ResMut<LadderSession>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    let Some(timer) = session.return_timer.as_mut() else {
+        return;
+    };
+
+    if timer.tick(time.delta()).finished() {
+        session.return_timer = None;
+        next_app_state.set(AppState::Ladder);
+    }
+}
@@ -0,0 +1,10 @@
+pub mod plugin;
+pub mod daily;
+pub mod ladder;
+pub mod tutorial;
+pub mod broadcast;
+pub mod rollover;
+pub mod arena;
+pub mod kiosk;
+
+pub use plugin::ModesPlugin;
@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+use crate::config::LoadSavedSettings;
+use crate::game_logic::plugin::start_new_game;
+use crate::game_logic::events::NewGameEvent;
+use crate::game_logic::state::{TutorialState, gameplay_active};
+use super::broadcast::{BroadcastState, start_broadcast_server, update_broadcast};
+use super::daily::setup_daily_challenge;
+use super::rollover::{AutoRollover, schedule_auto_rollover, run_auto_rollover, cancel_auto_rollover};
+use super::arena::{ArenaMatch, start_arena_match, record_arena_game, run_arena_match, cancel_arena_countdown};
+use super::ladder::{LadderSession, open_ladder_on_startup, record_ladder_result, return_to_ladder};
+use super::kiosk::{KioskMode, setup_kiosk};
+use super::tutorial::{TutorialSession, start_tutorial_on_startup, begin_tutorial, run_tutorial, end_tutorial};
+
+/// Plugin for the alternative ways to start a game (daily challenge, ladder, tutorial, ...)
+pub struct ModesPlugin;
+
+impl Plugin for ModesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LadderSession>()
+           .init_resource::<TutorialSession>()
+           .init_resource::<AutoRollover>()
+           .init_resource::<ArenaMatch>()
+           // PreStartup so the chosen drawbacks are in GameConfig before the game state is
+           // created, after the saved settings so the mode's own settings win
+           .add_systems(
+               PreStartup,
+               (setup_daily_challenge, setup_kiosk.run_if(resource_exists::<KioskMode>())).after(LoadSavedSettings)
+           )
+           .add_systems(Startup, (open_ladder_on_startup, start_tutorial_on_startup, start_arena_match))
+           .add_systems(Update, (record_ladder_result, return_to_ladder))
+           // Tutorial
+           .add_systems(OnEnter(TutorialState::Active), begin_tutorial)
+           .add_systems(OnExit(TutorialState::Active), end_tutorial)
+           .add_systems(
+               Update,
+               run_tutorial
+                   .after(start_new_game)
+                   .run_if(in_state(TutorialState::Active))
+           )
+           // Back to back AI-vs-AI games
+           .add_systems(
+               Update,
+               (
+                   cancel_auto_rollover.run_if(on_event::<NewGameEvent>()),
+                   schedule_auto_rollover,
+                   run_auto_rollover.run_if(gameplay_active),
+               )
+                   .chain()
+           )
+           // Best-of-N match between the two configured players
+           .add_systems(
+               Update,
+               (
+                   cancel_arena_countdown.run_if(on_event::<NewGameEvent>()),
+                   record_arena_game,
+                   run_arena_match.run_if(gameplay_active),
+               )
+                   .chain()
+           )
+           // Broadcast output for stream overlays
+           .add_systems(Startup, start_broadcast_server.run_if(resource_exists::<BroadcastState>()))
+           .add_systems(
+               Update,
+               update_broadcast
+                   .after(start_new_game)
+                   .run_if(resource_exists::<BroadcastState>().and_then(gameplay_active))
+           );
+
+        if let Some(broadcast) = BroadcastState::from_args() {
+            app.insert_resource(broadcast);
+        }
+        if let Some(kiosk) = KioskMode::from_args() {
+            app.insert_resource(kiosk);
+        }
+    }
+}
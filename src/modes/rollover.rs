@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use crate::game_logic::rng::GameRng;
+use crate::config::{GameConfig, DrawbackSetting};
+use crate::drawbacks::{DrawbackRegistry, DrawbackParams};
+use crate::game_logic::events::{GameOverEvent, NewGameEvent};
+use crate::game_logic::history::MoveHistory;
+use crate::game_logic::state::{GameState, ActiveBoard};
+use crate::modes::daily::today_utc;
+use crate::stats::plugin::drawback_name;
+use crate::stats::store::{PlayerStats, AiMatchRecord, STATS_FILE_PATH};
+
+/// Resource with the countdown to the next AI-vs-AI game
+#[derive(Resource, Debug, Default)]
+pub struct AutoRollover {
+    pub timer: Option<Timer>,
+}
+
+// Auto rollover only applies when nobody is at the board (an arena match starts its own games)
+fn rollover_active(config: &GameConfig) -> bool {
+    config.auto_rollover.enabled && !config.arena.enabled && config.white_player.is_ai && config.black_player.is_ai
+}
+
+/// System to log the result and start the countdown once an AI-vs-AI game is over
+pub fn schedule_auto_rollover(
+    mut ev_game_over: EventReader<GameOverEvent>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    history: Res<MoveHistory>,
+    config: Res<GameConfig>,
+    registry: Res<DrawbackRegistry>,
+    mut stats: ResMut<PlayerStats>,
+    mut rollover: ResMut<AutoRollover>,
+) {
+    let Some(ev) = ev_game_over.read().last() else {
+        return;
+    };
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    if !rollover_active(&config) {
+        return;
+    }
+
+    if config.auto_rollover.log_results {
+        let result = ev.0.pgn_result();
+        stats.ai_matches.push(AiMatchRecord {
+            date: today_utc(),
+            white_drawback: drawback_name(&registry, game_state.white_drawback),
+            black_drawback: drawback_name(&registry, game_state.black_drawback),
+            result: result.to_string(),
+            reason: ev.0.reason.to_string(),
+            plies: history.len(),
+        });
+        println!("AI match {} logged ({} games so far)", result, stats.ai_matches.len());
+        if let Err(e) = stats.save(STATS_FILE_PATH) {
+            eprintln!("Failed to save {}: {}", STATS_FILE_PATH, e);
+        }
+    }
+
+    println!("Next game starts in {}s", config.auto_rollover.delay_secs);
+    rollover.timer = Some(Timer::from_seconds(config.auto_rollover.delay_secs.max(0.0), TimerMode::Once));
+}
+
+/// System to start the next game once the countdown is over
+pub fn run_auto_rollover(
+    time: Res<Time>,
+    mut config: ResMut<GameConfig>,
+    registry: Res<DrawbackRegistry>,
+    mut rng: ResMut<GameRng>,
+    mut rollover: ResMut<AutoRollover>,
+    mut ev_new_game: EventWriter<NewGameEvent>,
+) {
+    let Some(timer) = rollover.timer.as_mut() else {
+        return;
+    };
+    if !timer.tick(time.delta()).finished() {
+        return;
+    }
+    rollover.timer = None;
+    // The config may have changed during the countdown (e.g. a human took over)
+    if !rollover_active(&config) {
+        return;
+    }
+
+    if config.auto_rollover.reroll_drawbacks {
+        reroll_drawbacks(&mut config, &registry, rng.session());
+    }
+    ev_new_game.send(NewGameEvent::default());
+}
+
+/// Gives both players a new random drawback for the next game
+pub fn reroll_drawbacks(config: &mut GameConfig, registry: &DrawbackRegistry, rng: &mut impl Rng) {
+    let ids = registry.sorted_ids();
+    for player in [&mut config.white_player, &mut config.black_player] {
+        if let Some(id) = ids.choose(rng) {
+            player.drawback = DrawbackSetting {
+                name: None,
+                index: Some(id.to_key_index()),
+                params: DrawbackParams::default(),
+            };
+        }
+    }
+}
+
+/// System to stop the countdown when a new game was started some other way
+pub fn cancel_auto_rollover(mut rollover: ResMut<AutoRollover>) {
+    rollover.timer = None;
+}
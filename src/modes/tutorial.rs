@@ -0,0 +1,319 @@
+use bevy::prelude::*;
+use shakmaty::{fen::Fen, CastlingMode, Chess, Color as ChessColor, Move, Position};
+use crate::config::{GameConfig, DrawbackSetting};
+use crate::drawbacks::{DrawbackRegistry, DrawbackId, DrawbackParams};
+use crate::game_logic::events::{MakeMoveEvent, NewGameEvent};
+use crate::game_logic::history::MoveHistory;
+use crate::game_logic::notation::parse_uci;
+use crate::game_logic::state::{TurnState, TutorialState, MoveRestriction};
+use super::ladder::LadderSession;
+
+/// Command line flag that starts the tutorial right away
+pub const TUTORIAL_FLAG: &str = "--tutorial";
+
+// The lessons, in the small script format documented at the top of the file
+const TUTORIAL_SCRIPT: &str = include_str!("tutorial.txt");
+
+/// One step of a lesson
+#[derive(Debug, Clone)]
+pub enum TutorialStep {
+    /// Explanation popup, the board is frozen until the player continues
+    Say(String),
+    /// The only move the player may make
+    Move(Move),
+    /// Scripted move by the opponent
+    Reply(Move),
+    /// The player has to guess the opponent's drawback
+    Guess(DrawbackId),
+}
+
+/// A scripted lesson: a position, the drawbacks and what happens on the board
+#[derive(Debug, Clone)]
+pub struct TutorialLesson {
+    pub title: String,
+    pub start_position: Chess,
+    pub white_drawback: DrawbackId,
+    pub black_drawback: DrawbackId,
+    pub steps: Vec<TutorialStep>,
+}
+
+/// Parses a tutorial script. Moves are checked against the position as the
+/// script plays out, so a broken script is reported instead of getting stuck.
+pub fn parse_tutorial_script(text: &str, registry: &DrawbackRegistry) -> Result<Vec<TutorialLesson>, String> {
+    let mut lessons: Vec<TutorialLesson> = Vec::new();
+    // Position reached by the moves of the lesson being parsed
+    let mut position = Chess::default();
+
+    for (index, raw_line) in text.lines().enumerate() {
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |message: String| format!("line {}: {}", index + 1, message);
+
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+        let argument = argument.trim();
+
+        if command == "lesson" {
+            lessons.push(TutorialLesson {
+                title: argument.to_string(),
+                start_position: Chess::default(),
+                white_drawback: DrawbackId::None,
+                black_drawback: DrawbackId::None,
+                steps: Vec::new(),
+            });
+            position = Chess::default();
+            continue;
+        }
+
+        let Some(lesson) = lessons.last_mut() else {
+            return Err(error(format!("'{}' before the first lesson", command)));
+        };
+
+        match command {
+            "position" => {
+                if !lesson.steps.is_empty() {
+                    return Err(error("position must come before the lesson's steps".to_string()));
+                }
+                lesson.start_position = if argument == "start" {
+                    Chess::default()
+                } else {
+                    Fen::from_ascii(argument.as_bytes())
+                        .map_err(|e| error(e.to_string()))?
+                        .into_position(CastlingMode::Standard)
+                        .map_err(|e| error(e.to_string()))?
+                };
+                position = lesson.start_position.clone();
+            }
+            "drawbacks" => {
+                let (white, black) = argument
+                    .split_once('/')
+                    .ok_or_else(|| error("expected '<white> / <black>'".to_string()))?;
+                lesson.white_drawback = drawback_by_name(white.trim(), registry).ok_or_else(|| error(format!("unknown drawback '{}'", white.trim())))?;
+                lesson.black_drawback = drawback_by_name(black.trim(), registry).ok_or_else(|| error(format!("unknown drawback '{}'", black.trim())))?;
+            }
+            "say" => lesson.steps.push(TutorialStep::Say(argument.to_string())),
+            "move" | "reply" => {
+                let mover = if command == "move" { ChessColor::White } else { ChessColor::Black };
+                if position.turn() != mover {
+                    return Err(error(format!("'{}' but it is {:?}'s turn", command, position.turn())));
+                }
+                let chess_move = parse_uci(&position, argument)
+                    .map_err(|_| error(format!("'{}' is not a legal move here", argument)))?;
+                position.play_unchecked(&chess_move);
+                lesson.steps.push(if command == "move" {
+                    TutorialStep::Move(chess_move)
+                } else {
+                    TutorialStep::Reply(chess_move)
+                });
+            }
+            "guess" => {
+                let answer = drawback_by_name(argument, registry).ok_or_else(|| error(format!("unknown drawback '{}'", argument)))?;
+                lesson.steps.push(TutorialStep::Guess(answer));
+            }
+            _ => return Err(error(format!("unknown command '{}'", command))),
+        }
+    }
+
+    if lessons.is_empty() {
+        return Err("the script has no lessons".to_string());
+    }
+    Ok(lessons)
+}
+
+// Helper function to look a drawback up by its display name ("None" for no drawback)
+fn drawback_by_name(name: &str, registry: &DrawbackRegistry) -> Option<DrawbackId> {
+    if name.eq_ignore_ascii_case("none") {
+        return Some(DrawbackId::None);
+    }
+    registry.rules.values().find(|rule| rule.name() == name).map(|rule| rule.id())
+}
+
+/// Answer the player gave to a guess step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GuessAnswer {
+    pub guessed: DrawbackId,
+    pub correct: bool,
+}
+
+/// Resource tracking progress through the tutorial
+#[derive(Resource, Default)]
+pub struct TutorialSession {
+    pub lessons: Vec<TutorialLesson>,
+    pub lesson: usize,
+    pub step: usize,
+    pub guess_answer: Option<GuessAnswer>,
+    // Plies the lesson has played so far, to notice when a scripted move landed
+    plies_played: usize,
+    reply_sent: bool,
+    // Configuration from before the tutorial, restored when it ends
+    saved_config: Option<GameConfig>,
+}
+
+impl TutorialSession {
+    pub fn current_lesson(&self) -> Option<&TutorialLesson> {
+        self.lessons.get(self.lesson)
+    }
+
+    pub fn current_step(&self) -> Option<&TutorialStep> {
+        self.current_lesson().and_then(|lesson| lesson.steps.get(self.step))
+    }
+
+    /// Moves on to the next step (popups call this when the player continues)
+    pub fn advance(&mut self) {
+        self.step += 1;
+        self.guess_answer = None;
+        self.reply_sent = false;
+    }
+
+    /// Records the player's guess for the current guess step
+    pub fn answer_guess(&mut self, guessed: DrawbackId) {
+        if let Some(TutorialStep::Guess(answer)) = self.current_step() {
+            let correct = guessed == *answer;
+            self.guess_answer = Some(GuessAnswer { guessed, correct });
+        }
+    }
+}
+
+/// Startup system: `--tutorial` starts the tutorial instead of a normal game
+pub fn start_tutorial_on_startup(mut next_tutorial_state: ResMut<NextState<TutorialState>>) {
+    if std::env::args().any(|arg| arg == TUTORIAL_FLAG) {
+        next_tutorial_state.set(TutorialState::Active);
+    }
+}
+
+/// Loads the lessons and starts the first one
+pub fn begin_tutorial(
+    mut session: ResMut<TutorialSession>,
+    mut config: ResMut<GameConfig>,
+    mut ladder: ResMut<LadderSession>,
+    registry: Res<DrawbackRegistry>,
+    mut ev_new_game: EventWriter<NewGameEvent>,
+    mut next_tutorial_state: ResMut<NextState<TutorialState>>,
+) {
+    let lessons = match parse_tutorial_script(TUTORIAL_SCRIPT, &registry) {
+        Ok(lessons) => lessons,
+        Err(e) => {
+            eprintln!("Failed to load the tutorial script: {}", e);
+            next_tutorial_state.set(TutorialState::Off);
+            return;
+        }
+    };
+
+    println!("Starting the tutorial ({} lessons)", lessons.len());
+    // The tutorial replaces whatever game was going on, ladder games included
+    ladder.active_rung = None;
+    *session = TutorialSession {
+        lessons,
+        saved_config: Some(config.clone()),
+        ..default()
+    };
+    start_lesson(0, &mut session, &mut config, &mut ev_new_game);
+}
+
+// Helper function to set up the board and drawbacks for a lesson
+fn start_lesson(
+    index: usize,
+    session: &mut TutorialSession,
+    config: &mut GameConfig,
+    ev_new_game: &mut EventWriter<NewGameEvent>,
+) {
+    let Some(lesson) = session.lessons.get(index) else {
+        return;
+    };
+    println!("Tutorial lesson {}: {}", index + 1, lesson.title);
+
+    // The player is White; Black's moves all come from the script
+    config.white_player.is_ai = false;
+    config.white_player.drawback = DrawbackSetting {
+        name: None,
+        index: Some(lesson.white_drawback.to_key_index()),
+        params: DrawbackParams::default(),
+    };
+    config.black_player.is_ai = false;
+    config.black_player.drawback = DrawbackSetting {
+        name: None,
+        index: Some(lesson.black_drawback.to_key_index()),
+        params: DrawbackParams::default(),
+    };
+
+    ev_new_game.send(NewGameEvent {
+        start_position: Some(lesson.start_position.clone()),
+    });
+
+    session.lesson = index;
+    session.step = 0;
+    session.plies_played = 0;
+    session.guess_answer = None;
+    session.reply_sent = false;
+}
+
+/// System driving the current lesson: frees exactly the scripted move,
+/// plays the opponent's replies and moves on once a move has landed.
+/// Runs after `start_new_game`, so the history always belongs to the current lesson.
+#[allow(clippy::too_many_arguments)]
+pub fn run_tutorial(
+    mut session: ResMut<TutorialSession>,
+    mut config: ResMut<GameConfig>,
+    mut restriction: ResMut<MoveRestriction>,
+    history: Res<MoveHistory>,
+    turn_state: Res<State<TurnState>>,
+    mut ev_make_move: EventWriter<MakeMoveEvent>,
+    mut ev_new_game: EventWriter<NewGameEvent>,
+    mut next_tutorial_state: ResMut<NextState<TutorialState>>,
+) {
+    if session.current_lesson().is_none() {
+        return;
+    }
+
+    let Some(step) = session.current_step().cloned() else {
+        // Lesson finished: on to the next one, or back to normal play
+        let next_lesson = session.lesson + 1;
+        if next_lesson < session.lessons.len() {
+            start_lesson(next_lesson, &mut session, &mut config, &mut ev_new_game);
+        } else {
+            println!("Tutorial finished");
+            next_tutorial_state.set(TutorialState::Off);
+        }
+        return;
+    };
+
+    match step {
+        TutorialStep::Say(_) | TutorialStep::Guess(_) => {
+            restriction.set_if_neq(MoveRestriction::Frozen);
+        }
+        TutorialStep::Move(ref chess_move) | TutorialStep::Reply(ref chess_move) => {
+            if history.len() > session.plies_played {
+                session.plies_played = history.len();
+                session.advance();
+                return;
+            }
+            restriction.set_if_neq(MoveRestriction::Only(chess_move.clone()));
+
+            if matches!(step, TutorialStep::Reply(_))
+                && !session.reply_sent
+                && *turn_state.get() == TurnState::AiTurn
+            {
+                ev_make_move.send(MakeMoveEvent(chess_move.clone()));
+                session.reply_sent = true;
+            }
+        }
+    }
+}
+
+/// Restores the configuration from before the tutorial and starts a normal game
+pub fn end_tutorial(
+    mut session: ResMut<TutorialSession>,
+    mut config: ResMut<GameConfig>,
+    mut restriction: ResMut<MoveRestriction>,
+    mut ev_new_game: EventWriter<NewGameEvent>,
+) {
+    if let Some(mut saved_config) = session.saved_config.take() {
+        // Display settings changed during the tutorial (e.g. the language) are kept
+        saved_config.display = config.display.clone();
+        *config = saved_config;
+    }
+    *session = TutorialSession::default();
+    *restriction = MoveRestriction::Free;
+    ev_new_game.send(NewGameEvent::default());
+}
@@ -0,0 +1,99 @@
+use std::collections::VecDeque;
+use bevy::prelude::*;
+use shakmaty::Color as ChessColor;
+use crate::config::GameConfig;
+use crate::game_logic::history::MoveHistory;
+use super::lockstep::NetSession;
+
+// Lines the chat remembers, and the longest line that can be sent
+const MAX_CHAT_LINES: usize = 50;
+pub const MAX_CHAT_LENGTH: usize = 200;
+
+/// Event sent to say something to the other player
+#[derive(Debug, Clone)]
+pub struct SendChatEvent(pub String);
+
+impl Event for SendChatEvent {}
+
+/// Event sent for every chat line, ours once it went out and the other player's as it arrives
+#[derive(Debug, Clone)]
+pub struct ChatEvent {
+    pub from: ChessColor, // Side of the player who wrote it
+    pub text: String,
+}
+
+impl Event for ChatEvent {}
+
+/// One line of the chat
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatLine {
+    pub from: ChessColor,
+    pub text: String,
+}
+
+/// Resource with the chat of a network game. Muting hides what the other
+/// player writes; our own lines still go out.
+#[derive(Resource, Debug, Default)]
+pub struct ChatLog {
+    lines: VecDeque<ChatLine>,
+    pub muted: bool,
+}
+
+impl ChatLog {
+    /// Adds a line, dropping the oldest ones past `MAX_CHAT_LINES`
+    pub fn add(&mut self, line: ChatLine) {
+        if self.lines.len() == MAX_CHAT_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+
+    pub fn lines(&self) -> impl DoubleEndedIterator<Item = &ChatLine> {
+        self.lines.iter()
+    }
+}
+
+/// System sending what the player typed or the emotes they picked
+pub fn send_chat_messages(
+    session: Res<NetSession>,
+    mut ev_send: EventReader<SendChatEvent>,
+    mut ev_chat: EventWriter<ChatEvent>,
+) {
+    for SendChatEvent(text) in ev_send.read() {
+        let text: String = text.trim().chars().take(MAX_CHAT_LENGTH).collect();
+        if text.is_empty() {
+            continue;
+        }
+        session.send_chat(text.clone());
+        ev_chat.send(ChatEvent { from: session.local_color, text });
+    }
+}
+
+/// System adding chat lines to the log and, if the player opted in, to the
+/// comment of the last move so they end up in the PGN. Lines written before
+/// the first move have no move to go with and are only shown.
+pub fn record_chat(
+    mut ev_chat: EventReader<ChatEvent>,
+    mut log: ResMut<ChatLog>,
+    mut history: ResMut<MoveHistory>,
+    session: Res<NetSession>,
+    config: Res<GameConfig>,
+) {
+    for ChatEvent { from, text } in ev_chat.read() {
+        if log.muted && *from != session.local_color {
+            continue;
+        }
+        log.add(ChatLine { from: *from, text: text.clone() });
+
+        if !config.network.chat_in_pgn {
+            continue;
+        }
+        if let Some(record) = history.moves.last_mut() {
+            let line = format!("{:?}: {}", from, text);
+            record.comment = Some(match record.comment.take() {
+                Some(comment) => format!("{} {}", comment, line),
+                None => line,
+            });
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::Duration;
+use super::protocol::NetMessage;
+
+/// Where the connection comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetRole {
+    Host { port: u16 },         // Waits for the other player on this port
+    Client { address: String }, // Connects to a host ("host:port")
+}
+
+/// What happened on the connection since the last frame
+#[derive(Debug)]
+pub enum LinkEvent {
+    Connected,
+    Received(NetMessage),
+    Closed(String), // Why the connection ended (or couldn't be made)
+}
+
+/// Connection to the other player. The socket lives on background threads;
+/// the game reads its events and queues messages without ever blocking.
+/// A lost connection is made again: the host takes the next player who
+/// connects, the client keeps trying until the link is closed.
+pub struct Link {
+    events: Mutex<Receiver<LinkEvent>>,
+    outgoing: Sender<NetMessage>,
+    current: Arc<Mutex<Option<TcpStream>>>, // Connection messages go to, if any
+    closed: Arc<AtomicBool>,
+}
+
+impl Link {
+    /// Starts listening or connecting in the background; the client waits
+    /// `retry_interval` between attempts
+    pub fn open(role: NetRole, retry_interval: Duration) -> Self {
+        let (event_sender, events) = channel();
+        let (outgoing, messages) = channel::<NetMessage>();
+        let current: Arc<Mutex<Option<TcpStream>>> = Arc::new(Mutex::new(None));
+        let closed = Arc::new(AtomicBool::new(false));
+
+        // Messages sent while nobody is connected are dropped; the host's game
+        // has them and sends what was missed once the other side is back
+        let writer = current.clone();
+        std::thread::spawn(move || {
+            for message in messages {
+                let Ok(line) = serde_json::to_string(&message) else {
+                    continue;
+                };
+                if let Ok(mut stream) = writer.lock() {
+                    if let Some(stream) = stream.as_mut() {
+                        let _ = writeln!(stream, "{}", line).and_then(|_| stream.flush());
+                    }
+                }
+            }
+        });
+
+        let (connection, stop) = (current.clone(), closed.clone());
+        std::thread::spawn(move || match role {
+            NetRole::Host { port } => {
+                let listener = match TcpListener::bind(("0.0.0.0", port)) {
+                    Ok(listener) => listener,
+                    Err(e) => {
+                        let _ = event_sender.send(LinkEvent::Closed(e.to_string()));
+                        return;
+                    }
+                };
+                println!("Waiting for the other player on port {}", port);
+                for stream in listener.incoming() {
+                    if stop.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    match stream {
+                        Ok(stream) => run_connection(stream, &connection, &event_sender),
+                        Err(e) => eprintln!("Failed to accept a connection: {}", e),
+                    }
+                }
+            }
+            NetRole::Client { address } => {
+                while !stop.load(Ordering::Relaxed) {
+                    match TcpStream::connect(address.as_str()) {
+                        Ok(stream) => run_connection(stream, &connection, &event_sender),
+                        Err(e) => {
+                            if event_sender.send(LinkEvent::Closed(e.to_string())).is_err() {
+                                return; // The game is gone
+                            }
+                        }
+                    }
+                    std::thread::sleep(retry_interval);
+                }
+            }
+        });
+
+        Self { events: Mutex::new(events), outgoing, current, closed }
+    }
+
+    /// Two links connected to each other in memory, for tests
+    pub fn pair() -> (Self, Self) {
+        let (first_sender, first_events) = channel();
+        let (second_sender, second_events) = channel();
+        let (first_outgoing, first_messages) = channel::<NetMessage>();
+        let (second_outgoing, second_messages) = channel::<NetMessage>();
+        for (messages, peer) in [(first_messages, second_sender.clone()), (second_messages, first_sender.clone())] {
+            std::thread::spawn(move || {
+                for message in messages {
+                    if peer.send(LinkEvent::Received(message)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        let _ = first_sender.send(LinkEvent::Connected);
+        let _ = second_sender.send(LinkEvent::Connected);
+        let link = |events, outgoing| Self {
+            events: Mutex::new(events),
+            outgoing,
+            current: Arc::new(Mutex::new(None)),
+            closed: Arc::new(AtomicBool::new(false)),
+        };
+        (link(first_events, first_outgoing), link(second_events, second_outgoing))
+    }
+
+    /// Events that arrived since the last call
+    pub fn poll(&self) -> Vec<LinkEvent> {
+        self.events.lock().map(|events| events.try_iter().collect()).unwrap_or_default()
+    }
+
+    pub fn send(&self, message: NetMessage) {
+        // A closed connection is reported through `poll`
+        let _ = self.outgoing.send(message);
+    }
+
+    /// Hangs up and stops connecting again
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+        if let Ok(mut stream) = self.current.lock() {
+            if let Some(stream) = stream.take() {
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+        }
+    }
+}
+
+impl Drop for Link {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+// Makes `stream` the connection messages are written to and reads incoming
+// messages until it closes
+fn run_connection(stream: TcpStream, current: &Mutex<Option<TcpStream>>, events: &Sender<LinkEvent>) {
+    let _ = stream.set_nodelay(true);
+    let writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(e) => {
+            let _ = events.send(LinkEvent::Closed(e.to_string()));
+            return;
+        }
+    };
+    if let Ok(mut current) = current.lock() {
+        *current = Some(writer);
+    }
+    let _ = events.send(LinkEvent::Connected);
+
+    let mut reason = "the other player left".to_string();
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                reason = e.to_string();
+                break;
+            }
+        };
+        match serde_json::from_str::<NetMessage>(&line) {
+            Ok(message) => {
+                if events.send(LinkEvent::Received(message)).is_err() {
+                    break; // The game is gone
+                }
+            }
+            Err(e) => eprintln!("Ignoring unreadable network message: {}", e),
+        }
+    }
+    if let Ok(mut current) = current.lock() {
+        *current = None;
+    }
+    let _ = events.send(LinkEvent::Closed(reason));
+}
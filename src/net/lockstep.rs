@@ -0,0 +1,575 @@
+use std::collections::VecDeque;
+use bevy::prelude::*;
+use shakmaty::{Color as ChessColor, Position};
+use crate::ai::zobrist::{calculate_board_hash, ZobristKeys};
+use crate::config::{DrawbackSetting, GameConfig};
+use crate::drawbacks::DrawbackParams;
+use crate::game_logic::clock::GameClock;
+use crate::game_logic::events::{FlipBoardEvent, GameOverEvent, GameOverReason, GameResult, MakeMoveEvent, MoveRejectedEvent, NewGameEvent};
+use crate::game_logic::history::MoveHistory;
+use crate::game_logic::notation::{format_uci, parse_uci};
+use crate::game_logic::rng::GameRng;
+use crate::game_logic::state::{ActiveBoard, GameState, GameStatus, TurnState};
+use super::chat::ChatEvent;
+use super::link::{Link, LinkEvent, NetRole};
+use super::protocol::{commit_drawback, drawback_setting, Ending, GameSetup, NetMessage, ResumePoint};
+
+/// How the networked game is doing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetStatus {
+    Connecting,
+    WaitingForSetup, // Connected, the host hasn't started a game yet
+    Playing,
+    Desynced,        // Our position differs from the host's, waiting for its game
+    Resyncing,       // Replaying the host's game
+    Reconnecting,    // Lost the connection, waiting for the other player to come back
+    Failed(String),  // Still different after a resync; the game can't go on
+    Disconnected(String),
+}
+
+// A move of the other player, waiting to be played here
+#[derive(Debug)]
+struct RemoteMove {
+    ply: usize,
+    uci: String,
+    hash: u64,
+}
+
+/// Resource for a game against a player on another machine
+#[derive(Resource)]
+pub struct NetSession {
+    pub role: NetRole,
+    pub local_color: ChessColor,
+    pub status: NetStatus,
+    link: Link,
+    synced_plies: usize,               // Moves of the game already sent or checked
+    last_hash: u64,                    // Position after the last of them
+    remote_moves: VecDeque<RemoteMove>,
+    replay: VecDeque<String>,          // Moves of the host's game still to replay
+    resync_hash: u64,                  // Position the replay has to end in
+    resync_clock: Option<(u64, u64)>,  // Host's clocks (White, Black) to take over after the replay
+    in_flight: bool,                   // A move from the network was handed to apply_move
+    seed: Option<u64>,                 // Seed of the game, None while there is none to resume
+    ply_hashes: Vec<u64>,              // Host: position at the start and after every move
+    reconnect_deadline: Option<f32>,   // When a lost connection counts as abandoned (elapsed seconds)
+    hidden: bool,                      // The host's drawback is kept from the client this game
+    drawback_salt: u64,                // Host: salt of the commitment to its hidden drawback
+    commitment: Option<String>,        // Client: the host's commitment to its hidden drawback
+}
+
+impl NetSession {
+    /// The host plays White
+    pub fn new(role: NetRole, link: Link) -> Self {
+        Self {
+            role,
+            local_color: ChessColor::White,
+            status: NetStatus::Connecting,
+            link,
+            synced_plies: 0,
+            last_hash: 0,
+            remote_moves: VecDeque::new(),
+            replay: VecDeque::new(),
+            resync_hash: 0,
+            resync_clock: None,
+            in_flight: false,
+            seed: None,
+            ply_hashes: Vec::new(),
+            reconnect_deadline: None,
+            hidden: false,
+            drawback_salt: 0,
+            commitment: None,
+        }
+    }
+
+    pub fn is_host(&self) -> bool {
+        matches!(self.role, NetRole::Host { .. })
+    }
+
+    /// Whether the host's drawback is kept from the client this game (the
+    /// client's never is: the host sets it up)
+    pub fn hides_host_drawback(&self) -> bool {
+        self.hidden
+    }
+
+    /// Whether the player at this machine may move for `color` right now. The
+    /// host's game goes on while the other player reconnects.
+    pub fn local_may_move(&self, color: ChessColor) -> bool {
+        let playing = match self.status {
+            NetStatus::Playing => true,
+            NetStatus::Reconnecting => self.is_host(),
+            _ => false,
+        };
+        playing && color == self.local_color
+    }
+
+    /// Seconds left for the other side to come back after the connection was lost
+    pub fn reconnect_seconds_left(&self, now: f32) -> Option<f32> {
+        self.reconnect_deadline.map(|deadline| (deadline - now).max(0.0))
+    }
+
+    // Hash both sides compare after every move. The client doesn't know a
+    // hidden drawback, so then only the chess position counts.
+    fn lockstep_hash(&self, game_state: &GameState, zobrist_keys: &ZobristKeys) -> u64 {
+        if self.hidden {
+            calculate_board_hash(&game_state.board, zobrist_keys)
+        } else {
+            game_state.position_key(zobrist_keys)
+        }
+    }
+
+    // Host: the salt of the commitment when our drawback is hidden
+    fn hidden_salt(&self) -> Option<u64> {
+        self.hidden.then_some(self.drawback_salt)
+    }
+
+    /// Sends a chat line to the other player
+    pub fn send_chat(&self, text: String) {
+        self.link.send(NetMessage::Chat { text });
+    }
+
+    // Forgets everything about the previous game
+    fn restart(&mut self) {
+        self.synced_plies = 0;
+        self.remote_moves.clear();
+        self.replay.clear();
+        self.resync_clock = None;
+        self.in_flight = false;
+    }
+
+    // Our position after `ply` differs from the other side's. The host's game
+    // wins: the host sends it, a client asks for it.
+    fn desync(&mut self, ply: usize, game_state: &GameState, history: &MoveHistory, config: &GameConfig, rng: &GameRng, clock: &GameClock) {
+        eprintln!("Network game out of sync after ply {}", ply + 1);
+        self.remote_moves.clear();
+        self.in_flight = false;
+        if self.is_host() {
+            self.send_resync(game_state, history, config, rng, clock);
+        } else {
+            self.status = NetStatus::Desynced;
+            self.link.send(NetMessage::Desync { ply });
+        }
+    }
+
+    // Host: sends the whole game for the client to replay
+    fn send_resync(&self, game_state: &GameState, history: &MoveHistory, config: &GameConfig, rng: &GameRng, clock: &GameClock) {
+        let setup = GameSetup::of_game(game_state, history, config.ruleset, rng.game_seed(), self.local_color, self.hidden_salt());
+        self.link.send(NetMessage::resync(setup, history, self.last_hash, clock_state(clock)));
+    }
+
+    // Host: the client is back. If it still has our game up to some move, it only
+    // gets the moves after it; anything else gets the whole game.
+    fn welcome_back(&mut self, resume: Option<ResumePoint>, game_state: &GameState, history: &MoveHistory, config: &GameConfig, rng: &GameRng, clock: &GameClock) {
+        let resumable = resume.filter(|point| {
+            Some(point.seed) == self.seed
+                && point.plies <= history.moves.len()
+                && self.ply_hashes.get(point.plies) == Some(&point.hash)
+        });
+        match resumable {
+            Some(point) => {
+                println!("The other player is back, sending the {} moves they missed", history.moves.len() - point.plies);
+                self.link.send(NetMessage::resume(history, point.plies, self.last_hash, clock_state(clock)));
+            }
+            None => {
+                println!("The other player is back, sending the whole game");
+                self.send_resync(game_state, history, config, rng, clock);
+            }
+        }
+    }
+
+    // Where our game stands, for the host to continue it after a reconnect
+    fn resume_point(&self) -> Option<ResumePoint> {
+        self.seed.map(|seed| ResumePoint { seed, plies: self.synced_plies, hash: self.last_hash })
+    }
+
+    // The connection dropped. While a game is going on, the other side gets
+    // until the abandonment timeout to come back.
+    fn connection_lost(&mut self, reason: String, game_over: bool, now: f32, timeout_secs: f32) {
+        eprintln!("Lost the connection to the other player: {}", reason);
+        if matches!(self.status, NetStatus::Disconnected(_)) {
+            return;
+        }
+        let waiting = if self.is_host() {
+            !self.ply_hashes.is_empty() && !game_over
+        } else {
+            // A game that wasn't in sync can't be resumed, it is fetched again
+            if self.status != NetStatus::Playing {
+                self.seed = None;
+            }
+            !game_over
+        };
+        if !waiting {
+            self.status = NetStatus::Disconnected(reason);
+            if !self.is_host() {
+                self.link.close();
+            }
+            return;
+        }
+        // A client that never got through keeps trying, just as long
+        if self.status != NetStatus::Connecting {
+            self.status = NetStatus::Reconnecting;
+        }
+        self.remote_moves.clear();
+        self.in_flight = false;
+        self.reconnect_deadline.get_or_insert(now + timeout_secs);
+    }
+}
+
+// Time left on both clocks (White, Black), None in untimed games
+fn clock_state(clock: &GameClock) -> Option<(u64, u64)> {
+    clock.enabled.then_some((clock.white_ms, clock.black_ms))
+}
+
+/// System handling what arrived from the other player. Runs before
+/// `start_new_game` so a game the host started begins in the same frame.
+#[allow(clippy::too_many_arguments)]
+pub fn receive_net_messages(
+    mut session: ResMut<NetSession>,
+    mut config: ResMut<GameConfig>,
+    mut rng: ResMut<GameRng>,
+    mut ev_new_game: EventWriter<NewGameEvent>,
+    mut ev_flip: EventWriter<FlipBoardEvent>,
+    mut ev_chat: EventWriter<ChatEvent>,
+    mut ev_game_over: EventWriter<GameOverEvent>,
+    mut next_state: ResMut<NextState<TurnState>>,
+    mut boards: Query<&mut GameState, With<ActiveBoard>>,
+    history: Res<MoveHistory>,
+    clock: Res<GameClock>,
+    time: Res<Time>,
+) {
+    let Ok(mut game_state) = boards.get_single_mut() else {
+        return;
+    };
+    let game_state = &mut *game_state;
+    for event in session.link.poll() {
+        match event {
+            // The host waits for the client's hello
+            LinkEvent::Connected if session.is_host() => {}
+            LinkEvent::Connected => {
+                session.status = NetStatus::WaitingForSetup;
+                session.reconnect_deadline = None;
+                let resume = session.resume_point();
+                session.link.send(NetMessage::Hello { resume });
+            }
+            LinkEvent::Received(NetMessage::Hello { resume }) if session.is_host() => {
+                session.status = NetStatus::Playing;
+                session.reconnect_deadline = None;
+                session.remote_moves.clear();
+                session.in_flight = false;
+                if session.ply_hashes.is_empty() {
+                    println!("The other player joined, starting a new game");
+                    // Both sides are played by people; the client's moves come over the network
+                    config.white_player.is_ai = false;
+                    config.black_player.is_ai = false;
+                    ev_new_game.send(NewGameEvent { start_position: None });
+                } else {
+                    session.welcome_back(resume, game_state, &history, &config, &rng, &clock);
+                }
+            }
+            LinkEvent::Received(NetMessage::Setup(setup)) if !session.is_host() => {
+                if start_setup(&mut session, &setup, &mut config, &mut rng, &mut ev_new_game, &mut ev_flip, game_state) {
+                    session.status = NetStatus::Playing;
+                }
+            }
+            LinkEvent::Received(NetMessage::Resync { setup, moves, hash, clock }) if !session.is_host() => {
+                if start_setup(&mut session, &setup, &mut config, &mut rng, &mut ev_new_game, &mut ev_flip, game_state) {
+                    println!("Replaying the host's game ({} moves)", moves.len());
+                    session.status = NetStatus::Resyncing;
+                    session.replay = moves.into();
+                    session.resync_hash = hash;
+                    session.resync_clock = clock;
+                }
+            }
+            LinkEvent::Received(NetMessage::Resume { moves, hash, clock }) if !session.is_host() => {
+                println!("Catching up on the host's game ({} moves)", moves.len());
+                session.remote_moves.clear();
+                session.in_flight = false;
+                session.status = NetStatus::Resyncing;
+                session.replay = moves.into();
+                session.resync_hash = hash;
+                session.resync_clock = clock;
+            }
+            LinkEvent::Received(NetMessage::Move { ply, uci, hash }) => {
+                session.remote_moves.push_back(RemoteMove { ply, uci, hash });
+            }
+            LinkEvent::Received(NetMessage::Reveal { drawback, salt, ending }) if !session.is_host() => {
+                if session.commitment.as_ref() != Some(&commit_drawback(&drawback, salt)) {
+                    session.status = NetStatus::Failed("The host revealed another drawback than the one it committed to".to_string());
+                    continue;
+                }
+                let id = config.resolve_drawback_id(&drawback);
+                println!("The host played with {:?}", id);
+                match !session.local_color {
+                    ChessColor::White => (game_state.white_drawback, game_state.white_drawback_params) = (id, drawback.params),
+                    ChessColor::Black => (game_state.black_drawback, game_state.black_drawback_params) = (id, drawback.params),
+                }
+                // The host saw how the game ended, which may have hung on its drawback
+                let drawback_of = |index| config.resolve_drawback_id(&DrawbackSetting { name: None, index: Some(index), params: DrawbackParams::default() });
+                if let (GameStatus::Ongoing, Some(reason)) = (game_state.status, ending.reason(drawback_of)) {
+                    let result = GameResult::new(reason, config.time_control.draw_result);
+                    game_state.status = GameStatus::Finished(result);
+                    next_state.set(TurnState::GameOver);
+                    ev_game_over.send(GameOverEvent(result));
+                    println!("Game over: {}", reason);
+                }
+            }
+            LinkEvent::Received(NetMessage::Chat { text }) => {
+                ev_chat.send(ChatEvent { from: !session.local_color, text });
+            }
+            LinkEvent::Received(NetMessage::Desync { ply }) if session.is_host() => {
+                session.desync(ply, game_state, &history, &config, &rng, &clock);
+            }
+            LinkEvent::Received(message) => eprintln!("Ignoring unexpected network message: {:?}", message),
+            LinkEvent::Closed(reason) => {
+                let game_over = game_state.status.is_over();
+                session.connection_lost(reason, game_over, time.elapsed_seconds(), config.network.abandon_timeout_secs);
+            }
+        }
+    }
+}
+
+// Helper function for the client to start the game the host described, with
+// the same drawbacks and randomness; false if the setup can't be used
+fn start_setup(
+    session: &mut NetSession,
+    setup: &GameSetup,
+    config: &mut GameConfig,
+    rng: &mut GameRng,
+    ev_new_game: &mut EventWriter<NewGameEvent>,
+    ev_flip: &mut EventWriter<FlipBoardEvent>,
+    game_state: &GameState,
+) -> bool {
+    let start_position = match setup.start_position() {
+        Ok(position) => position,
+        Err(e) => {
+            session.status = NetStatus::Failed(e);
+            return false;
+        }
+    };
+    config.white_player.drawback = setup.white.clone();
+    config.black_player.drawback = setup.black.clone();
+    config.white_player.is_ai = false;
+    config.black_player.is_ai = false;
+    config.ruleset = setup.ruleset;
+    *rng = GameRng::new(Some(setup.seed));
+
+    session.restart();
+    session.seed = Some(setup.seed);
+    session.hidden = setup.hidden;
+    session.commitment = setup.commitment.clone();
+    session.local_color = !setup.host_color();
+    // Our own pieces at the bottom
+    if game_state.board_flipped != (session.local_color == ChessColor::Black) {
+        ev_flip.send(FlipBoardEvent);
+    }
+    ev_new_game.send(NewGameEvent { start_position: Some(start_position) });
+    true
+}
+
+/// System noting where every new game starts. The host also tells the
+/// client about the game, or keeps it for when the client is back.
+pub fn announce_new_game(
+    mut session: ResMut<NetSession>,
+    config: Res<GameConfig>,
+    rng: Res<GameRng>,
+    zobrist_keys: Res<ZobristKeys>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    history: Res<MoveHistory>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    if session.is_host() {
+        session.hidden = config.network.hidden_drawbacks;
+        session.drawback_salt = rand::random();
+    }
+    session.last_hash = session.lockstep_hash(game_state, &zobrist_keys);
+    if !session.is_host() {
+        return;
+    }
+    session.restart();
+    session.seed = Some(rng.game_seed());
+    session.ply_hashes = vec![session.last_hash];
+    if session.status != NetStatus::Playing {
+        return;
+    }
+    let setup = GameSetup::of_game(game_state, &history, config.ruleset, rng.game_seed(), session.local_color, session.hidden_salt());
+    session.link.send(NetMessage::Setup(Box::new(setup)));
+}
+
+/// System handing the next move from the network to `apply_move`: the other
+/// player's moves in order, or the host's game while resyncing
+#[allow(clippy::too_many_arguments)]
+pub fn feed_remote_moves(
+    mut session: ResMut<NetSession>,
+    mut ev_make_move: EventWriter<MakeMoveEvent>,
+    config: Res<GameConfig>,
+    rng: Res<GameRng>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    history: Res<MoveHistory>,
+    clock: Res<GameClock>,
+    turn_state: Res<State<TurnState>>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    if session.in_flight || !matches!(turn_state.get(), TurnState::PlayerTurn | TurnState::AiTurn) {
+        return;
+    }
+
+    let next = match session.status {
+        NetStatus::Resyncing => session.replay.pop_front(),
+        NetStatus::Playing if game_state.current_player_turn != session.local_color => session
+            .remote_moves
+            .front()
+            .filter(|remote| remote.ply == history.moves.len())
+            .map(|remote| remote.uci.clone()),
+        _ => None,
+    };
+    let Some(uci) = next else {
+        return;
+    };
+    match parse_uci(&game_state.board, &uci) {
+        Ok(chess_move) => {
+            session.in_flight = true;
+            ev_make_move.send(MakeMoveEvent(chess_move));
+        }
+        Err(e) if session.status == NetStatus::Resyncing => {
+            session.status = NetStatus::Failed(format!("The host's game can't be replayed: {}", e));
+        }
+        Err(_) => {
+            let ply = history.moves.len();
+            session.desync(ply, game_state, &history, &config, &rng, &clock);
+        }
+    }
+}
+
+/// System comparing every move played with the other side: ours are sent
+/// with the hash of the position after them, theirs have to lead to the
+/// hash they were sent with
+#[allow(clippy::too_many_arguments)]
+pub fn check_lockstep(
+    mut session: ResMut<NetSession>,
+    mut ev_rejected: EventReader<MoveRejectedEvent>,
+    config: Res<GameConfig>,
+    rng: Res<GameRng>,
+    zobrist_keys: Res<ZobristKeys>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    history: Res<MoveHistory>,
+    mut clock: ResMut<GameClock>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    let rejected = ev_rejected.read().count() > 0;
+    if rejected && session.in_flight {
+        if session.status == NetStatus::Resyncing {
+            session.status = NetStatus::Failed("The host's game can't be replayed here".to_string());
+        } else {
+            session.desync(history.moves.len(), game_state, &history, &config, &rng, &clock);
+        }
+        return;
+    }
+
+    // The game started over or went back without the other side
+    if history.moves.len() < session.synced_plies {
+        session.synced_plies = history.moves.len();
+        session.ply_hashes.truncate(history.moves.len() + 1);
+        if session.status == NetStatus::Playing {
+            session.desync(history.moves.len(), game_state, &history, &config, &rng, &clock);
+        }
+    }
+
+    // apply_move plays at most one move per frame, so this is the position after it
+    while session.synced_plies < history.moves.len() {
+        let ply = session.synced_plies;
+        let hash = session.lockstep_hash(game_state, &zobrist_keys);
+        let start_turn = history.start_position.turn();
+        let mover = if ply.is_multiple_of(2) { start_turn } else { !start_turn };
+        session.synced_plies += 1;
+        session.last_hash = hash;
+        session.in_flight = false;
+        if session.is_host() {
+            session.ply_hashes.push(hash);
+        }
+
+        // Moves the host plays while the client is away reach it on its return
+        if session.status != NetStatus::Playing {
+            continue;
+        }
+        if mover == session.local_color {
+            let uci = format_uci(&history.moves[ply].chess_move);
+            session.link.send(NetMessage::Move { ply, uci, hash });
+        } else {
+            let matches = session.remote_moves.pop_front().is_some_and(|remote| remote.ply == ply && remote.hash == hash);
+            if !matches {
+                session.desync(ply, game_state, &history, &config, &rng, &clock);
+            }
+        }
+    }
+
+    if session.status == NetStatus::Resyncing && session.replay.is_empty() && !session.in_flight {
+        session.last_hash = session.lockstep_hash(game_state, &zobrist_keys);
+        session.status = if session.last_hash == session.resync_hash {
+            println!("Network game back in sync");
+            if let Some((white_ms, black_ms)) = session.resync_clock.take() {
+                clock.white_ms = white_ms;
+                clock.black_ms = black_ms;
+            }
+            NetStatus::Playing
+        } else {
+            NetStatus::Failed("Still out of sync after replaying the host's game".to_string())
+        };
+    }
+}
+
+/// System for the host to reveal its hidden drawback once the game is over,
+/// along with how the game ended
+pub fn reveal_hidden_drawback(
+    session: Res<NetSession>,
+    mut ev_game_over: EventReader<GameOverEvent>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+) {
+    let Some(GameOverEvent(result)) = ev_game_over.read().last() else {
+        return;
+    };
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    if session.is_host() && session.hidden {
+        let drawback = drawback_setting(game_state, session.local_color);
+        session.link.send(NetMessage::Reveal { drawback, salt: session.drawback_salt, ending: Ending::of(result.reason) });
+    }
+}
+
+/// System ending the game once the other side has been gone for longer than
+/// the abandonment timeout. The host's player wins; the client stops trying.
+pub fn check_abandonment(
+    mut session: ResMut<NetSession>,
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    mut boards: Query<&mut GameState, With<ActiveBoard>>,
+    mut next_state: ResMut<NextState<TurnState>>,
+    mut ev_game_over: EventWriter<GameOverEvent>,
+) {
+    if session.reconnect_seconds_left(time.elapsed_seconds()).is_none_or(|left| left > 0.0) {
+        return;
+    }
+    session.reconnect_deadline = None;
+
+    if !session.is_host() {
+        session.link.close();
+        session.status = NetStatus::Disconnected("The host can't be reached".to_string());
+        return;
+    }
+    if let Ok(mut game_state) = boards.get_single_mut() {
+        if game_state.status == GameStatus::Ongoing {
+            let reason = GameOverReason::Abandoned { loser: !session.local_color };
+            let result = GameResult::new(reason, config.time_control.draw_result);
+            game_state.status = GameStatus::Finished(result);
+            next_state.set(TurnState::GameOver);
+            ev_game_over.send(GameOverEvent(result));
+            println!("Game over: {}", reason);
+        }
+    }
+    session.status = NetStatus::Disconnected("The other player didn't come back".to_string());
+}
@@ -0,0 +1,7 @@
+pub mod plugin;
+pub mod link;
+pub mod protocol;
+pub mod lockstep;
+pub mod chat;
+
+pub use plugin::NetPlugin;
@@ -0,0 +1,77 @@
+use std::time::Duration;
+use bevy::prelude::*;
+use crate::config::GameConfig;
+use crate::game_logic::clock::tick_clock;
+use crate::game_logic::events::NewGameEvent;
+use crate::game_logic::plugin::start_new_game;
+use crate::game_logic::rng::restart_game_rng;
+use crate::game_logic::systems::apply_move;
+use crate::game_logic::state::gameplay_active;
+use super::chat::{ChatEvent, ChatLog, SendChatEvent, send_chat_messages, record_chat};
+use super::link::{Link, NetRole};
+use super::lockstep::{NetSession, receive_net_messages, announce_new_game, feed_remote_moves, check_lockstep, check_abandonment, reveal_hidden_drawback};
+
+/// `--host [port]` waits for another player to join a game against us
+pub const HOST_FLAG: &str = "--host";
+/// `--join <host:port>` plays against a player who started with `--host`
+pub const JOIN_FLAG: &str = "--join";
+pub const DEFAULT_NET_PORT: u16 = 7879;
+
+/// Plugin for games between two players on different machines
+pub struct NetPlugin;
+
+impl Plugin for NetPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SendChatEvent>()
+            .add_event::<ChatEvent>()
+            .init_resource::<ChatLog>();
+
+        app.add_systems(
+            Update,
+            (
+                receive_net_messages.before(start_new_game),
+                announce_new_game
+                    .after(start_new_game)
+                    .after(restart_game_rng)
+                    .run_if(on_event::<NewGameEvent>()),
+                feed_remote_moves
+                    .after(receive_net_messages)
+                    .after(start_new_game)
+                    .before(apply_move)
+                    .run_if(gameplay_active),
+                check_lockstep.after(apply_move).after(tick_clock).after(announce_new_game),
+                check_abandonment.after(check_lockstep),
+                reveal_hidden_drawback.after(check_abandonment),
+                // Chat with the other player
+                (send_chat_messages, record_chat).chain().after(receive_net_messages),
+            )
+                .run_if(resource_exists::<NetSession>())
+        );
+
+        if let Some(role) = role_from_args() {
+            let network = app.world.get_resource::<GameConfig>().map(|config| config.network.clone()).unwrap_or_default();
+            let retry_interval = Duration::from_secs_f32(network.reconnect_interval_secs.max(0.1));
+            app.insert_resource(NetSession::new(role.clone(), Link::open(role, retry_interval)));
+        }
+    }
+}
+
+// Reads `--host [port]` or `--join <address>` from the command line
+fn role_from_args() -> Option<NetRole> {
+    let args: Vec<String> = std::env::args().collect();
+    let argument = |flag: &str| args.iter().position(|arg| arg == flag).map(|i| args.get(i + 1));
+
+    if let Some(address) = argument(JOIN_FLAG) {
+        let Some(address) = address else {
+            eprintln!("Usage: {} <host:port>", JOIN_FLAG);
+            return None;
+        };
+        // The default port can be left out
+        let address = if address.contains(':') { address.clone() } else { format!("{}:{}", address, DEFAULT_NET_PORT) };
+        return Some(NetRole::Client { address });
+    }
+    argument(HOST_FLAG).map(|port| {
+        let port = port.and_then(|port| port.parse::<u16>().ok()).unwrap_or(DEFAULT_NET_PORT);
+        NetRole::Host { port }
+    })
+}
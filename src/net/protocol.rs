@@ -0,0 +1,185 @@
+use serde::{Deserialize, Serialize};
+use shakmaty::{fen::Fen, CastlingMode, Chess, Color as ChessColor, EnPassantMode, Square};
+use crate::config::{DrawbackSetting, Ruleset};
+use crate::drawbacks::{DrawbackId, DrawbackParams};
+use crate::game_logic::events::GameOverReason;
+use crate::game_logic::history::MoveHistory;
+use crate::game_logic::notation::format_uci;
+use crate::game_logic::state::GameState;
+
+/// Everything both sides need to start the same game: the start position, the
+/// drawbacks and the seed of the game's randomness
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GameSetup {
+    pub fen: String,
+    pub white: DrawbackSetting,
+    pub black: DrawbackSetting,
+    pub ruleset: Ruleset,
+    pub seed: u64,
+    pub host_white: bool, // Which side the host plays
+    #[serde(default)]
+    pub hidden: bool,     // The host's drawback is secret and left out (shown as none)
+    #[serde(default)]
+    pub commitment: Option<String>, // With `hidden`: `commit_drawback` of the host's drawback
+}
+
+impl GameSetup {
+    /// Setup of the game on the board, as the host started it. With a hidden
+    /// drawback (`hidden_salt`) the host's own one never leaves this machine;
+    /// the client only gets a commitment to it, to check the reveal against.
+    /// Only the host's drawback can be hidden: the host sets up the client's
+    /// and checks the client's moves against it.
+    pub fn of_game(game_state: &GameState, history: &MoveHistory, ruleset: Ruleset, seed: u64, host_color: ChessColor, hidden_salt: Option<u64>) -> Self {
+        let drawback = |color| {
+            if hidden_salt.is_some() && color == host_color {
+                return DrawbackSetting { name: None, index: None, params: DrawbackParams::default() };
+            }
+            drawback_setting(game_state, color)
+        };
+        let commitment = hidden_salt.map(|salt| commit_drawback(&drawback_setting(game_state, host_color), salt));
+        Self {
+            fen: Fen::from_position(history.start_position.clone(), EnPassantMode::Legal).to_string(),
+            white: drawback(ChessColor::White),
+            black: drawback(ChessColor::Black),
+            ruleset,
+            seed,
+            host_white: host_color == ChessColor::White,
+            hidden: hidden_salt.is_some(),
+            commitment,
+        }
+    }
+
+    pub fn start_position(&self) -> Result<Chess, String> {
+        self.fen
+            .parse::<Fen>()
+            .map_err(|e| format!("Invalid start position {}: {}", self.fen, e))?
+            .into_position(CastlingMode::Standard)
+            .map_err(|e| format!("Invalid start position {}: {}", self.fen, e))
+    }
+
+    pub fn host_color(&self) -> ChessColor {
+        if self.host_white { ChessColor::White } else { ChessColor::Black }
+    }
+}
+
+/// Setting naming the drawback `color` plays with in the game on the board
+pub fn drawback_setting(game_state: &GameState, color: ChessColor) -> DrawbackSetting {
+    let (id, params) = game_state.drawback_of(color);
+    DrawbackSetting { name: None, index: Some(id.to_key_index()), params: params.clone() }
+}
+
+/// Commitment to a hidden drawback: a hash of the drawback (as `drawback_setting`
+/// names it) and a random salt, so the drawback can't be guessed from it and
+/// can't be swapped for another one before the reveal
+pub fn commit_drawback(setting: &DrawbackSetting, salt: u64) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&salt.to_le_bytes());
+    hasher.update(&setting.index.unwrap_or(u16::MAX).to_le_bytes());
+    hasher.update(setting.params.to_string().as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+/// How a game ended, as the host sends it when the client can't work the
+/// ending out itself (it may depend on the host's hidden drawback)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum Ending {
+    KingCaptured { white_won: bool, square: u32 },
+    Checkmate { white_won: bool },
+    DrawbackLoss { white_lost: bool, drawback: u16 },
+    Timeout { white_lost: bool },
+    Resignation { white_lost: bool },
+    Abandoned { white_lost: bool },
+    Stalemate,
+    DrawByRepetition,
+}
+
+impl Ending {
+    pub fn of(reason: GameOverReason) -> Self {
+        let white = |color| color == ChessColor::White;
+        match reason {
+            GameOverReason::KingCaptured { winner, square } => Self::KingCaptured { white_won: white(winner), square: u32::from(square) },
+            GameOverReason::Checkmate { winner } => Self::Checkmate { white_won: white(winner) },
+            GameOverReason::DrawbackLoss { loser, drawback } => Self::DrawbackLoss { white_lost: white(loser), drawback: drawback.to_key_index() },
+            GameOverReason::Timeout { loser } => Self::Timeout { white_lost: white(loser) },
+            GameOverReason::Resignation { loser } => Self::Resignation { white_lost: white(loser) },
+            GameOverReason::Abandoned { loser } => Self::Abandoned { white_lost: white(loser) },
+            GameOverReason::Stalemate => Self::Stalemate,
+            GameOverReason::DrawByRepetition => Self::DrawByRepetition,
+        }
+    }
+
+    /// The reason again; `drawback_of` looks up drawbacks by their index
+    pub fn reason(self, drawback_of: impl Fn(u16) -> DrawbackId) -> Option<GameOverReason> {
+        let color = |white| if white { ChessColor::White } else { ChessColor::Black };
+        Some(match self {
+            Self::KingCaptured { white_won, square } => GameOverReason::KingCaptured { winner: color(white_won), square: Square::try_from(square).ok()? },
+            Self::Checkmate { white_won } => GameOverReason::Checkmate { winner: color(white_won) },
+            Self::DrawbackLoss { white_lost, drawback } => GameOverReason::DrawbackLoss { loser: color(white_lost), drawback: drawback_of(drawback) },
+            Self::Timeout { white_lost } => GameOverReason::Timeout { loser: color(white_lost) },
+            Self::Resignation { white_lost } => GameOverReason::Resignation { loser: color(white_lost) },
+            Self::Abandoned { white_lost } => GameOverReason::Abandoned { loser: color(white_lost) },
+            Self::Stalemate => GameOverReason::Stalemate,
+            Self::DrawByRepetition => GameOverReason::DrawByRepetition,
+        })
+    }
+}
+
+/// Where a reconnecting client's game stands, so the host only has to send
+/// the moves it missed
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResumePoint {
+    pub seed: u64,    // Seed of the game, telling it apart from other games
+    pub plies: usize, // Moves of it the client has played
+    pub hash: u64,    // Position after them
+}
+
+/// Messages exchanged by the two players, one JSON object per line.
+///
+/// Both sides play every move themselves (lockstep) and send the Zobrist hash
+/// of the position it led to, so a difference shows up on the next move
+/// instead of as a confusing game later on. The host's game is authoritative.
+///
+/// With hidden drawbacks the client only knows its own: it plays the host's
+/// moves as they come, the host checks the client's moves against the
+/// client's drawback (answering an illegal one with a resync), and the hashes
+/// leave the drawbacks out. The host's drawback crosses the wire only once the
+/// game is over, in the reveal, which the client checks against the
+/// commitment of the setup. This only goes one way: the host sets up the
+/// client's drawback, so it is never hidden from the host.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NetMessage {
+    /// Client, on every (re)connect: the game it has, if any
+    Hello { resume: Option<ResumePoint> },
+    /// Host: a new game starts
+    Setup(Box<GameSetup>),
+    /// A move of the sender; `ply` is its index in the game, `hash` the position after it
+    Move { ply: usize, uci: String, hash: u64 },
+    /// Client: my position differs from yours after `ply`
+    Desync { ply: usize },
+    /// Host: the whole game so far, replayed by the client in place of its own
+    /// `clock` is the time left for White and Black, when the game is timed
+    Resync { setup: Box<GameSetup>, moves: Vec<String>, hash: u64, clock: Option<(u64, u64)> },
+    /// Host: the moves played since the client's resume point, and where they lead
+    Resume { moves: Vec<String>, hash: u64, clock: Option<(u64, u64)> },
+    /// Host, at the end of a game with hidden drawbacks: its drawback, the salt
+    /// of its commitment and how the game ended
+    Reveal { drawback: DrawbackSetting, #[serde(default)] salt: u64, ending: Ending },
+    /// A chat line (or emote) from the sender
+    Chat { text: String },
+}
+
+impl NetMessage {
+    /// The resync for the game on the host's board
+    pub fn resync(setup: GameSetup, history: &MoveHistory, hash: u64, clock: Option<(u64, u64)>) -> Self {
+        let moves = history.moves.iter().map(|record| format_uci(&record.chess_move)).collect();
+        Self::Resync { setup: Box::new(setup), moves, hash, clock }
+    }
+
+    /// The moves of the host's game from ply `from` on
+    pub fn resume(history: &MoveHistory, from: usize, hash: u64, clock: Option<(u64, u64)>) -> Self {
+        let moves = history.moves[from..].iter().map(|record| format_uci(&record.chess_move)).collect();
+        Self::Resume { moves, hash, clock }
+    }
+}
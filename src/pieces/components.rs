@@ -7,4 +7,34 @@ pub struct Piece {
     pub role: Role,
     pub color: Color,
     pub pos: Square,
-} 
\ No newline at end of file
+}
+
+/// Stable identity of a piece entity. It stays the same while the piece moves,
+/// promotes or gets captured, so animations, history and the captured pieces
+/// tray can follow "the same piece" across moves.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PieceId(pub u32);
+
+/// A captured piece. The entity is kept (hidden, with its `PieceId`) instead of
+/// being despawned; the `Piece` component is removed so board queries skip it.
+#[derive(Component, Debug)]
+pub struct CapturedPiece {
+    pub role: Role,
+    pub color: Color,
+    pub square: Square, // Where it was captured
+    pub ply: usize,     // Number of the move (counting from 1) that captured it
+}
+
+/// Hands out piece IDs. IDs are never reused within a session.
+#[derive(Resource, Debug, Default)]
+pub struct PieceIdAllocator {
+    next: u32,
+}
+
+impl PieceIdAllocator {
+    pub fn next_id(&mut self) -> PieceId {
+        let id = PieceId(self.next);
+        self.next += 1;
+        id
+    }
+}
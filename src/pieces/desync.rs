@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+use shakmaty::{Color as ChessColor, Position, Role, Square};
+use std::collections::HashMap;
+use crate::board::components::BoardSquare;
+use crate::game_logic::state::{GameState, ActiveBoard};
+use crate::game_logic::history::MoveHistory;
+use super::components::{Piece, PieceId, PieceIdAllocator};
+use super::plugin::{sync_pieces_to_board, PieceEntities};
+
+/// One difference between the piece entities and `GameState.board`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Desync {
+    /// The board has a piece here but no entity shows it
+    Missing { square: Square, color: ChessColor, role: Role },
+    /// An entity shows a piece the board doesn't have on this square
+    Extra { square: Square, color: ChessColor, role: Role },
+    /// An entity shows the wrong piece for this square
+    Mismatch { square: Square, board: (ChessColor, Role), shown: (ChessColor, Role) },
+    /// More than one entity claims this square
+    Duplicate { square: Square, count: usize },
+}
+
+/// Compares the piece entities against the logical board
+pub fn find_desyncs<'a>(board: &shakmaty::Board, pieces: impl Iterator<Item = &'a Piece>) -> Vec<Desync> {
+    let mut shown: HashMap<Square, Vec<(ChessColor, Role)>> = HashMap::new();
+    for piece in pieces {
+        shown.entry(piece.pos).or_default().push((piece.color, piece.role));
+    }
+
+    let mut desyncs = Vec::new();
+    for square in Square::ALL {
+        let on_board = board.piece_at(square).map(|piece| (piece.color, piece.role));
+        let on_screen = shown.get(&square).map(Vec::as_slice).unwrap_or(&[]);
+
+        if on_screen.len() > 1 {
+            desyncs.push(Desync::Duplicate { square, count: on_screen.len() });
+            continue;
+        }
+
+        match (on_board, on_screen.first().copied()) {
+            (Some((color, role)), None) => desyncs.push(Desync::Missing { square, color, role }),
+            (None, Some((color, role))) => desyncs.push(Desync::Extra { square, color, role }),
+            (Some(board), Some(shown)) if board != shown => {
+                desyncs.push(Desync::Mismatch { square, board, shown })
+            }
+            _ => {}
+        }
+    }
+    desyncs
+}
+
+/// Debug system (dev builds only): after every applied move, checks that the
+/// piece entities match `GameState.board`, logs any divergence and repairs it
+/// by putting the pieces back where the board has them
+pub fn detect_board_desync(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    history: Res<MoveHistory>,
+    mut pieces: PieceEntities,
+    board_squares: Query<(&Transform, &BoardSquare), Without<PieceId>>,
+    mut piece_ids: ResMut<PieceIdAllocator>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    let desyncs = find_desyncs(game_state.board.board(), pieces.iter().filter_map(|(_, piece, ..)| piece));
+    if desyncs.is_empty() {
+        return;
+    }
+
+    eprintln!("!!! BOARD DESYNC: {} square(s) differ from the game state", desyncs.len());
+    for desync in &desyncs {
+        eprintln!("    {:?}", desync);
+    }
+    eprintln!("    Moving pieces to match the game state");
+    sync_pieces_to_board(&mut commands, &asset_server, game_state.board.board(), history.len(), &mut pieces, &board_squares, &mut piece_ids);
+}
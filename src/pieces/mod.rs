@@ -1,4 +1,6 @@
 pub mod components;
 pub mod plugin;
+pub mod promotion;
 
- 
\ No newline at end of file
+#[cfg(debug_assertions)] // Desync detection only runs in dev builds
+pub mod desync;
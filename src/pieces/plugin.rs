@@ -1,23 +1,24 @@
 use bevy::prelude::*;
-use shakmaty::{Square, Color as ChessColor, Role, Chess, Position, Move, File};
-use crate::constants::{TILE_SIZE, Z_PIECES, Z_UI_ELEMENTS};
-use crate::game_logic::state::{GameState, TurnState};
-use crate::game_logic::events::MakeMoveEvent;
-use super::components::Piece;
+use std::collections::HashMap;
+use shakmaty::{Square, Color as ChessColor, Role, Position, File};
+use crate::constants::{TILE_SIZE, Z_PIECES};
+use crate::game_logic::state::{GameState, ActiveBoard, active_board_exists, TurnState, gameplay_active};
+#[cfg(debug_assertions)]
+use crate::game_logic::state::ReplayState;
+#[cfg(debug_assertions)]
+use super::desync::detect_board_desync;
+use crate::game_logic::events::{MakeMoveEvent, MovePlayedEvent, NewGameEvent, UndoMoveEvent, RedoMoveEvent};
+use crate::game_logic::plugin::start_new_game;
+use super::components::{Piece, PieceId, PieceIdAllocator, CapturedPiece};
+use super::promotion::{PendingPromotion, PromotionCancelledEvent, show_promotion_picker, handle_promotion_selection};
+use crate::ui::pause_menu::toggle_pause;
+use crate::game_logic::history::MoveHistory;
+use crate::game_logic::systems::{apply_move, resume_loaded_game, take_back_moves};
+use crate::board::components::BoardSquare;
+use crate::board::geometry::world_pos;
+use crate::ui::reserve_tray::piece_image_path;
 use bevy::render::texture::Image;
 
-// Component for promotion UI
-#[derive(Component)]
-struct PromotionUI;
-
-#[derive(Component)]
-struct PromotionOption {
-    role: Role,
-    from: Square,
-    to: Square,
-    color: ChessColor,
-}
-
 #[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
 enum PiecesState {
     #[default]
@@ -30,9 +31,14 @@ pub struct PiecesPlugin;
 impl Plugin for PiecesPlugin {
     fn build(&self, app: &mut App) {
         app.add_state::<PiecesState>()
+           .init_resource::<PieceIdAllocator>()
+           .init_resource::<PendingPromotion>()
+           .add_event::<PromotionCancelledEvent>()
            .add_systems(Update, 
                 spawn_pieces
-                .run_if(resource_exists::<GameState>())
+                .after(start_new_game) // A game started on the first frame must be spawned as it is
+                .after(resume_loaded_game)
+                .run_if(active_board_exists)
                 .run_if(in_state(PiecesState::NotInitialized))
            )
            .add_systems(Update, 
@@ -40,8 +46,46 @@ impl Plugin for PiecesPlugin {
                 .after(spawn_pieces)
                 .run_if(in_state(PiecesState::NotInitialized))
            )
-           .add_systems(Update, update_piece_positions)
-           .add_systems(Update, handle_promotion_selection);
+           // Reads the board before the move is applied (e.g. to spot promotions)
+           .add_systems(Update, update_piece_positions.before(apply_move))
+           // A drawback may play the piece on from where it was put
+           .add_systems(Update, follow_substituted_moves.after(apply_move))
+           .add_systems(
+                Update,
+                drop_captured_pieces
+                    .after(start_new_game)
+                    .before(respawn_pieces_for_new_game)
+                    .run_if(on_event::<NewGameEvent>())
+           )
+           .add_systems(
+                Update,
+                respawn_pieces_for_new_game
+                    .after(start_new_game)
+                    .after(take_back_moves)
+                    .after(resume_loaded_game)
+                    .run_if(on_event::<NewGameEvent>().or_else(on_event::<UndoMoveEvent>()).or_else(on_event::<RedoMoveEvent>()))
+                    .run_if(in_state(PiecesState::Initialized))
+           )
+           // Promotion picker (Q/R/B/N or click, Esc cancels)
+           .add_systems(
+                Update,
+                (
+                    handle_promotion_selection.before(toggle_pause).run_if(gameplay_active),
+                    show_promotion_picker.run_if(resource_changed::<PendingPromotion>()),
+                )
+                    .chain()
+           );
+
+        // Dev builds check the pieces against the game state after every move.
+        // PostUpdate sees the spawns and despawns queued by this frame's move.
+        #[cfg(debug_assertions)]
+        app.add_systems(
+            PostUpdate,
+            detect_board_desync
+                .run_if(resource_changed::<MoveHistory>())
+                .run_if(in_state(ReplayState::Live))
+                .run_if(in_state(PiecesState::Initialized))
+        );
     }
 }
 
@@ -51,111 +95,26 @@ fn apply_state_transition(
     next_state.set(PiecesState::Initialized);
 }
 
-/// Handle clicks on promotion piece options
-fn handle_promotion_selection(
-    mut commands: Commands,
-    query: Query<(Entity, &PromotionOption, &Parent)>,
-    ui_query: Query<Entity, With<PromotionUI>>,
-    mouse_buttons: Res<Input<MouseButton>>,
-    windows: Query<&Window>,
-    camera_q: Query<(&Camera, &GlobalTransform)>,
-    mut ev_make_move: EventWriter<MakeMoveEvent>,
-) {
-    // Only process clicks
-    if !mouse_buttons.just_pressed(MouseButton::Left) {
-        return;
-    }
-    
-    // Get cursor position
-    let window = windows.single();
-    let (camera, camera_transform) = camera_q.single();
-    
-    if let Some(cursor_pos) = window.cursor_position() {
-        // Convert screen position to world coordinates
-        let world_pos = screen_to_world(cursor_pos, window, camera, camera_transform);
-        println!("Mouse clicked at screen position: {:?}", cursor_pos);
-        println!("Converted screen ({:?}) to world ({:?})", cursor_pos, world_pos);
-        
-        // Check if we clicked on a promotion option
-        for (entity, option, parent) in query.iter() {
-            // Calculate the expected position of each promotion UI option (match logic in spawn_promotion_ui)
-            let file = option.to.file().char() as u8 - b'a';
-            let rank = option.to.rank().char() as u8 - b'1';
-            let promotion_roles = [Role::Queen, Role::Rook, Role::Bishop, Role::Knight];
-            
-            // Find the index of this role in the array to determine its position
-            if let Some(role_index) = promotion_roles.iter().position(|r| *r == option.role) {
-                let offset = (role_index as f32 - 1.5) * 1.2; // Match offset in spawn_promotion_ui
-                let option_pos = Vec2::new(
-                    ((file as f32 - 3.5) * TILE_SIZE) + offset * TILE_SIZE,
-                    ((7 - rank) as f32 - 3.5) * TILE_SIZE
-                );
-                
-                // Check if clicked within range of this option (increased radius for easier clicking)
-                let distance = (option_pos - world_pos).length_squared();
-                if distance < (TILE_SIZE * 0.8).powi(2) { // Increased detection radius
-                    println!("Selected promotion: {:?}", option.role);
-                    
-                    // Create the promotion move
-                    let promotion_move = Move::Normal {
-                        role: Role::Pawn,
-                        from: option.from,
-                        capture: None, // We'll let the game logic handle capture detection
-                        to: option.to,
-                        promotion: Some(option.role),
-                    };
-                    
-                    // Log the promotion move details for debugging
-                    println!("Creating promotion move: from={:?}, to={:?}, role={:?}", 
-                             option.from, option.to, option.role);
-                    
-                    // Send the move event
-                    ev_make_move.send(MakeMoveEvent(promotion_move));
-                    
-                    // Remove all promotion UI
-                    for ui_entity in ui_query.iter() {
-                        commands.entity(ui_entity).despawn_recursive();
-                    }
-                    
-                    break;
-                }
-            }
-        }
-    }
-}
-
-// Helper function to convert screen to world coordinates
-fn screen_to_world(
-    cursor_pos: Vec2,
-    window: &Window,
-    camera: &Camera,
-    camera_transform: &GlobalTransform,
-) -> Vec2 {
-    // Get the size of the window
-    let window_size = Vec2::new(window.width(), window.height());
-    
-    // Convert screen position [0..resolution] to ndc [-1..1]
-    let ndc = (cursor_pos / window_size) * 2.0 - Vec2::ONE;
-    
-    // Convert to world space
-    let world_pos = camera_transform.compute_matrix() 
-                  * camera.projection_matrix().inverse()
-                  * Vec4::new(ndc.x, ndc.y, 0.0, 1.0);
-    
-    Vec2::new(world_pos.x, world_pos.y)
-}
-
 /// Update piece positions when moves are made
+#[allow(clippy::too_many_arguments)]
 pub fn update_piece_positions(
     mut commands: Commands,
     mut pieces: Query<(Entity, &mut Piece, &mut Transform)>,
     asset_server: Res<AssetServer>,
     mut ev_make_move: EventReader<MakeMoveEvent>,
-    game_state: Res<GameState>,
+    boards: Query<&GameState, With<ActiveBoard>>,
     current_state: Res<State<TurnState>>,
+    history: Res<MoveHistory>,
+    mut pending_promotion: ResMut<PendingPromotion>,
+    mut piece_ids: ResMut<PieceIdAllocator>,
 ) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
     for ev in ev_make_move.read() {
         let chess_move = &ev.0;
+        // The move about to be recorded
+        let ply = history.len() + 1;
         println!("Updating piece positions for move: {:?}", chess_move);
         
         // Handle different types of moves
@@ -173,7 +132,7 @@ pub fn update_piece_positions(
                             if (is_white_to_eighth || is_black_to_first) && 
                                *current_state.get() == TurnState::PlayerTurn {
                                 // Show promotion UI and don't process the move yet
-                                spawn_promotion_ui(&mut commands, &asset_server, *from, *to, piece.color);
+                                pending_promotion.0 = Some(chess_move.clone());
                                 continue;
                             }
                         }
@@ -184,7 +143,7 @@ pub fn update_piece_positions(
                 for (entity, piece, _) in pieces.iter() {
                     if piece.pos == *to {
                         println!("Removing captured piece at {:?}", to);
-                        commands.entity(entity).despawn();
+                        capture_piece(&mut commands, entity, piece, ply);
                     }
                 }
                 
@@ -223,25 +182,7 @@ pub fn update_piece_positions(
                         }
                         
                         // Update its visual position based on the board orientation
-                        let file = to.file().char() as u8 - b'a';
-                        let rank = to.rank().char() as u8 - b'1';
-                        
-                        let position = if game_state.board_flipped {
-                            // Flipped board (white at top)
-                            Vec3::new(
-                                (file as f32 - 3.5) * TILE_SIZE,
-                                (rank as f32 - 3.5) * TILE_SIZE,
-                                Z_PIECES
-                            )
-                        } else {
-                            // Standard board (white at bottom)
-                            Vec3::new(
-                                (file as f32 - 3.5) * TILE_SIZE,
-                                ((7 - rank) as f32 - 3.5) * TILE_SIZE,
-                                Z_PIECES
-                            )
-                        };
-                        transform.translation = position;
+                        transform.translation = world_pos(*to, game_state.board_flipped).extend(Z_PIECES);
                         
                         found = true;
                         break;
@@ -260,7 +201,7 @@ pub fn update_piece_positions(
                 for (entity, piece, _) in pieces.iter() {
                     if piece.pos == captured_square {
                         println!("Removing en passant captured piece at {:?}", captured_square);
-                        commands.entity(entity).despawn();
+                        capture_piece(&mut commands, entity, piece, ply);
                     }
                 }
                 
@@ -269,59 +210,30 @@ pub fn update_piece_positions(
                     if piece.pos == *from {
                         piece.pos = *to;
                         
-                        let file = to.file().char() as u8 - b'a';
-                        let rank = to.rank().char() as u8 - b'1';
-                        
-                        let position = if game_state.board_flipped {
-                            // Flipped board (white at top)
-                            Vec3::new(
-                                (file as f32 - 3.5) * TILE_SIZE,
-                                (rank as f32 - 3.5) * TILE_SIZE,
-                                Z_PIECES
-                            )
-                        } else {
-                            // Standard board (white at bottom)
-                            Vec3::new(
-                                (file as f32 - 3.5) * TILE_SIZE,
-                                ((7 - rank) as f32 - 3.5) * TILE_SIZE,
-                                Z_PIECES
-                            )
-                        };
-                        transform.translation = position;
+                        transform.translation = world_pos(*to, game_state.board_flipped).extend(Z_PIECES);
                         break;
                     }
                 }
             },
             
             // Handle castling
-            shakmaty::Move::Castle { king, rook } => {
+            shakmaty::Move::Castle { king, rook: _ } => {
                 // Handle each piece involved in castling separately
                 // First the king
                 for (_, mut piece, mut transform) in pieces.iter_mut() {
                     if piece.role == Role::King && piece.pos == *king {
-                        // King's final destination is stored in the move
-                        let king_to = chess_move.to();
+                        // Move::to() of a castling move is the rook's square, the king
+                        // ends up on the g or c file
+                        let king_to = match chess_move.castling_side() {
+                            Some(side) => side.king_to(piece.color),
+                            None => chess_move.to(),
+                        };
                         
                         // Update king position
                         piece.pos = king_to;
                         
                         // Update its visual position
-                        let file = king_to.file().char() as u8 - b'a';
-                        let rank = king_to.rank().char() as u8 - b'1';
-                        
-                        if game_state.board_flipped {
-                            transform.translation = Vec3::new(
-                                (file as f32 - 3.5) * TILE_SIZE,
-                                (rank as f32 - 3.5) * TILE_SIZE,
-                                Z_PIECES
-                            );
-                        } else {
-                            transform.translation = Vec3::new(
-                                (file as f32 - 3.5) * TILE_SIZE,
-                                ((7 - rank) as f32 - 3.5) * TILE_SIZE,
-                                Z_PIECES
-                            );
-                        }
+                        transform.translation = world_pos(king_to, game_state.board_flipped).extend(Z_PIECES);
                         
                         println!("Castling: moved king from {:?} to {:?}", *king, king_to);
                         break;
@@ -331,7 +243,7 @@ pub fn update_piece_positions(
                 // Then the rook - find the correct rook based on board position
                 // The rook must be in one of the corner positions of the correct color
                 let king_rank = king.rank();
-                let is_kingside_castle = chess_move.to().file() > king.file();
+                let is_kingside_castle = chess_move.castling_side() == Some(shakmaty::CastlingSide::KingSide);
                 
                 let rook_square = if is_kingside_castle {
                     // Kingside castle - rook is on the H file (rightmost)
@@ -362,23 +274,7 @@ pub fn update_piece_positions(
                         piece.pos = rook_to;
                         
                         // Update visual position
-                        let file = rook_to.file().char() as u8 - b'a';
-                        let rank = rook_to.rank().char() as u8 - b'1';
-                        
-                        // Update visual position based on board orientation
-                        if game_state.board_flipped {
-                            transform.translation = Vec3::new(
-                                (file as f32 - 3.5) * TILE_SIZE,
-                                (rank as f32 - 3.5) * TILE_SIZE,
-                                Z_PIECES
-                            );
-                        } else {
-                            transform.translation = Vec3::new(
-                                (file as f32 - 3.5) * TILE_SIZE,
-                                ((7 - rank) as f32 - 3.5) * TILE_SIZE,
-                                Z_PIECES
-                            );
-                        }
+                        transform.translation = world_pos(rook_to, game_state.board_flipped).extend(Z_PIECES);
                         
                         println!("Castling: moved rook from {:?} to {:?}", rook_from, rook_to);
                         break;
@@ -386,145 +282,88 @@ pub fn update_piece_positions(
                 }
             },
             
-            // Don't need to handle Drop or Put - not part of standard chess
-            _ => {
-                println!("Special move type not fully implemented: {:?}", chess_move);
+            // Handle drops: a new piece comes out of the mover's hand
+            shakmaty::Move::Put { role, to } => {
+                let color = game_state.board.turn();
+                let position = world_pos(*to, game_state.board_flipped).extend(Z_PIECES);
+
+                let color_prefix = match color {
+                    ChessColor::White => "w",
+                    ChessColor::Black => "b",
+                };
+                commands.spawn((
+                    SpriteBundle {
+                        texture: asset_server.load(format!("images/{}{}.png", color_prefix, role.upper_char())),
+                        transform: Transform::from_translation(position),
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::new(TILE_SIZE * 0.9, TILE_SIZE * 0.9)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    Piece { pos: *to, color, role: *role },
+                    piece_ids.next_id(),
+                ));
+                println!("Dropped {:?} {:?} on {:?}", color, role, to);
             }
         }
     }
 }
 
-// Function to spawn promotion UI
-fn spawn_promotion_ui(
-    commands: &mut Commands,
-    asset_server: &AssetServer,
-    from: Square,
-    to: Square,
-    color: ChessColor,
+/// Moves the piece on when a drawback played another move than the one picked
+/// (it went where the picked move put it before the move was applied)
+pub fn follow_substituted_moves(
+    mut ev_played: EventReader<MovePlayedEvent>,
+    mut pieces: Query<(&mut Piece, &mut Transform)>,
+    boards: Query<&GameState, With<ActiveBoard>>,
 ) {
-    // Calculate position for the promotion UI
-    let file = to.file().char() as u8 - b'a';
-    let rank = to.rank().char() as u8 - b'1';
-    
-    let base_position = Vec3::new(
-        (file as f32 - 3.5) * TILE_SIZE,
-        ((7 - rank) as f32 - 3.5) * TILE_SIZE,
-        Z_UI_ELEMENTS // Above pieces and indicators
-    );
-    
-    // Spawn parent entity for all promotion options
-    let parent = commands.spawn((
-        SpatialBundle::default(),
-        PromotionUI,
-    )).id();
-    
-    // Spawn the four promotion options
-    let promotion_roles = [Role::Queen, Role::Rook, Role::Bishop, Role::Knight];
-    let color_prefix = match color {
-        ChessColor::White => "w",
-        ChessColor::Black => "b",
+    let Ok(game_state) = boards.get_single() else {
+        return;
     };
-    
-    // Display options horizontally instead of vertically for better visibility
-    for (i, role) in promotion_roles.iter().enumerate() {
-        // Position horizontally with offset based on file position
-        let offset = (i as f32 - 1.5) * 1.2; // Spread out the options more
-        let position = Vec3::new(
-            base_position.x + offset * TILE_SIZE,
-            base_position.y,
-            base_position.z
-        );
-        
-        let role_suffix = match role {
-            Role::Queen => "Q",
-            Role::Rook => "R",
-            Role::Bishop => "B",
-            Role::Knight => "N",
-            _ => "Q", // Default (shouldn't happen)
+    for ev in ev_played.read() {
+        let Some(picked) = &ev.picked else {
+            continue;
         };
-        
-        let image_path = format!("images/{}{}.png", color_prefix, role_suffix);
-        
-        // Spawn the promotion option with a highlighted background for better visibility
-        commands.entity(parent).with_children(|parent| {
-            // First spawn a background highlight
-            parent.spawn(SpriteBundle {
-                sprite: Sprite {
-                    color: Color::rgba(1.0, 1.0, 0.0, 0.7), // More opaque yellow
-                    custom_size: Some(Vec2::new(TILE_SIZE * 1.2, TILE_SIZE * 1.2)), // Even larger background
-                    ..default()
-                },
-                transform: Transform::from_translation(position),
-                ..default()
-            });
-            
-            // Then spawn the piece image
-            parent.spawn((
-                SpriteBundle {
-                    texture: asset_server.load(&image_path),
-                    transform: Transform::from_translation(Vec3::new(
-                        position.x, 
-                        position.y, 
-                        position.z + 0.01 // Slightly above background
-                    )),
-                    sprite: Sprite {
-                        custom_size: Some(Vec2::new(TILE_SIZE * 1.0, TILE_SIZE * 1.0)),
-                        ..default()
-                    },
-                    ..default()
-                },
-                PromotionOption {
-                    role: *role,
-                    from,
-                    to,
-                    color,
-                },
-            ));
-        });
+        if let Some((mut piece, mut transform)) = pieces.iter_mut().find(|(piece, _)| piece.pos == picked.to()) {
+            piece.pos = ev.chess_move.to();
+            transform.translation = world_pos(piece.pos, game_state.board_flipped).extend(Z_PIECES);
+        }
     }
-    
-    println!("Spawned promotion UI at {:?}", to);
+}
+
+// Helper function to take a captured piece off the board. The entity stays
+// (hidden) so its PieceId can still be followed, e.g. by the captured pieces tray.
+fn capture_piece(commands: &mut Commands, entity: Entity, piece: &Piece, ply: usize) {
+    commands.entity(entity)
+        .remove::<Piece>()
+        .insert((
+            CapturedPiece {
+                role: piece.role,
+                color: piece.color,
+                square: piece.pos,
+                ply,
+            },
+            Visibility::Hidden,
+        ));
 }
 
 /// Spawns chess pieces based on the current game state
 pub fn spawn_pieces(
     mut commands: Commands,
-    game_state: Res<GameState>,
+    boards: Query<&GameState, With<ActiveBoard>>,
     asset_server: Res<AssetServer>,
+    mut piece_ids: ResMut<PieceIdAllocator>,
 ) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
     println!("Spawning chess pieces...");
     
     // Iterate through all squares on the board
     for square in Square::ALL {
         if let Some(piece) = game_state.board.board().piece_at(square) {
-            // Calculate position based on square
-            // File is horizontal (A-H, left to right)
-            let file = square.file().char() as u8 - b'a'; // 0-7 for files a-h
-            
-            // Rank is vertical (1-8, bottom to top)
-            // In chess, rank 1 is at the bottom (white's first rank)
-            // and rank 8 is at the top (black's first rank)
-            let rank = square.rank().char() as u8 - b'1'; // 0-7 for ranks 1-8
-            
-            // Debug information about coordinate mapping
-            println!(
-                "Mapping square {:?}: file={}, rank={}, file_char={}, rank_char={}", 
-                square, 
-                file, 
-                rank, 
-                square.file().char(),
-                square.rank().char()
-            );
-            
-            // In our screen coordinates:
-            // - x increases from left to right (matches chess files)
-            // - y increases from bottom to top (matches chess ranks)
-            // - (0,0) is the center of the screen
-            let position = Vec3::new(
-                (file as f32 - 3.5) * TILE_SIZE, // Center the board horizontally
-                ((7 - rank) as f32 - 3.5) * TILE_SIZE, // Flip the rank to get 0 at the bottom
-                Z_PIECES, // Place pieces above the board and indicators
-            );
+            // Place the piece on its square in the board's current orientation
+            let position = world_pos(square, game_state.board_flipped).extend(Z_PIECES);
 
             println!("Placing piece at square: {:?}, position: {:?}", square, position);
 
@@ -563,52 +402,154 @@ pub fn spawn_pieces(
                     color: piece.color,
                     role: piece.role,
                 },
+                piece_ids.next_id(),
             ));
         }
     }
 }
 
-// Helper function to spawn a single piece
-fn spawn_single_piece(
+/// System to despawn the pieces captured in the previous game when a new one starts
+fn drop_captured_pieces(mut commands: Commands, captured: Query<Entity, With<CapturedPiece>>) {
+    for entity in captured.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// System to put the position on the board after a new game was started or
+/// moves were taken back
+#[allow(clippy::too_many_arguments)]
+fn respawn_pieces_for_new_game(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    history: Res<MoveHistory>,
+    mut pieces: PieceEntities,
+    board_squares: Query<(&Transform, &BoardSquare), Without<PieceId>>,
+    mut pending_promotion: ResMut<PendingPromotion>,
+    mut piece_ids: ResMut<PieceIdAllocator>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    // A pending promotion belongs to the old game (this also closes the picker)
+    pending_promotion.0 = None;
+    sync_pieces_to_board(&mut commands, &asset_server, game_state.board.board(), history.len(), &mut pieces, &board_squares, &mut piece_ids);
+}
+
+/// Piece entities, on the board or captured, as `sync_pieces_to_board` updates them
+pub type PieceEntities<'w, 's> = Query<'w, 's, (Entity, Option<&'static mut Piece>, Option<&'static CapturedPiece>, &'static mut Transform, &'static mut Visibility), With<PieceId>>;
+
+/// Makes the piece entities show `board`, the position after `ply` moves.
+/// Used when the displayed position jumps (replay, loading a game, take backs)
+/// rather than following a single move. Pieces keep their entities and IDs:
+/// one already on its square stays, others of the same kind move there, and
+/// pieces captured after `ply` come back. Pieces that are no longer on the
+/// board count as captured by `ply`; captures from after it that didn't come
+/// back are dropped. Pieces are placed on their board square entities so they
+/// always match the current board orientation.
+pub fn sync_pieces_to_board(
     commands: &mut Commands,
     asset_server: &AssetServer,
-    square: Square,
-    color: ChessColor,
-    role: Role,
+    board: &shakmaty::Board,
+    ply: usize,
+    pieces: &mut PieceEntities,
+    board_squares: &Query<(&Transform, &BoardSquare), Without<PieceId>>,
+    piece_ids: &mut PieceIdAllocator,
 ) {
-    // Calculate position based on square
-    let file = square.file().char() as u8 - b'a';
-    let rank = square.rank().char() as u8 - b'1';
-    
-    let position = Vec3::new(
-        (file as f32 - 3.5) * TILE_SIZE,
-        ((7 - rank) as f32 - 3.5) * TILE_SIZE,
-        Z_PIECES // Update to use constant
-    );
-    
-    // Determine piece image path
-    let color_prefix = match color {
-        ChessColor::White => "w",
-        ChessColor::Black => "b",
-    };
-    
-    let role_suffix = match role {
-        Role::King => "K",
-        Role::Queen => "Q",
-        Role::Rook => "R",
-        Role::Bishop => "B",
-        Role::Knight => "N",
-        Role::Pawn => "P",
+    let positions: HashMap<Square, Vec3> = board_squares
+        .iter()
+        .map(|(transform, board_square)| (board_square.square, transform.translation.truncate().extend(Z_PIECES)))
+        .collect();
+
+    let mut on_board = Vec::new();
+    let mut captured = Vec::new();
+    for (entity, piece, capture, ..) in pieces.iter() {
+        match (piece, capture) {
+            (Some(piece), _) => on_board.push((entity, piece.role.of(piece.color), piece.pos)),
+            (None, Some(capture)) => captured.push((entity, capture.role.of(capture.color), capture.ply)),
+            (None, None) => {}
+        }
+    }
+    // The latest capture comes back first
+    captured.sort_by_key(|&(_, _, captured_ply)| std::cmp::Reverse(captured_ply));
+    let mut undone_captures: Vec<_> = captured.into_iter().filter(|&(_, _, captured_ply)| captured_ply > ply).collect();
+
+    // Pieces already on their square stay as they are
+    let mut missing = Vec::new();
+    for (square, piece) in board.clone() {
+        match on_board.iter().position(|&(_, shown, pos)| shown == piece && pos == square) {
+            Some(index) => {
+                let (entity, ..) = on_board.swap_remove(index);
+                place_piece_entity(pieces, entity, piece, square, &positions);
+            }
+            None => missing.push((square, piece)),
+        }
+    }
+
+    // Then pieces of the same kind move there, or captured ones come back
+    let mut changing_role = Vec::new();
+    for (square, piece) in missing {
+        if let Some(index) = on_board.iter().position(|&(_, shown, _)| shown == piece) {
+            let (entity, ..) = on_board.swap_remove(index);
+            place_piece_entity(pieces, entity, piece, square, &positions);
+        } else if let Some(index) = undone_captures.iter().position(|&(_, shown, _)| shown == piece) {
+            let (entity, ..) = undone_captures.remove(index);
+            let translation = positions.get(&square).copied().unwrap_or(Vec3::Z * Z_PIECES);
+            commands.entity(entity)
+                .remove::<CapturedPiece>()
+                .insert((Piece { pos: square, color: piece.color, role: piece.role }, Transform::from_translation(translation), Visibility::Inherited));
+        } else {
+            changing_role.push((square, piece));
+        }
+    }
+
+    // A promotion taken back or played again changes the role of the piece,
+    // anything else is a new piece
+    for (square, piece) in changing_role {
+        let promotion = on_board.iter().position(|&(_, shown, _)| {
+            shown.color == piece.color && (shown.role == Role::Pawn || piece.role == Role::Pawn)
+        });
+        match promotion {
+            Some(index) => {
+                let (entity, ..) = on_board.swap_remove(index);
+                place_piece_entity(pieces, entity, piece, square, &positions);
+                commands.entity(entity).insert(asset_server.load::<Image>(piece_image_path(piece.color, piece.role)));
+            }
+            None => {
+                let translation = positions.get(&square).copied().unwrap_or(Vec3::Z * Z_PIECES);
+                spawn_piece(commands, asset_server, piece, square, translation, piece_ids);
+            }
+        }
+    }
+
+    for (entity, piece, square) in on_board {
+        let piece = Piece { pos: square, color: piece.color, role: piece.role };
+        capture_piece(commands, entity, &piece, ply);
+    }
+    for (entity, ..) in undone_captures {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Helper function to put a piece entity that is on the board onto `square` as `piece`
+fn place_piece_entity(pieces: &mut PieceEntities, entity: Entity, piece: shakmaty::Piece, square: Square, positions: &HashMap<Square, Vec3>) {
+    let Ok((_, Some(mut shown), _, mut transform, mut visibility)) = pieces.get_mut(entity) else {
+        return;
     };
-    
-    // Load the piece image
-    let image_path = format!("images/{}{}.png", color_prefix, role_suffix);
-    
-    // Spawn piece with image
+    shown.pos = square;
+    shown.role = piece.role;
+    if let Some(translation) = positions.get(&square) {
+        transform.translation = *translation;
+    }
+    *visibility = Visibility::Inherited;
+}
+
+// Helper function to spawn a piece entity with a new ID
+fn spawn_piece(commands: &mut Commands, asset_server: &AssetServer, piece: shakmaty::Piece, square: Square, translation: Vec3, piece_ids: &mut PieceIdAllocator) {
     commands.spawn((
         SpriteBundle {
-            texture: asset_server.load(&image_path),
-            transform: Transform::from_translation(position),
+            texture: asset_server.load(piece_image_path(piece.color, piece.role)),
+            transform: Transform::from_translation(translation),
             sprite: Sprite {
                 custom_size: Some(Vec2::new(TILE_SIZE * 0.9, TILE_SIZE * 0.9)),
                 ..default()
@@ -617,8 +558,9 @@ fn spawn_single_piece(
         },
         Piece {
             pos: square,
-            color,
-            role,
+            color: piece.color,
+            role: piece.role,
         },
+        piece_ids.next_id(),
     ));
-} 
\ No newline at end of file
+}
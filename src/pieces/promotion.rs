@@ -0,0 +1,252 @@
+use bevy::prelude::*;
+use shakmaty::{Color as ChessColor, Move, Position, Role, Square};
+use crate::board::components::BoardSquare;
+use crate::constants::TILE_SIZE;
+use crate::game_logic::events::MakeMoveEvent;
+use crate::game_logic::state::{GameState, ActiveBoard};
+use crate::i18n::Localization;
+use crate::input::cursor::world_to_window;
+
+// Layout of the picker, in logical pixels
+const OPTION_SIZE: f32 = 72.0;
+const OPTION_GAP: f32 = 6.0;
+const PICKER_PADDING: f32 = 8.0;
+const TITLE_HEIGHT: f32 = 24.0;
+const PICKER_WIDTH: f32 = 4.0 * OPTION_SIZE + 3.0 * OPTION_GAP + 2.0 * PICKER_PADDING;
+const PICKER_HEIGHT: f32 = OPTION_SIZE + TITLE_HEIGHT + OPTION_GAP + 2.0 * PICKER_PADDING;
+
+const PICKER_COLOR: Color = Color::rgba(0.1, 0.1, 0.1, 0.95);
+const OPTION_COLOR: Color = Color::rgba(1.0, 1.0, 0.0, 0.7);
+const OPTION_HOVER_COLOR: Color = Color::rgba(1.0, 0.85, 0.2, 0.9);
+
+// Promotion choices in picker order, with the key that picks them
+const PROMOTION_CHOICES: [(Role, KeyCode); 4] = [
+    (Role::Queen, KeyCode::Q),
+    (Role::Rook, KeyCode::R),
+    (Role::Bishop, KeyCode::B),
+    (Role::Knight, KeyCode::N),
+];
+
+/// Resource with the promotion move waiting for the player to pick a piece.
+/// The move is the allowed move with its promotion role left out.
+#[derive(Resource, Debug, Default)]
+pub struct PendingPromotion(pub Option<Move>);
+
+/// Event sent when the player backs out of a promotion with Esc or a right click,
+/// so the pawn can be selected again
+pub struct PromotionCancelledEvent {
+    pub from: Square,
+}
+
+impl Event for PromotionCancelledEvent {}
+
+/// Run condition: true unless the promotion picker is open
+pub fn no_pending_promotion(pending: Res<PendingPromotion>) -> bool {
+    pending.0.is_none()
+}
+
+/// Marker for the root node of the promotion picker
+#[derive(Component)]
+pub struct PromotionUI;
+
+/// One of the pieces offered by the picker
+#[derive(Component)]
+pub struct PromotionOption {
+    role: Role,
+}
+
+/// Opens the picker when a promotion is pending and closes it once it is resolved.
+/// The picker sits in the window next to the promotion square, on the side
+/// facing the middle of the board, and is kept fully inside the window.
+#[allow(clippy::too_many_arguments)]
+pub fn show_promotion_picker(
+    mut commands: Commands,
+    pending: Res<PendingPromotion>,
+    pickers: Query<Entity, With<PromotionUI>>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    board_squares: Query<(&GlobalTransform, &BoardSquare)>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    windows: Query<&Window>,
+    asset_server: Res<AssetServer>,
+    localization: Res<Localization>,
+) {
+    for entity in pickers.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let (Some(chess_move), Ok(game_state)) = (&pending.0, boards.get_single()) else {
+        return;
+    };
+    let color = game_state.board.turn();
+
+    let (left, top) = match (windows.get_single(), cameras.get_single()) {
+        (Ok(window), Ok((camera, camera_transform))) => {
+            picker_position(chess_move, window, camera, camera_transform, &board_squares)
+        }
+        _ => (0.0, 0.0),
+    };
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(left),
+                top: Val::Px(top),
+                width: Val::Px(PICKER_WIDTH),
+                height: Val::Px(PICKER_HEIGHT),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(OPTION_GAP),
+                padding: UiRect::all(Val::Px(PICKER_PADDING)),
+                ..default()
+            },
+            background_color: PICKER_COLOR.into(),
+            z_index: ZIndex::Global(70), // Above the board UI, below the menus
+            ..default()
+        },
+        Interaction::default(), // To tell clicks on the picker from clicks elsewhere
+        PromotionUI,
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            localization.text("promotion-title"),
+            TextStyle { font_size: 18.0, color: Color::WHITE, ..default() },
+        ));
+        parent.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(OPTION_GAP),
+                ..default()
+            },
+            ..default()
+        }).with_children(|row| {
+            for (role, _) in PROMOTION_CHOICES {
+                row.spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(OPTION_SIZE),
+                            height: Val::Px(OPTION_SIZE),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: OPTION_COLOR.into(),
+                        ..default()
+                    },
+                    PromotionOption { role },
+                )).with_children(|button| {
+                    button.spawn(ImageBundle {
+                        image: UiImage::new(asset_server.load(piece_image_path(color, role))),
+                        style: Style {
+                            width: Val::Px(OPTION_SIZE - 8.0),
+                            height: Val::Px(OPTION_SIZE - 8.0),
+                            ..default()
+                        },
+                        ..default()
+                    });
+                });
+            }
+        });
+    });
+
+    println!("Spawned promotion UI for {:?}", chess_move.to());
+}
+
+/// Picks the promotion piece by click or with Q/R/B/N. Esc or a right click
+/// cancel the move and select the pawn again; a left click outside the picker
+/// cancels it too and is then handled like any other click on the board.
+pub fn handle_promotion_selection(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut mouse_buttons: ResMut<Input<MouseButton>>,
+    mut interactions: Query<(&Interaction, &PromotionOption, &mut BackgroundColor), Changed<Interaction>>,
+    pickers: Query<&Interaction, With<PromotionUI>>,
+    mut pending: ResMut<PendingPromotion>,
+    mut ev_make_move: EventWriter<MakeMoveEvent>,
+    mut ev_cancelled: EventWriter<PromotionCancelledEvent>,
+) {
+    let Some(chess_move) = pending.0.clone() else {
+        return;
+    };
+
+    let mut chosen = PROMOTION_CHOICES
+        .iter()
+        .find(|(_, key)| keys.just_pressed(*key))
+        .map(|(role, _)| *role);
+    for (interaction, option, mut background) in interactions.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => chosen = Some(option.role),
+            Interaction::Hovered => *background = OPTION_HOVER_COLOR.into(),
+            Interaction::None => *background = OPTION_COLOR.into(),
+        }
+    }
+
+    if let Some(role) = chosen {
+        println!("Selected promotion: {:?}", role);
+        let mut promotion_move = chess_move;
+        if let Move::Normal { promotion, .. } = &mut promotion_move {
+            *promotion = Some(role);
+        }
+        ev_make_move.send(MakeMoveEvent(promotion_move));
+        pending.0 = None;
+        // The click picked a piece, it mustn't also select whatever is under the picker
+        mouse_buttons.reset(MouseButton::Left);
+    } else if keys.just_pressed(KeyCode::Escape) || mouse_buttons.just_pressed(MouseButton::Right) {
+        println!("Promotion cancelled");
+        // Don't let the same key press open the pause menu
+        keys.reset(KeyCode::Escape);
+        pending.0 = None;
+        if let Some(from) = chess_move.from() {
+            ev_cancelled.send(PromotionCancelledEvent { from });
+        }
+    } else if mouse_buttons.just_pressed(MouseButton::Left)
+        // The click that opened the picker comes before the picker exists
+        && pickers.get_single().is_ok_and(|interaction| *interaction == Interaction::None)
+    {
+        println!("Promotion cancelled (clicked elsewhere)");
+        pending.0 = None;
+    }
+}
+
+fn piece_image_path(color: ChessColor, role: Role) -> String {
+    let color_prefix = match color {
+        ChessColor::White => "w",
+        ChessColor::Black => "b",
+    };
+    format!("images/{}{}.png", color_prefix, role.upper_char())
+}
+
+// Top-left corner of the picker: centered on the promotion square's column,
+// just past the square towards the middle of the board, clamped to the window
+fn picker_position(
+    chess_move: &Move,
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    board_squares: &Query<(&GlobalTransform, &BoardSquare)>,
+) -> (f32, f32) {
+    let max_left = (window.width() - PICKER_WIDTH).max(0.0);
+    let max_top = (window.height() - PICKER_HEIGHT).max(0.0);
+    let centered = (max_left / 2.0, max_top / 2.0);
+
+    let Some(square_center) = board_squares
+        .iter()
+        .find(|(_, board_square)| board_square.square == chess_move.to())
+        .map(|(transform, _)| transform.translation())
+    else {
+        return centered;
+    };
+    // Both edges of the square, since the board orientation decides which one faces inwards
+    let edge = Vec3::new(0.0, TILE_SIZE / 2.0, 0.0);
+    let (Some(upper), Some(lower)) = (
+        world_to_window(square_center + edge, camera, camera_transform),
+        world_to_window(square_center - edge, camera, camera_transform),
+    ) else {
+        return centered;
+    };
+
+    let left = upper.x - PICKER_WIDTH / 2.0;
+    let top = if upper.y < window.height() / 2.0 {
+        lower.y // Square in the top half: open downwards
+    } else {
+        upper.y - PICKER_HEIGHT // Square in the bottom half: open upwards
+    };
+    (left.clamp(0.0, max_left), top.clamp(0.0, max_top))
+}
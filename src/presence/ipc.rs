@@ -0,0 +1,129 @@
+use std::error::Error;
+use std::io::{Read, Write};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+// Opcodes of Discord's local IPC protocol
+const OP_HANDSHAKE: u32 = 0;
+const OP_FRAME: u32 = 1;
+
+// How long to wait before trying again when Discord isn't running
+const RECONNECT_DELAY: Duration = Duration::from_secs(15);
+
+/// What to show in the Discord status
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Activity {
+    pub details: String,    // First line, e.g. "Move 24"
+    pub state: String,      // Second line, e.g. "Playing with 'No Castling' drawback"
+    pub start_timestamp: u64, // Unix seconds, shown as "elapsed"
+}
+
+trait IpcStream: Read + Write + Send {}
+impl<T: Read + Write + Send> IpcStream for T {}
+
+/// Runs on its own thread: keeps a connection to the Discord client and
+/// sends it every activity that arrives, until the game closes the channel
+pub fn run_presence_client(client_id: String, activities: Receiver<Activity>) {
+    let mut latest: Option<Activity> = None;
+    let mut nonce: u64 = 0;
+
+    loop {
+        let mut connection = match connect(&client_id) {
+            Ok(connection) => connection,
+            Err(_) => {
+                // Discord not running (yet); keep only the newest activity while waiting
+                match activities.recv_timeout(RECONNECT_DELAY) {
+                    Ok(activity) => latest = Some(activity),
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+                continue;
+            }
+        };
+        println!("Connected to Discord for Rich Presence");
+
+        loop {
+            if let Some(activity) = latest.take() {
+                nonce += 1;
+                if let Err(e) = set_activity(&mut connection, &activity, nonce) {
+                    eprintln!("Lost the Discord connection: {}", e);
+                    latest = Some(activity);
+                    break;
+                }
+            }
+            match activities.recv() {
+                Ok(activity) => latest = Some(activity),
+                Err(_) => return,
+            }
+        }
+    }
+}
+
+// Opens the first Discord IPC socket that accepts us and performs the handshake
+fn connect(client_id: &str) -> Result<Box<dyn IpcStream>, Box<dyn Error>> {
+    let mut last_error: Box<dyn Error> = "no Discord IPC socket found".into();
+    for index in 0..10 {
+        match open_socket(index) {
+            Ok(mut stream) => {
+                let handshake = serde_json::json!({ "v": 1, "client_id": client_id });
+                write_frame(&mut stream, OP_HANDSHAKE, &handshake.to_string())?;
+                read_frame(&mut stream)?; // READY
+                return Ok(stream);
+            }
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+#[cfg(unix)]
+fn open_socket(index: u32) -> Result<Box<dyn IpcStream>, Box<dyn Error>> {
+    let directory = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok())
+        .unwrap_or_else(|| "/tmp".to_string());
+    let path = std::path::Path::new(&directory).join(format!("discord-ipc-{}", index));
+    Ok(Box::new(std::os::unix::net::UnixStream::connect(path)?))
+}
+
+#[cfg(windows)]
+fn open_socket(index: u32) -> Result<Box<dyn IpcStream>, Box<dyn Error>> {
+    let path = format!(r"\\.\pipe\discord-ipc-{}", index);
+    Ok(Box::new(std::fs::OpenOptions::new().read(true).write(true).open(path)?))
+}
+
+fn set_activity(stream: &mut Box<dyn IpcStream>, activity: &Activity, nonce: u64) -> Result<(), Box<dyn Error>> {
+    let command = serde_json::json!({
+        "cmd": "SET_ACTIVITY",
+        "args": {
+            "pid": std::process::id(),
+            "activity": {
+                "details": activity.details,
+                "state": activity.state,
+                "timestamps": { "start": activity.start_timestamp },
+            },
+        },
+        "nonce": nonce.to_string(),
+    });
+    write_frame(stream, OP_FRAME, &command.to_string())?;
+    read_frame(stream)?;
+    Ok(())
+}
+
+// Frames are a little-endian opcode and length followed by JSON
+fn write_frame(stream: &mut impl Write, opcode: u32, json: &str) -> Result<(), std::io::Error> {
+    let mut frame = Vec::with_capacity(8 + json.len());
+    frame.extend_from_slice(&opcode.to_le_bytes());
+    frame.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    frame.extend_from_slice(json.as_bytes());
+    stream.write_all(&frame)
+}
+
+fn read_frame(stream: &mut impl Read) -> Result<String, std::io::Error> {
+    let mut header = [0u8; 8];
+    stream.read_exact(&mut header)?;
+    let length = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+    let mut body = vec![0u8; length];
+    stream.read_exact(&mut body)?;
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
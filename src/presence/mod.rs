@@ -0,0 +1,4 @@
+pub mod plugin;
+pub mod ipc;
+
+pub use plugin::PresencePlugin;
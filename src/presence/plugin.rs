@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+use std::sync::mpsc::{channel, Sender};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::config::GameConfig;
+use crate::drawbacks::{DrawbackRegistry, DrawbackId};
+use crate::game_logic::history::MoveHistory;
+use crate::game_logic::state::{GameState, ActiveBoard, GameStatus};
+use crate::stats::plugin::{drawback_name, human_color};
+use super::ipc::{Activity, run_presence_client};
+
+/// Resource present while Discord Rich Presence is enabled
+#[derive(Resource)]
+pub struct DiscordPresence {
+    sender: Sender<Activity>,
+    start_timestamp: u64,
+    last: Option<Activity>,
+}
+
+/// Plugin showing the running game as Discord Rich Presence
+pub struct PresencePlugin;
+
+impl Plugin for PresencePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, start_discord_presence)
+           .add_systems(
+               Update,
+               update_discord_presence.run_if(resource_exists::<DiscordPresence>())
+           );
+    }
+}
+
+/// Startup system: connects to Discord in the background if the configuration enables it
+fn start_discord_presence(mut commands: Commands, config: Res<GameConfig>) {
+    let settings = &config.integrations;
+    if !settings.discord_presence {
+        return;
+    }
+    if settings.discord_client_id.is_empty() {
+        eprintln!("Discord presence is enabled but no discord_client_id is configured");
+        return;
+    }
+
+    let (sender, receiver) = channel();
+    let client_id = settings.discord_client_id.clone();
+    std::thread::spawn(move || run_presence_client(client_id, receiver));
+
+    commands.insert_resource(DiscordPresence {
+        sender,
+        start_timestamp: unix_now(),
+        last: None,
+    });
+}
+
+/// System to send the current move and drawback to Discord whenever they change
+fn update_discord_presence(
+    mut presence: ResMut<DiscordPresence>,
+    boards: Query<Ref<GameState>, With<ActiveBoard>>,
+    history: Res<MoveHistory>,
+    config: Res<GameConfig>,
+    registry: Res<DrawbackRegistry>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    if !game_state.is_changed() && !history.is_changed() {
+        return;
+    }
+    if history.is_empty() && history.is_changed() {
+        // A new game started, restart the elapsed time
+        presence.start_timestamp = unix_now();
+    }
+
+    let activity = Activity {
+        details: match game_state.status {
+            GameStatus::Ongoing => format!("Move {}", history.len() / 2 + 1),
+            GameStatus::Finished(_) => format!("Game over after {} moves", history.len().div_ceil(2)),
+        },
+        state: drawback_state(&game_state, &config, &registry),
+        start_timestamp: presence.start_timestamp,
+    };
+    if presence.last.as_ref() == Some(&activity) {
+        return;
+    }
+
+    // The client thread only stops when the resource is dropped
+    let _ = presence.sender.send(activity.clone());
+    presence.last = Some(activity);
+}
+
+// "Playing with 'No Castling' drawback", from the human's point of view
+fn drawback_state(game_state: &GameState, config: &GameConfig, registry: &DrawbackRegistry) -> String {
+    let drawback = match human_color(config).unwrap_or(shakmaty::Color::White) {
+        shakmaty::Color::White => game_state.white_drawback,
+        shakmaty::Color::Black => game_state.black_drawback,
+    };
+    if drawback == DrawbackId::None {
+        "Playing without a drawback".to_string()
+    } else {
+        format!("Playing with '{}' drawback", drawback_name(registry, drawback))
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
@@ -0,0 +1,177 @@
+use bevy::prelude::*;
+use shakmaty::{Color as ChessColor, Position, Role};
+use crate::drawbacks::{DrawbackRegistry, DrawbackId};
+use crate::game_logic::events::GameOverReason;
+use crate::game_logic::history::MoveHistory;
+use crate::i18n::Localization;
+use crate::modes::ladder::ladder_rungs;
+use super::store::{PlayerStats, PlayerResult};
+
+// A win in at most this many of your own moves counts as quick
+const QUICK_WIN_MOVES: usize = 20;
+// Days in a row needed for the daily streak achievement
+const DAILY_STREAK_TARGET: u32 = 7;
+
+/// Every achievement that can be unlocked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Achievement {
+    FirstWin,
+    WinWithDrawback(DrawbackId),
+    WinWithEveryDrawback,
+    WinOnTime,
+    KnightPromotionMate,
+    SurviveRandomFile,
+    QuickWin,
+    DailyWin,
+    DailyStreak,
+    LadderChampion,
+}
+
+impl Achievement {
+    /// Stable key used in the stats file
+    pub fn key(&self) -> String {
+        match self {
+            Self::FirstWin => "first_win".to_string(),
+            Self::WinWithDrawback(id) => format!("win_with_drawback_{}", id.to_key_index()),
+            Self::WinWithEveryDrawback => "win_with_every_drawback".to_string(),
+            Self::WinOnTime => "win_on_time".to_string(),
+            Self::KnightPromotionMate => "knight_promotion_mate".to_string(),
+            Self::SurviveRandomFile => "survive_random_file".to_string(),
+            Self::QuickWin => "quick_win".to_string(),
+            Self::DailyWin => "daily_win".to_string(),
+            Self::DailyStreak => "daily_streak".to_string(),
+            Self::LadderChampion => "ladder_champion".to_string(),
+        }
+    }
+
+    // Localization key shared by the achievement's name and description
+    fn text_key(&self) -> &'static str {
+        match self {
+            Self::FirstWin => "first-win",
+            Self::WinWithDrawback(_) => "win-with-drawback",
+            Self::WinWithEveryDrawback => "win-with-every-drawback",
+            Self::WinOnTime => "win-on-time",
+            Self::KnightPromotionMate => "knight-promotion-mate",
+            Self::SurviveRandomFile => "survive-random-file",
+            Self::QuickWin => "quick-win",
+            Self::DailyWin => "daily-win",
+            Self::DailyStreak => "daily-streak",
+            Self::LadderChampion => "ladder-champion",
+        }
+    }
+
+    // Values for the placeables in the achievement's strings
+    fn text_args(&self, localization: &Localization, registry: &DrawbackRegistry) -> Vec<(&'static str, String)> {
+        match self {
+            Self::WinWithDrawback(id) => vec![("drawback", localization.drawback_name(registry, *id))],
+            Self::SurviveRandomFile => vec![("drawback", localization.drawback_name(registry, DrawbackId::BlockRandomFile))],
+            Self::QuickWin => vec![("moves", QUICK_WIN_MOVES.to_string())],
+            Self::DailyStreak => vec![("days", DAILY_STREAK_TARGET.to_string())],
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn name(&self, localization: &Localization, registry: &DrawbackRegistry) -> String {
+        let key = format!("achievement-{}-name", self.text_key());
+        localization.text_with(&key, &self.text_args(localization, registry))
+    }
+
+    pub fn description(&self, localization: &Localization, registry: &DrawbackRegistry) -> String {
+        let key = format!("achievement-{}-description", self.text_key());
+        localization.text_with(&key, &self.text_args(localization, registry))
+    }
+}
+
+/// All achievements in display order (one "win with" per registered drawback)
+pub fn all_achievements(registry: &DrawbackRegistry) -> Vec<Achievement> {
+    let mut achievements = vec![Achievement::FirstWin];
+    achievements.extend(registry.sorted_ids().into_iter().map(Achievement::WinWithDrawback));
+    achievements.extend([
+        Achievement::WinWithEveryDrawback,
+        Achievement::WinOnTime,
+        Achievement::KnightPromotionMate,
+        Achievement::SurviveRandomFile,
+        Achievement::QuickWin,
+        Achievement::DailyWin,
+        Achievement::DailyStreak,
+        Achievement::LadderChampion,
+    ]);
+    achievements
+}
+
+/// What we know about a game that just ended, from the human's point of view
+pub struct FinishedGame<'a> {
+    pub reason: GameOverReason,
+    pub result: PlayerResult,
+    pub human: ChessColor,
+    pub human_drawback: DrawbackId,
+    pub history: &'a MoveHistory,
+    pub daily: bool,
+}
+
+impl FinishedGame<'_> {
+    // Moves played by the human, in order
+    fn human_moves(&self) -> impl Iterator<Item = &shakmaty::Move> {
+        let first_mover = self.history.start_position.turn();
+        self.history.moves.iter().enumerate().filter_map(move |(ply, record)| {
+            let mover = if ply % 2 == 0 { first_mover } else { !first_mover };
+            (mover == self.human).then_some(&record.chess_move)
+        })
+    }
+}
+
+/// Achievements earned by the game that just ended (including ones already unlocked).
+/// `stats` must already include this game's result.
+pub fn earned_achievements(game: &FinishedGame, stats: &PlayerStats, registry: &DrawbackRegistry) -> Vec<Achievement> {
+    let mut earned = Vec::new();
+    let won = game.result == PlayerResult::Win;
+
+    if won {
+        earned.push(Achievement::FirstWin);
+        if game.human_drawback != DrawbackId::None {
+            earned.push(Achievement::WinWithDrawback(game.human_drawback));
+        }
+        if matches!(game.reason, GameOverReason::Timeout { .. }) {
+            earned.push(Achievement::WinOnTime);
+        }
+        if matches!(game.reason, GameOverReason::Checkmate { .. })
+            && game.human_moves().any(|m| m.promotion() == Some(Role::Knight))
+        {
+            earned.push(Achievement::KnightPromotionMate);
+        }
+        if game.human_moves().count() <= QUICK_WIN_MOVES {
+            earned.push(Achievement::QuickWin);
+        }
+        if game.daily {
+            earned.push(Achievement::DailyWin);
+        }
+    }
+
+    if game.human_drawback == DrawbackId::BlockRandomFile && game.result != PlayerResult::Loss {
+        earned.push(Achievement::SurviveRandomFile);
+    }
+
+    if game.daily && stats.daily.current_streak >= DAILY_STREAK_TARGET {
+        earned.push(Achievement::DailyStreak);
+    }
+
+    if stats.ladder.rungs_beaten >= ladder_rungs().len() {
+        earned.push(Achievement::LadderChampion);
+    }
+
+    // Every drawback won with (counting this game)
+    let all_drawbacks_won = registry.sorted_ids().into_iter().all(|id| {
+        let achievement = Achievement::WinWithDrawback(id);
+        earned.contains(&achievement) || stats.has_achievement(&achievement.key())
+    });
+    if all_drawbacks_won {
+        earned.push(Achievement::WinWithEveryDrawback);
+    }
+
+    earned
+}
+
+/// Event sent when an achievement is unlocked for the first time
+pub struct AchievementUnlockedEvent(pub Achievement);
+
+impl Event for AchievementUnlockedEvent {}
@@ -0,0 +1,5 @@
+pub mod plugin;
+pub mod store;
+pub mod achievements;
+
+pub use plugin::StatsPlugin;
@@ -0,0 +1,171 @@
+use bevy::prelude::*;
+use shakmaty::Color as ChessColor;
+use crate::config::GameConfig;
+use crate::drawbacks::{DrawbackRegistry, DrawbackId};
+use crate::game_logic::events::GameOverEvent;
+use crate::game_logic::state::{GameState, ActiveBoard};
+use crate::game_logic::history::MoveHistory;
+use crate::modes::daily::{DailyChallenge, previous_date, today_utc};
+use crate::modes::ladder::record_ladder_result;
+use crate::ai::fallback::{AiFallbackEvent, AiFallbackReason};
+use super::store::{PlayerStats, PlayerResult, DailyRecord, STATS_FILE_PATH};
+use super::achievements::{AchievementUnlockedEvent, FinishedGame, earned_achievements};
+
+/// Plugin that keeps the local win/loss statistics up to date
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        let stats = PlayerStats::load(STATS_FILE_PATH).unwrap_or_else(|e| {
+            eprintln!("Failed to read {}: {} (starting with empty stats)", STATS_FILE_PATH, e);
+            PlayerStats::default()
+        });
+
+        app.insert_resource(stats)
+           .add_event::<AchievementUnlockedEvent>()
+           .add_systems(Update, (record_game_result, record_ai_fallbacks))
+           // Achievements look at the streak and ladder progress, so update those first
+           .add_systems(
+               Update,
+               evaluate_achievements
+                   .after(record_game_result)
+                   .after(record_ladder_result)
+           );
+    }
+}
+
+/// System to record the result of every finished game against the AI
+pub fn record_game_result(
+    mut ev_game_over: EventReader<GameOverEvent>,
+    config: Res<GameConfig>,
+    registry: Res<DrawbackRegistry>,
+    daily: Option<Res<DailyChallenge>>,
+    mut stats: ResMut<PlayerStats>,
+) {
+    for ev in ev_game_over.read() {
+        // Only games with exactly one human player have a "you" to record
+        let Some(human) = human_color(&config) else {
+            continue;
+        };
+
+        let result = match ev.0.winner {
+            Some(winner) if winner == human => PlayerResult::Win,
+            Some(_) => PlayerResult::Loss,
+            None => PlayerResult::Draw,
+        };
+        stats.record_game(result);
+        println!("Recorded {:?} ({})", result, ev.0.reason);
+
+        if let Some(challenge) = daily.as_deref() {
+            let record = DailyRecord {
+                date: challenge.date.clone(),
+                player_drawback: drawback_name(&registry, challenge.player_drawback),
+                opponent_drawback: drawback_name(&registry, challenge.opponent_drawback),
+                result,
+            };
+            if stats.record_daily(record, previous_date(&challenge.date).as_deref()) {
+                println!("Daily challenge streak: {} day(s)", stats.daily.current_streak);
+            } else {
+                println!("Today's daily challenge was already recorded; this attempt doesn't count");
+            }
+        }
+
+        if let Err(e) = stats.save(STATS_FILE_PATH) {
+            eprintln!("Failed to save {}: {}", STATS_FILE_PATH, e);
+        }
+    }
+}
+
+/// System to count the AI's fallback moves, so engine bugs show up in the stats
+fn record_ai_fallbacks(mut ev_fallback: EventReader<AiFallbackEvent>, mut stats: ResMut<PlayerStats>) {
+    let mut counted = false;
+    for ev in ev_fallback.read() {
+        match ev.reason {
+            AiFallbackReason::InvalidMove(_) => stats.ai_fallbacks.invalid_moves += 1,
+            AiFallbackReason::NoMove => stats.ai_fallbacks.no_moves += 1,
+            AiFallbackReason::Crashed(_) => stats.ai_fallbacks.crashes += 1,
+        }
+        eprintln!("AI fallback ({:?}) after {:?}: played {:?}", ev.policy, ev.reason, ev.played);
+        counted = true;
+    }
+
+    if counted {
+        if let Err(e) = stats.save(STATS_FILE_PATH) {
+            eprintln!("Failed to save {}: {}", STATS_FILE_PATH, e);
+        }
+    }
+}
+
+/// System to unlock achievements once a game against the AI has ended
+#[allow(clippy::too_many_arguments)]
+fn evaluate_achievements(
+    mut ev_game_over: EventReader<GameOverEvent>,
+    mut ev_unlocked: EventWriter<AchievementUnlockedEvent>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    history: Res<MoveHistory>,
+    config: Res<GameConfig>,
+    registry: Res<DrawbackRegistry>,
+    daily: Option<Res<DailyChallenge>>,
+    mut stats: ResMut<PlayerStats>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    for ev in ev_game_over.read() {
+        let Some(human) = human_color(&config) else {
+            continue;
+        };
+
+        let result = match ev.0.winner {
+            Some(winner) if winner == human => PlayerResult::Win,
+            Some(_) => PlayerResult::Loss,
+            None => PlayerResult::Draw,
+        };
+        let game = FinishedGame {
+            reason: ev.0.reason,
+            result,
+            human,
+            human_drawback: match human {
+                ChessColor::White => game_state.white_drawback,
+                ChessColor::Black => game_state.black_drawback,
+            },
+            history: &history,
+            daily: daily.is_some(),
+        };
+
+        let today = today_utc();
+        let mut unlocked_any = false;
+        for achievement in earned_achievements(&game, &stats, &registry) {
+            if stats.unlock_achievement(achievement.key(), today.clone()) {
+                println!("Achievement unlocked: {}", achievement.key());
+                ev_unlocked.send(AchievementUnlockedEvent(achievement));
+                unlocked_any = true;
+            }
+        }
+
+        if unlocked_any {
+            if let Err(e) = stats.save(STATS_FILE_PATH) {
+                eprintln!("Failed to save {}: {}", STATS_FILE_PATH, e);
+            }
+        }
+    }
+}
+
+/// The human's side in a game against the AI (None for AI vs AI or two humans)
+pub fn human_color(config: &GameConfig) -> Option<ChessColor> {
+    match (config.white_player.is_ai, config.black_player.is_ai) {
+        (false, true) => Some(ChessColor::White),
+        (true, false) => Some(ChessColor::Black),
+        _ => None,
+    }
+}
+
+/// Built-in (English) name of a drawback, "None" if the player had none.
+/// Used where the name is stored; the UI goes through `Localization` instead.
+pub fn drawback_name(registry: &DrawbackRegistry, id: DrawbackId) -> String {
+    registry
+        .rules
+        .get(&id)
+        .map(|rule| rule.name().to_string())
+        .unwrap_or_else(|| "None".to_string())
+}
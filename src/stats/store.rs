@@ -0,0 +1,185 @@
+use bevy::prelude::*;
+use serde::{Serialize, Deserialize};
+use std::error::Error;
+
+/// File the local statistics are stored in (next to the config file)
+pub const STATS_FILE_PATH: &str = "drawback_chess_stats.json";
+
+/// Result of a finished game from the human player's point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlayerResult {
+    Win,
+    Loss,
+    Draw,
+}
+
+/// A finished daily challenge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyRecord {
+    pub date: String,          // YYYY-MM-DD (UTC)
+    pub player_drawback: String,
+    pub opponent_drawback: String,
+    pub result: PlayerResult,
+}
+
+/// Daily challenge results and streak
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DailyStats {
+    pub current_streak: u32, // Consecutive days with a finished challenge
+    pub best_streak: u32,
+    pub history: Vec<DailyRecord>,
+}
+
+/// Progress through the single player ladder
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LadderProgress {
+    pub rungs_beaten: usize, // Rungs 0..rungs_beaten are beaten; rung `rungs_beaten` is the next one
+}
+
+impl LadderProgress {
+    pub fn is_unlocked(&self, rung: usize) -> bool {
+        rung <= self.rungs_beaten
+    }
+
+    pub fn is_beaten(&self, rung: usize) -> bool {
+        rung < self.rungs_beaten
+    }
+}
+
+/// Result of a game between two AI players (logged by the auto rollover)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiMatchRecord {
+    pub date: String,            // YYYY-MM-DD (UTC)
+    pub white_drawback: String,
+    pub black_drawback: String,
+    pub result: String,          // "1-0", "0-1" or "1/2-1/2"
+    pub reason: String,
+    pub plies: usize,
+}
+
+/// A finished best-of-N arena match. Player 1 is the one configured as White
+/// when the match started.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArenaMatchRecord {
+    pub date: String,            // YYYY-MM-DD (UTC)
+    pub best_of: u32,
+    pub player_one: String,      // "Human" or "AI"
+    pub player_two: String,
+    pub score_one: f32,          // Points, a draw is worth half a point
+    pub score_two: f32,
+    pub results: Vec<String>,    // Every game from player 1's side: "1-0", "0-1" or "1/2-1/2"
+}
+
+/// How often the AI's move had to be replaced by its fallback policy
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AiFallbackStats {
+    pub invalid_moves: u32, // The engine returned a move that wasn't allowed
+    pub no_moves: u32,      // The engine returned nothing although moves were left
+    #[serde(default)]
+    pub crashes: u32,       // The search panicked
+}
+
+/// An unlocked achievement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnlockedAchievement {
+    pub key: String,
+    pub date: String, // YYYY-MM-DD (UTC) it was unlocked on
+}
+
+/// Resource holding the locally persisted player statistics
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlayerStats {
+    #[serde(default)]
+    pub games_played: u32,
+    #[serde(default)]
+    pub wins: u32,
+    #[serde(default)]
+    pub losses: u32,
+    #[serde(default)]
+    pub draws: u32,
+    #[serde(default)]
+    pub daily: DailyStats,
+    #[serde(default)]
+    pub ladder: LadderProgress,
+    #[serde(default)]
+    pub achievements: Vec<UnlockedAchievement>,
+    #[serde(default)]
+    pub ai_matches: Vec<AiMatchRecord>,
+    #[serde(default)]
+    pub ai_fallbacks: AiFallbackStats,
+    #[serde(default)]
+    pub arena_matches: Vec<ArenaMatchRecord>,
+}
+
+impl PlayerStats {
+    /// Loads the stats file, starting fresh if it doesn't exist yet
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Ok(serde_json::from_str(&text)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Counts a finished game in the totals
+    pub fn record_game(&mut self, result: PlayerResult) {
+        self.games_played += 1;
+        match result {
+            PlayerResult::Win => self.wins += 1,
+            PlayerResult::Loss => self.losses += 1,
+            PlayerResult::Draw => self.draws += 1,
+        }
+    }
+
+    pub fn has_achievement(&self, key: &str) -> bool {
+        self.achievements.iter().any(|achievement| achievement.key == key)
+    }
+
+    /// Unlocks an achievement; returns false if it was already unlocked
+    pub fn unlock_achievement(&mut self, key: String, date: String) -> bool {
+        if self.has_achievement(&key) {
+            return false;
+        }
+        self.achievements.push(UnlockedAchievement { key, date });
+        true
+    }
+
+    /// Result of the daily challenge for `date`, if it was already played
+    pub fn daily_result(&self, date: &str) -> Option<&DailyRecord> {
+        self.daily.history.iter().find(|record| record.date == date)
+    }
+
+    /// Records a daily challenge result and updates the streak.
+    /// Only the first finished attempt of each day counts.
+    /// `previous_date` is the calendar day before `record.date`.
+    pub fn record_daily(&mut self, record: DailyRecord, previous_date: Option<&str>) -> bool {
+        if self.daily_result(&record.date).is_some() {
+            return false;
+        }
+
+        let continues_streak = match (self.daily.history.last(), previous_date) {
+            (Some(last), Some(previous)) => last.date == previous,
+            _ => false,
+        };
+
+        self.daily.current_streak = if continues_streak { self.daily.current_streak + 1 } else { 1 };
+        self.daily.best_streak = self.daily.best_streak.max(self.daily.current_streak);
+        self.daily.history.push(record);
+        true
+    }
+}
+
+impl DailyStats {
+    /// The streak as it stands today: it is broken once a whole day was missed
+    pub fn active_streak(&self, today: &str, yesterday: Option<&str>) -> u32 {
+        match self.history.last() {
+            Some(last) if last.date == today || Some(last.date.as_str()) == yesterday => self.current_streak,
+            _ => 0,
+        }
+    }
+}
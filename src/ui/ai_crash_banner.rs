@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+use crate::ai::crash_report::CRASH_REPORT_PATH;
+use crate::ai::fallback::{AiFallbackEvent, AiFallbackReason};
+use crate::game_logic::events::NewGameEvent;
+use crate::game_logic::notation::format_uci;
+use crate::i18n::Localization;
+
+// How long the banner stays up
+const BANNER_SECONDS: f32 = 8.0;
+
+/// Marker for the banner shown when the AI's search crashed
+#[derive(Component, Default)]
+pub struct AiCrashBanner {
+    shown_at: Option<f32>, // Elapsed seconds when it was last shown
+}
+
+/// Spawns the (initially hidden) banner at the bottom of the screen
+pub fn setup_ai_crash_banner(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(1.0, 0.45, 0.4),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(8.0),
+            left: Val::Px(8.0),
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.7)),
+        Visibility::Hidden,
+        AiCrashBanner::default(),
+    ));
+}
+
+/// Shows for a few seconds that the AI crashed and which move was played for
+/// it instead. The game goes on; the report file has what's needed to
+/// reproduce the crash.
+pub fn update_ai_crash_banner(
+    mut ev_fallback: EventReader<AiFallbackEvent>,
+    mut ev_new_game: EventReader<NewGameEvent>,
+    localization: Res<Localization>,
+    time: Res<Time>,
+    mut banner: Query<(&mut Text, &mut Visibility, &mut AiCrashBanner)>,
+) {
+    let Ok((mut text, mut visibility, mut banner)) = banner.get_single_mut() else {
+        return;
+    };
+    let now = time.elapsed_seconds();
+
+    for ev in ev_fallback.read() {
+        if let AiFallbackReason::Crashed(_) = ev.reason {
+            let played = ev.played.as_ref().map_or_else(|| "-".to_string(), format_uci);
+            text.sections[0].value = localization.text_with("ai-crashed", &[("move", played), ("path", CRASH_REPORT_PATH.to_string())]);
+            *visibility = Visibility::Visible;
+            banner.shown_at = Some(now);
+        }
+    }
+
+    let expired = banner.shown_at.is_some_and(|shown_at| now - shown_at > BANNER_SECONDS);
+    if ev_new_game.read().count() > 0 || expired {
+        *visibility = Visibility::Hidden;
+        banner.shown_at = None;
+    }
+}
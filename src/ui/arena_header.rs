@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use shakmaty::Color as ChessColor;
+use crate::config::GameConfig;
+use crate::i18n::Localization;
+use crate::modes::arena::ArenaMatch;
+
+/// Marker for the match score header
+#[derive(Component)]
+pub struct ArenaHeaderText;
+
+/// Spawns the match score header in the top-right corner when an arena match is on
+pub fn setup_arena_header(mut commands: Commands, config: Res<GameConfig>) {
+    if !config.arena.enabled {
+        return;
+    }
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(0.6, 0.85, 1.0),
+                ..default()
+            },
+        )
+        .with_text_alignment(TextAlignment::Right)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            right: Val::Px(8.0),
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.5)),
+        ArenaHeaderText,
+    ));
+}
+
+/// Shows the score of the match, who plays which color and, once it is over, who won
+pub fn update_arena_header(
+    arena: Res<ArenaMatch>,
+    config: Res<GameConfig>,
+    localization: Res<Localization>,
+    mut header: Query<&mut Text, With<ArenaHeaderText>>,
+) {
+    let Ok(mut text) = header.get_single_mut() else {
+        return;
+    };
+    if !arena.is_active() || !(arena.is_changed() || localization.is_changed()) {
+        return;
+    }
+
+    let player = |index: usize| {
+        let key = if arena.players[index].is_ai { "arena-ai" } else { "arena-human" };
+        let color = if arena.player_with(ChessColor::White) == index { ChessColor::White } else { ChessColor::Black };
+        localization.text_with("arena-player", &[
+            ("number", (index + 1).to_string()),
+            ("kind", localization.text(key)),
+            ("color", localization.color_name(color)),
+        ])
+    };
+    let mut lines = vec![
+        localization.text_with("arena-title", &[
+            ("games", config.arena.games.to_string()),
+            ("game", arena.game_number().to_string()),
+        ]),
+        localization.text_with("arena-score", &[
+            ("first", player(0)),
+            ("second", player(1)),
+            ("first_score", format_points(arena.scores[0])),
+            ("second_score", format_points(arena.scores[1])),
+        ]),
+    ];
+    if arena.finished {
+        lines.push(if arena.scores[0] == arena.scores[1] {
+            localization.text("arena-drawn")
+        } else {
+            let winner = if arena.scores[0] > arena.scores[1] { 1 } else { 2 };
+            localization.text_with("arena-won", &[("number", winner.to_string())])
+        });
+    }
+    text.sections[0].value = lines.join("\n");
+}
+
+// Points with a ½ for the draws, e.g. "2½"
+fn format_points(points: f32) -> String {
+    let whole = points.floor();
+    match (whole as u32, points > whole) {
+        (0, true) => "½".to_string(),
+        (whole, true) => format!("{}½", whole),
+        (whole, false) => whole.to_string(),
+    }
+}
@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+use shakmaty::Color as ChessColor;
+use crate::ai::opponent_model::{OpponentModel, BeliefSnapshot};
+use crate::config::GameConfig;
+use crate::drawbacks::{DrawbackRegistry, DrawbackId};
+use crate::i18n::Localization;
+
+// Candidates listed per player, and snapshots in the history line
+const SHOWN_CANDIDATES: usize = 4;
+const SHOWN_SNAPSHOTS: usize = 8;
+
+/// Whether the belief panel is shown (F8)
+#[derive(Resource, Default)]
+pub struct BeliefPanel {
+    pub shown: bool,
+}
+
+/// Marker for the root node of the belief panel
+#[derive(Component)]
+pub struct BeliefPanelRoot;
+
+/// Marker for the belief panel's text
+#[derive(Component)]
+pub struct BeliefPanelText;
+
+/// Spawns the (hidden) belief panel on the left of the board
+pub fn setup_belief_panel(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(35.0),
+                left: Val::Px(8.0),
+                max_width: Val::Px(320.0),
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        BeliefPanelRoot,
+    )).with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_section("", TextStyle { font_size: 15.0, color: Color::WHITE, ..default() }),
+            BeliefPanelText,
+        ));
+    });
+}
+
+/// Developer key: F8 shows or hides what the AI makes of the opponent's drawback
+pub fn toggle_belief_panel(keys: Res<Input<KeyCode>>, mut panel: ResMut<BeliefPanel>) {
+    if keys.just_pressed(KeyCode::F8) {
+        panel.shown = !panel.shown;
+    }
+}
+
+/// Shows the AI's belief about the drawback of every player it plays against,
+/// with how the likeliest candidate's probability moved over the game
+pub fn update_belief_panel(
+    panel: Res<BeliefPanel>,
+    model: Res<OpponentModel>,
+    config: Res<GameConfig>,
+    registry: Res<DrawbackRegistry>,
+    localization: Res<Localization>,
+    mut roots: Query<&mut Visibility, With<BeliefPanelRoot>>,
+    mut texts: Query<&mut Text, With<BeliefPanelText>>,
+) {
+    let Ok(mut visibility) = roots.get_single_mut() else {
+        return;
+    };
+
+    // Players whose opponent is an AI, i.e. whose drawback the AI has to guess
+    let modelled: Vec<ChessColor> = [ChessColor::White, ChessColor::Black]
+        .into_iter()
+        .filter(|&color| match color {
+            ChessColor::White => config.black_player.is_ai,
+            ChessColor::Black => config.white_player.is_ai,
+        })
+        .collect();
+    let shown = panel.shown && config.ai_settings.opponent_model && !modelled.is_empty();
+    visibility.set_if_neq(if shown { Visibility::Inherited } else { Visibility::Hidden });
+    if !shown || !(panel.is_changed() || model.is_changed() || localization.is_changed()) {
+        return;
+    }
+
+    let name = |id| localization.drawback_name(&registry, id);
+    let percent = |weight: f64| format!("{:.0}%", weight * 100.0);
+    let mut lines = Vec::new();
+    for color in modelled {
+        let candidates = model.beliefs.get(color).likeliest(&registry, SHOWN_CANDIDATES);
+        let list = candidates
+            .iter()
+            .map(|&(id, weight)| format!("{} {}", name(id), percent(weight)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        lines.push(localization.text_with("belief-likely", &[
+            ("color", localization.color_name(color)),
+            ("candidates", list),
+        ]));
+
+        let snapshots = model.history.get(color);
+        if let Some(&(leader, _)) = candidates.first() {
+            if !snapshots.is_empty() {
+                let trend = snapshots
+                    .iter()
+                    .skip(snapshots.len().saturating_sub(SHOWN_SNAPSHOTS))
+                    .map(|snapshot| format!("{}: {}", snapshot.ply, percent(weight_of(snapshot, leader))))
+                    .collect::<Vec<_>>()
+                    .join("  ");
+                lines.push(localization.text_with("belief-trend", &[
+                    ("drawback", name(leader)),
+                    ("trend", trend),
+                ]));
+            }
+        }
+        for snapshot in snapshots.iter().filter(|snapshot| !snapshot.ruled_out.is_empty()) {
+            let ruled_out = snapshot.ruled_out.iter().map(|&id| name(id)).collect::<Vec<_>>().join(", ");
+            lines.push(localization.text_with("belief-ruled-out", &[
+                ("ply", snapshot.ply.to_string()),
+                ("drawbacks", ruled_out),
+            ]));
+        }
+    }
+
+    if let Ok(mut text) = texts.get_single_mut() {
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+fn weight_of(snapshot: &BeliefSnapshot, id: DrawbackId) -> f64 {
+    snapshot.weights.iter().find(|(candidate, _)| *candidate == id).map_or(0.0, |(_, weight)| *weight)
+}
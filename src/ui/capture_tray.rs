@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use shakmaty::Color as ChessColor;
+use crate::game_logic::captures::CaptureLog;
+use crate::game_logic::state::{GameState, ActiveBoard};
+use crate::pieces::components::CapturedPiece;
+use super::reserve_tray::piece_image_path;
+
+const CAPTURED_PIECE_SIZE: f32 = 24.0;
+const LEAD_TEXT_COLOR: Color = Color::rgb(0.85, 0.85, 0.85);
+
+/// Marker for the root node of the captured pieces tray
+#[derive(Component)]
+pub struct CaptureTray;
+
+/// Row of the pieces one player has taken, followed by its material lead
+#[derive(Component, Debug)]
+pub struct CaptureRow {
+    pub color: ChessColor, // Player who took them
+}
+
+/// Spawns the (empty) tray on the left of the screen, one row per player
+pub fn setup_capture_tray(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(20.0),
+                left: Val::Px(8.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        CaptureTray,
+    )).with_children(|tray| {
+        for color in [ChessColor::Black, ChessColor::White] {
+            tray.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        min_height: Val::Px(CAPTURED_PIECE_SIZE),
+                        ..default()
+                    },
+                    ..default()
+                },
+                CaptureRow { color },
+            ));
+        }
+    });
+}
+
+/// Fills the rows with the captured piece entities (cheapest first) and the
+/// material lead of the player ahead. The side shown at the top of the board gets the top row.
+#[allow(clippy::too_many_arguments)]
+pub fn update_capture_tray(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    log: Res<CaptureLog>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    mut trays: Query<(&mut Visibility, &mut Style), With<CaptureTray>>,
+    rows: Query<(Entity, &CaptureRow)>,
+    captured: Query<&CapturedPiece>,
+    added: Query<(), Added<CapturedPiece>>,
+    mut removed: RemovedComponents<CapturedPiece>,
+) {
+    let (Ok(game_state), Ok((mut visibility, mut style))) = (boards.get_single(), trays.get_single_mut()) else {
+        return;
+    };
+    let shown = !captured.is_empty() || log.material != 0;
+    visibility.set_if_neq(if shown { Visibility::Inherited } else { Visibility::Hidden });
+
+    // The rows are spawned Black first
+    let direction = if game_state.board_flipped { FlexDirection::ColumnReverse } else { FlexDirection::Column };
+    if style.flex_direction != direction {
+        style.flex_direction = direction;
+    }
+    // Reading them all keeps old removals from counting next frame
+    let captures_changed = !added.is_empty() | (removed.read().count() > 0);
+    if !log.is_changed() && !captures_changed {
+        return;
+    }
+
+    for (entity, row) in rows.iter() {
+        let mut taken: Vec<_> = captured.iter().filter(|piece| piece.color != row.color).map(|piece| piece.role).collect();
+        taken.sort();
+        let lead = log.lead(row.color);
+        commands.entity(entity).despawn_descendants().with_children(|row_node| {
+            for role in taken {
+                row_node.spawn(ImageBundle {
+                    image: UiImage::new(asset_server.load(piece_image_path(!row.color, role))),
+                    style: Style {
+                        width: Val::Px(CAPTURED_PIECE_SIZE),
+                        height: Val::Px(CAPTURED_PIECE_SIZE),
+                        ..default()
+                    },
+                    ..default()
+                });
+            }
+            if lead > 0 {
+                row_node.spawn(TextBundle::from_section(
+                    format!("+{}", lead),
+                    TextStyle { font_size: 16.0, color: LEAD_TEXT_COLOR, ..default() },
+                ).with_style(Style { margin: UiRect::left(Val::Px(4.0)), ..default() }));
+            }
+        });
+    }
+}
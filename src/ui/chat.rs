@@ -0,0 +1,204 @@
+use bevy::prelude::*;
+use bevy::window::ReceivedCharacter;
+use crate::config::GameConfig;
+use crate::i18n::Localization;
+use crate::input::focus::TextInputFocus;
+use crate::net::chat::{ChatLog, SendChatEvent, MAX_CHAT_LENGTH};
+use crate::net::lockstep::NetSession;
+
+// Newest chat lines shown above the input line
+const SHOWN_CHAT_LINES: usize = 6;
+
+const CHAT_TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+const CHAT_HINT_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+const CHAT_BUTTON_COLOR: Color = Color::rgb(0.2, 0.2, 0.25);
+const CHAT_BUTTON_HOVER_COLOR: Color = Color::rgb(0.3, 0.3, 0.38);
+
+/// Resource with the line being typed into the chat (Enter starts and sends it)
+#[derive(Resource, Debug, Default)]
+pub struct ChatInput {
+    pub active: bool,
+    pub buffer: String,
+}
+
+/// Marker for the chat lines
+#[derive(Component)]
+pub struct ChatLinesText;
+
+/// Marker for the line being typed (or the hint how to start typing)
+#[derive(Component)]
+pub struct ChatInputText;
+
+/// Buttons under the chat
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatButton {
+    Emote(usize), // Index into the configured emotes
+    Mute,
+}
+
+/// Spawns the chat panel in the bottom-left corner when playing over the network
+pub fn setup_chat_panel(mut commands: Commands, session: Option<Res<NetSession>>, config: Res<GameConfig>) {
+    if session.is_none() {
+        return;
+    }
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(8.0),
+            left: Val::Px(8.0),
+            width: Val::Px(320.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+        ..default()
+    }).with_children(|parent| {
+        let text_style = TextStyle { font_size: 15.0, color: CHAT_TEXT_COLOR, ..default() };
+        parent.spawn((TextBundle::from_section("", text_style.clone()), ChatLinesText));
+        parent.spawn((TextBundle::from_section("", TextStyle { color: CHAT_HINT_COLOR, ..text_style }), ChatInputText));
+
+        parent.spawn(NodeBundle {
+            style: Style { flex_wrap: FlexWrap::Wrap, column_gap: Val::Px(4.0), row_gap: Val::Px(4.0), ..default() },
+            ..default()
+        }).with_children(|row| {
+            let buttons = config.network.emotes.iter().enumerate().map(|(i, emote)| (ChatButton::Emote(i), emote.clone()));
+            for (button, label) in buttons.chain([(ChatButton::Mute, String::new())]) {
+                row.spawn((
+                    ButtonBundle {
+                        style: Style { padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)), ..default() },
+                        background_color: CHAT_BUTTON_COLOR.into(),
+                        ..default()
+                    },
+                    button,
+                )).with_children(|button_parent| {
+                    button_parent.spawn(TextBundle::from_section(label, TextStyle { font_size: 14.0, color: Color::WHITE, ..default() }));
+                });
+            }
+        });
+    });
+}
+
+/// Enter starts typing a chat line
+pub fn open_chat_input(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut input: ResMut<ChatInput>,
+    mut focus: ResMut<TextInputFocus>,
+) {
+    if keys.just_pressed(KeyCode::Return) && !focus.0 {
+        input.active = true;
+        focus.0 = true;
+        // The same press would send the empty line right away
+        keys.reset(KeyCode::Return);
+    }
+}
+
+/// System handling text entry in the chat; Enter sends the line, Esc drops it
+pub fn edit_chat_input(
+    mut ev_chars: EventReader<ReceivedCharacter>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut input: ResMut<ChatInput>,
+    mut focus: ResMut<TextInputFocus>,
+    mut ev_send: EventWriter<SendChatEvent>,
+) {
+    if !input.active {
+        ev_chars.clear();
+        return;
+    }
+
+    for ev in ev_chars.read() {
+        if !ev.char.is_control() && input.buffer.chars().count() < MAX_CHAT_LENGTH {
+            input.buffer.push(ev.char);
+        }
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        input.buffer.pop();
+    }
+
+    if keys.just_pressed(KeyCode::Return) {
+        ev_send.send(SendChatEvent(std::mem::take(&mut input.buffer)));
+        input.active = false;
+        keys.reset(KeyCode::Return);
+    } else if keys.just_pressed(KeyCode::Escape) {
+        input.active = false;
+        // Don't let the same Esc open the pause menu
+        keys.reset(KeyCode::Escape);
+    }
+
+    if !input.active {
+        input.buffer.clear();
+        focus.0 = false;
+    }
+}
+
+/// Handles clicks on the emote and mute buttons
+pub fn handle_chat_buttons(
+    mut interactions: Query<(&Interaction, &ChatButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut log: ResMut<ChatLog>,
+    config: Res<GameConfig>,
+    mut ev_send: EventWriter<SendChatEvent>,
+) {
+    for (interaction, button, mut background) in interactions.iter_mut() {
+        *background = match interaction {
+            Interaction::None => CHAT_BUTTON_COLOR.into(),
+            Interaction::Hovered | Interaction::Pressed => CHAT_BUTTON_HOVER_COLOR.into(),
+        };
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            ChatButton::Emote(i) => {
+                if let Some(emote) = config.network.emotes.get(*i) {
+                    ev_send.send(SendChatEvent(emote.clone()));
+                }
+            }
+            ChatButton::Mute => log.muted = !log.muted,
+        }
+    }
+}
+
+/// Shows the newest chat lines, the line being typed and the mute state
+pub fn update_chat_panel(
+    log: Res<ChatLog>,
+    input: Res<ChatInput>,
+    localization: Res<Localization>,
+    mut lines_text: Query<&mut Text, (With<ChatLinesText>, Without<ChatInputText>)>,
+    mut input_text: Query<&mut Text, (With<ChatInputText>, Without<ChatLinesText>)>,
+    buttons: Query<(&ChatButton, &Children)>,
+    mut labels: Query<&mut Text, (Without<ChatLinesText>, Without<ChatInputText>)>,
+) {
+    if !log.is_changed() && !input.is_changed() && !localization.is_changed() {
+        return;
+    }
+
+    if let Ok(mut text) = lines_text.get_single_mut() {
+        let mut shown: Vec<String> = log
+            .lines()
+            .rev()
+            .take(SHOWN_CHAT_LINES)
+            .map(|line| format!("{}: {}", localization.color_name(line.from), line.text))
+            .collect();
+        shown.reverse();
+        text.sections[0].value = shown.join("\n");
+    }
+    if let Ok(mut text) = input_text.get_single_mut() {
+        text.sections[0].value = if input.active {
+            format!("> {}_", input.buffer)
+        } else if log.muted {
+            localization.text("chat-muted")
+        } else {
+            localization.text("chat-hint")
+        };
+    }
+
+    let mute_label = localization.text(if log.muted { "chat-unmute" } else { "chat-mute" });
+    for (_, children) in buttons.iter().filter(|(button, _)| **button == ChatButton::Mute) {
+        for child in children.iter() {
+            if let Ok(mut text) = labels.get_mut(*child) {
+                text.sections[0].value = mute_label.clone();
+            }
+        }
+    }
+}
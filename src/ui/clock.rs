@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use shakmaty::Color as ChessColor;
+use crate::audio::sounds::play_tone;
+use crate::config::{GameConfig, DrawResult};
+use crate::game_logic::clock::{GameClock, ClockThresholdEvent, LowTimeLevel, format_clock};
+use crate::game_logic::state::{GameState, ActiveBoard, GameStatus};
+use crate::i18n::Localization;
+
+// Colors for the running and the waiting clock
+const RUNNING_CLOCK_COLOR: Color = Color::WHITE;
+const WAITING_CLOCK_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+const WARNING_TIME_COLOR: Color = Color::rgb(1.0, 0.8, 0.3);
+const CRITICAL_TIME_COLOR: Color = Color::rgb(1.0, 0.35, 0.3);
+
+// Warning beeps: higher and longer once time is critical
+const WARNING_BEEP_HZ: f32 = 660.0;
+const CRITICAL_BEEP_HZ: f32 = 990.0;
+const WARNING_BEEP_MS: u64 = 120;
+const CRITICAL_BEEP_MS: u64 = 250;
+
+/// Marker for the chess clock text
+#[derive(Component)]
+pub struct ClockText;
+
+/// Spawns the (initially empty) clock display in the top-left corner
+pub fn setup_clock_display(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_sections([
+            TextSection::new("", TextStyle { font_size: 26.0, color: WAITING_CLOCK_COLOR, ..default() }),
+            TextSection::new("", TextStyle { font_size: 26.0, color: WAITING_CLOCK_COLOR, ..default() }),
+            TextSection::new("", TextStyle { font_size: 16.0, color: WAITING_CLOCK_COLOR, ..default() }),
+        ])
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            left: Val::Px(8.0),
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.5)),
+        ClockText,
+    ));
+}
+
+/// Shows both clocks (the running one highlighted) and any draw rule of the time control
+pub fn update_clock_display(
+    clock: Res<GameClock>,
+    config: Res<GameConfig>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    localization: Res<Localization>,
+    mut texts: Query<(&mut Text, &mut Visibility), With<ClockText>>,
+) {
+    let Ok((mut text, mut visibility)) = texts.get_single_mut() else {
+        return;
+    };
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+
+    visibility.set_if_neq(if clock.enabled { Visibility::Inherited } else { Visibility::Hidden });
+    if !clock.enabled || (!clock.is_changed() && !localization.is_changed()) {
+        return;
+    }
+
+    let running = (game_state.status == GameStatus::Ongoing).then_some(game_state.current_player_turn);
+    for (section, color) in [(0, ChessColor::White), (1, ChessColor::Black)] {
+        let remaining = clock.remaining_ms(color);
+        // The running clock also counts down what is left of its delay
+        let delay = match (running, clock.delay_remaining_ms()) {
+            (Some(turn), Some(delay)) if turn == color => format!("  +{}", format_clock(delay)),
+            _ => String::new(),
+        };
+        text.sections[section].value = format!("{} {}{}\n", localization.color_name(color), format_clock(remaining), delay);
+        text.sections[section].style.color = match (clock.low_time_level(color), running == Some(color)) {
+            (Some(LowTimeLevel::Critical), _) => CRITICAL_TIME_COLOR,
+            (Some(LowTimeLevel::Warning), _) => WARNING_TIME_COLOR,
+            (None, true) => RUNNING_CLOCK_COLOR,
+            (None, false) => WAITING_CLOCK_COLOR,
+        };
+    }
+
+    let draw_winner = match config.time_control.draw_result {
+        DrawResult::Draw => None,
+        DrawResult::WhiteWins => Some(ChessColor::White),
+        DrawResult::BlackWins => Some(ChessColor::Black),
+    };
+    text.sections[2].value = draw_winner
+        .map(|color| localization.text_with("clock-draw-counts-as", &[("color", localization.color_name(color))]))
+        .unwrap_or_default();
+}
+
+/// Beeps when a human player's clock crosses a low time threshold
+pub fn play_low_time_warnings(
+    mut commands: Commands,
+    mut ev_threshold: EventReader<ClockThresholdEvent>,
+    config: Res<GameConfig>,
+    mut pitches: ResMut<Assets<Pitch>>,
+) {
+    for ev in ev_threshold.read() {
+        // The AI doesn't need to be told
+        let is_human = match ev.color {
+            ChessColor::White => !config.white_player.is_ai,
+            ChessColor::Black => !config.black_player.is_ai,
+        };
+        if !config.time_control.low_time.play_sound || !is_human {
+            continue;
+        }
+
+        let (frequency, length) = match ev.level {
+            LowTimeLevel::Warning => (WARNING_BEEP_HZ, WARNING_BEEP_MS),
+            LowTimeLevel::Critical => (CRITICAL_BEEP_HZ, CRITICAL_BEEP_MS),
+        };
+        play_tone(&mut commands, &mut pitches, &config, frequency, length);
+    }
+}
@@ -0,0 +1,360 @@
+use bevy::prelude::*;
+use bevy::window::ReceivedCharacter;
+use shakmaty::{fen::Fen, CastlingMode, Chess, Color as ChessColor, Position};
+use std::collections::VecDeque;
+use crate::ai::components::AiThinking;
+use crate::ai::evaluation::{evaluate_position_with_pst, PieceSquareTables};
+use crate::ai::fallback::AiFallbackEvent;
+use crate::ai::transposition::SharedTranspositionTable;
+use crate::ai::zobrist::ZobristKeys;
+use crate::config::{DrawbackSetting, GameConfig};
+use crate::drawbacks::{DrawbackParams, DrawbackRegistry};
+use crate::game_logic::events::{ForceAiMoveEvent, GameOverEvent, MakeMoveEvent, MoveRejectedEvent, NewGameEvent, SwapSidesEvent};
+use crate::game_logic::history::MoveHistory;
+use crate::game_logic::notation::{format_uci, parse_uci};
+use crate::game_logic::state::{ActiveBoard, GameState, TurnState};
+use crate::input::focus::TextInputFocus;
+
+// Lines the console remembers, and how many of the newest it shows
+const MAX_LOG_LINES: usize = 200;
+const SHOWN_LOG_LINES: usize = 18;
+
+const HELP: &str = "Commands: setfen <fen> | forcemove <uci> | forceai | swapsides | setdrawback <white|black> <name or index> [name=value,...] | eval | hash [<mb>|clear] | clear | help";
+
+/// Resource with the developer console: whether it is open, the line being
+/// typed and the log of recent game events and command output
+#[derive(Resource, Debug, Default)]
+pub struct DevConsole {
+    pub open: bool,
+    pub input: String,
+    log: VecDeque<String>,
+    submitted: Vec<String>, // Entered commands the command system hasn't run yet
+}
+
+impl DevConsole {
+    /// Adds a line to the log, dropping the oldest ones past `MAX_LOG_LINES`
+    pub fn log(&mut self, line: impl Into<String>) {
+        if self.log.len() == MAX_LOG_LINES {
+            self.log.pop_front();
+        }
+        self.log.push_back(line.into());
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.log.iter().map(String::as_str)
+    }
+
+    /// Logs an entered line and queues it for `run_console_commands`
+    pub fn submit(&mut self, line: String) {
+        self.log(format!("> {}", line));
+        self.submitted.push(line);
+    }
+}
+
+/// A command typed into the console
+#[derive(Debug, Clone)]
+pub enum ConsoleCommand {
+    SetFen(Chess),
+    ForceMove(String), // UCI, parsed against the position when it runs
+    ForceAi,
+    SwapSides,
+    SetDrawback { color: ChessColor, setting: DrawbackSetting },
+    Eval,
+    Hash(HashCommand),
+    Clear,
+    Help,
+}
+
+/// What the `hash` command does with the AI's transposition table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashCommand {
+    Show,
+    Resize(u32), // New size in megabytes (0 = off)
+    Clear,
+}
+
+/// Parses one console line
+pub fn parse_command(line: &str) -> Result<ConsoleCommand, String> {
+    let line = line.trim();
+    let (name, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim();
+    match name.to_lowercase().as_str() {
+        "setfen" => {
+            let fen: Fen = rest.parse().map_err(|e| format!("Invalid FEN: {}", e))?;
+            let position = fen.into_position(CastlingMode::Standard).map_err(|e| format!("Invalid position: {}", e))?;
+            Ok(ConsoleCommand::SetFen(position))
+        }
+        "forcemove" if !rest.is_empty() => Ok(ConsoleCommand::ForceMove(rest.to_string())),
+        "forcemove" => Err("Usage: forcemove <uci>".to_string()),
+        "forceai" => Ok(ConsoleCommand::ForceAi),
+        "swapsides" => Ok(ConsoleCommand::SwapSides),
+        "setdrawback" => {
+            let (color, drawback) = rest.split_once(char::is_whitespace).ok_or("Usage: setdrawback <white|black> <name or index> [params]")?;
+            let color = match color.to_lowercase().as_str() {
+                "white" | "w" => ChessColor::White,
+                "black" | "b" => ChessColor::Black,
+                other => return Err(format!("Unknown side: {}", other)),
+            };
+            // Parameters go last, as in the PGN tags ("minimum=5")
+            let drawback = drawback.trim();
+            let (drawback, params) = match drawback.rsplit_once(char::is_whitespace) {
+                Some((name, params)) if params.contains('=') => (name.trim(), DrawbackParams::from_tag(params)?),
+                _ => (drawback, DrawbackParams::default()),
+            };
+            let setting = match drawback.parse::<u16>() {
+                Ok(index) => DrawbackSetting { name: None, index: Some(index), params },
+                Err(_) => DrawbackSetting { name: Some(drawback.to_string()), index: None, params },
+            };
+            Ok(ConsoleCommand::SetDrawback { color, setting })
+        }
+        "eval" => Ok(ConsoleCommand::Eval),
+        "hash" => match rest {
+            "" => Ok(ConsoleCommand::Hash(HashCommand::Show)),
+            "clear" => Ok(ConsoleCommand::Hash(HashCommand::Clear)),
+            size => size
+                .parse()
+                .map(|size_mb| ConsoleCommand::Hash(HashCommand::Resize(size_mb)))
+                .map_err(|_| "Usage: hash [<size in MB>|clear]".to_string()),
+        },
+        "clear" => Ok(ConsoleCommand::Clear),
+        "help" | "?" => Ok(ConsoleCommand::Help),
+        "" => Err("Nothing entered".to_string()),
+        other => Err(format!("Unknown command: {} (try help)", other)),
+    }
+}
+
+/// Marker for the root node of the console
+#[derive(Component)]
+pub struct ConsoleRoot;
+
+/// Marker for the console's text
+#[derive(Component)]
+pub struct ConsoleText;
+
+/// Spawns the (hidden) console across the top of the window
+pub fn setup_console(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                left: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(6.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(100),
+            ..default()
+        },
+        ConsoleRoot,
+    )).with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_section("", TextStyle { font_size: 15.0, color: Color::rgb(0.75, 1.0, 0.75), ..default() }),
+            ConsoleText,
+        ));
+    });
+}
+
+/// Developer key: ` (grave) opens or closes the console. While it is open it
+/// owns the keyboard; Esc closes it too.
+pub fn toggle_console(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut console: ResMut<DevConsole>,
+    mut focus: ResMut<TextInputFocus>,
+) {
+    let close = console.open && keys.just_pressed(KeyCode::Escape);
+    if !keys.just_pressed(KeyCode::Grave) && !close {
+        return;
+    }
+    if console.open {
+        console.open = false;
+        focus.0 = false;
+        // Don't let the same Esc open the pause menu
+        keys.reset(KeyCode::Escape);
+    } else if !focus.0 {
+        // Another text field has the keyboard
+        console.open = true;
+        focus.0 = true;
+    }
+}
+
+/// System handling text entry in the console; Enter submits the line
+pub fn edit_console_input(
+    mut ev_chars: EventReader<ReceivedCharacter>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut console: ResMut<DevConsole>,
+) {
+    if !console.open || keys.just_pressed(KeyCode::Grave) {
+        // Drop what was typed while closed (including the ` that opens it)
+        ev_chars.clear();
+        return;
+    }
+    for ev in ev_chars.read() {
+        if !ev.char.is_control() && ev.char != '`' {
+            console.input.push(ev.char);
+        }
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+    if keys.just_pressed(KeyCode::Return) {
+        let line = std::mem::take(&mut console.input);
+        if !line.trim().is_empty() {
+            console.submit(line);
+        }
+        keys.reset(KeyCode::Return);
+    }
+}
+
+/// System running the commands entered in the console
+#[allow(clippy::too_many_arguments)]
+pub fn run_console_commands(
+    mut console: ResMut<DevConsole>,
+    mut ev_new_game: EventWriter<NewGameEvent>,
+    mut ev_make_move: EventWriter<MakeMoveEvent>,
+    mut ev_force_ai: EventWriter<ForceAiMoveEvent>,
+    mut ev_swap: EventWriter<SwapSidesEvent>,
+    mut boards: Query<&mut GameState, With<ActiveBoard>>,
+    mut config: ResMut<GameConfig>,
+    registry: Res<DrawbackRegistry>,
+    zobrist_keys: Res<ZobristKeys>,
+    pst: Res<PieceSquareTables>,
+    transposition_table: Res<SharedTranspositionTable>,
+) {
+    if console.submitted.is_empty() {
+        return;
+    }
+    for line in std::mem::take(&mut console.submitted) {
+        let command = match parse_command(&line) {
+            Ok(command) => command,
+            Err(e) => {
+                console.log(e);
+                continue;
+            }
+        };
+        let Ok(mut game_state) = boards.get_single_mut() else {
+            console.log("No game on the board");
+            continue;
+        };
+        match command {
+            ConsoleCommand::SetFen(position) => {
+                console.log("Starting a new game from the position");
+                ev_new_game.send(NewGameEvent { start_position: Some(position) });
+            }
+            // Sent like any other move, so the game still checks it
+            ConsoleCommand::ForceMove(uci) => match parse_uci(&game_state.board, &uci) {
+                Ok(chess_move) => ev_make_move.send(MakeMoveEvent(chess_move)),
+                Err(e) => console.log(e),
+            },
+            ConsoleCommand::ForceAi => ev_force_ai.send(ForceAiMoveEvent),
+            ConsoleCommand::SwapSides => {
+                ev_swap.send(SwapSidesEvent);
+                console.log("Swapping sides");
+            }
+            ConsoleCommand::SetDrawback { color, setting } => {
+                let id = config.resolve_drawback_id(&setting);
+                match color {
+                    ChessColor::White => (game_state.white_drawback, game_state.white_drawback_params) = (id, setting.params),
+                    ChessColor::Black => (game_state.black_drawback, game_state.black_drawback_params) = (id, setting.params),
+                }
+                // The position key changes with the drawback, so the moves are filtered again
+                game_state.zobrist_hash = game_state.position_key(&zobrist_keys);
+                let name = registry.rules.get(&id).map_or("no drawback", |rule| rule.name());
+                console.log(format!("{:?} now plays with {}", color, name));
+            }
+            ConsoleCommand::Eval => {
+                let score = evaluate_position_with_pst(&game_state.board, &pst);
+                let white_score = if game_state.board.turn() == ChessColor::White { score } else { -score };
+                console.log(format!("Static evaluation: {:+.2} for White ({} cp for the side to move)", white_score as f32 / 100.0, score));
+            }
+            ConsoleCommand::Hash(HashCommand::Show) => {
+                let Ok(table) = transposition_table.0.lock() else {
+                    continue;
+                };
+                console.log(format!(
+                    "Hash: {} MB set, {:.1} MB allocated, {} of {} entries used",
+                    config.ai_settings.tt_size_mb,
+                    table.memory_bytes() as f64 / (1024.0 * 1024.0),
+                    table.used(),
+                    table.capacity(),
+                ));
+            }
+            // The AI plugin resizes the table when the config changes
+            ConsoleCommand::Hash(HashCommand::Resize(size_mb)) => {
+                config.ai_settings.tt_size_mb = size_mb;
+                console.log(format!("Hash size set to {} MB", size_mb));
+            }
+            ConsoleCommand::Hash(HashCommand::Clear) => {
+                transposition_table.clear();
+                console.log("Hash cleared");
+            }
+            ConsoleCommand::Clear => console.log.clear(),
+            ConsoleCommand::Help => console.log(HELP),
+        }
+    }
+}
+
+/// System adding what happens in the game to the console log: moves,
+/// refused moves, turn state changes, the AI thinking and new games
+#[allow(clippy::too_many_arguments)]
+pub fn record_console_events(
+    mut console: ResMut<DevConsole>,
+    history: Res<MoveHistory>,
+    turn_state: Res<State<TurnState>>,
+    mut ev_rejected: EventReader<MoveRejectedEvent>,
+    mut ev_game_over: EventReader<GameOverEvent>,
+    mut ev_new_game: EventReader<NewGameEvent>,
+    mut ev_fallback: EventReader<AiFallbackEvent>,
+    started_thinking: Query<(), Added<AiThinking>>,
+    mut logged_plies: Local<usize>,
+) {
+    for _ in ev_new_game.read() {
+        console.log("New game");
+    }
+    if history.len() < *logged_plies {
+        *logged_plies = 0;
+    }
+    for (ply, record) in history.moves.iter().enumerate().skip(*logged_plies) {
+        console.log(format!("Move {}: {} ({})", ply + 1, record.san, format_uci(&record.chess_move)));
+    }
+    *logged_plies = history.len();
+    for ev in ev_rejected.read() {
+        console.log(format!("Refused {}: {}", format_uci(&ev.chess_move), ev.reason));
+    }
+    if turn_state.is_changed() {
+        console.log(format!("Turn state: {:?}", turn_state.get()));
+    }
+    if !started_thinking.is_empty() {
+        console.log("AI thinking...");
+    }
+    for ev in ev_fallback.read() {
+        console.log(format!("AI fallback ({:?}): played {:?}", ev.reason, ev.played.as_ref().map(format_uci)));
+    }
+    for ev in ev_game_over.read() {
+        console.log(format!("Game over: {}", ev.0.reason));
+    }
+}
+
+/// Shows the newest log lines and the line being typed
+pub fn update_console_panel(
+    console: Res<DevConsole>,
+    mut roots: Query<&mut Visibility, With<ConsoleRoot>>,
+    mut texts: Query<&mut Text, With<ConsoleText>>,
+) {
+    let Ok(mut visibility) = roots.get_single_mut() else {
+        return;
+    };
+    visibility.set_if_neq(if console.open { Visibility::Inherited } else { Visibility::Hidden });
+    if !console.open || !console.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = texts.get_single_mut() {
+        let skipped = console.log.len().saturating_sub(SHOWN_LOG_LINES);
+        let mut lines: Vec<&str> = console.lines().skip(skipped).collect();
+        let prompt = format!("> {}_", console.input);
+        lines.push(&prompt);
+        text.sections[0].value = lines.join("\n");
+    }
+}
@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+use crate::drawbacks::DrawbackRegistry;
+use crate::game_logic::state::{GameState, ActiveBoard};
+use crate::modes::daily::{DailyChallenge, today_utc, previous_date};
+use crate::i18n::Localization;
+use crate::stats::store::PlayerStats;
+
+/// Marker for the daily challenge banner
+#[derive(Component)]
+pub struct DailyBannerText;
+
+/// Spawns the banner in the top-right corner when playing the daily challenge
+pub fn setup_daily_banner(mut commands: Commands, daily: Option<Res<DailyChallenge>>) {
+    if daily.is_none() {
+        return;
+    }
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(1.0, 0.85, 0.4),
+                ..default()
+            },
+        )
+        .with_text_alignment(TextAlignment::Right)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            right: Val::Px(8.0),
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.5)),
+        DailyBannerText,
+    ));
+}
+
+/// Shows the date, the player's drawback and the streak.
+/// The opponent's drawback stays secret until the game is over.
+pub fn update_daily_banner(
+    daily: Option<Res<DailyChallenge>>,
+    stats: Res<PlayerStats>,
+    boards: Query<Ref<GameState>, With<ActiveBoard>>,
+    registry: Res<DrawbackRegistry>,
+    localization: Res<Localization>,
+    mut banner: Query<&mut Text, With<DailyBannerText>>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    let (Some(challenge), Ok(mut text)) = (daily, banner.get_single_mut()) else {
+        return;
+    };
+
+    if !stats.is_changed() && !game_state.is_changed() && !localization.is_changed() {
+        return;
+    }
+
+    let today = today_utc();
+    let streak = stats.daily.active_streak(&today, previous_date(&today).as_deref());
+
+    let drawback = |id| localization.drawback_name(&registry, id);
+    let mut lines = vec![
+        localization.text_with("daily-title", &[("date", challenge.date.clone())]),
+        localization.text_with("daily-your-drawback", &[("drawback", drawback(challenge.player_drawback))]),
+    ];
+
+    if game_state.status.is_over() {
+        lines.push(localization.text_with("daily-ai-drawback-was", &[("drawback", drawback(challenge.opponent_drawback))]));
+        if let Some(record) = stats.daily_result(&challenge.date) {
+            lines.push(localization.text_with("daily-result", &[("result", localization.player_result(record.result))]));
+        }
+    } else {
+        lines.push(localization.text("daily-ai-drawback-hidden"));
+    }
+
+    lines.push(localization.text_with("daily-streak", &[
+        ("streak", streak.to_string()),
+        ("best", stats.daily.best_streak.to_string()),
+    ]));
+    text.sections[0].value = lines.join("\n");
+}
@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+use shakmaty::Color as ChessColor;
+use crate::config::GameConfig;
+use crate::drawbacks::DrawbackRegistry;
+use crate::game_logic::state::{GameState, ActiveBoard};
+use crate::i18n::Localization;
+use crate::modes::kiosk::KioskMode;
+
+/// Marker for the banner with the drawbacks that were rolled at random
+#[derive(Component)]
+pub struct RandomDrawbackBannerText;
+
+/// Spawns the (initially hidden) banner at the top of the screen
+pub fn setup_random_drawback_banner(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(1.0, 0.85, 0.4),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            left: Val::Percent(35.0),
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.5)),
+        Visibility::Hidden,
+        RandomDrawbackBannerText,
+    ));
+}
+
+/// Shows what each side set to a random drawback got. An AI's roll stays
+/// secret until the game is over, like any opponent's drawback. The kiosk
+/// overlay shows the drawbacks instead.
+pub fn update_random_drawback_banner(
+    config: Res<GameConfig>,
+    kiosk: Option<Res<KioskMode>>,
+    boards: Query<Ref<GameState>, With<ActiveBoard>>,
+    registry: Res<DrawbackRegistry>,
+    localization: Res<Localization>,
+    mut banner: Query<(&mut Text, &mut Visibility), With<RandomDrawbackBannerText>>,
+) {
+    let (Ok(game_state), Ok((mut text, mut visibility))) = (boards.get_single(), banner.get_single_mut()) else {
+        return;
+    };
+    if !game_state.is_changed() && !config.is_changed() && !localization.is_changed() {
+        return;
+    }
+
+    let lines: Vec<String> = [
+        (ChessColor::White, &config.white_player, game_state.white_drawback),
+        (ChessColor::Black, &config.black_player, game_state.black_drawback),
+    ]
+    .into_iter()
+    .filter(|(_, player, _)| player.drawback.is_random() && kiosk.is_none())
+    .map(|(color, player, id)| {
+        let color = localization.color_name(color);
+        if player.is_ai && !game_state.status.is_over() {
+            localization.text_with("random-drawback-hidden", &[("color", color)])
+        } else {
+            localization.text_with("random-drawback", &[("color", color), ("drawback", localization.drawback_name(&registry, id))])
+        }
+    })
+    .collect();
+
+    *visibility = if lines.is_empty() { Visibility::Hidden } else { Visibility::Visible };
+    text.sections[0].value = lines.join("\n");
+}
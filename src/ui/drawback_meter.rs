@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use shakmaty::Color as ChessColor;
+use crate::config::GameConfig;
+use crate::game_logic::legal_moves::LegalMovesCache;
+use crate::game_logic::state::{GameState, ActiveBoard, GameStatus};
+use crate::i18n::Localization;
+
+const METER_WIDTH: f32 = 220.0;
+const METER_BACKGROUND_COLOR: Color = Color::rgb(0.2, 0.6, 0.25); // Moves still allowed
+const METER_REMOVED_COLOR: Color = Color::rgb(0.75, 0.25, 0.2);   // Moves the drawback took away
+
+/// Marker for the root node of the drawback meter
+#[derive(Component)]
+pub struct DrawbackMeter;
+
+/// Marker for the meter's text
+#[derive(Component)]
+pub struct DrawbackMeterText;
+
+/// Marker for the part of the bar showing the removed moves
+#[derive(Component)]
+pub struct DrawbackMeterFill;
+
+/// Spawns the (hidden) meter in the bottom-left corner
+pub fn setup_drawback_meter(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(40.0),
+                left: Val::Px(8.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        DrawbackMeter,
+    )).with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_section("", TextStyle { font_size: 16.0, color: Color::WHITE, ..default() }),
+            DrawbackMeterText,
+        ));
+        parent.spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(METER_WIDTH),
+                height: Val::Px(6.0),
+                ..default()
+            },
+            background_color: METER_BACKGROUND_COLOR.into(),
+            ..default()
+        }).with_children(|bar| {
+            bar.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(0.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: METER_REMOVED_COLOR.into(),
+                    ..default()
+                },
+                DrawbackMeterFill,
+            ));
+        });
+    });
+}
+
+/// Shows how many moves a human player's drawback removed this turn
+pub fn update_drawback_meter(
+    legal_moves: Res<LegalMovesCache>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    config: Res<GameConfig>,
+    localization: Res<Localization>,
+    mut meters: Query<&mut Visibility, With<DrawbackMeter>>,
+    mut texts: Query<&mut Text, With<DrawbackMeterText>>,
+    mut fills: Query<&mut Style, With<DrawbackMeterFill>>,
+) {
+    let (Ok(mut visibility), Ok(game_state)) = (meters.get_single_mut(), boards.get_single()) else {
+        return;
+    };
+
+    let human_to_move = match game_state.current_player_turn {
+        ChessColor::White => !config.white_player.is_ai,
+        ChessColor::Black => !config.black_player.is_ai,
+    };
+    let shown = human_to_move && game_state.status == GameStatus::Ongoing && legal_moves.unfiltered_count() > 0;
+    visibility.set_if_neq(if shown { Visibility::Inherited } else { Visibility::Hidden });
+    if !shown || !(legal_moves.is_changed() || localization.is_changed()) {
+        return;
+    }
+
+    let removed = legal_moves.removed_count();
+    let total = legal_moves.unfiltered_count();
+    if let Ok(mut text) = texts.get_single_mut() {
+        text.sections[0].value = localization.text_with("drawback-meter", &[
+            ("removed", removed.to_string()),
+            ("total", total.to_string()),
+        ]);
+    }
+    if let Ok(mut style) = fills.get_single_mut() {
+        style.width = Val::Percent(100.0 * removed as f32 / total as f32);
+    }
+}
@@ -0,0 +1,242 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use bevy::prelude::*;
+use crate::board::components::BoardSquare;
+use crate::constants::TILE_SIZE;
+use crate::drawbacks::DrawbackRegistry;
+use crate::game_logic::events::{GameOverEvent, GameOverReason};
+use crate::game_logic::history::MoveHistory;
+use crate::game_logic::pgn::{drawback_tags, outcome_tags, write_pgn};
+use crate::game_logic::state::{ActiveBoard, GameState};
+use crate::i18n::Localization;
+use crate::input::focus::TextInputFocus;
+use crate::modes::daily::today_utc;
+
+// Accent colors of the banner, one per kind of ending
+const KING_CAPTURE_COLOR: Color = Color::rgb(0.95, 0.75, 0.2);
+const DRAWBACK_LOSS_COLOR: Color = Color::rgb(0.7, 0.4, 0.9);
+const CHECKMATE_COLOR: Color = Color::rgb(0.9, 0.3, 0.3);
+const TIMEOUT_COLOR: Color = Color::rgb(0.95, 0.5, 0.15);
+const RESIGNATION_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+const DRAW_COLOR: Color = Color::rgb(0.4, 0.6, 0.95);
+const BANNER_BACKGROUND_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.8);
+const SAVE_BUTTON_COLOR: Color = Color::rgb(0.25, 0.25, 0.3);
+
+// Rings bursting out of the square the king was captured on
+const CAPTURE_ANIMATION_SECS: f32 = 1.5;
+const CAPTURE_RINGS: usize = 3;
+
+/// Banner at the top of the screen announcing how the game ended.
+/// Stays up until the next game starts.
+#[derive(Component)]
+pub struct GameOverBanner {
+    pub reason: GameOverReason,
+    pub animation: Timer,
+}
+
+/// Button on the banner saving the finished game as PGN (or S)
+#[derive(Component)]
+pub struct SaveGameButton;
+
+/// Shows the banner (and starts the capture animation) when the game is over
+pub fn show_game_over_banner(
+    mut commands: Commands,
+    mut ev_game_over: EventReader<GameOverEvent>,
+    banners: Query<Entity, With<GameOverBanner>>,
+    registry: Res<DrawbackRegistry>,
+    localization: Res<Localization>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+) {
+    let Some(ev) = ev_game_over.read().last() else {
+        return;
+    };
+    for entity in banners.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let reason = ev.0.reason;
+    let title = match (reason, ev.0.winner) {
+        (GameOverReason::KingCaptured { winner, .. }, _) => {
+            localization.text_with("game-over-king-captured", &[("color", localization.color_name(winner))])
+        }
+        (_, Some(winner)) => localization.text_with("game-over-wins", &[("color", localization.color_name(winner))]),
+        (_, None) => localization.text("game-over-draw"),
+    };
+    let detail = match reason {
+        GameOverReason::KingCaptured { square, .. } => {
+            localization.text_with("game-over-king-captured-detail", &[("square", square.to_string())])
+        }
+        GameOverReason::DrawbackLoss { loser, drawback } => {
+            // The loser's parameters, as the rule was played
+            let params = boards.get_single().map(|game_state| game_state.drawback_of(loser).1.clone()).unwrap_or_default();
+            format!(
+                "{}\n{}",
+                localization.game_over_reason(&reason, &registry),
+                localization.drawback_description(&registry, drawback, &params),
+            )
+        }
+        GameOverReason::Timeout { loser } => {
+            localization.text_with("game-over-timeout-detail", &[("color", localization.color_name(loser))])
+        }
+        _ => localization.game_over_reason(&reason, &registry),
+    };
+    let accent = match reason {
+        GameOverReason::KingCaptured { .. } => KING_CAPTURE_COLOR,
+        GameOverReason::DrawbackLoss { .. } => DRAWBACK_LOSS_COLOR,
+        GameOverReason::Checkmate { .. } => CHECKMATE_COLOR,
+        GameOverReason::Timeout { .. } => TIMEOUT_COLOR,
+        GameOverReason::Resignation { .. } | GameOverReason::Abandoned { .. } => RESIGNATION_COLOR,
+        GameOverReason::Stalemate | GameOverReason::DrawByRepetition => DRAW_COLOR,
+    };
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.0),
+                left: Val::Percent(50.0),
+                width: Val::Px(420.0),
+                margin: UiRect::left(Val::Px(-210.0)), // Centered on the screen
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(8.0)),
+                border: UiRect::all(Val::Px(2.0)),
+                ..default()
+            },
+            background_color: BANNER_BACKGROUND_COLOR.into(),
+            border_color: accent.into(),
+            z_index: ZIndex::Global(10), // Below the menus and screens
+            ..default()
+        },
+        GameOverBanner {
+            reason,
+            animation: Timer::from_seconds(CAPTURE_ANIMATION_SECS, TimerMode::Once),
+        },
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(title, TextStyle { font_size: 30.0, color: accent, ..default() }));
+        parent.spawn(
+            TextBundle::from_section(detail, TextStyle { font_size: 18.0, color: Color::WHITE, ..default() })
+                .with_text_alignment(TextAlignment::Center),
+        );
+        parent.spawn((
+            ButtonBundle {
+                style: Style {
+                    margin: UiRect::top(Val::Px(6.0)),
+                    padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                    ..default()
+                },
+                background_color: SAVE_BUTTON_COLOR.into(),
+                ..default()
+            },
+            SaveGameButton,
+        )).with_children(|button| {
+            button.spawn(TextBundle::from_section(
+                localization.text("game-over-save"),
+                TextStyle { font_size: 16.0, color: Color::WHITE, ..default() },
+            ));
+        });
+    });
+}
+
+/// System saving the finished game to a PGN file of its own when the save
+/// button on the banner is clicked or S is pressed
+#[allow(clippy::too_many_arguments)]
+pub fn save_finished_game(
+    keys: Res<Input<KeyCode>>,
+    focus: Res<TextInputFocus>,
+    buttons: Query<(&Interaction, &Children), With<SaveGameButton>>,
+    mut labels: Query<&mut Text>,
+    history: Res<MoveHistory>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    registry: Res<DrawbackRegistry>,
+    localization: Res<Localization>,
+    mut last_interaction: Local<Interaction>,
+) {
+    let Ok((interaction, children)) = buttons.get_single() else {
+        return;
+    };
+    let clicked = *interaction == Interaction::Pressed && *last_interaction != Interaction::Pressed;
+    *last_interaction = *interaction;
+    let key_pressed = keys.just_pressed(KeyCode::S) && !focus.0;
+    if !clicked && !key_pressed {
+        return;
+    }
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+
+    let mut tags = outcome_tags(game_state.status.result());
+    tags.extend(drawback_tags(game_state, &registry));
+    tags.push(("Date".to_string(), today_utc().replace('-', ".")));
+    let path = finished_game_path();
+    let label = match std::fs::write(&path, write_pgn(&history, &tags)) {
+        Ok(()) => {
+            println!("Saved the game to {}", path);
+            localization.text_with("game-over-saved", &[("file", path)])
+        }
+        Err(e) => {
+            eprintln!("Failed to save {}: {}", path, e);
+            localization.text_with("game-over-save-failed", &[("error", e.to_string())])
+        }
+    };
+    for child in children.iter() {
+        if let Ok(mut text) = labels.get_mut(*child) {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
+
+// File name for a finished game, by the date and time (UTC) it was saved
+fn finished_game_path() -> String {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let time_of_day = secs % 86_400;
+    format!(
+        "drawback_chess_{}_{:02}-{:02}-{:02}.pgn",
+        today_utc(),
+        time_of_day / 3600,
+        time_of_day / 60 % 60,
+        time_of_day % 60
+    )
+}
+
+/// Draws rings bursting out of the square where the king was captured
+pub fn animate_king_capture(
+    time: Res<Time>,
+    mut banners: Query<&mut GameOverBanner>,
+    board_squares: Query<(&Transform, &BoardSquare)>,
+    mut gizmos: Gizmos,
+) {
+    for mut banner in banners.iter_mut() {
+        let GameOverReason::KingCaptured { square, .. } = banner.reason else {
+            continue;
+        };
+        if banner.animation.tick(time.delta()).finished() {
+            continue;
+        }
+        let Some(center) = board_squares
+            .iter()
+            .find(|(_, board_square)| board_square.square == square)
+            .map(|(transform, _)| transform.translation.truncate())
+        else {
+            continue;
+        };
+
+        // Staggered rings that grow and fade out, each taking half the animation
+        let progress = banner.animation.percent();
+        let stagger = 0.5 / (CAPTURE_RINGS - 1) as f32;
+        for ring in 0..CAPTURE_RINGS {
+            let ring_progress = (progress - ring as f32 * stagger) / 0.5;
+            if !(0.0..=1.0).contains(&ring_progress) {
+                continue;
+            }
+            let radius = TILE_SIZE * (0.3 + 1.2 * ring_progress);
+            gizmos.circle_2d(center, radius, KING_CAPTURE_COLOR.with_a(1.0 - ring_progress));
+        }
+    }
+}
+
+/// Removes the banner when a new game starts
+pub fn clear_game_over_banner(mut commands: Commands, banners: Query<Entity, With<GameOverBanner>>) {
+    for entity in banners.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use crate::drawbacks::DrawbackRegistry;
+use crate::game_logic::state::{GameState, ActiveBoard};
+use crate::i18n::Localization;
+use crate::modes::kiosk::KioskMode;
+
+const TITLE_COLOR: Color = Color::rgb(1.0, 0.85, 0.4);
+
+/// Marker for the line with both sides' drawbacks
+#[derive(Component)]
+pub struct KioskDrawbacksText;
+
+/// Spawns the attract overlay along the bottom of the screen in kiosk mode
+pub fn setup_kiosk_overlay(mut commands: Commands, kiosk: Option<Res<KioskMode>>, localization: Res<Localization>) {
+    if kiosk.is_none() {
+        return;
+    }
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(24.0),
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(6.0),
+            ..default()
+        },
+        ..default()
+    }).with_children(|parent| {
+        parent.spawn(
+            TextBundle::from_section(localization.text("kiosk-title"), TextStyle { font_size: 40.0, color: TITLE_COLOR, ..default() })
+                .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.5)),
+        );
+        parent.spawn((
+            TextBundle::from_section("", TextStyle { font_size: 26.0, color: Color::WHITE, ..default() })
+                .with_text_alignment(TextAlignment::Center)
+                .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.5)),
+            KioskDrawbacksText,
+        ));
+    });
+}
+
+/// Shows the drawbacks of the game being played, which are no secret to onlookers
+pub fn update_kiosk_overlay(
+    boards: Query<Ref<GameState>, With<ActiveBoard>>,
+    registry: Res<DrawbackRegistry>,
+    localization: Res<Localization>,
+    mut text: Query<&mut Text, With<KioskDrawbacksText>>,
+) {
+    let (Ok(game_state), Ok(mut text)) = (boards.get_single(), text.get_single_mut()) else {
+        return;
+    };
+    if !game_state.is_changed() && !localization.is_changed() {
+        return;
+    }
+    text.sections[0].value = localization.text_with("kiosk-drawbacks", &[
+        ("white", localization.drawback_name(&registry, game_state.white_drawback)),
+        ("black", localization.drawback_name(&registry, game_state.black_drawback)),
+    ]);
+}
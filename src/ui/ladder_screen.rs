@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+use bevy::app::AppExit;
+use crate::config::GameConfig;
+use crate::drawbacks::DrawbackRegistry;
+use crate::game_logic::events::NewGameEvent;
+use crate::game_logic::state::AppState;
+use crate::modes::ladder::{LadderSession, ladder_rungs, start_ladder_game};
+use crate::i18n::Localization;
+use crate::stats::store::{PlayerStats, PlayerResult};
+
+// Colors for the ladder screen
+const SCREEN_COLOR: Color = Color::rgba(0.05, 0.05, 0.08, 0.92);
+const RUNG_COLOR: Color = Color::rgb(0.25, 0.25, 0.25);
+const RUNG_HOVER_COLOR: Color = Color::rgb(0.35, 0.35, 0.35);
+const RUNG_BEATEN_COLOR: Color = Color::rgb(0.2, 0.35, 0.2);
+const RUNG_LOCKED_COLOR: Color = Color::rgb(0.12, 0.12, 0.12);
+
+/// Marker for the root node of the ladder screen
+#[derive(Component)]
+pub struct LadderScreenRoot;
+
+/// Action attached to each ladder screen button
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LadderButton {
+    Rung(usize),
+    Back,
+    Quit,
+}
+
+/// Spawns the ladder screen: one button per rung, beaten rungs in green,
+/// locked rungs greyed out
+pub fn spawn_ladder_screen(
+    mut commands: Commands,
+    stats: Res<PlayerStats>,
+    session: Res<LadderSession>,
+    registry: Res<DrawbackRegistry>,
+    localization: Res<Localization>,
+) {
+    let rungs = ladder_rungs();
+    let text_style = |font_size: f32| TextStyle {
+        font_size,
+        color: Color::WHITE,
+        ..default()
+    };
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            background_color: SCREEN_COLOR.into(),
+            z_index: ZIndex::Global(90), // Below the pause menu
+            ..default()
+        },
+        LadderScreenRoot,
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(localization.text("ladder-title"), text_style(44.0)));
+
+        if let Some(last) = &session.last_result {
+            let key = match last.result {
+                PlayerResult::Win => "ladder-result-win",
+                PlayerResult::Loss => "ladder-result-loss",
+                PlayerResult::Draw => "ladder-result-draw",
+            };
+            let name = rungs.get(last.rung).map(|rung| localization.text(rung.key)).unwrap_or_default();
+            let message = localization.text_with(key, &[
+                ("name", name),
+                ("reason", localization.game_over_reason(&last.reason, &registry)),
+            ]);
+            parent.spawn(TextBundle::from_section(message, text_style(22.0)));
+        }
+
+        for (index, rung) in rungs.iter().enumerate() {
+            let (background, status) = if stats.ladder.is_beaten(index) {
+                (RUNG_BEATEN_COLOR, "ladder-status-beaten")
+            } else if stats.ladder.is_unlocked(index) {
+                (RUNG_COLOR, "ladder-status-next")
+            } else {
+                (RUNG_LOCKED_COLOR, "ladder-status-locked")
+            };
+
+            let label = localization.text_with("ladder-rung", &[
+                ("number", (index + 1).to_string()),
+                ("name", localization.text(rung.key)),
+                ("status", localization.text(status)),
+                ("drawback", localization.drawback_name(&registry, rung.player_drawback)),
+            ]);
+
+            let mut button = parent.spawn(ButtonBundle {
+                style: Style {
+                    width: Val::Px(420.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: background.into(),
+                ..default()
+            });
+            // Locked rungs are shown but can't be clicked
+            if stats.ladder.is_unlocked(index) {
+                button.insert(LadderButton::Rung(index));
+            }
+            button.with_children(|button_parent| {
+                button_parent.spawn(
+                    TextBundle::from_section(label, text_style(20.0))
+                        .with_text_alignment(TextAlignment::Center),
+                );
+            });
+        }
+
+        for button in [LadderButton::Back, LadderButton::Quit] {
+            let label = match button {
+                LadderButton::Back => localization.text("ladder-back"),
+                _ => localization.text("ladder-quit"),
+            };
+            parent.spawn((
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(220.0),
+                        height: Val::Px(44.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: RUNG_COLOR.into(),
+                    ..default()
+                },
+                button,
+            )).with_children(|button_parent| {
+                button_parent.spawn(TextBundle::from_section(label, text_style(24.0)));
+            });
+        }
+    });
+}
+
+/// Removes the ladder screen
+pub fn despawn_ladder_screen(mut commands: Commands, roots: Query<Entity, With<LadderScreenRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Handles clicks on the ladder screen
+pub fn handle_ladder_buttons(
+    mut interactions: Query<(&Interaction, &LadderButton, &mut BackgroundColor), Changed<Interaction>>,
+    stats: Res<PlayerStats>,
+    mut config: ResMut<GameConfig>,
+    mut session: ResMut<LadderSession>,
+    mut ev_new_game: EventWriter<NewGameEvent>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut ev_exit: EventWriter<AppExit>,
+) {
+    for (interaction, button, mut background) in interactions.iter_mut() {
+        let idle_color = match button {
+            LadderButton::Rung(index) if stats.ladder.is_beaten(*index) => RUNG_BEATEN_COLOR,
+            _ => RUNG_COLOR,
+        };
+
+        match *interaction {
+            Interaction::Pressed => match button {
+                LadderButton::Rung(index) => {
+                    start_ladder_game(*index, &mut config, &mut session, &mut ev_new_game, &mut next_app_state);
+                }
+                LadderButton::Back => {
+                    next_app_state.set(AppState::InGame);
+                }
+                LadderButton::Quit => {
+                    println!("Quitting Drawback Chess");
+                    ev_exit.send(AppExit);
+                }
+            },
+            Interaction::Hovered => {
+                *background = RUNG_HOVER_COLOR.into();
+            }
+            Interaction::None => {
+                *background = idle_color.into();
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use bevy::window::RequestRedraw;
+use bevy::winit::WinitSettings;
+use crate::ai::components::AiThinking;
+use crate::ai::analysis::ReplayAnalysis;
+use crate::config::GameConfig;
+use crate::game_logic::state::{TurnState, PauseState, AppState, TutorialState};
+use crate::modes::ladder::LadderSession;
+use super::replay::PendingGameImport;
+use super::trophies::AchievementToast;
+
+// Number of frames to keep updating after any input or state change.
+// Moves travel through events and state transitions that take a couple of
+// frames to settle, and in reactive mode nothing would run them otherwise.
+const WAKE_FRAMES_AFTER_ACTIVITY: u32 = 10;
+
+/// Resource counting down the frames we still keep the app awake for
+#[derive(Resource, Default)]
+pub struct WakeFrames(pub u32);
+
+/// System to switch between reactive (low power) and continuous rendering
+/// whenever the display configuration changes
+pub fn apply_display_settings(mut commands: Commands, config: Res<GameConfig>) {
+    if !config.is_changed() {
+        return;
+    }
+
+    if config.display.low_power_mode {
+        println!("Low power mode enabled: redrawing only on input or activity");
+        commands.insert_resource(WinitSettings::desktop_app());
+    } else {
+        println!("Low power mode disabled: rendering continuously");
+        commands.insert_resource(WinitSettings::game());
+    }
+}
+
+/// System to request extra frames while there is work in flight (AI thinking,
+/// pending state transitions), so reactive mode never stalls the game
+#[allow(clippy::too_many_arguments)]
+pub fn keep_awake_while_busy(
+    config: Res<GameConfig>,
+    mut wake: ResMut<WakeFrames>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    turn_state: Res<State<TurnState>>,
+    pause_state: Res<State<PauseState>>,
+    app_state: Res<State<AppState>>,
+    tutorial_state: Res<State<TutorialState>>,
+    ladder: Res<LadderSession>,
+    ai_tasks: Query<(), With<AiThinking>>,
+    analysis: Res<ReplayAnalysis>,
+    imports: Query<(), With<PendingGameImport>>,
+    toasts: Query<(), With<AchievementToast>>,
+    mut ev_redraw: EventWriter<RequestRedraw>,
+) {
+    if !config.display.low_power_mode {
+        return;
+    }
+
+    let had_activity = keys.get_just_pressed().next().is_some()
+        || mouse_buttons.get_just_pressed().next().is_some()
+        || turn_state.is_changed()
+        || pause_state.is_changed()
+        || app_state.is_changed()
+        || tutorial_state.is_changed();
+
+    if had_activity {
+        wake.0 = WAKE_FRAMES_AFTER_ACTIVITY;
+    }
+
+    // Background tasks are polled from Update, so keep ticking until they report back
+    let busy = !ai_tasks.is_empty()
+        || analysis.is_running()
+        || !imports.is_empty()
+        || ladder.return_timer.is_some()
+        || !toasts.is_empty();
+    if busy || wake.0 > 0 {
+        wake.0 = wake.0.saturating_sub(1);
+        ev_redraw.send(RequestRedraw);
+    }
+}
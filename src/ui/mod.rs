@@ -1,3 +1,30 @@
 pub mod plugin;
-
- 
\ No newline at end of file
+pub mod pause_menu;
+pub mod low_power;
+pub mod thinking_indicator;
+pub mod replay;
+pub mod daily_banner;
+pub mod ladder_screen;
+pub mod trophies;
+pub mod tutorial;
+pub mod clock;
+pub mod move_tooltip;
+pub mod review;
+pub mod drawback_meter;
+pub mod game_over;
+pub mod observer_arrows;
+pub mod reserve_tray;
+pub mod capture_tray;
+pub mod substitution_notice;
+pub mod rule_explanation;
+pub mod belief_panel;
+pub mod arena_header;
+pub mod console;
+pub mod share;
+pub mod net_banner;
+pub mod chat;
+pub mod move_reminder;
+pub mod drawback_banner;
+pub mod setup_screen;
+pub mod kiosk_overlay;
+pub mod ai_crash_banner;
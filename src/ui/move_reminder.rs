@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+use crate::i18n::Localization;
+use crate::input::systems::MoveReminder;
+
+const REMINDER_COLOR: Color = Color::rgb(1.0, 0.8, 0.3);
+
+/// Marker for the anti-stall reminder text
+#[derive(Component)]
+pub struct MoveReminderText;
+
+/// Spawns the (initially hidden) reminder at the top of the screen
+pub fn setup_move_reminder(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 22.0,
+                color: REMINDER_COLOR,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(48.0),
+            left: Val::Percent(35.0),
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.6)),
+        Visibility::Hidden,
+        MoveReminderText,
+    ));
+}
+
+/// Shows the reminder while the player to move is taking too long, with the
+/// countdown to the random move if one is coming
+pub fn update_move_reminder(
+    reminder: Res<MoveReminder>,
+    localization: Res<Localization>,
+    mut text: Query<(&mut Text, &mut Visibility), With<MoveReminderText>>,
+) {
+    if !reminder.is_changed() && !localization.is_changed() {
+        return;
+    }
+    let Ok((mut text, mut visibility)) = text.get_single_mut() else {
+        return;
+    };
+
+    *visibility = if reminder.shown { Visibility::Visible } else { Visibility::Hidden };
+    text.sections[0].value = match reminder.auto_move_in_secs {
+        Some(seconds) => localization.text_with("move-reminder-auto", &[("seconds", format!("{:.0}", seconds.ceil()))]),
+        None => localization.text("move-reminder"),
+    };
+}
@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+use shakmaty::{Move, Position, Square};
+use crate::game_logic::legal_moves::LegalMovesCache;
+use crate::game_logic::notation::format_san;
+use crate::game_logic::state::{GameState, ActiveBoard};
+use crate::input::cursor::hovered_square;
+use crate::input::systems::{ValidMoveDestination, BlockedMoveDestination};
+use crate::drawbacks::DrawbackRegistry;
+use crate::i18n::Localization;
+
+// Distance of the tooltip from the mouse cursor, in pixels
+const TOOLTIP_OFFSET: f32 = 18.0;
+
+/// Marker for the tooltip naming the hovered square
+#[derive(Component)]
+pub struct MoveTooltip;
+
+/// Spawns the (hidden) hover tooltip
+pub fn setup_move_tooltip(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section("", TextStyle { font_size: 18.0, color: Color::WHITE, ..default() })
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                ..default()
+            })
+            .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.75)),
+        MoveTooltip,
+        Visibility::Hidden,
+    ));
+}
+
+/// Shows the name of the square under the cursor, and the SAN of the move
+/// if it is a legal destination of the selected piece (e.g. "e5  Nxe5+").
+/// Moves the drawback removed (teaching mode) also name the drawback.
+/// With Alt held over a piece of the side to move, it sums up what the
+/// drawback does to that piece instead.
+#[allow(clippy::too_many_arguments)]
+pub fn update_move_tooltip(
+    keys: Res<Input<KeyCode>>,
+    legal_moves: Res<LegalMovesCache>,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    destinations: Query<&ValidMoveDestination>,
+    blocked: Query<&BlockedMoveDestination>,
+    registry: Res<DrawbackRegistry>,
+    localization: Res<Localization>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    mut tooltips: Query<(&mut Text, &mut Style, &mut Visibility), With<MoveTooltip>>,
+) {
+    let Ok((mut text, mut style, mut visibility)) = tooltips.get_single_mut() else {
+        return;
+    };
+    let (Ok(window), Ok((camera, camera_transform)), Ok(game_state)) = (windows.get_single(), cameras.get_single(), boards.get_single()) else {
+        return;
+    };
+
+    let hovered = window.cursor_position().zip(hovered_square(window, camera, camera_transform, game_state.board_flipped));
+    let Some((cursor, square)) = hovered else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+
+    // Alt over a piece of the side to move explains its drawback instead
+    let summary = keys
+        .any_pressed([KeyCode::AltLeft, KeyCode::AltRight])
+        .then(|| piece_summary(game_state, square, &legal_moves, &registry, &localization))
+        .flatten();
+    let blocked_move = blocked.iter().find(|destination| destination.chess_move.to() == square);
+    let label = summary.unwrap_or_else(|| match destinations.iter().find(|destination| destination.chess_move.to() == square) {
+        Some(destination) => format!("{}  {}", square, format_san(&game_state.board, &destination.chess_move)),
+        None => match blocked_move {
+            Some(destination) => localization.text_with("tooltip-blocked-move", &[
+                ("square", square.to_string()),
+                ("san", format_san(&game_state.board, &destination.chess_move)),
+                ("drawback", localization.drawback_name(&registry, game_state.get_current_player_drawback_id())),
+            ]),
+            None => square.to_string(),
+        },
+    });
+    if text.sections[0].value != label {
+        text.sections[0].value = label;
+    }
+    style.left = Val::Px(cursor.x + TOOLTIP_OFFSET);
+    style.top = Val::Px(cursor.y + TOOLTIP_OFFSET);
+    visibility.set_if_neq(Visibility::Inherited);
+}
+
+// What the drawback of the side to move does to its piece on `square`:
+// how many of the piece's moves it leaves, then the rule's own explanation
+fn piece_summary(game_state: &GameState, square: Square, legal_moves: &LegalMovesCache, registry: &DrawbackRegistry, localization: &Localization) -> Option<String> {
+    let board = game_state.board.board();
+    let role = board.role_at(square).filter(|_| board.color_at(square) == Some(game_state.board.turn()))?;
+    let from_square = |chess_move: &&Move| chess_move.from() == Some(square);
+    let allowed = legal_moves.moves().iter().filter(from_square).count();
+    let blocked = legal_moves.blocked().iter().filter(from_square).count();
+
+    let mut summary = localization.text_with("tooltip-piece", &[
+        ("piece", localization.role_name(role)),
+        ("square", square.to_string()),
+        ("allowed", allowed.to_string()),
+        ("legal", (allowed + blocked).to_string()),
+    ]);
+    let explanation = game_state.current_drawback_rule(registry).and_then(|rule| {
+        rule.explain_piece(&game_state.board, square, &game_state.turn_context())
+    });
+    if let Some(explanation) = explanation {
+        summary.push('\n');
+        summary.push_str(&explanation);
+    }
+    Some(summary)
+}
+
+/// Hides the tooltip while the board can't be played on (menus, replay)
+pub fn hide_move_tooltip(mut tooltips: Query<&mut Visibility, With<MoveTooltip>>) {
+    for mut visibility in tooltips.iter_mut() {
+        visibility.set_if_neq(Visibility::Hidden);
+    }
+}
@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+use crate::i18n::Localization;
+use crate::net::lockstep::{NetSession, NetStatus};
+
+// Colors of the banner text while all is well and after something went wrong
+const NET_OK_COLOR: Color = Color::rgb(0.6, 0.9, 1.0);
+const NET_ERROR_COLOR: Color = Color::rgb(1.0, 0.45, 0.4);
+
+/// Marker for the network game banner
+#[derive(Component)]
+pub struct NetBannerText;
+
+/// Spawns the banner in the top-left corner when playing over the network
+pub fn setup_net_banner(mut commands: Commands, session: Option<Res<NetSession>>) {
+    if session.is_none() {
+        return;
+    }
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.0,
+                color: NET_OK_COLOR,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            left: Val::Px(8.0),
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.5)),
+        NetBannerText,
+    ));
+}
+
+/// Shows whether the other player is connected and the game is in sync
+pub fn update_net_banner(
+    session: Option<Res<NetSession>>,
+    localization: Res<Localization>,
+    time: Res<Time>,
+    mut banner: Query<&mut Text, With<NetBannerText>>,
+) {
+    let (Some(session), Ok(mut text)) = (session, banner.get_single_mut()) else {
+        return;
+    };
+    // The countdown while reconnecting changes every frame
+    let seconds_left = session.reconnect_seconds_left(time.elapsed_seconds());
+    if !session.is_changed() && !localization.is_changed() && seconds_left.is_none() {
+        return;
+    }
+    let seconds = format!("{:.0}", seconds_left.unwrap_or_default().ceil());
+
+    let (line, failed) = match &session.status {
+        NetStatus::Connecting if session.is_host() => (localization.text("net-waiting-for-player"), false),
+        NetStatus::Connecting => (localization.text("net-connecting"), false),
+        NetStatus::WaitingForSetup => (localization.text("net-waiting-for-setup"), false),
+        NetStatus::Playing => {
+            let mut line = localization.text_with("net-playing", &[("color", localization.color_name(session.local_color))]);
+            // Only the host's drawback can be hidden, so the players are told who sees what
+            if session.hides_host_drawback() {
+                line.push('\n');
+                line.push_str(&localization.text(if session.is_host() { "net-hidden-host" } else { "net-hidden-client" }));
+            }
+            (line, false)
+        }
+        NetStatus::Desynced => (localization.text("net-desynced"), true),
+        NetStatus::Resyncing => (localization.text("net-resyncing"), true),
+        NetStatus::Reconnecting if session.is_host() => (localization.text_with("net-opponent-away", &[("seconds", seconds)]), true),
+        NetStatus::Reconnecting => (localization.text_with("net-reconnecting", &[("seconds", seconds)]), true),
+        NetStatus::Failed(reason) => (localization.text_with("net-failed", &[("reason", reason.clone())]), true),
+        NetStatus::Disconnected(reason) => (localization.text_with("net-disconnected", &[("reason", reason.clone())]), true),
+    };
+    if let Some(section) = text.sections.first_mut() {
+        section.value = line;
+        section.style.color = if failed { NET_ERROR_COLOR } else { NET_OK_COLOR };
+    }
+}
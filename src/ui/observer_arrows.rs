@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+use shakmaty::{ByColor, Color as ChessColor, Move, Position};
+use crate::ai::components::AiThinking;
+use crate::board::geometry::world_pos;
+use crate::config::GameConfig;
+use crate::game_logic::state::{GameState, ActiveBoard};
+use super::thinking_indicator::draw_arrow;
+
+// Arrow color of each engine's plan
+const WHITE_ENGINE_COLOR: Color = Color::rgb(0.3, 0.7, 1.0);
+const BLACK_ENGINE_COLOR: Color = Color::rgb(1.0, 0.35, 0.35);
+// Opacity of the plan behind an engine's previous move
+const TRACE_ALPHA: f32 = 0.25;
+// Each ply further into a plan is drawn this much fainter than the one before
+const PLY_FADE: f32 = 0.7;
+
+/// The lines both engines expect in an AI vs AI game. The plan of the engine
+/// that is thinking follows its search; the other one is the plan behind its last move.
+#[derive(Resource, Default)]
+pub struct EnginePlans {
+    pub plans: ByColor<Vec<Move>>,
+    pub thinking: Option<ChessColor>,
+}
+
+/// True when both sides are played by the AI and their plans should be drawn
+pub fn observing_engines(config: &GameConfig) -> bool {
+    config.display.show_ai_observer_arrows && config.white_player.is_ai && config.black_player.is_ai
+}
+
+/// Copies the plan of the engine that is thinking out of its search
+pub fn track_engine_plans(
+    config: Res<GameConfig>,
+    ai_tasks: Query<&AiThinking>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    mut engine_plans: ResMut<EnginePlans>,
+) {
+    if !observing_engines(&config) {
+        return;
+    }
+    let (Some(ai_task), Ok(game_state)) = (ai_tasks.iter().next(), boards.get_single()) else {
+        if engine_plans.thinking.is_some() {
+            engine_plans.thinking = None;
+        }
+        return;
+    };
+
+    let color = game_state.board.turn();
+    if engine_plans.thinking != Some(color) {
+        // A new search: the plan from this engine's previous move is out of date
+        engine_plans.thinking = Some(color);
+        engine_plans.plans.get_mut(color).clear();
+    }
+    let plan = ai_task.progress.plan();
+    if !plan.is_empty() && *engine_plans.plans.get(color) != plan {
+        *engine_plans.plans.get_mut(color) = plan;
+    }
+}
+
+/// Draws each engine's plan in its own color: pulsing while it thinks,
+/// faded once it has moved
+pub fn draw_engine_plans(
+    config: Res<GameConfig>,
+    time: Res<Time>,
+    engine_plans: Res<EnginePlans>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    mut gizmos: Gizmos,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    if !observing_engines(&config) {
+        return;
+    }
+
+    for (color, engine_color) in [(ChessColor::White, WHITE_ENGINE_COLOR), (ChessColor::Black, BLACK_ENGINE_COLOR)] {
+        let alpha = if engine_plans.thinking == Some(color) {
+            0.55 + 0.35 * (time.elapsed_seconds() * 4.0).sin()
+        } else {
+            TRACE_ALPHA
+        };
+        for (ply, plan_move) in engine_plans.plans.get(color).iter().enumerate() {
+            let Some(from) = plan_move.from() else {
+                continue;
+            };
+            let start = world_pos(from, game_state.board_flipped);
+            let end = world_pos(plan_move.to(), game_state.board_flipped);
+            draw_arrow(&mut gizmos, start, end, engine_color.with_a(alpha * PLY_FADE.powi(ply as i32)));
+        }
+    }
+}
+
+/// Forgets the plans of the previous game
+pub fn clear_engine_plans(mut engine_plans: ResMut<EnginePlans>) {
+    *engine_plans = EnginePlans::default();
+}
@@ -0,0 +1,554 @@
+use bevy::prelude::*;
+use bevy::app::AppExit;
+use bevy::ecs::system::SystemParam;
+use shakmaty::Color as ChessColor;
+use crate::config::{GameConfig, AiSettings, DelayMode, SavedSettings, DEFAULT_DELAY_MS};
+use crate::drawbacks::DrawbackRegistry;
+use crate::game_logic::state::{GameState, ActiveBoard, GameStatus, TurnState, PauseState, AppState, TutorialState};
+use crate::game_logic::events::{GameOverEvent, GameOverReason, GameResult, FlipBoardEvent, ForceAiMoveEvent, SwapSidesEvent, SharePositionEvent, NewGameEvent};
+use crate::game_logic::rng::GameRng;
+use crate::i18n::Localization;
+use crate::modes::rollover::reroll_drawbacks;
+use crate::net::lockstep::NetSession;
+
+// Colors for the pause overlay
+const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6); // Dims the board underneath
+const BUTTON_COLOR: Color = Color::rgb(0.25, 0.25, 0.25);
+const BUTTON_HOVER_COLOR: Color = Color::rgb(0.35, 0.35, 0.35);
+const BUTTON_PRESSED_COLOR: Color = Color::rgb(0.45, 0.55, 0.45);
+// Sizes of the AI's transposition table the settings page steps through (0 = off)
+const HASH_SIZES_MB: [u32; 5] = [0, 16, 64, 256, 1024];
+// AI strengths the settings page steps through: name, search time (ms) and depth
+const AI_STRENGTHS: [(&str, u32, u8); 3] = [
+    ("menu-ai-strength-easy", 500, 2),
+    ("menu-ai-strength-medium", 1500, 8),
+    ("menu-ai-strength-hard", 3000, 24),
+];
+
+/// Marker for the root node of the pause overlay
+#[derive(Component)]
+pub struct PauseMenuRoot;
+
+/// Which page of the pause menu is currently shown
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PauseMenuPage {
+    #[default]
+    Main,
+    Settings,
+}
+
+/// Resource set while a rematch offered between two people at the same
+/// board waits for the other one to accept it
+#[derive(Resource, Debug, Default, PartialEq, Eq)]
+pub struct RematchOffer {
+    pub offered: bool,
+}
+
+/// Action attached to each pause menu button
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseMenuButton {
+    Resume,
+    Settings,
+    NewGame,
+    Ladder,
+    Trophies,
+    Review,
+    Tutorial,
+    LeaveTutorial,
+    ForceAiMove,
+    SwapSides,
+    SharePosition,
+    Rematch,
+    NewDrawbacks,
+    Resign,
+    Quit,
+    // Settings page
+    FlipBoard,
+    ToggleLowPower,
+    TeachingMode,
+    Language,
+    ClockDelay,
+    ShareWithFen,
+    HashSize,
+    Sound,
+    RuleExplanations,
+    AiStrength,
+    Back,
+}
+
+impl PauseMenuButton {
+    // Whether the button changes a setting, which is then saved for the next start
+    fn changes_setting(self) -> bool {
+        matches!(
+            self,
+            Self::ToggleLowPower | Self::TeachingMode | Self::Language | Self::ClockDelay | Self::ShareWithFen
+                | Self::HashSize | Self::Sound | Self::RuleExplanations | Self::AiStrength
+        )
+    }
+
+    fn label(&self, localization: &Localization, config: &GameConfig, rematch: &RematchOffer) -> String {
+        let key = match self {
+            Self::Resume => "menu-resume",
+            Self::Settings => "menu-settings",
+            Self::NewGame => "menu-new-game",
+            Self::Ladder => "menu-ladder",
+            Self::Trophies => "menu-trophies",
+            Self::Review => "menu-review",
+            Self::Tutorial => "menu-tutorial",
+            Self::LeaveTutorial => "menu-leave-tutorial",
+            Self::ForceAiMove => "menu-force-ai-move",
+            Self::SwapSides => "menu-swap-sides",
+            Self::SharePosition => "menu-share-position",
+            Self::Rematch if rematch.offered => "menu-accept-rematch",
+            Self::Rematch => "menu-offer-rematch",
+            Self::NewDrawbacks => "menu-new-drawbacks",
+            Self::Resign => "menu-resign",
+            Self::Quit => "menu-quit",
+            Self::FlipBoard => "menu-flip-board",
+            Self::ToggleLowPower => "menu-toggle-low-power",
+            Self::TeachingMode => {
+                let state = if config.display.show_blocked_moves { "menu-on" } else { "menu-off" };
+                return localization.text_with("menu-teaching-mode", &[("state", localization.text(state))]);
+            }
+            Self::Language => {
+                return localization.text_with("menu-language", &[("language", localization.text("language-name"))]);
+            }
+            Self::ClockDelay => {
+                let mode = match config.time_control.delay_mode {
+                    DelayMode::None => "menu-clock-delay-none",
+                    DelayMode::Simple => "menu-clock-delay-simple",
+                    DelayMode::Bronstein => "menu-clock-delay-bronstein",
+                };
+                return localization.text_with("menu-clock-delay", &[("mode", localization.text(mode))]);
+            }
+            Self::ShareWithFen => {
+                let state = if config.display.share_with_fen { "menu-on" } else { "menu-off" };
+                return localization.text_with("menu-share-with-fen", &[("state", localization.text(state))]);
+            }
+            Self::HashSize => {
+                return localization.text_with("menu-hash-size", &[("size", config.ai_settings.tt_size_mb.to_string())]);
+            }
+            Self::Sound => {
+                let state = if config.display.sound_enabled { "menu-on" } else { "menu-off" };
+                return localization.text_with("menu-sound", &[("state", localization.text(state))]);
+            }
+            Self::RuleExplanations => {
+                let state = if config.display.show_rule_explanations { "menu-on" } else { "menu-off" };
+                return localization.text_with("menu-rule-explanations", &[("state", localization.text(state))]);
+            }
+            Self::AiStrength => {
+                let level = ai_strength(&config.ai_settings).map_or("menu-ai-strength-custom", |index| AI_STRENGTHS[index].0);
+                return localization.text_with("menu-ai-strength", &[("level", localization.text(level))]);
+            }
+            Self::Back => "menu-back",
+        };
+        localization.text(key)
+    }
+}
+
+// Helper function for which of `AI_STRENGTHS` the settings are at, None if they were set some other way
+fn ai_strength(settings: &AiSettings) -> Option<usize> {
+    AI_STRENGTHS
+        .iter()
+        .position(|&(_, time_limit_ms, depth_limit)| settings.time_limit_ms == time_limit_ms && settings.depth_limit == depth_limit)
+}
+
+// Helper function to set the AI to one of `AI_STRENGTHS`, deeper searches
+// getting more iterations and quiescence like the ladder's opponents
+fn set_ai_strength(settings: &mut AiSettings, index: usize) {
+    let (_, time_limit_ms, depth_limit) = AI_STRENGTHS[index];
+    settings.time_limit_ms = time_limit_ms;
+    settings.depth_limit = depth_limit;
+    settings.iteration_limit = 100_000 * depth_limit as u32;
+    settings.check_quietness = depth_limit > 4;
+    settings.quiescence_depth = depth_limit / 2;
+}
+
+/// System to toggle the pause state with the Esc key
+pub fn toggle_pause(
+    keys: Res<Input<KeyCode>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut page: ResMut<PauseMenuPage>,
+) {
+    if !keys.just_pressed(KeyCode::Escape) {
+        return;
+    }
+
+    match pause_state.get() {
+        PauseState::Running => {
+            println!("Game paused");
+            page.set_if_neq(PauseMenuPage::Main);
+            next_pause_state.set(PauseState::Paused);
+        }
+        PauseState::Paused => {
+            // Esc on a sub page goes back first, like most games do
+            if *page != PauseMenuPage::Main {
+                *page = PauseMenuPage::Main;
+            } else {
+                println!("Game resumed");
+                next_pause_state.set(PauseState::Running);
+            }
+        }
+    }
+}
+
+/// Spawns the dimming overlay and the buttons for the current page
+pub fn spawn_pause_menu(
+    mut commands: Commands,
+    page: Res<PauseMenuPage>,
+    tutorial_state: Res<State<TutorialState>>,
+    localization: Res<Localization>,
+    config: Res<GameConfig>,
+    rematch: Res<RematchOffer>,
+    session: Option<Res<NetSession>>,
+) {
+    let tutorial_active = *tutorial_state.get() == TutorialState::Active;
+    build_pause_menu(&mut commands, *page, tutorial_active, session.is_some(), &localization, &config, &rematch);
+}
+
+// Helper function to build the overlay for a given page
+fn build_pause_menu(
+    commands: &mut Commands,
+    page: PauseMenuPage,
+    tutorial_active: bool,
+    network_game: bool,
+    localization: &Localization,
+    config: &GameConfig,
+    rematch: &RematchOffer,
+) {
+    let tutorial_button = if tutorial_active {
+        PauseMenuButton::LeaveTutorial
+    } else {
+        PauseMenuButton::Tutorial
+    };
+
+    let mut buttons: Vec<PauseMenuButton> = match page {
+        PauseMenuPage::Main => vec![
+            PauseMenuButton::Resume,
+            PauseMenuButton::Settings,
+            PauseMenuButton::NewGame,
+            PauseMenuButton::Ladder,
+            PauseMenuButton::Trophies,
+            PauseMenuButton::Review,
+            tutorial_button,
+            PauseMenuButton::ForceAiMove,
+            PauseMenuButton::SwapSides,
+            PauseMenuButton::SharePosition,
+            PauseMenuButton::Rematch,
+            PauseMenuButton::NewDrawbacks,
+            PauseMenuButton::Resign,
+            PauseMenuButton::Quit,
+        ],
+        PauseMenuPage::Settings => vec![
+            PauseMenuButton::FlipBoard,
+            PauseMenuButton::ToggleLowPower,
+            PauseMenuButton::TeachingMode,
+            PauseMenuButton::Language,
+            PauseMenuButton::ClockDelay,
+            PauseMenuButton::ShareWithFen,
+            PauseMenuButton::HashSize,
+            PauseMenuButton::Sound,
+            PauseMenuButton::RuleExplanations,
+            PauseMenuButton::AiStrength,
+            PauseMenuButton::Back,
+        ],
+    };
+    if network_game {
+        // A new game over the network has to be agreed on through the session
+        buttons.retain(|button| !matches!(button, PauseMenuButton::Rematch | PauseMenuButton::NewDrawbacks));
+    }
+
+    let title = match page {
+        PauseMenuPage::Main => localization.text("menu-paused"),
+        PauseMenuPage::Settings => localization.text("menu-settings"),
+    };
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            background_color: OVERLAY_COLOR.into(),
+            z_index: ZIndex::Global(100), // Above everything else
+            ..default()
+        },
+        PauseMenuRoot,
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            title,
+            TextStyle {
+                font_size: 48.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        ));
+
+        // The buttons wrap into a second column when they don't fit the window
+        parent.spawn(NodeBundle {
+            style: Style {
+                height: Val::Percent(80.0),
+                flex_direction: FlexDirection::Column,
+                flex_wrap: FlexWrap::Wrap,
+                justify_content: JustifyContent::Center,
+                align_content: AlignContent::Center,
+                row_gap: Val::Px(12.0),
+                column_gap: Val::Px(12.0),
+                ..default()
+            },
+            ..default()
+        }).with_children(|parent| {
+            for button in &buttons {
+                parent.spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(220.0),
+                            height: Val::Px(50.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: BUTTON_COLOR.into(),
+                        ..default()
+                    },
+                    *button,
+                )).with_children(|button_parent| {
+                    button_parent.spawn(TextBundle::from_section(
+                        button.label(localization, config, rematch),
+                        TextStyle {
+                            font_size: 28.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ));
+                });
+            }
+        });
+    });
+}
+
+/// Removes the pause overlay
+pub fn despawn_pause_menu(mut commands: Commands, roots: Query<Entity, With<PauseMenuRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Rebuilds the overlay when switching between menu pages, languages or settings
+#[allow(clippy::too_many_arguments)]
+pub fn refresh_pause_menu(
+    mut commands: Commands,
+    page: Res<PauseMenuPage>,
+    tutorial_state: Res<State<TutorialState>>,
+    localization: Res<Localization>,
+    config: Res<GameConfig>,
+    rematch: Res<RematchOffer>,
+    session: Option<Res<NetSession>>,
+    roots: Query<Entity, With<PauseMenuRoot>>,
+) {
+    if !(page.is_changed() || localization.is_changed() || config.is_changed() || rematch.is_changed()) || roots.is_empty() {
+        return;
+    }
+
+    for entity in roots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let tutorial_active = *tutorial_state.get() == TutorialState::Active;
+    build_pause_menu(&mut commands, *page, tutorial_active, session.is_some(), &localization, &config, &rematch);
+}
+
+/// Events the pause menu buttons send
+#[derive(SystemParam)]
+pub struct PauseMenuEvents<'w> {
+    game_over: EventWriter<'w, GameOverEvent>,
+    flip: EventWriter<'w, FlipBoardEvent>,
+    force_ai: EventWriter<'w, ForceAiMoveEvent>,
+    swap: EventWriter<'w, SwapSidesEvent>,
+    share: EventWriter<'w, SharePositionEvent>,
+    new_game: EventWriter<'w, NewGameEvent>,
+    exit: EventWriter<'w, AppExit>,
+}
+
+/// System to withdraw an open rematch offer once a new game has started
+pub fn clear_rematch_offer(mut rematch: ResMut<RematchOffer>) {
+    rematch.set_if_neq(RematchOffer::default());
+}
+
+/// Handles clicks on the pause menu buttons
+#[allow(clippy::too_many_arguments)]
+pub fn handle_pause_menu_buttons(
+    mut interactions: Query<(&Interaction, &PauseMenuButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut next_turn_state: ResMut<NextState<TurnState>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_tutorial_state: ResMut<NextState<TutorialState>>,
+    mut page: ResMut<PauseMenuPage>,
+    mut boards: Query<&mut GameState, With<ActiveBoard>>,
+    mut config: ResMut<GameConfig>,
+    mut saved: ResMut<SavedSettings>,
+    localization: Res<Localization>,
+    mut rematch: ResMut<RematchOffer>,
+    registry: Res<DrawbackRegistry>,
+    mut rng: ResMut<GameRng>,
+    mut events: PauseMenuEvents,
+) {
+    let Ok(mut game_state) = boards.get_single_mut() else {
+        return;
+    };
+    for (interaction, button, mut background) in interactions.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background = BUTTON_PRESSED_COLOR.into();
+
+                let before = config.clone();
+                match button {
+                    PauseMenuButton::Resume => {
+                        println!("Game resumed");
+                        next_pause_state.set(PauseState::Running);
+                    }
+                    PauseMenuButton::Settings => {
+                        *page = PauseMenuPage::Settings;
+                    }
+                    PauseMenuButton::NewGame => {
+                        next_pause_state.set(PauseState::Running);
+                        next_app_state.set(AppState::Setup);
+                    }
+                    PauseMenuButton::Ladder => {
+                        next_pause_state.set(PauseState::Running);
+                        next_app_state.set(AppState::Ladder);
+                    }
+                    PauseMenuButton::Trophies => {
+                        next_pause_state.set(PauseState::Running);
+                        next_app_state.set(AppState::Trophies);
+                    }
+                    PauseMenuButton::Review => {
+                        next_pause_state.set(PauseState::Running);
+                        next_app_state.set(AppState::Review);
+                    }
+                    PauseMenuButton::Tutorial => {
+                        next_pause_state.set(PauseState::Running);
+                        next_tutorial_state.set(TutorialState::Active);
+                    }
+                    PauseMenuButton::LeaveTutorial => {
+                        next_pause_state.set(PauseState::Running);
+                        next_tutorial_state.set(TutorialState::Off);
+                    }
+                    PauseMenuButton::ForceAiMove => {
+                        next_pause_state.set(PauseState::Running);
+                        events.force_ai.send(ForceAiMoveEvent);
+                    }
+                    PauseMenuButton::SwapSides => {
+                        next_pause_state.set(PauseState::Running);
+                        events.swap.send(SwapSidesEvent);
+                    }
+                    PauseMenuButton::SharePosition => {
+                        // The menu closes first, so it isn't in the picture
+                        next_pause_state.set(PauseState::Running);
+                        events.share.send(SharePositionEvent);
+                    }
+                    PauseMenuButton::Rematch => {
+                        // Against the AI the offer is taken right away; two
+                        // people at the board both have to press it
+                        let both_human = !config.white_player.is_ai && !config.black_player.is_ai;
+                        if both_human && !rematch.offered {
+                            rematch.offered = true;
+                        } else {
+                            println!("Rematch with the same drawbacks");
+                            next_pause_state.set(PauseState::Running);
+                            events.new_game.send(NewGameEvent::default());
+                        }
+                    }
+                    PauseMenuButton::NewDrawbacks => {
+                        reroll_drawbacks(&mut config, &registry, rng.session());
+                        println!("Restarting with new drawbacks");
+                        next_pause_state.set(PauseState::Running);
+                        events.new_game.send(NewGameEvent::default());
+                    }
+                    PauseMenuButton::Resign => {
+                        if game_state.status == GameStatus::Ongoing {
+                            let loser = resigning_color(&game_state, &config);
+                            let reason = GameOverReason::Resignation { loser };
+                            let result = GameResult::new(reason, config.time_control.draw_result);
+                            game_state.status = GameStatus::Finished(result);
+                            next_turn_state.set(TurnState::GameOver);
+                            events.game_over.send(GameOverEvent(result));
+                            println!("Game over: {}", reason);
+                        }
+                        next_pause_state.set(PauseState::Running);
+                    }
+                    PauseMenuButton::Quit => {
+                        println!("Quitting Drawback Chess");
+                        events.exit.send(AppExit);
+                    }
+                    PauseMenuButton::FlipBoard => {
+                        events.flip.send(FlipBoardEvent);
+                    }
+                    PauseMenuButton::ToggleLowPower => {
+                        config.display.low_power_mode = !config.display.low_power_mode;
+                    }
+                    PauseMenuButton::TeachingMode => {
+                        config.display.show_blocked_moves = !config.display.show_blocked_moves;
+                    }
+                    PauseMenuButton::Language => {
+                        config.display.language = localization.language().next().code().to_string();
+                    }
+                    PauseMenuButton::ClockDelay => {
+                        // Takes effect when the next game starts
+                        let time_control = &mut config.time_control;
+                        time_control.delay_mode = time_control.delay_mode.next();
+                        if time_control.delay_mode != DelayMode::None && time_control.delay_ms == 0 {
+                            time_control.delay_ms = DEFAULT_DELAY_MS;
+                        }
+                    }
+                    PauseMenuButton::ShareWithFen => {
+                        config.display.share_with_fen = !config.display.share_with_fen;
+                    }
+                    PauseMenuButton::HashSize => {
+                        // The AI plugin resizes the table right away
+                        let size_mb = &mut config.ai_settings.tt_size_mb;
+                        *size_mb = HASH_SIZES_MB.into_iter().find(|&size| size > *size_mb).unwrap_or(HASH_SIZES_MB[0]);
+                    }
+                    PauseMenuButton::Sound => {
+                        config.display.sound_enabled = !config.display.sound_enabled;
+                    }
+                    PauseMenuButton::RuleExplanations => {
+                        config.display.show_rule_explanations = !config.display.show_rule_explanations;
+                    }
+                    PauseMenuButton::AiStrength => {
+                        // Used from the AI's next search on
+                        let next = ai_strength(&config.ai_settings).map_or(0, |index| (index + 1) % AI_STRENGTHS.len());
+                        set_ai_strength(&mut config.ai_settings, next);
+                    }
+                    PauseMenuButton::Back => {
+                        *page = PauseMenuPage::Main;
+                    }
+                }
+                if button.changes_setting() {
+                    saved.record_and_save(&before, &config);
+                }
+            }
+            Interaction::Hovered => {
+                *background = BUTTON_HOVER_COLOR.into();
+            }
+            Interaction::None => {
+                *background = BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+/// The human side resigns; if both or neither side is human, the side to move resigns
+fn resigning_color(game_state: &GameState, config: &GameConfig) -> ChessColor {
+    match (config.white_player.is_ai, config.black_player.is_ai) {
+        (false, true) => ChessColor::White,
+        (true, false) => ChessColor::Black,
+        _ => game_state.current_player_turn,
+    }
+}
@@ -1,10 +1,185 @@
 use bevy::prelude::*;
+use crate::game_logic::events::NewGameEvent;
+use crate::game_logic::state::{PauseState, ReplayState, AppState, TutorialState, gameplay_active};
+use crate::input::focus::keyboard_shortcuts_enabled;
+use crate::modes::kiosk::KioskMode;
+use super::pause_menu::*;
+use super::low_power::*;
+use super::thinking_indicator::*;
+use super::replay::*;
+use super::daily_banner::*;
+use super::net_banner::*;
+use super::move_reminder::*;
+use super::drawback_banner::*;
+use super::setup_screen::*;
+use super::kiosk_overlay::*;
+use super::ai_crash_banner::*;
+use super::ladder_screen::*;
+use super::trophies::*;
+use super::tutorial::*;
+use super::clock::*;
+use super::move_tooltip::*;
+use super::review::*;
+use super::drawback_meter::*;
+use super::game_over::*;
+use super::observer_arrows::*;
+use super::reserve_tray::*;
+use super::capture_tray::*;
+use super::substitution_notice::*;
+use super::rule_explanation::*;
+use super::belief_panel::*;
+use super::arena_header::*;
+use super::console::*;
+use super::share::*;
+use super::chat::*;
+use crate::net::lockstep::NetSession;
 
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_ui);
+        app.init_resource::<PauseMenuPage>()
+           .init_resource::<RematchOffer>()
+           .init_resource::<WakeFrames>()
+           .init_resource::<CommentEditor>()
+           .init_resource::<EnginePlans>()
+           .init_resource::<BeliefPanel>()
+           .init_resource::<DevConsole>()
+           .init_resource::<ChatInput>()
+           .init_resource::<RuleExplanations>()
+           .add_systems(Startup, (setup_ui, setup_thinking_indicator, setup_replay_panel, start_url_import, setup_daily_banner, setup_clock_display, setup_move_tooltip, setup_drawback_meter, setup_reserve_tray, setup_capture_tray, setup_belief_panel, setup_arena_header, setup_console, setup_net_banner, setup_chat_panel, setup_move_reminder, setup_random_drawback_banner, setup_kiosk_overlay, setup_ai_crash_banner))
+           // Low power (reactive) rendering
+           .add_systems(Update, (apply_display_settings, keep_awake_while_busy))
+           // AI thinking indicator
+           .add_systems(Update, (update_thinking_indicator, draw_best_move_arrow))
+           // Both engines' plans in AI vs AI games
+           .add_systems(Update, (track_engine_plans, draw_engine_plans).chain().run_if(in_state(ReplayState::Live)))
+           .add_systems(Update, clear_engine_plans.run_if(on_event::<NewGameEvent>()))
+           // Replay mode with move annotations (Tab)
+           .add_systems(
+               Update,
+               toggle_replay
+                   .run_if(keyboard_shortcuts_enabled)
+                   .run_if(in_state(PauseState::Running))
+                   .run_if(in_state(AppState::InGame))
+           )
+           // Browsing the history from the live board (mouse wheel, Left)
+           .add_systems(
+               Update,
+               browse_history
+                   .before(navigate_replay)
+                   .run_if(in_state(PauseState::Running))
+                   .run_if(in_state(AppState::InGame))
+           )
+           .add_systems(Update, leave_browsing_on_new_move.run_if(in_state(ReplayState::Replay)))
+           .add_systems(
+               Update,
+               (
+                   edit_comment,
+                   (navigate_replay, annotate_move, save_or_load_pgn).run_if(keyboard_shortcuts_enabled),
+                   show_replay_position,
+               )
+                   .chain()
+                   .run_if(in_state(ReplayState::Replay))
+                   .run_if(in_state(PauseState::Running))
+           )
+           .add_systems(OnExit(ReplayState::Replay), restore_live_position)
+           .add_systems(Update, (update_replay_panel, finish_url_import))
+           // Daily challenge banner and chess clocks
+           .add_systems(Update, (update_daily_banner, update_net_banner, update_clock_display, play_low_time_warnings))
+           // Anti-stall reminder for the player to move
+           .add_systems(Update, update_move_reminder)
+           // Score of the best-of-N match
+           .add_systems(Update, update_arena_header)
+           // How many moves the drawback removed this turn, and what random drawbacks were rolled
+           .add_systems(Update, (update_drawback_meter, update_random_drawback_banner))
+           // Notice that the AI's search crashed and a fallback move was played
+           .add_systems(Update, update_ai_crash_banner)
+           // Attract overlay with both drawbacks in kiosk mode
+           .add_systems(Update, update_kiosk_overlay.run_if(resource_exists::<KioskMode>()))
+           // What the AI makes of the opponent's drawback (F8)
+           .add_systems(Update, (toggle_belief_panel.run_if(keyboard_shortcuts_enabled), update_belief_panel).chain())
+           // Captioned screenshots of the position ("Share Position" in the pause menu)
+           .init_resource::<PendingShare>()
+           .add_systems(Update, (start_position_share, capture_shared_position).chain())
+           // Developer console (`) with the game's events and debug commands
+           .add_systems(Update, (toggle_console, edit_console_input, run_console_commands, record_console_events, update_console_panel).chain())
+           // Chat with the other player of a network game (Enter)
+           .add_systems(
+               Update,
+               (open_chat_input.run_if(keyboard_shortcuts_enabled), edit_chat_input, handle_chat_buttons, update_chat_panel)
+                   .chain()
+                   .run_if(resource_exists::<NetSession>())
+           )
+           // Pieces in hand for rulesets with drops
+           .add_systems(Update, update_reserve_tray)
+           // Pieces each player has taken and who is ahead in material
+           .add_systems(Update, update_capture_tray)
+           // Where a piece ended up when a drawback changed the picked move
+           .add_systems(Update, (show_substitution_notice, expire_substitution_notice))
+           // The rule of a human player's drawback, the first time it takes away moves or rolls in a game
+           .add_systems(
+               Update,
+               (handle_rule_explanation_buttons, show_rule_explanation)
+                   .chain()
+                   .run_if(in_state(ReplayState::Live))
+                   .run_if(in_state(TutorialState::Off))
+           )
+           .add_systems(Update, clear_rule_explanations.run_if(on_event::<NewGameEvent>()))
+           // Game over banner and king capture animation
+           .add_systems(Update, (show_game_over_banner, animate_king_capture).chain())
+           .add_systems(Update, save_finished_game.after(show_game_over_banner).run_if(in_state(ReplayState::Live)))
+           .add_systems(Update, clear_game_over_banner.run_if(on_event::<NewGameEvent>()))
+           // Square name and move preview under the cursor
+           .add_systems(Update, update_move_tooltip.run_if(gameplay_active))
+           .add_systems(Update, hide_move_tooltip.run_if(not(gameplay_active)))
+           // Setup screen for the next game's players and drawbacks
+           .add_systems(Startup, open_setup_on_startup)
+           .add_systems(OnEnter(AppState::Setup), spawn_setup_screen)
+           .add_systems(OnExit(AppState::Setup), despawn_setup_screen)
+           .add_systems(Update, (handle_setup_buttons, refresh_setup_screen).chain().run_if(in_state(AppState::Setup)))
+           // Ladder screen
+           .add_systems(OnEnter(AppState::Ladder), spawn_ladder_screen)
+           .add_systems(OnExit(AppState::Ladder), despawn_ladder_screen)
+           .add_systems(Update, handle_ladder_buttons.run_if(in_state(AppState::Ladder)))
+           // Trophies screen and achievement toasts
+           .add_systems(OnEnter(AppState::Trophies), spawn_trophies_screen)
+           .add_systems(OnExit(AppState::Trophies), despawn_trophies_screen)
+           .add_systems(Update, handle_trophies_buttons.run_if(in_state(AppState::Trophies)))
+           .add_systems(Update, (show_achievement_toasts, expire_achievement_toasts))
+           // Game review screen
+           .add_systems(OnEnter(AppState::Review), spawn_review_screen)
+           .add_systems(OnExit(AppState::Review), despawn_review_screen)
+           .add_systems(Update, (handle_review_buttons, refresh_review_screen).chain().run_if(in_state(AppState::Review)))
+           // Tutorial popup and forced move highlight
+           .add_systems(OnEnter(TutorialState::Active), spawn_tutorial_popup)
+           .add_systems(OnExit(TutorialState::Active), despawn_tutorial_popup)
+           .add_systems(
+               Update,
+               (
+                   handle_tutorial_keys.run_if(keyboard_shortcuts_enabled).run_if(gameplay_active),
+                   update_tutorial_popup,
+                   draw_forced_move_highlight,
+               )
+                   .chain()
+                   .run_if(in_state(TutorialState::Active))
+           )
+           // Pause menu (Esc)
+           .add_systems(
+               Update,
+               toggle_pause
+                   .run_if(keyboard_shortcuts_enabled)
+                   .run_if(in_state(AppState::InGame))
+           )
+           .add_systems(OnEnter(PauseState::Paused), spawn_pause_menu)
+           .add_systems(OnExit(PauseState::Paused), despawn_pause_menu)
+           .add_systems(
+               Update,
+               (handle_pause_menu_buttons, refresh_pause_menu)
+                   .chain()
+                   .run_if(in_state(PauseState::Paused))
+           )
+           .add_systems(Update, clear_rematch_offer.run_if(on_event::<NewGameEvent>()));
     }
 }
 
@@ -18,4 +193,4 @@ fn setup_ui(mut commands: Commands) {
     });
     
     // Setup UI elements - to be implemented based on game requirements
-} 
\ No newline at end of file
+}
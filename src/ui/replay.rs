@@ -0,0 +1,509 @@
+use bevy::prelude::*;
+use bevy::tasks::{IoTaskPool, Task};
+use bevy::input::mouse::MouseWheel;
+use bevy::window::ReceivedCharacter;
+use futures_lite::future;
+use shakmaty::{Position, Color as ChessColor};
+use std::error::Error;
+use crate::board::components::BoardSquare;
+use crate::pieces::components::{PieceId, PieceIdAllocator};
+use crate::pieces::plugin::{sync_pieces_to_board, PieceEntities};
+use crate::game_logic::state::{GameState, ActiveBoard, GameStatus, TurnState, ReplayState};
+use crate::game_logic::clock::format_clock;
+use crate::game_logic::history::{MoveHistory, ReplayCursor, nag_glyph};
+use crate::game_logic::pgn::{write_pgn, read_pgn, outcome_tags, drawback_tags, ImportedGame};
+use crate::game_logic::online_import::{parse_game_source, fetch_game_pgn, GameSource};
+use crate::ai::analysis::ReplayAnalysis;
+use crate::ai::zobrist::ZobristKeys;
+use crate::drawbacks::DrawbackRegistry;
+use crate::input::focus::TextInputFocus;
+use crate::i18n::Localization;
+
+/// File used by the replay mode's save (S) and load (L) commands
+pub const REPLAY_PGN_PATH: &str = "drawback_chess_game.pgn";
+
+/// State of the move comment editor
+#[derive(Resource, Debug, Default)]
+pub struct CommentEditor {
+    pub active: bool,
+    pub buffer: String,
+}
+
+/// Component for a game download started with `--import <url>`
+#[derive(Component)]
+pub struct PendingGameImport {
+    pub source: GameSource,
+    pub task: Task<Result<String, String>>,
+}
+
+/// Marker for the replay information panel
+#[derive(Component)]
+pub struct ReplayPanelText;
+
+/// Spawns the (initially hidden) replay panel at the bottom of the window
+pub fn setup_replay_panel(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(8.0),
+            left: Val::Px(8.0),
+            right: Val::Px(8.0),
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.7)),
+        ReplayPanelText,
+    ));
+}
+
+/// System to enter/leave replay mode with Tab
+pub fn toggle_replay(
+    keys: Res<Input<KeyCode>>,
+    replay_state: Res<State<ReplayState>>,
+    mut next_replay_state: ResMut<NextState<ReplayState>>,
+    mut cursor: ResMut<ReplayCursor>,
+    history: Res<MoveHistory>,
+) {
+    if !keys.just_pressed(KeyCode::Tab) {
+        return;
+    }
+
+    match replay_state.get() {
+        ReplayState::Live => {
+            println!("Entering replay mode ({} moves recorded)", history.len());
+            cursor.ply = history.len();
+            cursor.browsing_from = None;
+            next_replay_state.set(ReplayState::Replay);
+        }
+        ReplayState::Replay => {
+            println!("Leaving replay mode");
+            next_replay_state.set(ReplayState::Live);
+        }
+    }
+}
+
+// Moves the replay cursor one move back (-1) or forward (1); stepping forward
+// past the last move ends the browsing started from the live board
+fn step_replay(step: i32, history: &MoveHistory, cursor: &mut ReplayCursor, next_replay_state: &mut NextState<ReplayState>) {
+    let ply = cursor.ply.min(history.len());
+    if step < 0 {
+        cursor.ply = ply.saturating_sub(1);
+    } else if step > 0 && ply == history.len() && cursor.browsing_from.is_some() {
+        cursor.browsing_from = None;
+        next_replay_state.set(ReplayState::Live);
+    } else if step > 0 {
+        cursor.ply = (ply + 1).min(history.len());
+    }
+}
+
+/// System to browse the move history with the mouse wheel (or Left on the live
+/// board): scrolling up from the live game shows the position before the last
+/// move, with input and the AI paused until the browsing steps past the last
+/// move again
+pub fn browse_history(
+    keys: Res<Input<KeyCode>>,
+    focus: Res<TextInputFocus>,
+    mut wheel: EventReader<MouseWheel>,
+    replay_state: Res<State<ReplayState>>,
+    history: Res<MoveHistory>,
+    mut cursor: ResMut<ReplayCursor>,
+    mut next_replay_state: ResMut<NextState<ReplayState>>,
+) {
+    // Ctrl + wheel zooms the board instead
+    let zooming = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let scrolled: f32 = wheel.read().filter(|_| !zooming).map(|event| event.y).sum();
+    let step = if scrolled > 0.0 { -1 } else if scrolled < 0.0 { 1 } else { 0 };
+
+    match replay_state.get() {
+        ReplayState::Live => {
+            let back = step < 0 || (!focus.0 && keys.just_pressed(KeyCode::Left));
+            if back && !history.is_empty() {
+                cursor.ply = history.len() - 1;
+                cursor.browsing_from = Some(history.len());
+                next_replay_state.set(ReplayState::Replay);
+            }
+        }
+        ReplayState::Replay => step_replay(step, &history, &mut cursor, &mut next_replay_state),
+    }
+}
+
+/// System to step through the recorded moves with the arrow keys
+pub fn navigate_replay(
+    keys: Res<Input<KeyCode>>,
+    history: Res<MoveHistory>,
+    mut cursor: ResMut<ReplayCursor>,
+    mut next_replay_state: ResMut<NextState<ReplayState>>,
+) {
+    if keys.just_pressed(KeyCode::Left) {
+        step_replay(-1, &history, &mut cursor, &mut next_replay_state);
+    }
+    if keys.just_pressed(KeyCode::Right) {
+        step_replay(1, &history, &mut cursor, &mut next_replay_state);
+    }
+    if keys.just_pressed(KeyCode::Home) && cursor.ply != 0 {
+        cursor.ply = 0;
+    }
+    if keys.just_pressed(KeyCode::End) && cursor.ply != history.len() {
+        cursor.ply = history.len();
+    }
+}
+
+/// System to snap back to the live game when a move is made (or a new game
+/// starts) while browsing the history
+pub fn leave_browsing_on_new_move(
+    history: Res<MoveHistory>,
+    mut cursor: ResMut<ReplayCursor>,
+    mut next_replay_state: ResMut<NextState<ReplayState>>,
+) {
+    if cursor.browsing_from.is_some_and(|plies| plies != history.len()) {
+        cursor.browsing_from = None;
+        next_replay_state.set(ReplayState::Live);
+    }
+}
+
+/// System to show the position at the replay cursor whenever it moves
+pub fn show_replay_position(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    history: Res<MoveHistory>,
+    cursor: Res<ReplayCursor>,
+    mut pieces: PieceEntities,
+    board_squares: Query<(&Transform, &BoardSquare), Without<PieceId>>,
+    mut piece_ids: ResMut<PieceIdAllocator>,
+) {
+    if !cursor.is_changed() && !history.is_changed() {
+        return;
+    }
+
+    let position = history.position_at(cursor.ply);
+    sync_pieces_to_board(&mut commands, &asset_server, position.board(), cursor.ply, &mut pieces, &board_squares, &mut piece_ids);
+}
+
+/// System to put the live position back on the board when leaving replay mode
+pub fn restore_live_position(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    history: Res<MoveHistory>,
+    mut pieces: PieceEntities,
+    board_squares: Query<(&Transform, &BoardSquare), Without<PieceId>>,
+    mut piece_ids: ResMut<PieceIdAllocator>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    sync_pieces_to_board(&mut commands, &asset_server, game_state.board.board(), history.len(), &mut pieces, &board_squares, &mut piece_ids);
+}
+
+/// System to attach NAG glyphs (keys 1-6, 0 clears) or open the comment editor (C)
+/// for the move that led to the displayed position
+pub fn annotate_move(
+    keys: Res<Input<KeyCode>>,
+    cursor: Res<ReplayCursor>,
+    mut history: ResMut<MoveHistory>,
+    mut editor: ResMut<CommentEditor>,
+    mut focus: ResMut<TextInputFocus>,
+) {
+    if cursor.ply == 0 || cursor.ply > history.len() {
+        return; // No move leads to the start position
+    }
+
+    let glyph_keys = [
+        (KeyCode::Key1, 1),
+        (KeyCode::Key2, 2),
+        (KeyCode::Key3, 3),
+        (KeyCode::Key4, 4),
+        (KeyCode::Key5, 5),
+        (KeyCode::Key6, 6),
+    ];
+
+    let record = &mut history.moves[cursor.ply - 1];
+
+    for (key, nag) in glyph_keys {
+        if keys.just_pressed(key) {
+            // Move assessment glyphs are mutually exclusive, so pressing the
+            // same one again removes it and a different one replaces it
+            let had_nag = record.nags.contains(&nag);
+            record.nags.retain(|n| !(1..=6).contains(n));
+            if !had_nag {
+                record.nags.push(nag);
+            }
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Key0) {
+        record.nags.clear();
+    }
+
+    if keys.just_pressed(KeyCode::C) {
+        editor.active = true;
+        editor.buffer = record.comment.clone().unwrap_or_default();
+        focus.0 = true;
+    }
+}
+
+/// System handling text entry for the comment editor.
+/// Enter saves (an empty comment removes it), Esc cancels.
+pub fn edit_comment(
+    mut ev_chars: EventReader<ReceivedCharacter>,
+    mut keys: ResMut<Input<KeyCode>>,
+    cursor: Res<ReplayCursor>,
+    mut history: ResMut<MoveHistory>,
+    mut editor: ResMut<CommentEditor>,
+    mut focus: ResMut<TextInputFocus>,
+) {
+    if !editor.active {
+        // Drop characters typed while not editing (including the C that opens the editor)
+        ev_chars.clear();
+        return;
+    }
+
+    for ev in ev_chars.read() {
+        if !ev.char.is_control() {
+            editor.buffer.push(ev.char);
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Back) {
+        editor.buffer.pop();
+    }
+
+    if keys.just_pressed(KeyCode::Return) {
+        if cursor.ply > 0 && cursor.ply <= history.len() {
+            let text = editor.buffer.trim().to_string();
+            history.moves[cursor.ply - 1].comment = if text.is_empty() { None } else { Some(text) };
+        }
+        editor.active = false;
+        // Don't let the same key press reach other shortcuts
+        keys.reset(KeyCode::Return);
+    } else if keys.just_pressed(KeyCode::Escape) {
+        editor.active = false;
+        keys.reset(KeyCode::Escape);
+    }
+
+    if !editor.active {
+        editor.buffer.clear();
+        focus.0 = false;
+    }
+}
+
+/// System to save the annotated game (S) or load it back (L) as PGN
+pub fn save_or_load_pgn(
+    keys: Res<Input<KeyCode>>,
+    mut history: ResMut<MoveHistory>,
+    mut cursor: ResMut<ReplayCursor>,
+    mut boards: Query<&mut GameState, With<ActiveBoard>>,
+    mut next_turn_state: ResMut<NextState<TurnState>>,
+    registry: Res<DrawbackRegistry>,
+    zobrist_keys: Res<ZobristKeys>,
+) {
+    let Ok(mut game_state) = boards.get_single_mut() else {
+        return;
+    };
+    if keys.just_pressed(KeyCode::S) && history.is_empty() {
+        println!("No moves to save yet");
+    } else if keys.just_pressed(KeyCode::S) {
+        let mut tags = outcome_tags(game_state.status.result());
+        tags.extend(drawback_tags(&game_state, &registry));
+        let pgn = write_pgn(&history, &tags);
+        match std::fs::write(REPLAY_PGN_PATH, pgn) {
+            Ok(()) => println!("Saved annotated game to {}", REPLAY_PGN_PATH),
+            Err(e) => eprintln!("Failed to save {}: {}", REPLAY_PGN_PATH, e),
+        }
+    }
+
+    if keys.just_pressed(KeyCode::L) {
+        match load_pgn_file(REPLAY_PGN_PATH) {
+            Ok(imported) => {
+                println!("Loaded {} moves from {}", imported.history.len(), REPLAY_PGN_PATH);
+                apply_imported_game(imported, &mut history, &mut cursor, &mut game_state, &mut next_turn_state, &zobrist_keys);
+            }
+            Err(e) => eprintln!("Failed to load {}: {}", REPLAY_PGN_PATH, e),
+        }
+    }
+}
+
+/// Replaces the recorded game with an imported one and shows its final position.
+/// The live game continues from the end of the imported game.
+pub fn apply_imported_game(
+    imported: ImportedGame,
+    history: &mut MoveHistory,
+    cursor: &mut ReplayCursor,
+    game_state: &mut GameState,
+    next_turn_state: &mut NextState<TurnState>,
+    zobrist_keys: &ZobristKeys,
+) {
+    println!(
+        "Imported game: {} vs {} ({} moves)",
+        imported.tag("White").unwrap_or("?"),
+        imported.tag("Black").unwrap_or("?"),
+        imported.history.len()
+    );
+
+    // Drawback parameters saved with the game replace the configured ones
+    if let Some(params) = imported.drawback_params(ChessColor::White) {
+        game_state.white_drawback_params = params;
+    }
+    if let Some(params) = imported.drawback_params(ChessColor::Black) {
+        game_state.black_drawback_params = params;
+    }
+
+    *history = imported.history;
+    cursor.ply = history.len();
+    cursor.browsing_from = None;
+
+    game_state.board = history.final_position();
+    game_state.current_player_turn = game_state.board.turn();
+    game_state.status = GameStatus::Ongoing;
+    game_state.current_turn_rng_outcome = None;
+    game_state.last_move = history.moves.last().map(|record| record.chess_move.clone());
+    game_state.zobrist_hash = game_state.position_key(zobrist_keys);
+    next_turn_state.set(match game_state.current_player_turn {
+        ChessColor::White => TurnState::PlayerTurn,
+        ChessColor::Black => TurnState::AiTurn,
+    });
+}
+
+/// Startup system: `--import <url or lichess id>` downloads a game in the background
+pub fn start_url_import(mut commands: Commands) {
+    let args: Vec<String> = std::env::args().collect();
+    let Some(input) = args.iter().position(|arg| arg == "--import").and_then(|i| args.get(i + 1)) else {
+        return;
+    };
+
+    let Some(source) = parse_game_source(input) else {
+        eprintln!("Not a lichess/chess.com game URL or ID: {}", input);
+        return;
+    };
+
+    println!("Downloading {}...", source.describe());
+    let task_source = source.clone();
+    let task = IoTaskPool::get().spawn(async move {
+        fetch_game_pgn(&task_source).map_err(|e| e.to_string())
+    });
+
+    commands.spawn(PendingGameImport { source, task });
+}
+
+/// System to open a downloaded game in replay mode once it arrives
+#[allow(clippy::too_many_arguments)]
+pub fn finish_url_import(
+    mut commands: Commands,
+    mut imports: Query<(Entity, &mut PendingGameImport)>,
+    mut history: ResMut<MoveHistory>,
+    mut cursor: ResMut<ReplayCursor>,
+    mut boards: Query<&mut GameState, With<ActiveBoard>>,
+    mut next_turn_state: ResMut<NextState<TurnState>>,
+    mut next_replay_state: ResMut<NextState<ReplayState>>,
+    zobrist_keys: Res<ZobristKeys>,
+) {
+    let Ok(mut game_state) = boards.get_single_mut() else {
+        return;
+    };
+    for (entity, mut import) in imports.iter_mut() {
+        let Some(result) = future::block_on(future::poll_once(&mut import.task)) else {
+            continue;
+        };
+        commands.entity(entity).despawn();
+
+        match result.map_err(Into::into).and_then(|pgn| read_pgn(&pgn)) {
+            Ok(imported) => {
+                apply_imported_game(imported, &mut history, &mut cursor, &mut game_state, &mut next_turn_state, &zobrist_keys);
+                next_replay_state.set(ReplayState::Replay);
+            }
+            Err(e) => eprintln!("Failed to import {}: {}", import.source.describe(), e),
+        }
+    }
+}
+
+// Helper function to read and parse a PGN file
+fn load_pgn_file(path: &str) -> Result<ImportedGame, Box<dyn Error>> {
+    let text = std::fs::read_to_string(path)?;
+    read_pgn(&text)
+}
+
+/// Updates the replay panel with the current move, its annotations and the key help
+pub fn update_replay_panel(
+    replay_state: Res<State<ReplayState>>,
+    history: Res<MoveHistory>,
+    cursor: Res<ReplayCursor>,
+    editor: Res<CommentEditor>,
+    analysis: Res<ReplayAnalysis>,
+    localization: Res<Localization>,
+    mut panel: Query<(&mut Text, &mut Visibility), With<ReplayPanelText>>,
+) {
+    let Ok((mut text, mut visibility)) = panel.get_single_mut() else {
+        return;
+    };
+
+    if *replay_state.get() != ReplayState::Replay {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    *visibility = Visibility::Visible;
+
+    if !replay_state.is_changed()
+        && !history.is_changed()
+        && !cursor.is_changed()
+        && !editor.is_changed()
+        && !analysis.is_changed()
+        && !localization.is_changed()
+    {
+        return;
+    }
+
+    let ply = cursor.ply.min(history.len());
+    let mut lines = Vec::new();
+
+    if ply == 0 {
+        lines.push(localization.text_with("replay-start", &[("total", history.len().to_string())]));
+    } else {
+        let record = &history.moves[ply - 1];
+        let before = history.position_at(ply - 1);
+        let number = match before.turn() {
+            ChessColor::White => format!("{}.", before.fullmoves()),
+            ChessColor::Black => format!("{}...", before.fullmoves()),
+        };
+        let glyphs: String = record.nags.iter().map(|nag| match nag_glyph(*nag) {
+            Some(glyph) => glyph.to_string(),
+            None => format!(" ${}", nag),
+        }).collect();
+        lines.push(localization.text_with("replay-move", &[
+            ("number", number),
+            ("san", record.san.clone()),
+            ("glyphs", glyphs),
+            ("ply", ply.to_string()),
+            ("total", history.len().to_string()),
+        ]));
+        match (record.time_ms, record.clock_ms) {
+            (Some(spent), Some(clock)) => lines.push(localization.text_with("replay-time-clock", &[("spent", format_clock(spent)), ("clock", format_clock(clock))])),
+            (Some(spent), None) => lines.push(localization.text_with("replay-time", &[("spent", format_clock(spent))])),
+            (None, _) => {}
+        }
+        if let Some(comment) = &record.comment {
+            lines.push(format!("{{{}}}", comment));
+        }
+    }
+
+    let score = match (analysis.score_cp, analysis.is_running()) {
+        (_, true) => "...".to_string(),
+        (Some(score), false) => format!("{:+.2}", score as f32 / 100.0),
+        (None, false) => "-".to_string(),
+    };
+    lines.push(localization.text_with("replay-eval", &[("score", score)]));
+
+    if editor.active {
+        lines.push(localization.text_with("replay-comment-editor", &[("text", editor.buffer.clone())]));
+    } else {
+        lines.push(localization.text("replay-help"));
+    }
+
+    text.sections[0].value = lines.join("\n");
+}
@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+use shakmaty::{Color as ChessColor, Role};
+use crate::config::{GameConfig, Ruleset};
+use crate::drawbacks::DrawbackRegistry;
+use crate::game_logic::drops::{DROPPABLE_ROLES, drawback_has_drops};
+use crate::game_logic::state::{GameState, ActiveBoard};
+
+const SLOT_SIZE: f32 = 48.0;
+const SLOT_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.15);
+const SLOT_HOVER_COLOR: Color = Color::rgba(1.0, 0.85, 0.2, 0.6);
+const EMPTY_SLOT_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.03);
+
+/// Marker for the root node of the reserve tray
+#[derive(Component)]
+pub struct ReserveTray;
+
+/// A kind of piece in a player's hand; clicking it picks the piece to drop
+#[derive(Component, Debug)]
+pub struct ReserveButton {
+    pub color: ChessColor,
+    pub role: Role,
+}
+
+/// Marker for the count shown on a reserve button
+#[derive(Component)]
+pub struct ReserveCount {
+    pub color: ChessColor,
+    pub role: Role,
+}
+
+/// Spawns the (hidden) tray with both players' hands on the right of the screen
+pub fn setup_reserve_tray(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(35.0),
+                right: Val::Px(8.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(12.0),
+                padding: UiRect::all(Val::Px(6.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        ReserveTray,
+    )).with_children(|tray| {
+        for color in [ChessColor::Black, ChessColor::White] {
+            tray.spawn(NodeBundle {
+                style: Style { flex_direction: FlexDirection::Row, column_gap: Val::Px(4.0), ..default() },
+                ..default()
+            }).with_children(|row| {
+                for role in DROPPABLE_ROLES {
+                    row.spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(SLOT_SIZE),
+                                height: Val::Px(SLOT_SIZE),
+                                ..default()
+                            },
+                            background_color: EMPTY_SLOT_COLOR.into(),
+                            ..default()
+                        },
+                        ReserveButton { color, role },
+                    )).with_children(|button| {
+                        button.spawn(ImageBundle {
+                            image: UiImage::new(asset_server.load(piece_image_path(color, role))),
+                            style: Style { width: Val::Percent(100.0), height: Val::Percent(100.0), ..default() },
+                            ..default()
+                        });
+                        // Count in the bottom right corner, empty while there is none in hand
+                        button.spawn((
+                            TextBundle::from_section("", TextStyle { font_size: 16.0, color: Color::WHITE, ..default() })
+                                .with_style(Style {
+                                    position_type: PositionType::Absolute,
+                                    right: Val::Px(2.0),
+                                    bottom: Val::Px(0.0),
+                                    ..default()
+                                })
+                                .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.7)),
+                            ReserveCount { color, role },
+                        ));
+                    });
+                }
+            });
+        }
+    });
+}
+
+/// Shows the tray while anyone can drop pieces (by the ruleset or a drawback), with the count of every piece in hand.
+/// The side shown at the top of the board gets the top row.
+pub fn update_reserve_tray(
+    config: Res<GameConfig>,
+    registry: Res<DrawbackRegistry>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    mut trays: Query<(&mut Visibility, &mut Style), With<ReserveTray>>,
+    mut buttons: Query<(&ReserveButton, &Interaction, &mut BackgroundColor)>,
+    mut counts: Query<(&ReserveCount, &mut Text)>,
+) {
+    let (Ok(game_state), Ok((mut visibility, mut style))) = (boards.get_single(), trays.get_single_mut()) else {
+        return;
+    };
+    let reserves = &game_state.reserves;
+    let drawback_drops = [game_state.white_drawback, game_state.black_drawback]
+        .iter()
+        .filter_map(|drawback| registry.rules.get(drawback))
+        .any(|rule| drawback_has_drops(rule.as_ref()));
+    let shown = config.ruleset == Ruleset::Crazyhouse || drawback_drops || !reserves.is_empty();
+    visibility.set_if_neq(if shown { Visibility::Inherited } else { Visibility::Hidden });
+    if !shown {
+        return;
+    }
+
+    // The rows are spawned Black first
+    let direction = if game_state.board_flipped { FlexDirection::ColumnReverse } else { FlexDirection::Column };
+    if style.flex_direction != direction {
+        style.flex_direction = direction;
+    }
+    for (count, mut text) in counts.iter_mut() {
+        let value = match reserves.count(count.color, count.role) {
+            0 => String::new(),
+            n => n.to_string(),
+        };
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+    for (button, interaction, mut background) in buttons.iter_mut() {
+        let color = match (reserves.count(button.color, button.role), interaction) {
+            (0, _) => EMPTY_SLOT_COLOR,
+            (_, Interaction::Hovered | Interaction::Pressed) => SLOT_HOVER_COLOR,
+            _ => SLOT_COLOR,
+        };
+        if background.0 != color {
+            background.0 = color;
+        }
+    }
+}
+
+/// Image of a piece, as the board shows it
+pub fn piece_image_path(color: ChessColor, role: Role) -> String {
+    let color_prefix = match color {
+        ChessColor::White => "w",
+        ChessColor::Black => "b",
+    };
+    format!("images/{}{}.png", color_prefix, role.upper_char())
+}
@@ -0,0 +1,320 @@
+use bevy::prelude::*;
+use shakmaty::Color as ChessColor;
+use crate::ai::review::{GameReview, GameReviewState};
+use crate::drawbacks::DrawbackRegistry;
+use crate::game_logic::history::ReplayCursor;
+use crate::game_logic::state::{AppState, ReplayState};
+use crate::i18n::Localization;
+
+// Colors for the review screen
+const SCREEN_COLOR: Color = Color::rgba(0.05, 0.05, 0.08, 0.92);
+const TEXT_COLOR: Color = Color::WHITE;
+const DIM_TEXT_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+const BUTTON_COLOR: Color = Color::rgb(0.25, 0.25, 0.25);
+const GRAPH_BACKGROUND_COLOR: Color = Color::rgb(0.15, 0.15, 0.18);
+const WHITE_AHEAD_COLOR: Color = Color::rgb(0.9, 0.9, 0.85);
+const BLACK_AHEAD_COLOR: Color = Color::rgb(0.45, 0.3, 0.25);
+const EVAL_COLOR: Color = Color::rgb(0.95, 0.6, 0.15);
+const GRAPH_HOVER_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.12);
+
+// Size of the material graph; each half fits this many pawns of advantage
+const GRAPH_WIDTH: f32 = 600.0;
+const GRAPH_HALF_HEIGHT: f32 = 60.0;
+const GRAPH_MAX_PAWNS: f32 = 15.0;
+const EVAL_MARKER_HEIGHT: f32 = 3.0;
+const TIME_GRAPH_HEIGHT: f32 = 50.0;
+
+/// Marker for the root node of the review screen
+#[derive(Component)]
+pub struct ReviewScreenRoot;
+
+/// Buttons of the review screen
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewButton {
+    JumpTo(usize), // Shows the position before this ply in replay mode
+    GraphPly(usize), // Column of the graph; jumps like `JumpTo`
+    Back,
+}
+
+/// Spawns the review screen for the last finished game
+pub fn spawn_review_screen(
+    mut commands: Commands,
+    review_state: Res<GameReviewState>,
+    registry: Res<DrawbackRegistry>,
+    localization: Res<Localization>,
+) {
+    build_review_screen(&mut commands, &review_state, &registry, &localization);
+}
+
+/// Removes the review screen
+pub fn despawn_review_screen(mut commands: Commands, roots: Query<Entity, With<ReviewScreenRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Rebuilds the screen once the analysis running in the background is done
+pub fn refresh_review_screen(
+    mut commands: Commands,
+    review_state: Res<GameReviewState>,
+    registry: Res<DrawbackRegistry>,
+    localization: Res<Localization>,
+    roots: Query<Entity, With<ReviewScreenRoot>>,
+) {
+    if !(review_state.is_changed() || localization.is_changed()) || roots.is_empty() {
+        return;
+    }
+
+    for entity in roots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    build_review_screen(&mut commands, &review_state, &registry, &localization);
+}
+
+/// Handles the mistake links, the graph columns and the "Back" button
+pub fn handle_review_buttons(
+    mut interactions: Query<(&Interaction, &ReviewButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_replay_state: ResMut<NextState<ReplayState>>,
+    mut cursor: ResMut<ReplayCursor>,
+) {
+    for (interaction, button, mut background) in interactions.iter_mut() {
+        if let ReviewButton::GraphPly(_) = button {
+            let hovered = *interaction != Interaction::None;
+            *background = if hovered { GRAPH_HOVER_COLOR } else { Color::NONE }.into();
+        }
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            ReviewButton::JumpTo(ply) | ReviewButton::GraphPly(ply) => {
+                cursor.ply = *ply;
+                cursor.browsing_from = None;
+                next_replay_state.set(ReplayState::Replay);
+            }
+            ReviewButton::Back => {}
+        }
+        next_app_state.set(AppState::InGame);
+    }
+}
+
+// Helper function to build the screen for the current review state
+fn build_review_screen(commands: &mut Commands, review_state: &GameReviewState, registry: &DrawbackRegistry, localization: &Localization) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            background_color: SCREEN_COLOR.into(),
+            z_index: ZIndex::Global(90), // Below the pause menu
+            ..default()
+        },
+        ReviewScreenRoot,
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            localization.text("review-title"),
+            TextStyle { font_size: 40.0, color: TEXT_COLOR, ..default() },
+        ));
+
+        match &review_state.review {
+            Some(review) => build_review_contents(parent, review, registry, localization),
+            None => {
+                let key = if review_state.is_running() { "review-analysing" } else { "review-none" };
+                parent.spawn(TextBundle::from_section(
+                    localization.text(key),
+                    TextStyle { font_size: 20.0, color: DIM_TEXT_COLOR, ..default() },
+                ));
+            }
+        }
+
+        spawn_button(parent, localization.text("review-back"), ReviewButton::Back);
+    });
+}
+
+// Helper function to add the statistics, material graph and mistakes of a review
+fn build_review_contents(parent: &mut ChildBuilder, review: &GameReview, registry: &DrawbackRegistry, localization: &Localization) {
+    let line = |text: String, color: Color| TextBundle::from_section(text, TextStyle { font_size: 20.0, color, ..default() });
+
+    if let Some(reason) = &review.reason {
+        parent.spawn(line(
+            localization.text_with("review-reason", &[("reason", localization.game_over_reason(reason, registry))]),
+            DIM_TEXT_COLOR,
+        ));
+    }
+    for color in [ChessColor::White, ChessColor::Black] {
+        let Some(accuracy) = review.accuracy(color) else {
+            continue;
+        };
+        parent.spawn(line(
+            localization.text_with("review-player", &[
+                ("color", localization.color_name(color)),
+                ("accuracy", format!("{:.0}", accuracy)),
+                ("removed", format!("{:.1}", review.drawback_impact(color).unwrap_or(0.0))),
+            ]),
+            TEXT_COLOR,
+        ));
+    }
+
+    parent.spawn(line(localization.text("review-graph"), DIM_TEXT_COLOR));
+    spawn_review_graph(parent, &review.material_graph(), &review.eval_graph());
+
+    let times = review.time_graph();
+    if times.iter().any(Option::is_some) {
+        parent.spawn(line(localization.text("review-time"), DIM_TEXT_COLOR));
+        spawn_time_graph(parent, review, &times);
+    }
+
+    parent.spawn(line(localization.text("review-mistakes"), DIM_TEXT_COLOR));
+    let mistakes = review.biggest_mistakes();
+    if mistakes.is_empty() {
+        parent.spawn(line(localization.text("review-no-mistakes"), TEXT_COLOR));
+    }
+    for mistake in mistakes {
+        let dots = if mistake.color == ChessColor::White { "." } else { "..." };
+        let label = localization.text_with("review-mistake", &[
+            ("number", format!("{}{}", mistake.move_number, dots)),
+            ("san", mistake.san.clone()),
+            ("loss", format!("{:.0}", mistake.win_chance_lost())),
+        ]);
+        spawn_button(parent, label, ReviewButton::JumpTo(mistake.ply));
+    }
+}
+
+// Helper function to draw one clickable column per ply: the material balance as
+// a bar (up for a White advantage, down for a Black one) and the evaluation as a
+// marker at White's winning chances
+fn spawn_review_graph(parent: &mut ChildBuilder, material: &[i32], evals: &[Option<f32>]) {
+    let column_width = GRAPH_WIDTH / material.len().max(1) as f32;
+
+    parent.spawn(NodeBundle {
+        style: Style {
+            width: Val::Px(GRAPH_WIDTH),
+            height: Val::Px(GRAPH_HALF_HEIGHT * 2.0),
+            flex_direction: FlexDirection::Row,
+            ..default()
+        },
+        background_color: GRAPH_BACKGROUND_COLOR.into(),
+        ..default()
+    }).with_children(|graph| {
+        for (ply, &balance) in material.iter().enumerate() {
+            let height = (balance.unsigned_abs() as f32 / GRAPH_MAX_PAWNS).min(1.0) * GRAPH_HALF_HEIGHT;
+            let (top, color) = if balance >= 0 {
+                (GRAPH_HALF_HEIGHT - height, WHITE_AHEAD_COLOR)
+            } else {
+                (GRAPH_HALF_HEIGHT, BLACK_AHEAD_COLOR)
+            };
+            graph.spawn((
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(column_width),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: Color::NONE.into(),
+                    ..default()
+                },
+                ReviewButton::GraphPly(ply),
+            )).with_children(|column| {
+                column.spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.0),
+                        height: Val::Px(height),
+                        top: Val::Px(top),
+                        ..default()
+                    },
+                    background_color: color.into(),
+                    ..default()
+                });
+                if let Some(eval) = evals.get(ply).copied().flatten() {
+                    let center = GRAPH_HALF_HEIGHT * (1.0 - eval.clamp(-1.0, 1.0));
+                    column.spawn(NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            width: Val::Percent(100.0),
+                            height: Val::Px(EVAL_MARKER_HEIGHT),
+                            top: Val::Px((center - EVAL_MARKER_HEIGHT / 2.0).clamp(0.0, GRAPH_HALF_HEIGHT * 2.0 - EVAL_MARKER_HEIGHT)),
+                            ..default()
+                        },
+                        background_color: EVAL_COLOR.into(),
+                        ..default()
+                    });
+                }
+            });
+        }
+    });
+}
+
+// Helper function to draw the time spent on each move as a bar in the mover's
+// color; clicking a bar shows the position after that move
+fn spawn_time_graph(parent: &mut ChildBuilder, review: &GameReview, times: &[Option<f32>]) {
+    let column_width = GRAPH_WIDTH / times.len().max(1) as f32;
+
+    parent.spawn(NodeBundle {
+        style: Style {
+            width: Val::Px(GRAPH_WIDTH),
+            height: Val::Px(TIME_GRAPH_HEIGHT),
+            flex_direction: FlexDirection::Row,
+            ..default()
+        },
+        background_color: GRAPH_BACKGROUND_COLOR.into(),
+        ..default()
+    }).with_children(|graph| {
+        for (move_review, share) in review.moves.iter().zip(times) {
+            let color = if move_review.color == ChessColor::White { WHITE_AHEAD_COLOR } else { BLACK_AHEAD_COLOR };
+            graph.spawn((
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(column_width),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: Color::NONE.into(),
+                    ..default()
+                },
+                ReviewButton::GraphPly(move_review.ply + 1),
+            )).with_children(|column| {
+                let height = share.unwrap_or(0.0) * TIME_GRAPH_HEIGHT;
+                column.spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.0),
+                        height: Val::Px(height),
+                        top: Val::Px(TIME_GRAPH_HEIGHT - height),
+                        ..default()
+                    },
+                    background_color: color.into(),
+                    ..default()
+                });
+            });
+        }
+    });
+}
+
+fn spawn_button(parent: &mut ChildBuilder, label: String, button: ReviewButton) {
+    parent.spawn((
+        ButtonBundle {
+            style: Style {
+                min_width: Val::Px(220.0),
+                height: Val::Px(36.0),
+                padding: UiRect::horizontal(Val::Px(12.0)),
+                margin: UiRect::top(Val::Px(6.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: BUTTON_COLOR.into(),
+            ..default()
+        },
+        button,
+    )).with_children(|button_parent| {
+        button_parent.spawn(TextBundle::from_section(label, TextStyle { font_size: 20.0, color: TEXT_COLOR, ..default() }));
+    });
+}
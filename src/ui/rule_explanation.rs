@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+use shakmaty::Color as ChessColor;
+use crate::config::{GameConfig, SavedSettings};
+use crate::drawbacks::DrawbackRegistry;
+use crate::game_logic::legal_moves::LegalMovesCache;
+use crate::game_logic::state::{GameState, ActiveBoard, GameStatus};
+use crate::i18n::Localization;
+use crate::input::systems::SelectedPiece;
+use crate::pieces::components::Piece;
+
+const POPUP_COLOR: Color = Color::rgba(0.05, 0.05, 0.12, 0.92);
+const REASON_COLOR: Color = Color::rgb(0.7, 0.7, 0.7);
+const BUTTON_COLOR: Color = Color::rgb(0.2, 0.2, 0.25);
+const BUTTON_HOVER_COLOR: Color = Color::rgb(0.3, 0.3, 0.38);
+
+/// Resource with the players whose drawback was already explained this game
+#[derive(Resource, Debug, Default)]
+pub struct RuleExplanations {
+    pub explained: Vec<ChessColor>,
+}
+
+/// Marker for the root node of the rule explanation popup
+#[derive(Component)]
+pub struct RuleExplanationPopup;
+
+/// Buttons of the rule explanation popup
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleExplanationButton {
+    Close,
+    DontShowAgain, // Also turns `show_rule_explanations` off
+}
+
+// What made the drawback show itself
+#[derive(Debug, Clone, Copy)]
+enum Trigger {
+    Filtered, // It took away moves of the piece just selected
+    Rolled,   // It rolled for the turn
+}
+
+/// Explains a human player's drawback the first time in the game it takes away
+/// moves of a selected piece or rolls for the turn
+#[allow(clippy::too_many_arguments)]
+pub fn show_rule_explanation(
+    mut commands: Commands,
+    mut explanations: ResMut<RuleExplanations>,
+    boards: Query<Ref<GameState>, With<ActiveBoard>>,
+    config: Res<GameConfig>,
+    registry: Res<DrawbackRegistry>,
+    localization: Res<Localization>,
+    legal_moves: Res<LegalMovesCache>,
+    selected: Query<&Piece, Added<SelectedPiece>>,
+    popups: Query<(), With<RuleExplanationPopup>>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    if !config.display.show_rule_explanations || game_state.status != GameStatus::Ongoing || !popups.is_empty() {
+        return;
+    }
+    let color = game_state.current_player_turn;
+    let is_ai = match color {
+        ChessColor::White => config.white_player.is_ai,
+        ChessColor::Black => config.black_player.is_ai,
+    };
+    if is_ai || explanations.explained.contains(&color) {
+        return;
+    }
+    let Some(rule) = game_state.current_drawback_rule(&registry) else {
+        return;
+    };
+
+    let filtered = selected.iter().any(|piece| {
+        piece.color == color && legal_moves.blocked().iter().any(|chess_move| chess_move.from() == Some(piece.pos))
+    });
+    let rolled = game_state.is_changed() && rule.needs_turn_rng() && game_state.current_turn_rng_outcome.is_some();
+    let trigger = match (filtered, rolled) {
+        (true, _) => Trigger::Filtered,
+        (false, true) => Trigger::Rolled,
+        (false, false) => return,
+    };
+    explanations.explained.push(color);
+
+    let (id, params) = game_state.drawback_of(color);
+    let title = localization.text_with("rule-explanation-title", &[
+        ("color", localization.color_name(color)),
+        ("drawback", localization.drawback_name(&registry, id)),
+    ]);
+    let reason = localization.text(match trigger {
+        Trigger::Filtered => "rule-explanation-filtered",
+        Trigger::Rolled => "rule-explanation-rolled",
+    });
+    spawn_popup(&mut commands, &localization, title, localization.drawback_description(&registry, id, params), reason);
+}
+
+// Helper function spawning the popup at the top of the window
+fn spawn_popup(commands: &mut Commands, localization: &Localization, title: String, description: String, reason: String) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(60.0),
+                left: Val::Percent(25.0),
+                width: Val::Percent(50.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            background_color: POPUP_COLOR.into(),
+            z_index: ZIndex::Global(80), // Below the pause menu and other screens
+            ..default()
+        },
+        RuleExplanationPopup,
+    )).with_children(|popup| {
+        popup.spawn(TextBundle::from_section(title, TextStyle { font_size: 22.0, color: Color::WHITE, ..default() }));
+        popup.spawn(TextBundle::from_section(description, TextStyle { font_size: 18.0, color: Color::WHITE, ..default() }));
+        popup.spawn(TextBundle::from_section(reason, TextStyle { font_size: 16.0, color: REASON_COLOR, ..default() }));
+
+        popup.spawn(NodeBundle {
+            style: Style { column_gap: Val::Px(8.0), ..default() },
+            ..default()
+        }).with_children(|row| {
+            let buttons = [
+                (RuleExplanationButton::Close, "rule-explanation-close"),
+                (RuleExplanationButton::DontShowAgain, "rule-explanation-dont-show"),
+            ];
+            for (button, key) in buttons {
+                row.spawn((
+                    ButtonBundle {
+                        style: Style { padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)), ..default() },
+                        background_color: BUTTON_COLOR.into(),
+                        ..default()
+                    },
+                    button,
+                )).with_children(|button_node| {
+                    button_node.spawn(TextBundle::from_section(localization.text(key), TextStyle { font_size: 16.0, color: Color::WHITE, ..default() }));
+                });
+            }
+        });
+    });
+}
+
+/// Closes the popup; "don't show again" also turns the explanations off
+pub fn handle_rule_explanation_buttons(
+    mut commands: Commands,
+    mut interactions: Query<(&Interaction, &RuleExplanationButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut config: ResMut<GameConfig>,
+    mut saved: ResMut<SavedSettings>,
+    popups: Query<Entity, With<RuleExplanationPopup>>,
+) {
+    for (interaction, button, mut background) in interactions.iter_mut() {
+        *background = match interaction {
+            Interaction::None => BUTTON_COLOR.into(),
+            Interaction::Hovered | Interaction::Pressed => BUTTON_HOVER_COLOR.into(),
+        };
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if *button == RuleExplanationButton::DontShowAgain {
+            let before = config.clone();
+            config.display.show_rule_explanations = false;
+            saved.record_and_save(&before, &config);
+        }
+        for entity in popups.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// A new game explains the drawbacks afresh
+pub fn clear_rule_explanations(
+    mut commands: Commands,
+    mut explanations: ResMut<RuleExplanations>,
+    popups: Query<Entity, With<RuleExplanationPopup>>,
+) {
+    explanations.explained.clear();
+    for entity in popups.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,255 @@
+use bevy::prelude::*;
+use shakmaty::Color as ChessColor;
+use crate::config::{DrawbackSetting, GameConfig};
+use crate::drawbacks::{DrawbackId, DrawbackParams, DrawbackRegistry};
+use crate::game_logic::events::NewGameEvent;
+use crate::game_logic::state::AppState;
+use crate::i18n::Localization;
+
+/// `--setup` opens the setup screen instead of starting the configured game
+pub const SETUP_FLAG: &str = "--setup";
+
+// Colors for the setup screen
+const SCREEN_COLOR: Color = Color::rgba(0.05, 0.05, 0.08, 0.92);
+const PANEL_COLOR: Color = Color::rgb(0.12, 0.12, 0.15);
+const BUTTON_COLOR: Color = Color::rgb(0.25, 0.25, 0.25);
+const BUTTON_HOVER_COLOR: Color = Color::rgb(0.35, 0.35, 0.35);
+const DESCRIPTION_COLOR: Color = Color::rgb(0.75, 0.75, 0.75);
+
+/// Marker for the root node of the setup screen
+#[derive(Component)]
+pub struct SetupScreenRoot;
+
+/// Action attached to each setup screen button
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetupButton {
+    Role(ChessColor),             // Switches the side between human and AI
+    PreviousDrawback(ChessColor),
+    NextDrawback(ChessColor),
+    Start,
+    Back,
+}
+
+/// Startup system: `--setup` opens the setup screen first
+pub fn open_setup_on_startup(mut next_app_state: ResMut<NextState<AppState>>) {
+    if std::env::args().any(|arg| arg == SETUP_FLAG) {
+        next_app_state.set(AppState::Setup);
+    }
+}
+
+// What a player can pick: no drawback, a random one, or any registered drawback
+fn drawback_choices(registry: &DrawbackRegistry) -> Vec<DrawbackSetting> {
+    let fixed = |id: DrawbackId| DrawbackSetting { name: None, index: Some(id.to_key_index()), params: DrawbackParams::default() };
+    let none = DrawbackSetting { name: None, index: None, params: DrawbackParams::default() };
+    [none, DrawbackSetting::random()]
+        .into_iter()
+        .chain(registry.sorted_ids().into_iter().map(fixed))
+        .collect()
+}
+
+// Position of `setting` among the choices (a drawback set up some other way counts as none)
+fn choice_index(choices: &[DrawbackSetting], setting: &DrawbackSetting, config: &GameConfig) -> usize {
+    choices
+        .iter()
+        .position(|choice| {
+            choice.is_random() == setting.is_random()
+                && config.resolve_drawback_id(choice) == config.resolve_drawback_id(setting)
+        })
+        .unwrap_or(0)
+}
+
+/// Spawns the setup screen
+pub fn spawn_setup_screen(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    registry: Res<DrawbackRegistry>,
+    localization: Res<Localization>,
+) {
+    build_setup_screen(&mut commands, &config, &registry, &localization);
+}
+
+// Helper function to build the screen: a panel per player with their role and
+// drawback, then the buttons to start or go back
+fn build_setup_screen(commands: &mut Commands, config: &GameConfig, registry: &DrawbackRegistry, localization: &Localization) {
+    let text_style = |font_size: f32| TextStyle {
+        font_size,
+        color: Color::WHITE,
+        ..default()
+    };
+    let button_bundle = |width: f32| ButtonBundle {
+        style: Style {
+            width: Val::Px(width),
+            height: Val::Px(40.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: BUTTON_COLOR.into(),
+        ..default()
+    };
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            background_color: SCREEN_COLOR.into(),
+            z_index: ZIndex::Global(90), // Below the pause menu
+            ..default()
+        },
+        SetupScreenRoot,
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(localization.text("setup-title"), text_style(44.0)));
+
+        let choices = drawback_choices(registry);
+        for color in [ChessColor::White, ChessColor::Black] {
+            let player = match color {
+                ChessColor::White => &config.white_player,
+                ChessColor::Black => &config.black_player,
+            };
+            let choice = &choices[choice_index(&choices, &player.drawback, config)];
+            let (name, description) = if choice.is_random() {
+                (localization.text("setup-random-drawback"), localization.text("setup-random-description"))
+            } else {
+                let id = config.resolve_drawback_id(choice);
+                (localization.drawback_name(registry, id), localization.drawback_description(registry, id, &choice.params))
+            };
+
+            parent.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(520.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(8.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                background_color: PANEL_COLOR.into(),
+                ..default()
+            }).with_children(|panel| {
+                panel.spawn(NodeBundle {
+                    style: Style { align_items: AlignItems::Center, column_gap: Val::Px(12.0), ..default() },
+                    ..default()
+                }).with_children(|row| {
+                    row.spawn(TextBundle::from_section(localization.color_name(color), text_style(28.0)));
+                    let role = localization.text(if player.is_ai { "setup-ai" } else { "setup-human" });
+                    row.spawn((button_bundle(120.0), SetupButton::Role(color))).with_children(|button| {
+                        button.spawn(TextBundle::from_section(role, text_style(22.0)));
+                    });
+                });
+
+                panel.spawn(NodeBundle {
+                    style: Style { align_items: AlignItems::Center, column_gap: Val::Px(12.0), ..default() },
+                    ..default()
+                }).with_children(|row| {
+                    row.spawn((button_bundle(40.0), SetupButton::PreviousDrawback(color))).with_children(|button| {
+                        button.spawn(TextBundle::from_section("<", text_style(22.0)));
+                    });
+                    row.spawn(
+                        TextBundle::from_section(name, text_style(22.0))
+                            .with_style(Style { width: Val::Px(280.0), ..default() })
+                            .with_text_alignment(TextAlignment::Center),
+                    );
+                    row.spawn((button_bundle(40.0), SetupButton::NextDrawback(color))).with_children(|button| {
+                        button.spawn(TextBundle::from_section(">", text_style(22.0)));
+                    });
+                });
+
+                panel.spawn(
+                    TextBundle::from_section(description, TextStyle { font_size: 16.0, color: DESCRIPTION_COLOR, ..default() })
+                        .with_style(Style { max_width: Val::Px(480.0), ..default() })
+                        .with_text_alignment(TextAlignment::Center),
+                );
+            });
+        }
+
+        for (button, key) in [(SetupButton::Start, "setup-start"), (SetupButton::Back, "setup-back")] {
+            parent.spawn((button_bundle(220.0), button)).with_children(|button_parent| {
+                button_parent.spawn(TextBundle::from_section(localization.text(key), text_style(24.0)));
+            });
+        }
+    });
+}
+
+/// Removes the setup screen
+pub fn despawn_setup_screen(mut commands: Commands, roots: Query<Entity, With<SetupScreenRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Rebuilds the screen when a choice or the language changed
+pub fn refresh_setup_screen(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    registry: Res<DrawbackRegistry>,
+    localization: Res<Localization>,
+    roots: Query<Entity, With<SetupScreenRoot>>,
+) {
+    if !(config.is_changed() || localization.is_changed()) || roots.is_empty() {
+        return;
+    }
+    for entity in roots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    build_setup_screen(&mut commands, &config, &registry, &localization);
+}
+
+/// Handles clicks on the setup screen. The choices go straight into the
+/// config; Start begins a new game with them.
+pub fn handle_setup_buttons(
+    mut interactions: Query<(&Interaction, &SetupButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut config: ResMut<GameConfig>,
+    registry: Res<DrawbackRegistry>,
+    mut ev_new_game: EventWriter<NewGameEvent>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, button, mut background) in interactions.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => match *button {
+                SetupButton::Role(color) => {
+                    let player = match color {
+                        ChessColor::White => &mut config.white_player,
+                        ChessColor::Black => &mut config.black_player,
+                    };
+                    player.is_ai = !player.is_ai;
+                }
+                SetupButton::PreviousDrawback(color) | SetupButton::NextDrawback(color) => {
+                    let choices = drawback_choices(&registry);
+                    let setting = match color {
+                        ChessColor::White => &config.white_player.drawback,
+                        ChessColor::Black => &config.black_player.drawback,
+                    };
+                    let index = choice_index(&choices, setting, &config);
+                    let step = if matches!(button, SetupButton::NextDrawback(_)) { 1 } else { choices.len() - 1 };
+                    let choice = choices[(index + step) % choices.len()].clone();
+                    match color {
+                        ChessColor::White => config.white_player.drawback = choice,
+                        ChessColor::Black => config.black_player.drawback = choice,
+                    }
+                }
+                SetupButton::Start => {
+                    println!("Starting a new game from the setup screen");
+                    ev_new_game.send(NewGameEvent::default());
+                    next_app_state.set(AppState::InGame);
+                }
+                SetupButton::Back => {
+                    next_app_state.set(AppState::InGame);
+                }
+            },
+            Interaction::Hovered => {
+                *background = BUTTON_HOVER_COLOR.into();
+            }
+            Interaction::None => {
+                *background = BUTTON_COLOR.into();
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::window::PrimaryWindow;
+use shakmaty::{fen::Fen, Color as ChessColor, EnPassantMode, Position};
+use crate::config::GameConfig;
+use crate::drawbacks::DrawbackRegistry;
+use crate::game_logic::clock::{GameClock, format_clock};
+use crate::game_logic::events::SharePositionEvent;
+use crate::game_logic::state::{ActiveBoard, GameState};
+use crate::i18n::Localization;
+
+// Frames to wait for the pause menu to close before the picture is taken
+const SETTLE_FRAMES: u8 = 3;
+
+/// Marker for the caption shown while a shared image is taken
+#[derive(Component)]
+pub struct ShareCaption;
+
+enum ShareStage {
+    Settling(u8), // Frames left before the screenshot
+    Captured,     // Screenshot requested, the caption goes next frame
+}
+
+/// Resource with the image being shared, if any
+#[derive(Resource, Default)]
+pub struct PendingShare(Option<(ShareStage, String)>);
+
+/// Puts the caption (both drawbacks, the clocks and optionally the FEN) under
+/// the board and schedules the screenshot
+#[allow(clippy::too_many_arguments)]
+pub fn start_position_share(
+    mut commands: Commands,
+    mut ev_share: EventReader<SharePositionEvent>,
+    mut pending: ResMut<PendingShare>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    clock: Res<GameClock>,
+    config: Res<GameConfig>,
+    registry: Res<DrawbackRegistry>,
+    localization: Res<Localization>,
+) {
+    if ev_share.read().count() == 0 || pending.0.is_some() {
+        return;
+    }
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+
+    let mut lines: Vec<String> = [ChessColor::White, ChessColor::Black]
+        .into_iter()
+        .map(|color| {
+            let (drawback, _) = game_state.drawback_of(color);
+            let mut line = localization.text_with("share-drawback", &[
+                ("color", localization.color_name(color)),
+                ("drawback", localization.drawback_name(&registry, drawback)),
+            ]);
+            if clock.enabled {
+                line.push_str(&format!("  {}", format_clock(clock.remaining_ms(color))));
+            }
+            line
+        })
+        .collect();
+    if config.display.share_with_fen {
+        lines.push(localization.text_with("share-fen", &[
+            ("move", game_state.board.fullmoves().to_string()),
+            ("fen", Fen::from_position(game_state.board.clone(), EnPassantMode::Legal).to_string()),
+        ]));
+    }
+
+    commands.spawn((
+        TextBundle::from_section(lines.join("\n"), TextStyle { font_size: 20.0, color: Color::WHITE, ..default() })
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(0.0),
+                left: Val::Px(0.0),
+                right: Val::Px(0.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            })
+            .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.8)),
+        ShareCaption,
+    ));
+
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    pending.0 = Some((ShareStage::Settling(SETTLE_FRAMES), format!("drawback_chess_position_{}.png", seconds)));
+}
+
+/// Takes the screenshot once the menu is gone, then removes the caption again
+pub fn capture_shared_position(
+    mut commands: Commands,
+    mut pending: ResMut<PendingShare>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+    captions: Query<Entity, With<ShareCaption>>,
+) {
+    let Some((stage, path)) = pending.0.as_mut() else {
+        return;
+    };
+    match stage {
+        ShareStage::Settling(0) => {
+            match windows.get_single().map_err(|e| e.to_string()).and_then(|window| {
+                screenshots.save_screenshot_to_disk(window, path.as_str()).map_err(|e| e.to_string())
+            }) {
+                Ok(()) => println!("Position saved to {}", path),
+                Err(e) => eprintln!("Failed to save the position image: {}", e),
+            }
+            *stage = ShareStage::Captured;
+        }
+        ShareStage::Settling(frames) => *frames -= 1,
+        ShareStage::Captured => {
+            for entity in captions.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+            pending.0 = None;
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+use crate::game_logic::events::MovePlayedEvent;
+use crate::game_logic::state::{GameState, ActiveBoard};
+use crate::i18n::Localization;
+
+const NOTICE_COLOR: Color = Color::rgb(0.5, 0.8, 1.0);
+const NOTICE_SECS: f32 = 3.0;
+
+/// Notice that a drawback played another move than the one picked (e.g.
+/// Slippery Fingers overshooting), removed once its timer runs out
+#[derive(Component)]
+pub struct SubstitutionNotice {
+    pub timer: Timer,
+}
+
+/// Shows where the picked move was headed and where the piece ended up instead
+pub fn show_substitution_notice(
+    mut commands: Commands,
+    mut ev_played: EventReader<MovePlayedEvent>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    localization: Res<Localization>,
+    notices: Query<Entity, With<SubstitutionNotice>>,
+) {
+    let Ok(game_state) = boards.get_single() else {
+        return;
+    };
+    for ev in ev_played.read() {
+        let Some(picked) = &ev.picked else {
+            continue;
+        };
+        // The newest notice replaces the one still showing
+        for entity in notices.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        let text = localization.text_with("move-substituted", &[
+            ("color", localization.color_name(!game_state.current_player_turn)),
+            ("picked", picked.to().to_string()),
+            ("played", ev.chess_move.to().to_string()),
+        ]);
+        commands.spawn((
+            TextBundle::from_section(text, TextStyle { font_size: 20.0, color: NOTICE_COLOR, ..default() })
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(84.0),
+                    left: Val::Percent(30.0),
+                    ..default()
+                })
+                .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.6)),
+            SubstitutionNotice { timer: Timer::from_seconds(NOTICE_SECS, TimerMode::Once) },
+        ));
+    }
+}
+
+/// Removes the notice once its time is up
+pub fn expire_substitution_notice(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut notices: Query<(Entity, &mut SubstitutionNotice)>,
+) {
+    for (entity, mut notice) in notices.iter_mut() {
+        if notice.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
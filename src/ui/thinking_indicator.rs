@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+use crate::ai::components::AiThinking;
+use crate::board::geometry::world_pos;
+use crate::config::GameConfig;
+use crate::game_logic::legal_moves::LegalMovesCache;
+use crate::game_logic::state::{GameState, ActiveBoard};
+use crate::i18n::Localization;
+use super::observer_arrows::observing_engines;
+
+// Spinner frames cycled while the AI is thinking
+const SPINNER_FRAMES: [&str; 4] = ["|", "/", "-", "\\"];
+const SPINNER_FRAME_SECS: f32 = 0.12;
+// Filtering the legal moves is only worth mentioning once it takes this long
+const COMPUTING_MOVES_SHOWN_AFTER_SECS: f32 = 0.15;
+
+// Color for the best-move-so-far arrow (alpha is animated)
+const BEST_MOVE_ARROW_COLOR: Color = Color::rgb(1.0, 0.6, 0.0);
+
+/// Marker for the "AI is thinking" text node
+#[derive(Component)]
+pub struct ThinkingIndicatorText;
+
+/// Spawns the (initially hidden) thinking indicator in the top-left corner
+pub fn setup_thinking_indicator(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            left: Val::Px(8.0),
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.5)),
+        ThinkingIndicatorText,
+    ));
+}
+
+/// Updates the text with a spinner, elapsed time and current search depth,
+/// or says the legal moves are still being computed when that takes a while
+pub fn update_thinking_indicator(
+    config: Res<GameConfig>,
+    localization: Res<Localization>,
+    ai_tasks: Query<&AiThinking>,
+    legal_moves: Res<LegalMovesCache>,
+    mut indicator: Query<(&mut Text, &mut Visibility), With<ThinkingIndicatorText>>,
+) {
+    let Ok((mut text, mut visibility)) = indicator.get_single_mut() else {
+        return;
+    };
+
+    let thinking = if config.display.show_ai_thinking { ai_tasks.iter().next() } else { None };
+
+    match thinking {
+        Some(ai_task) => {
+            let elapsed = ai_task.started_at.elapsed().as_secs_f32();
+            let frame = (elapsed / SPINNER_FRAME_SECS) as usize % SPINNER_FRAMES.len();
+            let depth = ai_task.progress.depth();
+            let nodes = ai_task.progress.nodes();
+
+            text.sections[0].value = format!(" {} ", localization.text_with("ai-thinking", &[
+                ("spinner", SPINNER_FRAMES[frame].to_string()),
+                ("seconds", format!("{:.1}", elapsed)),
+                ("depth", depth.to_string()),
+                ("nodes", nodes.to_string()),
+            ]));
+            *visibility = Visibility::Visible;
+        }
+        None => match legal_moves.computing_for().map(|elapsed| elapsed.as_secs_f32()) {
+            Some(elapsed) if elapsed >= COMPUTING_MOVES_SHOWN_AFTER_SECS => {
+                let frame = (elapsed / SPINNER_FRAME_SECS) as usize % SPINNER_FRAMES.len();
+                text.sections[0].value = format!(" {} ", localization.text_with("computing-legal-moves", &[
+                    ("spinner", SPINNER_FRAMES[frame].to_string()),
+                ]));
+                *visibility = Visibility::Visible;
+            }
+            _ => {
+                *visibility = Visibility::Hidden;
+            }
+        },
+    }
+}
+
+/// Draws a pulsing arrow for the AI's best move so far
+pub fn draw_best_move_arrow(
+    config: Res<GameConfig>,
+    time: Res<Time>,
+    ai_tasks: Query<&AiThinking>,
+    boards: Query<&GameState, With<ActiveBoard>>,
+    mut gizmos: Gizmos,
+) {
+    // AI vs AI games show the engines' whole plans instead
+    if !config.display.show_ai_thinking || !config.display.show_ai_best_move_arrow || observing_engines(&config) {
+        return;
+    }
+
+    let Some(best_move) = ai_tasks.iter().next().and_then(|task| task.progress.best_move()) else {
+        return;
+    };
+    let (Some(from), Ok(game_state)) = (best_move.from(), boards.get_single()) else {
+        return;
+    };
+    let start = world_pos(from, game_state.board_flipped);
+    let end = world_pos(best_move.to(), game_state.board_flipped);
+
+    // Pulse the alpha so the arrow reads as "in progress"
+    let alpha = 0.55 + 0.35 * (time.elapsed_seconds() * 4.0).sin();
+    draw_arrow(&mut gizmos, start, end, BEST_MOVE_ARROW_COLOR.with_a(alpha));
+}
+
+/// Draws an arrow from one world position to another
+pub fn draw_arrow(gizmos: &mut Gizmos, start: Vec2, end: Vec2, color: Color) {
+    gizmos.line_2d(start, end, color);
+
+    // Arrow head
+    let direction = (end - start).normalize_or_zero();
+    let normal = Vec2::new(-direction.y, direction.x);
+    let head_length = 18.0;
+    let head_width = 10.0;
+    let base = end - direction * head_length;
+    gizmos.line_2d(end, base + normal * head_width, color);
+    gizmos.line_2d(end, base - normal * head_width, color);
+}
+
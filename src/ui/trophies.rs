@@ -0,0 +1,182 @@
+use bevy::prelude::*;
+use crate::drawbacks::DrawbackRegistry;
+use crate::game_logic::state::AppState;
+use crate::i18n::Localization;
+use crate::stats::achievements::{AchievementUnlockedEvent, all_achievements};
+use crate::stats::store::PlayerStats;
+
+// Colors for the trophies screen
+const SCREEN_COLOR: Color = Color::rgba(0.05, 0.05, 0.08, 0.92);
+const UNLOCKED_COLOR: Color = Color::rgb(1.0, 0.85, 0.4);
+const LOCKED_COLOR: Color = Color::rgb(0.5, 0.5, 0.5);
+const BUTTON_COLOR: Color = Color::rgb(0.25, 0.25, 0.25);
+
+// How long an "achievement unlocked" toast stays up
+const TOAST_SECS: f32 = 4.0;
+
+/// Marker for the root node of the trophies screen
+#[derive(Component)]
+pub struct TrophiesScreenRoot;
+
+/// Marker for the "Back" button of the trophies screen
+#[derive(Component)]
+pub struct TrophiesBackButton;
+
+/// Toast shown in the bottom-right corner when an achievement unlocks
+#[derive(Component)]
+pub struct AchievementToast {
+    pub timer: Timer,
+}
+
+/// Spawns the trophies screen listing every achievement
+pub fn spawn_trophies_screen(
+    mut commands: Commands,
+    stats: Res<PlayerStats>,
+    registry: Res<DrawbackRegistry>,
+    localization: Res<Localization>,
+) {
+    let achievements = all_achievements(&registry);
+    let unlocked_count = achievements.iter().filter(|a| stats.has_achievement(&a.key())).count();
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            background_color: SCREEN_COLOR.into(),
+            z_index: ZIndex::Global(90), // Below the pause menu
+            ..default()
+        },
+        TrophiesScreenRoot,
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            localization.text_with("trophies-title", &[
+                ("unlocked", unlocked_count.to_string()),
+                ("total", achievements.len().to_string()),
+            ]),
+            TextStyle {
+                font_size: 40.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        ));
+
+        for achievement in &achievements {
+            let unlocked = stats
+                .achievements
+                .iter()
+                .find(|unlocked| unlocked.key == achievement.key());
+
+            let name = achievement.name(&localization, &registry);
+            let description = achievement.description(&localization, &registry);
+            let label = match unlocked {
+                Some(unlocked) => format!("[x] {} - {} ({})", name, description, unlocked.date),
+                None => format!("[ ] {} - {}", name, description),
+            };
+
+            parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font_size: 17.0,
+                    color: if unlocked.is_some() { UNLOCKED_COLOR } else { LOCKED_COLOR },
+                    ..default()
+                },
+            ));
+        }
+
+        parent.spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(220.0),
+                    height: Val::Px(44.0),
+                    margin: UiRect::top(Val::Px(12.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BUTTON_COLOR.into(),
+                ..default()
+            },
+            TrophiesBackButton,
+        )).with_children(|button_parent| {
+            button_parent.spawn(TextBundle::from_section(
+                localization.text("trophies-back"),
+                TextStyle {
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+    });
+}
+
+/// Removes the trophies screen
+pub fn despawn_trophies_screen(mut commands: Commands, roots: Query<Entity, With<TrophiesScreenRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Handles the trophies screen "Back" button
+pub fn handle_trophies_buttons(
+    interactions: Query<&Interaction, (Changed<Interaction>, With<TrophiesBackButton>)>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    if interactions.iter().any(|interaction| *interaction == Interaction::Pressed) {
+        next_app_state.set(AppState::InGame);
+    }
+}
+
+/// Spawns a toast for every newly unlocked achievement
+pub fn show_achievement_toasts(
+    mut commands: Commands,
+    mut ev_unlocked: EventReader<AchievementUnlockedEvent>,
+    registry: Res<DrawbackRegistry>,
+    localization: Res<Localization>,
+    toasts: Query<(), With<AchievementToast>>,
+) {
+    // Stack new toasts above the ones already showing
+    for (stacked, ev) in (toasts.iter().count()..).zip(ev_unlocked.read()) {
+        commands.spawn((
+            TextBundle::from_section(
+                format!(" {} ", localization.text_with("achievement-unlocked", &[("name", ev.0.name(&localization, &registry))])),
+                TextStyle {
+                    font_size: 20.0,
+                    color: UNLOCKED_COLOR,
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(60.0 + 32.0 * stacked as f32),
+                right: Val::Px(8.0),
+                ..default()
+            })
+            .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.75)),
+            AchievementToast {
+                timer: Timer::from_seconds(TOAST_SECS, TimerMode::Once),
+            },
+        ));
+    }
+}
+
+/// Removes toasts once their time is up
+pub fn expire_achievement_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toasts: Query<(Entity, &mut AchievementToast)>,
+) {
+    for (entity, mut toast) in toasts.iter_mut() {
+        if toast.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
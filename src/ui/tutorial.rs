@@ -0,0 +1,182 @@
+use bevy::prelude::*;
+use crate::board::components::BoardSquare;
+use crate::constants::TILE_SIZE;
+use crate::drawbacks::{DrawbackRegistry, DrawbackParams};
+use crate::game_logic::state::MoveRestriction;
+use crate::modes::tutorial::{TutorialSession, TutorialStep};
+use crate::i18n::Localization;
+
+// Colors for the tutorial popup and the forced move highlight
+const POPUP_COLOR: Color = Color::rgba(0.05, 0.05, 0.12, 0.9);
+const PROMPT_COLOR: Color = Color::rgb(0.7, 0.7, 0.7);
+const FORCED_MOVE_COLOR: Color = Color::rgb(0.2, 0.8, 1.0);
+
+/// Marker for the tutorial popup's root node
+#[derive(Component)]
+pub struct TutorialPopupRoot;
+
+/// Marker for the tutorial popup's text
+#[derive(Component)]
+pub struct TutorialPopupText;
+
+/// Spawns the (initially empty) tutorial popup along the bottom of the window
+pub fn spawn_tutorial_popup(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(8.0),
+                left: Val::Percent(15.0),
+                width: Val::Percent(70.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            background_color: POPUP_COLOR.into(),
+            z_index: ZIndex::Global(80), // Below the pause menu and other screens
+            ..default()
+        },
+        TutorialPopupRoot,
+    )).with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_sections([
+                TextSection::new("", TextStyle { font_size: 24.0, color: Color::WHITE, ..default() }),
+                TextSection::new("", TextStyle { font_size: 20.0, color: Color::WHITE, ..default() }),
+                TextSection::new("", TextStyle { font_size: 16.0, color: PROMPT_COLOR, ..default() }),
+            ]),
+            TutorialPopupText,
+        ));
+    });
+}
+
+/// Removes the tutorial popup
+pub fn despawn_tutorial_popup(mut commands: Commands, roots: Query<Entity, With<TutorialPopupRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Shows the lesson title, the current explanation and what the player should do
+pub fn update_tutorial_popup(
+    session: Res<TutorialSession>,
+    registry: Res<DrawbackRegistry>,
+    localization: Res<Localization>,
+    mut texts: Query<&mut Text, With<TutorialPopupText>>,
+) {
+    if !session.is_changed() && !localization.is_changed() {
+        return;
+    }
+    let Some(lesson) = session.current_lesson() else {
+        return;
+    };
+
+    let drawback = |id| localization.drawback_name(&registry, id);
+    let (body, prompt) = match session.current_step() {
+        Some(TutorialStep::Say(text)) => (script_text(&localization, text), localization.text("tutorial-continue")),
+        Some(TutorialStep::Move(_)) => (String::new(), localization.text("tutorial-play-move")),
+        Some(TutorialStep::Reply(_)) => (String::new(), localization.text("tutorial-opponent-moving")),
+        Some(TutorialStep::Guess(answer)) => match session.guess_answer {
+            Some(guess) if guess.correct => (
+                localization.text_with("tutorial-guess-correct", &[
+                    ("drawback", drawback(*answer)),
+                    ("description", localization.drawback_description(&registry, *answer, &DrawbackParams::default())),
+                ]),
+                localization.text("tutorial-continue"),
+            ),
+            Some(guess) => (
+                localization.text_with("tutorial-guess-wrong", &[("drawback", drawback(guess.guessed))]),
+                guess_options(&localization, &registry),
+            ),
+            None => (localization.text("tutorial-guess-question"), guess_options(&localization, &registry)),
+        },
+        None => (String::new(), String::new()),
+    };
+
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = format!("{}\n", localization.text_with("tutorial-lesson", &[
+            ("number", (session.lesson + 1).to_string()),
+            ("total", session.lessons.len().to_string()),
+            ("title", script_text(&localization, &lesson.title)),
+        ]));
+        text.sections[1].value = if body.is_empty() { body.clone() } else { format!("{}\n", body) };
+        text.sections[2].value = format!("{}    {}", prompt, localization.text("tutorial-menu-hint"));
+    }
+}
+
+// Script texts are localization keys, or plain text for lessons without translations
+fn script_text(localization: &Localization, text: &str) -> String {
+    if localization.has(text) {
+        localization.text(text)
+    } else {
+        text.to_string()
+    }
+}
+
+// Helper function listing the drawbacks the player can pick from
+fn guess_options(localization: &Localization, registry: &DrawbackRegistry) -> String {
+    registry
+        .sorted_ids()
+        .into_iter()
+        .enumerate()
+        .map(|(index, id)| format!("{}: {}", index + 1, localization.drawback_name(registry, id)))
+        .collect::<Vec<_>>()
+        .join("   ")
+}
+
+/// Handles the tutorial keys: Enter continues, number keys answer a guess
+pub fn handle_tutorial_keys(
+    keys: Res<Input<KeyCode>>,
+    mut session: ResMut<TutorialSession>,
+    registry: Res<DrawbackRegistry>,
+) {
+    let continue_pressed = keys.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter, KeyCode::Space]);
+
+    match session.current_step() {
+        Some(TutorialStep::Say(_)) if continue_pressed => session.advance(),
+        Some(TutorialStep::Guess(_)) => {
+            let answered_correctly = session.guess_answer.is_some_and(|guess| guess.correct);
+            if answered_correctly {
+                if continue_pressed {
+                    session.advance();
+                }
+                return;
+            }
+
+            const NUMBER_KEYS: [KeyCode; 9] = [
+                KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5,
+                KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+            ];
+            let options = registry.sorted_ids();
+            let picked = NUMBER_KEYS
+                .iter()
+                .zip(options)
+                .find(|(key, _)| keys.just_pressed(**key))
+                .map(|(_, id)| id);
+            if let Some(id) = picked {
+                session.answer_guess(id);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Outlines the from and to squares of the move the tutorial asks for
+pub fn draw_forced_move_highlight(
+    mut gizmos: Gizmos,
+    restriction: Res<MoveRestriction>,
+    board_squares: Query<(&Transform, &BoardSquare)>,
+) {
+    let MoveRestriction::Only(forced) = restriction.as_ref() else {
+        return;
+    };
+
+    for (transform, board_square) in board_squares.iter() {
+        if Some(board_square.square) == forced.from() || board_square.square == forced.to() {
+            gizmos.rect_2d(
+                transform.translation.truncate(),
+                0.0,
+                Vec2::splat(TILE_SIZE - 4.0),
+                FORCED_MOVE_COLOR,
+            );
+        }
+    }
+}
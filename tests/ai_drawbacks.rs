@@ -0,0 +1,271 @@
+// The engine playing under drawbacks: it has to stay within what its drawback
+// allows and still play the game reasonably
+
+use std::time::Duration;
+use shakmaty::{Bitboard, ByColor, Color as ChessColor, Move, Position, Rank, Role, Square};
+use drawback_chess::ai::components::SearchProgress;
+use drawback_chess::ai::evaluation::{is_attacked, PieceSquareTables};
+use drawback_chess::ai::alphabeta::find_best_move_alphabeta;
+use drawback_chess::ai::mcts::{MctsTree, find_best_move_mcts};
+use drawback_chess::ai::opponent_model::{OpponentModel, opponent_belief};
+use drawback_chess::ai::plugin::AiGameStateContext;
+use drawback_chess::ai::pleco_ai::find_best_move_pleco;
+use drawback_chess::ai::sprt::play_game;
+use drawback_chess::ai::transposition::TranspositionTable;
+use drawback_chess::ai::zobrist::{calculate_board_hash, initialize_zobrist_keys, search_turn_hash};
+use drawback_chess::config::{AiEngine, GameConfig, TtReplacement};
+use drawback_chess::drawbacks::{DrawbackId, DrawbackRegistry};
+use drawback_chess::drawbacks::definition::TurnContext;
+use drawback_chess::drawbacks::glass_cannon::defends_queen;
+use drawback_chess::game_logic::perspective::PlayerPerspective;
+use drawback_chess::game_logic::state::GameState;
+
+const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+// The move the engine picks for the side to move, searching only what its drawback allows
+fn engine_move(game_state: &GameState, registry: &DrawbackRegistry) -> Move {
+    let config = GameConfig::default();
+    let mut ctx = AiGameStateContext::from_game_state(game_state, &config);
+    ctx.rng_seed = Some(7);
+    ctx.opponent_belief = opponent_belief(game_state.current_player_turn, game_state, &config, &OpponentModel::default(), registry);
+    ctx.allowed_moves = game_state.allowed_moves(registry);
+    ctx.player_rule = game_state.current_drawback_rule(registry);
+    find_best_move_pleco(ctx, Duration::from_millis(1000), 3, &SearchProgress::default()).expect("A move")
+}
+
+fn play(game_state: &mut GameState, chess_move: &Move) {
+    game_state.board.play_unchecked(chess_move);
+    game_state.current_player_turn = game_state.board.turn();
+}
+
+// Minor pieces of `color` off their home rank
+fn developed_minor_pieces(game_state: &GameState, color: ChessColor) -> usize {
+    let board = game_state.board.board();
+    let home = Bitboard::from_rank(color.fold_wb(Rank::First, Rank::Eighth));
+    ((board.knights() | board.bishops()) & board.by_color(color) & !home).count()
+}
+
+#[test]
+fn pacifist_opening_develops_without_capturing() {
+    let registry = DrawbackRegistry::default();
+    let mut game_state = GameState::from_fen(START_FEN).expect("Valid FEN");
+    game_state.white_drawback = DrawbackId::PacifistOpening;
+    game_state.black_drawback = DrawbackId::PacifistOpening;
+
+    // Five moves each, all before captures are allowed
+    for _ in 0..10 {
+        let chess_move = engine_move(&game_state, &registry);
+        assert!(!chess_move.is_capture(), "{} captures before move 6", chess_move);
+        play(&mut game_state, &chess_move);
+    }
+    for color in [ChessColor::White, ChessColor::Black] {
+        assert!(developed_minor_pieces(&game_state, color) >= 2, "{:?} barely developed", color);
+        let king_home = color.fold_wb(Square::E1, Square::E8);
+        assert_eq!(game_state.board.board().king_of(color), Some(king_home), "{:?} king went for a walk", color);
+    }
+}
+
+#[test]
+fn pacifist_opening_takes_the_queen_once_allowed() {
+    let registry = DrawbackRegistry::default();
+    // The black queen hangs to the knight on f3
+    let hanging_queen = |fullmoves: u32| {
+        let fen = format!("rnb1kbnr/pppp1ppp/8/4p3/3qP3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 0 {}", fullmoves);
+        let mut game_state = GameState::from_fen(&fen).expect("Valid FEN");
+        game_state.white_drawback = DrawbackId::PacifistOpening;
+        game_state
+    };
+
+    let early = engine_move(&hanging_queen(3), &registry);
+    assert!(!early.is_capture(), "{} captures on move 3", early);
+
+    let late = engine_move(&hanging_queen(6), &registry);
+    assert_eq!((late.from(), late.to(), late.capture()), (Some(Square::F3), Square::D4, Some(Role::Queen)));
+}
+
+#[test]
+fn mcts_takes_the_queen_and_keeps_its_tree_and_table_for_the_next_move() {
+    let registry = DrawbackRegistry::default();
+    let fen = "rnb1kbnr/pppp1ppp/8/4p3/3qP3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 0 6";
+    let mut game_state = GameState::from_fen(fen).expect("Valid FEN");
+    game_state.white_drawback = DrawbackId::PacifistOpening;
+
+    let mut ctx = AiGameStateContext::from_game_state(&game_state, &GameConfig::default());
+    ctx.rng_seed = Some(7);
+    ctx.iteration_limit = 3000;
+    ctx.allowed_moves = game_state.allowed_moves(&registry);
+    ctx.player_rule = game_state.current_drawback_rule(&registry);
+    let mut tree = MctsTree::default();
+    let mut tt = TranspositionTable::new(4, TtReplacement::DepthPreferred);
+    let pst = PieceSquareTables::default();
+    let keys = initialize_zobrist_keys();
+    let best = find_best_move_mcts(ctx, &mut tree, &mut tt, Duration::from_secs(10), &pst, &keys, &SearchProgress::default())
+        .expect("A move");
+    assert_eq!((best.from(), best.to(), best.capture()), (Some(Square::F3), Square::D4, Some(Role::Queen)));
+    // The table remembers the move for when the position comes up again
+    let entry = tt.probe(calculate_board_hash(&game_state.board, &keys)).expect("The root is stored");
+    assert_eq!(entry.best_move.as_ref(), Some(&best));
+
+    // Black's search starts from what was already spent on the position after the capture
+    play(&mut game_state, &best);
+    let reply_ctx = AiGameStateContext::from_game_state(&game_state, &GameConfig::default());
+    tree.reroot(&reply_ctx, &keys, &mut reply_ctx.rng());
+    assert!(tree.root_visits() > 100, "Only {} visits kept", tree.root_visits());
+}
+
+#[test]
+fn alphabeta_takes_the_queen_once_allowed_and_finds_mate() {
+    let registry = DrawbackRegistry::default();
+    let pst = PieceSquareTables::default();
+    let keys = initialize_zobrist_keys();
+    let search = |game_state: &GameState| {
+        let mut ctx = AiGameStateContext::from_game_state(game_state, &GameConfig::default());
+        ctx.depth = 4;
+        ctx.allowed_moves = game_state.allowed_moves(&registry);
+        ctx.player_rule = game_state.current_drawback_rule(&registry);
+        let mut tt = TranspositionTable::new(4, TtReplacement::DepthPreferred);
+        find_best_move_alphabeta(ctx, &mut tt, Duration::from_secs(10), &pst, &keys, &SearchProgress::default()).expect("A move")
+    };
+    let hanging_queen = |fullmoves: u32| {
+        let fen = format!("rnb1kbnr/pppp1ppp/8/4p3/3qP3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 0 {}", fullmoves);
+        let mut game_state = GameState::from_fen(&fen).expect("Valid FEN");
+        game_state.white_drawback = DrawbackId::PacifistOpening;
+        game_state
+    };
+
+    assert!(!search(&hanging_queen(3)).is_capture());
+    let late = search(&hanging_queen(6));
+    assert_eq!((late.from(), late.to(), late.capture()), (Some(Square::F3), Square::D4, Some(Role::Queen)));
+
+    // Back rank mate
+    let mate = GameState::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").expect("Valid FEN");
+    let best = search(&mate);
+    assert_eq!((best.from(), best.to()), (Some(Square::A1), Square::A8));
+}
+
+#[test]
+fn sprt_match_games_are_played_to_the_end() {
+    let registry = DrawbackRegistry::default();
+    let pst = PieceSquareTables::default();
+    let keys = initialize_zobrist_keys();
+    let mut settings = GameConfig::default().ai_settings;
+    settings.engine = AiEngine::AlphaBeta;
+    settings.depth_limit = 3;
+    settings.time_limit_ms = 200;
+    settings.tt_size_mb = 1;
+
+    let mut mate = GameState::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").expect("Valid FEN");
+    mate.zobrist_hash = mate.position_key(&keys);
+    assert_eq!(play_game(&settings, &settings, &mate, &registry, &pst, &keys, 1), Some(ChessColor::White));
+
+    let bare_kings = GameState::from_fen("8/8/4k3/8/8/3K4/8/8 w - - 0 1").expect("Valid FEN");
+    assert_eq!(play_game(&settings, &settings, &bare_kings, &registry, &pst, &keys, 1), None);
+}
+
+#[test]
+fn the_search_holds_both_sides_to_their_drawbacks_below_the_root() {
+    let registry = DrawbackRegistry::default();
+    let mut game_state = GameState::from_fen(START_FEN).expect("Valid FEN");
+    game_state.white_drawback = DrawbackId::PawnPushOneOnly;
+    game_state.black_drawback = DrawbackId::PacifistOpening;
+
+    let config = GameConfig::default();
+    let mut ctx = AiGameStateContext::from_game_state(&game_state, &config);
+    ctx.player_rule = game_state.current_drawback_rule(&registry);
+    ctx.opponent_belief = opponent_belief(game_state.current_player_turn, &game_state, &config, &OpponentModel::default(), &registry);
+    let mut rng = ctx.rng();
+
+    // Two plies in: White may still not push a pawn two squares
+    let mut position = game_state.board.clone();
+    for uci in ["e2e3", "d7d5"] {
+        let m = uci.parse::<shakmaty::uci::Uci>().expect("Valid UCI").to_move(&position).expect("Legal move");
+        position.play_unchecked(&m);
+    }
+    let white_moves = ctx.search_moves(&position, &TurnContext::default(), &mut rng);
+    assert!(!white_moves.is_empty());
+    assert!(white_moves.iter().all(|m| m.role() != Role::Pawn || PlayerPerspective::new(ChessColor::White).ranks_forward(m.from().expect("A normal move"), m.to()) == 1));
+
+    // ...and Black may not take the bishop that came to a6 on move 2
+    let m = "f1a6".parse::<shakmaty::uci::Uci>().expect("Valid UCI").to_move(&position).expect("Legal move");
+    position.play_unchecked(&m);
+    assert!(position.legal_moves().iter().any(|m| m.is_capture()));
+    let black_moves = ctx.search_moves(&position, &TurnContext { last_move: Some(&m), ..TurnContext::default() }, &mut rng);
+    assert!(!black_moves.is_empty() && black_moves.iter().all(|m| !m.is_capture()));
+}
+
+#[test]
+fn vampire_captures_now_rather_than_starve_on_its_next_move() {
+    let registry = DrawbackRegistry::default();
+    let pst = PieceSquareTables::default();
+    let keys = initialize_zobrist_keys();
+    // Three quiet moves played: any quiet move now leaves the knight nothing
+    // to take on the next one, so it has to give itself up on a3
+    let mut game_state = GameState::from_fen("7k/8/8/8/1p6/p7/8/1N5K w - - 0 1").expect("Valid FEN");
+    game_state.white_drawback = DrawbackId::Vampire;
+    game_state.white_moves_since_capture = 3;
+    assert!(game_state.allowed_moves(&registry).iter().any(|m| !m.is_capture()));
+
+    let mut ctx = AiGameStateContext::from_game_state(&game_state, &GameConfig::default());
+    ctx.depth = 3;
+    ctx.allowed_moves = game_state.allowed_moves(&registry);
+    ctx.player_rule = game_state.current_drawback_rule(&registry);
+    let mut tt = TranspositionTable::new(4, TtReplacement::DepthPreferred);
+    let best = find_best_move_alphabeta(ctx, &mut tt, Duration::from_secs(10), &pst, &keys, &SearchProgress::default()).expect("A move");
+    assert_eq!((best.from(), best.to()), (Some(Square::B1), Square::A3));
+}
+
+#[test]
+fn search_keys_tell_apart_what_the_drawbacks_see_beyond_the_board() {
+    let keys = initialize_zobrist_keys();
+    let key = |rng_outcome, last_move, moves_since_capture| search_turn_hash(ChessColor::White, rng_outcome, last_move, moves_since_capture, &keys);
+    let pawn = Move::Normal { role: Role::Pawn, from: Square::E7, capture: None, to: Square::E5, promotion: None };
+    let knight = Move::Normal { role: Role::Knight, from: Square::G8, capture: None, to: Square::F6, promotion: None };
+
+    // Without rules that depend on them, positions keep their board hash
+    assert_eq!(key(None, None, None), 0);
+    assert_ne!(key(Some(0), None, None), key(Some(1), None, None));
+    assert_ne!(key(None, Some(&pawn), None), key(None, Some(&knight), None));
+    // Both sides' counts matter, and they aren't interchangeable
+    let counts = |white, black| Some(ByColor { white, black });
+    assert_ne!(key(None, None, counts(3, 0)), key(None, None, counts(4, 0)));
+    assert_ne!(key(None, None, counts(3, 0)), key(None, None, counts(3, 1)));
+    assert_ne!(key(None, None, counts(1, 2)), key(None, None, counts(2, 1)));
+}
+
+#[test]
+fn glass_cannon_queen_escapes_to_a_safe_square() {
+    let registry = DrawbackRegistry::default();
+    // The pawn on e5 attacks the queen, which may not retreat to a square the rook or king protect
+    let mut game_state = GameState::from_fen("6k1/5ppp/8/4p3/3Q4/8/5PPP/R5K1 w - - 0 1").expect("Valid FEN");
+    game_state.white_drawback = DrawbackId::GlassCannon;
+
+    let chess_move = engine_move(&game_state, &registry);
+    assert!(!defends_queen(&game_state.board, &chess_move), "{} defends the queen", chess_move);
+    play(&mut game_state, &chess_move);
+    let board = game_state.board.board();
+    let queen = board.queens() & board.white();
+    assert!(queen.any(), "{} gave up the queen", chess_move);
+    assert!(queen.into_iter().all(|square| !is_attacked(&game_state.board, square, ChessColor::Black)), "{} leaves the queen en prise", chess_move);
+}
+
+#[test]
+fn alphabeta_scores_a_return_to_a_played_position_as_a_draw() {
+    let pst = PieceSquareTables::default();
+    let keys = initialize_zobrist_keys();
+    // Taking the rook is the obvious move, but the game has been in the position it leads to
+    let game_state = GameState::from_fen("7k/8/8/8/3r4/8/8/3Q3K w - - 0 1").expect("Valid FEN");
+    let capture = "d1d4".parse::<shakmaty::uci::Uci>().expect("Valid UCI").to_move(&game_state.board).expect("Legal move");
+    let mut after_capture = game_state.board.clone();
+    after_capture.play_unchecked(&capture);
+
+    let search = |played_positions: Vec<u64>| {
+        let mut ctx = AiGameStateContext::from_game_state(&game_state, &GameConfig::default());
+        ctx.depth = 2;
+        ctx.played_positions = played_positions;
+        let mut tt = TranspositionTable::new(4, TtReplacement::DepthPreferred);
+        find_best_move_alphabeta(ctx, &mut tt, Duration::from_secs(10), &pst, &keys, &SearchProgress::default()).expect("A move")
+    };
+    assert_eq!(search(Vec::new()), capture);
+    // Still a queen up after any other move, so the draw is avoided
+    assert_ne!(search(vec![calculate_board_hash(&after_capture, &keys)]), capture);
+}
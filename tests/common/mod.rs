@@ -0,0 +1,107 @@
+// Harness for the integration tests: the game plugins without a window, driven
+// with `MakeMoveEvent`s, running the schedules until the next turn has started
+#![allow(dead_code)] // Every test crate uses its own part of the harness
+
+use bevy::prelude::*;
+use shakmaty::{fen::Fen, CastlingMode, Chess, Color as ChessColor};
+use drawback_chess::ai::zobrist::ZobristPlugin;
+use drawback_chess::config::{DrawbackSetting, GameConfig};
+use drawback_chess::drawbacks::registry::DrawbacksPlugin;
+use drawback_chess::drawbacks::{DrawbackId, DrawbackParams};
+use drawback_chess::game_logic::notation::parse_uci;
+use drawback_chess::game_logic::events::{GameOverEvent, GameOverReason, MakeMoveEvent, NewGameEvent};
+use drawback_chess::game_logic::plugin::GameLogicPlugin;
+use drawback_chess::game_logic::state::{ActiveBoard, GameState, TurnState};
+use drawback_chess::input::focus::TextInputFocus;
+use drawback_chess::pieces::components::PieceIdAllocator;
+
+// Frames to wait for the legal moves of a new position before giving up
+const MAX_FRAMES_PER_MOVE: usize = 1000;
+
+/// The game logic of the binary with both players on the given drawbacks
+pub fn headless_app(white: DrawbackId, black: DrawbackId) -> App {
+    let drawback = |id: DrawbackId| DrawbackSetting { name: None, index: Some(id.to_key_index()), params: DrawbackParams::default() };
+    headless_app_with(drawback(white), drawback(black))
+}
+
+/// Same as `headless_app`, with the drawbacks given as in the config file
+pub fn headless_app_with(white: DrawbackSetting, black: DrawbackSetting) -> App {
+    let mut config = GameConfig { rng_seed: Some(1), ..GameConfig::default() };
+    config.white_player.drawback = white;
+    config.black_player.drawback = black;
+
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), bevy::input::InputPlugin))
+        .insert_resource(config)
+        .init_resource::<TextInputFocus>()
+        .init_resource::<PieceIdAllocator>()
+        .add_plugins((DrawbacksPlugin, ZobristPlugin, GameLogicPlugin));
+    app.update();
+    app
+}
+
+/// Starts a new game from `fen` and waits for its first turn
+pub fn start_from(app: &mut App, fen: &str) {
+    let position: Chess = fen
+        .parse::<Fen>()
+        .expect("Valid FEN")
+        .into_position(CastlingMode::Standard)
+        .expect("Valid position");
+    app.world.send_event(NewGameEvent { start_position: Some(position) });
+    app.update();
+    settle(app);
+}
+
+/// Reads something off the state of the live game
+pub fn read_game<R>(app: &mut App, read: impl FnOnce(&GameState) -> R) -> R {
+    read(app.world.query_filtered::<&GameState, With<ActiveBoard>>().single(&app.world))
+}
+
+pub fn board(app: &mut App) -> Chess {
+    read_game(app, |game_state| game_state.board.clone())
+}
+
+pub fn side_to_move(app: &mut App) -> ChessColor {
+    read_game(app, |game_state| game_state.current_player_turn)
+}
+
+pub fn turn_state(app: &App) -> TurnState {
+    app.world.resource::<State<TurnState>>().get().clone()
+}
+
+/// Runs frames until the move being processed has handed the turn over
+/// (state changes only take effect in the frame after they were requested)
+pub fn settle(app: &mut App) {
+    for _ in 0..MAX_FRAMES_PER_MOVE {
+        let pending = app.world.resource::<NextState<TurnState>>().0.is_some();
+        if !pending && turn_state(app) != TurnState::ProcessingMove {
+            return;
+        }
+        std::thread::yield_now();
+        app.update();
+    }
+    panic!("the next turn never started");
+}
+
+/// Sends the move `uci` (from the side to move) and plays the frames that apply it.
+/// Returns whether the game accepted it.
+pub fn play(app: &mut App, uci: &str) -> bool {
+    let before = board(app);
+    let chess_move = parse_uci(&before, uci).unwrap_or_else(|e| panic!("{}", e));
+    app.world.send_event(MakeMoveEvent(chess_move));
+    app.update();
+    settle(app);
+    board(app) != before
+}
+
+pub fn play_all(app: &mut App, moves: &[&str]) {
+    for uci in moves {
+        assert!(play(app, uci), "{} was rejected", uci);
+    }
+}
+
+/// Reasons of the game over events sent in the last two frames
+pub fn game_over_reasons(app: &App) -> Vec<GameOverReason> {
+    let events = app.world.resource::<Events<GameOverEvent>>();
+    events.get_reader().read(events).map(|ev| ev.0.reason).collect()
+}
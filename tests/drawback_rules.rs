@@ -0,0 +1,340 @@
+// Drawback rules on their own: which moves they leave in a given position
+
+mod common;
+
+use bevy::prelude::*;
+use shakmaty::{fen::Fen, CastlingMode, Chess, Color as ChessColor, File, Move, Piece, Position, Rank, Role, Square};
+use drawback_chess::config::{GameConfig, Ruleset};
+use drawback_chess::drawbacks::{DrawbackId, DrawbackParams, DrawbackRegistry};
+use drawback_chess::drawbacks::definition::{DrawbackRule, TurnContext};
+use drawback_chess::game_logic::notation::{format_san, parse_san, parse_uci};
+use drawback_chess::drawbacks::block_random_file::BlockRandomFile;
+use drawback_chess::drawbacks::claustrophobia::Claustrophobia;
+use drawback_chess::drawbacks::edge_phobic::EdgePhobic;
+use drawback_chess::drawbacks::follow_the_leader::FollowTheLeader;
+use drawback_chess::drawbacks::glass_cannon::GlassCannon;
+use drawback_chess::drawbacks::mirror::{diagonal_steps, Mirror};
+use drawback_chess::drawbacks::pawn_horde::PawnHorde;
+use drawback_chess::drawbacks::recycling::Recycling;
+use drawback_chess::drawbacks::slippery_fingers::{overshoot, SlipperyFingers};
+use drawback_chess::drawbacks::vampire::Vampire;
+use drawback_chess::game_logic::drops::{captured_piece_hand, drop_moves, Reserves};
+use drawback_chess::game_logic::events::{MakeMoveEvent, UndoMoveEvent};
+use drawback_chess::i18n::localization::{Language, Localization};
+use common::*;
+
+fn position(fen: &str) -> Chess {
+    fen.parse::<Fen>()
+        .expect("Valid FEN")
+        .into_position(CastlingMode::Standard)
+        .expect("Valid position")
+}
+
+// The moves `rule` leaves out of all legal moves in `position`
+fn allowed(rule: &dyn DrawbackRule, position: &Chess) -> Vec<Move> {
+    rule.filter_pseudo_legal_moves(position, position.legal_moves().into_iter().collect(), None)
+}
+
+#[test]
+fn edge_phobic_exceptions_can_be_switched_off() {
+    let board = position("4k3/1P6/8/8/8/8/8/R3K2R w KQ - 0 1");
+    let castles = |moves: &[Move]| moves.iter().filter(|mv| matches!(mv, Move::Castle { .. })).count();
+    let promotions = |moves: &[Move]| moves.iter().filter(|mv| mv.promotion().is_some()).count();
+
+    let lenient = allowed(&EdgePhobic::default(), &board);
+    assert_eq!(castles(&lenient), 2);
+    assert_eq!(promotions(&lenient), 4);
+
+    let strict = allowed(&EdgePhobic { castling_exempt: false, promotion_exempt: false }, &board);
+    assert_eq!(castles(&strict), 0);
+    assert_eq!(promotions(&strict), 0);
+    // The pawn can't go anywhere but the last rank, so it's stuck for good
+    assert!(strict.iter().all(|mv| mv.role() != Role::Pawn));
+    // What's left stays off the rim
+    let on_rim = |square: Square| [File::A, File::H].contains(&square.file()) || [Rank::First, Rank::Eighth].contains(&square.rank());
+    assert!(strict.iter().all(|mv| !on_rim(mv.to())));
+}
+
+#[test]
+fn pawn_horde_loses_below_its_minimum() {
+    let four_pawns = position("4k3/8/8/8/8/8/PPPP4/4K3 w - - 0 1");
+    let moves: Vec<Move> = four_pawns.legal_moves().into_iter().collect();
+    assert!(!PawnHorde::default().check_loss_condition(&four_pawns, &moves, &TurnContext::default()));
+    assert!(PawnHorde { minimum: 5 }.check_loss_condition(&four_pawns, &moves, &TurnContext::default()));
+    assert!(!PawnHorde { minimum: 0 }.check_loss_condition(&position("4k3/8/8/8/8/8/8/4K3 w - - 0 1"), &moves, &TurnContext::default()));
+
+    // Only the pawns of the side to move count
+    let black_to_move = position("4k3/8/8/8/8/8/PPPP4/4K3 b - - 0 1");
+    assert!(PawnHorde::default().check_loss_condition(&black_to_move, &moves, &TurnContext::default()));
+}
+
+#[test]
+fn registry_applies_drawback_params() {
+    let registry = DrawbackRegistry::default();
+    let params = DrawbackParams::from_tag("minimum=6").expect("Valid parameters");
+    assert_eq!(params.to_string(), "minimum=6");
+
+    let horde = registry.rule(DrawbackId::PawnHorde, &params).expect("Pawn Horde is registered");
+    assert_eq!(horde.params(), params);
+    let five_pawns = position("4k3/8/8/8/8/8/PPPPP3/4K3 w - - 0 1");
+    assert!(horde.check_loss_condition(&five_pawns, &[], &TurnContext::default()));
+
+    // Out of range values fall back to the defaults
+    let fallback = registry.rule(DrawbackId::PawnHorde, &DrawbackParams::default().with("minimum", 9)).expect("Pawn Horde is registered");
+    assert_eq!(fallback.params(), DrawbackParams::default().with("minimum", 4));
+
+    // A fixed file replaces the per-turn roll
+    let blocked = registry.rule(DrawbackId::BlockRandomFile, &DrawbackParams::default().with("file", 0)).expect("Random File Blocked is registered");
+    assert!(!blocked.needs_turn_rng());
+    assert!(allowed(blocked.as_ref(), &Chess::default()).iter().all(|mv| mv.to().file() != File::A));
+}
+
+#[test]
+fn descriptions_follow_the_drawback_params() {
+    let registry = DrawbackRegistry::default();
+    assert!(PawnHorde { minimum: 6 }.description().contains("fewer than 6 pawns"));
+
+    let english = Localization::new(Language::English);
+    let german = Localization::new(Language::German);
+    let described = |localization: &Localization, id, tag: &str| {
+        localization.drawback_description(&registry, id, &DrawbackParams::from_tag(tag).expect("Valid parameters"))
+    };
+    assert!(described(&english, DrawbackId::PawnHorde, "minimum=6").contains("fewer than 6 pawns"));
+    assert!(described(&german, DrawbackId::PawnHorde, "minimum=6").contains("weniger als 6 Bauern"));
+    assert!(described(&english, DrawbackId::PacifistOpening, "first_capture_move=10").contains("before move 10"));
+    assert!(described(&english, DrawbackId::PawnHorde, "").contains("fewer than 4 pawns"));
+
+    // A fixed file isn't random any more
+    let fixed = described(&english, DrawbackId::BlockRandomFile, "file=2");
+    assert!(fixed.contains("c-file") && !fixed.contains("random"));
+    assert!(described(&german, DrawbackId::BlockRandomFile, "file=2").contains("c-Linie"));
+    // A file off the board isn't named, so the description doesn't claim one
+    let off_board = BlockRandomFile { fixed_file: Some(8) };
+    assert_eq!((off_board.description_variant(), off_board.description_args()), (None, Vec::new()));
+    assert!(described(&english, DrawbackId::EdgePhobic, "castling_exempt=0,promotion_exempt=0").contains("aren't allowed either"));
+}
+
+#[test]
+fn rules_explain_what_they_do_to_a_piece() {
+    let registry = DrawbackRegistry::default();
+    let rule = |id: DrawbackId| registry.rule(id, &DrawbackParams::default()).expect("Drawback is registered");
+    let start = Chess::default();
+
+    let pacifist = rule(DrawbackId::PacifistOpening);
+    assert_eq!(pacifist.explain_piece(&start, Square::G1, &TurnContext::default()).as_deref(), Some("Cannot capture before move 6"));
+    assert!(pacifist.explain_piece(&position("4k3/8/8/8/8/8/8/4K3 w - - 0 6"), Square::E1, &TurnContext::default()).is_none());
+
+    // Only pieces the rule cares about are explained
+    let no_castling = rule(DrawbackId::NoCastling);
+    assert!(no_castling.explain_piece(&start, Square::E1, &TurnContext::default()).is_some());
+    assert!(no_castling.explain_piece(&start, Square::G1, &TurnContext::default()).is_none());
+    let blocked = rule(DrawbackId::BlockRandomFile);
+    assert!(blocked.explain_piece(&start, Square::G1, &TurnContext { rng_outcome: Some(4), ..TurnContext::default() }).is_some_and(|text| text.contains("e-file")));
+    // Vampire counts down the moves left without a capture
+    let vampire = rule(DrawbackId::Vampire);
+    let after = |moves_since_capture: u32| TurnContext { moves_since_capture, ..TurnContext::default() };
+    assert_eq!(vampire.explain_piece(&start, Square::G1, &after(1)).as_deref(), Some("3 more move(s) without a capture allowed"));
+    assert!(vampire.explain_piece(&start, Square::G1, &after(4)).is_some_and(|text| text.starts_with("Must capture")));
+}
+
+#[test]
+fn mirror_adds_diagonal_pawn_steps_that_keep_the_king_safe() {
+    let step = |from, to, promotion| Move::Normal { role: Role::Pawn, from, capture: None, to, promotion };
+
+    // The d2 pawn is pinned: it may step along the pin to c3, but not off it to e3
+    let pinned = position("4k3/8/8/b7/8/8/3P4/4K3 w - - 0 1");
+    assert_eq!(diagonal_steps(&pinned), vec![step(Square::D2, Square::C3, None)]);
+    let moves = allowed(&Mirror, &pinned);
+    assert_eq!(moves.len(), pinned.legal_moves().len() + 1);
+    assert!(moves.contains(&step(Square::D2, Square::C3, None)));
+
+    // Onto the last rank the step promotes, to any piece; occupied squares are left alone
+    let promoting = position("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+    let promotions: Vec<Option<Role>> = diagonal_steps(&promoting).iter().map(Move::promotion).collect();
+    assert_eq!(promotions, vec![Some(Role::Knight), Some(Role::Bishop), Some(Role::Rook), Some(Role::Queen)]);
+    assert!(diagonal_steps(&promoting).iter().all(|mv| mv.to() == Square::C8));
+
+    // Written with the pawn's file but without the "x", and read back the same way
+    let queen = step(Square::B7, Square::C8, Some(Role::Queen));
+    assert_eq!(format_san(&promoting, &queen), "bc8=Q+");
+    assert_eq!(parse_san(&promoting, "bc8=Q+"), Ok(queen));
+
+    // Black steps towards rank 1
+    let black = position("4k3/4p3/8/8/8/8/8/4K3 b - - 0 1");
+    assert_eq!(diagonal_steps(&black), vec![step(Square::E7, Square::D6, None), step(Square::E7, Square::F6, None)]);
+}
+
+#[test]
+fn claustrophobia_keeps_pieces_off_the_squares_around_the_king() {
+    let board = position("r3k2r/8/8/8/8/8/3PPP2/R3K2R w KQkq - 0 1");
+    let moves = allowed(&Claustrophobia, &board);
+    let allows = |from: Square, to: Square| moves.iter().any(|mv| mv.from() == Some(from) && mv.to() == to);
+
+    // Both castles are king moves, though the rooks land next to the king on d1 and f1
+    assert_eq!(moves.iter().filter(|mv| matches!(mv, Move::Castle { .. })).count(), 2);
+    // The rooks may not step onto d1 or f1 themselves, only further away
+    assert!(!allows(Square::A1, Square::D1) && !allows(Square::H1, Square::F1));
+    assert!(allows(Square::A1, Square::C1) && allows(Square::H1, Square::G1));
+    // Pawns already next to the king may move away from it
+    assert!(allows(Square::E2, Square::E3) && allows(Square::D2, Square::D4));
+    // The king goes wherever it could
+    assert!(allows(Square::E1, Square::D1) && allows(Square::E1, Square::F1));
+    assert_eq!(moves.len(), board.legal_moves().len() - 2);
+}
+
+#[test]
+fn follow_the_leader_moves_the_kind_of_piece_moved_last() {
+    let board = position("rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2");
+    let legal: Vec<Move> = board.legal_moves().into_iter().collect();
+    let knight_out = Move::Normal { role: Role::Knight, from: Square::G8, capture: None, to: Square::F6, promotion: None };
+    let following = FollowTheLeader.filter_moves(&board, legal.clone(), &TurnContext { last_move: Some(&knight_out), ..TurnContext::default() });
+
+    // Black brought out a knight, so White moves one of its knights
+    assert_eq!(following.len(), 5);
+    assert!(following.iter().all(|mv| mv.role() == Role::Knight));
+    // Nothing to follow on the first move
+    assert_eq!(FollowTheLeader.filter_moves(&board, legal.clone(), &TurnContext::default()).len(), legal.len());
+
+    // Without a piece of that kind any move goes
+    let queenless = position("4k3/3q4/8/8/8/8/4P3/R3K3 w - - 2 2");
+    let queen_move = Move::Normal { role: Role::Queen, from: Square::D8, capture: None, to: Square::D7, promotion: None };
+    let legal: Vec<Move> = queenless.legal_moves().into_iter().collect();
+    let free = FollowTheLeader.filter_moves(&queenless, legal.clone(), &TurnContext { last_move: Some(&queen_move), ..TurnContext::default() });
+    assert_eq!(free.len(), legal.len());
+}
+
+#[test]
+fn vampire_has_to_capture_on_its_fifth_move_in_a_row() {
+    let board = position("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1");
+    let legal: Vec<Move> = board.legal_moves().into_iter().collect();
+    let turn = |moves_since_capture: u32| TurnContext { moves_since_capture, ..TurnContext::default() };
+    let counted = |count: u32| Vampire.filter_moves(&board, legal.clone(), &turn(count));
+
+    assert_eq!(counted(3).len(), legal.len());
+    assert!(!Vampire.check_loss_condition(&board, &counted(3), &turn(3)));
+    // After four quiet moves only the capture is left
+    let hungry = counted(4);
+    assert_eq!(hungry.len(), 1);
+    assert!(hungry[0].is_capture());
+    assert!(!Vampire.check_loss_condition(&board, &hungry, &turn(4)));
+
+    // Nothing to capture on the fifth move loses
+    let starving = position("4k3/8/8/8/4P3/8/8/4K3 w - - 0 1");
+    let moves: Vec<Move> = starving.legal_moves().into_iter().collect();
+    let left = Vampire.filter_moves(&starving, moves.clone(), &turn(4));
+    assert!(left.is_empty());
+    assert!(Vampire.check_loss_condition(&starving, &left, &turn(4)));
+    assert!(!Vampire.check_loss_condition(&starving, &moves, &turn(0)));
+}
+
+#[test]
+fn glass_cannon_queen_never_gains_a_defender() {
+    let board = position("4k3/8/8/8/3Q4/8/8/R3K3 w Q - 0 1");
+    let moves = allowed(&GlassCannon, &board);
+    let allows = |from: Square, to: Square| moves.iter().any(|mv| mv.from() == Some(from) && mv.to() == to);
+
+    // The rook may not line up behind the queen, not even by castling onto d1
+    assert!(!allows(Square::A1, Square::A4) && !allows(Square::A1, Square::D1));
+    assert!(!moves.iter().any(|mv| matches!(mv, Move::Castle { .. })));
+    assert!(allows(Square::A1, Square::B1) && allows(Square::A1, Square::A2));
+    // The queen may not step next to its king, but roams free elsewhere
+    assert!(!allows(Square::D4, Square::D1) && !allows(Square::D4, Square::D2));
+    assert!(allows(Square::D4, Square::H8) && allows(Square::D4, Square::D3));
+
+    // Moving the knight away would uncover the rook behind it
+    let blocked = position("4k3/8/8/8/3Q4/8/3N4/3RK3 w - - 0 1");
+    assert!(allowed(&GlassCannon, &blocked).iter().all(|mv| mv.role() != Role::Knight));
+}
+
+#[test]
+fn slippery_fingers_overshoots_long_quiet_slides() {
+    let board = position("4k3/p7/8/8/8/8/8/R3K2B w - - 0 1");
+    let slide = |uci: &str| parse_uci(&board, uci).expect("Legal move");
+    let landing = |uci: &str| overshoot(&board, &slide(uci)).map(|mv| mv.to());
+
+    assert_eq!(landing("a1a5"), Some(Square::A6));
+    assert_eq!(landing("h1d5"), Some(Square::C6));
+    // Too short, blocked by the pawn, or a capture: played as picked
+    assert_eq!(landing("a1a4"), None);
+    assert_eq!(landing("a1a6"), None);
+    assert_eq!(landing("a1a7"), None);
+    // Only on the turns the roll comes up
+    assert!(SlipperyFingers.substitute_move(&board, &slide("a1a5"), Some(0)).is_some());
+    assert!(SlipperyFingers.substitute_move(&board, &slide("a1a5"), Some(1)).is_none());
+    assert!(SlipperyFingers.substitute_move(&board, &slide("a1a5"), None).is_none());
+}
+
+#[test]
+fn recycling_keeps_the_pawns_it_captures() {
+    let board = position("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1");
+    let black = |role| Piece { color: ChessColor::Black, role };
+    // Pawns go to the capturer's hand, other pieces don't
+    assert_eq!(captured_piece_hand(black(Role::Pawn), Ruleset::Standard, None, Some(&Recycling)), Some(ChessColor::White));
+    assert_eq!(captured_piece_hand(black(Role::Knight), Ruleset::Standard, None, Some(&Recycling)), None);
+    assert_eq!(captured_piece_hand(black(Role::Pawn), Ruleset::Standard, None, None), None);
+
+    let mut reserves = Reserves::default();
+    let capture = parse_uci(&board, "e4d5").expect("Legal move");
+    reserves.record_move(&board, &capture, |captured| captured_piece_hand(captured, Ruleset::Standard, None, Some(&Recycling)));
+    assert_eq!(reserves.count(ChessColor::White, Role::Pawn), 1);
+    assert_eq!(reserves.count(ChessColor::Black, Role::Pawn), 0);
+}
+
+#[test]
+fn recycled_pawns_drop_only_into_the_own_half_without_leaving_check() {
+    let mut reserves = Reserves::default();
+    reserves.add(ChessColor::White, Role::Pawn);
+    reserves.add(ChessColor::Black, Role::Pawn);
+
+    let quiet = position("4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+    let drops = Recycling.filter_pseudo_legal_moves(&quiet, drop_moves(&quiet, &reserves), None);
+    // Ranks 2-4: the first rank is closed to pawns, ranks 5-8 are the other half
+    assert_eq!(drops.len(), 3 * 8);
+    assert!(drops.iter().all(|mv| [Rank::Second, Rank::Third, Rank::Fourth].contains(&mv.to().rank())));
+    let black = position("4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    let drops = Recycling.filter_pseudo_legal_moves(&black, drop_moves(&black, &reserves), None);
+    assert!(drops.iter().all(|mv| [Rank::Fifth, Rank::Sixth, Rank::Seventh].contains(&mv.to().rank())));
+
+    // In check from the rook only the drops that block it are left
+    let checked = position("4r1k1/8/8/8/8/8/8/4K3 w - - 0 1");
+    let drops = Recycling.filter_pseudo_legal_moves(&checked, drop_moves(&checked, &reserves), None);
+    let targets: Vec<Square> = drops.iter().map(Move::to).collect();
+    assert_eq!(targets, vec![Square::E2, Square::E3, Square::E4]);
+    // From a knight nothing blocks
+    let knight = position("6k1/8/8/8/8/3n4/8/4K3 w - - 0 1");
+    assert!(drop_moves(&knight, &reserves).is_empty());
+}
+
+#[test]
+fn taking_back_moves_restores_the_reserve() {
+    let mut app = headless_app(DrawbackId::Recycling, DrawbackId::None);
+    {
+        let mut config = app.world.resource_mut::<GameConfig>();
+        config.white_player.is_ai = false;
+        config.black_player.is_ai = false;
+    }
+    start_from(&mut app, "4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1");
+    let pawns_in_hand = |app: &mut App| read_game(app, |game_state| game_state.reserves.count(ChessColor::White, Role::Pawn));
+
+    play_all(&mut app, &["e4d5", "e8e7"]);
+    assert_eq!(pawns_in_hand(&mut app), 1);
+    let drop = Move::Put { role: Role::Pawn, to: Square::E3 };
+    app.world.send_event(MakeMoveEvent(drop));
+    app.update();
+    settle(&mut app);
+    assert_eq!(pawns_in_hand(&mut app), 0);
+    assert_eq!(board(&mut app).board().role_at(Square::E3), Some(Role::Pawn));
+
+    let take_back = |app: &mut App| {
+        app.world.send_event(UndoMoveEvent);
+        app.update();
+        settle(app);
+    };
+    take_back(&mut app);
+    assert_eq!(pawns_in_hand(&mut app), 1);
+    assert!(board(&mut app).board().piece_at(Square::E3).is_none());
+    take_back(&mut app);
+    take_back(&mut app);
+    assert_eq!(pawns_in_hand(&mut app), 0);
+    assert_eq!(board(&mut app).board().role_at(Square::D5), Some(Role::Pawn));
+}
@@ -0,0 +1,407 @@
+// Plays whole turns through the game plugins without a window
+
+mod common;
+
+use std::time::Duration;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy::ecs::system::RunSystemOnce;
+use shakmaty::{Chess, Color as ChessColor, File, Move, Position, Rank, Role, Square};
+use drawback_chess::config::{AntiStallSettings, ConfigPlugin, DrawResult, DrawbackSetting, GameConfig, SavedSettings};
+use drawback_chess::drawbacks::registry::DrawbacksPlugin;
+use drawback_chess::ai::zobrist::ZobristPlugin;
+use drawback_chess::game_logic::plugin::GameLogicPlugin;
+use drawback_chess::input::focus::TextInputFocus;
+use drawback_chess::modes::ModesPlugin;
+use drawback_chess::modes::kiosk::KioskMode;
+use drawback_chess::stats::store::PlayerStats;
+use drawback_chess::pieces::components::{CapturedPiece, Piece, PieceId, PieceIdAllocator};
+use drawback_chess::pieces::plugin::{sync_pieces_to_board, PieceEntities};
+use drawback_chess::board::components::BoardSquare;
+use drawback_chess::drawbacks::{DrawbackId, DrawbackRegistry};
+use drawback_chess::drawbacks::slippery_fingers::overshoot;
+use drawback_chess::ai::zobrist::ZobristKeys;
+use drawback_chess::game_logic::events::{GameOverReason, GameResult, LoadGameEvent, NewGameEvent, SwapSidesEvent, UndoMoveEvent, RedoMoveEvent};
+use drawback_chess::game_logic::captures::CaptureLog;
+use drawback_chess::game_logic::history::MoveHistory;
+use drawback_chess::game_logic::notation::format_uci;
+use drawback_chess::game_logic::pgn::read_pgn;
+use drawback_chess::game_logic::state::{ActiveBoard, GameState, GameStatus, TurnState};
+use drawback_chess::game_logic::systems::apply_move;
+use drawback_chess::game_logic::watchdog::TurnWatchdog;
+use drawback_chess::input::systems::{human_to_move, remind_stalled_player, MoveReminder};
+use common::*;
+
+#[test]
+fn turns_alternate_between_the_players() {
+    let mut app = headless_app(DrawbackId::None, DrawbackId::None);
+    assert_eq!(turn_state(&app), TurnState::PlayerTurn);
+
+    play_all(&mut app, &["e2e4"]);
+    assert_eq!(turn_state(&app), TurnState::AiTurn);
+    assert_eq!(side_to_move(&mut app), ChessColor::Black);
+
+    play_all(&mut app, &["e7e5"]);
+    assert_eq!(turn_state(&app), TurnState::PlayerTurn);
+    assert_eq!(side_to_move(&mut app), ChessColor::White);
+    let board = board(&mut app);
+    assert_eq!(board.board().role_at(Square::E4), Some(Role::Pawn));
+    assert_eq!(board.board().role_at(Square::E5), Some(Role::Pawn));
+}
+
+#[test]
+fn castling_moves_king_and_rook() {
+    let mut app = headless_app(DrawbackId::None, DrawbackId::None);
+    play_all(&mut app, &["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "e1g1"]);
+
+    let board = board(&mut app);
+    assert_eq!(board.board().role_at(Square::G1), Some(Role::King));
+    assert_eq!(board.board().role_at(Square::F1), Some(Role::Rook));
+    assert!(board.board().piece_at(Square::E1).is_none());
+    assert!(board.board().piece_at(Square::H1).is_none());
+}
+
+#[test]
+fn en_passant_removes_the_passed_pawn() {
+    let mut app = headless_app(DrawbackId::None, DrawbackId::None);
+    play_all(&mut app, &["e2e4", "a7a6", "e4e5", "d7d5", "e5d6"]);
+
+    let board = board(&mut app);
+    assert_eq!(board.board().role_at(Square::D6), Some(Role::Pawn));
+    assert!(board.board().piece_at(Square::D5).is_none());
+    assert!(board.board().piece_at(Square::E5).is_none());
+}
+
+#[test]
+fn promotion_puts_the_new_piece_on_the_board() {
+    let mut app = headless_app(DrawbackId::None, DrawbackId::None);
+    start_from(&mut app, "8/P6k/8/8/8/8/8/K7 w - - 0 1");
+    play_all(&mut app, &["a7a8q"]);
+
+    let board = board(&mut app);
+    assert_eq!(board.board().role_at(Square::A8), Some(Role::Queen));
+    assert!(board.board().piece_at(Square::A7).is_none());
+    assert_eq!(turn_state(&app), TurnState::AiTurn);
+}
+
+#[test]
+fn captures_are_logged_with_en_passant_and_promotions() {
+    let mut app = headless_app(DrawbackId::None, DrawbackId::None);
+    {
+        let mut config = app.world.resource_mut::<GameConfig>();
+        config.white_player.is_ai = false;
+        config.black_player.is_ai = false;
+    }
+    start_from(&mut app, "r6k/1P6/8/3pP3/8/8/8/2K5 w - d6 0 1");
+    play_all(&mut app, &["e5d6", "h8g8", "b7a8q"]);
+
+    let log = app.world.resource::<CaptureLog>();
+    assert_eq!(log.by_white, vec![Role::Pawn, Role::Rook]);
+    assert!(log.by_black.is_empty());
+    // The promoted queen counts, the pawn it came from doesn't
+    assert_eq!((log.material, log.lead(ChessColor::White), log.lead(ChessColor::Black)), (10, 10, 0));
+
+    app.world.send_event(UndoMoveEvent);
+    app.update();
+    settle(&mut app);
+    let log = app.world.resource::<CaptureLog>();
+    assert_eq!(log.by_white, vec![Role::Pawn]);
+    assert_eq!(log.lead(ChessColor::Black), 3);
+}
+
+#[test]
+fn resyncing_the_board_keeps_the_piece_ids() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Image>()
+        .init_resource::<PieceIdAllocator>();
+    let positions: Vec<Chess> = ["e2e4", "d7d5", "e4d5"].iter().fold(vec![Chess::default()], |mut positions, uci| {
+        let position = positions.last().expect("The start position").clone();
+        let chess_move = uci.parse::<shakmaty::uci::Uci>().expect("Valid UCI").to_move(&position).expect("Legal move");
+        positions.push(position.play(&chess_move).expect("Legal move"));
+        positions
+    });
+    let show = |app: &mut App, ply: usize| {
+        let board = positions[ply].board().clone();
+        app.world.run_system_once(move |mut commands: Commands, asset_server: Res<AssetServer>, mut pieces: PieceEntities, board_squares: Query<(&Transform, &BoardSquare), Without<PieceId>>, mut piece_ids: ResMut<PieceIdAllocator>| {
+            sync_pieces_to_board(&mut commands, &asset_server, &board, ply, &mut pieces, &board_squares, &mut piece_ids);
+        });
+    };
+    let id_on = |app: &mut App, square: Square| {
+        let mut pieces = app.world.query::<(&Piece, &PieceId)>();
+        pieces.iter(&app.world).find(|(piece, _)| piece.pos == square).map(|(_, id)| *id)
+    };
+
+    show(&mut app, 0);
+    let e_pawn = id_on(&mut app, Square::E2).expect("A pawn on e2");
+    let d_pawn = id_on(&mut app, Square::D7).expect("A pawn on d7");
+
+    // Jumping to the end moves the pawns and takes the black one off the board
+    show(&mut app, 3);
+    assert_eq!(id_on(&mut app, Square::D5), Some(e_pawn));
+    let mut captured = app.world.query::<(&CapturedPiece, &PieceId)>();
+    let taken: Vec<_> = captured.iter(&app.world).map(|(piece, id)| (piece.role, piece.color, piece.ply, *id)).collect();
+    assert_eq!(taken, vec![(Role::Pawn, ChessColor::Black, 3, d_pawn)]);
+
+    // Going back brings the captured pawn back with its ID
+    show(&mut app, 2);
+    assert_eq!(id_on(&mut app, Square::D5), Some(d_pawn));
+    assert_eq!(id_on(&mut app, Square::E4), Some(e_pawn));
+    assert_eq!(captured.iter(&app.world).count(), 0);
+    assert_eq!(app.world.query::<&PieceId>().iter(&app.world).count(), 32);
+}
+
+#[test]
+fn slippery_fingers_slips_in_a_seeded_game() {
+    let mut app = headless_app(DrawbackId::SlipperyFingers, DrawbackId::None);
+    start_from(&mut app, "7k/6pp/8/8/8/8/8/R6K w - - 0 1");
+
+    // White slides its rook where even a slip stays away from the black king,
+    // Black pushes its pawns so no position comes back, until the fingers have slipped
+    let replies = ["g7g6", "h7h6", "g6g5", "h6h5", "g5g4", "h5h4", "h8g8", "g8g7", "g7h7", "h7h8", "h8g8", "g8g7", "g7h7", "h7h8"];
+    let mut slipped = false;
+    for (turn, reply) in replies.into_iter().enumerate() {
+        let (position, rolled) = read_game(&mut app, |game_state| (game_state.board.clone(), game_state.current_turn_rng_outcome));
+        assert!(rolled.is_some(), "no roll on turn {}", turn);
+        let slides: Vec<(Move, Option<Move>)> = position
+            .legal_moves()
+            .into_iter()
+            .filter(|chess_move| chess_move.role() == Role::Rook && chess_move.from().is_some_and(|from| from.distance(chess_move.to()) >= 4))
+            .map(|slide| {
+                let longer = overshoot(&position, &slide);
+                (slide, longer)
+            })
+            .filter(|(slide, longer)| {
+                let landing = longer.as_ref().unwrap_or(slide).to();
+                landing.file() < File::G && landing.rank() <= Rank::Sixth
+            })
+            .collect();
+        // Slides a slip can lengthen come first, a different one each turn
+        let lengthened: Vec<_> = slides.iter().filter(|(_, longer)| longer.is_some()).collect();
+        let choices: Vec<_> = if lengthened.is_empty() { slides.iter().collect() } else { lengthened };
+        let (slide, longer) = choices[turn % choices.len()].clone();
+
+        play_all(&mut app, &[&format_uci(&slide)]);
+        let landed = app.world.resource::<MoveHistory>().moves.last().map(|record| record.chess_move.clone());
+        if rolled == Some(0) && longer.is_some() {
+            assert_eq!(landed, longer);
+            slipped = true;
+            break;
+        }
+        assert_eq!(landed, Some(slide));
+        play_all(&mut app, &[reply]);
+    }
+    assert!(slipped, "the fingers never slipped");
+}
+
+#[test]
+fn drawback_rejects_forbidden_moves() {
+    let mut app = headless_app(DrawbackId::PawnPushOneOnly, DrawbackId::NoCastling);
+
+    // White may only push pawns one square; the double push is ignored and it stays White's turn
+    assert!(!play(&mut app, "e2e4"));
+    assert_eq!(turn_state(&app), TurnState::PlayerTurn);
+    assert_eq!(side_to_move(&mut app), ChessColor::White);
+    assert!(play(&mut app, "e2e3"));
+
+    // Black can't castle, even with the way clear
+    play_all(&mut app, &["e7e5", "g1f3", "g8f6", "f1e2", "f8e7", "a2a3"]);
+    assert!(!play(&mut app, "e8g8"));
+    assert_eq!(turn_state(&app), TurnState::AiTurn);
+    assert!(play(&mut app, "e8f8"));
+}
+
+#[test]
+fn checkmate_ends_the_game() {
+    let mut app = headless_app(DrawbackId::None, DrawbackId::None);
+    play_all(&mut app, &["f2f3", "e7e5", "g2g4", "d8h4"]);
+
+    assert_eq!(turn_state(&app), TurnState::GameOver);
+    let GameStatus::Finished(result) = read_game(&mut app, |game_state| game_state.status) else {
+        panic!("the game is still going");
+    };
+    assert_eq!(result.winner, Some(ChessColor::Black));
+    assert_eq!(game_over_reasons(&app), vec![GameOverReason::Checkmate { winner: ChessColor::Black }]);
+}
+
+#[test]
+fn watchdog_recovers_a_stuck_turn() {
+    let mut app = headless_app(DrawbackId::None, DrawbackId::None);
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(500)));
+
+    // The game ended, but the turn never left processing (as if the game over got lost)
+    let mut game_state = app.world.query_filtered::<&mut GameState, With<ActiveBoard>>().single_mut(&mut app.world);
+    game_state.status = GameStatus::Finished(GameResult::new(GameOverReason::Stalemate, DrawResult::default()));
+    app.world.resource_mut::<NextState<TurnState>>().set(TurnState::ProcessingMove);
+    app.update();
+    assert_eq!(turn_state(&app), TurnState::ProcessingMove);
+
+    for _ in 0..30 {
+        app.update();
+    }
+    assert_eq!(turn_state(&app), TurnState::GameOver);
+    assert_eq!(app.world.resource::<TurnWatchdog>().recoveries(), 1);
+}
+
+#[test]
+fn swapping_sides_swaps_human_and_ai() {
+    let mut app = headless_app(DrawbackId::None, DrawbackId::None);
+    let players = |app: &App| {
+        let config = app.world.resource::<GameConfig>();
+        (config.white_player.is_ai, config.black_player.is_ai)
+    };
+    {
+        let mut config = app.world.resource_mut::<GameConfig>();
+        config.white_player.is_ai = false;
+        config.black_player.is_ai = true;
+    }
+
+    app.world.send_event(SwapSidesEvent);
+    app.update();
+    assert_eq!(players(&app), (true, false));
+    // The turn stays where it was, only who plays it changed
+    assert_eq!(turn_state(&app), TurnState::PlayerTurn);
+    assert_eq!(side_to_move(&mut app), ChessColor::White);
+}
+
+#[test]
+fn moves_can_be_taken_back_and_played_again() {
+    let mut app = headless_app(DrawbackId::None, DrawbackId::None);
+    {
+        let mut config = app.world.resource_mut::<GameConfig>();
+        config.white_player.is_ai = false;
+        config.black_player.is_ai = false;
+    }
+    play_all(&mut app, &["e2e4", "e7e5"]);
+    let after_e5 = board(&mut app);
+
+    app.world.send_event(UndoMoveEvent);
+    app.update();
+    settle(&mut app);
+    assert_eq!(side_to_move(&mut app), ChessColor::Black);
+    assert_eq!(turn_state(&app), TurnState::AiTurn);
+    assert!(board(&mut app).board().piece_at(Square::E5).is_none());
+    // The hash is the one of the position, not of the move taken back
+    let keys = app.world.resource::<ZobristKeys>().clone();
+    let (hash, key) = read_game(&mut app, |game_state| (game_state.zobrist_hash, game_state.position_key(&keys)));
+    assert_eq!(hash, key);
+
+    app.world.send_event(RedoMoveEvent);
+    app.update();
+    settle(&mut app);
+    assert_eq!(board(&mut app), after_e5);
+    assert_eq!(turn_state(&app), TurnState::PlayerTurn);
+}
+
+#[test]
+fn a_loaded_pgn_game_goes_on_from_its_last_move() {
+    let mut app = headless_app(DrawbackId::None, DrawbackId::None);
+    let imported = read_pgn("[WhiteDrawback \"None\"]\n\n1. e4 e5 2. Nf3 *\n").expect("Valid PGN");
+    let start_position = imported.history.start_position.clone();
+    let final_position = imported.history.final_position();
+    app.world.send_event(NewGameEvent { start_position: Some(start_position) });
+    app.world.send_event(LoadGameEvent(imported.history));
+    app.update();
+    settle(&mut app);
+
+    assert_eq!(board(&mut app), final_position);
+    assert_eq!(app.world.resource::<MoveHistory>().len(), 3);
+    assert_eq!(turn_state(&app), TurnState::AiTurn);
+    let keys = app.world.resource::<ZobristKeys>().clone();
+    let (hash, key) = read_game(&mut app, |game_state| (game_state.zobrist_hash, game_state.position_key(&keys)));
+    assert_eq!(hash, key);
+    assert!(play(&mut app, "b8c6"));
+}
+
+#[test]
+fn castling_written_with_zeros_is_read_from_pgn() {
+    let imported = read_pgn("1. e4 d5 2. Nf3 Qd6 3. Bc4 Bd7 4. 0-0 Nc6 5.d3 0-0-0 *\n").expect("Valid PGN");
+    let board = imported.history.final_position();
+    assert_eq!(imported.history.len(), 10);
+    assert_eq!(board.board().role_at(Square::G1), Some(Role::King));
+    assert_eq!(board.board().role_at(Square::F1), Some(Role::Rook));
+    assert_eq!(board.board().role_at(Square::C8), Some(Role::King));
+    assert_eq!(board.board().role_at(Square::D8), Some(Role::Rook));
+}
+
+#[test]
+fn a_random_move_is_played_for_a_stalling_player() {
+    let mut app = headless_app(DrawbackId::None, DrawbackId::None);
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(500)));
+    app.init_resource::<MoveReminder>()
+        .add_systems(Update, remind_stalled_player.before(apply_move).run_if(human_to_move));
+    let mut config = app.world.resource_mut::<GameConfig>();
+    config.anti_stall = AntiStallSettings { enabled: true, move_secs: 2.0, auto_move: true, grace_secs: 1.0 };
+
+    // The reminder goes up first, the move follows after the grace period
+    while !app.world.resource::<MoveReminder>().shown {
+        app.update();
+    }
+    assert_eq!(side_to_move(&mut app), ChessColor::White);
+    for _ in 0..4 {
+        app.update();
+    }
+    settle(&mut app);
+    assert_eq!(side_to_move(&mut app), ChessColor::Black);
+    assert_eq!(app.world.resource::<MoveHistory>().len(), 1);
+}
+
+#[test]
+fn random_drawbacks_are_rolled_from_the_seed() {
+    let rolled = |app: &mut App| read_game(app, |game_state| (game_state.white_drawback, game_state.black_drawback));
+    let mut app = headless_app_with(DrawbackSetting::random(), DrawbackSetting::random());
+    let (white, black) = rolled(&mut app);
+    let registry = app.world.resource::<DrawbackRegistry>();
+    assert!(registry.rules.contains_key(&white) && registry.rules.contains_key(&black));
+
+    // The same seed rolls the same drawbacks
+    let mut again = headless_app_with(DrawbackSetting::random(), DrawbackSetting::random());
+    assert_eq!(rolled(&mut again), (white, black));
+}
+
+#[test]
+fn saved_settings_give_way_to_the_kiosk_setup() {
+    let saved = SavedSettings { show_ai_thinking: Some(true), show_rule_explanations: Some(false), ..SavedSettings::default() };
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), bevy::input::InputPlugin))
+        .insert_resource(saved)
+        .insert_resource(KioskMode)
+        .init_resource::<PlayerStats>()
+        .init_resource::<TextInputFocus>()
+        .init_resource::<PieceIdAllocator>()
+        .add_plugins((ConfigPlugin, DrawbacksPlugin, ZobristPlugin, GameLogicPlugin, ModesPlugin));
+    app.update();
+
+    // The saved setting the kiosk doesn't touch is back, the one it sets is the kiosk's
+    let config = app.world.resource::<GameConfig>();
+    assert!(!config.display.show_rule_explanations);
+    assert!(!config.display.show_ai_thinking);
+    assert!(config.white_player.is_ai && config.black_player.is_ai);
+}
+
+#[test]
+fn only_the_settings_changed_in_a_menu_are_saved() {
+    // A session's setup (here the tutorial's human White) is no preference of the player's
+    let mut session = GameConfig::default();
+    session.white_player.is_ai = false;
+    session.display.show_ai_thinking = false;
+    let mut changed = session.clone();
+    changed.display.show_rule_explanations = !session.display.show_rule_explanations;
+
+    let mut saved = SavedSettings::default();
+    saved.record(&session, &changed);
+    assert_eq!(saved, SavedSettings { show_rule_explanations: Some(changed.display.show_rule_explanations), ..SavedSettings::default() });
+
+    // Through the file into the next start's configuration
+    let path = std::env::temp_dir().join(format!("drawback_chess_config_{}.json", std::process::id()));
+    let path = path.to_str().expect("UTF-8 temp path");
+    assert_eq!(SavedSettings::load(path).expect("No file is no error"), SavedSettings::default());
+    saved.save(path).expect("Settings saved");
+    let loaded = SavedSettings::load(path).expect("Settings read");
+    std::fs::remove_file(path).ok();
+    let mut next_start = GameConfig::default();
+    loaded.apply(&mut next_start);
+    assert_eq!(next_start.display.show_rule_explanations, changed.display.show_rule_explanations);
+    assert_eq!(next_start.display.show_ai_thinking, GameConfig::default().display.show_ai_thinking);
+    assert_eq!(next_start.white_player.is_ai, GameConfig::default().white_player.is_ai);
+}
@@ -0,0 +1,217 @@
+// Two headless games playing each other over an in-memory link, or over TCP
+// on this machine where the connection has to drop
+
+mod common;
+
+use std::time::Duration;
+use bevy::prelude::*;
+use shakmaty::Color as ChessColor;
+use drawback_chess::ai::zobrist::ZobristKeys;
+use drawback_chess::config::{DrawbackSetting, GameConfig};
+use drawback_chess::drawbacks::{DrawbackId, DrawbackParams};
+use drawback_chess::game_logic::events::{GameOverEvent, GameOverReason, GameResult};
+use drawback_chess::config::DrawResult;
+use drawback_chess::game_logic::history::MoveHistory;
+use drawback_chess::game_logic::state::{ActiveBoard, GameState, GameStatus};
+use drawback_chess::net::chat::{ChatLine, ChatLog, SendChatEvent};
+use drawback_chess::net::link::{Link, NetRole};
+use drawback_chess::net::protocol::commit_drawback;
+use drawback_chess::net::lockstep::{NetSession, NetStatus};
+use drawback_chess::net::NetPlugin;
+use common::*;
+
+// Frames to wait for the other side before giving up
+const MAX_FRAMES: usize = 2000;
+
+fn networked_pair() -> (App, App) {
+    networked_pair_with(|_| {})
+}
+
+fn networked_pair_with(configure_host: impl FnOnce(&mut GameConfig)) -> (App, App) {
+    let (host_link, client_link) = Link::pair();
+    let mut host = headless_app(DrawbackId::PawnPushOneOnly, DrawbackId::None);
+    configure_host(&mut host.world.resource_mut::<GameConfig>());
+    host.insert_resource(NetSession::new(NetRole::Host { port: 0 }, host_link)).add_plugins(NetPlugin);
+    let mut client = headless_app(DrawbackId::None, DrawbackId::None);
+    client.insert_resource(NetSession::new(NetRole::Client { address: String::new() }, client_link)).add_plugins(NetPlugin);
+    pump_until(&mut host, &mut client, |host, client| status(host) == NetStatus::Playing && status(client) == NetStatus::Playing);
+    (host, client)
+}
+
+fn tcp_host(port: u16) -> App {
+    let mut host = headless_app(DrawbackId::None, DrawbackId::None);
+    let role = NetRole::Host { port };
+    host.insert_resource(NetSession::new(role.clone(), Link::open(role, Duration::from_millis(20)))).add_plugins(NetPlugin);
+    host
+}
+
+fn tcp_client(port: u16) -> App {
+    let mut client = headless_app(DrawbackId::None, DrawbackId::None);
+    let role = NetRole::Client { address: format!("127.0.0.1:{}", port) };
+    client.insert_resource(NetSession::new(role.clone(), Link::open(role, Duration::from_millis(20)))).add_plugins(NetPlugin);
+    client
+}
+
+fn pump_until(host: &mut App, client: &mut App, done: impl Fn(&mut App, &mut App) -> bool) {
+    for _ in 0..MAX_FRAMES {
+        host.update();
+        client.update();
+        if done(host, client) {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    panic!("the network game never got there");
+}
+
+fn pump_host_until(host: &mut App, done: impl Fn(&App) -> bool) {
+    for _ in 0..MAX_FRAMES {
+        host.update();
+        if done(host) {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    panic!("the host never got there");
+}
+
+fn status(app: &App) -> NetStatus {
+    app.world.resource::<NetSession>().status.clone()
+}
+
+fn plies(app: &App) -> usize {
+    app.world.resource::<MoveHistory>().moves.len()
+}
+
+#[test]
+fn both_sides_play_the_same_game() {
+    let (mut host, mut client) = networked_pair();
+    assert_eq!(client.world.resource::<NetSession>().local_color, ChessColor::Black);
+    // The client took over the host's drawbacks
+    assert_eq!(read_game(&mut client, |game_state| game_state.white_drawback), DrawbackId::PawnPushOneOnly);
+
+    assert!(play(&mut host, "e2e3"));
+    pump_until(&mut host, &mut client, |_, client| plies(client) == 1);
+    settle(&mut client);
+    assert!(play(&mut client, "e7e5"));
+    pump_until(&mut host, &mut client, |host, _| plies(host) == 2);
+    settle(&mut host);
+
+    assert_eq!(board(&mut host), board(&mut client));
+    assert_eq!(status(&host), NetStatus::Playing);
+    assert_eq!(status(&client), NetStatus::Playing);
+}
+
+#[test]
+fn a_desync_is_repaired_from_the_host() {
+    let (mut host, mut client) = networked_pair();
+    assert!(play(&mut host, "e2e3"));
+    pump_until(&mut host, &mut client, |_, client| plies(client) == 1);
+    settle(&mut client);
+
+    // Something went wrong on the client: it thinks Black has another drawback
+    let keys = client.world.resource::<ZobristKeys>().clone();
+    let mut game_state = client.world.query_filtered::<&mut GameState, With<ActiveBoard>>().single_mut(&mut client.world);
+    game_state.black_drawback = DrawbackId::NoCastling;
+    game_state.zobrist_hash = game_state.position_key(&keys);
+    assert!(play(&mut client, "e7e5"));
+
+    // The host notices on the client's move and sends its game, which the client replays
+    pump_until(&mut host, &mut client, |_, client| {
+        let resynced = read_game(client, |game_state| game_state.black_drawback) == DrawbackId::None;
+        resynced && status(client) == NetStatus::Playing && plies(client) == 2
+    });
+    settle(&mut client);
+    assert_eq!(board(&mut host), board(&mut client));
+}
+
+#[test]
+fn a_player_who_comes_back_gets_the_game_so_far() {
+    const PORT: u16 = 47_871;
+    let mut host = tcp_host(PORT);
+    let mut client = tcp_client(PORT);
+    pump_until(&mut host, &mut client, |host, client| status(host) == NetStatus::Playing && status(client) == NetStatus::Playing);
+    assert!(play(&mut host, "e2e3"));
+    pump_until(&mut host, &mut client, |_, client| plies(client) == 1);
+    settle(&mut client);
+    assert!(play(&mut client, "e7e5"));
+    pump_until(&mut host, &mut client, |host, _| plies(host) == 2);
+    settle(&mut host);
+
+    // The client goes away; the host waits for it and keeps playing
+    drop(client);
+    pump_host_until(&mut host, |host| status(host) == NetStatus::Reconnecting);
+    assert!(play(&mut host, "d2d4"));
+    let mut client = tcp_client(PORT);
+
+    pump_until(&mut host, &mut client, |host, client| {
+        status(host) == NetStatus::Playing && status(client) == NetStatus::Playing && plies(client) == 3
+    });
+    settle(&mut client);
+    assert_eq!(board(&mut host), board(&mut client));
+    assert_eq!(read_game(&mut host, |game_state| game_state.status), GameStatus::Ongoing);
+}
+
+#[test]
+fn the_game_is_forfeited_when_the_other_player_stays_away() {
+    const PORT: u16 = 47_872;
+    let mut host = tcp_host(PORT);
+    host.world.resource_mut::<GameConfig>().network.abandon_timeout_secs = 0.0;
+    let mut client = tcp_client(PORT);
+    pump_until(&mut host, &mut client, |host, client| status(host) == NetStatus::Playing && status(client) == NetStatus::Playing);
+
+    drop(client);
+    pump_host_until(&mut host, |host| matches!(status(host), NetStatus::Disconnected(_)));
+    let ending = read_game(&mut host, |game_state| game_state.status);
+    assert!(matches!(ending, GameStatus::Finished(result) if result.reason == GameOverReason::Abandoned { loser: ChessColor::Black }));
+}
+
+#[test]
+fn chat_reaches_the_other_player_and_can_go_into_the_pgn() {
+    let (mut host, mut client) = networked_pair();
+    client.world.resource_mut::<GameConfig>().network.chat_in_pgn = true;
+    assert!(play(&mut host, "e2e3"));
+    pump_until(&mut host, &mut client, |_, client| plies(client) == 1);
+
+    host.world.send_event(SendChatEvent("Good luck".to_string()));
+    pump_until(&mut host, &mut client, |_, client| client.world.resource::<ChatLog>().lines().count() == 1);
+
+    let line = client.world.resource::<ChatLog>().lines().next().cloned();
+    assert_eq!(line, Some(ChatLine { from: ChessColor::White, text: "Good luck".to_string() }));
+    assert_eq!(client.world.resource::<MoveHistory>().moves[0].comment.as_deref(), Some("White: Good luck"));
+    // The host didn't opt in
+    assert_eq!(host.world.resource::<MoveHistory>().moves[0].comment, None);
+}
+
+#[test]
+fn a_hidden_drawback_stays_on_the_host_until_the_game_is_over() {
+    let (mut host, mut client) = networked_pair_with(|config| config.network.hidden_drawbacks = true);
+    // The client never heard of White's drawback, but the game goes on in sync
+    assert_eq!(read_game(&mut client, |game_state| game_state.white_drawback), DrawbackId::None);
+    assert!(play(&mut host, "e2e3"));
+    pump_until(&mut host, &mut client, |_, client| plies(client) == 1);
+    settle(&mut client);
+    assert!(play(&mut client, "e7e5"));
+    pump_until(&mut host, &mut client, |host, _| plies(host) == 2);
+    settle(&mut host);
+    assert_eq!(status(&host), NetStatus::Playing);
+
+    // White resigns on the host; the ending and the drawback go out together
+    let result = GameResult::new(GameOverReason::Resignation { loser: ChessColor::White }, DrawResult::Draw);
+    host.world.query_filtered::<&mut GameState, With<ActiveBoard>>().single_mut(&mut host.world).status = GameStatus::Finished(result);
+    host.world.send_event(GameOverEvent(result));
+    pump_until(&mut host, &mut client, |_, client| read_game(client, |game_state| game_state.status) == GameStatus::Finished(result));
+    assert_eq!(read_game(&mut client, |game_state| game_state.white_drawback), DrawbackId::PawnPushOneOnly);
+    // The drawback revealed is the one the host committed to at the start
+    assert_eq!(status(&client), NetStatus::Playing);
+}
+
+#[test]
+fn a_drawback_commitment_only_opens_to_its_drawback() {
+    let setting = |id: DrawbackId, params: DrawbackParams| DrawbackSetting { name: None, index: Some(id.to_key_index()), params };
+    let committed = commit_drawback(&setting(DrawbackId::PawnHorde, DrawbackParams::default()), 7);
+    assert_eq!(commit_drawback(&setting(DrawbackId::PawnHorde, DrawbackParams::default()), 7), committed);
+    assert_ne!(commit_drawback(&setting(DrawbackId::PawnHorde, DrawbackParams::default()), 8), committed);
+    assert_ne!(commit_drawback(&setting(DrawbackId::NoCastling, DrawbackParams::default()), 7), committed);
+    assert_ne!(commit_drawback(&setting(DrawbackId::PawnHorde, DrawbackParams::default().with("minimum", 5)), 7), committed);
+}
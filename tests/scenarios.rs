@@ -0,0 +1,163 @@
+// Runs every scenario in `tests/scenarios/`: a start position, the drawbacks,
+// moves both sides are made to play and the outcome the game has to reach.
+//
+// {
+//   "description": "What the scenario checks",
+//   "fen": "k7/8/8/2Q5/8/8/8/7K w - - 0 1",     (optional, standard start position)
+//   "white_drawback": { "name": "No Castling" },  (optional, as in the config file)
+//   "black_drawback": { "index": 8, "params": { "minimum": 5 } },  (optional, parameters too)
+//   "moves": ["c5b6"],                             (UCI, all of them have to be accepted)
+//   "rejected": ["e1g1"],                          (optional, moves the final position refuses)
+//   "expect": { "outcome": "stalemate" }
+// }
+//
+// Outcomes: "ongoing" (optionally with "to_move"), "checkmate" and "king_captured"
+// (with "winner"), "drawback_loss" (with "loser"), "stalemate" and "repetition".
+
+mod common;
+
+use std::panic::{self, AssertUnwindSafe};
+use serde::Deserialize;
+use shakmaty::Color as ChessColor;
+use drawback_chess::config::DrawbackSetting;
+use drawback_chess::drawbacks::DrawbackParams;
+use drawback_chess::game_logic::events::GameOverReason;
+use drawback_chess::game_logic::state::{GameStatus, TurnState};
+use common::*;
+
+const SCENARIO_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/scenarios");
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Scenario {
+    description: String,
+    #[serde(default)]
+    fen: Option<String>,
+    #[serde(default = "no_drawback")]
+    white_drawback: DrawbackSetting,
+    #[serde(default = "no_drawback")]
+    black_drawback: DrawbackSetting,
+    moves: Vec<String>,
+    #[serde(default)]
+    rejected: Vec<String>,
+    expect: Outcome,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case", deny_unknown_fields)]
+enum Outcome {
+    Ongoing {
+        #[serde(default)]
+        to_move: Option<Side>,
+    },
+    Checkmate { winner: Side },
+    KingCaptured { winner: Side },
+    DrawbackLoss { loser: Side },
+    Stalemate,
+    Repetition,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Side {
+    White,
+    Black,
+}
+
+impl From<Side> for ChessColor {
+    fn from(side: Side) -> Self {
+        match side {
+            Side::White => ChessColor::White,
+            Side::Black => ChessColor::Black,
+        }
+    }
+}
+
+fn no_drawback() -> DrawbackSetting {
+    DrawbackSetting { name: None, index: None, params: DrawbackParams::default() }
+}
+
+impl Outcome {
+    // Whether the game ended the way this outcome says
+    fn matches(&self, reason: GameOverReason) -> bool {
+        match (self, reason) {
+            (Self::Checkmate { winner }, GameOverReason::Checkmate { winner: actual }) => ChessColor::from(*winner) == actual,
+            (Self::KingCaptured { winner }, GameOverReason::KingCaptured { winner: actual, .. }) => ChessColor::from(*winner) == actual,
+            (Self::DrawbackLoss { loser }, GameOverReason::DrawbackLoss { loser: actual, .. }) => ChessColor::from(*loser) == actual,
+            (Self::Stalemate, GameOverReason::Stalemate) => true,
+            (Self::Repetition, GameOverReason::DrawByRepetition) => true,
+            _ => false,
+        }
+    }
+}
+
+fn run(scenario: &Scenario) -> Result<(), String> {
+    let mut app = headless_app_with(scenario.white_drawback.clone(), scenario.black_drawback.clone());
+    if let Some(fen) = &scenario.fen {
+        start_from(&mut app, fen);
+    }
+    for uci in &scenario.moves {
+        if !play(&mut app, uci) {
+            return Err(format!("{} was rejected", uci));
+        }
+    }
+
+    // The game over event only stays readable for two frames, so the outcome comes first
+    let status = read_game(&mut app, |game_state| game_state.status);
+    match (&scenario.expect, status) {
+        (Outcome::Ongoing { to_move }, GameStatus::Ongoing) => {
+            let actual = side_to_move(&mut app);
+            if to_move.is_some_and(|side| ChessColor::from(side) != actual) {
+                return Err(format!("{:?} is to move, expected {:?}", actual, to_move));
+            }
+        }
+        (expected, GameStatus::Finished(result)) if expected.matches(result.reason) => {
+            if turn_state(&app) != TurnState::GameOver {
+                return Err(format!("the game ended ({}) but the turn state is {:?}", result.reason, turn_state(&app)));
+            }
+            if game_over_reasons(&app) != vec![result.reason] {
+                return Err(format!("expected one game over event for {}, got {:?}", result.reason, game_over_reasons(&app)));
+            }
+        }
+        (expected, GameStatus::Finished(result)) => return Err(format!("expected {:?}, the game ended with {}", expected, result.reason)),
+        (expected, GameStatus::Ongoing) => return Err(format!("expected {:?}, the game is still going", expected)),
+    }
+
+    for uci in &scenario.rejected {
+        let turn = turn_state(&app);
+        if play(&mut app, uci) {
+            return Err(format!("{} was accepted", uci));
+        }
+        if turn_state(&app) != turn {
+            return Err(format!("refusing {} changed the turn state to {:?}", uci, turn_state(&app)));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn scenarios() {
+    let mut paths: Vec<_> = std::fs::read_dir(SCENARIO_DIR)
+        .expect("tests/scenarios exists")
+        .map(|entry| entry.expect("Readable directory entry").path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no scenarios in {}", SCENARIO_DIR);
+
+    let mut failures = Vec::new();
+    for path in &paths {
+        let name = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        let text = std::fs::read_to_string(path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e));
+        let scenario: Scenario = serde_json::from_str(&text).unwrap_or_else(|e| panic!("Invalid scenario {}: {}", name, e));
+
+        // A harness panic (e.g. a move that isn't legal chess) fails only its own scenario
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| run(&scenario)))
+            .unwrap_or_else(|_| Err("panicked".to_string()));
+        match outcome {
+            Ok(()) => println!("scenario {} ... ok", name),
+            Err(e) => failures.push(format!("{} ({}): {}", name, scenario.description, e)),
+        }
+    }
+    assert!(failures.is_empty(), "{} of {} scenarios failed:\n{}", failures.len(), paths.len(), failures.join("\n"));
+}